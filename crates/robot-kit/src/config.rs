@@ -20,11 +20,15 @@ pub struct RobotConfig {
 
     /// Safety limits
     pub safety: SafetyConfig,
+
+    /// Simulator settings (drive backend / LIDAR type "sim")
+    #[serde(default)]
+    pub sim: SimConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DriveConfig {
-    /// "ros2", "gpio", "serial", or "mock"
+    /// "ros2", "gpio", "serial", "sim", or "mock"
     pub backend: String,
 
     /// ROS2 topic for cmd_vel (if using ROS2)
@@ -40,6 +44,12 @@ pub struct DriveConfig {
     pub max_rotation: f64,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimConfig {
+    /// Map TOML (polygons and/or occupancy grid); open space if unset
+    pub map: Option<PathBuf>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CameraConfig {
     /// Camera device (e.g., "/dev/video0" or "picam")
@@ -82,7 +92,7 @@ pub struct SensorConfig {
     /// LIDAR device (e.g., "/dev/ttyUSB0")
     pub lidar_port: String,
 
-    /// LIDAR type ("rplidar", "ydlidar", "mock")
+    /// LIDAR type ("rplidar", "ydlidar", "sim", "mock")
     pub lidar_type: String,
 
     /// GPIO pins for motion sensors (BCM numbering)
@@ -197,6 +207,7 @@ impl Default for RobotConfig {
                 sensor_timeout_secs: 5,       // Block if sensors stale 5s
                 blind_mode_speed_limit: 0.2,  // 20% speed without sensors
             },
+            sim: SimConfig::default(),
         }
     }
 }
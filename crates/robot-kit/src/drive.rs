@@ -4,9 +4,11 @@
 //! - ROS2: Publishes geometry_msgs/Twist to cmd_vel topic
 //! - GPIO: Direct PWM control via rppal
 //! - Serial: Arduino/motor controller via serial commands
+//! - Sim: 2D kinematic simulator (see [`crate::sim`])
 //! - Mock: Logs commands for testing
 
 use crate::config::RobotConfig;
+use crate::sim::SimWorld;
 use crate::traits::{Tool, ToolResult};
 use anyhow::Result;
 use async_trait::async_trait;
@@ -63,6 +65,36 @@ impl DriveBackend for MockDrive {
     }
 }
 
+/// Simulator backend - moves the robot in a [`SimWorld`]
+struct SimDrive {
+    world: Arc<SimWorld>,
+}
+
+#[async_trait]
+impl DriveBackend for SimDrive {
+    async fn move_robot(
+        &self,
+        linear_x: f64,
+        linear_y: f64,
+        angular_z: f64,
+        duration_ms: u64,
+    ) -> Result<()> {
+        if let Some(sensor) = self.world.drive(linear_x, linear_y, angular_z, duration_ms) {
+            tracing::warn!("SIM DRIVE: collision, bump sensor {}", sensor);
+        }
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_odometry(&self) -> Result<(f64, f64, f64)> {
+        let pose = self.world.pose();
+        Ok((pose.x, pose.y, pose.theta))
+    }
+}
+
 /// ROS2 backend - shells out to ros2 topic pub
 struct Ros2Drive {
     topic: String,
@@ -193,6 +225,13 @@ impl DriveTool {
             "serial" => Arc::new(SerialDrive {
                 port: config.drive.serial_port.clone(),
             }),
+            "sim" => match SimWorld::shared(&config.sim) {
+                Ok(world) => Arc::new(SimDrive { world }),
+                Err(e) => {
+                    tracing::warn!("Simulator unavailable, using mock drive: {e:#}");
+                    Arc::new(MockDrive)
+                }
+            },
             // "gpio" => Arc::new(GpioDrive::new(&config)), // Would use rppal
             _ => Arc::new(MockDrive),
         };
@@ -203,6 +242,15 @@ impl DriveTool {
             last_command: Arc::new(Mutex::new(None)),
         }
     }
+
+    /// Drive the robot in a specific simulated world
+    pub fn with_sim(config: RobotConfig, world: Arc<SimWorld>) -> Self {
+        Self {
+            config,
+            backend: Arc::new(SimDrive { world }),
+            last_command: Arc::new(Mutex::new(None)),
+        }
+    }
 }
 
 #[async_trait]
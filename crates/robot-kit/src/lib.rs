@@ -4,13 +4,14 @@
 //!
 //! ## Features
 //!
-//! - **Drive**: Omni-directional motor control (ROS2, serial, GPIO, mock)
+//! - **Drive**: Omni-directional motor control (ROS2, serial, GPIO, sim, mock)
 //! - **Look**: Camera capture + vision model description (Ollama)
 //! - **Listen**: Speech-to-text via Whisper.cpp
 //! - **Speak**: Text-to-speech via Piper TTS
 //! - **Sense**: LIDAR, motion sensors, ultrasonic distance
 //! - **Emote**: LED matrix expressions and sound effects
 //! - **Safety**: Independent safety monitor (collision avoidance, E-stop, watchdog)
+//! - **Sim**: 2D kinematic simulator for drive and sense (no hardware needed)
 //!
 //! ## Architecture
 //!
@@ -95,6 +96,7 @@ pub mod emote;
 pub mod listen;
 pub mod look;
 pub mod sense;
+pub mod sim;
 pub mod speak;
 
 #[cfg(feature = "safety")]
@@ -112,6 +114,7 @@ pub use emote::EmoteTool;
 pub use listen::ListenTool;
 pub use look::LookTool;
 pub use sense::SenseTool;
+pub use sim::{Pose, SimMap, SimWorld};
 pub use speak::SpeakTool;
 
 #[cfg(feature = "safety")]
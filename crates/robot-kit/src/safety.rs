@@ -276,7 +276,10 @@ impl SafetyMonitor {
     /// Run the safety monitor loop (call in background task)
     pub async fn run(&self, mut sensor_rx: tokio::sync::mpsc::Receiver<SensorReading>) {
         let watchdog_timeout = Duration::from_secs(self.config.max_drive_duration);
+        let sensor_timeout = Duration::from_secs(self.config.sensor_timeout_secs);
         let mut last_sensor_update = Instant::now();
+        // An interval keeps ticking while sensor readings stream in
+        let mut watchdog = tokio::time::interval(Duration::from_secs(1));
 
         while !self.shutdown.load(Ordering::SeqCst) {
             tokio::select! {
@@ -299,9 +302,9 @@ impl SafetyMonitor {
                 }
 
                 // Watchdog check every second
-                _ = tokio::time::sleep(Duration::from_secs(1)) => {
+                _ = watchdog.tick() => {
                     // Check for sensor timeout
-                    if last_sensor_update.elapsed() > Duration::from_secs(5) {
+                    if last_sensor_update.elapsed() > sensor_timeout {
                         tracing::warn!("Sensor data stale - blocking movement");
                         self.state.can_move.store(false, Ordering::SeqCst);
                         *self.state.block_reason.write().await =
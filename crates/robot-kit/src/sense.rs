@@ -1,9 +1,10 @@
 //! Sense Tool - LIDAR, motion sensors, ultrasonic distance
 //!
 //! Provides environmental awareness through various sensors.
-//! Supports multiple backends: direct GPIO, ROS2 topics, or mock.
+//! Supports multiple backends: direct GPIO, ROS2 topics, simulator, or mock.
 
 use crate::config::RobotConfig;
+use crate::sim::SimWorld;
 use crate::traits::{Tool, ToolResult};
 use anyhow::Result;
 use async_trait::async_trait;
@@ -32,18 +33,39 @@ pub struct MotionResult {
 pub struct SenseTool {
     config: RobotConfig,
     last_scan: Arc<Mutex<Option<LidarScan>>>,
+    world: Option<Arc<SimWorld>>,
 }
 
 impl SenseTool {
     pub fn new(config: RobotConfig) -> Self {
+        let world = if config.sensors.lidar_type == "sim" {
+            SimWorld::shared(&config.sim)
+                .map_err(|e| tracing::warn!("Simulator unavailable, using mock sensors: {e:#}"))
+                .ok()
+        } else {
+            None
+        };
+        Self {
+            config,
+            last_scan: Arc::new(Mutex::new(None)),
+            world,
+        }
+    }
+
+    /// Read sensors from a specific simulated world
+    pub fn with_sim(config: RobotConfig, world: Arc<SimWorld>) -> Self {
         Self {
             config,
             last_scan: Arc::new(Mutex::new(None)),
+            world: Some(world),
         }
     }
 
     /// Read LIDAR scan
     async fn scan_lidar(&self) -> Result<LidarScan> {
+        if let Some(world) = &self.world {
+            return Ok(self.scan_sim(world));
+        }
         match self.config.sensors.lidar_type.as_str() {
             "rplidar" => self.scan_rplidar().await,
             "ros2" => self.scan_ros2().await,
@@ -88,6 +110,29 @@ impl SenseTool {
         })
     }
 
+    /// Ray-cast LIDAR in the simulated world
+    fn scan_sim(&self, world: &SimWorld) -> LidarScan {
+        let ranges = world.lidar();
+
+        let nearest = ranges
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, &d)| (d, i as u16))
+            .unwrap_or((999.0, 0));
+
+        let forward_clear = ranges[0..30]
+            .iter()
+            .chain(ranges[330..360].iter())
+            .all(|&d| d > self.config.safety.min_obstacle_distance);
+
+        LidarScan {
+            ranges,
+            nearest,
+            forward_clear,
+        }
+    }
+
     /// Read from RPLidar via serial
     async fn scan_rplidar(&self) -> Result<LidarScan> {
         // In production, use rplidar_drv crate
@@ -181,6 +226,13 @@ impl SenseTool {
 
     /// Check PIR motion sensors
     async fn check_motion(&self) -> Result<MotionResult> {
+        if self.world.is_some() {
+            // Nothing moves in the simulated world but the robot
+            return Ok(MotionResult {
+                detected: false,
+                sensors_triggered: Vec::new(),
+            });
+        }
         let pins = &self.config.sensors.motion_pins;
 
         // In production, use rppal GPIO
@@ -205,6 +257,9 @@ impl SenseTool {
 
     /// Read ultrasonic distance sensor
     async fn check_distance(&self) -> Result<f64> {
+        if let Some(world) = &self.world {
+            return Ok(world.ultrasonic());
+        }
         let Some((trigger, echo)) = self.config.sensors.ultrasonic_pins else {
             return Ok(999.0); // No sensor configured
         };
@@ -0,0 +1,499 @@
+//! Simulator - 2D kinematic world for hardware-free testing
+//!
+//! Backs `drive` (`backend = "sim"`) and `sense` (`lidar_type = "sim"`)
+//! with a shared simulated world:
+//! - Map: polygon obstacles and/or an occupancy grid loaded from TOML
+//! - Drive: integrates the robot pose from velocity commands
+//! - Sense: ray-cast LIDAR (360 × 1°) and forward ultrasonic readings
+//! - Collisions: the robot stops at the wall and a bump is reported
+//!
+//! Simulated time advances instantly; a 2 s drive command returns at once.
+//! With the `safety` feature, readings can be fed to a `SafetyMonitor`.
+//!
+//! ## Map format
+//!
+//! ```toml
+//! robot_radius = 0.15
+//! start = { x = 0.0, y = 0.0, theta = 0.0 }
+//!
+//! # Polygon edges are walls (closed when 3+ points)
+//! [[obstacles]]
+//! points = [[-2.0, -2.0], [2.0, -2.0], [2.0, 2.0], [-2.0, 2.0]]
+//!
+//! # '#' cells are occupied; row 0 is the top (highest y)
+//! [grid]
+//! resolution = 0.5
+//! origin = [-2.0, -2.0]
+//! rows = ["########", "#......#", "#..##..#", "#......#", "########"]
+//! ```
+
+use crate::config::SimConfig;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+
+/// Integration step (seconds)
+const STEP_SECS: f64 = 0.01;
+
+/// Worlds shared by drive and sense tools, keyed by map path
+static WORLDS: LazyLock<Mutex<HashMap<Option<PathBuf>, Weak<SimWorld>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Robot pose in world coordinates (meters, radians)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pose {
+    pub x: f64,
+    pub y: f64,
+    /// Heading, counter-clockwise from +x
+    pub theta: f64,
+}
+
+/// Polygon obstacle (vertices in meters)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Polygon {
+    pub points: Vec<[f64; 2]>,
+}
+
+/// Occupancy grid
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridMap {
+    /// Cell size in meters
+    pub resolution: f64,
+    /// World position of the bottom-left corner
+    #[serde(default)]
+    pub origin: [f64; 2],
+    /// One string per row, '#' = occupied
+    pub rows: Vec<String>,
+}
+
+/// Simulated world description
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimMap {
+    /// Robot footprint radius (meters)
+    pub robot_radius: f64,
+    /// Initial pose
+    pub start: Pose,
+    /// Maximum LIDAR range (meters)
+    pub lidar_range: f64,
+    /// Maximum ultrasonic range (meters)
+    pub ultrasonic_range: f64,
+    /// Polygon obstacles
+    pub obstacles: Vec<Polygon>,
+    /// Optional occupancy grid
+    pub grid: Option<GridMap>,
+}
+
+impl Default for SimMap {
+    fn default() -> Self {
+        Self {
+            robot_radius: 0.15,
+            start: Pose::default(),
+            lidar_range: 12.0,
+            ultrasonic_range: 4.0,
+            obstacles: Vec::new(),
+            grid: None,
+        }
+    }
+}
+
+/// Wall segment
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    a: (f64, f64),
+    b: (f64, f64),
+}
+
+impl Segment {
+    /// Closest point on the segment to `p`
+    fn closest_point(&self, p: (f64, f64)) -> (f64, f64) {
+        let (dx, dy) = (self.b.0 - self.a.0, self.b.1 - self.a.1);
+        let len_sq = dx * dx + dy * dy;
+        if len_sq == 0.0 {
+            return self.a;
+        }
+        let t = (((p.0 - self.a.0) * dx + (p.1 - self.a.1) * dy) / len_sq).clamp(0.0, 1.0);
+        (self.a.0 + t * dx, self.a.1 + t * dy)
+    }
+
+    /// Distance along the ray from `origin` in direction `dir` to this segment
+    fn ray_hit(&self, origin: (f64, f64), dir: (f64, f64)) -> Option<f64> {
+        let (ex, ey) = (self.b.0 - self.a.0, self.b.1 - self.a.1);
+        let denom = dir.0 * ey - dir.1 * ex;
+        if denom.abs() < 1e-12 {
+            return None;
+        }
+        let (wx, wy) = (self.a.0 - origin.0, self.a.1 - origin.1);
+        let t = (wx * ey - wy * ex) / denom;
+        let u = (wx * dir.1 - wy * dir.0) / denom;
+        (t >= 0.0 && (0.0..=1.0).contains(&u)).then_some(t)
+    }
+}
+
+fn polygon_walls(polygon: &Polygon) -> Vec<Segment> {
+    let points = &polygon.points;
+    let mut walls: Vec<Segment> = points
+        .windows(2)
+        .map(|w| Segment {
+            a: (w[0][0], w[0][1]),
+            b: (w[1][0], w[1][1]),
+        })
+        .collect();
+    if points.len() > 2 {
+        walls.push(Segment {
+            a: (points[points.len() - 1][0], points[points.len() - 1][1]),
+            b: (points[0][0], points[0][1]),
+        });
+    }
+    walls
+}
+
+/// Outer edges of occupied cells (edges shared by two occupied cells are skipped)
+fn grid_walls(grid: &GridMap) -> Vec<Segment> {
+    let rows: Vec<Vec<bool>> = grid
+        .rows
+        .iter()
+        .map(|row| row.chars().map(|c| c == '#').collect())
+        .collect();
+    let occupied = |r: isize, c: isize| -> bool {
+        r >= 0
+            && c >= 0
+            && rows
+                .get(r as usize)
+                .and_then(|row| row.get(c as usize))
+                .copied()
+                .unwrap_or(false)
+    };
+    let res = grid.resolution;
+    let height = rows.len() as isize;
+    let mut walls = Vec::new();
+    for (r, row) in rows.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
+            if !cell {
+                continue;
+            }
+            let (r, c) = (r as isize, c as isize);
+            let x0 = grid.origin[0] + c as f64 * res;
+            let y0 = grid.origin[1] + (height - 1 - r) as f64 * res;
+            let (x1, y1) = (x0 + res, y0 + res);
+            if !occupied(r - 1, c) {
+                walls.push(Segment {
+                    a: (x0, y1),
+                    b: (x1, y1),
+                });
+            }
+            if !occupied(r + 1, c) {
+                walls.push(Segment {
+                    a: (x0, y0),
+                    b: (x1, y0),
+                });
+            }
+            if !occupied(r, c - 1) {
+                walls.push(Segment {
+                    a: (x0, y0),
+                    b: (x0, y1),
+                });
+            }
+            if !occupied(r, c + 1) {
+                walls.push(Segment {
+                    a: (x1, y0),
+                    b: (x1, y1),
+                });
+            }
+        }
+    }
+    walls
+}
+
+/// Simulated world shared by the drive and sense tools
+pub struct SimWorld {
+    map: SimMap,
+    walls: Vec<Segment>,
+    pose: Mutex<Pose>,
+    #[cfg(feature = "safety")]
+    feed: Mutex<Option<tokio::sync::mpsc::Sender<crate::safety::SensorReading>>>,
+}
+
+impl SimWorld {
+    pub fn new(map: SimMap) -> Self {
+        let mut walls: Vec<Segment> = map.obstacles.iter().flat_map(polygon_walls).collect();
+        if let Some(grid) = &map.grid {
+            walls.extend(grid_walls(grid));
+        }
+        Self {
+            pose: Mutex::new(map.start),
+            map,
+            walls,
+            #[cfg(feature = "safety")]
+            feed: Mutex::new(None),
+        }
+    }
+
+    /// Parse a map from TOML
+    pub fn from_toml(content: &str) -> Result<Self> {
+        Ok(Self::new(toml::from_str(content)?))
+    }
+
+    /// Load a map from a TOML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read sim map {}", path.display()))?;
+        Self::from_toml(&content)
+            .with_context(|| format!("Failed to parse sim map {}", path.display()))
+    }
+
+    /// World for `[sim]`, shared with every tool using the same map
+    ///
+    /// Without a map the robot drives in open space.
+    pub fn shared(config: &SimConfig) -> Result<Arc<Self>> {
+        let mut worlds = WORLDS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(world) = worlds.get(&config.map).and_then(Weak::upgrade) {
+            return Ok(world);
+        }
+        let world = Arc::new(match &config.map {
+            Some(path) => Self::load(path)?,
+            None => Self::new(SimMap::default()),
+        });
+        worlds.insert(config.map.clone(), Arc::downgrade(&world));
+        Ok(world)
+    }
+
+    pub fn map(&self) -> &SimMap {
+        &self.map
+    }
+
+    /// Current robot pose
+    pub fn pose(&self) -> Pose {
+        *self.pose.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Teleport the robot (no collision check)
+    pub fn set_pose(&self, pose: Pose) {
+        *self.pose.lock().unwrap_or_else(|e| e.into_inner()) = pose;
+    }
+
+    /// Distance from the robot centre to the nearest wall, and the nearest point
+    fn clearance(&self, pose: &Pose) -> (f64, Option<(f64, f64)>) {
+        self.walls
+            .iter()
+            .map(|wall| {
+                let p = wall.closest_point((pose.x, pose.y));
+                ((p.0 - pose.x).hypot(p.1 - pose.y), Some(p))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap_or((f64::INFINITY, None))
+    }
+
+    /// Apply a velocity command in the robot frame for `duration_ms`
+    ///
+    /// Returns the bump sensor that triggered if the robot hit a wall; the
+    /// robot stops at the point of contact.
+    pub fn drive(
+        &self,
+        linear_x: f64,
+        linear_y: f64,
+        angular_z: f64,
+        duration_ms: u64,
+    ) -> Option<String> {
+        let mut pose = self.pose.lock().unwrap_or_else(|e| e.into_inner());
+        let steps = (duration_ms as f64 / 1000.0 / STEP_SECS).round() as u64;
+        let mut bump = None;
+        let (mut clearance, _) = self.clearance(&pose);
+
+        for _ in 0..steps {
+            let theta = pose.theta + angular_z * STEP_SECS;
+            let (sin, cos) = theta.sin_cos();
+            let next = Pose {
+                x: pose.x + (linear_x * cos - linear_y * sin) * STEP_SECS,
+                y: pose.y + (linear_x * sin + linear_y * cos) * STEP_SECS,
+                theta,
+            };
+            let (next_clearance, contact) = self.clearance(&next);
+            // Moving away from a wall the robot already touches is allowed
+            if next_clearance < self.map.robot_radius && next_clearance < clearance {
+                bump = contact.map(|p| bump_sensor(&pose, p));
+                break;
+            }
+            *pose = next;
+            clearance = next_clearance;
+        }
+        let pose = *pose;
+
+        #[cfg(feature = "safety")]
+        {
+            if let Some(sensor) = &bump {
+                self.send(crate::safety::SensorReading::Bump {
+                    sensor: sensor.clone(),
+                });
+            }
+            self.publish_at(&pose);
+        }
+        #[cfg(not(feature = "safety"))]
+        let _ = pose;
+
+        bump
+    }
+
+    fn cast(&self, pose: &Pose, angle: f64, max_range: f64) -> f64 {
+        let dir = angle.sin_cos();
+        self.walls
+            .iter()
+            .filter_map(|wall| wall.ray_hit((pose.x, pose.y), (dir.1, dir.0)))
+            .fold(max_range, f64::min)
+    }
+
+    fn lidar_at(&self, pose: &Pose) -> Vec<f64> {
+        (0..360)
+            .map(|deg| {
+                self.cast(
+                    pose,
+                    pose.theta + f64::from(deg).to_radians(),
+                    self.map.lidar_range,
+                )
+            })
+            .collect()
+    }
+
+    /// LIDAR ranges, one per degree counter-clockwise from forward
+    /// (index 90 = left, 270 = right)
+    pub fn lidar(&self) -> Vec<f64> {
+        self.lidar_at(&self.pose())
+    }
+
+    /// Forward ultrasonic distance
+    pub fn ultrasonic(&self) -> f64 {
+        let pose = self.pose();
+        self.cast(&pose, pose.theta, self.map.ultrasonic_range)
+    }
+
+    /// Send sensor readings to a `SafetyMonitor` after every move
+    #[cfg(feature = "safety")]
+    pub fn connect(&self, tx: tokio::sync::mpsc::Sender<crate::safety::SensorReading>) {
+        *self.feed.lock().unwrap_or_else(|e| e.into_inner()) = Some(tx);
+    }
+
+    /// Send the nearest LIDAR reading to the connected monitor
+    #[cfg(feature = "safety")]
+    pub fn publish(&self) {
+        self.publish_at(&self.pose());
+    }
+
+    #[cfg(feature = "safety")]
+    fn publish_at(&self, pose: &Pose) {
+        let (distance, angle) = self
+            .lidar_at(pose)
+            .into_iter()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, d)| (d, i as u16))
+            .unwrap_or((self.map.lidar_range, 0));
+        self.send(crate::safety::SensorReading::Lidar { distance, angle });
+    }
+
+    #[cfg(feature = "safety")]
+    fn send(&self, reading: crate::safety::SensorReading) {
+        if let Some(tx) = self.feed.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            // A full channel just drops a reading, like a busy sensor bus
+            let _ = tx.try_send(reading);
+        }
+    }
+
+    /// Publish LIDAR readings periodically, like a spinning sensor
+    #[cfg(feature = "safety")]
+    pub fn spawn_sensor_feed(
+        self: &Arc<Self>,
+        period: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let world = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                world.publish();
+            }
+        })
+    }
+}
+
+/// Bump sensor name for a contact point relative to the robot heading
+fn bump_sensor(pose: &Pose, contact: (f64, f64)) -> String {
+    let bearing = (contact.1 - pose.y).atan2(contact.0 - pose.x) - pose.theta;
+    let bearing = bearing.sin().atan2(bearing.cos());
+    let side = if bearing >= 0.0 { "left" } else { "right" };
+    let end = if bearing.abs() <= std::f64::consts::FRAC_PI_2 {
+        "front"
+    } else {
+        "rear"
+    };
+    format!("{end}_{side}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corridor() -> SimWorld {
+        SimWorld::from_toml(
+            r#"
+            robot_radius = 0.2
+            [[obstacles]]
+            points = [[2.0, -1.0], [2.0, 1.0]]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn integrates_pose_from_velocity() {
+        let world = SimWorld::new(SimMap::default());
+        assert!(world.drive(0.5, 0.0, 0.0, 2000).is_none());
+        let pose = world.pose();
+        assert!((pose.x - 1.0).abs() < 1e-6 && pose.y.abs() < 1e-6);
+
+        world.drive(0.0, 0.0, std::f64::consts::FRAC_PI_2, 1000);
+        world.drive(0.5, 0.0, 0.0, 1000);
+        let pose = world.pose();
+        assert!((pose.x - 1.0).abs() < 1e-3 && (pose.y - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn stops_at_wall_and_reports_bump() {
+        let world = corridor();
+        let bump = world.drive(0.5, 0.0, 0.0, 10_000);
+        assert!(bump.is_some_and(|s| s.starts_with("front")));
+        let pose = world.pose();
+        assert!((pose.x - 1.8).abs() < 0.01, "{pose:?}");
+        // Backing away is always possible
+        assert!(world.drive(-0.5, 0.0, 0.0, 1000).is_none());
+    }
+
+    #[test]
+    fn lidar_sees_walls() {
+        let world = corridor();
+        let ranges = world.lidar();
+        assert_eq!(ranges.len(), 360);
+        assert!((ranges[0] - 2.0).abs() < 1e-6);
+        assert!((ranges[180] - 12.0).abs() < 1e-6);
+        assert!((world.ultrasonic() - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn grid_cells_become_walls() {
+        let world = SimWorld::from_toml(
+            "
+            start = { x = 0.5, y = 0.5, theta = 0.0 }
+            [grid]
+            resolution = 1.0
+            origin = [-1.0, -1.0]
+            rows = [\"###\", \"#.#\", \"###\"]
+            ",
+        )
+        .unwrap();
+        let ranges = world.lidar();
+        for deg in [0, 90, 180, 270] {
+            assert!((ranges[deg] - 0.5).abs() < 1e-6, "{deg}: {}", ranges[deg]);
+        }
+    }
+}
@@ -538,3 +538,175 @@ mod integration_tests {
         assert!(result.error.unwrap().contains("Safety"));
     }
 }
+
+#[cfg(test)]
+#[cfg(feature = "safety")]
+mod sim_tests {
+    use crate::config::RobotConfig;
+    use crate::safety::{SafetyEvent, SafetyMonitor};
+    use crate::sim::{Pose, SimWorld};
+    use crate::traits::Tool;
+    use crate::{DriveTool, SafeDrive, SenseTool};
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::broadcast;
+
+    /// Open room with a wall 1m ahead of the start pose
+    const WALL_AHEAD: &str = r#"
+        robot_radius = 0.15
+        [[obstacles]]
+        points = [[1.0, -2.0], [1.0, 2.0]]
+    "#;
+
+    fn sim_config() -> RobotConfig {
+        let mut config = RobotConfig::default();
+        config.drive.backend = "sim".to_string();
+        config.sensors.lidar_type = "sim".to_string();
+        config
+    }
+
+    /// Monitor fed by the world's sensor readings
+    fn monitored(config: &RobotConfig, world: &SimWorld) -> Arc<SafetyMonitor> {
+        let (monitor, _rx) = SafetyMonitor::new(config.safety.clone());
+        let monitor = Arc::new(monitor);
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        world.connect(tx);
+        tokio::spawn({
+            let monitor = monitor.clone();
+            async move { monitor.run(rx).await }
+        });
+        monitor
+    }
+
+    async fn wait_for(
+        events: &mut broadcast::Receiver<SafetyEvent>,
+        matches: impl Fn(&SafetyEvent) -> bool,
+    ) -> bool {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while let Ok(event) = events.recv().await {
+                if matches(&event) {
+                    return true;
+                }
+            }
+            false
+        })
+        .await
+        .unwrap_or(false)
+    }
+
+    #[tokio::test]
+    async fn sim_drive_and_sense_share_world() {
+        let dir = tempfile::tempdir().unwrap();
+        let map = dir.path().join("map.toml");
+        std::fs::write(&map, WALL_AHEAD).unwrap();
+        let mut config = sim_config();
+        config.sim.map = Some(map);
+
+        let drive = DriveTool::new(config.clone());
+        let sense = SenseTool::new(config);
+
+        let before = sense.execute(json!({"action": "distance"})).await.unwrap();
+        assert!(before.output.contains("1.00m"), "{}", before.output);
+
+        let result = drive
+            .execute(json!({"action": "forward", "distance": 0.5}))
+            .await
+            .unwrap();
+        assert!(result.success);
+
+        let after = sense.execute(json!({"action": "distance"})).await.unwrap();
+        assert!(after.output.contains("0.50m"), "{}", after.output);
+    }
+
+    #[tokio::test]
+    async fn sim_collision_fires_bump() {
+        let config = sim_config();
+        let world = Arc::new(SimWorld::from_toml(WALL_AHEAD).unwrap());
+        let monitor = monitored(&config, &world);
+        let mut events = monitor.subscribe();
+
+        let drive = DriveTool::with_sim(config, world.clone());
+        let result = drive
+            .execute(json!({"action": "forward", "distance": 3.0}))
+            .await
+            .unwrap();
+        assert!(result.success);
+
+        assert!(
+            wait_for(&mut events, |e| matches!(
+                e,
+                SafetyEvent::BumpDetected { .. }
+            ))
+            .await
+        );
+        assert!(!monitor.can_move().await);
+        assert!((world.pose().x - 0.85).abs() < 0.01, "{:?}", world.pose());
+    }
+
+    #[tokio::test]
+    async fn safe_drive_refuses_to_approach_wall_in_sim() {
+        let config = sim_config();
+        let world = Arc::new(SimWorld::from_toml(WALL_AHEAD).unwrap());
+        world.set_pose(Pose {
+            x: 0.75,
+            y: 0.0,
+            theta: 0.0,
+        });
+        let monitor = monitored(&config, &world);
+        let mut events = monitor.subscribe();
+        world.publish();
+        assert!(
+            wait_for(&mut events, |e| matches!(
+                e,
+                SafetyEvent::ObstacleDetected { .. }
+            ))
+            .await
+        );
+
+        let drive = Arc::new(DriveTool::with_sim(config, world.clone()));
+        let safe_drive = SafeDrive::new(drive, monitor);
+        let result = safe_drive
+            .execute(json!({"action": "forward", "distance": 0.5}))
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(world.pose().x, 0.75);
+    }
+
+    #[tokio::test]
+    async fn watchdog_fires_while_sim_sensors_stream() {
+        let mut config = sim_config();
+        config.safety.max_drive_duration = 0;
+        config.safety.sensor_timeout_secs = 1;
+        let world = Arc::new(SimWorld::from_toml(WALL_AHEAD).unwrap());
+        let monitor = monitored(&config, &world);
+        let mut events = monitor.subscribe();
+        let feed = world.spawn_sensor_feed(Duration::from_millis(100));
+
+        let drive = Arc::new(DriveTool::with_sim(config, world.clone()));
+        let safe_drive = SafeDrive::new(drive, monitor.clone());
+        let result = safe_drive
+            .execute(json!({"action": "forward", "distance": 0.2}))
+            .await
+            .unwrap();
+        assert!(result.success, "{result:?}");
+
+        assert!(wait_for(&mut events, |e| matches!(e, SafetyEvent::WatchdogTimeout)).await);
+        // Fresh readings keep the stale-sensor guard from blocking movement
+        assert!(monitor.can_move().await);
+        feed.abort();
+    }
+
+    #[tokio::test]
+    async fn stale_sim_sensors_block_movement() {
+        let mut config = sim_config();
+        config.safety.sensor_timeout_secs = 1;
+        let world = SimWorld::from_toml(WALL_AHEAD).unwrap();
+        let monitor = monitored(&config, &world);
+
+        tokio::time::sleep(Duration::from_millis(2200)).await;
+        assert!(!monitor.can_move().await);
+    }
+}
@@ -1,14 +1,23 @@
 /*
  * ZeroClaw Arduino Uno Firmware
  *
- * Listens for JSON commands on Serial (115200 baud), executes gpio_read/gpio_write,
- * responds with JSON. Compatible with ZeroClaw SerialPeripheral protocol.
+ * Listens for JSON commands on Serial (115200 baud), executes GPIO, I2C, SPI,
+ * PWM and ADC commands, responds with JSON. Compatible with ZeroClaw
+ * SerialPeripheral protocol.
  *
  * Protocol (newline-delimited JSON):
  *   Request:  {"id":"1","cmd":"gpio_write","args":{"pin":13,"value":1}}
  *   Response: {"id":"1","ok":true,"result":"done"}
+ *   Event:    {"event":"gpio_edge","sub":"door","data":{"pin":2,"value":1}}
  *
  * Arduino Uno: Pin 13 has built-in LED. Digital pins 0-13 supported.
+ *   I2C bus 0:  SDA=A4, SCL=A5 (Wire)
+ *   SPI bus 0:  SCK=13, MISO=12, MOSI=11, CS=10 (mode 0, 1 MHz)
+ *   PWM pins:   3, 5, 6, 9, 10, 11 (freq_hz=50 drives a servo instead)
+ *   ADC:        channels 0-5 = A0-A5, 10 bit, 5 V reference
+ * Byte payloads ("data", read results) are hex strings, at most 32 bytes.
+ * "subscribe" watches a pin edge, ADC threshold or ADC sample interval; up to
+ * 4 subscriptions are checked every loop and push events tagged with "sub".
  *
  * 1. Open in Arduino IDE
  * 2. Select Board: Arduino Uno
@@ -16,8 +25,36 @@
  * 4. Upload
  */
 
+#include <SPI.h>
+#include <Servo.h>
+#include <Wire.h>
+
 #define BAUDRATE 115200
 #define MAX_LINE 256
+#define MAX_BYTES 32
+#define SPI_CS_PIN 10
+#define MAX_SUBS 4
+#define SUB_NAME_LEN 24
+
+enum SubKind { SUB_NONE, SUB_GPIO_EDGE, SUB_ADC_THRESHOLD, SUB_ADC_SAMPLE };
+
+struct Subscription {
+  SubKind kind;
+  char name[SUB_NAME_LEN];
+  int pin;            // GPIO pin or ADC channel
+  int edge;           // gpio_edge: 1 rising, -1 falling, 0 both
+  long above;         // adc_threshold: -1 when unset
+  long below;
+  unsigned long intervalMs;
+  unsigned long nextSample;
+  int last;
+};
+
+const int PWM_PINS[] = {3, 5, 6, 9, 10, 11};
+const int PWM_PIN_COUNT = sizeof(PWM_PINS) / sizeof(PWM_PINS[0]);
+Servo servos[PWM_PIN_COUNT];
+
+Subscription subs[MAX_SUBS];
 
 char lineBuf[MAX_LINE];
 int lineLen = 0;
@@ -32,6 +69,30 @@ int parseArg(const char* key, const char* json) {
   return atoi(p);
 }
 
+// Parse long from JSON, or fallback when the key is missing
+long parseLongArg(const char* key, const char* json, long fallback) {
+  char search[32];
+  snprintf(search, sizeof(search), "\"%s\":", key);
+  const char* p = strstr(json, search);
+  if (!p) return fallback;
+  return atol(p + strlen(search));
+}
+
+// Copy a string argument: "sub":"door". Returns false if missing.
+bool parseStrArg(const char* key, const char* json, char* out, int outLen) {
+  char search[32];
+  snprintf(search, sizeof(search), "\"%s\":\"", key);
+  const char* p = strstr(json, search);
+  if (!p) return false;
+  p += strlen(search);
+  int i = 0;
+  while (i < outLen - 1 && *p && *p != '"') {
+    out[i++] = *p++;
+  }
+  out[i] = '\0';
+  return true;
+}
+
 // Extract "id" for response
 void copyId(char* out, int outLen, const char* json) {
   const char* p = strstr(json, "\"id\":\"");
@@ -55,6 +116,295 @@ bool hasCmd(const char* json, const char* cmd) {
   return strstr(json, search) != NULL;
 }
 
+// Check if an argument is present: "reg":
+bool hasArg(const char* key, const char* json) {
+  char search[32];
+  snprintf(search, sizeof(search), "\"%s\":", key);
+  return strstr(json, search) != NULL;
+}
+
+int hexDigit(char c) {
+  if (c >= '0' && c <= '9') return c - '0';
+  if (c >= 'a' && c <= 'f') return c - 'a' + 10;
+  if (c >= 'A' && c <= 'F') return c - 'A' + 10;
+  return -1;
+}
+
+// Parse hex bytes from JSON: "data":"0a1b". Returns byte count, or -1 if invalid.
+int parseHexArg(const char* key, const char* json, uint8_t* out, int maxLen) {
+  char search[32];
+  snprintf(search, sizeof(search), "\"%s\":\"", key);
+  const char* p = strstr(json, search);
+  if (!p) return -1;
+  p += strlen(search);
+  int n = 0;
+  while (*p && *p != '"') {
+    int hi = hexDigit(p[0]);
+    int lo = p[1] ? hexDigit(p[1]) : -1;
+    if (hi < 0 || lo < 0 || n >= maxLen) return -1;
+    out[n++] = (hi << 4) | lo;
+    p += 2;
+  }
+  return n;
+}
+
+void replyOk(const char* id, const char* result) {
+  Serial.print("{\"id\":\"");
+  Serial.print(id);
+  Serial.print("\",\"ok\":true,\"result\":\"");
+  Serial.print(result);
+  Serial.println("\"}");
+}
+
+void replyError(const char* id, const char* error) {
+  Serial.print("{\"id\":\"");
+  Serial.print(id);
+  Serial.print("\",\"ok\":false,\"result\":\"\",\"error\":\"");
+  Serial.print(error);
+  Serial.println("\"}");
+}
+
+// Reply with bytes as a hex string
+void replyHex(const char* id, const uint8_t* data, int len) {
+  char hex[MAX_BYTES * 2 + 1];
+  for (int i = 0; i < len; i++) {
+    snprintf(hex + i * 2, 3, "%02x", data[i]);
+  }
+  hex[len * 2] = '\0';
+  replyOk(id, hex);
+}
+
+int pwmIndex(int pin) {
+  for (int i = 0; i < PWM_PIN_COUNT; i++) {
+    if (PWM_PINS[i] == pin) return i;
+  }
+  return -1;
+}
+
+void handleI2cScan(const char* id, const char* line) {
+  if (parseArg("bus", line) != 0) {
+    replyError(id, "Invalid I2C bus");
+    return;
+  }
+  char found[128];
+  int len = 0;
+  found[0] = '\0';
+  for (uint8_t addr = 0x08; addr <= 0x77; addr++) {
+    Wire.beginTransmission(addr);
+    if (Wire.endTransmission() == 0 && len < (int)sizeof(found) - 5) {
+      len += snprintf(found + len, sizeof(found) - len, len ? ",%d" : "%d", addr);
+    }
+  }
+  replyOk(id, found);
+}
+
+void handleI2cRead(const char* id, const char* line) {
+  int addr = parseArg("addr", line);
+  int len = parseArg("len", line);
+  if (parseArg("bus", line) != 0 || addr < 0x08 || addr > 0x77 || len < 1 || len > MAX_BYTES) {
+    replyError(id, "Invalid I2C arguments");
+    return;
+  }
+  if (hasArg("reg", line)) {
+    Wire.beginTransmission(addr);
+    Wire.write((uint8_t)parseArg("reg", line));
+    if (Wire.endTransmission(false) != 0) {
+      replyError(id, "I2C device did not acknowledge");
+      return;
+    }
+  }
+  uint8_t buf[MAX_BYTES];
+  int n = Wire.requestFrom(addr, len);
+  for (int i = 0; i < n; i++) {
+    buf[i] = Wire.read();
+  }
+  if (n != len) {
+    replyError(id, "I2C short read");
+    return;
+  }
+  replyHex(id, buf, n);
+}
+
+void handleI2cWrite(const char* id, const char* line) {
+  int addr = parseArg("addr", line);
+  uint8_t buf[MAX_BYTES];
+  int len = parseHexArg("data", line, buf, MAX_BYTES);
+  if (parseArg("bus", line) != 0 || addr < 0x08 || addr > 0x77 || len < 1) {
+    replyError(id, "Invalid I2C arguments");
+    return;
+  }
+  Wire.beginTransmission(addr);
+  if (hasArg("reg", line)) {
+    Wire.write((uint8_t)parseArg("reg", line));
+  }
+  Wire.write(buf, len);
+  if (Wire.endTransmission() != 0) {
+    replyError(id, "I2C device did not acknowledge");
+    return;
+  }
+  replyOk(id, "done");
+}
+
+void handleSpiTransfer(const char* id, const char* line) {
+  uint8_t buf[MAX_BYTES];
+  int len = parseHexArg("data", line, buf, MAX_BYTES);
+  if (parseArg("bus", line) != 0 || len < 1) {
+    replyError(id, "Invalid SPI arguments");
+    return;
+  }
+  SPI.beginTransaction(SPISettings(1000000, MSBFIRST, SPI_MODE0));
+  digitalWrite(SPI_CS_PIN, LOW);
+  SPI.transfer(buf, len);
+  digitalWrite(SPI_CS_PIN, HIGH);
+  SPI.endTransaction();
+  replyHex(id, buf, len);
+}
+
+void handlePwmSet(const char* id, const char* line) {
+  int pin = parseArg("pin", line);
+  int duty = parseArg("duty_permille", line);
+  int freq = hasArg("freq_hz", line) ? parseArg("freq_hz", line) : 0;
+  int idx = pwmIndex(pin);
+  if (idx < 0 || duty < 0 || duty > 1000) {
+    replyError(id, "Invalid PWM arguments");
+    return;
+  }
+  if (freq == 50) {
+    // Servo: 20 ms period, pulse width from the duty cycle
+    if (!servos[idx].attached()) servos[idx].attach(pin);
+    servos[idx].writeMicroseconds(duty * 20);
+  } else if (freq == 0) {
+    if (servos[idx].attached()) servos[idx].detach();
+    pinMode(pin, OUTPUT);
+    analogWrite(pin, (long)duty * 255 / 1000);
+  } else {
+    replyError(id, "Only the default PWM frequency or 50 Hz (servo) is supported");
+    return;
+  }
+  replyOk(id, "done");
+}
+
+void handleAdcRead(const char* id, const char* line) {
+  int channel = parseArg("channel", line);
+  if (channel < 0 || channel > 5) {
+    replyError(id, "Invalid ADC channel");
+    return;
+  }
+  char result[8];
+  snprintf(result, sizeof(result), "%d", analogRead(A0 + channel));
+  replyOk(id, result);
+}
+
+int readSubscription(const Subscription& sub) {
+  if (sub.kind == SUB_GPIO_EDGE) return digitalRead(sub.pin);
+  return analogRead(A0 + sub.pin);
+}
+
+void handleSubscribe(const char* id, const char* line) {
+  char name[SUB_NAME_LEN];
+  char kind[16];
+  if (!parseStrArg("sub", line, name, sizeof(name)) || !name[0] ||
+      !parseStrArg("kind", line, kind, sizeof(kind))) {
+    replyError(id, "Missing subscription name");
+    return;
+  }
+  Subscription sub = {};
+  strncpy(sub.name, name, SUB_NAME_LEN);
+  sub.above = parseLongArg("above", line, -1);
+  sub.below = parseLongArg("below", line, -1);
+  if (strcmp(kind, "gpio_edge") == 0) {
+    char edge[8] = "both";
+    parseStrArg("edge", line, edge, sizeof(edge));
+    sub.kind = SUB_GPIO_EDGE;
+    sub.pin = parseArg("pin", line);
+    sub.edge = strcmp(edge, "rising") == 0 ? 1 : strcmp(edge, "falling") == 0 ? -1 : 0;
+    if (sub.pin < 0 || sub.pin > 13) {
+      replyError(id, "Invalid pin");
+      return;
+    }
+    pinMode(sub.pin, INPUT);
+  } else if (strcmp(kind, "adc_threshold") == 0 || strcmp(kind, "adc_sample") == 0) {
+    sub.kind = kind[4] == 't' ? SUB_ADC_THRESHOLD : SUB_ADC_SAMPLE;
+    sub.pin = parseArg("channel", line);
+    sub.intervalMs = parseLongArg("interval_ms", line, 0);
+    if (sub.pin < 0 || sub.pin > 5) {
+      replyError(id, "Invalid ADC channel");
+      return;
+    }
+    if (sub.kind == SUB_ADC_THRESHOLD && sub.above < 0 && sub.below < 0) {
+      replyError(id, "Missing threshold");
+      return;
+    }
+    if (sub.kind == SUB_ADC_SAMPLE && sub.intervalMs == 0) {
+      replyError(id, "Invalid interval");
+      return;
+    }
+  } else {
+    replyError(id, "Unknown subscription kind");
+    return;
+  }
+  sub.last = readSubscription(sub);
+  sub.nextSample = millis() + sub.intervalMs;
+
+  // Replace a subscription with the same name, else take a free slot
+  int slot = -1;
+  for (int i = 0; i < MAX_SUBS; i++) {
+    if (subs[i].kind != SUB_NONE && strcmp(subs[i].name, name) == 0) {
+      slot = i;
+      break;
+    }
+    if (slot < 0 && subs[i].kind == SUB_NONE) slot = i;
+  }
+  if (slot < 0) {
+    replyError(id, "Too many subscriptions");
+    return;
+  }
+  subs[slot] = sub;
+  replyOk(id, "done");
+}
+
+void pushEvent(const char* event, const Subscription& sub, const char* key, int value, const char* crossed) {
+  Serial.print("{\"event\":\"");
+  Serial.print(event);
+  Serial.print("\",\"sub\":\"");
+  Serial.print(sub.name);
+  Serial.print("\",\"data\":{\"");
+  Serial.print(key);
+  Serial.print("\":");
+  Serial.print(sub.pin);
+  Serial.print(",\"value\":");
+  Serial.print(value);
+  if (crossed) {
+    Serial.print(",\"crossed\":\"");
+    Serial.print(crossed);
+    Serial.print("\"");
+  }
+  Serial.println("}}");
+}
+
+void pollSubscriptions() {
+  for (int i = 0; i < MAX_SUBS; i++) {
+    Subscription& sub = subs[i];
+    if (sub.kind == SUB_NONE) continue;
+    int value = readSubscription(sub);
+    int last = sub.last;
+    sub.last = value;
+    if (sub.kind == SUB_GPIO_EDGE) {
+      bool fired = sub.edge > 0 ? value > last : sub.edge < 0 ? value < last : value != last;
+      if (fired) pushEvent("gpio_edge", sub, "pin", value, NULL);
+    } else if (sub.kind == SUB_ADC_THRESHOLD) {
+      if (sub.above >= 0 && last <= sub.above && value > sub.above) {
+        pushEvent("adc_threshold", sub, "channel", value, "above");
+      } else if (sub.below >= 0 && last >= sub.below && value < sub.below) {
+        pushEvent("adc_threshold", sub, "channel", value, "below");
+      }
+    } else if ((long)(millis() - sub.nextSample) >= 0) {
+      sub.nextSample = millis() + sub.intervalMs;
+      pushEvent("adc_sample", sub, "channel", value, NULL);
+    }
+  }
+}
+
 void handleLine(const char* line) {
   char idBuf[16];
   copyId(idBuf, sizeof(idBuf), line);
@@ -66,11 +416,11 @@ void handleLine(const char* line) {
     return;
   }
 
-  // Phase C: Dynamic discovery — report GPIO pins and LED pin
+  // Phase C: Dynamic discovery — report GPIO pins, LED pin, buses and ADC
   if (hasCmd(line, "capabilities")) {
     Serial.print("{\"id\":\"");
     Serial.print(idBuf);
-    Serial.print("\",\"ok\":true,\"result\":\"{\\\"gpio\\\":[0,1,2,3,4,5,6,7,8,9,10,11,12,13],\\\"led_pin\\\":13}\"}");
+    Serial.print("\",\"ok\":true,\"result\":\"{\\\"gpio\\\":[0,1,2,3,4,5,6,7,8,9,10,11,12,13],\\\"led_pin\\\":13,\\\"i2c\\\":[0],\\\"spi\\\":[0],\\\"pwm\\\":[3,5,6,9,10,11],\\\"adc\\\":[0,1,2,3,4,5],\\\"adc_bits\\\":10,\\\"adc_vref_mv\\\":5000}\"}");
     Serial.println();
     return;
   }
@@ -114,6 +464,35 @@ void handleLine(const char* line) {
     return;
   }
 
+  if (hasCmd(line, "i2c_scan")) {
+    handleI2cScan(idBuf, line);
+    return;
+  }
+  if (hasCmd(line, "i2c_read")) {
+    handleI2cRead(idBuf, line);
+    return;
+  }
+  if (hasCmd(line, "i2c_write")) {
+    handleI2cWrite(idBuf, line);
+    return;
+  }
+  if (hasCmd(line, "spi_transfer")) {
+    handleSpiTransfer(idBuf, line);
+    return;
+  }
+  if (hasCmd(line, "pwm_set")) {
+    handlePwmSet(idBuf, line);
+    return;
+  }
+  if (hasCmd(line, "adc_read")) {
+    handleAdcRead(idBuf, line);
+    return;
+  }
+  if (hasCmd(line, "subscribe")) {
+    handleSubscribe(idBuf, line);
+    return;
+  }
+
   // Unknown command
   Serial.print("{\"id\":\"");
   Serial.print(idBuf);
@@ -122,6 +501,10 @@ void handleLine(const char* line) {
 
 void setup() {
   Serial.begin(BAUDRATE);
+  Wire.begin();
+  pinMode(SPI_CS_PIN, OUTPUT);
+  digitalWrite(SPI_CS_PIN, HIGH);
+  SPI.begin();
   lineLen = 0;
 }
 
@@ -140,4 +523,5 @@ void loop() {
       lineLen = 0;  // Overflow, discard
     }
   }
+  pollSubscriptions();
 }
@@ -4,6 +4,12 @@
 //! USART2 is connected to ST-Link VCP — host sees /dev/ttyACM0 (Linux) or /dev/cu.usbmodem* (macOS).
 //!
 //! Protocol: same as Arduino/ESP32 — see docs/hardware-peripherals-design.md
+//!
+//! Buses (Arduino header names):
+//! - I2C bus 0: I2C1, SCL=PB8 (D15), SDA=PB9 (D14)
+//! - SPI bus 0: SPI2, SCK=PB13, MISO=PB14, MOSI=PB15, CS=PB12 (mode 0, 1 MHz)
+//! - PWM: TIM3, D5=PB4 (CH1), D9=PC7 (CH2)
+//! - ADC: ADC1, channels 0-2 = A0 (PA0), A1 (PA1), A2 (PA4), 12 bit, 3.3 V
 
 #![no_std]
 #![no_main]
@@ -12,7 +18,13 @@ use core::fmt::Write;
 use core::str;
 use defmt::info;
 use embassy_executor::Spawner;
-use embassy_stm32::gpio::{Level, Output, Speed};
+use embassy_stm32::adc::Adc;
+use embassy_stm32::gpio::{Level, Output, OutputType, Speed};
+use embassy_stm32::i2c::I2c;
+use embassy_stm32::spi::{self, Spi};
+use embassy_stm32::time::{hz, Hertz};
+use embassy_stm32::timer::low_level::CountingMode;
+use embassy_stm32::timer::simple_pwm::{PwmPin, SimplePwm};
 use embassy_stm32::usart::{Config, Uart};
 use heapless::String;
 use {defmt_rtt as _, panic_probe as _};
@@ -20,6 +32,14 @@ use {defmt_rtt as _, panic_probe as _};
 /// Arduino-style pin 13 = PA5 (User LED LD2 on Nucleo-F401RE)
 const LED_PIN: u8 = 13;
 
+/// Largest I2C/SPI payload, in bytes (sent as hex).
+const MAX_BYTES: usize = 32;
+
+/// Default PWM frequency when the request gives none.
+const PWM_DEFAULT_HZ: u32 = 1_000;
+
+type Response = String<256>;
+
 /// Parse integer from JSON: "pin":13 or "value":1
 fn parse_arg(line: &[u8], key: &[u8]) -> Option<i32> {
     // key like b"pin" -> search for b"\"pin\":"
@@ -62,6 +82,76 @@ fn parse_arg(line: &[u8], key: &[u8]) -> Option<i32> {
     None
 }
 
+/// Whether the key is present: "reg":
+fn has_arg(line: &[u8], key: &[u8]) -> bool {
+    parse_arg(line, key).is_some()
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Parse hex bytes from JSON: "data":"0a1b". Returns the byte count.
+fn parse_hex_arg(line: &[u8], key: &[u8], out: &mut [u8]) -> Option<usize> {
+    let mut pat: [u8; 36] = [0; 36];
+    let klen = key.len().min(30);
+    pat[0] = b'"';
+    pat[1..1 + klen].copy_from_slice(&key[..klen]);
+    pat[1 + klen..4 + klen].copy_from_slice(b"\":\"");
+    let pat = &pat[..4 + klen];
+
+    let start = (0..=line.len().checked_sub(pat.len())?).find(|&i| line[i..].starts_with(pat))?;
+    let mut rest = &line[start + pat.len()..];
+    let mut n = 0;
+    while let [hi, lo, tail @ ..] = rest {
+        if *hi == b'"' {
+            break;
+        }
+        if n >= out.len() {
+            return None;
+        }
+        out[n] = hex_digit(*hi)? << 4 | hex_digit(*lo)?;
+        n += 1;
+        rest = tail;
+    }
+    match rest.first() {
+        Some(b'"') => Some(n),
+        _ => None,
+    }
+}
+
+fn reply_ok(resp: &mut Response, id: &str, result: &str) {
+    let _ = write!(resp, "{{\"id\":\"{}\",\"ok\":true,\"result\":\"{}\"}}", id, result);
+}
+
+fn reply_error(resp: &mut Response, id: &str, error: &str) {
+    let _ = write!(
+        resp,
+        "{{\"id\":\"{}\",\"ok\":false,\"result\":\"\",\"error\":\"{}\"}}",
+        id, error
+    );
+}
+
+/// Reply with bytes as a hex string.
+fn reply_hex(resp: &mut Response, id: &str, data: &[u8]) {
+    let _ = write!(resp, "{{\"id\":\"{}\",\"ok\":true,\"result\":\"", id);
+    for b in data {
+        let _ = write!(resp, "{:02x}", b);
+    }
+    let _ = write!(resp, "\"}}");
+}
+
+/// I2C address and payload length from a request, when on bus 0 and in range.
+fn i2c_target(line: &[u8]) -> Option<u8> {
+    let addr = parse_arg(line, b"addr")?;
+    (parse_arg(line, b"bus") == Some(0) && (0x08..=0x77).contains(&addr)).then_some(addr as u8)
+}
+
 fn has_cmd(line: &[u8], cmd: &[u8]) -> bool {
     let mut pat: [u8; 64] = [0; 64];
     pat[0..7].copy_from_slice(b"\"cmd\":\"");
@@ -114,11 +204,34 @@ async fn main(_spawner: Spawner) {
     let mut usart = Uart::new_blocking(p.USART2, p.PA3, p.PA2, config).unwrap();
     let mut led = Output::new(p.PA5, Level::Low, Speed::Low);
 
+    let mut i2c = I2c::new_blocking(p.I2C1, p.PB8, p.PB9, Default::default());
+
+    let mut spi_config = spi::Config::default();
+    spi_config.frequency = Hertz(1_000_000);
+    let mut spi = Spi::new_blocking(p.SPI2, p.PB13, p.PB15, p.PB14, spi_config);
+    let mut spi_cs = Output::new(p.PB12, Level::High, Speed::VeryHigh);
+
+    let mut pwm = SimplePwm::new(
+        p.TIM3,
+        Some(PwmPin::new(p.PB4, OutputType::PushPull)),
+        Some(PwmPin::new(p.PC7, OutputType::PushPull)),
+        None,
+        None,
+        hz(PWM_DEFAULT_HZ),
+        CountingMode::EdgeAlignedUp,
+    );
+
+    let mut adc = Adc::new(p.ADC1);
+    let mut adc_a0 = p.PA0;
+    let mut adc_a1 = p.PA1;
+    let mut adc_a2 = p.PA4;
+
     info!("ZeroClaw Nucleo firmware ready on USART2 (115200)");
 
     let mut line_buf: heapless::Vec<u8, 256> = heapless::Vec::new();
     let mut id_buf = [0u8; 16];
-    let mut resp_buf: String<128> = String::new();
+    let mut resp_buf: Response = String::new();
+    let mut bytes = [0u8; MAX_BYTES];
 
     loop {
         let mut byte = [0u8; 1];
@@ -135,7 +248,7 @@ async fn main(_spawner: Spawner) {
                     } else if has_cmd(&line_buf, b"capabilities") {
                         let _ = write!(
                             resp_buf,
-                            "{{\"id\":\"{}\",\"ok\":true,\"result\":\"{{\\\"gpio\\\":[0,1,2,3,4,5,6,7,8,9,10,11,12,13],\\\"led_pin\\\":13}}\"}}",
+                            "{{\"id\":\"{}\",\"ok\":true,\"result\":\"{{\\\"gpio\\\":[0,1,2,3,4,5,6,7,8,9,10,11,12,13],\\\"led_pin\\\":13,\\\"i2c\\\":[0],\\\"spi\\\":[0],\\\"pwm\\\":[5,9],\\\"adc\\\":[0,1,2],\\\"adc_bits\\\":12,\\\"adc_vref_mv\\\":3300}}\"}}",
                             id_str
                         );
                     } else if has_cmd(&line_buf, b"gpio_read") {
@@ -167,6 +280,96 @@ async fn main(_spawner: Spawner) {
                                 id_str, pin
                             );
                         }
+                    } else if has_cmd(&line_buf, b"i2c_scan") {
+                        if parse_arg(&line_buf, b"bus") == Some(0) {
+                            let mut found: String<128> = String::new();
+                            for addr in 0x08u8..=0x77 {
+                                if i2c.blocking_read(addr, &mut bytes[..1]).is_ok() {
+                                    let sep = if found.is_empty() { "" } else { "," };
+                                    let _ = write!(found, "{}{}", sep, addr);
+                                }
+                            }
+                            reply_ok(&mut resp_buf, id_str, &found);
+                        } else {
+                            reply_error(&mut resp_buf, id_str, "Invalid I2C bus");
+                        }
+                    } else if has_cmd(&line_buf, b"i2c_read") {
+                        let len = parse_arg(&line_buf, b"len").unwrap_or(0);
+                        match i2c_target(&line_buf) {
+                            Some(addr) if (1..=MAX_BYTES as i32).contains(&len) => {
+                                let buf = &mut bytes[..len as usize];
+                                let result = match parse_arg(&line_buf, b"reg") {
+                                    Some(reg) => i2c.blocking_write_read(addr, &[reg as u8], buf),
+                                    None => i2c.blocking_read(addr, buf),
+                                };
+                                match result {
+                                    Ok(()) => reply_hex(&mut resp_buf, id_str, buf),
+                                    Err(_) => reply_error(&mut resp_buf, id_str, "I2C device did not acknowledge"),
+                                }
+                            }
+                            _ => reply_error(&mut resp_buf, id_str, "Invalid I2C arguments"),
+                        }
+                    } else if has_cmd(&line_buf, b"i2c_write") {
+                        // Register byte first, then the payload
+                        let offset = usize::from(has_arg(&line_buf, b"reg"));
+                        let parsed = parse_hex_arg(&line_buf, b"data", &mut bytes[offset..]);
+                        match (i2c_target(&line_buf), parsed) {
+                            (Some(addr), Some(n)) if n > 0 => {
+                                if offset == 1 {
+                                    bytes[0] = parse_arg(&line_buf, b"reg").unwrap_or(0) as u8;
+                                }
+                                match i2c.blocking_write(addr, &bytes[..offset + n]) {
+                                    Ok(()) => reply_ok(&mut resp_buf, id_str, "done"),
+                                    Err(_) => reply_error(&mut resp_buf, id_str, "I2C device did not acknowledge"),
+                                }
+                            }
+                            _ => reply_error(&mut resp_buf, id_str, "Invalid I2C arguments"),
+                        }
+                    } else if has_cmd(&line_buf, b"spi_transfer") {
+                        let parsed = parse_hex_arg(&line_buf, b"data", &mut bytes);
+                        match parsed {
+                            Some(n) if n > 0 && parse_arg(&line_buf, b"bus") == Some(0) => {
+                                spi_cs.set_low();
+                                let result = spi.blocking_transfer_in_place(&mut bytes[..n]);
+                                spi_cs.set_high();
+                                match result {
+                                    Ok(()) => reply_hex(&mut resp_buf, id_str, &bytes[..n]),
+                                    Err(_) => reply_error(&mut resp_buf, id_str, "SPI transfer failed"),
+                                }
+                            }
+                            _ => reply_error(&mut resp_buf, id_str, "Invalid SPI arguments"),
+                        }
+                    } else if has_cmd(&line_buf, b"pwm_set") {
+                        let pin = parse_arg(&line_buf, b"pin").unwrap_or(-1);
+                        let duty = parse_arg(&line_buf, b"duty_permille").unwrap_or(-1);
+                        let freq = parse_arg(&line_buf, b"freq_hz").unwrap_or(PWM_DEFAULT_HZ as i32);
+                        if !(0..=1000).contains(&duty) || !(1..=100_000).contains(&freq) {
+                            reply_error(&mut resp_buf, id_str, "Invalid PWM arguments");
+                        } else if pin == 5 || pin == 9 {
+                            // Both channels share TIM3, so the frequency applies to both.
+                            pwm.set_frequency(hz(freq as u32));
+                            let mut channel = if pin == 5 { pwm.ch1() } else { pwm.ch2() };
+                            channel.set_duty_cycle_fraction(duty as u16, 1000);
+                            channel.enable();
+                            reply_ok(&mut resp_buf, id_str, "done");
+                        } else {
+                            reply_error(&mut resp_buf, id_str, "Pin has no PWM output");
+                        }
+                    } else if has_cmd(&line_buf, b"adc_read") {
+                        let value = match parse_arg(&line_buf, b"channel") {
+                            Some(0) => Some(adc.blocking_read(&mut adc_a0)),
+                            Some(1) => Some(adc.blocking_read(&mut adc_a1)),
+                            Some(2) => Some(adc.blocking_read(&mut adc_a2)),
+                            _ => None,
+                        };
+                        match value {
+                            Some(v) => {
+                                let mut result: String<8> = String::new();
+                                let _ = write!(result, "{}", v);
+                                reply_ok(&mut resp_buf, id_str, &result);
+                            }
+                            None => reply_error(&mut resp_buf, id_str, "Invalid ADC channel"),
+                        }
                     } else {
                         let _ = write!(
                             resp_buf,
@@ -35,6 +35,7 @@ pub struct Agent {
     history: Vec<ConversationMessage>,
     classification_config: crate::config::QueryClassificationConfig,
     available_hints: Vec<String>,
+    hooks: Option<Arc<crate::hooks::HookRunner>>,
 }
 
 pub struct AgentBuilder {
@@ -55,6 +56,7 @@ pub struct AgentBuilder {
     auto_save: Option<bool>,
     classification_config: Option<crate::config::QueryClassificationConfig>,
     available_hints: Option<Vec<String>>,
+    hooks: Option<Arc<crate::hooks::HookRunner>>,
 }
 
 impl AgentBuilder {
@@ -77,6 +79,7 @@ impl AgentBuilder {
             auto_save: None,
             classification_config: None,
             available_hints: None,
+            hooks: None,
         }
     }
 
@@ -171,6 +174,11 @@ impl AgentBuilder {
         self
     }
 
+    pub fn hooks(mut self, hooks: Option<Arc<crate::hooks::HookRunner>>) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
     pub fn build(self) -> Result<Agent> {
         let tools = self
             .tools
@@ -213,6 +221,7 @@ impl AgentBuilder {
             history: Vec::new(),
             classification_config: self.classification_config.unwrap_or_default(),
             available_hints: self.available_hints.unwrap_or_default(),
+            hooks: self.hooks,
         })
     }
 }
@@ -259,6 +268,7 @@ impl Agent {
             None
         };
 
+        let hooks = crate::hooks::HookRunner::from_config(config)?.map(Arc::new);
         let tools = tools::all_tools_with_runtime(
             Arc::new(config.clone()),
             &security,
@@ -272,6 +282,7 @@ impl Agent {
             &config.agents,
             config.api_key.as_deref(),
             config,
+            hooks.clone(),
         );
 
         let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
@@ -326,6 +337,7 @@ impl Agent {
             ))
             .skills_prompt_mode(config.skills.prompt_injection_mode)
             .auto_save(config.memory.auto_save)
+            .hooks(hooks)
             .build()
     }
 
@@ -373,14 +385,46 @@ impl Agent {
     async fn execute_tool_call(&self, call: &ParsedToolCall) -> ToolExecutionResult {
         let start = Instant::now();
 
-        let result = if let Some(tool) = self.tools.iter().find(|t| t.name() == call.name) {
-            match tool.execute(call.arguments.clone()).await {
+        let mut tool_name = call.name.clone();
+        let mut tool_args = call.arguments.clone();
+        if let Some(hooks) = &self.hooks {
+            let context = crate::hooks::ToolCallContext {
+                channel: "cli".to_string(),
+                sender: None,
+            };
+            match hooks
+                .run_before_tool_call_in_context(&context, tool_name, tool_args)
+                .await
+            {
+                crate::hooks::HookResult::Cancel(reason) => {
+                    tracing::info!(tool = %call.name, %reason, "tool call cancelled by hook");
+                    return ToolExecutionResult {
+                        name: call.name.clone(),
+                        output: format!("Cancelled by hook: {reason}"),
+                        success: false,
+                        tool_call_id: call.tool_call_id.clone(),
+                    };
+                }
+                crate::hooks::HookResult::Continue((name, args)) => {
+                    tool_name = name;
+                    tool_args = args;
+                }
+            }
+        }
+
+        let result = if let Some(tool) = self.tools.iter().find(|t| t.name() == tool_name) {
+            match tool.execute(tool_args).await {
                 Ok(r) => {
                     self.observer.record_event(&ObserverEvent::ToolCall {
                         tool: call.name.clone(),
                         duration: start.elapsed(),
                         success: r.success,
                     });
+                    if let Some(hooks) = &self.hooks {
+                        hooks
+                            .fire_after_tool_call(&tool_name, &r, start.elapsed())
+                            .await;
+                    }
                     if r.success {
                         r.output
                     } else {
@@ -764,4 +808,77 @@ mod tests {
             .iter()
             .any(|msg| matches!(msg, ConversationMessage::ToolResults(_))));
     }
+
+    struct DenyEchoHook;
+
+    #[async_trait]
+    impl crate::hooks::HookHandler for DenyEchoHook {
+        fn name(&self) -> &str {
+            "deny-echo"
+        }
+
+        async fn before_tool_call(
+            &self,
+            name: String,
+            args: serde_json::Value,
+        ) -> crate::hooks::HookResult<(String, serde_json::Value)> {
+            if name == "echo" {
+                crate::hooks::HookResult::Cancel("echo is denied".into())
+            } else {
+                crate::hooks::HookResult::Continue((name, args))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_calls_go_through_before_tool_call_hooks() {
+        let provider = Box::new(MockProvider {
+            responses: Mutex::new(vec![crate::providers::ChatResponse {
+                text: Some(String::new()),
+                tool_calls: vec![crate::providers::ToolCall {
+                    id: "tc1".into(),
+                    name: "echo".into(),
+                    arguments: "{}".into(),
+                }],
+                usage: None,
+            }]),
+        });
+
+        let memory_cfg = crate::config::MemoryConfig {
+            backend: "none".into(),
+            ..crate::config::MemoryConfig::default()
+        };
+        let mem: Arc<dyn Memory> = Arc::from(
+            crate::memory::create_memory(&memory_cfg, std::path::Path::new("/tmp"), None)
+                .expect("memory creation should succeed with valid config"),
+        );
+
+        let mut hooks = crate::hooks::HookRunner::new();
+        hooks.register(Box::new(DenyEchoHook));
+
+        let observer: Arc<dyn Observer> = Arc::from(crate::observability::NoopObserver {});
+        let mut agent = Agent::builder()
+            .provider(provider)
+            .tools(vec![Box::new(MockTool)])
+            .memory(mem)
+            .observer(observer)
+            .tool_dispatcher(Box::new(NativeToolDispatcher))
+            .workspace_dir(std::path::PathBuf::from("/tmp"))
+            .hooks(Some(Arc::new(hooks)))
+            .build()
+            .expect("agent builder should succeed with valid config");
+
+        agent.turn("hi").await.unwrap();
+        let outputs: Vec<&str> = agent
+            .history()
+            .iter()
+            .filter_map(|msg| match msg {
+                ConversationMessage::ToolResults(results) => Some(results),
+                _ => None,
+            })
+            .flatten()
+            .map(|result| result.content.as_str())
+            .collect();
+        assert_eq!(outputs, vec!["Cancelled by hook: echo is denied"]);
+    }
 }
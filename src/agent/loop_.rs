@@ -145,7 +145,7 @@ fn autosave_memory_key(prefix: &str) -> String {
 
 /// Trim conversation history to prevent unbounded growth.
 /// Preserves the system prompt (first message if role=system) and the most recent messages.
-fn trim_history(history: &mut Vec<ChatMessage>, max_history: usize) {
+pub(crate) fn trim_history(history: &mut Vec<ChatMessage>, max_history: usize) {
     // Nothing to trim if within limit
     let has_system = history.first().map_or(false, |m| m.role == "system");
     let non_system_count = if has_system {
@@ -1767,43 +1767,6 @@ pub(crate) fn is_tool_loop_cancelled(err: &anyhow::Error) -> bool {
     err.chain().any(|source| source.is::<ToolLoopCancelled>())
 }
 
-/// Execute a single turn of the agent loop: send messages, parse tool calls,
-/// execute tools, and loop until the LLM produces a final text response.
-/// When `silent` is true, suppresses stdout (for channel use).
-#[allow(clippy::too_many_arguments)]
-pub(crate) async fn agent_turn(
-    provider: &dyn Provider,
-    history: &mut Vec<ChatMessage>,
-    tools_registry: &[Box<dyn Tool>],
-    observer: &dyn Observer,
-    provider_name: &str,
-    model: &str,
-    temperature: f64,
-    silent: bool,
-    multimodal_config: &crate::config::MultimodalConfig,
-    max_tool_iterations: usize,
-) -> Result<String> {
-    run_tool_call_loop(
-        provider,
-        history,
-        tools_registry,
-        observer,
-        provider_name,
-        model,
-        temperature,
-        silent,
-        None,
-        "channel",
-        multimodal_config,
-        max_tool_iterations,
-        None,
-        None,
-        None,
-        &[],
-    )
-    .await
-}
-
 async fn execute_one_tool(
     call_name: &str,
     call_arguments: serde_json::Value,
@@ -1984,6 +1947,7 @@ pub(crate) async fn run_tool_call_loop(
     silent: bool,
     approval: Option<&ApprovalManager>,
     channel_name: &str,
+    sender: Option<&str>,
     multimodal_config: &crate::config::MultimodalConfig,
     max_tool_iterations: usize,
     cancellation_token: Option<CancellationToken>,
@@ -2303,8 +2267,12 @@ pub(crate) async fn run_tool_call_loop(
             let mut tool_name = call.name.clone();
             let mut tool_args = call.arguments.clone();
             if let Some(hooks) = hooks {
+                let context = crate::hooks::ToolCallContext {
+                    channel: channel_name.to_string(),
+                    sender: sender.map(str::to_string),
+                };
                 match hooks
-                    .run_before_tool_call(tool_name.clone(), tool_args.clone())
+                    .run_before_tool_call_in_context(&context, tool_name.clone(), tool_args.clone())
                     .await
                 {
                     crate::hooks::HookResult::Cancel(reason) => {
@@ -2668,6 +2636,9 @@ pub async fn run(
     } else {
         (None, None)
     };
+    // ── Hooks (lifecycle + tool rules) ───────────────────────────
+    let hooks = crate::hooks::HookRunner::from_config(&config)?.map(Arc::new);
+
     let mut tools_registry = tools::all_tools_with_runtime(
         Arc::new(config.clone()),
         &security,
@@ -2681,6 +2652,7 @@ pub async fn run(
         &config.agents,
         config.api_key.as_deref(),
         &config,
+        hooks.clone(),
     );
 
     let peripheral_tools: Vec<Box<dyn Tool>> =
@@ -2928,11 +2900,12 @@ pub async fn run(
             false,
             approval_manager.as_ref(),
             channel_name,
+            None,
             &config.multimodal,
             config.agent.max_tool_iterations,
             None,
             None,
-            None,
+            hooks.as_deref(),
             &[],
         )
         .await?;
@@ -3049,11 +3022,12 @@ pub async fn run(
                 false,
                 approval_manager.as_ref(),
                 channel_name,
+                None,
                 &config.multimodal,
                 config.agent.max_tool_iterations,
                 None,
                 None,
-                None,
+                hooks.as_deref(),
                 &[],
             )
             .await
@@ -3109,6 +3083,23 @@ pub async fn run(
 /// Process a single message through the full agent (with tools, peripherals, memory).
 /// Used by channels (Telegram, Discord, etc.) to enable hardware and tool use.
 pub async fn process_message(config: Config, message: &str) -> Result<String> {
+    let memory_dir = config.workspace_dir.clone();
+    Box::pin(process_message_with_memory_dir(
+        config,
+        message,
+        &memory_dir,
+    ))
+    .await
+}
+
+/// Like [`process_message`], but memory (context recall and the memory tools)
+/// lives under `memory_dir` instead of the workspace. The multitenant server
+/// uses this to give each user a private memory inside a shared tenant workspace.
+pub async fn process_message_with_memory_dir(
+    config: Config,
+    message: &str,
+    memory_dir: &std::path::Path,
+) -> Result<String> {
     let observer: Arc<dyn Observer> =
         Arc::from(observability::create_observer(&config.observability));
     let runtime: Arc<dyn runtime::RuntimeAdapter> =
@@ -3120,7 +3111,7 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory_with_storage(
         &config.memory,
         Some(&config.storage.provider.config),
-        &config.workspace_dir,
+        memory_dir,
         config.api_key.as_deref(),
     )?);
 
@@ -3132,6 +3123,8 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
     } else {
         (None, None)
     };
+    // Tool rules and, for tenant users, the capability ceiling.
+    let hooks = crate::hooks::HookRunner::from_config(&config)?.map(Arc::new);
     let mut tools_registry = tools::all_tools_with_runtime(
         Arc::new(config.clone()),
         &security,
@@ -3145,6 +3138,7 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         &config.agents,
         config.api_key.as_deref(),
         &config,
+        hooks.clone(),
     );
     let peripheral_tools: Vec<Box<dyn Tool>> =
         crate::peripherals::create_peripheral_tools(&config.peripherals).await?;
@@ -3272,7 +3266,9 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         ChatMessage::user(&enriched),
     ];
 
-    agent_turn(
+    let sender = config.tenant_context.as_ref().map(|c| c.user_id.as_str());
+
+    let response = run_tool_call_loop(
         provider.as_ref(),
         &mut history,
         &tools_registry,
@@ -3281,10 +3277,19 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         &model_name,
         config.default_temperature,
         true,
+        None,
+        "channel",
+        sender,
         &config.multimodal,
         config.agent.max_tool_iterations,
+        None,
+        None,
+        hooks.as_deref(),
+        &[],
     )
-    .await
+    .await?;
+    observer.record_event(&ObserverEvent::TurnComplete);
+    Ok(response)
 }
 
 #[cfg(test)]
@@ -3588,6 +3593,7 @@ mod tests {
             true,
             None,
             "cli",
+            None,
             &crate::config::MultimodalConfig::default(),
             3,
             None,
@@ -3634,6 +3640,7 @@ mod tests {
             true,
             None,
             "cli",
+            None,
             &multimodal,
             3,
             None,
@@ -3674,6 +3681,7 @@ mod tests {
             true,
             None,
             "cli",
+            None,
             &crate::config::MultimodalConfig::default(),
             3,
             None,
@@ -3800,6 +3808,7 @@ mod tests {
             true,
             Some(&approval_mgr),
             "telegram",
+            None,
             &crate::config::MultimodalConfig::default(),
             4,
             None,
@@ -3869,6 +3878,7 @@ mod tests {
             true,
             None,
             "cli",
+            None,
             &crate::config::MultimodalConfig::default(),
             4,
             None,
@@ -3925,6 +3935,7 @@ mod tests {
             true,
             None,
             "cli",
+            None,
             &crate::config::MultimodalConfig::default(),
             4,
             None,
@@ -352,6 +352,7 @@ mod tests {
                 args: std::collections::HashMap::new(),
             }],
             prompts: vec!["Run smoke tests before deploy.".into()],
+            policy: crate::skills::SkillPolicy::default(),
             location: None,
         }];
 
@@ -390,6 +391,7 @@ mod tests {
                 args: std::collections::HashMap::new(),
             }],
             prompts: vec!["Run smoke tests before deploy.".into()],
+            policy: crate::skills::SkillPolicy::default(),
             location: Some(Path::new("/tmp/workspace/skills/deploy/SKILL.md").to_path_buf()),
         }];
 
@@ -450,6 +452,7 @@ mod tests {
                 args: std::collections::HashMap::new(),
             }],
             prompts: vec!["Use <tool_call> and & keep output \"safe\"".into()],
+            policy: crate::skills::SkillPolicy::default(),
             location: None,
         }];
         let ctx = PromptContext {
@@ -157,7 +157,7 @@ impl ApprovalManager {
 // ── CLI prompt ───────────────────────────────────────────────────
 
 /// Display the approval prompt and read user input from stdin.
-fn prompt_cli_interactive(request: &ApprovalRequest) -> ApprovalResponse {
+pub(crate) fn prompt_cli_interactive(request: &ApprovalRequest) -> ApprovalResponse {
     let summary = summarize_args(&request.arguments);
     eprintln!();
     eprintln!("🔧 Agent wants to execute: {}", request.tool_name);
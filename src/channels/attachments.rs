@@ -0,0 +1,1086 @@
+//! Typed file attachments shared by every channel.
+//!
+//! Channels put received files on [`ChannelMessage::attachments`] and read
+//! outgoing files from [`SendMessage::attachments`] instead of inventing their
+//! own text conventions. Before a message reaches the agent, the runtime stores
+//! its attachments in the workspace inbox through [`AttachmentInbox`] and
+//! renders them into the message text, so `file_read`, `pdf_read` and the
+//! multimodal pipeline all see the same workspace paths.
+//!
+//! Replies may still reference files with `[IMAGE:<path-or-url>]`-style markers;
+//! [`collect_outgoing`] merges those with the typed attachments. Marker paths
+//! come from the model, so they are only honoured inside the workspace (or an
+//! allowed root) and outside `forbidden_paths`; see [`set_outgoing_file_policy`].
+//!
+//! [`ChannelMessage::attachments`]: super::traits::ChannelMessage::attachments
+//! [`SendMessage::attachments`]: super::traits::SendMessage::attachments
+
+use super::traits::ChannelMessage;
+use crate::config::ChannelAttachmentsConfig;
+use crate::security::SecurityPolicy;
+use anyhow::{bail, Context, Result};
+use parking_lot::RwLock;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Default per-attachment size limit (20 MB, Telegram's bot download cap).
+pub const DEFAULT_MAX_ATTACHMENT_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Default inbox directory, relative to the workspace.
+pub const DEFAULT_INBOX_DIR: &str = "inbox";
+
+/// Policy deciding which local files replies may attach. `None` denies all.
+static OUTGOING_FILE_POLICY: RwLock<Option<Arc<SecurityPolicy>>> = RwLock::new(None);
+
+/// Install the security policy that gates local files referenced by outgoing
+/// markers and path-only replies. Called once by the channel runtime and the
+/// gateway at startup.
+pub fn set_outgoing_file_policy(policy: Arc<SecurityPolicy>) {
+    *OUTGOING_FILE_POLICY.write() = Some(policy);
+}
+
+/// Resolve a model-supplied local path against the workspace and canonicalize
+/// it. The result must stay inside the workspace or an allowed root and must
+/// not fall under `forbidden_paths`.
+fn resolve_outgoing_path(path: &Path) -> Option<PathBuf> {
+    let policy = OUTGOING_FILE_POLICY.read().clone()?;
+    let resolved = policy.workspace_dir.join(path).canonicalize().ok()?;
+    (policy.is_resolved_path_allowed(&resolved) && !policy.is_resolved_path_forbidden(&resolved))
+        .then_some(resolved)
+}
+
+/// Broad media category of an attachment; decides how channels deliver it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentKind {
+    Image,
+    Document,
+    Video,
+    Audio,
+    Voice,
+}
+
+impl AttachmentKind {
+    /// Parse the marker keyword of `[KIND:target]` (case-insensitive).
+    pub fn from_marker(marker: &str) -> Option<Self> {
+        match marker.trim().to_ascii_uppercase().as_str() {
+            "IMAGE" | "PHOTO" => Some(Self::Image),
+            "DOCUMENT" | "FILE" => Some(Self::Document),
+            "VIDEO" => Some(Self::Video),
+            "AUDIO" => Some(Self::Audio),
+            "VOICE" => Some(Self::Voice),
+            _ => None,
+        }
+    }
+
+    /// Infer the kind from a MIME type. Unknown types are documents.
+    pub fn from_mime(mime: &str) -> Self {
+        let mime = mime.to_ascii_lowercase();
+        if mime.starts_with("image/") {
+            Self::Image
+        } else if mime.starts_with("video/") {
+            Self::Video
+        } else if mime.starts_with("audio/") {
+            Self::Audio
+        } else {
+            Self::Document
+        }
+    }
+
+    /// Infer the kind from the file extension of a path or URL.
+    pub fn from_target(target: &str) -> Option<Self> {
+        let extension = Path::new(strip_query(target))
+            .extension()
+            .and_then(|ext| ext.to_str())?
+            .to_ascii_lowercase();
+
+        match extension.as_str() {
+            "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" => Some(Self::Image),
+            "mp4" | "mov" | "mkv" | "avi" | "webm" => Some(Self::Video),
+            "mp3" | "m4a" | "wav" | "flac" => Some(Self::Audio),
+            "ogg" | "oga" | "opus" => Some(Self::Voice),
+            "pdf" | "txt" | "md" | "csv" | "json" | "zip" | "tar" | "gz" | "doc" | "docx"
+            | "xls" | "xlsx" | "ppt" | "pptx" => Some(Self::Document),
+            _ => None,
+        }
+    }
+
+    /// Marker keyword, e.g. `IMAGE` in `[IMAGE:/tmp/a.png]`.
+    pub fn marker(self) -> &'static str {
+        match self {
+            Self::Image => "IMAGE",
+            Self::Document => "DOCUMENT",
+            Self::Video => "VIDEO",
+            Self::Audio => "AUDIO",
+            Self::Voice => "VOICE",
+        }
+    }
+
+    /// Human-readable label, e.g. `Document`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Image => "Image",
+            Self::Document => "Document",
+            Self::Video => "Video",
+            Self::Audio => "Audio",
+            Self::Voice => "Voice",
+        }
+    }
+}
+
+/// Where the attachment's content lives.
+#[derive(Clone, PartialEq, Eq)]
+pub enum AttachmentSource {
+    /// In-memory content (e.g. downloaded by the channel with its own credentials).
+    Bytes(Vec<u8>),
+    /// A file on the local filesystem.
+    Path(PathBuf),
+    /// A publicly fetchable `http(s)` URL.
+    Url(String),
+}
+
+impl std::fmt::Debug for AttachmentSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bytes(bytes) => write!(f, "Bytes({} bytes)", bytes.len()),
+            Self::Path(path) => f.debug_tuple("Path").field(path).finish(),
+            Self::Url(url) => f.debug_tuple("Url").field(url).finish(),
+        }
+    }
+}
+
+/// A file attached to an incoming or outgoing channel message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub kind: AttachmentKind,
+    pub mime: String,
+    pub name: String,
+    /// Size in bytes, when known.
+    pub size: Option<u64>,
+    /// Playback length of audio and video, when the platform reports it.
+    pub duration_secs: Option<u64>,
+    pub source: AttachmentSource,
+}
+
+impl Attachment {
+    /// In-memory attachment. MIME type and kind are guessed from `name`.
+    pub fn from_bytes(name: impl Into<String>, bytes: Vec<u8>) -> Self {
+        let name = name.into();
+        Self {
+            kind: infer_kind(&name),
+            mime: guess_mime(&name),
+            size: Some(bytes.len() as u64),
+            duration_secs: None,
+            name,
+            source: AttachmentSource::Bytes(bytes),
+        }
+    }
+
+    /// Local file attachment. The size is read from the filesystem if the file exists.
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("file")
+            .to_string();
+        Self {
+            kind: infer_kind(&name),
+            mime: guess_mime(&name),
+            size: std::fs::metadata(&path).ok().map(|m| m.len()),
+            duration_secs: None,
+            name,
+            source: AttachmentSource::Path(path),
+        }
+    }
+
+    /// Remote attachment. The name is the last URL path segment.
+    pub fn from_url(url: impl Into<String>) -> Self {
+        let url = url.into();
+        let name = strip_query(&url)
+            .rsplit('/')
+            .find(|segment| !segment.is_empty())
+            .filter(|segment| !segment.contains(':'))
+            .unwrap_or("file")
+            .to_string();
+        Self {
+            kind: infer_kind(&name),
+            mime: guess_mime(&name),
+            size: None,
+            duration_secs: None,
+            name,
+            source: AttachmentSource::Url(url),
+        }
+    }
+
+    /// Attachment for a marker target: a URL, a `file://` URI or a local path.
+    pub fn from_target(target: &str) -> Self {
+        let target = target.trim();
+        if is_http_url(target) {
+            Self::from_url(target)
+        } else {
+            Self::from_path(target.strip_prefix("file://").unwrap_or(target))
+        }
+    }
+
+    /// Override the kind (e.g. from an explicit `[VOICE:...]` marker).
+    #[must_use]
+    pub fn with_kind(mut self, kind: AttachmentKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Set the MIME type reported by the platform. The kind is re-inferred
+    /// unless the file name already determined it.
+    #[must_use]
+    pub fn with_mime(mut self, mime: impl Into<String>) -> Self {
+        let mime = mime.into();
+        if mime.trim().is_empty() {
+            return self;
+        }
+        if AttachmentKind::from_target(&self.name).is_none() {
+            self.kind = AttachmentKind::from_mime(&mime);
+        }
+        self.mime = mime;
+        self
+    }
+
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    #[must_use]
+    pub fn with_duration(mut self, secs: u64) -> Self {
+        self.duration_secs = Some(secs);
+        self
+    }
+
+    pub fn local_path(&self) -> Option<&Path> {
+        match &self.source {
+            AttachmentSource::Path(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        match &self.source {
+            AttachmentSource::Url(url) => Some(url),
+            _ => None,
+        }
+    }
+
+    /// Whether the known size exceeds `max_bytes`.
+    pub fn exceeds(&self, max_bytes: u64) -> bool {
+        self.size.is_some_and(|size| size > max_bytes)
+    }
+
+    /// Load the attachment content, refusing anything larger than `max_bytes`.
+    ///
+    /// This is the common upload path: channels call it to get the bytes to
+    /// send regardless of where the attachment lives.
+    pub async fn read_bytes(&self, max_bytes: u64) -> Result<Vec<u8>> {
+        if self.exceeds(max_bytes) {
+            bail!(
+                "attachment '{}' is {} (limit {})",
+                self.name,
+                format_size(self.size.unwrap_or_default()),
+                format_size(max_bytes)
+            );
+        }
+        match &self.source {
+            AttachmentSource::Bytes(bytes) => Ok(bytes.clone()),
+            AttachmentSource::Path(path) => {
+                let len = tokio::fs::metadata(path)
+                    .await
+                    .with_context(|| format!("attachment not found: {}", path.display()))?
+                    .len();
+                if len > max_bytes {
+                    bail!(
+                        "attachment '{}' is {} (limit {})",
+                        self.name,
+                        format_size(len),
+                        format_size(max_bytes)
+                    );
+                }
+                tokio::fs::read(path)
+                    .await
+                    .with_context(|| format!("failed to read {}", path.display()))
+            }
+            AttachmentSource::Url(url) => download_public(url, max_bytes).await,
+        }
+    }
+
+    /// `[KIND:target]` marker for path and URL attachments.
+    pub fn marker(&self) -> Option<String> {
+        let target = match &self.source {
+            AttachmentSource::Path(path) => path.display().to_string(),
+            AttachmentSource::Url(url) => url.clone(),
+            AttachmentSource::Bytes(_) => return None,
+        };
+        Some(format!("[{}:{target}]", self.kind.marker()))
+    }
+}
+
+/// Fetch `url`, aborting once the body grows past `max_bytes`.
+/// [`download`] for URLs nobody vouched for, such as reply markers: the host
+/// must not be or resolve to a loopback, link-local or private address, the
+/// connection is pinned to the checked addresses and redirects are refused.
+pub async fn download_public(url: &str, max_bytes: u64) -> Result<Vec<u8>> {
+    let parsed =
+        reqwest::Url::parse(url).with_context(|| format!("invalid attachment URL {url}"))?;
+    let builder = crate::config::apply_runtime_proxy_to_builder(
+        reqwest::Client::builder(),
+        "channel.attachments",
+    );
+    let client = crate::tools::http_request::public_client(&parsed, builder)
+        .await
+        .with_context(|| format!("refusing to fetch attachment {url}"))?;
+    download(&client, url, max_bytes).await
+}
+
+pub async fn download(client: &reqwest::Client, url: &str, max_bytes: u64) -> Result<Vec<u8>> {
+    let mut resp = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("failed to fetch attachment {url}"))?;
+    if !resp.status().is_success() {
+        bail!("attachment download failed ({}): {url}", resp.status());
+    }
+    if let Some(len) = resp.content_length() {
+        if len > max_bytes {
+            bail!(
+                "attachment at {url} is {} (limit {})",
+                format_size(len),
+                format_size(max_bytes)
+            );
+        }
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        if (body.len() + chunk.len()) as u64 > max_bytes {
+            bail!(
+                "attachment at {url} exceeds the {} limit",
+                format_size(max_bytes)
+            );
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Workspace directory where incoming attachments are stored.
+pub struct AttachmentInbox {
+    workspace_dir: PathBuf,
+    dir: PathBuf,
+    max_bytes: u64,
+    client: reqwest::Client,
+    /// Fetch URLs on local and private hosts too (test servers listen on loopback).
+    allow_private_hosts: bool,
+}
+
+impl AttachmentInbox {
+    pub fn new(workspace_dir: &Path, config: &ChannelAttachmentsConfig) -> Self {
+        let dir = Path::new(&config.inbox_dir);
+        let dir = if dir.is_absolute() {
+            dir.to_path_buf()
+        } else {
+            workspace_dir.join(dir)
+        };
+        Self {
+            workspace_dir: workspace_dir.to_path_buf(),
+            dir,
+            max_bytes: config.max_bytes,
+            client: crate::config::build_runtime_proxy_client("channel.attachments"),
+            allow_private_hosts: false,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// Store an attachment under `{inbox}/{channel}/` and return it as a local
+    /// file attachment. Files already inside the workspace are left in place.
+    pub async fn store(&self, channel: &str, attachment: &Attachment) -> Result<Attachment> {
+        if let Some(path) = attachment.local_path() {
+            let canonical = tokio::fs::canonicalize(path)
+                .await
+                .with_context(|| format!("attachment not found: {}", path.display()))?;
+            let workspace = tokio::fs::canonicalize(&self.workspace_dir)
+                .await
+                .unwrap_or_else(|_| self.workspace_dir.clone());
+            if canonical.starts_with(&workspace) {
+                let len = tokio::fs::metadata(&canonical).await?.len();
+                if len > self.max_bytes {
+                    bail!(
+                        "attachment '{}' is {} (limit {})",
+                        attachment.name,
+                        format_size(len),
+                        format_size(self.max_bytes)
+                    );
+                }
+                return Ok(Attachment {
+                    size: Some(len),
+                    source: AttachmentSource::Path(canonical),
+                    ..attachment.clone()
+                });
+            }
+        }
+
+        let bytes = match &attachment.source {
+            AttachmentSource::Url(url) => {
+                if attachment.exceeds(self.max_bytes) {
+                    bail!(
+                        "attachment '{}' is {} (limit {})",
+                        attachment.name,
+                        format_size(attachment.size.unwrap_or_default()),
+                        format_size(self.max_bytes)
+                    );
+                }
+                if self.allow_private_hosts {
+                    download(&self.client, url, self.max_bytes).await?
+                } else {
+                    download_public(url, self.max_bytes).await?
+                }
+            }
+            _ => attachment.read_bytes(self.max_bytes).await?,
+        };
+
+        let dir = self.dir.join(sanitize_file_name(channel));
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("failed to create inbox {}", dir.display()))?;
+        let unique = uuid::Uuid::new_v4().simple().to_string();
+        let path = dir.join(format!(
+            "{}-{}",
+            &unique[..8],
+            sanitize_file_name(&attachment.name)
+        ));
+        tokio::fs::write(&path, &bytes)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?;
+
+        Ok(Attachment {
+            size: Some(bytes.len() as u64),
+            source: AttachmentSource::Path(path),
+            ..attachment.clone()
+        })
+    }
+
+    /// Store every attachment of `msg` and append a line per attachment to its
+    /// content. Attachments that cannot be stored are replaced by a short note
+    /// so the agent knows something was sent.
+    pub async fn ingest(&self, msg: &mut ChannelMessage) {
+        if msg.attachments.is_empty() {
+            return;
+        }
+
+        let mut lines = Vec::with_capacity(msg.attachments.len());
+        let mut stored = Vec::with_capacity(msg.attachments.len());
+        for attachment in &msg.attachments {
+            match self.store(&msg.channel, attachment).await {
+                Ok(local) => {
+                    lines.push(self.render(&local));
+                    stored.push(local);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        channel = %msg.channel,
+                        name = %attachment.name,
+                        "failed to store attachment: {e:#}"
+                    );
+                    lines.push(format!(
+                        "[{} not available: {} — {e}]",
+                        attachment.kind.label(),
+                        attachment.name
+                    ));
+                }
+            }
+        }
+
+        let rendered = lines.join("\n");
+        msg.content = if msg.content.trim().is_empty() {
+            rendered
+        } else {
+            format!("{rendered}\n\n{}", msg.content)
+        };
+        msg.attachments = stored;
+    }
+
+    /// Text form of a stored attachment as seen by the agent.
+    ///
+    /// Images use `[IMAGE:<absolute path>]` so the multimodal pipeline picks
+    /// them up; everything else lists a workspace-relative path that the file
+    /// tools accept.
+    pub fn render(&self, attachment: &Attachment) -> String {
+        let Some(path) = attachment.local_path() else {
+            return attachment.marker().unwrap_or_default();
+        };
+        if attachment.kind == AttachmentKind::Image {
+            return format!("[IMAGE:{}]", path.display());
+        }
+
+        let workspace = std::fs::canonicalize(&self.workspace_dir)
+            .unwrap_or_else(|_| self.workspace_dir.clone());
+        let shown = path
+            .strip_prefix(&workspace)
+            .or_else(|_| path.strip_prefix(&self.workspace_dir))
+            .unwrap_or(path);
+
+        let mut line = format!(
+            "[{}: {} ({}",
+            attachment.kind.label(),
+            attachment.name,
+            attachment.mime
+        );
+        if let Some(size) = attachment.size {
+            let _ = write!(line, ", {}", format_size(size));
+        }
+        let _ = write!(line, ")] {}", shown.display());
+        line
+    }
+}
+
+/// Split `[KIND:target]` markers out of `message`.
+///
+/// Returns the remaining text and the referenced attachments. Brackets that
+/// are not valid markers are kept in the text.
+pub fn parse_attachment_markers(message: &str) -> (String, Vec<Attachment>) {
+    let mut cleaned = String::with_capacity(message.len());
+    let mut attachments = Vec::new();
+    let mut cursor = 0;
+
+    while cursor < message.len() {
+        let Some(open_rel) = message[cursor..].find('[') else {
+            cleaned.push_str(&message[cursor..]);
+            break;
+        };
+
+        let open = cursor + open_rel;
+        cleaned.push_str(&message[cursor..open]);
+
+        let Some(close_rel) = message[open..].find(']') else {
+            cleaned.push_str(&message[open..]);
+            break;
+        };
+
+        let close = open + close_rel;
+        let marker = &message[open + 1..close];
+
+        let parsed = marker.split_once(':').and_then(|(kind, target)| {
+            let kind = AttachmentKind::from_marker(kind)?;
+            let target = target.trim();
+            if target.is_empty() {
+                return None;
+            }
+            Some(Attachment::from_target(target).with_kind(kind))
+        });
+
+        if let Some(attachment) = parsed {
+            attachments.push(attachment);
+        } else {
+            cleaned.push_str(&message[open..=close]);
+        }
+
+        cursor = close + 1;
+    }
+
+    (cleaned.trim().to_string(), attachments)
+}
+
+/// Treat a reply that consists only of an existing file path or a media URL
+/// as an attachment.
+pub fn parse_path_only_attachment(message: &str) -> Option<Attachment> {
+    let trimmed = message.trim();
+    if trimmed.is_empty() || trimmed.contains('\n') {
+        return None;
+    }
+
+    let candidate = trimmed.trim_matches(|c| matches!(c, '`' | '"' | '\''));
+    if candidate.chars().any(char::is_whitespace) {
+        return None;
+    }
+
+    let candidate = candidate.strip_prefix("file://").unwrap_or(candidate);
+    let kind = AttachmentKind::from_target(candidate)?;
+
+    if is_http_url(candidate) {
+        return Some(Attachment::from_url(candidate).with_kind(kind));
+    }
+
+    let resolved = resolve_outgoing_path(Path::new(candidate))?;
+    Some(Attachment::from_path(resolved).with_kind(kind))
+}
+
+/// Everything an outgoing message should deliver: the typed attachments
+/// followed by any markers left in `content`.
+///
+/// Marker paths outside the outgoing file policy are dropped; the reply text
+/// gets a note instead so the user knows something was withheld.
+pub fn collect_outgoing(content: &str, typed: &[Attachment]) -> (String, Vec<Attachment>) {
+    let (mut text, markers) = parse_attachment_markers(content);
+    let mut attachments = typed.to_vec();
+    for marker in markers {
+        if let Some(url) = marker.url() {
+            if is_literal_private_url(url) {
+                tracing::warn!("Withholding outgoing attachment on a local/private host: {url}");
+                push_line(
+                    &mut text,
+                    &format!("[attachment withheld: {}]", marker.name),
+                );
+            } else {
+                attachments.push(marker);
+            }
+            continue;
+        }
+        let Some(path) = marker.local_path().map(Path::to_path_buf) else {
+            attachments.push(marker);
+            continue;
+        };
+        if let Some(resolved) = resolve_outgoing_path(&path) {
+            let kind = marker.kind;
+            attachments.push(Attachment::from_path(resolved).with_kind(kind));
+        } else {
+            tracing::warn!(
+                "Withholding outgoing attachment outside the workspace: {}",
+                path.display()
+            );
+            push_line(
+                &mut text,
+                &format!("[attachment withheld: {}]", marker.name),
+            );
+        }
+    }
+    (text, attachments)
+}
+
+/// Whether the URL names a local or private host outright. Hostnames that only
+/// resolve to one are caught by [`download_public`].
+fn is_literal_private_url(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|parsed| parsed.host_str().map(str::to_string))
+        .is_none_or(|host| crate::tools::http_request::is_private_or_local_host(&host))
+}
+
+/// Render the attachments of a reply as text for channels that cannot upload
+/// files: markers become `[Kind: target]` lines, which keeps URLs usable.
+pub fn attachments_as_text(channel: &str, content: &str) -> String {
+    let (mut text, markers) = parse_attachment_markers(content);
+    if markers.is_empty() {
+        return content.to_string();
+    }
+    tracing::info!(
+        "Channel {channel} does not support attachments; sending {} as text",
+        markers.len()
+    );
+    for marker in &markers {
+        let target = match &marker.source {
+            AttachmentSource::Url(url) => url.clone(),
+            _ => marker.name.clone(),
+        };
+        push_line(&mut text, &format!("[{}: {target}]", marker.kind.label()));
+    }
+    text
+}
+
+fn push_line(text: &mut String, line: &str) {
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text.push_str(line);
+}
+
+pub fn is_http_url(target: &str) -> bool {
+    target.starts_with("http://") || target.starts_with("https://")
+}
+
+/// Reduce a file name to a safe single path component.
+pub fn sanitize_file_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let cleaned: String = base
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.');
+    let cleaned: String = cleaned.chars().take(100).collect();
+    if cleaned.is_empty() {
+        "file".to_string()
+    } else {
+        cleaned
+    }
+}
+
+fn infer_kind(name: &str) -> AttachmentKind {
+    AttachmentKind::from_target(name)
+        .unwrap_or_else(|| AttachmentKind::from_mime(&guess_mime(name)))
+}
+
+fn guess_mime(name: &str) -> String {
+    mime_guess::from_path(strip_query(name))
+        .first_or_octet_stream()
+        .essence_str()
+        .to_string()
+}
+
+fn strip_query(target: &str) -> &str {
+    let target = target.split('?').next().unwrap_or(target);
+    target.split('#').next().unwrap_or(target)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn format_size(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = 1024 * KB;
+    if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn message(channel: &str, content: &str, attachments: Vec<Attachment>) -> ChannelMessage {
+        ChannelMessage {
+            id: "1".into(),
+            sender: "alice".into(),
+            reply_target: "alice".into(),
+            content: content.into(),
+            channel: channel.into(),
+            timestamp: 0,
+            thread_ts: None,
+            attachments,
+        }
+    }
+
+    fn inbox(workspace: &Path, max_bytes: u64) -> AttachmentInbox {
+        AttachmentInbox::new(
+            workspace,
+            &ChannelAttachmentsConfig {
+                max_bytes,
+                ..ChannelAttachmentsConfig::default()
+            },
+        )
+    }
+
+    #[test]
+    fn parse_attachment_markers_extracts_multiple_types() {
+        let message = "Here are files [IMAGE:/tmp/a.png] and [DOCUMENT:https://example.com/a.pdf]";
+        let (cleaned, attachments) = parse_attachment_markers(message);
+
+        assert_eq!(cleaned, "Here are files  and");
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments[0].kind, AttachmentKind::Image);
+        assert_eq!(
+            attachments[0].source,
+            AttachmentSource::Path(PathBuf::from("/tmp/a.png"))
+        );
+        assert_eq!(attachments[1].kind, AttachmentKind::Document);
+        assert_eq!(attachments[1].url(), Some("https://example.com/a.pdf"));
+        assert_eq!(attachments[1].name, "a.pdf");
+        assert_eq!(attachments[1].mime, "application/pdf");
+    }
+
+    #[test]
+    fn parse_attachment_markers_keeps_invalid_markers_in_text() {
+        let message = "Report [UNKNOWN:/tmp/a.bin]";
+        let (cleaned, attachments) = parse_attachment_markers(message);
+
+        assert_eq!(cleaned, "Report [UNKNOWN:/tmp/a.bin]");
+        assert!(attachments.is_empty());
+    }
+
+    /// Workspace shared by every test that exercises the outgoing file policy,
+    /// which is process-wide. Files under `secret/` are forbidden.
+    fn outgoing_workspace() -> &'static Path {
+        static WORKSPACE: std::sync::OnceLock<tempfile::TempDir> = std::sync::OnceLock::new();
+        let dir = WORKSPACE.get_or_init(|| {
+            let dir = tempfile::tempdir().unwrap();
+            let root = dir.path().canonicalize().unwrap();
+            std::fs::create_dir_all(root.join("secret")).unwrap();
+            set_outgoing_file_policy(Arc::new(SecurityPolicy {
+                workspace_dir: root.clone(),
+                forbidden_paths: vec![root.join("secret").to_string_lossy().into_owned()],
+                ..SecurityPolicy::default()
+            }));
+            dir
+        });
+        dir.path()
+    }
+
+    #[test]
+    fn parse_path_only_attachment_detects_existing_file() {
+        let workspace = outgoing_workspace().canonicalize().unwrap();
+        let image_path = workspace.join("snap.png");
+        std::fs::write(&image_path, b"fake-png").unwrap();
+
+        let parsed = parse_path_only_attachment(image_path.to_string_lossy().as_ref())
+            .expect("expected attachment");
+
+        assert_eq!(parsed.kind, AttachmentKind::Image);
+        assert_eq!(parsed.local_path(), Some(image_path.as_path()));
+        assert_eq!(parsed.size, Some(8));
+
+        let relative = parse_path_only_attachment("snap.png").expect("relative to workspace");
+        assert_eq!(relative.local_path(), Some(image_path.as_path()));
+    }
+
+    #[test]
+    fn parse_path_only_attachment_ignores_files_outside_the_workspace() {
+        outgoing_workspace();
+        let outside = tempfile::tempdir().unwrap();
+        let image_path = outside.path().join("snap.png");
+        std::fs::write(&image_path, b"fake-png").unwrap();
+
+        assert!(parse_path_only_attachment(image_path.to_string_lossy().as_ref()).is_none());
+    }
+
+    #[test]
+    fn parse_path_only_attachment_rejects_sentence_text() {
+        assert!(parse_path_only_attachment("Screenshot saved to /tmp/snap.png").is_none());
+    }
+
+    #[test]
+    fn kind_from_target_ignores_query_string() {
+        assert_eq!(
+            AttachmentKind::from_target("https://example.com/files/specs.pdf?download=1"),
+            Some(AttachmentKind::Document)
+        );
+    }
+
+    #[test]
+    fn collect_outgoing_puts_typed_attachments_first() {
+        std::fs::write(outgoing_workspace().join("r.ogg"), b"ogg").unwrap();
+        let typed = vec![Attachment::from_bytes("notes.txt", b"hi".to_vec())];
+        let (text, attachments) = collect_outgoing("done [VOICE:r.ogg]", &typed);
+        assert_eq!(text, "done");
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments[0].name, "notes.txt");
+        assert_eq!(attachments[1].kind, AttachmentKind::Voice);
+        assert_eq!(attachments[1].size, Some(3));
+    }
+
+    #[test]
+    fn collect_outgoing_withholds_paths_outside_policy() {
+        let workspace = outgoing_workspace();
+        std::fs::write(workspace.join("secret").join("key.txt"), b"k").unwrap();
+        let secret = workspace.join("secret").join("key.txt");
+
+        let content = format!(
+            "here [DOCUMENT:/etc/shadow] [DOCUMENT:{}] [DOCUMENT:../escape.txt] [IMAGE:https://example.com/a.png]",
+            secret.display()
+        );
+        let (text, attachments) = collect_outgoing(&content, &[]);
+
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].url(), Some("https://example.com/a.png"));
+        assert_eq!(
+            text,
+            "here\n[attachment withheld: shadow]\n[attachment withheld: key.txt]\n[attachment withheld: escape.txt]"
+        );
+    }
+
+    #[tokio::test]
+    async fn url_attachments_on_local_or_private_hosts_are_not_fetched() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"internal".to_vec()))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let (text, attachments) = collect_outgoing(
+            &format!(
+                "[DOCUMENT:{}/secret] [IMAGE:http://169.254.169.254/latest/meta-data] [IMAGE:http://localhost/x.png]",
+                server.uri()
+            ),
+            &[],
+        );
+        assert!(attachments.is_empty());
+        assert_eq!(text.matches("[attachment withheld:").count(), 3, "{text}");
+
+        // Typed attachments skip marker parsing; the fetch itself still refuses.
+        let attachments = [
+            Attachment::from_url(format!("{}/secret", server.uri())),
+            Attachment::from_url("http://169.254.169.254/latest/meta-data"),
+            Attachment::from_url("http://[::1]/x.png"),
+        ];
+        for attachment in &attachments {
+            let err = attachment.read_bytes(1024).await.unwrap_err();
+            assert!(format!("{err:#}").contains("local/private"), "{err:#}");
+        }
+    }
+
+    #[test]
+    fn attachments_as_text_keeps_urls_and_names() {
+        assert_eq!(attachments_as_text("irc", "plain reply"), "plain reply");
+        assert_eq!(
+            attachments_as_text(
+                "irc",
+                "chart [IMAGE:https://example.com/c.png] [DOCUMENT:/workspace/report.pdf]"
+            ),
+            "chart\n[Image: https://example.com/c.png]\n[Document: report.pdf]"
+        );
+    }
+
+    #[test]
+    fn mime_refines_kind_only_without_known_extension() {
+        let blob = Attachment::from_bytes("upload", vec![0; 4]).with_mime("image/jpeg");
+        assert_eq!(blob.kind, AttachmentKind::Image);
+
+        let voice = Attachment::from_bytes("note.ogg", vec![0; 4]).with_mime("audio/ogg");
+        assert_eq!(voice.kind, AttachmentKind::Voice);
+    }
+
+    #[test]
+    fn sanitize_file_name_strips_paths_and_odd_characters() {
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_file_name("my report (1).pdf"), "my_report__1_.pdf");
+        assert_eq!(sanitize_file_name(".hidden"), "hidden");
+        assert_eq!(sanitize_file_name(""), "file");
+    }
+
+    #[tokio::test]
+    async fn ingest_stores_bytes_and_renders_paths() {
+        let workspace = tempfile::tempdir().unwrap();
+        let inbox = inbox(workspace.path(), DEFAULT_MAX_ATTACHMENT_BYTES);
+        let mut msg = message(
+            "telegram",
+            "what is in these?",
+            vec![
+                Attachment::from_bytes("report.pdf", b"%PDF-1.4".to_vec()),
+                Attachment::from_bytes("photo.jpg", vec![0xff, 0xd8, 0xff]),
+            ],
+        );
+
+        inbox.ingest(&mut msg).await;
+
+        assert_eq!(msg.attachments.len(), 2);
+        let pdf = msg.attachments[0].local_path().unwrap();
+        assert!(pdf.starts_with(workspace.path().join("inbox").join("telegram")));
+        assert_eq!(std::fs::read(pdf).unwrap(), b"%PDF-1.4");
+
+        let lines: Vec<&str> = msg.content.lines().collect();
+        assert!(
+            lines[0].starts_with("[Document: report.pdf (application/pdf, 8 B)] inbox/telegram/")
+        );
+        assert!(lines[0].ends_with("-report.pdf"));
+        assert!(lines[1].starts_with("[IMAGE:/"));
+        assert!(msg.content.ends_with("\n\nwhat is in these?"));
+    }
+
+    #[tokio::test]
+    async fn ingest_replaces_oversized_attachments_with_note() {
+        let workspace = tempfile::tempdir().unwrap();
+        let inbox = inbox(workspace.path(), 4);
+        let mut msg = message(
+            "signal",
+            "",
+            vec![Attachment::from_bytes("big.bin", vec![0; 16])],
+        );
+
+        inbox.ingest(&mut msg).await;
+
+        assert!(msg.attachments.is_empty());
+        assert!(msg.content.starts_with("[Document not available: big.bin"));
+        assert!(!inbox.dir().join("signal").exists());
+    }
+
+    #[tokio::test]
+    async fn store_keeps_workspace_files_in_place() {
+        let workspace = tempfile::tempdir().unwrap();
+        let file = workspace.path().join("notes.md");
+        std::fs::write(&file, "# hi").unwrap();
+        let inbox = inbox(workspace.path(), DEFAULT_MAX_ATTACHMENT_BYTES);
+
+        let stored = inbox
+            .store("cli", &Attachment::from_path(&file))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            stored.local_path(),
+            Some(std::fs::canonicalize(&file).unwrap().as_path())
+        );
+        assert!(!inbox.dir().exists());
+    }
+
+    #[tokio::test]
+    async fn store_downloads_urls_with_size_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/files/small.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"hello".to_vec()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/files/large.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![b'x'; 64]))
+            .mount(&server)
+            .await;
+
+        let workspace = tempfile::tempdir().unwrap();
+        let mut inbox = inbox(workspace.path(), 32);
+        let blocked = inbox
+            .store(
+                "discord",
+                &Attachment::from_url(format!("{}/files/small.txt", server.uri())),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{blocked:#}").contains("local/private"),
+            "{blocked:#}"
+        );
+        inbox.allow_private_hosts = true;
+
+        let small = inbox
+            .store(
+                "discord",
+                &Attachment::from_url(format!("{}/files/small.txt", server.uri())),
+            )
+            .await
+            .unwrap();
+        assert_eq!(small.size, Some(5));
+        assert_eq!(
+            std::fs::read(small.local_path().unwrap()).unwrap(),
+            b"hello"
+        );
+
+        let err = inbox
+            .store(
+                "discord",
+                &Attachment::from_url(format!("{}/files/large.txt", server.uri())),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("limit"));
+    }
+}
@@ -1,3 +1,4 @@
+use super::attachments::{collect_outgoing, Attachment, AttachmentSource};
 use super::traits::{Channel, ChannelMessage, SendMessage};
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
@@ -51,18 +52,56 @@ impl DiscordChannel {
         let part = token.split('.').next()?;
         base64_decode(part)
     }
+
+    /// Upload a file to a channel. URL attachments are posted as links, which
+    /// Discord embeds itself.
+    async fn send_attachment(&self, channel_id: &str, file: &Attachment) -> anyhow::Result<()> {
+        let url = format!("https://discord.com/api/v10/channels/{channel_id}/messages");
+        let request = self
+            .http_client()
+            .post(&url)
+            .header("Authorization", format!("Bot {}", self.bot_token));
+
+        let request = if let AttachmentSource::Url(link) = &file.source {
+            request.json(&json!({ "content": link }))
+        } else {
+            let bytes = file.read_bytes(DISCORD_MAX_UPLOAD_BYTES).await?;
+            let part = reqwest::multipart::Part::bytes(bytes)
+                .file_name(file.name.clone())
+                .mime_str(&file.mime)?;
+            let form = reqwest::multipart::Form::new()
+                .text(
+                    "payload_json",
+                    json!({ "attachments": [{ "id": 0, "filename": file.name }] }).to_string(),
+                )
+                .part("files[0]", part);
+            request.multipart(form)
+        };
+
+        let resp = request.send().await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            anyhow::bail!("Discord file upload failed ({status}): {err}");
+        }
+        Ok(())
+    }
 }
 
-/// Process Discord message attachments and return a string to append to the
-/// agent message context.
+/// Process Discord message attachments.
 ///
-/// Only `text/*` MIME types are fetched and inlined. All other types are
-/// silently skipped. Fetch errors are logged as warnings.
+/// `text/*` files are fetched and inlined into the returned string. All other
+/// types are returned as URL [`Attachment`]s for the runtime to store in the
+/// workspace inbox. Fetch errors are logged as warnings.
 async fn process_attachments(
     attachments: &[serde_json::Value],
     client: &reqwest::Client,
-) -> String {
+) -> (String, Vec<Attachment>) {
     let mut parts: Vec<String> = Vec::new();
+    let mut files = Vec::new();
     for att in attachments {
         let ct = att
             .get("content_type")
@@ -91,14 +130,20 @@ async fn process_attachments(
                 }
             }
         } else {
-            tracing::debug!(
-                name,
-                content_type = ct,
-                "discord: skipping unsupported attachment type"
-            );
+            let mut file = Attachment::from_url(url).with_name(name).with_mime(ct);
+            if let Some(size) = att.get("size").and_then(serde_json::Value::as_u64) {
+                file = file.with_size(size);
+            }
+            // Voice messages report their length; used for transcription limits.
+            if let Some(secs) = att.get("duration_secs").and_then(serde_json::Value::as_f64) {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let secs = secs.max(0.0).ceil() as u64;
+                file = file.with_duration(secs);
+            }
+            files.push(file);
         }
     }
-    parts.join("\n---\n")
+    (parts.join("\n---\n"), files)
 }
 
 const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
@@ -107,6 +152,8 @@ const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstu
 ///
 /// Discord rejects longer payloads with `50035 Invalid Form Body`.
 const DISCORD_MAX_MESSAGE_LENGTH: usize = 2000;
+/// Discord's upload limit for bots in servers without boosts.
+const DISCORD_MAX_UPLOAD_BYTES: u64 = 10 * 1024 * 1024;
 const DISCORD_ACK_REACTIONS: &[&str] = &["⚡️", "🦀", "🙌", "💪", "👌", "👀", "👣"];
 
 /// Split a message into chunks that respect Discord's 2000-character limit.
@@ -280,9 +327,22 @@ impl Channel for DiscordChannel {
         "discord"
     }
 
+    fn supports_attachments(&self) -> bool {
+        true
+    }
+
+    fn supports_voice_replies(&self) -> bool {
+        true
+    }
+
     async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
         let content = super::strip_tool_call_tags(&message.content);
-        let chunks = split_message_for_discord(&content);
+        let (content, files) = collect_outgoing(&content, &message.attachments);
+        let chunks = if content.is_empty() && !files.is_empty() {
+            Vec::new()
+        } else {
+            split_message_for_discord(&content)
+        };
 
         for (i, chunk) in chunks.iter().enumerate() {
             let url = format!(
@@ -315,6 +375,10 @@ impl Channel for DiscordChannel {
             }
         }
 
+        for file in &files {
+            self.send_attachment(&message.recipient, file).await?;
+        }
+
         Ok(())
     }
 
@@ -481,20 +545,23 @@ impl Channel for DiscordChannel {
                     }
 
                     let content = d.get("content").and_then(|c| c.as_str()).unwrap_or("");
-                    let Some(clean_content) =
-                        normalize_incoming_content(content, self.mention_only, &bot_user_id)
-                    else {
-                        continue;
-                    };
+                    let atts = d
+                        .get("attachments")
+                        .and_then(|a| a.as_array())
+                        .cloned()
+                        .unwrap_or_default();
+                    // File-only messages have no text; accept them unless a mention is required.
+                    let clean_content =
+                        match normalize_incoming_content(content, self.mention_only, &bot_user_id) {
+                            Some(text) => text,
+                            None if content.is_empty() && !atts.is_empty() && !self.mention_only => {
+                                String::new()
+                            }
+                            None => continue,
+                        };
 
-                    let attachment_text = {
-                        let atts = d
-                            .get("attachments")
-                            .and_then(|a| a.as_array())
-                            .cloned()
-                            .unwrap_or_default();
-                        process_attachments(&atts, &self.http_client()).await
-                    };
+                    let (attachment_text, files) =
+                        process_attachments(&atts, &self.http_client()).await;
                     let final_content = if attachment_text.is_empty() {
                         clean_content
                     } else {
@@ -554,6 +621,7 @@ impl Channel for DiscordChannel {
                             .unwrap_or_default()
                             .as_secs(),
                         thread_ts: None,
+                        attachments: files,
                     };
 
                     if tx.send(channel_msg).await.is_err() {
@@ -1203,19 +1271,29 @@ mod tests {
     #[tokio::test]
     async fn process_attachments_empty_list_returns_empty() {
         let client = reqwest::Client::new();
-        let result = process_attachments(&[], &client).await;
-        assert!(result.is_empty());
+        let (text, files) = process_attachments(&[], &client).await;
+        assert!(text.is_empty());
+        assert!(files.is_empty());
     }
 
     #[tokio::test]
-    async fn process_attachments_skips_unsupported_types() {
+    async fn process_attachments_returns_binary_files_as_attachments() {
         let client = reqwest::Client::new();
         let attachments = vec![serde_json::json!({
             "url": "https://cdn.discordapp.com/attachments/123/456/doc.pdf",
             "filename": "doc.pdf",
-            "content_type": "application/pdf"
+            "content_type": "application/pdf",
+            "size": 2048
         })];
-        let result = process_attachments(&attachments, &client).await;
-        assert!(result.is_empty());
+        let (text, files) = process_attachments(&attachments, &client).await;
+        assert!(text.is_empty());
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "doc.pdf");
+        assert_eq!(files[0].mime, "application/pdf");
+        assert_eq!(files[0].size, Some(2048));
+        assert_eq!(
+            files[0].url(),
+            Some("https://cdn.discordapp.com/attachments/123/456/doc.pdf")
+        );
     }
 }
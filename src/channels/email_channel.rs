@@ -14,7 +14,8 @@ use async_imap::types::Fetch;
 use async_imap::Session;
 use async_trait::async_trait;
 use futures_util::TryStreamExt;
-use lettre::message::SinglePart;
+use lettre::message::header::ContentType;
+use lettre::message::{MultiPart, SinglePart};
 use lettre::transport::smtp::authentication::Credentials;
 use lettre::{Message, SmtpTransport, Transport};
 use mail_parser::{MessageParser, MimeHeaders};
@@ -33,8 +34,12 @@ use tokio_rustls::TlsConnector;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use super::attachments::{collect_outgoing, Attachment};
 use super::traits::{Channel, ChannelMessage, SendMessage};
 
+/// Largest file attached to an outgoing email (most SMTP relays cap messages at 25 MB).
+const EMAIL_MAX_ATTACHMENT_BYTES: u64 = 20 * 1024 * 1024;
+
 /// Email channel configuration
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EmailConfig {
@@ -205,6 +210,25 @@ impl EmailChannel {
         "(no readable content)".to_string()
     }
 
+    /// Collect file attachments of a parsed email.
+    fn extract_attachments(parsed: &mail_parser::Message) -> Vec<Attachment> {
+        parsed
+            .attachments()
+            .map(|part| {
+                let name = MimeHeaders::attachment_name(part).unwrap_or("file");
+                let mut file = Attachment::from_bytes(name, part.contents().to_vec());
+                if let Some(ct) = MimeHeaders::content_type(part) {
+                    let mime = match ct.subtype() {
+                        Some(subtype) => format!("{}/{}", ct.ctype(), subtype),
+                        None => ct.ctype().to_string(),
+                    };
+                    file = file.with_mime(mime);
+                }
+                file
+            })
+            .collect()
+    }
+
     /// Connect to IMAP server with TLS and authenticate
     async fn connect_imap(&self) -> Result<ImapSession> {
         let addr = format!("{}:{}", self.config.imap_host, self.config.imap_port);
@@ -265,6 +289,7 @@ impl EmailChannel {
                     let sender = Self::extract_sender(&parsed);
                     let subject = parsed.subject().unwrap_or("(no subject)").to_string();
                     let body_text = Self::extract_text(&parsed);
+                    let attachments = Self::extract_attachments(&parsed);
                     let content = format!("Subject: {}\n\n{}", subject, body_text);
                     let msg_id = parsed
                         .message_id()
@@ -302,6 +327,7 @@ impl EmailChannel {
                         sender,
                         content,
                         timestamp: ts,
+                        attachments,
                     });
                 }
             }
@@ -460,6 +486,7 @@ impl EmailChannel {
                 channel: "email".to_string(),
                 timestamp: email.timestamp,
                 thread_ts: None,
+                attachments: email.attachments,
             };
 
             if tx.send(msg).await.is_err() {
@@ -495,6 +522,7 @@ struct ParsedEmail {
     sender: String,
     content: String,
     timestamp: u64,
+    attachments: Vec<Attachment>,
 }
 
 /// Result from waiting on IDLE
@@ -510,25 +538,44 @@ impl Channel for EmailChannel {
         "email"
     }
 
+    fn supports_attachments(&self) -> bool {
+        true
+    }
+
     async fn send(&self, message: &SendMessage) -> Result<()> {
+        let (content, files) = collect_outgoing(&message.content, &message.attachments);
+
         // Use explicit subject if provided, otherwise fall back to legacy parsing or default
         let (subject, body) = if let Some(ref subj) = message.subject {
-            (subj.as_str(), message.content.as_str())
-        } else if message.content.starts_with("Subject: ") {
-            if let Some(pos) = message.content.find('\n') {
-                (&message.content[9..pos], message.content[pos + 1..].trim())
+            (subj.as_str(), content.as_str())
+        } else if content.starts_with("Subject: ") {
+            if let Some(pos) = content.find('\n') {
+                (&content[9..pos], content[pos + 1..].trim())
             } else {
-                ("ZeroClaw Message", message.content.as_str())
+                ("ZeroClaw Message", content.as_str())
             }
         } else {
-            ("ZeroClaw Message", message.content.as_str())
+            ("ZeroClaw Message", content.as_str())
         };
 
-        let email = Message::builder()
+        let builder = Message::builder()
             .from(self.config.from_address.parse()?)
             .to(message.recipient.parse()?)
-            .subject(subject)
-            .singlepart(SinglePart::plain(body.to_string()))?;
+            .subject(subject);
+        let email = if files.is_empty() {
+            builder.singlepart(SinglePart::plain(body.to_string()))?
+        } else {
+            let mut parts = MultiPart::mixed().singlepart(SinglePart::plain(body.to_string()));
+            for file in &files {
+                let bytes = file.read_bytes(EMAIL_MAX_ATTACHMENT_BYTES).await?;
+                let content_type = ContentType::parse(&file.mime)
+                    .or_else(|_| ContentType::parse("application/octet-stream"))?;
+                parts = parts.singlepart(
+                    lettre::message::Attachment::new(file.name.clone()).body(bytes, content_type),
+                );
+            }
+            builder.multipart(parts)?
+        };
 
         let transport = self.create_smtp_transport()?;
         transport.send(&email)?;
@@ -0,0 +1,142 @@
+use super::traits::{Channel, ChannelMessage, SendMessage};
+use crate::config::{Config, HardwareEventAction};
+use crate::cron::{Schedule, SessionTarget};
+use crate::peripherals::events::{self, HardwareEvent};
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Hardware channel — pin and sensor events from `[[peripherals.subscriptions]]`.
+///
+/// Events with `action = "channel"` arrive as messages from the board, with
+/// the subscription name as reply target. Events with `action = "agent"`
+/// start a one-shot isolated agent job through the cron scheduler instead.
+pub struct HardwareChannel {
+    config: Config,
+}
+
+impl HardwareChannel {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+
+    /// Whether any hardware event subscriptions are configured.
+    pub fn is_configured(config: &Config) -> bool {
+        config.peripherals.enabled && !config.peripherals.subscriptions.is_empty()
+    }
+
+    fn start_agent_job(&self, event: &HardwareEvent) -> anyhow::Result<()> {
+        let job = crate::cron::add_agent_job(
+            &self.config,
+            Some(format!("hardware:{}", event.subscription.name)),
+            Schedule::At {
+                at: chrono::Utc::now() + chrono::Duration::seconds(1),
+            },
+            &event.message,
+            SessionTarget::Isolated,
+            None,
+            None,
+            true,
+        )?;
+        tracing::info!(
+            subscription = %event.subscription.name,
+            job = %job.id,
+            "Hardware event queued an agent job"
+        );
+        Ok(())
+    }
+}
+
+fn to_message(event: HardwareEvent) -> ChannelMessage {
+    ChannelMessage {
+        id: Uuid::new_v4().to_string(),
+        sender: event.subscription.board,
+        reply_target: event.subscription.name,
+        content: event.message,
+        channel: "hardware".to_string(),
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        thread_ts: None,
+        attachments: Vec::new(),
+    }
+}
+
+#[async_trait]
+impl Channel for HardwareChannel {
+    fn name(&self) -> &str {
+        "hardware"
+    }
+
+    async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
+        // Boards have no display; keep the agent's reply in the log.
+        tracing::info!(
+            subscription = %message.recipient,
+            "Hardware event reply: {}",
+            message.content
+        );
+        Ok(())
+    }
+
+    async fn listen(&self, tx: mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+        let (event_tx, mut event_rx) = mpsc::channel(32);
+        let subscriptions = events::run(&self.config.peripherals, event_tx);
+        tokio::pin!(subscriptions);
+        loop {
+            let event = tokio::select! {
+                result = &mut subscriptions => return result,
+                Some(event) = event_rx.recv() => event,
+            };
+            if event.subscription.action == HardwareEventAction::Agent {
+                if self.config.cron.enabled {
+                    if let Err(e) = self.start_agent_job(&event) {
+                        tracing::warn!("Failed to queue agent job for hardware event: {e}");
+                    }
+                    continue;
+                }
+                tracing::warn!(
+                    subscription = %event.subscription.name,
+                    "cron is disabled; delivering hardware event as a channel message"
+                );
+            }
+            if tx.send(to_message(event)).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{HardwareEventKind, HardwareSubscriptionConfig};
+
+    #[test]
+    fn hardware_channel_needs_enabled_subscriptions() {
+        let mut config = Config::default();
+        assert!(!HardwareChannel::is_configured(&config));
+        config
+            .peripherals
+            .subscriptions
+            .push(HardwareSubscriptionConfig {
+                name: "door".into(),
+                board: "uno".into(),
+                kind: HardwareEventKind::GpioEdge,
+                pin: Some(2),
+                edge: crate::config::GpioEdge::Both,
+                channel: None,
+                above: None,
+                below: None,
+                interval_ms: None,
+                message: None,
+                action: HardwareEventAction::Channel,
+                debounce_ms: 0,
+                min_interval_secs: 0,
+            });
+        assert!(!HardwareChannel::is_configured(&config));
+        config.peripherals.enabled = true;
+        assert!(HardwareChannel::is_configured(&config));
+        assert_eq!(HardwareChannel::new(config).name(), "hardware");
+    }
+}
@@ -1,11 +1,14 @@
+use crate::channels::attachments::{Attachment, AttachmentKind};
 use crate::channels::traits::{Channel, ChannelMessage, SendMessage};
 use async_trait::async_trait;
 use matrix_sdk::{
     authentication::matrix::MatrixSession,
     config::SyncSettings,
+    media::{MediaFormat, MediaRequestParameters},
     ruma::{
         events::room::message::{
-            MessageType, OriginalSyncRoomMessageEvent, RoomMessageEventContent,
+            AudioMessageEventContent, MessageType, OriginalSyncRoomMessageEvent,
+            RoomMessageEventContent,
         },
         OwnedRoomId, OwnedUserId,
     },
@@ -210,13 +213,46 @@ impl MatrixChannel {
     }
 
     fn is_supported_message_type(msgtype: &str) -> bool {
-        matches!(msgtype, "m.text" | "m.notice")
+        matches!(msgtype, "m.text" | "m.notice" | "m.audio")
     }
 
     fn has_non_empty_body(body: &str) -> bool {
         !body.trim().is_empty()
     }
 
+    /// Fetch (and decrypt, in encrypted rooms) an `m.audio` event's file so
+    /// the runtime can transcribe it.
+    async fn download_audio(room: &Room, content: &AudioMessageEventContent) -> Option<Attachment> {
+        let request = MediaRequestParameters {
+            source: content.source.clone(),
+            format: MediaFormat::File,
+        };
+        let data = match room
+            .client()
+            .media()
+            .get_media_content(&request, true)
+            .await
+        {
+            Ok(data) => data,
+            Err(error) => {
+                tracing::warn!("Matrix audio download failed: {error}");
+                return None;
+            }
+        };
+
+        let mut audio = Attachment::from_bytes(content.filename(), data);
+        if let Some(info) = content.info.as_deref() {
+            if let Some(mime) = &info.mimetype {
+                audio = audio.with_mime(mime.clone());
+            }
+            if let Some(duration) = info.duration {
+                audio = audio
+                    .with_duration(duration.as_secs() + u64::from(duration.subsec_nanos() > 0));
+            }
+        }
+        Some(audio.with_kind(AttachmentKind::Audio))
+    }
+
     fn cache_event_id(
         event_id: &str,
         recent_order: &mut std::collections::VecDeque<String>,
@@ -615,13 +651,19 @@ impl Channel for MatrixChannel {
                     return;
                 }
 
-                let body = match &event.content.msgtype {
-                    MessageType::Text(content) => content.body.clone(),
-                    MessageType::Notice(content) => content.body.clone(),
+                let (body, attachments) = match &event.content.msgtype {
+                    MessageType::Text(content) => (content.body.clone(), Vec::new()),
+                    MessageType::Notice(content) => (content.body.clone(), Vec::new()),
+                    MessageType::Audio(content) => {
+                        match MatrixChannel::download_audio(&room, content).await {
+                            Some(audio) => (String::new(), vec![audio]),
+                            None => return,
+                        }
+                    }
                     _ => return,
                 };
 
-                if !MatrixChannel::has_non_empty_body(&body) {
+                if attachments.is_empty() && !MatrixChannel::has_non_empty_body(&body) {
                     return;
                 }
 
@@ -645,6 +687,7 @@ impl Channel for MatrixChannel {
                         .unwrap_or_default()
                         .as_secs(),
                     thread_ts: None,
+                    attachments,
                 };
 
                 let _ = tx.send(msg).await;
@@ -830,6 +873,7 @@ mod tests {
     fn supported_message_type_detection() {
         assert!(MatrixChannel::is_supported_message_type("m.text"));
         assert!(MatrixChannel::is_supported_message_type("m.notice"));
+        assert!(MatrixChannel::is_supported_message_type("m.audio"));
         assert!(!MatrixChannel::is_supported_message_type("m.image"));
         assert!(!MatrixChannel::is_supported_message_type("m.file"));
     }
@@ -14,11 +14,13 @@
 //! To add a new channel, implement [`Channel`] in a new submodule and wire it into
 //! [`start_channels`]. See `AGENTS.md` §7.2 for the full change playbook.
 
+pub mod attachments;
 pub mod clawdtalk;
 pub mod cli;
 pub mod dingtalk;
 pub mod discord;
 pub mod email_channel;
+pub mod hardware;
 pub mod imessage;
 pub mod irc;
 #[cfg(feature = "channel-lark")]
@@ -35,6 +37,7 @@ pub mod slack;
 pub mod telegram;
 pub mod traits;
 pub mod transcription;
+pub mod tts;
 pub mod whatsapp;
 #[cfg(feature = "whatsapp-web")]
 pub mod whatsapp_storage;
@@ -46,6 +49,7 @@ pub use cli::CliChannel;
 pub use dingtalk::DingTalkChannel;
 pub use discord::DiscordChannel;
 pub use email_channel::EmailChannel;
+pub use hardware::HardwareChannel;
 pub use imessage::IMessageChannel;
 pub use irc::IrcChannel;
 #[cfg(feature = "channel-lark")]
@@ -69,7 +73,7 @@ use crate::agent::loop_::{build_tool_instructions, run_tool_call_loop, scrub_cre
 use crate::config::Config;
 use crate::identity;
 use crate::memory::{self, Memory};
-use crate::observability::{self, runtime_trace, Observer};
+use crate::observability::{self, runtime_trace, Observer, ObserverEvent};
 use crate::providers::{self, ChatMessage, Provider};
 use crate::runtime;
 use crate::security::SecurityPolicy;
@@ -219,6 +223,9 @@ struct ChannelRuntimeContext {
     message_timeout_secs: u64,
     interrupt_on_new_message: bool,
     multimodal: crate::config::MultimodalConfig,
+    attachments: crate::config::ChannelAttachmentsConfig,
+    transcription: crate::config::TranscriptionConfig,
+    tts: crate::config::TtsConfig,
     hooks: Option<Arc<crate::hooks::HookRunner>>,
     non_cli_excluded_tools: Arc<Vec<String>>,
 }
@@ -400,6 +407,9 @@ fn channel_delivery_instructions(channel_name: &str) -> Option<&'static str> {
              - Keep normal text outside markers and never wrap markers in code fences.\n\
              - Use tool results silently: answer the latest user message directly, and do not narrate delayed/internal tool execution bookkeeping.",
         ),
+        "discord" | "signal" | "email" => Some(
+            "To send files, use markers: [IMAGE:<path-or-url>], [DOCUMENT:<path-or-url>], [VIDEO:<path-or-url>], [AUDIO:<path-or-url>], or [VOICE:<path-or-url>]. Keep normal text outside markers and never wrap markers in code fences.",
+        ),
         _ => None,
     }
 }
@@ -575,23 +585,6 @@ async fn config_file_stamp(path: &Path) -> Option<ConfigFileStamp> {
     })
 }
 
-fn decrypt_optional_secret_for_runtime_reload(
-    store: &crate::security::SecretStore,
-    value: &mut Option<String>,
-    field_name: &str,
-) -> Result<()> {
-    if let Some(raw) = value.clone() {
-        if crate::security::SecretStore::is_encrypted(&raw) {
-            *value = Some(
-                store
-                    .decrypt(&raw)
-                    .with_context(|| format!("Failed to decrypt {field_name}"))?,
-            );
-        }
-    }
-    Ok(())
-}
-
 async fn load_runtime_defaults_from_config_file(path: &Path) -> Result<ChannelRuntimeDefaults> {
     let contents = tokio::fs::read_to_string(path)
         .await
@@ -600,11 +593,12 @@ async fn load_runtime_defaults_from_config_file(path: &Path) -> Result<ChannelRu
         toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))?;
     parsed.config_path = path.to_path_buf();
 
+    // Same secret handling as startup: decrypt first, since resolving a
+    // vault:// reference needs the decrypted Vault token.
     if let Some(zeroclaw_dir) = path.parent() {
-        let store = crate::security::SecretStore::new(zeroclaw_dir, parsed.secrets.encrypt);
-        decrypt_optional_secret_for_runtime_reload(&store, &mut parsed.api_key, "config.api_key")?;
+        parsed.decrypt_secrets(zeroclaw_dir)?;
     }
-
+    parsed.resolve_secret_refs().await?;
     parsed.apply_env_overrides();
     Ok(runtime_defaults_from_config(&parsed))
 }
@@ -1472,21 +1466,54 @@ fn spawn_scoped_typing_task(
     handle
 }
 
+/// Spoken copy of `reply` when the channel supports audio and `[tts]` asks for it.
+async fn voice_reply(
+    ctx: &ChannelRuntimeContext,
+    channel: &dyn Channel,
+    msg: &traits::ChannelMessage,
+    reply: &str,
+) -> Option<attachments::Attachment> {
+    if !channel.supports_voice_replies() || !tts::wants_voice_reply(&ctx.tts, msg) {
+        return None;
+    }
+    let out_dir = std::env::temp_dir().join("zeroclaw-tts");
+    match tts::synthesize_speech(reply, &ctx.tts, &out_dir).await {
+        Ok(path) => Some(attachments::Attachment::from_path(path)),
+        Err(e) => {
+            tracing::warn!(channel = %msg.channel, "Voice reply skipped: {e:#}");
+            None
+        }
+    }
+}
+
 async fn process_channel_message(
     ctx: Arc<ChannelRuntimeContext>,
-    msg: traits::ChannelMessage,
+    mut msg: traits::ChannelMessage,
     cancellation_token: CancellationToken,
 ) {
     if cancellation_token.is_cancelled() {
         return;
     }
 
+    // Move received files into the workspace inbox and reference them in the
+    // text so file tools and the multimodal pipeline can reach them.
+    if !msg.attachments.is_empty() {
+        attachments::AttachmentInbox::new(ctx.workspace_dir.as_path(), &ctx.attachments)
+            .ingest(&mut msg)
+            .await;
+        transcription::transcribe_attachments(&mut msg, &ctx.transcription).await;
+    }
+
     println!(
         "  💬 [{}] from {}: {}",
         msg.channel,
         msg.sender,
         truncate_with_ellipsis(&msg.content, 80)
     );
+    ctx.observer.record_event(&ObserverEvent::ChannelMessage {
+        channel: msg.channel.clone(),
+        direction: "inbound".to_string(),
+    });
     runtime_trace::record_event(
         "channel_message_inbound",
         Some(msg.channel.as_str()),
@@ -1710,6 +1737,7 @@ async fn process_channel_message(
                 true,
                 None,
                 msg.channel.as_str(),
+                Some(msg.sender.as_str()),
                 &ctx.multimodal,
                 ctx.max_tool_iterations,
                 Some(cancellation_token.clone()),
@@ -1832,6 +1860,10 @@ async fn process_channel_message(
             } else {
                 sanitized_response
             };
+            ctx.observer.record_event(&ObserverEvent::ChannelMessage {
+                channel: msg.channel.clone(),
+                direction: "outbound".to_string(),
+            });
             runtime_trace::record_event(
                 "channel_message_outbound",
                 Some(msg.channel.as_str()),
@@ -1868,6 +1900,13 @@ async fn process_channel_message(
                 truncate_with_ellipsis(&delivered_response, 80)
             );
             if let Some(channel) = target_channel.as_ref() {
+                let delivered_response = if channel.supports_attachments() {
+                    delivered_response
+                } else {
+                    attachments::attachments_as_text(channel.name(), &delivered_response)
+                };
+                let voice =
+                    voice_reply(ctx.as_ref(), channel.as_ref(), &msg, &delivered_response).await;
                 if let Some(ref draft_id) = draft_message_id {
                     if let Err(e) = channel
                         .finalize_draft(&msg.reply_target, draft_id, &delivered_response)
@@ -1877,20 +1916,38 @@ async fn process_channel_message(
                         let _ = channel
                             .send(
                                 &SendMessage::new(&delivered_response, &msg.reply_target)
-                                    .in_thread(msg.thread_ts.clone()),
+                                    .in_thread(msg.thread_ts.clone())
+                                    .with_attachments(voice.iter().cloned().collect()),
                             )
                             .await;
+                    } else if let Some(ref voice) = voice {
+                        // The draft already shows the text; follow up with the audio.
+                        if let Err(e) = channel
+                            .send(
+                                &SendMessage::new("", &msg.reply_target)
+                                    .in_thread(msg.thread_ts.clone())
+                                    .with_attachments(vec![voice.clone()]),
+                            )
+                            .await
+                        {
+                            tracing::warn!("Failed to send voice reply: {e}");
+                        }
                     }
                 } else if let Err(e) = channel
                     .send(
                         &SendMessage::new(delivered_response, &msg.reply_target)
-                            .in_thread(msg.thread_ts.clone()),
+                            .in_thread(msg.thread_ts.clone())
+                            .with_attachments(voice.iter().cloned().collect()),
                     )
                     .await
                 {
                     eprintln!("  ❌ Failed to reply on {}: {e}", channel.name());
                 }
+                if let Some(path) = voice.as_ref().and_then(attachments::Attachment::local_path) {
+                    let _ = tokio::fs::remove_file(path).await;
+                }
             }
+            ctx.observer.record_event(&ObserverEvent::TurnComplete);
         }
         LlmExecutionResult::Completed(Ok(Err(e))) => {
             if crate::agent::loop_::is_tool_loop_cancelled(&e) || cancellation_token.is_cancelled()
@@ -2125,7 +2182,12 @@ async fn run_message_dispatch_loop(
                 }
             }
 
-            process_channel_message(worker_ctx, msg, cancellation_token).await;
+            let channel = msg.channel.clone();
+            observability::events::with_channel(
+                channel,
+                process_channel_message(worker_ctx, msg, cancellation_token),
+            )
+            .await;
 
             if interrupt_enabled {
                 let mut active = in_flight.lock().await;
@@ -2658,8 +2720,7 @@ fn collect_configured_channels(
                     tg.mention_only,
                 )
                 .with_streaming(tg.stream_mode, tg.draft_update_interval_ms)
-                .with_transcription(config.transcription.clone())
-                .with_workspace_dir(config.workspace_dir.clone()),
+                .with_transcription(config.transcription.clone()),
             ),
         });
     }
@@ -2889,6 +2950,13 @@ fn collect_configured_channels(
         });
     }
 
+    if HardwareChannel::is_configured(config) {
+        channels.push(ConfiguredChannel {
+            display_name: "Hardware",
+            channel: Arc::new(HardwareChannel::new(config.clone())),
+        });
+    }
+
     channels
 }
 
@@ -2999,6 +3067,7 @@ pub async fn start_channels(config: Config) -> Result<()> {
         &config.autonomy,
         &config.workspace_dir,
     ));
+    attachments::set_outgoing_file_policy(Arc::clone(&security));
     let model = resolved_default_model(&config);
     let temperature = config.default_temperature;
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory_with_storage(
@@ -3017,7 +3086,8 @@ pub async fn start_channels(config: Config) -> Result<()> {
     };
     // Build system prompt from workspace identity files + skills
     let workspace = config.workspace_dir.clone();
-    let tools_registry = Arc::new(tools::all_tools_with_runtime(
+    let hooks = crate::hooks::HookRunner::from_config(&config)?.map(Arc::new);
+    let mut tools = tools::all_tools_with_runtime(
         Arc::new(config.clone()),
         &security,
         runtime,
@@ -3030,7 +3100,11 @@ pub async fn start_channels(config: Config) -> Result<()> {
         &config.agents,
         config.api_key.as_deref(),
         &config,
-    ));
+        hooks.clone(),
+    );
+    // Board tools share their connections with the hardware channel.
+    tools.extend(crate::peripherals::create_peripheral_tools(&config.peripherals).await?);
+    let tools_registry = Arc::new(tools);
 
     let skills = crate::skills::load_skills_with_config(&workspace, &config);
 
@@ -3237,15 +3311,10 @@ pub async fn start_channels(config: Config) -> Result<()> {
         message_timeout_secs,
         interrupt_on_new_message,
         multimodal: config.multimodal.clone(),
-        hooks: if config.hooks.enabled {
-            let mut runner = crate::hooks::HookRunner::new();
-            if config.hooks.builtin.command_logger {
-                runner.register(Box::new(crate::hooks::builtin::CommandLoggerHook::new()));
-            }
-            Some(Arc::new(runner))
-        } else {
-            None
-        },
+        attachments: config.channels_config.attachments.clone(),
+        transcription: config.transcription.clone(),
+        tts: config.tts.clone(),
+        hooks,
         non_cli_excluded_tools: Arc::new(config.autonomy.non_cli_excluded_tools.clone()),
     });
 
@@ -3455,6 +3524,9 @@ mod tests {
             reliability: Arc::new(crate::config::ReliabilityConfig::default()),
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             provider_runtime_options: providers::ProviderRuntimeOptions::default(),
             workspace_dir: Arc::new(std::env::temp_dir()),
@@ -3504,6 +3576,9 @@ mod tests {
             reliability: Arc::new(crate::config::ReliabilityConfig::default()),
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             provider_runtime_options: providers::ProviderRuntimeOptions::default(),
             workspace_dir: Arc::new(std::env::temp_dir()),
@@ -3556,6 +3631,9 @@ mod tests {
             reliability: Arc::new(crate::config::ReliabilityConfig::default()),
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             provider_runtime_options: providers::ProviderRuntimeOptions::default(),
             workspace_dir: Arc::new(std::env::temp_dir()),
@@ -4002,6 +4080,119 @@ BTC is currently around $65,000 based on latest tool output."#
         }
     }
 
+    #[tokio::test]
+    async fn process_channel_message_adds_voice_reply_for_voice_notes() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        #[derive(Default)]
+        struct VoiceChannel {
+            sent: tokio::sync::Mutex<Vec<(String, Vec<Vec<u8>>)>>,
+        }
+
+        #[async_trait::async_trait]
+        impl Channel for VoiceChannel {
+            fn name(&self) -> &str {
+                "voice-channel"
+            }
+
+            fn supports_voice_replies(&self) -> bool {
+                true
+            }
+
+            async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
+                let mut audio = Vec::new();
+                for attachment in &message.attachments {
+                    assert_eq!(attachment.kind, attachments::AttachmentKind::Voice);
+                    audio.push(attachment.read_bytes(1024).await?);
+                }
+                self.sent
+                    .lock()
+                    .await
+                    .push((message.content.clone(), audio));
+                Ok(())
+            }
+
+            async fn listen(
+                &self,
+                _tx: tokio::sync::mpsc::Sender<traits::ChannelMessage>,
+            ) -> anyhow::Result<()> {
+                Ok(())
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/audio/speech"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"OggS".to_vec()))
+            .mount(&server)
+            .await;
+
+        let channel_impl = Arc::new(VoiceChannel::default());
+        let channel: Arc<dyn Channel> = channel_impl.clone();
+        let mut channels_by_name = HashMap::new();
+        channels_by_name.insert(channel.name().to_string(), channel);
+
+        let runtime_ctx = Arc::new(ChannelRuntimeContext {
+            channels_by_name: Arc::new(channels_by_name),
+            provider: Arc::new(DummyProvider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("test-system-prompt".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 10,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            provider_cache: Arc::new(Mutex::new(HashMap::new())),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(std::env::temp_dir()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            interrupt_on_new_message: false,
+            non_cli_excluded_tools: Arc::new(Vec::new()),
+            multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig {
+                enabled: true,
+                api_url: format!("{}/audio/speech", server.uri()),
+                api_key: Some("test-key".into()),
+                ..crate::config::TtsConfig::default()
+            },
+            hooks: None,
+        });
+
+        for (id, content) in [("msg-1", "[Voice] hello"), ("msg-2", "hello")] {
+            process_channel_message(
+                runtime_ctx.clone(),
+                traits::ChannelMessage {
+                    id: id.to_string(),
+                    sender: "alice".to_string(),
+                    reply_target: "chat-42".to_string(),
+                    content: content.to_string(),
+                    channel: "voice-channel".to_string(),
+                    timestamp: 1,
+                    thread_ts: None,
+                    attachments: Vec::new(),
+                },
+                CancellationToken::new(),
+            )
+            .await;
+        }
+
+        let sent = channel_impl.sent.lock().await;
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0], ("ok".to_string(), vec![b"OggS".to_vec()]));
+        assert_eq!(sent[1], ("ok".to_string(), Vec::new()));
+    }
+
     #[tokio::test]
     async fn process_channel_message_executes_tool_calls_instead_of_sending_raw_json() {
         let channel_impl = Arc::new(RecordingChannel::default());
@@ -4035,6 +4226,9 @@ BTC is currently around $65,000 based on latest tool output."#
             interrupt_on_new_message: false,
             non_cli_excluded_tools: Arc::new(Vec::new()),
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
         });
 
@@ -4048,6 +4242,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "test-channel".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -4094,6 +4289,9 @@ BTC is currently around $65,000 based on latest tool output."#
             interrupt_on_new_message: false,
             non_cli_excluded_tools: Arc::new(Vec::new()),
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
         });
 
@@ -4107,6 +4305,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "telegram".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -4166,6 +4365,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -4180,6 +4382,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "test-channel".to_string(),
                 timestamp: 3,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -4225,6 +4428,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -4239,6 +4445,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "test-channel".to_string(),
                 timestamp: 2,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -4293,6 +4500,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -4307,6 +4517,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "telegram".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -4382,6 +4593,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -4396,6 +4610,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "telegram".to_string(),
                 timestamp: 2,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -4453,6 +4668,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -4467,6 +4685,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "telegram".to_string(),
                 timestamp: 3,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -4539,6 +4758,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -4553,6 +4775,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "telegram".to_string(),
                 timestamp: 4,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -4610,6 +4833,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -4624,6 +4850,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "test-channel".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -4670,6 +4897,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -4684,6 +4914,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "test-channel".to_string(),
                 timestamp: 2,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -4841,6 +5072,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -4854,6 +5088,7 @@ BTC is currently around $65,000 based on latest tool output."#
             channel: "test-channel".to_string(),
             timestamp: 1,
             thread_ts: None,
+            attachments: Vec::new(),
         })
         .await
         .unwrap();
@@ -4865,6 +5100,7 @@ BTC is currently around $65,000 based on latest tool output."#
             channel: "test-channel".to_string(),
             timestamp: 2,
             thread_ts: None,
+            attachments: Vec::new(),
         })
         .await
         .unwrap();
@@ -4921,6 +5157,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: true,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -4935,6 +5174,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "telegram".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             })
             .await
             .unwrap();
@@ -4947,6 +5187,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "telegram".to_string(),
                 timestamp: 2,
                 thread_ts: None,
+                attachments: Vec::new(),
             })
             .await
             .unwrap();
@@ -5013,6 +5254,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: true,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -5027,6 +5271,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "telegram".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             })
             .await
             .unwrap();
@@ -5039,6 +5284,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "telegram".to_string(),
                 timestamp: 2,
                 thread_ts: None,
+                attachments: Vec::new(),
             })
             .await
             .unwrap();
@@ -5087,6 +5333,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -5101,6 +5350,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "test-channel".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -5146,6 +5396,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -5160,6 +5413,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "test-channel".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -5357,6 +5611,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 args: HashMap::new(),
             }],
             prompts: vec!["Always run cargo test before final response.".into()],
+            policy: crate::skills::SkillPolicy::default(),
             location: None,
         }];
 
@@ -5392,6 +5647,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 args: HashMap::new(),
             }],
             prompts: vec!["Always run cargo test before final response.".into()],
+            policy: crate::skills::SkillPolicy::default(),
             location: None,
         }];
 
@@ -5433,6 +5689,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 args: HashMap::new(),
             }],
             prompts: vec!["Use <tool_call> and & keep output \"safe\"".into()],
+            policy: crate::skills::SkillPolicy::default(),
             location: None,
         }];
 
@@ -5536,6 +5793,7 @@ BTC is currently around $65,000 based on latest tool output."#
             channel: "slack".into(),
             timestamp: 1,
             thread_ts: None,
+            attachments: Vec::new(),
         };
 
         assert_eq!(conversation_memory_key(&msg), "slack_U123_msg_abc123");
@@ -5551,6 +5809,7 @@ BTC is currently around $65,000 based on latest tool output."#
             channel: "slack".into(),
             timestamp: 1,
             thread_ts: None,
+            attachments: Vec::new(),
         };
         let msg2 = traits::ChannelMessage {
             id: "msg_2".into(),
@@ -5560,6 +5819,7 @@ BTC is currently around $65,000 based on latest tool output."#
             channel: "slack".into(),
             timestamp: 2,
             thread_ts: None,
+            attachments: Vec::new(),
         };
 
         assert_ne!(
@@ -5581,6 +5841,7 @@ BTC is currently around $65,000 based on latest tool output."#
             channel: "slack".into(),
             timestamp: 1,
             thread_ts: None,
+            attachments: Vec::new(),
         };
         let msg2 = traits::ChannelMessage {
             id: "msg_2".into(),
@@ -5590,6 +5851,7 @@ BTC is currently around $65,000 based on latest tool output."#
             channel: "slack".into(),
             timestamp: 2,
             thread_ts: None,
+            attachments: Vec::new(),
         };
 
         mem.store(
@@ -5662,6 +5924,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -5676,6 +5941,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "test-channel".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -5691,6 +5957,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "test-channel".to_string(),
                 timestamp: 2,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -5747,6 +6014,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -5761,6 +6031,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "test-channel".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -5832,6 +6103,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -5846,6 +6120,7 @@ BTC is currently around $65,000 based on latest tool output."#
                 channel: "telegram".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -6381,6 +6656,9 @@ This is an example JSON object for profile settings."#;
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -6396,6 +6674,7 @@ This is an example JSON object for profile settings."#;
                 channel: "test-channel".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -6447,6 +6726,9 @@ This is an example JSON object for profile settings."#;
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            attachments: crate::config::ChannelAttachmentsConfig::default(),
+            transcription: crate::config::TranscriptionConfig::default(),
+            tts: crate::config::TtsConfig::default(),
             hooks: None,
             non_cli_excluded_tools: Arc::new(Vec::new()),
         });
@@ -6461,6 +6743,7 @@ This is an example JSON object for profile settings."#;
                 channel: "test-channel".to_string(),
                 timestamp: 1,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -6476,6 +6759,7 @@ This is an example JSON object for profile settings."#;
                 channel: "test-channel".to_string(),
                 timestamp: 2,
                 thread_ts: None,
+                attachments: Vec::new(),
             },
             CancellationToken::new(),
         )
@@ -6512,4 +6796,34 @@ This is an example JSON object for profile settings."#;
             "failed vision turn must not persist image marker content"
         );
     }
+
+    #[tokio::test]
+    async fn runtime_reload_decrypts_vault_token_and_resolves_refs() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/reload/openai"))
+            .and(header("X-Vault-Token", "reload-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": { "data": { "api_key": "sk-reloaded" } }
+            })))
+            .mount(&server)
+            .await;
+
+        let tmp = TempDir::new().unwrap();
+        let store = crate::security::SecretStore::new(tmp.path(), true);
+        let mut config = Config::default();
+        config.api_key = Some("vault://secret/reload/openai#api_key".into());
+        config.secrets.vault.address = Some(server.uri());
+        config.secrets.vault.token = Some(store.encrypt("reload-token").unwrap());
+        let config_path = tmp.path().join("config.toml");
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+        let defaults = load_runtime_defaults_from_config_file(&config_path)
+            .await
+            .unwrap();
+        assert_eq!(defaults.api_key.as_deref(), Some("sk-reloaded"));
+    }
 }
@@ -461,6 +461,7 @@ impl Channel for QQChannel {
                                     .unwrap_or_default()
                                     .as_secs(),
                                 thread_ts: None,
+                                attachments: Vec::new(),
                             };
 
                             if tx.send(channel_msg).await.is_err() {
@@ -499,6 +500,7 @@ impl Channel for QQChannel {
                                     .unwrap_or_default()
                                     .as_secs(),
                                 thread_ts: None,
+                                attachments: Vec::new(),
                             };
 
                             if tx.send(channel_msg).await.is_err() {
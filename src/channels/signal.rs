@@ -1,5 +1,7 @@
+use crate::channels::attachments::{collect_outgoing, Attachment};
 use crate::channels::traits::{Channel, ChannelMessage, SendMessage};
 use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use futures_util::StreamExt;
 use reqwest::Client;
 use serde::Deserialize;
@@ -9,6 +11,9 @@ use uuid::Uuid;
 
 const GROUP_TARGET_PREFIX: &str = "group:";
 
+/// Signal's attachment size limit (100 MB).
+const SIGNAL_MAX_ATTACHMENT_BYTES: u64 = 100 * 1024 * 1024;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum RecipientTarget {
     Direct(String),
@@ -215,6 +220,79 @@ impl SignalChannel {
         Ok(parsed.get("result").cloned())
     }
 
+    /// Process an SSE envelope and download its attachments.
+    async fn envelope_message(&self, envelope: &Envelope) -> Option<ChannelMessage> {
+        let mut msg = self.process_envelope(envelope)?;
+        if !self.ignore_attachments {
+            if let Some(data_msg) = envelope.data_message.as_ref() {
+                msg.attachments = self.fetch_attachments(data_msg, &msg.sender).await;
+            }
+        }
+        Some(msg)
+    }
+
+    /// Download attachments of a data message via the `getAttachment` RPC.
+    async fn fetch_attachments(&self, data_msg: &DataMessage, sender: &str) -> Vec<Attachment> {
+        let mut files = Vec::new();
+        for att in data_msg.attachments.iter().flatten() {
+            let Some(id) = att.get("id").and_then(serde_json::Value::as_str) else {
+                continue;
+            };
+            let mut params = serde_json::json!({ "account": &self.account, "id": id });
+            match data_msg
+                .group_info
+                .as_ref()
+                .and_then(|g| g.group_id.as_deref())
+            {
+                Some(group_id) => params["groupId"] = serde_json::json!(group_id),
+                None => params["recipient"] = serde_json::json!(sender),
+            }
+
+            let data = match self.rpc_request("getAttachment", params).await {
+                Ok(Some(result)) => result
+                    .get("data")
+                    .and_then(serde_json::Value::as_str)
+                    .or_else(|| result.as_str())
+                    .and_then(|encoded| STANDARD.decode(encoded).ok()),
+                Ok(None) => None,
+                Err(e) => {
+                    tracing::warn!("Signal getAttachment failed for {id}: {e}");
+                    continue;
+                }
+            };
+            let Some(data) = data else {
+                tracing::warn!("Signal getAttachment returned no data for {id}");
+                continue;
+            };
+
+            let name = att
+                .get("filename")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or(id);
+            let mut file = Attachment::from_bytes(name, data);
+            if let Some(mime) = att.get("contentType").and_then(serde_json::Value::as_str) {
+                file = file.with_mime(mime);
+            }
+            files.push(file);
+        }
+        files
+    }
+
+    /// Encode attachments as `data:` URIs accepted by signal-cli's `send`.
+    async fn encode_attachments(files: &[Attachment]) -> anyhow::Result<Vec<String>> {
+        let mut encoded = Vec::with_capacity(files.len());
+        for file in files {
+            let bytes = file.read_bytes(SIGNAL_MAX_ATTACHMENT_BYTES).await?;
+            encoded.push(format!(
+                "data:{};filename={};base64,{}",
+                file.mime,
+                file.name,
+                STANDARD.encode(bytes)
+            ));
+        }
+        Ok(encoded)
+    }
+
     /// Process a single SSE envelope, returning a ChannelMessage if valid.
     fn process_envelope(&self, envelope: &Envelope) -> Option<ChannelMessage> {
         // Skip story messages when configured
@@ -224,15 +302,13 @@ impl SignalChannel {
 
         let data_msg = envelope.data_message.as_ref()?;
 
-        // Skip attachment-only messages when configured
-        if self.ignore_attachments {
-            let has_attachments = data_msg.attachments.as_ref().is_some_and(|a| !a.is_empty());
-            if has_attachments && data_msg.message.is_none() {
-                return None;
-            }
-        }
-
-        let text = data_msg.message.as_deref().filter(|t| !t.is_empty())?;
+        // Attachment-only messages are accepted unless configured otherwise
+        let has_attachments = data_msg.attachments.as_ref().is_some_and(|a| !a.is_empty());
+        let text = match data_msg.message.as_deref().filter(|t| !t.is_empty()) {
+            Some(text) => text,
+            None if has_attachments && !self.ignore_attachments => "",
+            None => return None,
+        };
         let sender = Self::sender(envelope)?;
 
         if !self.is_sender_allowed(&sender) {
@@ -266,6 +342,7 @@ impl SignalChannel {
             channel: "signal".to_string(),
             timestamp: timestamp / 1000, // millis → secs
             thread_ts: None,
+            attachments: Vec::new(),
         })
     }
 }
@@ -276,19 +353,31 @@ impl Channel for SignalChannel {
         "signal"
     }
 
+    fn supports_attachments(&self) -> bool {
+        true
+    }
+
+    fn supports_voice_replies(&self) -> bool {
+        true
+    }
+
     async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
-        let params = match Self::parse_recipient_target(&message.recipient) {
+        let (text, files) = collect_outgoing(&message.content, &message.attachments);
+        let mut params = match Self::parse_recipient_target(&message.recipient) {
             RecipientTarget::Direct(number) => serde_json::json!({
                 "recipient": [number],
-                "message": &message.content,
+                "message": &text,
                 "account": &self.account,
             }),
             RecipientTarget::Group(group_id) => serde_json::json!({
                 "groupId": group_id,
-                "message": &message.content,
+                "message": &text,
                 "account": &self.account,
             }),
         };
+        if !files.is_empty() {
+            params["attachments"] = serde_json::json!(Self::encode_attachments(&files).await?);
+        }
 
         self.rpc_request("send", params).await?;
         Ok(())
@@ -369,7 +458,7 @@ impl Channel for SignalChannel {
                             match serde_json::from_str::<SseEnvelope>(&current_data) {
                                 Ok(sse) => {
                                     if let Some(ref envelope) = sse.envelope {
-                                        if let Some(msg) = self.process_envelope(envelope) {
+                                        if let Some(msg) = self.envelope_message(envelope).await {
                                             if tx.send(msg).await.is_err() {
                                                 return Ok(());
                                             }
@@ -396,7 +485,7 @@ impl Channel for SignalChannel {
                 match serde_json::from_str::<SseEnvelope>(&current_data) {
                     Ok(sse) => {
                         if let Some(ref envelope) = sse.envelope {
-                            if let Some(msg) = self.process_envelope(envelope) {
+                            if let Some(msg) = self.envelope_message(envelope).await {
                                 let _ = tx.send(msg).await;
                             }
                         }
@@ -855,6 +944,68 @@ mod tests {
         assert!(ch.process_envelope(&env).is_none());
     }
 
+    #[test]
+    fn process_envelope_accepts_attachment_only_when_enabled() {
+        let ch = make_channel();
+        let env = Envelope {
+            source: Some("+1111111111".to_string()),
+            source_number: Some("+1111111111".to_string()),
+            data_message: Some(DataMessage {
+                message: None,
+                timestamp: Some(1_700_000_000_000),
+                group_info: None,
+                attachments: Some(vec![serde_json::json!({"contentType": "image/png"})]),
+            }),
+            story_message: None,
+            timestamp: Some(1_700_000_000_000),
+        };
+        let msg = ch.process_envelope(&env).expect("attachment-only message");
+        assert!(msg.content.is_empty());
+    }
+
+    #[tokio::test]
+    async fn envelope_message_downloads_attachments() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/rpc"))
+            .and(body_partial_json(serde_json::json!({
+                "method": "getAttachment",
+                "params": {"id": "abc123", "recipient": "+1111111111"}
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "1",
+                "result": {"data": STANDARD.encode(b"%PDF-1.4")}
+            })))
+            .mount(&server)
+            .await;
+
+        let ch = SignalChannel::new(
+            server.uri(),
+            "+1234567890".to_string(),
+            None,
+            vec!["+1111111111".to_string()],
+            false,
+            false,
+        );
+        let mut env = make_envelope(Some("+1111111111"), Some("see attached"));
+        env.data_message.as_mut().unwrap().attachments = Some(vec![serde_json::json!({
+            "id": "abc123",
+            "filename": "invoice.pdf",
+            "contentType": "application/pdf"
+        })]);
+
+        let msg = ch.envelope_message(&env).await.expect("message");
+        assert_eq!(msg.content, "see attached");
+        assert_eq!(msg.attachments.len(), 1);
+        assert_eq!(msg.attachments[0].name, "invoice.pdf");
+        assert_eq!(msg.attachments[0].mime, "application/pdf");
+        assert_eq!(msg.attachments[0].size, Some(8));
+    }
+
     #[test]
     fn sse_envelope_deserializes() {
         let json = r#"{
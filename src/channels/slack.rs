@@ -1,8 +1,12 @@
+use super::attachments::{Attachment, AttachmentSource};
 use super::traits::{Channel, ChannelMessage, SendMessage};
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
+/// Largest audio clip fetched for transcription (the Whisper API limit).
+const SLACK_MAX_AUDIO_BYTES: u64 = 25 * 1024 * 1024;
+
 /// Slack channel — polls conversations.history via Web API
 pub struct SlackChannel {
     bot_token: String,
@@ -57,6 +61,64 @@ impl SlackChannel {
             .map(str::to_string)
     }
 
+    /// Audio files shared in `msg` (voice clips and uploaded recordings) as
+    /// URL attachments. Their private URLs need the bot token to download.
+    fn audio_files(msg: &serde_json::Value) -> Vec<Attachment> {
+        let Some(files) = msg.get("files").and_then(|f| f.as_array()) else {
+            return Vec::new();
+        };
+        files
+            .iter()
+            .filter_map(|file| {
+                let mime = file.get("mimetype").and_then(|m| m.as_str())?;
+                if !mime.starts_with("audio/") {
+                    return None;
+                }
+                let url = file
+                    .get("url_private_download")
+                    .or_else(|| file.get("url_private"))
+                    .and_then(|u| u.as_str())?;
+                let name = file.get("name").and_then(|n| n.as_str()).unwrap_or("audio");
+                let mut audio = Attachment::from_url(url).with_name(name).with_mime(mime);
+                if let Some(size) = file.get("size").and_then(serde_json::Value::as_u64) {
+                    audio = audio.with_size(size);
+                }
+                if let Some(ms) = file.get("duration_ms").and_then(serde_json::Value::as_u64) {
+                    audio = audio.with_duration(ms.div_ceil(1000));
+                }
+                Some(audio)
+            })
+            .collect()
+    }
+
+    /// Download a private Slack file into memory.
+    async fn download_private(&self, attachment: Attachment) -> anyhow::Result<Attachment> {
+        let url = attachment
+            .url()
+            .ok_or_else(|| anyhow::anyhow!("Slack file has no URL"))?;
+        if attachment.exceeds(SLACK_MAX_AUDIO_BYTES) {
+            anyhow::bail!("Slack file '{}' is too large", attachment.name);
+        }
+        let resp = self
+            .http_client()
+            .get(url)
+            .bearer_auth(&self.bot_token)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            anyhow::bail!("Slack file download failed ({})", resp.status());
+        }
+        let bytes = resp.bytes().await?;
+        if bytes.len() as u64 > SLACK_MAX_AUDIO_BYTES {
+            anyhow::bail!("Slack file '{}' is too large", attachment.name);
+        }
+        Ok(Attachment {
+            size: Some(bytes.len() as u64),
+            source: AttachmentSource::Bytes(bytes.to_vec()),
+            ..attachment
+        })
+    }
+
     fn normalized_channel_id(input: Option<&str>) -> Option<String> {
         input
             .map(str::trim)
@@ -324,12 +386,24 @@ impl Channel for SlackChannel {
                         }
 
                         // Skip empty or already-seen
-                        if text.is_empty() || ts <= last_ts {
+                        let audio = Self::audio_files(msg);
+                        if (text.is_empty() && audio.is_empty()) || ts <= last_ts {
                             continue;
                         }
 
                         last_ts_by_channel.insert(channel_id.clone(), ts.to_string());
 
+                        let mut attachments = Vec::with_capacity(audio.len());
+                        for file in audio {
+                            match self.download_private(file).await {
+                                Ok(file) => attachments.push(file),
+                                Err(e) => tracing::warn!("Slack audio download failed: {e:#}"),
+                            }
+                        }
+                        if text.is_empty() && attachments.is_empty() {
+                            continue;
+                        }
+
                         let channel_msg = ChannelMessage {
                             id: format!("slack_{channel_id}_{ts}"),
                             sender: user.to_string(),
@@ -341,6 +415,7 @@ impl Channel for SlackChannel {
                                 .unwrap_or_default()
                                 .as_secs(),
                             thread_ts: Self::inbound_thread_ts(msg, ts),
+                            attachments,
                         };
 
                         if tx.send(channel_msg).await.is_err() {
@@ -532,4 +607,31 @@ mod tests {
         let thread_ts = SlackChannel::inbound_thread_ts(&msg, "");
         assert_eq!(thread_ts, None);
     }
+
+    #[test]
+    fn audio_files_keeps_only_audio_with_duration() {
+        let msg = serde_json::json!({
+            "files": [
+                {
+                    "name": "audio_message.m4a",
+                    "mimetype": "audio/mp4",
+                    "size": 2048,
+                    "duration_ms": 4200,
+                    "url_private_download": "https://files.slack.com/files-pri/T1-F1/download/audio_message.m4a"
+                },
+                {
+                    "name": "diagram.png",
+                    "mimetype": "image/png",
+                    "url_private_download": "https://files.slack.com/files-pri/T1-F2/download/diagram.png"
+                }
+            ]
+        });
+
+        let files = SlackChannel::audio_files(&msg);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "audio_message.m4a");
+        assert_eq!(files[0].size, Some(2048));
+        assert_eq!(files[0].duration_secs, Some(5));
+        assert!(SlackChannel::audio_files(&serde_json::json!({})).is_empty());
+    }
 }
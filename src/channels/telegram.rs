@@ -1,3 +1,6 @@
+use super::attachments::{
+    collect_outgoing, parse_path_only_attachment, Attachment, AttachmentKind, AttachmentSource,
+};
 use super::traits::{Channel, ChannelMessage, SendMessage};
 use crate::config::{Config, StreamMode};
 use crate::security::pairing::PairingGuard;
@@ -124,138 +127,11 @@ fn build_telegram_ack_reaction_request(
     })
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum TelegramAttachmentKind {
-    Image,
-    Document,
-    Video,
-    Audio,
-    Voice,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct TelegramAttachment {
-    kind: TelegramAttachmentKind,
-    target: String,
-}
-
-impl TelegramAttachmentKind {
-    fn from_marker(marker: &str) -> Option<Self> {
-        match marker.trim().to_ascii_uppercase().as_str() {
-            "IMAGE" | "PHOTO" => Some(Self::Image),
-            "DOCUMENT" | "FILE" => Some(Self::Document),
-            "VIDEO" => Some(Self::Video),
-            "AUDIO" => Some(Self::Audio),
-            "VOICE" => Some(Self::Voice),
-            _ => None,
-        }
-    }
-}
-
-fn is_http_url(target: &str) -> bool {
-    target.starts_with("http://") || target.starts_with("https://")
-}
-
-fn infer_attachment_kind_from_target(target: &str) -> Option<TelegramAttachmentKind> {
-    let normalized = target
-        .split('?')
-        .next()
-        .unwrap_or(target)
-        .split('#')
-        .next()
-        .unwrap_or(target);
-
-    let extension = Path::new(normalized)
-        .extension()
-        .and_then(|ext| ext.to_str())?
-        .to_ascii_lowercase();
-
-    match extension.as_str() {
-        "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" => Some(TelegramAttachmentKind::Image),
-        "mp4" | "mov" | "mkv" | "avi" | "webm" => Some(TelegramAttachmentKind::Video),
-        "mp3" | "m4a" | "wav" | "flac" => Some(TelegramAttachmentKind::Audio),
-        "ogg" | "oga" | "opus" => Some(TelegramAttachmentKind::Voice),
-        "pdf" | "txt" | "md" | "csv" | "json" | "zip" | "tar" | "gz" | "doc" | "docx" | "xls"
-        | "xlsx" | "ppt" | "pptx" => Some(TelegramAttachmentKind::Document),
-        _ => None,
-    }
-}
-
-fn parse_path_only_attachment(message: &str) -> Option<TelegramAttachment> {
-    let trimmed = message.trim();
-    if trimmed.is_empty() || trimmed.contains('\n') {
-        return None;
-    }
-
-    let candidate = trimmed.trim_matches(|c| matches!(c, '`' | '"' | '\''));
-    if candidate.chars().any(char::is_whitespace) {
-        return None;
-    }
-
-    let candidate = candidate.strip_prefix("file://").unwrap_or(candidate);
-    let kind = infer_attachment_kind_from_target(candidate)?;
-
-    if !is_http_url(candidate) && !Path::new(candidate).exists() {
-        return None;
-    }
-
-    Some(TelegramAttachment {
-        kind,
-        target: candidate.to_string(),
-    })
-}
-
 /// Delegate to the shared `strip_tool_call_tags` in the parent module.
 fn strip_tool_call_tags(message: &str) -> String {
     super::strip_tool_call_tags(message)
 }
 
-fn parse_attachment_markers(message: &str) -> (String, Vec<TelegramAttachment>) {
-    let mut cleaned = String::with_capacity(message.len());
-    let mut attachments = Vec::new();
-    let mut cursor = 0;
-
-    while cursor < message.len() {
-        let Some(open_rel) = message[cursor..].find('[') else {
-            cleaned.push_str(&message[cursor..]);
-            break;
-        };
-
-        let open = cursor + open_rel;
-        cleaned.push_str(&message[cursor..open]);
-
-        let Some(close_rel) = message[open..].find(']') else {
-            cleaned.push_str(&message[open..]);
-            break;
-        };
-
-        let close = open + close_rel;
-        let marker = &message[open + 1..close];
-
-        let parsed = marker.split_once(':').and_then(|(kind, target)| {
-            let kind = TelegramAttachmentKind::from_marker(kind)?;
-            let target = target.trim();
-            if target.is_empty() {
-                return None;
-            }
-            Some(TelegramAttachment {
-                kind,
-                target: target.to_string(),
-            })
-        });
-
-        if let Some(attachment) = parsed {
-            attachments.push(attachment);
-        } else {
-            cleaned.push_str(&message[open..=close]);
-        }
-
-        cursor = close + 1;
-    }
-
-    (cleaned.trim().to_string(), attachments)
-}
-
 /// Telegram Bot API maximum file download size (20 MB).
 const TELEGRAM_MAX_FILE_DOWNLOAD_BYTES: u64 = 20 * 1024 * 1024;
 
@@ -276,7 +152,6 @@ pub struct TelegramChannel {
     api_base: String,
     transcription: Option<crate::config::TranscriptionConfig>,
     voice_transcriptions: Mutex<std::collections::HashMap<String, String>>,
-    workspace_dir: Option<std::path::PathBuf>,
 }
 
 impl TelegramChannel {
@@ -307,16 +182,9 @@ impl TelegramChannel {
             api_base: "https://api.telegram.org".to_string(),
             transcription: None,
             voice_transcriptions: Mutex::new(std::collections::HashMap::new()),
-            workspace_dir: None,
         }
     }
 
-    /// Configure workspace directory for saving downloaded attachments.
-    pub fn with_workspace_dir(mut self, dir: std::path::PathBuf) -> Self {
-        self.workspace_dir = Some(dir);
-        self
-    }
-
     /// Configure streaming mode for progressive draft updates.
     pub fn with_streaming(
         mut self,
@@ -870,10 +738,9 @@ Allowlist Telegram username (without '@') or numeric user ID.",
 
     /// Attempt to parse a Telegram update as a document/photo attachment.
     ///
-    /// Downloads the file to `{workspace_dir}/telegram_files/` and returns a
-    /// `ChannelMessage` with the local file path. Returns `None` if the message
-    /// is not an attachment, workspace_dir is not configured, or the file exceeds
-    /// size limits.
+    /// Downloads the file and returns a `ChannelMessage` carrying it as an
+    /// [`Attachment`]; the runtime stores it in the workspace inbox. Returns
+    /// `None` if the message is not an attachment or the file exceeds size limits.
     async fn try_parse_attachment_message(
         &self,
         update: &serde_json::Value,
@@ -925,18 +792,6 @@ Allowlist Telegram username (without '@') or numeric user ID.",
             chat_id.clone()
         };
 
-        // Ensure workspace directory is configured
-        let workspace = self.workspace_dir.as_ref().or_else(|| {
-            tracing::warn!("Cannot save attachment: workspace_dir not configured");
-            None
-        })?;
-
-        let save_dir = workspace.join("telegram_files");
-        if let Err(e) = tokio::fs::create_dir_all(&save_dir).await {
-            tracing::warn!("Failed to create telegram_files directory: {e}");
-            return None;
-        }
-
         // Download file from Telegram
         let tg_file_path = match self.get_file_path(&attachment.file_id).await {
             Ok(p) => p,
@@ -954,8 +809,8 @@ Allowlist Telegram username (without '@') or numeric user ID.",
             }
         };
 
-        // Determine local filename
-        let local_filename = match &attachment.file_name {
+        // Determine file name
+        let file_name = match &attachment.file_name {
             Some(name) => name.clone(),
             None => {
                 // For photos, derive extension from Telegram file path
@@ -964,33 +819,18 @@ Allowlist Telegram username (without '@') or numeric user ID.",
             }
         };
 
-        let local_path = save_dir.join(&local_filename);
-        if let Err(e) = tokio::fs::write(&local_path, &file_data).await {
-            tracing::warn!("Failed to save attachment to {}: {e}", local_path.display());
-            return None;
-        }
-
-        // Build message content.
-        // Photos use [IMAGE:] marker so the multimodal pipeline validates
+        // Photos become image attachments so the multimodal pipeline validates
         // vision capability and rejects unsupported providers early.
-        let mut content = match attachment.kind {
-            IncomingAttachmentKind::Document => {
-                format!("[Document: {}] {}", local_filename, local_path.display())
-            }
-            IncomingAttachmentKind::Photo => {
-                format!("[IMAGE:{}]", local_path.display())
-            }
-        };
-        if let Some(caption) = &attachment.caption {
-            if !caption.is_empty() {
-                use std::fmt::Write;
-                let _ = write!(content, "\n\n{caption}");
-            }
+        let mut file = Attachment::from_bytes(file_name, file_data);
+        if attachment.kind == IncomingAttachmentKind::Photo {
+            file = file.with_kind(AttachmentKind::Image);
         }
 
+        let mut content = attachment.caption.clone().unwrap_or_default();
+
         // Prepend reply context if replying to another message
         if let Some(quote) = self.extract_reply_context(message) {
-            content = format!("{quote}\n\n{content}");
+            content = format!("{quote}\n\n{content}").trim_end().to_string();
         }
 
         Some(ChannelMessage {
@@ -1004,6 +844,7 @@ Allowlist Telegram username (without '@') or numeric user ID.",
                 .unwrap_or_default()
                 .as_secs(),
             thread_ts: None,
+            attachments: vec![file],
         })
     }
 
@@ -1121,6 +962,7 @@ Allowlist Telegram username (without '@') or numeric user ID.",
                 .unwrap_or_default()
                 .as_secs(),
             thread_ts: None,
+            attachments: Vec::new(),
         })
     }
 
@@ -1277,6 +1119,7 @@ Allowlist Telegram username (without '@') or numeric user ID.",
                 .unwrap_or_default()
                 .as_secs(),
             thread_ts: None,
+            attachments: Vec::new(),
         })
     }
 
@@ -1617,71 +1460,78 @@ Allowlist Telegram username (without '@') or numeric user ID.",
         &self,
         chat_id: &str,
         thread_id: Option<&str>,
-        attachment: &TelegramAttachment,
+        attachment: &Attachment,
     ) -> anyhow::Result<()> {
-        let target = attachment.target.trim();
+        let path = match &attachment.source {
+            AttachmentSource::Url(url) => {
+                let target = url.trim();
+                let result = match attachment.kind {
+                    AttachmentKind::Image => {
+                        self.send_photo_by_url(chat_id, thread_id, target, None)
+                            .await
+                    }
+                    AttachmentKind::Document => {
+                        self.send_document_by_url(chat_id, thread_id, target, None)
+                            .await
+                    }
+                    AttachmentKind::Video => {
+                        self.send_video_by_url(chat_id, thread_id, target, None)
+                            .await
+                    }
+                    AttachmentKind::Audio => {
+                        self.send_audio_by_url(chat_id, thread_id, target, None)
+                            .await
+                    }
+                    AttachmentKind::Voice => {
+                        self.send_voice_by_url(chat_id, thread_id, target, None)
+                            .await
+                    }
+                };
 
-        if is_http_url(target) {
-            let result = match attachment.kind {
-                TelegramAttachmentKind::Image => {
-                    self.send_photo_by_url(chat_id, thread_id, target, None)
-                        .await
-                }
-                TelegramAttachmentKind::Document => {
-                    self.send_document_by_url(chat_id, thread_id, target, None)
-                        .await
-                }
-                TelegramAttachmentKind::Video => {
-                    self.send_video_by_url(chat_id, thread_id, target, None)
-                        .await
-                }
-                TelegramAttachmentKind::Audio => {
-                    self.send_audio_by_url(chat_id, thread_id, target, None)
-                        .await
-                }
-                TelegramAttachmentKind::Voice => {
-                    self.send_voice_by_url(chat_id, thread_id, target, None)
-                        .await
+                // If sending media by URL failed (e.g. Telegram can't fetch the URL,
+                // wrong content type, etc.), fall back to sending the URL as a text link
+                // instead of losing the reply entirely.
+                if let Err(e) = result {
+                    tracing::warn!(
+                        url = target,
+                        error = %e,
+                        "Telegram send media by URL failed; falling back to text link"
+                    );
+                    let fallback_text = format!("{}: {target}", attachment.kind.label());
+                    self.send_text_chunks(&fallback_text, chat_id, thread_id)
+                        .await?;
                 }
-            };
 
-            // If sending media by URL failed (e.g. Telegram can't fetch the URL,
-            // wrong content type, etc.), fall back to sending the URL as a text link
-            // instead of losing the reply entirely.
-            if let Err(e) = result {
-                tracing::warn!(
-                    url = target,
-                    error = %e,
-                    "Telegram send media by URL failed; falling back to text link"
-                );
-                let kind_label = match attachment.kind {
-                    TelegramAttachmentKind::Image => "Image",
-                    TelegramAttachmentKind::Document => "Document",
-                    TelegramAttachmentKind::Video => "Video",
-                    TelegramAttachmentKind::Audio => "Audio",
-                    TelegramAttachmentKind::Voice => "Voice",
+                return Ok(());
+            }
+            AttachmentSource::Bytes(bytes) => {
+                return if attachment.kind == AttachmentKind::Image {
+                    self.send_photo_bytes(chat_id, thread_id, bytes.clone(), &attachment.name, None)
+                        .await
+                } else {
+                    self.send_document_bytes(
+                        chat_id,
+                        thread_id,
+                        bytes.clone(),
+                        &attachment.name,
+                        None,
+                    )
+                    .await
                 };
-                let fallback_text = format!("{kind_label}: {target}");
-                self.send_text_chunks(&fallback_text, chat_id, thread_id)
-                    .await?;
             }
+            AttachmentSource::Path(path) => path.as_path(),
+        };
 
-            return Ok(());
-        }
-
-        let path = Path::new(target);
         if !path.exists() {
-            anyhow::bail!("Telegram attachment path not found: {target}");
+            anyhow::bail!("Telegram attachment path not found: {}", path.display());
         }
 
         match attachment.kind {
-            TelegramAttachmentKind::Image => self.send_photo(chat_id, thread_id, path, None).await,
-            TelegramAttachmentKind::Document => {
-                self.send_document(chat_id, thread_id, path, None).await
-            }
-            TelegramAttachmentKind::Video => self.send_video(chat_id, thread_id, path, None).await,
-            TelegramAttachmentKind::Audio => self.send_audio(chat_id, thread_id, path, None).await,
-            TelegramAttachmentKind::Voice => self.send_voice(chat_id, thread_id, path, None).await,
+            AttachmentKind::Image => self.send_photo(chat_id, thread_id, path, None).await,
+            AttachmentKind::Document => self.send_document(chat_id, thread_id, path, None).await,
+            AttachmentKind::Video => self.send_video(chat_id, thread_id, path, None).await,
+            AttachmentKind::Audio => self.send_audio(chat_id, thread_id, path, None).await,
+            AttachmentKind::Voice => self.send_voice(chat_id, thread_id, path, None).await,
         }
     }
 
@@ -2100,6 +1950,14 @@ impl Channel for TelegramChannel {
         "telegram"
     }
 
+    fn supports_attachments(&self) -> bool {
+        true
+    }
+
+    fn supports_voice_replies(&self) -> bool {
+        true
+    }
+
     fn supports_draft_updates(&self) -> bool {
         self.stream_mode != StreamMode::Off
     }
@@ -2353,12 +2211,12 @@ impl Channel for TelegramChannel {
             None => (message.recipient.as_str(), None),
         };
 
-        let (text_without_markers, attachments) = parse_attachment_markers(&content);
+        // Withheld markers are replaced by a note in `text`.
+        let (text, attachments) = collect_outgoing(&content, &message.attachments);
 
         if !attachments.is_empty() {
-            if !text_without_markers.is_empty() {
-                self.send_text_chunks(&text_without_markers, chat_id, thread_id)
-                    .await?;
+            if !text.is_empty() {
+                self.send_text_chunks(&text, chat_id, thread_id).await?;
             }
 
             for attachment in &attachments {
@@ -2374,7 +2232,7 @@ impl Channel for TelegramChannel {
             return Ok(());
         }
 
-        self.send_text_chunks(&content, chat_id, thread_id).await
+        self.send_text_chunks(&text, chat_id, thread_id).await
     }
 
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
@@ -2836,54 +2694,6 @@ mod tests {
         assert_eq!(TelegramChannel::extract_bind_code("/start"), None);
     }
 
-    #[test]
-    fn parse_attachment_markers_extracts_multiple_types() {
-        let message = "Here are files [IMAGE:/tmp/a.png] and [DOCUMENT:https://example.com/a.pdf]";
-        let (cleaned, attachments) = parse_attachment_markers(message);
-
-        assert_eq!(cleaned, "Here are files  and");
-        assert_eq!(attachments.len(), 2);
-        assert_eq!(attachments[0].kind, TelegramAttachmentKind::Image);
-        assert_eq!(attachments[0].target, "/tmp/a.png");
-        assert_eq!(attachments[1].kind, TelegramAttachmentKind::Document);
-        assert_eq!(attachments[1].target, "https://example.com/a.pdf");
-    }
-
-    #[test]
-    fn parse_attachment_markers_keeps_invalid_markers_in_text() {
-        let message = "Report [UNKNOWN:/tmp/a.bin]";
-        let (cleaned, attachments) = parse_attachment_markers(message);
-
-        assert_eq!(cleaned, "Report [UNKNOWN:/tmp/a.bin]");
-        assert!(attachments.is_empty());
-    }
-
-    #[test]
-    fn parse_path_only_attachment_detects_existing_file() {
-        let dir = tempfile::tempdir().unwrap();
-        let image_path = dir.path().join("snap.png");
-        std::fs::write(&image_path, b"fake-png").unwrap();
-
-        let parsed = parse_path_only_attachment(image_path.to_string_lossy().as_ref())
-            .expect("expected attachment");
-
-        assert_eq!(parsed.kind, TelegramAttachmentKind::Image);
-        assert_eq!(parsed.target, image_path.to_string_lossy());
-    }
-
-    #[test]
-    fn parse_path_only_attachment_rejects_sentence_text() {
-        assert!(parse_path_only_attachment("Screenshot saved to /tmp/snap.png").is_none());
-    }
-
-    #[test]
-    fn infer_attachment_kind_from_target_detects_document_extension() {
-        assert_eq!(
-            infer_attachment_kind_from_target("https://example.com/files/specs.pdf?download=1"),
-            Some(TelegramAttachmentKind::Document)
-        );
-    }
-
     #[test]
     fn parse_update_message_uses_chat_id_as_reply_target() {
         let ch = TelegramChannel::new("token".into(), vec!["*".into()], false);
@@ -4095,16 +3905,6 @@ mod tests {
         assert!(TelegramChannel::parse_attachment_metadata(&message).is_none());
     }
 
-    #[test]
-    fn with_workspace_dir_sets_field() {
-        let ch = TelegramChannel::new("fake-token".into(), vec!["*".into()], false)
-            .with_workspace_dir(std::path::PathBuf::from("/tmp/test_workspace"));
-        assert_eq!(
-            ch.workspace_dir.as_deref(),
-            Some(std::path::Path::new("/tmp/test_workspace"))
-        );
-    }
-
     #[test]
     fn telegram_max_file_download_bytes_is_20mb() {
         assert_eq!(TELEGRAM_MAX_FILE_DOWNLOAD_BYTES, 20 * 1024 * 1024);
@@ -4112,59 +3912,41 @@ mod tests {
 
     // ── Attachment content format tests ──────────────────────────────
 
-    /// Photo attachments must use `[IMAGE:/path]` marker so the multimodal
-    /// pipeline validates vision capability on the provider.
-    #[test]
-    fn attachment_photo_content_uses_image_marker() {
-        let att = IncomingAttachment {
-            file_id: "photo_file_id".into(),
-            file_name: None,
-            file_size: Some(5000),
-            caption: None,
-            kind: IncomingAttachmentKind::Photo,
-        };
+    /// Photo attachments must render as an `[IMAGE:/path]` marker so the
+    /// multimodal pipeline validates vision capability on the provider.
+    #[tokio::test]
+    async fn attachment_photo_content_uses_image_marker() {
+        let workspace = tempfile::tempdir().unwrap();
+        let inbox = crate::channels::attachments::AttachmentInbox::new(
+            workspace.path(),
+            &crate::config::ChannelAttachmentsConfig::default(),
+        );
+        let photo = Attachment::from_bytes("photo_123_45", vec![0xff, 0xd8])
+            .with_kind(AttachmentKind::Image);
 
-        let local_path = std::path::Path::new("/tmp/workspace/photo_123_45.jpg");
-        let local_filename = "photo_123_45.jpg";
+        let stored = inbox.store("telegram", &photo).await.unwrap();
+        let content = inbox.render(&stored);
 
-        let content = match att.kind {
-            IncomingAttachmentKind::Document => {
-                format!("[Document: {}] {}", local_filename, local_path.display())
-            }
-            IncomingAttachmentKind::Photo => {
-                format!("[IMAGE:{}]", local_path.display())
-            }
-        };
-
-        assert_eq!(content, "[IMAGE:/tmp/workspace/photo_123_45.jpg]");
-        assert!(content.starts_with("[IMAGE:"));
-        assert!(content.ends_with(']'));
+        assert!(content.starts_with("[IMAGE:/"));
+        assert!(content.ends_with("-photo_123_45]"));
     }
 
-    /// Document attachments keep `[Document: name] /path` format.
-    #[test]
-    fn attachment_document_content_uses_document_label() {
-        let att = IncomingAttachment {
-            file_id: "doc_file_id".into(),
-            file_name: Some("report.pdf".into()),
-            file_size: Some(12345),
-            caption: None,
-            kind: IncomingAttachmentKind::Document,
-        };
-
-        let local_path = std::path::Path::new("/tmp/workspace/report.pdf");
-        let local_filename = att.file_name.as_deref().unwrap();
+    /// Document attachments render as a labelled, workspace-relative path.
+    #[tokio::test]
+    async fn attachment_document_content_uses_document_label() {
+        let workspace = tempfile::tempdir().unwrap();
+        let inbox = crate::channels::attachments::AttachmentInbox::new(
+            workspace.path(),
+            &crate::config::ChannelAttachmentsConfig::default(),
+        );
+        let doc = Attachment::from_bytes("report.pdf", b"%PDF-1.4".to_vec());
 
-        let content = match att.kind {
-            IncomingAttachmentKind::Document => {
-                format!("[Document: {}] {}", local_filename, local_path.display())
-            }
-            IncomingAttachmentKind::Photo => {
-                format!("[IMAGE:{}]", local_path.display())
-            }
-        };
+        let stored = inbox.store("telegram", &doc).await.unwrap();
+        let content = inbox.render(&stored);
 
-        assert_eq!(content, "[Document: report.pdf] /tmp/workspace/report.pdf");
+        assert!(
+            content.starts_with("[Document: report.pdf (application/pdf, 8 B)] inbox/telegram/")
+        );
         assert!(!content.contains("[IMAGE:"));
     }
 
@@ -1,3 +1,4 @@
+use super::attachments::Attachment;
 use async_trait::async_trait;
 
 /// A message received from or sent to a channel
@@ -12,6 +13,9 @@ pub struct ChannelMessage {
     /// Platform thread identifier (e.g. Slack `ts`, Discord thread ID).
     /// When set, replies should be posted as threaded responses.
     pub thread_ts: Option<String>,
+    /// Files received with the message. The runtime stores them in the
+    /// workspace inbox before the agent sees the message.
+    pub attachments: Vec<Attachment>,
 }
 
 /// Message to send through a channel
@@ -22,6 +26,8 @@ pub struct SendMessage {
     pub subject: Option<String>,
     /// Platform thread identifier for threaded replies (e.g. Slack `thread_ts`).
     pub thread_ts: Option<String>,
+    /// Files to deliver alongside `content`.
+    pub attachments: Vec<Attachment>,
 }
 
 impl SendMessage {
@@ -32,6 +38,7 @@ impl SendMessage {
             recipient: recipient.into(),
             subject: None,
             thread_ts: None,
+            attachments: Vec::new(),
         }
     }
 
@@ -46,6 +53,7 @@ impl SendMessage {
             recipient: recipient.into(),
             subject: Some(subject.into()),
             thread_ts: None,
+            attachments: Vec::new(),
         }
     }
 
@@ -54,6 +62,12 @@ impl SendMessage {
         self.thread_ts = thread_ts;
         self
     }
+
+    /// Attach files to the message.
+    pub fn with_attachments(mut self, attachments: Vec<Attachment>) -> Self {
+        self.attachments = attachments;
+        self
+    }
 }
 
 /// Core channel trait — implement for any messaging platform
@@ -89,6 +103,17 @@ pub trait Channel: Send + Sync {
         false
     }
 
+    /// Whether this channel uploads attachments and `[KIND:target]` markers.
+    /// Replies to other channels carry their markers as plain text instead.
+    fn supports_attachments(&self) -> bool {
+        false
+    }
+
+    /// Whether this channel can deliver synthesized speech as an audio attachment.
+    fn supports_voice_replies(&self) -> bool {
+        false
+    }
+
     /// Send an initial draft message. Returns a platform-specific message ID for later edits.
     async fn send_draft(&self, _message: &SendMessage) -> anyhow::Result<Option<String>> {
         Ok(None)
@@ -172,6 +197,7 @@ mod tests {
                 channel: "dummy".into(),
                 timestamp: 123,
                 thread_ts: None,
+                attachments: Vec::new(),
             })
             .await
             .map_err(|e| anyhow::anyhow!(e.to_string()))
@@ -188,6 +214,7 @@ mod tests {
             channel: "dummy".into(),
             timestamp: 999,
             thread_ts: None,
+            attachments: Vec::new(),
         };
 
         let cloned = message.clone();
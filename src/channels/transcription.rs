@@ -1,6 +1,9 @@
 use anyhow::{bail, Context, Result};
 use reqwest::multipart::{Form, Part};
+use std::process::Stdio;
 
+use super::attachments::AttachmentKind;
+use super::traits::ChannelMessage;
 use crate::config::TranscriptionConfig;
 
 /// Maximum upload size accepted by the Groq Whisper API (25 MB).
@@ -20,6 +23,22 @@ fn mime_for_audio(extension: &str) -> Option<&'static str> {
     }
 }
 
+/// File extension for an audio MIME type, ignoring parameters such as
+/// `; codecs=opus`.
+fn extension_for_mime(mime: &str) -> Option<&'static str> {
+    let essence = mime.split(';').next().unwrap_or("").trim();
+    match essence.to_ascii_lowercase().as_str() {
+        "audio/flac" | "audio/x-flac" => Some("flac"),
+        "audio/mpeg" | "audio/mp3" => Some("mp3"),
+        "audio/mp4" | "audio/x-m4a" | "audio/m4a" => Some("m4a"),
+        "audio/ogg" => Some("ogg"),
+        "audio/opus" => Some("opus"),
+        "audio/wav" | "audio/x-wav" | "audio/wave" => Some("wav"),
+        "audio/webm" => Some("webm"),
+        _ => None,
+    }
+}
+
 /// Normalize audio filename for Whisper-compatible APIs.
 ///
 /// Groq validates the filename extension — `.oga` (Opus-in-Ogg) is not in
@@ -31,11 +50,12 @@ fn normalize_audio_filename(file_name: &str) -> String {
     }
 }
 
-/// Transcribe audio bytes via a Whisper-compatible transcription API.
+/// Transcribe audio bytes with the backend selected in `config.provider`.
 ///
-/// Returns the transcribed text on success.  Requires `GROQ_API_KEY` in the
-/// environment.  The caller is responsible for enforcing duration limits
-/// *before* downloading the file; this function enforces the byte-size cap.
+/// Returns the transcribed text on success.  The `groq` backend requires
+/// `GROQ_API_KEY` in the environment; the whisper.cpp backends run locally.
+/// The caller is responsible for enforcing duration limits *before*
+/// downloading the file; this function enforces the byte-size cap.
 pub async fn transcribe_audio(
     audio_data: Vec<u8>,
     file_name: &str,
@@ -59,6 +79,23 @@ pub async fn transcribe_audio(
         )
     })?;
 
+    match config.provider.as_str() {
+        "groq" => transcribe_api(audio_data, normalized_name, mime, config).await,
+        "whisper_server" => transcribe_whisper_server(audio_data, normalized_name, mime, config).await,
+        "whisper_cli" => transcribe_whisper_cli(&audio_data, &normalized_name, config).await,
+        other => bail!(
+            "Unknown transcription provider '{other}' — expected 'groq', 'whisper_server' or 'whisper_cli'"
+        ),
+    }
+}
+
+/// Transcribe via a Whisper-compatible `/audio/transcriptions` API.
+async fn transcribe_api(
+    audio_data: Vec<u8>,
+    file_name: String,
+    mime: &str,
+    config: &TranscriptionConfig,
+) -> Result<String> {
     let api_key = std::env::var("GROQ_API_KEY").context(
         "GROQ_API_KEY environment variable is not set — required for voice transcription",
     )?;
@@ -66,7 +103,7 @@ pub async fn transcribe_audio(
     let client = crate::config::build_runtime_proxy_client("transcription.groq");
 
     let file_part = Part::bytes(audio_data)
-        .file_name(normalized_name)
+        .file_name(file_name)
         .mime_str(mime)?;
 
     let mut form = Form::new()
@@ -105,6 +142,187 @@ pub async fn transcribe_audio(
     Ok(text)
 }
 
+/// Transcribe via a local whisper.cpp server (`whisper-server`).
+async fn transcribe_whisper_server(
+    audio_data: Vec<u8>,
+    file_name: String,
+    mime: &str,
+    config: &TranscriptionConfig,
+) -> Result<String> {
+    let file_part = Part::bytes(audio_data)
+        .file_name(file_name)
+        .mime_str(mime)?;
+
+    // whisper.cpp defaults to English; "auto" enables language detection.
+    let form = Form::new()
+        .part("file", file_part)
+        .text("response_format", "json")
+        .text("temperature", "0.0")
+        .text(
+            "language",
+            config.language.clone().unwrap_or_else(|| "auto".into()),
+        );
+
+    let resp = reqwest::Client::new()
+        .post(&config.whisper_url)
+        .multipart(form)
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to reach whisper.cpp server at {}",
+                config.whisper_url
+            )
+        })?;
+
+    let status = resp.status();
+    let body: serde_json::Value = resp
+        .json()
+        .await
+        .context("Failed to parse whisper.cpp response")?;
+
+    if !status.is_success() || body.get("error").is_some() {
+        let error_msg = body["error"].as_str().unwrap_or("unknown error");
+        bail!("whisper.cpp server error ({status}): {error_msg}");
+    }
+
+    let text = body["text"]
+        .as_str()
+        .context("whisper.cpp response missing 'text' field")?;
+    Ok(text.trim().to_string())
+}
+
+/// Transcribe by running the whisper.cpp CLI on a temporary copy of the audio.
+async fn transcribe_whisper_cli(
+    audio_data: &[u8],
+    file_name: &str,
+    config: &TranscriptionConfig,
+) -> Result<String> {
+    let model = config
+        .whisper_model
+        .as_deref()
+        .context("transcription.whisper_model is required for the whisper_cli provider")?;
+    let model = shellexpand::tilde(model);
+
+    let dir = std::env::temp_dir().join("zeroclaw-transcription");
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+    let unique = uuid::Uuid::new_v4().simple().to_string();
+    let path = dir.join(format!("{}-{file_name}", &unique[..8]));
+    tokio::fs::write(&path, audio_data)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    let output = tokio::process::Command::new(&config.whisper_path)
+        .arg("--model")
+        .arg(model.as_ref())
+        .arg("--file")
+        .arg(&path)
+        .arg("--language")
+        .arg(config.language.as_deref().unwrap_or("auto"))
+        .arg("--no-timestamps")
+        .arg("--no-prints")
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await;
+    let _ = tokio::fs::remove_file(&path).await;
+    let output =
+        output.with_context(|| format!("Failed to start whisper.cpp ({})", config.whisper_path))?;
+
+    if !output.status.success() {
+        bail!(
+            "whisper.cpp failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(text)
+}
+
+/// Transcribe the audio attachments of `msg` and append each transcript as a
+/// `[Voice] ...` line.
+///
+/// This is the shared ingest path for every channel that delivers voice notes
+/// as attachments. The attachments themselves are kept so the agent can still
+/// reach the file. Clips longer than `max_duration_secs` or that fail to
+/// transcribe are logged and left as they are.
+pub async fn transcribe_attachments(msg: &mut ChannelMessage, config: &TranscriptionConfig) {
+    if !config.enabled {
+        return;
+    }
+
+    let mut transcripts = Vec::new();
+    for attachment in &msg.attachments {
+        if !matches!(
+            attachment.kind,
+            AttachmentKind::Voice | AttachmentKind::Audio
+        ) {
+            continue;
+        }
+        if let Some(duration) = attachment.duration_secs {
+            if duration > config.max_duration_secs {
+                tracing::info!(
+                    channel = %msg.channel,
+                    "Skipping transcription of '{}': duration {duration}s exceeds limit {}s",
+                    attachment.name,
+                    config.max_duration_secs
+                );
+                continue;
+            }
+        }
+
+        let audio = match attachment.read_bytes(MAX_AUDIO_BYTES as u64).await {
+            Ok(audio) => audio,
+            Err(e) => {
+                tracing::warn!(channel = %msg.channel, "Failed to read audio for transcription: {e:#}");
+                continue;
+            }
+        };
+
+        // Platforms often name voice notes without an extension; fall back to
+        // the MIME type so the backend knows the container format.
+        let has_audio_extension = attachment
+            .name
+            .rsplit_once('.')
+            .is_some_and(|(_, ext)| mime_for_audio(ext).is_some());
+        let file_name = match extension_for_mime(&attachment.mime) {
+            Some(ext) if !has_audio_extension => format!("{}.{ext}", attachment.name),
+            _ => attachment.name.clone(),
+        };
+
+        match transcribe_audio(audio, &file_name, config).await {
+            Ok(text) if !text.trim().is_empty() => {
+                transcripts.push(format!("[Voice] {}", text.trim()));
+            }
+            Ok(_) => tracing::info!(
+                channel = %msg.channel,
+                "Voice transcription returned empty text, skipping"
+            ),
+            Err(e) => {
+                tracing::warn!(channel = %msg.channel, "Voice transcription failed: {e:#}");
+            }
+        }
+    }
+
+    if transcripts.is_empty() {
+        return;
+    }
+    let transcripts = transcripts.join("\n");
+    msg.content = if msg.content.trim().is_empty() {
+        transcripts
+    } else {
+        format!("{}\n\n{transcripts}", msg.content)
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -215,4 +433,149 @@ mod tests {
             "error should mention the rejected extension, got: {msg}"
         );
     }
+
+    #[tokio::test]
+    async fn rejects_unknown_provider() {
+        let config = TranscriptionConfig {
+            provider: "nope".into(),
+            ..TranscriptionConfig::default()
+        };
+        let err = transcribe_audio(vec![0u8; 10], "voice.ogg", &config)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Unknown transcription provider"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn extension_for_mime_ignores_parameters() {
+        assert_eq!(extension_for_mime("audio/ogg; codecs=opus"), Some("ogg"));
+        assert_eq!(extension_for_mime("audio/x-m4a"), Some("m4a"));
+        assert_eq!(extension_for_mime("audio/aac"), None);
+    }
+
+    #[tokio::test]
+    async fn whisper_server_sends_language_hint() {
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/inference"))
+            .and(body_string_contains("name=\"language\"\r\n\r\nde"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"text": " Hallo Welt\n"})),
+            )
+            .mount(&server)
+            .await;
+
+        let config = TranscriptionConfig {
+            provider: "whisper_server".into(),
+            whisper_url: format!("{}/inference", server.uri()),
+            language: Some("de".into()),
+            ..TranscriptionConfig::default()
+        };
+        let text = transcribe_audio(vec![0u8; 10], "voice.ogg", &config)
+            .await
+            .unwrap();
+        assert_eq!(text, "Hallo Welt");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn whisper_cli_reads_transcript_from_stdout() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let script = tmp.path().join("whisper-cli");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\n\
+             case \"$*\" in *'--language auto'*'--no-timestamps'*) ;; *) exit 3 ;; esac\n\
+             printf ' first line\\n second line\\n'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let config = TranscriptionConfig {
+            provider: "whisper_cli".into(),
+            whisper_path: script.display().to_string(),
+            whisper_model: Some("ggml-base.bin".into()),
+            ..TranscriptionConfig::default()
+        };
+        let text = transcribe_audio(vec![0u8; 10], "voice.wav", &config)
+            .await
+            .unwrap();
+        assert_eq!(text, "first line second line");
+
+        let config = TranscriptionConfig {
+            whisper_model: None,
+            ..config
+        };
+        let err = transcribe_audio(vec![0u8; 10], "voice.wav", &config)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("whisper_model"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn transcribe_attachments_appends_voice_lines() {
+        use crate::channels::attachments::Attachment;
+        use wiremock::matchers::{body_string_contains, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        // The MIME type supplies the extension for an extensionless name.
+        Mock::given(method("POST"))
+            .and(body_string_contains("filename=\"voice-1.ogg\""))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"text": "turn on the lights"})),
+            )
+            .mount(&server)
+            .await;
+
+        let config = TranscriptionConfig {
+            enabled: true,
+            provider: "whisper_server".into(),
+            whisper_url: server.uri(),
+            max_duration_secs: 60,
+            ..TranscriptionConfig::default()
+        };
+        let mut msg = ChannelMessage {
+            id: "1".into(),
+            sender: "alice".into(),
+            reply_target: "alice".into(),
+            content: "[Voice: voice-1 (audio/ogg, 4 B)] inbox/voice-1".into(),
+            channel: "signal".into(),
+            timestamp: 0,
+            thread_ts: None,
+            attachments: vec![
+                Attachment::from_bytes("voice-1", vec![0; 4])
+                    .with_mime("audio/ogg")
+                    .with_kind(AttachmentKind::Voice)
+                    .with_duration(3),
+                Attachment::from_bytes("lecture.mp3", vec![0; 4]).with_duration(3600),
+                Attachment::from_bytes("notes.txt", b"hi".to_vec()),
+            ],
+        };
+
+        transcribe_attachments(&mut msg, &config).await;
+        assert_eq!(
+            msg.content,
+            "[Voice: voice-1 (audio/ogg, 4 B)] inbox/voice-1\n\n[Voice] turn on the lights"
+        );
+        assert_eq!(msg.attachments.len(), 3, "attachments are kept");
+
+        let before = msg.content.clone();
+        let disabled = TranscriptionConfig {
+            enabled: false,
+            ..config
+        };
+        transcribe_attachments(&mut msg, &disabled).await;
+        assert_eq!(msg.content, before);
+    }
 }
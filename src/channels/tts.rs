@@ -0,0 +1,292 @@
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+
+use super::attachments::{parse_attachment_markers, AttachmentKind};
+use super::traits::ChannelMessage;
+use crate::config::{TtsConfig, TtsReplyMode};
+
+/// Whether the user spoke to us: an audio attachment or a transcribed voice note.
+pub fn is_voice_message(msg: &ChannelMessage) -> bool {
+    msg.attachments
+        .iter()
+        .any(|a| matches!(a.kind, AttachmentKind::Voice | AttachmentKind::Audio))
+        || msg
+            .content
+            .lines()
+            .any(|line| line.trim_start().starts_with("[Voice] "))
+}
+
+/// Whether a reply to `msg` should include synthesized speech.
+pub fn wants_voice_reply(config: &TtsConfig, msg: &ChannelMessage) -> bool {
+    match config.reply_mode(&msg.channel) {
+        TtsReplyMode::Never => false,
+        TtsReplyMode::OnVoice => is_voice_message(msg),
+        TtsReplyMode::Always => true,
+    }
+}
+
+/// Reply text as it should be spoken: media markers, code blocks and
+/// Markdown emphasis are dropped.
+pub fn speakable_text(text: &str) -> String {
+    let (text, _) = parse_attachment_markers(text);
+    let mut spoken = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let line: String = line
+            .chars()
+            .filter(|c| !matches!(c, '*' | '_' | '`' | '#' | '>'))
+            .collect();
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !line.is_empty() {
+            spoken.push(line);
+        }
+    }
+    spoken.join("\n")
+}
+
+/// File extension for an OpenAI `response_format`.
+fn extension_for_format(format: &str) -> &'static str {
+    match format.to_ascii_lowercase().as_str() {
+        // Opus comes back in an Ogg container — a voice note on most platforms.
+        "opus" => "ogg",
+        "aac" => "aac",
+        "flac" => "flac",
+        "wav" => "wav",
+        "pcm" => "pcm",
+        _ => "mp3",
+    }
+}
+
+/// Synthesize `text` into an audio file under `out_dir` and return its path.
+///
+/// Fails if the text is empty after cleanup or longer than `max_chars`; the
+/// caller then keeps the reply text-only.
+pub async fn synthesize_speech(text: &str, config: &TtsConfig, out_dir: &Path) -> Result<PathBuf> {
+    let text = speakable_text(text);
+    if text.trim().is_empty() {
+        bail!("Nothing to speak");
+    }
+    let chars = text.chars().count();
+    if chars > config.max_chars {
+        bail!(
+            "Reply too long to speak ({chars} chars, max {})",
+            config.max_chars
+        );
+    }
+
+    tokio::fs::create_dir_all(out_dir)
+        .await
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    let stem = uuid::Uuid::new_v4().simple().to_string();
+
+    match config.provider.as_str() {
+        "openai" => {
+            let path = out_dir.join(format!(
+                "reply-{}.{}",
+                &stem[..8],
+                extension_for_format(&config.format)
+            ));
+            let audio = synthesize_openai(&text, config).await?;
+            tokio::fs::write(&path, audio)
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            Ok(path)
+        }
+        "piper" => {
+            let path = out_dir.join(format!("reply-{}.wav", &stem[..8]));
+            synthesize_piper(&text, config, &path).await?;
+            Ok(path)
+        }
+        other => bail!("Unknown TTS provider '{other}' — expected 'openai' or 'piper'"),
+    }
+}
+
+/// Call an OpenAI-compatible `/audio/speech` endpoint.
+async fn synthesize_openai(text: &str, config: &TtsConfig) -> Result<Vec<u8>> {
+    let api_key = match config.api_key.as_deref().filter(|k| !k.trim().is_empty()) {
+        Some(key) => key.to_string(),
+        None => std::env::var("OPENAI_API_KEY")
+            .context("tts.api_key or OPENAI_API_KEY is required for speech synthesis")?,
+    };
+
+    let client = crate::config::build_runtime_proxy_client("tts.openai");
+    let resp = client
+        .post(&config.api_url)
+        .bearer_auth(&api_key)
+        .json(&serde_json::json!({
+            "model": config.model,
+            "input": text,
+            "voice": config.voice,
+            "response_format": config.format,
+        }))
+        .send()
+        .await
+        .context("Failed to send speech request")?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let body: serde_json::Value = resp.json().await.unwrap_or_default();
+        let error_msg = body["error"]["message"].as_str().unwrap_or("unknown error");
+        bail!("Speech API error ({status}): {error_msg}");
+    }
+
+    let audio = resp
+        .bytes()
+        .await
+        .context("Failed to read speech response")?;
+    if audio.is_empty() {
+        bail!("Speech API returned no audio");
+    }
+    Ok(audio.to_vec())
+}
+
+/// Run the local Piper binary, writing a WAV file to `path`.
+async fn synthesize_piper(text: &str, config: &TtsConfig, path: &Path) -> Result<()> {
+    let model = config
+        .piper_model
+        .as_deref()
+        .context("tts.piper_model is required for the piper provider")?;
+    let model = shellexpand::tilde(model);
+
+    let mut child = tokio::process::Command::new(&config.piper_path)
+        .arg("--model")
+        .arg(model.as_ref())
+        .arg("--output_file")
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start Piper ({})", config.piper_path))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).await?;
+    }
+
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        bail!(
+            "Piper failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channels::attachments::Attachment;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn message(channel: &str, content: &str) -> ChannelMessage {
+        ChannelMessage {
+            id: "1".into(),
+            sender: "alice".into(),
+            reply_target: "alice".into(),
+            content: content.into(),
+            channel: channel.into(),
+            timestamp: 0,
+            thread_ts: None,
+            attachments: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn voice_reply_follows_mode() {
+        let mut config = TtsConfig {
+            enabled: true,
+            ..TtsConfig::default()
+        };
+        let text = message("telegram", "hello");
+        let voice = message("telegram", "[Voice] hello");
+        let mut audio = message("signal", "");
+        audio.attachments = vec![Attachment::from_bytes("note.ogg", vec![0; 4])];
+
+        assert!(!wants_voice_reply(&config, &text));
+        assert!(wants_voice_reply(&config, &voice));
+        assert!(wants_voice_reply(&config, &audio));
+
+        config
+            .channels
+            .insert("telegram".into(), TtsReplyMode::Always);
+        assert!(wants_voice_reply(&config, &text));
+
+        config.enabled = false;
+        assert!(!wants_voice_reply(&config, &voice));
+    }
+
+    #[test]
+    fn speakable_text_drops_markup() {
+        let text =
+            "**Done!** See `notes.md`.\n```rust\nfn main() {}\n```\n[IMAGE:/tmp/a.png]\n## Next";
+        assert_eq!(speakable_text(text), "Done! See notes.md.\nNext");
+    }
+
+    #[tokio::test]
+    async fn openai_speech_is_written_as_voice_note() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/audio/speech"))
+            .and(header("authorization", "Bearer test-key"))
+            .and(body_partial_json(serde_json::json!({
+                "input": "Hello there",
+                "voice": "alloy",
+                "response_format": "opus",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"OggS".to_vec()))
+            .mount(&server)
+            .await;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = TtsConfig {
+            enabled: true,
+            api_url: format!("{}/v1/audio/speech", server.uri()),
+            api_key: Some("test-key".into()),
+            ..TtsConfig::default()
+        };
+        let path = synthesize_speech("Hello *there*", &config, tmp.path())
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"OggS");
+        assert_eq!(
+            Attachment::from_path(&path).kind,
+            AttachmentKind::Voice,
+            "opus replies should be voice notes"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_long_replies_and_missing_piper_model() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = TtsConfig {
+            max_chars: 5,
+            ..TtsConfig::default()
+        };
+        let err = synthesize_speech("far too long", &config, tmp.path())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("too long"), "{err}");
+
+        let config = TtsConfig {
+            provider: "piper".into(),
+            ..TtsConfig::default()
+        };
+        let err = synthesize_speech("hi", &config, tmp.path())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("piper_model"), "{err}");
+    }
+}
@@ -1,7 +1,13 @@
+use super::attachments::{collect_outgoing, Attachment, AttachmentKind, AttachmentSource};
 use super::traits::{Channel, ChannelMessage, SendMessage};
+use anyhow::Context;
 use async_trait::async_trait;
+use reqwest::multipart::{Form, Part};
 use uuid::Uuid;
 
+/// Cloud API upload limit for audio and video (16 MB).
+const MAX_MEDIA_BYTES: u64 = 16 * 1024 * 1024;
+
 /// `WhatsApp` channel — uses `WhatsApp` Business Cloud API
 ///
 /// This channel operates in webhook mode (push-based) rather than polling.
@@ -48,6 +54,183 @@ impl WhatsAppChannel {
         crate::config::build_runtime_proxy_client("channel.whatsapp")
     }
 
+    /// POST a message object to the Cloud API.
+    async fn post_message(&self, body: &serde_json::Value) -> anyhow::Result<()> {
+        // WhatsApp Cloud API: POST to /v18.0/{phone_number_id}/messages
+        let url = format!(
+            "https://graph.facebook.com/v18.0/{}/messages",
+            self.endpoint_id
+        );
+
+        ensure_https(&url)?;
+
+        let resp = self
+            .http_client()
+            .post(&url)
+            .bearer_auth(&self.access_token)
+            .header("Content-Type", "application/json")
+            .json(body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let error_body = resp.text().await.unwrap_or_default();
+            tracing::error!("WhatsApp send failed: {status} — {error_body}");
+            anyhow::bail!("WhatsApp API error: {status}");
+        }
+
+        Ok(())
+    }
+
+    /// Upload a file to the Cloud API media store and return its media ID.
+    async fn upload_media(&self, attachment: &Attachment) -> anyhow::Result<String> {
+        let bytes = attachment.read_bytes(MAX_MEDIA_BYTES).await?;
+        let url = format!(
+            "https://graph.facebook.com/v18.0/{}/media",
+            self.endpoint_id
+        );
+        ensure_https(&url)?;
+
+        let form = Form::new()
+            .text("messaging_product", "whatsapp")
+            .text("type", attachment.mime.clone())
+            .part(
+                "file",
+                Part::bytes(bytes)
+                    .file_name(attachment.name.clone())
+                    .mime_str(&attachment.mime)?,
+            );
+        let resp = self
+            .http_client()
+            .post(&url)
+            .bearer_auth(&self.access_token)
+            .multipart(form)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let error_body = resp.text().await.unwrap_or_default();
+            tracing::error!("WhatsApp media upload failed: {status} — {error_body}");
+            anyhow::bail!("WhatsApp media upload error: {status}");
+        }
+
+        let body: serde_json::Value = resp.json().await?;
+        body["id"]
+            .as_str()
+            .map(str::to_string)
+            .context("WhatsApp media upload returned no id")
+    }
+
+    /// Message object for an attachment: remote files by link, others uploaded first.
+    async fn media_message(
+        &self,
+        to: &str,
+        attachment: &Attachment,
+    ) -> anyhow::Result<serde_json::Value> {
+        let kind = match attachment.kind {
+            AttachmentKind::Image => "image",
+            AttachmentKind::Video => "video",
+            AttachmentKind::Audio | AttachmentKind::Voice => "audio",
+            AttachmentKind::Document => "document",
+        };
+        let mut media = match attachment.url() {
+            Some(url) => serde_json::json!({ "link": url }),
+            None => serde_json::json!({ "id": self.upload_media(attachment).await? }),
+        };
+        if attachment.kind == AttachmentKind::Document {
+            media["filename"] = serde_json::json!(attachment.name);
+        }
+        Ok(serde_json::json!({
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": to,
+            "type": kind,
+            kind: media,
+        }))
+    }
+
+    /// Audio attachment for an `audio` webhook message, pointing at the Graph
+    /// API media object. Voice notes are flagged with `"voice": true`.
+    fn parse_audio(msg: &serde_json::Value) -> Option<Attachment> {
+        let audio = msg.get("audio")?;
+        let id = audio.get("id").and_then(|id| id.as_str())?;
+        let mut file = Attachment::from_url(format!("https://graph.facebook.com/v18.0/{id}"))
+            .with_name(format!("whatsapp-{id}"));
+        if let Some(mime) = audio.get("mime_type").and_then(|m| m.as_str()) {
+            file = file.with_mime(mime);
+        }
+        let voice = audio.get("voice").and_then(serde_json::Value::as_bool) == Some(true);
+        Some(file.with_kind(if voice {
+            AttachmentKind::Voice
+        } else {
+            AttachmentKind::Audio
+        }))
+    }
+
+    /// Download the media referenced by `msg`'s attachments.
+    ///
+    /// Cloud API media URLs need the access token, so the generic attachment
+    /// download cannot fetch them. Attachments that fail to download are
+    /// dropped with a warning.
+    pub async fn download_media(&self, msg: &mut ChannelMessage) {
+        let mut downloaded = Vec::with_capacity(msg.attachments.len());
+        for attachment in msg.attachments.drain(..) {
+            let Some(url) = attachment.url() else {
+                downloaded.push(attachment);
+                continue;
+            };
+            match self.fetch_media(url).await {
+                Ok(bytes) => downloaded.push(Attachment {
+                    size: Some(bytes.len() as u64),
+                    source: AttachmentSource::Bytes(bytes),
+                    ..attachment
+                }),
+                Err(e) => {
+                    tracing::warn!(
+                        "WhatsApp media download failed for {}: {e:#}",
+                        attachment.name
+                    );
+                }
+            }
+        }
+        msg.attachments = downloaded;
+    }
+
+    /// Resolve a media object to its short-lived URL and fetch the content.
+    async fn fetch_media(&self, media_url: &str) -> anyhow::Result<Vec<u8>> {
+        ensure_https(media_url)?;
+        let client = self.http_client();
+        let resp = client
+            .get(media_url)
+            .bearer_auth(&self.access_token)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            anyhow::bail!("WhatsApp media lookup error: {}", resp.status());
+        }
+        let meta: serde_json::Value = resp.json().await?;
+        let url = meta["url"]
+            .as_str()
+            .context("WhatsApp media lookup returned no url")?;
+        ensure_https(url)?;
+
+        let resp = client
+            .get(url)
+            .bearer_auth(&self.access_token)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            anyhow::bail!("WhatsApp media download error: {}", resp.status());
+        }
+        let bytes = resp.bytes().await?;
+        if bytes.len() as u64 > MAX_MEDIA_BYTES {
+            anyhow::bail!("WhatsApp media exceeds {MAX_MEDIA_BYTES} bytes");
+        }
+        Ok(bytes.to_vec())
+    }
+
     /// Check if a phone number is allowed (E.164 format: +1234567890)
     fn is_number_allowed(&self, phone: &str) -> bool {
         self.allowed_numbers.iter().any(|n| n == "*" || n == phone)
@@ -105,20 +288,25 @@ impl WhatsAppChannel {
                         continue;
                     }
 
-                    // Extract text content (support text messages only for now)
+                    // Text messages carry a body; voice notes and audio files
+                    // arrive as a media ID that `download_media` resolves.
+                    let mut attachments = Vec::new();
                     let content = if let Some(text_obj) = msg.get("text") {
                         text_obj
                             .get("body")
                             .and_then(|b| b.as_str())
                             .unwrap_or("")
                             .to_string()
+                    } else if let Some(audio) = Self::parse_audio(msg) {
+                        attachments.push(audio);
+                        String::new()
                     } else {
-                        // Could be image, audio, etc. — skip for now
-                        tracing::debug!("WhatsApp: skipping non-text message from {from}");
+                        // Could be image, video, etc. — skip for now
+                        tracing::debug!("WhatsApp: skipping unsupported message from {from}");
                         continue;
                     };
 
-                    if content.is_empty() {
+                    if content.is_empty() && attachments.is_empty() {
                         continue;
                     }
 
@@ -142,6 +330,7 @@ impl WhatsAppChannel {
                         channel: "whatsapp".to_string(),
                         timestamp,
                         thread_ts: None,
+                        attachments,
                     });
                 }
             }
@@ -157,46 +346,40 @@ impl Channel for WhatsAppChannel {
         "whatsapp"
     }
 
-    async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
-        // WhatsApp Cloud API: POST to /v18.0/{phone_number_id}/messages
-        let url = format!(
-            "https://graph.facebook.com/v18.0/{}/messages",
-            self.endpoint_id
-        );
+    fn supports_attachments(&self) -> bool {
+        true
+    }
+
+    fn supports_voice_replies(&self) -> bool {
+        true
+    }
 
+    async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
         // Normalize recipient (remove leading + if present for API)
         let to = message
             .recipient
             .strip_prefix('+')
             .unwrap_or(&message.recipient);
 
-        let body = serde_json::json!({
-            "messaging_product": "whatsapp",
-            "recipient_type": "individual",
-            "to": to,
-            "type": "text",
-            "text": {
-                "preview_url": false,
-                "body": message.content
-            }
-        });
-
-        ensure_https(&url)?;
-
-        let resp = self
-            .http_client()
-            .post(&url)
-            .bearer_auth(&self.access_token)
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
+        let (text, attachments) = collect_outgoing(&message.content, &message.attachments);
+
+        if !text.is_empty() || attachments.is_empty() {
+            let body = serde_json::json!({
+                "messaging_product": "whatsapp",
+                "recipient_type": "individual",
+                "to": to,
+                "type": "text",
+                "text": {
+                    "preview_url": false,
+                    "body": text
+                }
+            });
+            self.post_message(&body).await?;
+        }
 
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let error_body = resp.text().await.unwrap_or_default();
-            tracing::error!("WhatsApp send failed: {status} — {error_body}");
-            anyhow::bail!("WhatsApp API error: {status}");
+        for attachment in &attachments {
+            let body = self.media_message(to, attachment).await?;
+            self.post_message(&body).await?;
         }
 
         Ok(())
@@ -248,6 +431,20 @@ mod tests {
         )
     }
 
+    #[tokio::test]
+    async fn remote_media_is_sent_by_link() {
+        let ch = make_channel();
+        let voice = Attachment::from_url("https://example.com/reply.ogg");
+        let body = ch.media_message("1234567890", &voice).await.unwrap();
+        assert_eq!(body["type"], "audio");
+        assert_eq!(body["audio"]["link"], "https://example.com/reply.ogg");
+
+        let doc = Attachment::from_url("https://example.com/report.pdf");
+        let body = ch.media_message("1234567890", &doc).await.unwrap();
+        assert_eq!(body["type"], "document");
+        assert_eq!(body["document"]["filename"], "report.pdf");
+    }
+
     #[test]
     fn whatsapp_channel_name() {
         let ch = make_channel();
@@ -736,7 +933,7 @@ mod tests {
     }
 
     #[test]
-    fn whatsapp_parse_audio_message_skipped() {
+    fn whatsapp_parse_audio_message_as_attachment() {
         let ch = WhatsAppChannel::new("tok".into(), "123".into(), "ver".into(), vec!["*".into()]);
         let payload = serde_json::json!({
             "entry": [{
@@ -746,14 +943,27 @@ mod tests {
                             "from": "111",
                             "timestamp": "1",
                             "type": "audio",
-                            "audio": { "id": "audio123", "mime_type": "audio/ogg" }
+                            "audio": {
+                                "id": "audio123",
+                                "mime_type": "audio/ogg; codecs=opus",
+                                "voice": true
+                            }
                         }]
                     }
                 }]
             }]
         });
         let msgs = ch.parse_webhook_payload(&payload);
-        assert!(msgs.is_empty());
+        assert_eq!(msgs.len(), 1);
+        assert!(msgs[0].content.is_empty());
+
+        let audio = &msgs[0].attachments[0];
+        assert_eq!(audio.kind, AttachmentKind::Voice);
+        assert_eq!(audio.mime, "audio/ogg; codecs=opus");
+        assert_eq!(
+            audio.url(),
+            Some("https://graph.facebook.com/v18.0/audio123")
+        );
     }
 
     #[test]
@@ -37,9 +37,11 @@ const SUPPORTED_PROXY_SERVICE_KEYS: &[&str] = &[
     "tool.composio",
     "tool.http_request",
     "tool.pushover",
+    "tool.skill",
     "memory.embeddings",
     "tunnel.custom",
     "transcription.groq",
+    "tts.openai",
 ];
 
 const SUPPORTED_PROXY_SERVICE_SELECTORS: &[&str] = &[
@@ -49,6 +51,7 @@ const SUPPORTED_PROXY_SERVICE_SELECTORS: &[&str] = &[
     "memory.*",
     "tunnel.*",
     "transcription.*",
+    "tts.*",
 ];
 
 static RUNTIME_PROXY_CONFIG: OnceLock<RwLock<ProxyConfig>> = OnceLock::new();
@@ -71,6 +74,13 @@ pub struct Config {
     /// Path to config.toml - computed from home, not serialized
     #[serde(skip)]
     pub config_path: PathBuf,
+    /// Set when the agent runs for a user of the multi-tenant server, not serialized
+    #[serde(skip)]
+    pub tenant_context: Option<TenantContext>,
+    /// Tenant user a supervised container instance acts for (`[tenant]`).
+    /// Written by the container supervisor at launch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<TenantInstanceConfig>,
     /// API key for the selected provider. Overridden by `ZEROCLAW_API_KEY` or `API_KEY` env vars.
     pub api_key: Option<String>,
     /// Base URL override for provider API (e.g. "http://10.0.0.1:11434" for remote Ollama)
@@ -194,6 +204,14 @@ pub struct Config {
     #[serde(default)]
     pub peripherals: PeripheralsConfig,
 
+    /// Robot-kit tools for drive, vision, speech and sensors (`[robot]`).
+    #[serde(default)]
+    pub robot: RobotConfig,
+
+    /// Limits for agent containers created through the server API (`[containers]`).
+    #[serde(default)]
+    pub containers: ContainersConfig,
+
     /// Delegate agent configurations for multi-agent workflows.
     #[serde(default)]
     pub agents: HashMap<String, DelegateAgentConfig>,
@@ -206,9 +224,31 @@ pub struct Config {
     #[serde(default)]
     pub hardware: HardwareConfig,
 
-    /// Voice transcription configuration (Whisper API via Groq).
+    /// Voice transcription configuration (Whisper API or local whisper.cpp).
     #[serde(default)]
     pub transcription: TranscriptionConfig,
+
+    /// Text-to-speech for voice replies on channels that support audio.
+    #[serde(default)]
+    pub tts: TtsConfig,
+}
+
+/// Identity of the multi-tenant server user an agent run acts for.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TenantContext {
+    /// Server data directory holding the tenant stores
+    pub data_dir: PathBuf,
+    pub tenant_id: String,
+    pub user_id: String,
+}
+
+/// Tenant identity and capability ceiling of a supervised container instance.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TenantInstanceConfig {
+    pub tenant_id: String,
+    pub user_id: String,
+    /// Effective ceiling of the owning user when the instance was launched
+    pub ceiling: crate::container::PermissionLevel,
 }
 
 // ── Delegate Agents ──────────────────────────────────────────────
@@ -335,12 +375,28 @@ fn default_transcription_max_duration_secs() -> u64 {
     120
 }
 
-/// Voice transcription configuration (Whisper API via Groq).
+fn default_transcription_provider() -> String {
+    "groq".into()
+}
+
+fn default_whisper_url() -> String {
+    "http://127.0.0.1:8080/inference".into()
+}
+
+fn default_whisper_path() -> String {
+    "whisper-cli".into()
+}
+
+/// Voice transcription configuration (Whisper API or local whisper.cpp).
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TranscriptionConfig {
-    /// Enable voice transcription for channels that support it.
+    /// Transcribe voice notes and audio attachments on every channel.
     #[serde(default)]
     pub enabled: bool,
+    /// Backend: "groq" (any Whisper-compatible API at `api_url`),
+    /// "whisper_server" (whisper.cpp server) or "whisper_cli" (whisper.cpp binary).
+    #[serde(default = "default_transcription_provider")]
+    pub provider: String,
     /// Whisper API endpoint URL.
     #[serde(default = "default_transcription_api_url")]
     pub api_url: String,
@@ -353,17 +409,147 @@ pub struct TranscriptionConfig {
     /// Maximum voice duration in seconds (messages longer than this are skipped).
     #[serde(default = "default_transcription_max_duration_secs")]
     pub max_duration_secs: u64,
+    /// whisper.cpp server inference endpoint (`whisper_server` provider).
+    #[serde(default = "default_whisper_url")]
+    pub whisper_url: String,
+    /// whisper.cpp CLI binary (`whisper_cli` provider).
+    #[serde(default = "default_whisper_path")]
+    pub whisper_path: String,
+    /// GGML model file for the `whisper_cli` provider (e.g. "~/models/ggml-base.bin").
+    #[serde(default)]
+    pub whisper_model: Option<String>,
 }
 
 impl Default for TranscriptionConfig {
     fn default() -> Self {
         Self {
             enabled: false,
+            provider: default_transcription_provider(),
             api_url: default_transcription_api_url(),
             model: default_transcription_model(),
             language: None,
             max_duration_secs: default_transcription_max_duration_secs(),
+            whisper_url: default_whisper_url(),
+            whisper_path: default_whisper_path(),
+            whisper_model: None,
+        }
+    }
+}
+
+// ── Text-to-speech ───────────────────────────────────────────────
+
+fn default_tts_provider() -> String {
+    "openai".into()
+}
+
+fn default_tts_api_url() -> String {
+    "https://api.openai.com/v1/audio/speech".into()
+}
+
+fn default_tts_model() -> String {
+    "gpt-4o-mini-tts".into()
+}
+
+fn default_tts_voice() -> String {
+    "alloy".into()
+}
+
+fn default_tts_format() -> String {
+    "opus".into()
+}
+
+fn default_tts_piper_path() -> String {
+    "piper".into()
+}
+
+fn default_tts_max_chars() -> usize {
+    4000
+}
+
+/// When a channel replies with synthesized speech.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TtsReplyMode {
+    /// Text replies only.
+    Never,
+    /// Add a voice reply when the user sent audio.
+    #[default]
+    OnVoice,
+    /// Add a voice reply to every message.
+    Always,
+}
+
+/// Text-to-speech configuration (`[tts]` section).
+///
+/// Voice-capable channels (Telegram, WhatsApp, Signal, Discord) attach the
+/// spoken reply as audio next to the text reply.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TtsConfig {
+    /// Enable voice replies.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Backend: `"openai"` (any OpenAI-compatible `/audio/speech` endpoint) or `"piper"`.
+    #[serde(default = "default_tts_provider")]
+    pub provider: String,
+    /// Speech endpoint URL for the `openai` provider.
+    #[serde(default = "default_tts_api_url")]
+    pub api_url: String,
+    /// API key for the `openai` provider. Falls back to `OPENAI_API_KEY`.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Speech model for the `openai` provider.
+    #[serde(default = "default_tts_model")]
+    pub model: String,
+    /// Voice name for the `openai` provider.
+    #[serde(default = "default_tts_voice")]
+    pub voice: String,
+    /// Audio format for the `openai` provider (`opus`, `mp3`, `aac`, `flac`, `wav`).
+    /// `opus` is delivered as a voice note where the platform has them.
+    #[serde(default = "default_tts_format")]
+    pub format: String,
+    /// Path to the Piper binary.
+    #[serde(default = "default_tts_piper_path")]
+    pub piper_path: String,
+    /// Piper voice model (`.onnx`), required for the `piper` provider.
+    #[serde(default)]
+    pub piper_model: Option<String>,
+    /// Longest reply (in characters) that is spoken; longer replies stay text-only.
+    #[serde(default = "default_tts_max_chars")]
+    pub max_chars: usize,
+    /// Default reply mode for voice-capable channels.
+    #[serde(default)]
+    pub reply: TtsReplyMode,
+    /// Per-channel reply mode overrides, keyed by channel name (e.g. `telegram`).
+    #[serde(default)]
+    pub channels: HashMap<String, TtsReplyMode>,
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: default_tts_provider(),
+            api_url: default_tts_api_url(),
+            api_key: None,
+            model: default_tts_model(),
+            voice: default_tts_voice(),
+            format: default_tts_format(),
+            piper_path: default_tts_piper_path(),
+            piper_model: None,
+            max_chars: default_tts_max_chars(),
+            reply: TtsReplyMode::default(),
+            channels: HashMap::new(),
+        }
+    }
+}
+
+impl TtsConfig {
+    /// Reply mode for `channel`, honouring per-channel overrides.
+    pub fn reply_mode(&self, channel: &str) -> TtsReplyMode {
+        if !self.enabled {
+            return TtsReplyMode::Never;
         }
+        self.channels.get(channel).copied().unwrap_or(self.reply)
     }
 }
 
@@ -446,6 +632,10 @@ pub struct SkillsConfig {
     /// `full` preserves legacy behavior. `compact` keeps context small and loads skills on demand.
     #[serde(default)]
     pub prompt_injection_mode: SkillsPromptInjectionMode,
+    /// Signature requirement for installed and loaded skills (`SKILL.sig`).
+    /// `require-signed` only accepts skills signed by a trusted publisher.
+    #[serde(default)]
+    pub signature_policy: SkillSignaturePolicy,
 }
 
 impl Default for SkillsConfig {
@@ -454,10 +644,24 @@ impl Default for SkillsConfig {
             open_skills_enabled: false,
             open_skills_dir: None,
             prompt_injection_mode: SkillsPromptInjectionMode::default(),
+            signature_policy: SkillSignaturePolicy::default(),
         }
     }
 }
 
+/// Skill signature verification policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkillSignaturePolicy {
+    /// Reject skills without a valid signature from a trusted publisher.
+    RequireSigned,
+    /// Reject invalid signatures; log unsigned or untrusted skills.
+    #[default]
+    Warn,
+    /// Skip signature verification.
+    Off,
+}
+
 /// Multimodal (image) handling configuration (`[multimodal]` section).
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MultimodalConfig {
@@ -691,6 +895,81 @@ pub struct PeripheralsConfig {
     /// Place .md/.txt files named by board (e.g. nucleo-f401re.md, rpi-gpio.md).
     #[serde(default)]
     pub datasheet_dir: Option<String>,
+    /// Board events the agent reacts to (`[[peripherals.subscriptions]]`)
+    #[serde(default)]
+    pub subscriptions: Vec<HardwareSubscriptionConfig>,
+}
+
+/// Event a board watches for and pushes over the serial protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HardwareEventKind {
+    /// A GPIO pin changes level (`pin`, `edge`).
+    GpioEdge,
+    /// An ADC reading crosses `above` or `below` (`channel`).
+    AdcThreshold,
+    /// An ADC reading every `interval_ms` (`channel`).
+    AdcSample,
+}
+
+/// Which GPIO level changes a `gpio_edge` subscription reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GpioEdge {
+    Rising,
+    Falling,
+    #[default]
+    Both,
+}
+
+/// How a hardware event reaches the agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HardwareEventAction {
+    /// As a message on the `hardware` channel, with conversation history (default).
+    #[default]
+    Channel,
+    /// As a one-shot agent job run by the cron scheduler.
+    Agent,
+}
+
+/// A hardware event subscription (e.g. "door opened", "temperature high").
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HardwareSubscriptionConfig {
+    /// Unique name; the board tags its events with it
+    pub name: String,
+    /// Board that watches for the event (a `[[peripherals.boards]]` board)
+    pub board: String,
+    pub kind: HardwareEventKind,
+    /// GPIO pin for `gpio_edge`
+    #[serde(default)]
+    pub pin: Option<u64>,
+    #[serde(default)]
+    pub edge: GpioEdge,
+    /// ADC channel for `adc_threshold` and `adc_sample`
+    #[serde(default)]
+    pub channel: Option<u64>,
+    /// `adc_threshold`: report when the raw reading rises above this
+    #[serde(default)]
+    pub above: Option<u32>,
+    /// `adc_threshold`: report when the raw reading falls below this
+    #[serde(default)]
+    pub below: Option<u32>,
+    /// `adc_sample`: sampling interval in milliseconds
+    #[serde(default)]
+    pub interval_ms: Option<u64>,
+    /// Text for the agent; `{value}` becomes the reading. Defaults to a
+    /// description of the event.
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub action: HardwareEventAction,
+    /// Deliver only once events have stopped for this long (last one wins)
+    #[serde(default)]
+    pub debounce_ms: u64,
+    /// Deliver at most one event per this many seconds; extra events are dropped
+    #[serde(default)]
+    pub min_interval_secs: u64,
 }
 
 /// Configuration for a single peripheral board (e.g. STM32, RPi GPIO).
@@ -698,10 +977,11 @@ pub struct PeripheralsConfig {
 pub struct PeripheralBoardConfig {
     /// Board type: "nucleo-f401re", "rpi-gpio", "esp32", etc.
     pub board: String,
-    /// Transport: "serial", "native", "websocket"
+    /// Transport: "serial", "native", "websocket", "virtual" (simulated board)
     #[serde(default = "default_peripheral_transport")]
     pub transport: String,
-    /// Path for serial: "/dev/ttyACM0", "/dev/ttyUSB0"
+    /// Path for serial: "/dev/ttyACM0", "/dev/ttyUSB0". For virtual: optional
+    /// TOML script with pin states, sensor waveforms and faults.
     #[serde(default)]
     pub path: Option<String>,
     /// Baud rate for serial (default: 115200)
@@ -728,12 +1008,87 @@ impl Default for PeripheralBoardConfig {
     }
 }
 
+// ── Robot kit (drive, look, listen, speak, sense, emote) ─────────────────
+
+/// Robot-kit integration (`[robot]` section).
+///
+/// Registers the `zeroclaw-robot-kit` tools with the agent. Hardware backends,
+/// devices and safety limits come from the robot kit's own `robot.toml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct RobotConfig {
+    /// Enable robot tools
+    #[serde(default)]
+    pub enabled: bool,
+    /// Path to the robot kit's `robot.toml`. Defaults to the kit's built-in
+    /// config (mock drive and sensors).
+    #[serde(default)]
+    pub config_path: Option<String>,
+    /// Tools to register: drive, look, listen, speak, sense, emote (default: all)
+    #[serde(default)]
+    pub tools: Vec<String>,
+}
+
+// ── Server containers ────────────────────────────────────────────
+
+/// Policy for containers users create through the multi-tenant server
+/// (`[containers]` section).
+///
+/// Requested resources are clamped to the maxima; a missing limit gets the
+/// maximum instead of running unbounded.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ContainersConfig {
+    /// Images users may launch. Empty allows only `runtime.docker.image`.
+    #[serde(default)]
+    pub allowed_images: Vec<String>,
+    /// Backends users may choose (`native`, `docker`)
+    #[serde(default = "default_container_backends")]
+    pub allowed_backends: Vec<String>,
+    /// CPU cores per container
+    #[serde(default = "default_container_max_cpu")]
+    pub max_cpu: u32,
+    /// Memory per container in MB
+    #[serde(default = "default_container_max_memory_mb")]
+    pub max_memory_mb: u64,
+    /// Disk usage per container in MB
+    #[serde(default = "default_container_max_disk_mb")]
+    pub max_disk_mb: u64,
+}
+
+fn default_container_backends() -> Vec<String> {
+    vec!["native".into(), "docker".into()]
+}
+
+fn default_container_max_cpu() -> u32 {
+    4
+}
+
+fn default_container_max_memory_mb() -> u64 {
+    4096
+}
+
+fn default_container_max_disk_mb() -> u64 {
+    10240
+}
+
+impl Default for ContainersConfig {
+    fn default() -> Self {
+        Self {
+            allowed_images: Vec::new(),
+            allowed_backends: default_container_backends(),
+            max_cpu: default_container_max_cpu(),
+            max_memory_mb: default_container_max_memory_mb(),
+            max_disk_mb: default_container_max_disk_mb(),
+        }
+    }
+}
+
 // ── Gateway security ─────────────────────────────────────────────
 
 /// Gateway server configuration (`[gateway]` section).
 ///
 /// Controls the HTTP gateway for webhook and pairing endpoints.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct GatewayConfig {
     /// Gateway port (default: 42617)
     #[serde(default = "default_gateway_port")]
@@ -775,6 +1130,18 @@ pub struct GatewayConfig {
     /// Maximum distinct idempotency keys retained in memory.
     #[serde(default = "default_gateway_idempotency_max_keys")]
     pub idempotency_max_keys: usize,
+
+    /// Retries for a failed outbound webhook delivery before it is dead-lettered.
+    #[serde(default = "default_outbound_webhook_max_retries")]
+    pub outbound_webhook_max_retries: u32,
+
+    /// Initial backoff between outbound webhook retries; doubles per attempt.
+    #[serde(default = "default_outbound_webhook_backoff_ms")]
+    pub outbound_webhook_backoff_ms: u64,
+
+    /// Allow outbound webhooks to loopback, link-local and private addresses.
+    #[serde(default)]
+    pub outbound_webhook_allow_private_hosts: bool,
 }
 
 fn default_gateway_port() -> u16 {
@@ -805,6 +1172,14 @@ fn default_gateway_idempotency_max_keys() -> usize {
     10_000
 }
 
+fn default_outbound_webhook_max_retries() -> u32 {
+    3
+}
+
+fn default_outbound_webhook_backoff_ms() -> u64 {
+    1_000
+}
+
 fn default_true() -> bool {
     true
 }
@@ -823,6 +1198,9 @@ impl Default for GatewayConfig {
             rate_limit_max_keys: default_gateway_rate_limit_max_keys(),
             idempotency_ttl_secs: default_idempotency_ttl_secs(),
             idempotency_max_keys: default_gateway_idempotency_max_keys(),
+            outbound_webhook_max_retries: default_outbound_webhook_max_retries(),
+            outbound_webhook_backoff_ms: default_outbound_webhook_backoff_ms(),
+            outbound_webhook_allow_private_hosts: false,
         }
     }
 }
@@ -867,11 +1245,70 @@ pub struct SecretsConfig {
     /// Enable encryption for API keys and tokens in config.toml
     #[serde(default = "default_true")]
     pub encrypt: bool,
+    /// Seconds to cache values resolved from external secret references
+    /// (`vault://`, `file://`, `cmd://`). `0` disables caching. Default: `300`.
+    #[serde(default = "default_secret_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+    /// Allow `cmd://` secret references (e.g. `cmd://pass show openai`).
+    /// Off by default because the command runs with the agent's privileges.
+    #[serde(default)]
+    pub allow_command_refs: bool,
+    /// HashiCorp Vault settings for `vault://` references (`[secrets.vault]`).
+    #[serde(default)]
+    pub vault: VaultSecretsConfig,
+    /// Fields loaded from external references; used by `save()` to write the
+    /// reference back instead of the resolved value. Not serialized.
+    #[serde(skip)]
+    pub resolved_refs: crate::security::secret_resolver::SecretRefBindings,
+}
+
+fn default_secret_cache_ttl_secs() -> u64 {
+    300
 }
 
 impl Default for SecretsConfig {
     fn default() -> Self {
-        Self { encrypt: true }
+        Self {
+            encrypt: true,
+            cache_ttl_secs: default_secret_cache_ttl_secs(),
+            allow_command_refs: false,
+            vault: VaultSecretsConfig::default(),
+            resolved_refs: crate::security::secret_resolver::SecretRefBindings::default(),
+        }
+    }
+}
+
+/// HashiCorp Vault KV v2 configuration (`[secrets.vault]` section).
+///
+/// Unset fields fall back to `VAULT_ADDR`, `VAULT_TOKEN` and `VAULT_NAMESPACE`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VaultSecretsConfig {
+    /// Vault server address (e.g. `"http://127.0.0.1:8200"`)
+    #[serde(default)]
+    pub address: Option<String>,
+    /// Vault token (stored encrypted when secrets.encrypt = true)
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Vault Enterprise namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Request timeout in seconds. Default: `10`.
+    #[serde(default = "default_vault_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_vault_timeout_secs() -> u64 {
+    10
+}
+
+impl Default for VaultSecretsConfig {
+    fn default() -> Self {
+        Self {
+            address: None,
+            token: None,
+            namespace: None,
+            timeout_secs: default_vault_timeout_secs(),
+        }
     }
 }
 
@@ -1779,6 +2216,14 @@ pub struct ObservabilityConfig {
     /// Maximum entries retained when runtime_trace_mode = "rolling".
     #[serde(default = "default_runtime_trace_max_entries")]
     pub runtime_trace_max_entries: usize,
+
+    /// Events kept for `/api/events` replay via `Last-Event-ID`. 0 disables the stream.
+    #[serde(default = "default_events_buffer_size")]
+    pub events_buffer_size: usize,
+
+    /// File persisting the event buffer across restarts. Relative paths are resolved under workspace_dir.
+    #[serde(default = "default_events_path")]
+    pub events_path: String,
 }
 
 impl Default for ObservabilityConfig {
@@ -1790,6 +2235,8 @@ impl Default for ObservabilityConfig {
             runtime_trace_mode: default_runtime_trace_mode(),
             runtime_trace_path: default_runtime_trace_path(),
             runtime_trace_max_entries: default_runtime_trace_max_entries(),
+            events_buffer_size: default_events_buffer_size(),
+            events_path: default_events_path(),
         }
     }
 }
@@ -1806,6 +2253,14 @@ fn default_runtime_trace_max_entries() -> usize {
     200
 }
 
+fn default_events_buffer_size() -> usize {
+    1000
+}
+
+fn default_events_path() -> String {
+    "state/events.jsonl".to_string()
+}
+
 // ── Hooks ────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -2468,6 +2923,9 @@ pub struct ChannelsConfig {
     /// Default: 300s for on-device LLMs (Ollama) which are slower than cloud APIs.
     #[serde(default = "default_channel_message_timeout_secs")]
     pub message_timeout_secs: u64,
+    /// Inbox and size limits for files exchanged over channels.
+    #[serde(default)]
+    pub attachments: ChannelAttachmentsConfig,
 }
 
 impl ChannelsConfig {
@@ -2583,6 +3041,36 @@ impl Default for ChannelsConfig {
             nostr: None,
             clawdtalk: None,
             message_timeout_secs: default_channel_message_timeout_secs(),
+            attachments: ChannelAttachmentsConfig::default(),
+        }
+    }
+}
+
+/// Channel attachment handling (`[channels_config.attachments]` section).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChannelAttachmentsConfig {
+    /// Largest attachment accepted or sent, in bytes. Default: 20 MiB.
+    #[serde(default = "default_attachment_max_bytes")]
+    pub max_bytes: u64,
+    /// Directory for received files, relative to the workspace unless absolute.
+    /// Default: `inbox`.
+    #[serde(default = "default_attachment_inbox_dir")]
+    pub inbox_dir: String,
+}
+
+fn default_attachment_max_bytes() -> u64 {
+    crate::channels::attachments::DEFAULT_MAX_ATTACHMENT_BYTES
+}
+
+fn default_attachment_inbox_dir() -> String {
+    crate::channels::attachments::DEFAULT_INBOX_DIR.to_string()
+}
+
+impl Default for ChannelAttachmentsConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: default_attachment_max_bytes(),
+            inbox_dir: default_attachment_inbox_dir(),
         }
     }
 }
@@ -3055,6 +3543,41 @@ pub struct SecurityConfig {
     /// Emergency-stop state machine configuration.
     #[serde(default)]
     pub estop: EstopConfig,
+
+    /// Policy-as-code rules evaluated before every tool call.
+    #[serde(default)]
+    pub tool_rules: ToolRulesConfig,
+}
+
+/// Tool-call rules configuration (`[security.tool_rules]` section).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ToolRulesConfig {
+    /// Enforce the rules file before every tool call.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Rules file path; relative paths resolve against the config directory.
+    #[serde(default = "default_tool_rules_path")]
+    pub path: String,
+
+    /// Evaluate and log decisions without blocking or prompting.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+fn default_tool_rules_path() -> String {
+    crate::security::tool_rules::DEFAULT_RULES_FILE.to_string()
+}
+
+impl Default for ToolRulesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_tool_rules_path(),
+            dry_run: false,
+        }
+    }
 }
 
 /// OTP validation strategy.
@@ -3180,6 +3703,14 @@ pub struct SandboxConfig {
     /// Custom Firejail arguments (when backend = firejail)
     #[serde(default)]
     pub firejail_args: Vec<String>,
+
+    /// Syscall profile (when backend = seccomp)
+    #[serde(default)]
+    pub seccomp_profile: SeccompProfile,
+
+    /// Stack Landlock filesystem rules on top of seccomp when available (default: true)
+    #[serde(default = "default_true")]
+    pub seccomp_landlock: bool,
 }
 
 impl Default for SandboxConfig {
@@ -3188,10 +3719,23 @@ impl Default for SandboxConfig {
             enabled: None, // Auto-detect
             backend: SandboxBackend::Auto,
             firejail_args: Vec::new(),
+            seccomp_profile: SeccompProfile::default(),
+            seccomp_landlock: true,
         }
     }
 }
 
+/// Curated seccomp syscall profile for shell tool commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum SeccompProfile {
+    /// Deny ptrace, mount/namespace changes, kernel module loading and raw sockets
+    #[default]
+    Standard,
+    /// `standard` plus deny all sockets except `AF_UNIX`
+    NoNetwork,
+}
+
 /// Sandbox backend selection
 #[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "lowercase")]
@@ -3207,6 +3751,8 @@ pub enum SandboxBackend {
     Bubblewrap,
     /// Docker container isolation
     Docker,
+    /// Seccomp-bpf syscall filter (Linux, optionally stacked with Landlock)
+    Seccomp,
     /// No sandboxing (application-layer only)
     None,
 }
@@ -3386,6 +3932,8 @@ impl Default for Config {
             workspace_base: None,
             workspace_dir: zeroclaw_dir.join("workspace"),
             config_path: zeroclaw_dir.join("config.toml"),
+            tenant_context: None,
+            tenant: None,
             api_key: None,
             api_url: None,
             default_provider: Some("openrouter".to_string()),
@@ -3418,11 +3966,14 @@ impl Default for Config {
             identity: IdentityConfig::default(),
             cost: CostConfig::default(),
             peripherals: PeripheralsConfig::default(),
+            robot: RobotConfig::default(),
+            containers: ContainersConfig::default(),
             agents: HashMap::new(),
             hooks: HooksConfig::default(),
             hardware: HardwareConfig::default(),
             query_classification: QueryClassificationConfig::default(),
             transcription: TranscriptionConfig::default(),
+            tts: TtsConfig::default(),
         }
     }
 }
@@ -3688,29 +4239,62 @@ fn decrypt_secret(
     Ok(())
 }
 
+async fn resolve_optional_secret_ref(
+    resolver: &crate::security::SecretResolver,
+    bindings: &mut crate::security::secret_resolver::SecretRefBindings,
+    value: &mut Option<String>,
+    field_name: &str,
+) -> Result<()> {
+    if let Some(raw) = value.as_mut() {
+        resolve_secret_ref(resolver, bindings, raw, field_name).await?;
+    }
+    Ok(())
+}
+
+async fn resolve_secret_ref(
+    resolver: &crate::security::SecretResolver,
+    bindings: &mut crate::security::secret_resolver::SecretRefBindings,
+    value: &mut String,
+    field_name: &str,
+) -> Result<()> {
+    if crate::security::secret_resolver::is_secret_ref(value) {
+        let resolved = resolver
+            .resolve(value)
+            .await
+            .with_context(|| format!("Failed to resolve {field_name}"))?;
+        bindings.record(field_name, value.trim(), &resolved);
+        *value = resolved;
+    }
+    Ok(())
+}
+
 fn encrypt_optional_secret(
     store: &crate::security::SecretStore,
+    refs: &crate::security::secret_resolver::SecretRefBindings,
     value: &mut Option<String>,
     field_name: &str,
 ) -> Result<()> {
-    if let Some(raw) = value.clone() {
-        if !crate::security::SecretStore::is_encrypted(&raw) {
-            *value = Some(
-                store
-                    .encrypt(&raw)
-                    .with_context(|| format!("Failed to encrypt {field_name}"))?,
-            );
-        }
+    if let Some(raw) = value.as_mut() {
+        encrypt_secret(store, refs, raw, field_name)?;
     }
     Ok(())
 }
 
 fn encrypt_secret(
     store: &crate::security::SecretStore,
+    refs: &crate::security::secret_resolver::SecretRefBindings,
     value: &mut String,
     field_name: &str,
 ) -> Result<()> {
-    if !crate::security::SecretStore::is_encrypted(value) {
+    // Values loaded from an external reference are saved as the reference,
+    // never as the resolved plaintext.
+    if let Some(reference) = refs.reference_for(field_name, value) {
+        *value = reference.to_string();
+        return Ok(());
+    }
+    if !crate::security::SecretStore::is_encrypted(value)
+        && !crate::security::secret_resolver::is_secret_ref(value)
+    {
         *value = store
             .encrypt(value)
             .with_context(|| format!("Failed to encrypt {field_name}"))?;
@@ -3796,44 +4380,8 @@ impl Config {
             // Set computed paths that are skipped during serialization
             config.config_path = config_path.clone();
             config.workspace_dir = workspace_dir;
-            let store = crate::security::SecretStore::new(&zeroclaw_dir, config.secrets.encrypt);
-            decrypt_optional_secret(&store, &mut config.api_key, "config.api_key")?;
-            decrypt_optional_secret(
-                &store,
-                &mut config.composio.api_key,
-                "config.composio.api_key",
-            )?;
-
-            decrypt_optional_secret(
-                &store,
-                &mut config.browser.computer_use.api_key,
-                "config.browser.computer_use.api_key",
-            )?;
-
-            decrypt_optional_secret(
-                &store,
-                &mut config.web_search.brave_api_key,
-                "config.web_search.brave_api_key",
-            )?;
-
-            decrypt_optional_secret(
-                &store,
-                &mut config.storage.provider.config.db_url,
-                "config.storage.provider.config.db_url",
-            )?;
-
-            for agent in config.agents.values_mut() {
-                decrypt_optional_secret(&store, &mut agent.api_key, "config.agents.*.api_key")?;
-            }
-
-            if let Some(ref mut ns) = config.channels_config.nostr {
-                decrypt_secret(
-                    &store,
-                    &mut ns.private_key,
-                    "config.channels_config.nostr.private_key",
-                )?;
-            }
-
+            config.decrypt_secrets(&zeroclaw_dir)?;
+            config.resolve_secret_refs().await?;
             config.apply_env_overrides();
             config.validate()?;
             tracing::info!(
@@ -4273,6 +4821,122 @@ impl Config {
         set_runtime_proxy_config(self.proxy.clone());
     }
 
+    /// Decrypt `enc:`/`enc2:` secret fields with the secret store in
+    /// `zeroclaw_dir`. Runs before [`Config::resolve_secret_refs`], which may
+    /// need the decrypted Vault token.
+    pub(crate) fn decrypt_secrets(&mut self, zeroclaw_dir: &Path) -> Result<()> {
+        let store = crate::security::SecretStore::new(zeroclaw_dir, self.secrets.encrypt);
+        decrypt_optional_secret(&store, &mut self.api_key, "config.api_key")?;
+        decrypt_optional_secret(
+            &store,
+            &mut self.composio.api_key,
+            "config.composio.api_key",
+        )?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut self.browser.computer_use.api_key,
+            "config.browser.computer_use.api_key",
+        )?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut self.web_search.brave_api_key,
+            "config.web_search.brave_api_key",
+        )?;
+
+        decrypt_optional_secret(&store, &mut self.tts.api_key, "config.tts.api_key")?;
+
+        decrypt_optional_secret(
+            &store,
+            &mut self.storage.provider.config.db_url,
+            "config.storage.provider.config.db_url",
+        )?;
+
+        for agent in self.agents.values_mut() {
+            decrypt_optional_secret(&store, &mut agent.api_key, "config.agents.*.api_key")?;
+        }
+
+        if let Some(ref mut ns) = self.channels_config.nostr {
+            decrypt_secret(
+                &store,
+                &mut ns.private_key,
+                "config.channels_config.nostr.private_key",
+            )?;
+        }
+
+        decrypt_optional_secret(
+            &store,
+            &mut self.secrets.vault.token,
+            "config.secrets.vault.token",
+        )
+    }
+
+    /// Resolve external secret references (`vault://`, `env://`, `file://`,
+    /// `cmd://`) in secret-bearing fields, remembering each reference so
+    /// [`Config::save`] writes it back instead of the resolved value.
+    pub async fn resolve_secret_refs(&mut self) -> Result<()> {
+        let resolver = crate::security::SecretResolver::from_config(&self.secrets);
+        let mut bindings = std::mem::take(&mut self.secrets.resolved_refs);
+
+        resolve_optional_secret_ref(
+            &resolver,
+            &mut bindings,
+            &mut self.api_key,
+            "config.api_key",
+        )
+        .await?;
+        resolve_optional_secret_ref(
+            &resolver,
+            &mut bindings,
+            &mut self.composio.api_key,
+            "config.composio.api_key",
+        )
+        .await?;
+        resolve_optional_secret_ref(
+            &resolver,
+            &mut bindings,
+            &mut self.browser.computer_use.api_key,
+            "config.browser.computer_use.api_key",
+        )
+        .await?;
+        resolve_optional_secret_ref(
+            &resolver,
+            &mut bindings,
+            &mut self.web_search.brave_api_key,
+            "config.web_search.brave_api_key",
+        )
+        .await?;
+        resolve_optional_secret_ref(
+            &resolver,
+            &mut bindings,
+            &mut self.storage.provider.config.db_url,
+            "config.storage.provider.config.db_url",
+        )
+        .await?;
+        for (name, agent) in &mut self.agents {
+            resolve_optional_secret_ref(
+                &resolver,
+                &mut bindings,
+                &mut agent.api_key,
+                &format!("config.agents.{name}.api_key"),
+            )
+            .await?;
+        }
+        if let Some(ref mut ns) = self.channels_config.nostr {
+            resolve_secret_ref(
+                &resolver,
+                &mut bindings,
+                &mut ns.private_key,
+                "config.channels_config.nostr.private_key",
+            )
+            .await?;
+        }
+
+        self.secrets.resolved_refs = bindings;
+        Ok(())
+    }
+
     pub async fn save(&self) -> Result<()> {
         // Encrypt secrets before serialization
         let mut config_to_save = self.clone();
@@ -4281,44 +4945,69 @@ impl Config {
             .parent()
             .context("Config path must have a parent directory")?;
         let store = crate::security::SecretStore::new(zeroclaw_dir, self.secrets.encrypt);
+        let refs = &self.secrets.resolved_refs;
 
-        encrypt_optional_secret(&store, &mut config_to_save.api_key, "config.api_key")?;
+        encrypt_optional_secret(&store, refs, &mut config_to_save.api_key, "config.api_key")?;
         encrypt_optional_secret(
             &store,
+            refs,
             &mut config_to_save.composio.api_key,
             "config.composio.api_key",
         )?;
 
         encrypt_optional_secret(
             &store,
+            refs,
             &mut config_to_save.browser.computer_use.api_key,
             "config.browser.computer_use.api_key",
         )?;
 
         encrypt_optional_secret(
             &store,
+            refs,
             &mut config_to_save.web_search.brave_api_key,
             "config.web_search.brave_api_key",
         )?;
 
         encrypt_optional_secret(
             &store,
+            refs,
+            &mut config_to_save.tts.api_key,
+            "config.tts.api_key",
+        )?;
+
+        encrypt_optional_secret(
+            &store,
+            refs,
             &mut config_to_save.storage.provider.config.db_url,
             "config.storage.provider.config.db_url",
         )?;
 
-        for agent in config_to_save.agents.values_mut() {
-            encrypt_optional_secret(&store, &mut agent.api_key, "config.agents.*.api_key")?;
+        for (name, agent) in &mut config_to_save.agents {
+            encrypt_optional_secret(
+                &store,
+                refs,
+                &mut agent.api_key,
+                &format!("config.agents.{name}.api_key"),
+            )?;
         }
 
         if let Some(ref mut ns) = config_to_save.channels_config.nostr {
             encrypt_secret(
                 &store,
+                refs,
                 &mut ns.private_key,
                 "config.channels_config.nostr.private_key",
             )?;
         }
 
+        encrypt_optional_secret(
+            &store,
+            refs,
+            &mut config_to_save.secrets.vault.token,
+            "config.secrets.vault.token",
+        )?;
+
         let toml_str =
             toml::to_string_pretty(&config_to_save).context("Failed to serialize config")?;
 
@@ -4500,6 +5189,8 @@ mod tests {
         assert_eq!(o.runtime_trace_mode, "none");
         assert_eq!(o.runtime_trace_path, "state/runtime-trace.jsonl");
         assert_eq!(o.runtime_trace_max_entries, 200);
+        assert_eq!(o.events_buffer_size, 1000);
+        assert_eq!(o.events_path, "state/events.jsonl");
     }
 
     #[test]
@@ -4603,8 +5294,11 @@ default_temperature = 0.7
     #[test]
     async fn config_toml_roundtrip() {
         let config = Config {
+            workspace_base: None,
             workspace_dir: PathBuf::from("/tmp/test/workspace"),
             config_path: PathBuf::from("/tmp/test/config.toml"),
+            tenant_context: None,
+            tenant: None,
             api_key: Some("sk-test-key".into()),
             api_url: None,
             default_provider: Some("openrouter".into()),
@@ -4673,6 +5367,7 @@ default_temperature = 0.7
                 nostr: None,
                 clawdtalk: None,
                 message_timeout_secs: 300,
+                attachments: ChannelAttachmentsConfig::default(),
             },
             memory: MemoryConfig::default(),
             storage: StorageConfig::default(),
@@ -4689,10 +5384,13 @@ default_temperature = 0.7
             identity: IdentityConfig::default(),
             cost: CostConfig::default(),
             peripherals: PeripheralsConfig::default(),
+            robot: RobotConfig::default(),
+            containers: ContainersConfig::default(),
             agents: HashMap::new(),
             hooks: HooksConfig::default(),
             hardware: HardwareConfig::default(),
             transcription: TranscriptionConfig::default(),
+            tts: TtsConfig::default(),
         };
 
         let toml_str = toml::to_string_pretty(&config).unwrap();
@@ -4828,8 +5526,11 @@ tool_dispatcher = "xml"
 
         let config_path = dir.join("config.toml");
         let config = Config {
+            workspace_base: None,
             workspace_dir: dir.join("workspace"),
             config_path: config_path.clone(),
+            tenant_context: None,
+            tenant: None,
             api_key: Some("sk-roundtrip".into()),
             api_url: None,
             default_provider: Some("openrouter".into()),
@@ -4863,10 +5564,13 @@ tool_dispatcher = "xml"
             identity: IdentityConfig::default(),
             cost: CostConfig::default(),
             peripherals: PeripheralsConfig::default(),
+            robot: RobotConfig::default(),
+            containers: ContainersConfig::default(),
             agents: HashMap::new(),
             hooks: HooksConfig::default(),
             hardware: HardwareConfig::default(),
             transcription: TranscriptionConfig::default(),
+            tts: TtsConfig::default(),
         };
 
         config.save().await.unwrap();
@@ -4973,6 +5677,67 @@ tool_dispatcher = "xml"
         let _ = fs::remove_dir_all(&dir).await;
     }
 
+    #[tokio::test]
+    async fn config_save_keeps_secret_refs_unresolved() {
+        let dir = std::env::temp_dir().join(format!(
+            "zeroclaw_test_secret_refs_{}",
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&dir).await.unwrap();
+        let key_file = dir.join("brave_key");
+        fs::write(&key_file, "brave-from-file\n").await.unwrap();
+        std::env::set_var("ZEROCLAW_TEST_CONFIG_REF_KEY", "sk-from-env");
+
+        let mut config = Config::default();
+        config.workspace_dir = dir.join("workspace");
+        config.config_path = dir.join("config.toml");
+        config.secrets.cache_ttl_secs = 0;
+        config.api_key = Some("env://ZEROCLAW_TEST_CONFIG_REF_KEY".into());
+        config.web_search.brave_api_key = Some(format!("file://{}", key_file.display()));
+        config.composio.api_key = Some("composio-plain".into());
+
+        config.resolve_secret_refs().await.unwrap();
+        assert_eq!(config.api_key.as_deref(), Some("sk-from-env"));
+        assert_eq!(
+            config.web_search.brave_api_key.as_deref(),
+            Some("brave-from-file")
+        );
+
+        config.save().await.unwrap();
+        let contents = fs::read_to_string(&config.config_path).await.unwrap();
+        assert!(!contents.contains("sk-from-env"));
+        assert!(!contents.contains("brave-from-file"));
+        let stored: Config = toml::from_str(&contents).unwrap();
+        assert_eq!(
+            stored.api_key.as_deref(),
+            Some("env://ZEROCLAW_TEST_CONFIG_REF_KEY")
+        );
+        assert!(stored
+            .web_search
+            .brave_api_key
+            .as_deref()
+            .is_some_and(|v| v.starts_with("file://")));
+        assert!(stored
+            .composio
+            .api_key
+            .as_deref()
+            .is_some_and(crate::security::SecretStore::is_encrypted));
+
+        // A value changed after load replaces the reference.
+        config.api_key = Some("sk-replaced".into());
+        config.save().await.unwrap();
+        let stored: Config =
+            toml::from_str(&fs::read_to_string(&config.config_path).await.unwrap()).unwrap();
+        let store = crate::security::SecretStore::new(&dir, true);
+        assert_eq!(
+            store.decrypt(stored.api_key.as_deref().unwrap()).unwrap(),
+            "sk-replaced"
+        );
+
+        std::env::remove_var("ZEROCLAW_TEST_CONFIG_REF_KEY");
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
     #[tokio::test]
     async fn config_save_atomic_cleanup() {
         let dir =
@@ -5226,6 +5991,7 @@ allowed_users = ["@ops:matrix.org"]
             nostr: None,
             clawdtalk: None,
             message_timeout_secs: 300,
+            attachments: ChannelAttachmentsConfig::default(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -5438,6 +6204,7 @@ channel_id = "C123"
             nostr: None,
             clawdtalk: None,
             message_timeout_secs: 300,
+            attachments: ChannelAttachmentsConfig::default(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -5522,6 +6289,9 @@ channel_id = "C123"
             rate_limit_max_keys: 2048,
             idempotency_ttl_secs: 600,
             idempotency_max_keys: 4096,
+            outbound_webhook_max_retries: 5,
+            outbound_webhook_backoff_ms: 250,
+            outbound_webhook_allow_private_hosts: true,
         };
         let toml_str = toml::to_string(&g).unwrap();
         let parsed: GatewayConfig = toml::from_str(&toml_str).unwrap();
@@ -5534,6 +6304,9 @@ channel_id = "C123"
         assert_eq!(parsed.rate_limit_max_keys, 2048);
         assert_eq!(parsed.idempotency_ttl_secs, 600);
         assert_eq!(parsed.idempotency_max_keys, 4096);
+        assert_eq!(parsed.outbound_webhook_max_retries, 5);
+        assert_eq!(parsed.outbound_webhook_backoff_ms, 250);
+        assert!(parsed.outbound_webhook_allow_private_hosts);
     }
 
     #[test]
@@ -5648,7 +6421,10 @@ enable = true
 
     #[test]
     async fn secrets_config_serde_roundtrip() {
-        let s = SecretsConfig { encrypt: false };
+        let s = SecretsConfig {
+            encrypt: false,
+            ..SecretsConfig::default()
+        };
         let toml_str = toml::to_string(&s).unwrap();
         let parsed: SecretsConfig = toml::from_str(&toml_str).unwrap();
         assert!(!parsed.encrypt);
@@ -6701,6 +7477,7 @@ default_model = "legacy-model"
                 baud: 115_200,
             }],
             datasheet_dir: None,
+            subscriptions: Vec::new(),
         };
         let toml_str = toml::to_string(&p).unwrap();
         let parsed: PeripheralsConfig = toml::from_str(&toml_str).unwrap();
@@ -6857,6 +7634,9 @@ default_model = "legacy-model"
         assert_eq!(tc.model, "whisper-large-v3-turbo");
         assert!(tc.language.is_none());
         assert_eq!(tc.max_duration_secs, 120);
+        assert_eq!(tc.provider, "groq");
+        assert_eq!(tc.whisper_path, "whisper-cli");
+        assert!(tc.whisper_model.is_none());
     }
 
     #[test]
@@ -6885,6 +7665,28 @@ default_model = "legacy-model"
         assert_eq!(parsed.transcription.max_duration_secs, 120);
     }
 
+    #[test]
+    async fn tts_reply_mode_honours_channel_overrides() {
+        let parsed: Config = toml::from_str(
+            r#"
+            default_temperature = 0.7
+            [tts]
+            enabled = true
+            reply = "always"
+            [tts.channels]
+            discord = "never"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(parsed.tts.provider, "openai");
+        assert_eq!(parsed.tts.reply_mode("telegram"), TtsReplyMode::Always);
+        assert_eq!(parsed.tts.reply_mode("discord"), TtsReplyMode::Never);
+
+        let disabled = TtsConfig::default();
+        assert_eq!(disabled.reply, TtsReplyMode::OnVoice);
+        assert_eq!(disabled.reply_mode("telegram"), TtsReplyMode::Never);
+    }
+
     #[test]
     async fn security_defaults_are_backward_compatible() {
         let parsed: Config = toml::from_str(
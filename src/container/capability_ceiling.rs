@@ -1,9 +1,13 @@
+use crate::tenant::db::{or_default, query_doc, to_doc, TenantDb};
 use anyhow::{bail, Result};
+use rusqlite::{params, Transaction};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
 pub enum PermissionLevel {
     None,
     Read,
@@ -83,6 +87,45 @@ pub struct AgentCeilingConfig {
     pub updated_at: String,
 }
 
+/// Permission a tool call needs from the acting user's ceiling.
+///
+/// Tools that only look at workspace data or attached boards need `Read`;
+/// tools that change files, reach the network, capture the screen or reveal
+/// secrets need `Write`; arbitrary shell commands and tools that reconfigure
+/// the agent or flash hardware need `Admin`. Unknown tools (skills, MCP,
+/// plugins) are treated as `Write`.
+pub fn tool_permission_level(tool: &str) -> PermissionLevel {
+    match tool {
+        "file_read"
+        | "glob_search"
+        | "content_search"
+        | "pdf_read"
+        | "image_info"
+        | "memory_recall"
+        | "cron_list"
+        | "cron_runs"
+        | "agent_inbox"
+        | "hardware_board_info"
+        | "hardware_memory_map"
+        | "hardware_memory_read"
+        | "hardware_capabilities"
+        | "gpio_read" => PermissionLevel::Read,
+        "shell" | "model_routing_config" | "proxy_config" | "arduino_upload" => {
+            PermissionLevel::Admin
+        }
+        _ => PermissionLevel::Write,
+    }
+}
+
+pub const ESCALATION_PENDING: &str = "pending";
+pub const ESCALATION_APPROVED: &str = "approved";
+pub const ESCALATION_DENIED: &str = "denied";
+
+/// How long an approved escalation lasts when the approver gives no duration.
+pub const DEFAULT_ESCALATION_SECS: i64 = 3600;
+/// Longest raise an approver may grant.
+pub const MAX_ESCALATION_SECS: i64 = 7 * 24 * 3600;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EscalationRequest {
     pub id: String,
@@ -92,62 +135,120 @@ pub struct EscalationRequest {
     pub status: String,
     pub created_at: String,
     pub resolved_at: Option<String>,
+    /// Admin who approved or denied the request
+    #[serde(default)]
+    pub resolved_by: Option<String>,
+    /// End of the temporary raise granted by an approval
+    #[serde(default)]
+    pub expires_at: Option<String>,
 }
 
+impl EscalationRequest {
+    /// Whether this is an approved raise that has not expired yet.
+    pub fn is_active(&self) -> bool {
+        self.status == ESCALATION_APPROVED
+            && self
+                .expires_at
+                .as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .is_some_and(|t| t > chrono::Utc::now())
+    }
+}
+
+/// The legacy `ceilings.json` layout, read once by the tenant JSON importer.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CeilingStoreData {
     pub ceilings: HashMap<String, AgentCeilingConfig>,
     pub escalation_requests: Vec<EscalationRequest>,
 }
 
+/// Per-user agent permission ceilings, stored in the tenant database.
 pub struct CeilingManager {
-    data: CeilingStoreData,
-    path: std::path::PathBuf,
+    db: TenantDb,
+}
+
+pub(crate) fn put_ceiling(tx: &Transaction<'_>, config: &AgentCeilingConfig) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO ceilings (user_id, data) VALUES (?1, ?2)",
+        params![config.user_id, to_doc(config)?],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn put_escalation(tx: &Transaction<'_>, request: &EscalationRequest) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO escalation_requests (id, user_id, created_at, data)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            request.id,
+            request.user_id,
+            request.created_at,
+            to_doc(request)?
+        ],
+    )?;
+    Ok(())
 }
 
 impl CeilingManager {
-    pub fn new(base_path: &Path) -> Self {
-        let path = base_path.join("ceilings.json");
-        let data = if path.exists() {
-            let content = std::fs::read_to_string(&path).unwrap_or_default();
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            CeilingStoreData::default()
-        };
-        Self { data, path }
+    pub fn new(db: TenantDb) -> Self {
+        Self { db }
     }
 
     pub fn set_ceiling(&mut self, user_id: &str, ceiling: PermissionLevel) -> Result<()> {
         let now = chrono::Utc::now().to_rfc3339();
-        let config = AgentCeilingConfig {
-            user_id: user_id.to_string(),
-            ceiling,
-            created_at: now.clone(),
-            updated_at: now,
-        };
-        self.data.ceilings.insert(user_id.to_string(), config);
-        self.save()
+        self.db.write(|tx| {
+            let created_at = query_doc::<AgentCeilingConfig>(
+                tx,
+                "SELECT data FROM ceilings WHERE user_id = ?1",
+                params![user_id],
+            )?
+            .map_or_else(|| now.clone(), |existing| existing.created_at);
+            put_ceiling(
+                tx,
+                &AgentCeilingConfig {
+                    user_id: user_id.to_string(),
+                    ceiling,
+                    created_at,
+                    updated_at: now,
+                },
+            )
+        })
     }
 
     pub fn get_ceiling(&self, user_id: &str) -> PermissionLevel {
-        self.data
-            .ceilings
-            .get(user_id)
-            .map(|c| c.ceiling)
-            .unwrap_or(DEFAULT_AGENT_CEILING)
+        or_default(self.db.query_doc::<AgentCeilingConfig>(
+            "SELECT data FROM ceilings WHERE user_id = ?1",
+            params![user_id],
+        ))
+        .map(|c| c.ceiling)
+        .unwrap_or(DEFAULT_AGENT_CEILING)
     }
 
-    pub fn list_ceilings(&self) -> Vec<&AgentCeilingConfig> {
-        self.data.ceilings.values().collect()
+    pub fn list_ceilings(&self) -> Vec<AgentCeilingConfig> {
+        or_default(
+            self.db
+                .query_docs("SELECT data FROM ceilings ORDER BY user_id", []),
+        )
     }
 
     pub fn remove_ceiling(&mut self, user_id: &str) -> Result<()> {
-        self.data.ceilings.remove(user_id);
-        self.save()
+        self.db.write(|tx| {
+            tx.execute("DELETE FROM ceilings WHERE user_id = ?1", params![user_id])?;
+            Ok(())
+        })
+    }
+
+    /// The user's ceiling, raised by any approved escalation still in effect.
+    pub fn effective_ceiling(&self, user_id: &str) -> PermissionLevel {
+        self.list_escalations_for_user(user_id)
+            .iter()
+            .filter(|r| r.is_active())
+            .map(|r| r.requested_level)
+            .fold(self.get_ceiling(user_id), Ord::max)
     }
 
     pub fn check_permission(&self, user_id: &str, requested: PermissionLevel) -> Result<()> {
-        let ceiling = self.get_ceiling(user_id);
+        let ceiling = self.effective_ceiling(user_id);
         if !is_within_ceiling(requested, ceiling) {
             bail!(
                 "Permission denied: requested {:?} exceeds ceiling {:?}",
@@ -158,11 +259,280 @@ impl CeilingManager {
         Ok(())
     }
 
-    fn save(&self) -> Result<()> {
-        if let Some(parent) = self.path.parent() {
-            std::fs::create_dir_all(parent)?;
+    /// Ask for a temporary raise of the user's ceiling to `level`.
+    pub fn request_escalation(
+        &mut self,
+        user_id: &str,
+        level: PermissionLevel,
+        reason: String,
+    ) -> Result<EscalationRequest> {
+        if is_within_ceiling(level, self.effective_ceiling(user_id)) {
+            bail!("Ceiling already allows {level:?}");
         }
-        std::fs::write(&self.path, serde_json::to_string(&self.data)?)?;
-        Ok(())
+        if self
+            .list_escalations_for_user(user_id)
+            .iter()
+            .any(|r| r.status == ESCALATION_PENDING)
+        {
+            bail!("An escalation request is already pending");
+        }
+        let request = EscalationRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            requested_level: level,
+            reason,
+            status: ESCALATION_PENDING.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            resolved_at: None,
+            resolved_by: None,
+            expires_at: None,
+        };
+        self.db.write(|tx| put_escalation(tx, &request))?;
+        Ok(request)
+    }
+
+    pub fn get_escalation(&self, id: &str) -> Option<EscalationRequest> {
+        or_default(self.db.query_doc(
+            "SELECT data FROM escalation_requests WHERE id = ?1",
+            params![id],
+        ))
+    }
+
+    /// All escalation requests, newest first.
+    pub fn list_escalations(&self) -> Vec<EscalationRequest> {
+        or_default(self.db.query_docs(
+            "SELECT data FROM escalation_requests ORDER BY created_at DESC",
+            [],
+        ))
+    }
+
+    /// A user's escalation requests, newest first.
+    pub fn list_escalations_for_user(&self, user_id: &str) -> Vec<EscalationRequest> {
+        or_default(self.db.query_docs(
+            "SELECT data FROM escalation_requests WHERE user_id = ?1 ORDER BY created_at DESC",
+            params![user_id],
+        ))
+    }
+
+    /// Approve a pending request, raising the ceiling for `duration_secs`.
+    pub fn approve_escalation(
+        &mut self,
+        id: &str,
+        approver: &str,
+        duration_secs: i64,
+    ) -> Result<EscalationRequest> {
+        if !(1..=MAX_ESCALATION_SECS).contains(&duration_secs) {
+            bail!("Duration must be between 1 and {MAX_ESCALATION_SECS} seconds");
+        }
+        let expires_at = chrono::Utc::now() + chrono::Duration::seconds(duration_secs);
+        self.resolve(
+            id,
+            approver,
+            ESCALATION_APPROVED,
+            Some(expires_at.to_rfc3339()),
+        )
+    }
+
+    pub fn deny_escalation(&mut self, id: &str, approver: &str) -> Result<EscalationRequest> {
+        self.resolve(id, approver, ESCALATION_DENIED, None)
+    }
+
+    fn resolve(
+        &mut self,
+        id: &str,
+        approver: &str,
+        status: &str,
+        expires_at: Option<String>,
+    ) -> Result<EscalationRequest> {
+        self.db.write(|tx| {
+            let Some(mut request) = query_doc::<EscalationRequest>(
+                tx,
+                "SELECT data FROM escalation_requests WHERE id = ?1",
+                params![id],
+            )?
+            else {
+                bail!("Escalation request not found");
+            };
+            if request.status != ESCALATION_PENDING {
+                bail!("Escalation request is already {}", request.status);
+            }
+            if request.user_id == approver {
+                bail!("Users cannot approve their own escalation");
+            }
+            request.status = status.to_string();
+            request.resolved_at = Some(chrono::Utc::now().to_rfc3339());
+            request.resolved_by = Some(approver.to_string());
+            request.expires_at = expires_at;
+            put_escalation(tx, &request)?;
+            Ok(request)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every tool the registry, peripherals and robot kit can register.
+    const TOOL_TIERS: &[(&str, PermissionLevel)] = &[
+        ("file_read", PermissionLevel::Read),
+        ("glob_search", PermissionLevel::Read),
+        ("content_search", PermissionLevel::Read),
+        ("pdf_read", PermissionLevel::Read),
+        ("image_info", PermissionLevel::Read),
+        ("memory_recall", PermissionLevel::Read),
+        ("cron_list", PermissionLevel::Read),
+        ("cron_runs", PermissionLevel::Read),
+        ("agent_inbox", PermissionLevel::Read),
+        ("hardware_board_info", PermissionLevel::Read),
+        ("hardware_memory_map", PermissionLevel::Read),
+        ("hardware_memory_read", PermissionLevel::Read),
+        ("hardware_capabilities", PermissionLevel::Read),
+        ("gpio_read", PermissionLevel::Read),
+        ("file_write", PermissionLevel::Write),
+        ("file_edit", PermissionLevel::Write),
+        ("memory_store", PermissionLevel::Write),
+        ("memory_forget", PermissionLevel::Write),
+        ("cron_add", PermissionLevel::Write),
+        ("cron_remove", PermissionLevel::Write),
+        ("cron_update", PermissionLevel::Write),
+        ("cron_run", PermissionLevel::Write),
+        ("schedule", PermissionLevel::Write),
+        ("git_operations", PermissionLevel::Write),
+        ("pushover", PermissionLevel::Write),
+        ("http_request", PermissionLevel::Write),
+        ("web_search_tool", PermissionLevel::Write),
+        ("browser", PermissionLevel::Write),
+        ("browser_open", PermissionLevel::Write),
+        ("screenshot", PermissionLevel::Write),
+        ("composio", PermissionLevel::Write),
+        ("delegate", PermissionLevel::Write),
+        ("send_to_agent", PermissionLevel::Write),
+        ("vault_get", PermissionLevel::Write),
+        ("gpio_write", PermissionLevel::Write),
+        ("drive", PermissionLevel::Write),
+        ("look", PermissionLevel::Write),
+        ("listen", PermissionLevel::Write),
+        ("speak", PermissionLevel::Write),
+        ("sense", PermissionLevel::Write),
+        ("emote", PermissionLevel::Write),
+        ("shell", PermissionLevel::Admin),
+        ("model_routing_config", PermissionLevel::Admin),
+        ("proxy_config", PermissionLevel::Admin),
+        ("arduino_upload", PermissionLevel::Admin),
+    ];
+
+    #[test]
+    fn tools_map_to_permission_levels() {
+        for (tool, level) in TOOL_TIERS {
+            assert_eq!(tool_permission_level(tool), *level, "tier of {tool}");
+        }
+        assert_eq!(tool_permission_level("some_skill"), PermissionLevel::Write);
+    }
+
+    #[test]
+    fn every_registry_tool_has_a_tier() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut config = crate::config::Config {
+            workspace_dir: tmp.path().join("workspace"),
+            config_path: tmp.path().join("config.toml"),
+            browser: crate::config::BrowserConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            http_request: crate::config::HttpRequestConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            tenant_context: Some(crate::config::TenantContext {
+                data_dir: tmp.path().to_path_buf(),
+                tenant_id: "t1".into(),
+                user_id: "u1".into(),
+            }),
+            ..Default::default()
+        };
+        config.web_search.enabled = true;
+        let agents = std::collections::HashMap::from([(
+            "researcher".to_string(),
+            crate::config::DelegateAgentConfig {
+                provider: "ollama".into(),
+                model: "llama3".into(),
+                system_prompt: None,
+                api_key: None,
+                temperature: None,
+                max_depth: 1,
+                agentic: false,
+                allowed_tools: Vec::new(),
+                max_iterations: 1,
+            },
+        )]);
+        let memory: std::sync::Arc<dyn crate::memory::Memory> = std::sync::Arc::from(
+            crate::memory::create_memory(
+                &crate::config::MemoryConfig {
+                    backend: "markdown".into(),
+                    ..Default::default()
+                },
+                tmp.path(),
+                None,
+            )
+            .unwrap(),
+        );
+        let tools = crate::tools::all_tools(
+            std::sync::Arc::new(config.clone()),
+            &std::sync::Arc::new(crate::security::SecurityPolicy::default()),
+            memory,
+            Some("composio-key"),
+            None,
+            &config.browser,
+            &config.http_request,
+            tmp.path(),
+            &agents,
+            Some("key"),
+            &config,
+            None,
+        );
+        for tool in &tools {
+            assert!(
+                TOOL_TIERS.iter().any(|(name, _)| *name == tool.name()),
+                "{} has no entry in the tier table",
+                tool.name()
+            );
+        }
+        assert!(tools.iter().any(|tool| tool.name() == "vault_get"));
+    }
+
+    #[test]
+    fn approved_escalation_raises_ceiling_until_expiry() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut ceilings = CeilingManager::new(TenantDb::open(tmp.path()).unwrap());
+        ceilings.set_ceiling("u1", PermissionLevel::Read).unwrap();
+        assert!(ceilings
+            .check_permission("u1", PermissionLevel::Write)
+            .is_err());
+
+        let request = ceilings
+            .request_escalation("u1", PermissionLevel::Write, "deploy".into())
+            .unwrap();
+        assert!(ceilings
+            .request_escalation("u1", PermissionLevel::Write, "again".into())
+            .is_err());
+        assert!(ceilings.approve_escalation(&request.id, "u1", 60).is_err());
+
+        let approved = ceilings
+            .approve_escalation(&request.id, "admin", 60)
+            .unwrap();
+        assert_eq!(approved.resolved_by.as_deref(), Some("admin"));
+        assert_eq!(ceilings.effective_ceiling("u1"), PermissionLevel::Write);
+        assert_eq!(ceilings.get_ceiling("u1"), PermissionLevel::Read);
+        assert!(ceilings.deny_escalation(&request.id, "admin").is_err());
+
+        // An expired raise no longer counts.
+        let mut expired = approved;
+        expired.expires_at = Some((chrono::Utc::now() - chrono::Duration::seconds(1)).to_rfc3339());
+        ceilings
+            .db
+            .write(|tx| put_escalation(tx, &expired))
+            .unwrap();
+        assert_eq!(ceilings.effective_ceiling("u1"), PermissionLevel::Read);
     }
 }
@@ -5,8 +5,8 @@ pub mod secret_store;
 
 pub use agent_credentials::{AgentCredential, CredentialProvider, LocalCredentialProvider};
 pub use capability_ceiling::{
-    CeilingManager, CeilingRole, PermissionLevel, CEILING_ROLES, DEFAULT_AGENT_CEILING,
-    PERMISSION_LEVELS,
+    tool_permission_level, CeilingManager, CeilingRole, EscalationRequest, PermissionLevel,
+    CEILING_ROLES, DEFAULT_AGENT_CEILING, PERMISSION_LEVELS,
 };
 pub use key_rotation::{generate_key_id, KeyRotationManager, KeyRotationState, VersionedIdentity};
 pub use secret_store::{Identity, Integration, MultiTenantSecretStore, SharingTier};
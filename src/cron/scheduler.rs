@@ -6,6 +6,7 @@ use crate::cron::{
     due_jobs, next_run_for_schedule, record_last_run, record_run, remove_job, reschedule_after_run,
     update_job, CronJob, CronJobPatch, DeliveryConfig, JobType, Schedule, SessionTarget,
 };
+use crate::observability::{Observer, ObserverEvent};
 use crate::security::SecurityPolicy;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
@@ -27,6 +28,8 @@ pub async fn run(config: Config) -> Result<()> {
         &config.autonomy,
         &config.workspace_dir,
     ));
+    let observer: Arc<dyn Observer> =
+        Arc::from(crate::observability::create_observer(&config.observability));
 
     crate::health::mark_component_ok(SCHEDULER_COMPONENT);
 
@@ -44,7 +47,7 @@ pub async fn run(config: Config) -> Result<()> {
             }
         };
 
-        process_due_jobs(&config, &security, jobs, SCHEDULER_COMPONENT).await;
+        process_due_jobs(&config, &security, &observer, jobs, SCHEDULER_COMPONENT).await;
     }
 }
 
@@ -91,6 +94,7 @@ async fn execute_job_with_retry(
 async fn process_due_jobs(
     config: &Config,
     security: &Arc<SecurityPolicy>,
+    observer: &Arc<dyn Observer>,
     jobs: Vec<CronJob>,
     component: &str,
 ) {
@@ -98,18 +102,23 @@ async fn process_due_jobs(
     crate::health::mark_component_ok(component);
 
     let max_concurrent = config.scheduler.max_concurrent.max(1);
-    let mut in_flight =
-        stream::iter(
-            jobs.into_iter().map(|job| {
-                let config = config.clone();
-                let security = Arc::clone(security);
-                let component = component.to_owned();
-                async move {
-                    execute_and_persist_job(&config, security.as_ref(), &job, &component).await
-                }
-            }),
-        )
-        .buffer_unordered(max_concurrent);
+    let mut in_flight = stream::iter(jobs.into_iter().map(|job| {
+        let config = config.clone();
+        let security = Arc::clone(security);
+        let observer = Arc::clone(observer);
+        let component = component.to_owned();
+        async move {
+            execute_and_persist_job(
+                &config,
+                security.as_ref(),
+                observer.as_ref(),
+                &job,
+                &component,
+            )
+            .await
+        }
+    }))
+    .buffer_unordered(max_concurrent);
 
     while let Some((job_id, success)) = in_flight.next().await {
         if !success {
@@ -121,6 +130,7 @@ async fn process_due_jobs(
 async fn execute_and_persist_job(
     config: &Config,
     security: &SecurityPolicy,
+    observer: &dyn Observer,
     job: &CronJob,
     component: &str,
 ) -> (String, bool) {
@@ -131,6 +141,12 @@ async fn execute_and_persist_job(
     let (success, output) = execute_job_with_retry(config, security, job).await;
     let finished_at = Utc::now();
     let success = persist_job_result(config, job, success, &output, started_at, finished_at).await;
+    observer.record_event(&ObserverEvent::CronRun {
+        job_id: job.id.clone(),
+        name: job.name.clone(),
+        success,
+        duration: (finished_at - started_at).to_std().unwrap_or_default(),
+    });
 
     (job.id.clone(), success)
 }
@@ -752,7 +768,8 @@ mod tests {
         let component = unique_component("scheduler-idle");
 
         crate::health::mark_component_error(&component, "pre-existing error");
-        process_due_jobs(&config, &security, Vec::new(), &component).await;
+        let observer: Arc<dyn Observer> = Arc::new(crate::observability::NoopObserver);
+        process_due_jobs(&config, &security, &observer, Vec::new(), &component).await;
 
         let snapshot = crate::health::snapshot_json();
         let entry = &snapshot["components"][component.as_str()];
@@ -773,7 +790,8 @@ mod tests {
         let component = unique_component("scheduler-fail");
 
         crate::health::mark_component_ok(&component);
-        process_due_jobs(&config, &security, vec![job], &component).await;
+        let observer: Arc<dyn Observer> = Arc::new(crate::observability::NoopObserver);
+        process_due_jobs(&config, &security, &observer, vec![job], &component).await;
 
         let snapshot = crate::health::snapshot_json();
         let entry = &snapshot["components"][component.as_str()];
@@ -222,6 +222,7 @@ fn has_supervised_channels(config: &Config) -> bool {
         .channels_except_webhook()
         .iter()
         .any(|(_, ok)| *ok)
+        || crate::channels::HardwareChannel::is_configured(config)
 }
 
 #[cfg(test)]
@@ -292,6 +293,24 @@ mod tests {
         assert!(!has_supervised_channels(&config));
     }
 
+    #[test]
+    fn hardware_subscriptions_are_supervised() {
+        let mut config = Config::default();
+        config.peripherals.enabled = true;
+        config.peripherals.subscriptions = toml::from_str::<crate::config::PeripheralsConfig>(
+            r#"
+            [[subscriptions]]
+            name = "door"
+            board = "uno"
+            kind = "gpio_edge"
+            pin = 2
+            "#,
+        )
+        .unwrap()
+        .subscriptions;
+        assert!(has_supervised_channels(&config));
+    }
+
     #[test]
     fn detects_supervised_channels_present() {
         let mut config = Config::default();
@@ -1,8 +1,12 @@
 //! REST API handlers for the web dashboard.
 //!
 //! All `/api/*` routes require bearer token authentication (PairingGuard).
+//! Read-only routes need the `read` scope; anything that mutates config,
+//! memory, cron jobs or tokens needs `admin`, as do the outbound webhook
+//! routes, whose URLs may embed credentials.
 
 use super::AppState;
+use crate::security::pairing::{GatewayToken, TokenScope};
 use axum::{
     extract::{Path, Query, State},
     http::{header, HeaderMap, StatusCode},
@@ -20,25 +24,32 @@ fn extract_bearer_token(headers: &HeaderMap) -> Option<&str> {
         .and_then(|auth| auth.strip_prefix("Bearer "))
 }
 
-/// Verify bearer token against PairingGuard. Returns error response if unauthorized.
+/// Verify bearer token against PairingGuard and check it grants `scope`.
+/// Returns 401 for missing/invalid/expired tokens and 403 for insufficient scope.
 fn require_auth(
     state: &AppState,
     headers: &HeaderMap,
+    scope: TokenScope,
 ) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
     if !state.pairing.require_pairing() {
         return Ok(());
     }
 
     let token = extract_bearer_token(headers).unwrap_or("");
-    if state.pairing.is_authenticated(token) {
-        Ok(())
-    } else {
-        Err((
+    match state.pairing.authenticate(token) {
+        Some(record) if record.grants(scope) => Ok(()),
+        Some(_) => Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({
+                "error": format!("Forbidden — token lacks the '{}' scope", scope.as_str())
+            })),
+        )),
+        None => Err((
             StatusCode::UNAUTHORIZED,
             Json(serde_json::json!({
                 "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
             })),
-        ))
+        )),
     }
 }
 
@@ -57,6 +68,29 @@ pub struct MemoryStoreBody {
     pub category: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct TokenCreateBody {
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    /// Time-to-live in seconds; omitted means the token never expires.
+    pub ttl_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct WebhookCreateBody {
+    pub url: String,
+    /// Events to deliver; omitted or `["*"]` means all of them.
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// HMAC signing secret; generated when omitted.
+    pub secret: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DeadLetterQuery {
+    pub limit: Option<usize>,
+}
+
 #[derive(Deserialize)]
 pub struct CronAddBody {
     pub name: Option<String>,
@@ -71,7 +105,7 @@ pub async fn handle_api_status(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Read) {
         return e.into_response();
     }
 
@@ -105,7 +139,7 @@ pub async fn handle_api_config_get(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
         return e.into_response();
     }
 
@@ -139,7 +173,7 @@ pub async fn handle_api_config_put(
     headers: HeaderMap,
     body: String,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
         return e.into_response();
     }
 
@@ -175,14 +209,15 @@ pub async fn handle_api_tools(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Read) {
         return e.into_response();
     }
 
     let tools: Vec<serde_json::Value> = state
         .tools_registry
         .iter()
-        .map(|spec| {
+        .map(|tool| {
+            let spec = tool.spec();
             serde_json::json!({
                 "name": spec.name,
                 "description": spec.description,
@@ -199,7 +234,7 @@ pub async fn handle_api_cron_list(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Read) {
         return e.into_response();
     }
 
@@ -236,7 +271,7 @@ pub async fn handle_api_cron_add(
     headers: HeaderMap,
     Json(body): Json<CronAddBody>,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
         return e.into_response();
     }
 
@@ -271,7 +306,7 @@ pub async fn handle_api_cron_delete(
     headers: HeaderMap,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
         return e.into_response();
     }
 
@@ -291,7 +326,7 @@ pub async fn handle_api_integrations(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Read) {
         return e.into_response();
     }
 
@@ -319,7 +354,7 @@ pub async fn handle_api_doctor(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
         return e.into_response();
     }
 
@@ -356,7 +391,7 @@ pub async fn handle_api_memory_list(
     headers: HeaderMap,
     Query(params): Query<MemoryQuery>,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Read) {
         return e.into_response();
     }
 
@@ -396,7 +431,7 @@ pub async fn handle_api_memory_store(
     headers: HeaderMap,
     Json(body): Json<MemoryStoreBody>,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
         return e.into_response();
     }
 
@@ -431,7 +466,7 @@ pub async fn handle_api_memory_delete(
     headers: HeaderMap,
     Path(key): Path<String>,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
         return e.into_response();
     }
 
@@ -452,7 +487,7 @@ pub async fn handle_api_cost(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Read) {
         return e.into_response();
     }
 
@@ -485,7 +520,7 @@ pub async fn handle_api_cli_tools(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Read) {
         return e.into_response();
     }
 
@@ -499,7 +534,7 @@ pub async fn handle_api_health(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if let Err(e) = require_auth(&state, &headers) {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Read) {
         return e.into_response();
     }
 
@@ -507,8 +542,206 @@ pub async fn handle_api_health(
     Json(serde_json::json!({"health": snapshot})).into_response()
 }
 
+/// GET /api/tokens — list gateway tokens (metadata only, never plaintext)
+pub async fn handle_api_tokens_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
+        return e.into_response();
+    }
+
+    let tokens: Vec<serde_json::Value> = state
+        .pairing
+        .list_tokens()
+        .iter()
+        .map(token_to_json)
+        .collect();
+
+    Json(serde_json::json!({"tokens": tokens})).into_response()
+}
+
+/// POST /api/tokens — mint a named, scoped token
+pub async fn handle_api_tokens_create(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<TokenCreateBody>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
+        return e.into_response();
+    }
+
+    let ttl = match body.ttl_secs.map(i64::try_from).transpose() {
+        Ok(secs) => secs.map(chrono::Duration::seconds),
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": "ttl_secs is out of range"})),
+            )
+                .into_response();
+        }
+    };
+
+    match state.pairing.create_token(&body.name, &body.scopes, ttl) {
+        Ok((token, record)) => Json(serde_json::json!({
+            "status": "ok",
+            "token": token,
+            "info": token_to_json(&record),
+            "message": "Save this token — it will not be shown again",
+        }))
+        .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("Failed to create token: {e}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// DELETE /api/tokens/:name — revoke a gateway token
+pub async fn handle_api_tokens_revoke(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
+        return e.into_response();
+    }
+
+    let removed = match state.pairing.revoke_token(&name) {
+        Ok(Some(removed)) => removed,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({"error": format!("No token named '{name}'")})),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Failed to revoke token: {e}")})),
+            )
+                .into_response();
+        }
+    };
+
+    if removed.paired {
+        if let Err(e) = super::persist_pairing_tokens(state.config.clone(), &state.pairing).await {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Token revoked in memory but config update failed: {e}")
+                })),
+            )
+                .into_response();
+        }
+    }
+
+    Json(serde_json::json!({"status": "ok", "revoked": name})).into_response()
+}
+
+/// GET /api/webhooks — list outbound webhook subscriptions (secrets omitted)
+pub async fn handle_api_webhooks_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
+        return e.into_response();
+    }
+
+    let webhooks: Vec<serde_json::Value> = state
+        .webhooks
+        .list()
+        .iter()
+        .map(|subscription| subscription.to_json())
+        .collect();
+
+    Json(serde_json::json!({
+        "webhooks": webhooks,
+        "events": super::webhooks::WEBHOOK_EVENTS,
+    }))
+    .into_response()
+}
+
+/// POST /api/webhooks — subscribe a URL to agent events
+pub async fn handle_api_webhooks_create(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<WebhookCreateBody>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
+        return e.into_response();
+    }
+
+    match state.webhooks.add(&body.url, body.events, body.secret) {
+        Ok(subscription) => Json(serde_json::json!({
+            "status": "ok",
+            "webhook": subscription.to_json(),
+            "secret": subscription.secret,
+            "message": "Save this secret — it will not be shown again",
+        }))
+        .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("Failed to add webhook: {e:#}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// DELETE /api/webhooks/:id — remove a webhook subscription
+pub async fn handle_api_webhooks_delete(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
+        return e.into_response();
+    }
+
+    match state.webhooks.remove(&id) {
+        Ok(true) => Json(serde_json::json!({"status": "ok", "deleted": id})).into_response(),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": format!("No webhook with id '{id}'")})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to remove webhook: {e:#}")})),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/webhooks/dead-letters — deliveries that failed after every retry
+pub async fn handle_api_webhooks_dead_letters(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<DeadLetterQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = require_auth(&state, &headers, TokenScope::Admin) {
+        return e.into_response();
+    }
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    Json(serde_json::json!({"dead_letters": state.webhooks.dead_letters(limit)})).into_response()
+}
+
 // ── Helpers ─────────────────────────────────────────────────────
 
+fn token_to_json(token: &GatewayToken) -> serde_json::Value {
+    serde_json::json!({
+        "name": token.name,
+        "scopes": token.scopes,
+        "created_at": token.created_at.to_rfc3339(),
+        "expires_at": token.expires_at.map(|t| t.to_rfc3339()),
+        "last_used_at": token.last_used_at.map(|t| t.to_rfc3339()),
+        "paired": token.paired,
+    })
+}
+
 fn mask_sensitive_fields(toml_str: &str) -> String {
     let mut output = String::with_capacity(toml_str.len());
     for line in toml_str.lines() {
@@ -521,7 +754,7 @@ fn mask_sensitive_fields(toml_str: &str) -> String {
             || trimmed.starts_with("signing_secret")
         {
             if let Some(eq_pos) = line.find('=') {
-                output.push_str(&line[..eq_pos + 1]);
+                output.push_str(&line[..=eq_pos]);
                 output.push_str(" \"***MASKED***\"");
             } else {
                 output.push_str(line);
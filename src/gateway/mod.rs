@@ -7,12 +7,21 @@
 //! - Request timeouts (30s) to prevent slow-loris attacks
 //! - Header sanitization (handled by axum/hyper)
 
-use crate::channels::{Channel, LinqChannel, NextcloudTalkChannel, SendMessage, WhatsAppChannel};
+pub mod api;
+pub mod sse;
+pub mod webhooks;
+pub mod ws;
+
+use crate::channels::{
+    attachments, Channel, LinqChannel, NextcloudTalkChannel, SendMessage, WhatsAppChannel,
+};
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::providers::{self, ChatMessage, Provider};
 use crate::runtime;
-use crate::security::pairing::{constant_time_eq, is_public_bind, PairingGuard};
+use crate::security::pairing::{
+    constant_time_eq, is_public_bind, token_store_path, PairingGuard, TokenScope,
+};
 use crate::security::SecurityPolicy;
 use crate::tools;
 use crate::util::truncate_with_ellipsis;
@@ -22,7 +31,7 @@ use axum::{
     extract::{ConnectInfo, Query, State},
     http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Json},
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use parking_lot::Mutex;
@@ -291,6 +300,14 @@ pub struct AppState {
     /// Observability backend for metrics scraping
     pub observer: Arc<dyn crate::observability::Observer>,
     pub tools_registry: Arc<Vec<Box<dyn crate::tools::Tool>>>,
+    /// Cost tracker backing `/api/cost` (only when `[cost] enabled = true`)
+    pub cost_tracker: Option<Arc<crate::cost::CostTracker>>,
+    /// Resumable `/ws/chat` sessions
+    pub ws_sessions: Arc<ws::WsSessions>,
+    /// Tool rules and lifecycle hooks for agent turns run by the gateway
+    pub hooks: Option<Arc<crate::hooks::HookRunner>>,
+    /// Outbound webhook subscriptions (`/api/webhooks`)
+    pub webhooks: Arc<webhooks::OutboundWebhooks>,
 }
 
 /// Run the HTTP gateway using axum with proper HTTP/1.1 compliance.
@@ -341,6 +358,7 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         &config.autonomy,
         &config.workspace_dir,
     ));
+    attachments::set_outgoing_file_policy(Arc::clone(&security));
 
     let (composio_key, composio_entity_id) = if config.composio.enabled {
         (
@@ -351,6 +369,7 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         (None, None)
     };
 
+    let hooks = crate::hooks::HookRunner::from_config(&config)?.map(Arc::new);
     let tools_registry = Arc::new(tools::all_tools_with_runtime(
         Arc::new(config.clone()),
         &security,
@@ -364,6 +383,7 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         &config.agents,
         config.api_key.as_deref(),
         &config,
+        hooks.clone(),
     ));
 
     // Extract webhook secret for authentication
@@ -473,10 +493,7 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
             .map(Arc::from);
 
     // ── Pairing guard ──────────────────────────────────────
-    let pairing = Arc::new(PairingGuard::new(
-        config.gateway.require_pairing,
-        &config.gateway.paired_tokens,
-    ));
+    let pairing = Arc::new(create_pairing_guard(&config)?);
     let rate_limit_max_keys = normalize_max_keys(
         config.gateway.rate_limit_max_keys,
         RATE_LIMIT_MAX_KEYS_DEFAULT,
@@ -548,8 +565,47 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     crate::health::mark_component_ok("gateway");
 
     // Build shared state
+    crate::observability::events::init_from_config(&config.observability, &config.workspace_dir);
     let observer: Arc<dyn crate::observability::Observer> =
         Arc::from(crate::observability::create_observer(&config.observability));
+    if config.security.estop.enabled {
+        if let Some(config_dir) = config.config_path.parent() {
+            tokio::spawn(watch_estop_state(
+                config.security.estop.clone(),
+                config_dir.to_path_buf(),
+                Arc::clone(&observer),
+            ));
+        }
+    }
+    let cost_tracker = if config.cost.enabled {
+        match crate::cost::CostTracker::new(config.cost.clone(), &config.workspace_dir) {
+            Ok(tracker) => Some(Arc::new(tracker)),
+            Err(err) => {
+                tracing::warn!("Cost tracking unavailable for gateway: {err:#}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    if let Some(tracker) = &cost_tracker {
+        tokio::spawn(watch_budget(Arc::clone(tracker), Arc::clone(&observer)));
+    }
+
+    let webhooks = Arc::new(
+        webhooks::OutboundWebhooks::load(&config).unwrap_or_else(|e| {
+            tracing::warn!("Outbound webhooks unavailable: {e:#}");
+            webhooks::OutboundWebhooks::default()
+        }),
+    );
+    match crate::observability::events::hub() {
+        Some(hub) => {
+            tokio::spawn(Arc::clone(&webhooks).run(hub));
+        }
+        None => tracing::warn!(
+            "Outbound webhooks are disabled because observability.events_buffer_size = 0"
+        ),
+    }
 
     let state = AppState {
         config: config_state,
@@ -571,10 +627,27 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         nextcloud_talk_webhook_secret,
         observer,
         tools_registry,
+        cost_tracker,
+        ws_sessions: Arc::new(ws::WsSessions::default()),
+        hooks,
+        webhooks,
     };
 
-    // Build router with middleware
-    let app = Router::new()
+    let app = build_router(state);
+
+    // Run the server
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Gateway routes with the body size and timeout limits applied.
+fn build_router(state: AppState) -> Router {
+    Router::new()
         .route("/health", get(handle_health))
         .route("/metrics", get(handle_metrics))
         .route("/pair", post(handle_pair))
@@ -583,21 +656,144 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .route("/whatsapp", post(handle_whatsapp_message))
         .route("/linq", post(handle_linq_webhook))
         .route("/nextcloud-talk", post(handle_nextcloud_talk_webhook))
+        .route("/api/status", get(api::handle_api_status))
+        .route(
+            "/api/config",
+            get(api::handle_api_config_get).put(api::handle_api_config_put),
+        )
+        .route("/api/tools", get(api::handle_api_tools))
+        .route(
+            "/api/cron",
+            get(api::handle_api_cron_list).post(api::handle_api_cron_add),
+        )
+        .route("/api/cron/{id}", delete(api::handle_api_cron_delete))
+        .route("/api/integrations", get(api::handle_api_integrations))
+        .route("/api/doctor", post(api::handle_api_doctor))
+        .route(
+            "/api/memory",
+            get(api::handle_api_memory_list).post(api::handle_api_memory_store),
+        )
+        .route("/api/memory/{key}", delete(api::handle_api_memory_delete))
+        .route("/api/cost", get(api::handle_api_cost))
+        .route("/api/cli-tools", get(api::handle_api_cli_tools))
+        .route("/api/health", get(api::handle_api_health))
+        .route(
+            "/api/tokens",
+            get(api::handle_api_tokens_list).post(api::handle_api_tokens_create),
+        )
+        .route("/api/tokens/{name}", delete(api::handle_api_tokens_revoke))
+        .route("/api/events", get(sse::handle_sse_events))
+        .route(
+            "/api/webhooks",
+            get(api::handle_api_webhooks_list).post(api::handle_api_webhooks_create),
+        )
+        .route(
+            "/api/webhooks/dead-letters",
+            get(api::handle_api_webhooks_dead_letters),
+        )
+        .route(
+            "/api/webhooks/{id}",
+            delete(api::handle_api_webhooks_delete),
+        )
+        .route("/ws/chat", get(ws::handle_ws_chat))
         .with_state(state)
         .layer(RequestBodyLimitLayer::new(MAX_BODY_SIZE))
         .layer(TimeoutLayer::with_status_code(
             StatusCode::REQUEST_TIMEOUT,
             Duration::from_secs(REQUEST_TIMEOUT_SECS),
-        ));
+        ))
+}
 
-    // Run the server
-    axum::serve(
-        listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
-    )
-    .await?;
+/// How often the gateway checks the estop state file for changes.
+const ESTOP_POLL_SECS: u64 = 2;
+
+/// How often the gateway checks spending against the cost budget.
+const BUDGET_POLL_SECS: u64 = 60;
+
+/// Poll the estop state file and record an event whenever it changes.
+///
+/// `zeroclaw estop` runs in its own process, so the gateway learns about
+/// changes from the persisted state.
+async fn watch_estop_state(
+    estop: crate::config::EstopConfig,
+    config_dir: std::path::PathBuf,
+    observer: Arc<dyn crate::observability::Observer>,
+) {
+    let load = || {
+        crate::security::EstopManager::load(&estop, &config_dir)
+            .ok()
+            .map(|manager| {
+                let state = manager.status();
+                (state.is_engaged(), state.summary())
+            })
+    };
+    let mut last = load();
+    let mut interval = tokio::time::interval(Duration::from_secs(ESTOP_POLL_SECS));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        interval.tick().await;
+        let Some((engaged, detail)) = load() else {
+            continue;
+        };
+        if last.as_ref().map(|(_, previous)| previous) != Some(&detail) {
+            observer.record_event(&crate::observability::ObserverEvent::EstopChange {
+                engaged,
+                detail: detail.clone(),
+            });
+        }
+        last = Some((engaged, detail));
+    }
+}
 
-    Ok(())
+/// Check the cost budget and record an event when spending first crosses the
+/// warning threshold or a limit.
+async fn watch_budget(
+    tracker: Arc<crate::cost::CostTracker>,
+    observer: Arc<dyn crate::observability::Observer>,
+) {
+    use crate::cost::{BudgetCheck, UsagePeriod};
+
+    let mut last: Option<(UsagePeriod, bool)> = None;
+    let mut interval = tokio::time::interval(Duration::from_secs(BUDGET_POLL_SECS));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        interval.tick().await;
+        let (period, current_usd, limit_usd, exceeded) = match tracker.check_budget(0.0) {
+            Ok(BudgetCheck::Warning {
+                current_usd,
+                limit_usd,
+                period,
+            }) => (period, current_usd, limit_usd, false),
+            Ok(BudgetCheck::Exceeded {
+                current_usd,
+                limit_usd,
+                period,
+            }) => (period, current_usd, limit_usd, true),
+            Ok(BudgetCheck::Allowed) => {
+                last = None;
+                continue;
+            }
+            Err(e) => {
+                tracing::warn!("Budget check failed: {e:#}");
+                continue;
+            }
+        };
+        if last == Some((period, exceeded)) {
+            continue;
+        }
+        last = Some((period, exceeded));
+        observer.record_event(&crate::observability::ObserverEvent::BudgetWarning {
+            period: match period {
+                UsagePeriod::Session => "session",
+                UsagePeriod::Day => "day",
+                UsagePeriod::Month => "month",
+            }
+            .to_string(),
+            current_usd,
+            limit_usd,
+            exceeded,
+        });
+    }
 }
 
 // ══════════════════════════════════════════════════════════════════════════════
@@ -700,6 +896,21 @@ async fn handle_pair(
     }
 }
 
+/// Build the gateway pairing guard, backed by the token store next to `config.toml`.
+fn create_pairing_guard(config: &Config) -> Result<PairingGuard> {
+    match config.config_path.parent() {
+        Some(config_dir) => PairingGuard::with_token_store(
+            config.gateway.require_pairing,
+            &config.gateway.paired_tokens,
+            &token_store_path(config_dir),
+        ),
+        None => Ok(PairingGuard::new(
+            config.gateway.require_pairing,
+            &config.gateway.paired_tokens,
+        )),
+    }
+}
+
 async fn persist_pairing_tokens(config: Arc<Mutex<Config>>, pairing: &PairingGuard) -> Result<()> {
     let paired_tokens = pairing.tokens();
     // This is needed because parking_lot's guard is not Send so we clone the inner
@@ -716,6 +927,81 @@ async fn persist_pairing_tokens(config: Arc<Mutex<Config>>, pairing: &PairingGua
     Ok(())
 }
 
+/// Handle `zeroclaw gateway <subcommand>` management commands.
+pub async fn handle_command(command: crate::GatewayCommands, config: &Config) -> Result<()> {
+    match command {
+        crate::GatewayCommands::Tokens { token_command } => {
+            handle_token_command(token_command, config).await
+        }
+    }
+}
+
+async fn handle_token_command(command: crate::GatewayTokenCommands, config: &Config) -> Result<()> {
+    let pairing = create_pairing_guard(config)?;
+    match command {
+        crate::GatewayTokenCommands::List => {
+            let tokens = pairing.list_tokens();
+            if tokens.is_empty() {
+                println!("No gateway tokens yet.");
+                println!("\nUsage:");
+                println!("  zeroclaw gateway tokens create <name> --scope webhook");
+                return Ok(());
+            }
+            let now = chrono::Utc::now();
+            println!("🔐 Gateway tokens ({}):", tokens.len());
+            for token in tokens {
+                let scopes = token
+                    .scopes
+                    .iter()
+                    .map(|scope| scope.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let expires = token
+                    .expires_at
+                    .map_or_else(|| "never".into(), |t| t.to_rfc3339());
+                let last_used = token
+                    .last_used_at
+                    .map_or_else(|| "never".into(), |t| t.to_rfc3339());
+                let status = if token.is_expired(now) {
+                    " (expired)"
+                } else {
+                    ""
+                };
+                println!(
+                    "- {}{status} | scopes={scopes} | expires={expires} | last_used={last_used}",
+                    token.name
+                );
+            }
+            Ok(())
+        }
+        crate::GatewayTokenCommands::Create { name, scopes, ttl } => {
+            let scopes = scopes
+                .iter()
+                .map(|scope| scope.parse::<TokenScope>())
+                .collect::<Result<Vec<_>>>()?;
+            let ttl = ttl.as_deref().map(crate::cron::parse_delay).transpose()?;
+            let (token, record) = pairing.create_token(&name, &scopes, ttl)?;
+            println!("✅ Created gateway token '{}'", record.name);
+            if let Some(expires_at) = record.expires_at {
+                println!("  Expires: {}", expires_at.to_rfc3339());
+            }
+            println!("  Token  : {token}");
+            println!("  Save this token now — it will not be shown again.");
+            Ok(())
+        }
+        crate::GatewayTokenCommands::Revoke { name } => {
+            let Some(removed) = pairing.revoke_token(&name)? else {
+                anyhow::bail!("No gateway token named '{name}'");
+            };
+            if removed.paired {
+                persist_pairing_tokens(Arc::new(Mutex::new(config.clone())), &pairing).await?;
+            }
+            println!("✅ Revoked gateway token '{name}'");
+            Ok(())
+        }
+    }
+}
+
 /// Simple chat for webhook endpoint (no tools, for backward compatibility and testing).
 async fn run_gateway_chat_simple(
     state: &AppState,
@@ -753,9 +1039,27 @@ async fn run_gateway_chat_simple(
 }
 
 /// Full-featured chat with tools for channel handlers (WhatsApp, Linq, Nextcloud Talk).
-async fn run_gateway_chat_with_tools(state: &AppState, message: &str) -> anyhow::Result<String> {
+async fn run_gateway_chat_with_tools(
+    state: &AppState,
+    msg: &crate::channels::traits::ChannelMessage,
+) -> anyhow::Result<String> {
+    let record = |direction: &str| {
+        state
+            .observer
+            .record_event(&crate::observability::ObserverEvent::ChannelMessage {
+                channel: msg.channel.clone(),
+                direction: direction.to_string(),
+            });
+    };
+    record("inbound");
     let config = state.config.lock().clone();
-    crate::agent::process_message(config, message).await
+    let response = crate::observability::events::with_channel(
+        msg.channel.clone(),
+        Box::pin(crate::agent::process_message(config, &msg.content)),
+    )
+    .await?;
+    record("outbound");
+    Ok(response)
 }
 
 /// Webhook request body
@@ -789,12 +1093,25 @@ async fn handle_webhook(
             .and_then(|v| v.to_str().ok())
             .unwrap_or("");
         let token = auth.strip_prefix("Bearer ").unwrap_or("");
-        if !state.pairing.is_authenticated(token) {
-            tracing::warn!("Webhook: rejected — not paired / invalid bearer token");
-            let err = serde_json::json!({
-                "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
-            });
-            return (StatusCode::UNAUTHORIZED, Json(err));
+        match state.pairing.authenticate(token) {
+            None => {
+                tracing::warn!("Webhook: rejected — not paired / invalid bearer token");
+                let err = serde_json::json!({
+                    "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
+                });
+                return (StatusCode::UNAUTHORIZED, Json(err));
+            }
+            Some(record) if !record.grants(TokenScope::Webhook) => {
+                tracing::warn!(
+                    "Webhook: rejected — token '{}' lacks webhook scope",
+                    record.name
+                );
+                let err = serde_json::json!({
+                    "error": "Forbidden — token lacks the 'webhook' scope"
+                });
+                return (StatusCode::FORBIDDEN, Json(err));
+            }
+            Some(_) => {}
         }
     }
 
@@ -1059,7 +1376,18 @@ async fn handle_whatsapp_message(
     };
 
     // Parse messages from the webhook payload
-    let messages = wa.parse_webhook_payload(&payload);
+    let mut messages = wa.parse_webhook_payload(&payload);
+
+    // Voice notes arrive as media IDs; download and transcribe them so the
+    // agent sees text. Without transcription they are dropped as before.
+    let transcription = state.config.lock().transcription.clone();
+    for msg in &mut messages {
+        if transcription.enabled && !msg.attachments.is_empty() {
+            wa.download_media(msg).await;
+            crate::channels::transcription::transcribe_attachments(msg, &transcription).await;
+        }
+    }
+    messages.retain(|msg| !msg.content.trim().is_empty());
 
     if messages.is_empty() {
         // Acknowledge the webhook even if no messages (could be status updates)
@@ -1083,7 +1411,7 @@ async fn handle_whatsapp_message(
                 .await;
         }
 
-        match run_gateway_chat_with_tools(&state, &msg.content).await {
+        match run_gateway_chat_with_tools(&state, msg).await {
             Ok(response) => {
                 // Send reply via WhatsApp
                 if let Err(e) = wa
@@ -1191,9 +1519,10 @@ async fn handle_linq_webhook(
         }
 
         // Call the LLM
-        match run_gateway_chat_with_tools(&state, &msg.content).await {
+        match run_gateway_chat_with_tools(&state, msg).await {
             Ok(response) => {
                 // Send reply via Linq
+                let response = attachments::attachments_as_text(linq.name(), &response);
                 if let Err(e) = linq
                     .send(&SendMessage::new(response, &msg.reply_target))
                     .await
@@ -1302,8 +1631,9 @@ async fn handle_nextcloud_talk_webhook(
                 .await;
         }
 
-        match run_gateway_chat_with_tools(&state, &msg.content).await {
+        match run_gateway_chat_with_tools(&state, msg).await {
             Ok(response) => {
+                let response = attachments::attachments_as_text(nextcloud_talk.name(), &response);
                 if let Err(e) = nextcloud_talk
                     .send(&SendMessage::new(response, &msg.reply_target))
                     .await
@@ -1405,6 +1735,10 @@ mod tests {
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
             tools_registry: Arc::new(vec![]),
+            cost_tracker: None,
+            ws_sessions: Arc::new(ws::WsSessions::default()),
+            hooks: None,
+            webhooks: Arc::new(webhooks::OutboundWebhooks::default()),
         };
 
         let response = handle_metrics(State(state)).await.into_response();
@@ -1451,6 +1785,10 @@ mod tests {
             nextcloud_talk_webhook_secret: None,
             observer,
             tools_registry: Arc::new(vec![]),
+            cost_tracker: None,
+            ws_sessions: Arc::new(ws::WsSessions::default()),
+            hooks: None,
+            webhooks: Arc::new(webhooks::OutboundWebhooks::default()),
         };
 
         let response = handle_metrics(State(state)).await.into_response();
@@ -1461,6 +1799,96 @@ mod tests {
         assert!(text.contains("zeroclaw_heartbeat_ticks_total 1"));
     }
 
+    #[tokio::test]
+    async fn api_routes_enforce_token_scopes() {
+        use crate::security::pairing::TokenScope;
+        use tower::Service;
+
+        let pairing = PairingGuard::new(true, &[]);
+        let token = |scope: TokenScope| {
+            pairing
+                .create_token(scope.as_str(), &[scope], None)
+                .unwrap()
+                .0
+        };
+        let webhook = token(TokenScope::Webhook);
+        let read = token(TokenScope::Read);
+        let admin = token(TokenScope::Admin);
+        let state = AppState {
+            config: Arc::new(Mutex::new(Config::default())),
+            provider: Arc::new(MockProvider::default()),
+            model: "test-model".into(),
+            temperature: 0.0,
+            mem: Arc::new(MockMemory),
+            auto_save: false,
+            webhook_secret_hash: None,
+            pairing: Arc::new(pairing),
+            trust_forwarded_headers: false,
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300), 1000)),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            linq: None,
+            linq_signing_secret: None,
+            nextcloud_talk: None,
+            nextcloud_talk_webhook_secret: None,
+            observer: Arc::new(crate::observability::NoopObserver),
+            tools_registry: Arc::new(vec![]),
+            cost_tracker: None,
+            ws_sessions: Arc::new(ws::WsSessions::default()),
+            hooks: None,
+            webhooks: Arc::new(webhooks::OutboundWebhooks::default()),
+        };
+        let mut app = build_router(state);
+
+        let cases = [
+            ("GET", "/api/tools", None, StatusCode::UNAUTHORIZED),
+            (
+                "GET",
+                "/api/tools",
+                Some("zc_bogus"),
+                StatusCode::UNAUTHORIZED,
+            ),
+            ("GET", "/api/tools", Some(&webhook), StatusCode::FORBIDDEN),
+            ("GET", "/api/tools", Some(&read), StatusCode::OK),
+            ("GET", "/api/tools", Some(&admin), StatusCode::OK),
+            ("GET", "/api/config", Some(&read), StatusCode::FORBIDDEN),
+            ("GET", "/api/tokens", Some(&webhook), StatusCode::FORBIDDEN),
+            ("GET", "/api/tokens", Some(&read), StatusCode::FORBIDDEN),
+            ("GET", "/api/tokens", Some(&admin), StatusCode::OK),
+            (
+                "DELETE",
+                "/api/tokens/read",
+                Some(&read),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                "DELETE",
+                "/api/memory/key",
+                Some(&read),
+                StatusCode::FORBIDDEN,
+            ),
+            ("GET", "/api/webhooks", Some(&read), StatusCode::FORBIDDEN),
+            (
+                "GET",
+                "/api/events",
+                Some(&webhook),
+                StatusCode::UNAUTHORIZED,
+            ),
+        ];
+        for (method, uri, token, expected) in cases {
+            let mut request = axum::http::Request::builder().method(method).uri(uri);
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+            }
+            let response = app
+                .call(request.body(axum::body::Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), expected, "{method} {uri} with {token:?}");
+        }
+    }
+
     #[test]
     fn gateway_rate_limiter_blocks_after_limit() {
         let limiter = GatewayRateLimiter::new(2, 2, 100);
@@ -1649,6 +2077,7 @@ mod tests {
             channel: "whatsapp".into(),
             timestamp: 1,
             thread_ts: None,
+            attachments: Vec::new(),
         };
 
         let key = whatsapp_memory_key(&msg);
@@ -1814,6 +2243,10 @@ mod tests {
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
             tools_registry: Arc::new(vec![]),
+            cost_tracker: None,
+            ws_sessions: Arc::new(ws::WsSessions::default()),
+            hooks: None,
+            webhooks: Arc::new(webhooks::OutboundWebhooks::default()),
         };
 
         let mut headers = HeaderMap::new();
@@ -1875,6 +2308,10 @@ mod tests {
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
             tools_registry: Arc::new(vec![]),
+            cost_tracker: None,
+            ws_sessions: Arc::new(ws::WsSessions::default()),
+            hooks: None,
+            webhooks: Arc::new(webhooks::OutboundWebhooks::default()),
         };
 
         let headers = HeaderMap::new();
@@ -1948,6 +2385,10 @@ mod tests {
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
             tools_registry: Arc::new(vec![]),
+            cost_tracker: None,
+            ws_sessions: Arc::new(ws::WsSessions::default()),
+            hooks: None,
+            webhooks: Arc::new(webhooks::OutboundWebhooks::default()),
         };
 
         let response = handle_webhook(
@@ -1993,6 +2434,10 @@ mod tests {
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
             tools_registry: Arc::new(vec![]),
+            cost_tracker: None,
+            ws_sessions: Arc::new(ws::WsSessions::default()),
+            hooks: None,
+            webhooks: Arc::new(webhooks::OutboundWebhooks::default()),
         };
 
         let mut headers = HeaderMap::new();
@@ -2043,6 +2488,10 @@ mod tests {
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
             tools_registry: Arc::new(vec![]),
+            cost_tracker: None,
+            ws_sessions: Arc::new(ws::WsSessions::default()),
+            hooks: None,
+            webhooks: Arc::new(webhooks::OutboundWebhooks::default()),
         };
 
         let mut headers = HeaderMap::new();
@@ -2098,6 +2547,10 @@ mod tests {
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
             tools_registry: Arc::new(vec![]),
+            cost_tracker: None,
+            ws_sessions: Arc::new(ws::WsSessions::default()),
+            hooks: None,
+            webhooks: Arc::new(webhooks::OutboundWebhooks::default()),
         };
 
         let response = handle_nextcloud_talk_webhook(
@@ -2149,6 +2602,10 @@ mod tests {
             nextcloud_talk_webhook_secret: Some(Arc::from(secret)),
             observer: Arc::new(crate::observability::NoopObserver),
             tools_registry: Arc::new(vec![]),
+            cost_tracker: None,
+            ws_sessions: Arc::new(ws::WsSessions::default()),
+            hooks: None,
+            webhooks: Arc::new(webhooks::OutboundWebhooks::default()),
         };
 
         let mut headers = HeaderMap::new();
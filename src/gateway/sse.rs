@@ -1,158 +1,171 @@
 //! Server-Sent Events (SSE) stream for real-time event delivery.
 //!
-//! Wraps the broadcast channel in AppState to deliver events to web dashboard clients.
+//! Relays the agent event hub ([`crate::observability::events`]) to web
+//! dashboard clients. Each SSE message carries the event id, its type as the
+//! SSE `event` name, and the JSON-encoded [`AgentEvent`] as data.
+//!
+//! Query parameters:
+//! - `types` — comma-separated event types to keep (e.g. `tool_call,cron_run`)
+//! - `channel` — comma-separated channel names to keep (e.g. `telegram`)
+//! - `last_event_id` — replay fallback for clients that cannot send headers
+//! - `token` — bearer token for browsers whose `EventSource` cannot set headers
+//!
+//! Reconnecting clients send `Last-Event-ID` and first receive every buffered
+//! event after that id, then the live feed.
 
 use super::AppState;
+use crate::observability::events::{self, AgentEvent, EventFilter, EventHub};
+use crate::security::pairing::TokenScope;
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::{header, HeaderMap, StatusCode},
     response::{
         sse::{Event, KeepAlive, Sse},
         IntoResponse,
     },
 };
+use futures_util::Stream;
+use serde::Deserialize;
 use std::convert::Infallible;
-use tokio_stream::wrappers::BroadcastStream;
+use std::sync::Arc;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
 use tokio_stream::StreamExt;
 
+#[derive(Debug, Default, Deserialize)]
+pub struct EventsQuery {
+    pub types: Option<String>,
+    pub channel: Option<String>,
+    pub last_event_id: Option<u64>,
+    pub token: Option<String>,
+}
+
 /// GET /api/events — SSE event stream
 pub async fn handle_sse_events(
     State(state): State<AppState>,
+    Query(params): Query<EventsQuery>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    // Auth check
+    // Auth check: header, or query param for browser EventSource clients
     if state.pairing.require_pairing() {
         let token = headers
             .get(header::AUTHORIZATION)
             .and_then(|v| v.to_str().ok())
             .and_then(|auth| auth.strip_prefix("Bearer "))
+            .or(params.token.as_deref())
             .unwrap_or("");
 
-        if !state.pairing.is_authenticated(token) {
+        if !state.pairing.is_authorized(token, TokenScope::Read) {
             return (
                 StatusCode::UNAUTHORIZED,
-                "Unauthorized — provide Authorization: Bearer <token>",
+                "Unauthorized — provide Authorization: Bearer <token> with the 'read' scope",
             )
                 .into_response();
         }
     }
 
-    let rx = state.event_tx.subscribe();
-    let stream = BroadcastStream::new(rx).filter_map(
-        |result: Result<
-            serde_json::Value,
-            tokio_stream::wrappers::errors::BroadcastStreamRecvError,
-        >| {
-            match result {
-                Ok(value) => Some(Ok::<_, Infallible>(
-                    Event::default().data(value.to_string()),
-                )),
-                Err(_) => None, // Skip lagged messages
-            }
-        },
-    );
+    let Some(hub) = events::hub() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Event stream disabled — set observability.events_buffer_size above 0",
+        )
+            .into_response();
+    };
+
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .or(params.last_event_id);
+    let filter = EventFilter::parse(params.types.as_deref(), params.channel.as_deref());
+
+    let stream = event_stream(&hub, filter, last_event_id).map(|event| {
+        Ok::<_, Infallible>(
+            Event::default()
+                .id(event.id.to_string())
+                .event(event.event_type.clone())
+                .data(serde_json::to_string(&event).unwrap_or_default()),
+        )
+    });
 
     Sse::new(stream)
         .keep_alive(KeepAlive::default())
         .into_response()
 }
 
-/// Broadcast observer that forwards events to the SSE broadcast channel.
-pub struct BroadcastObserver {
-    inner: Box<dyn crate::observability::Observer>,
-    tx: tokio::sync::broadcast::Sender<serde_json::Value>,
-}
-
-impl BroadcastObserver {
-    pub fn new(
-        inner: Box<dyn crate::observability::Observer>,
-        tx: tokio::sync::broadcast::Sender<serde_json::Value>,
-    ) -> Self {
-        Self { inner, tx }
-    }
-}
+/// Buffered events after `last_event_id` followed by the live feed, filtered.
+fn event_stream(
+    hub: &Arc<EventHub>,
+    filter: EventFilter,
+    last_event_id: Option<u64>,
+) -> impl Stream<Item = AgentEvent> {
+    // Subscribe before reading the buffer so nothing published in between is lost.
+    let live = BroadcastStream::new(hub.subscribe());
+    let replay = last_event_id.map_or_else(Vec::new, |id| hub.replay_after(id));
+    // An id from a wiped buffer must not hold back the live feed.
+    let mut cursor = last_event_id.unwrap_or(0).min(hub.last_id());
 
-impl crate::observability::Observer for BroadcastObserver {
-    fn record_event(&self, event: &crate::observability::ObserverEvent) {
-        // Forward to inner observer
-        self.inner.record_event(event);
-
-        // Broadcast to SSE subscribers
-        let json = match event {
-            crate::observability::ObserverEvent::LlmRequest {
-                provider, model, ..
-            } => serde_json::json!({
-                "type": "llm_request",
-                "provider": provider,
-                "model": model,
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-            }),
-            crate::observability::ObserverEvent::ToolCall {
-                tool,
-                duration,
-                success,
-            } => serde_json::json!({
-                "type": "tool_call",
-                "tool": tool,
-                "duration_ms": duration.as_millis(),
-                "success": success,
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-            }),
-            crate::observability::ObserverEvent::ToolCallStart { tool } => serde_json::json!({
-                "type": "tool_call_start",
-                "tool": tool,
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-            }),
-            crate::observability::ObserverEvent::Error { component, message } => {
-                serde_json::json!({
-                    "type": "error",
-                    "component": component,
-                    "message": message,
-                    "timestamp": chrono::Utc::now().to_rfc3339(),
-                })
+    let live = live.filter_map(
+        |result: Result<AgentEvent, BroadcastStreamRecvError>| match result {
+            Ok(event) => Some(event),
+            Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                tracing::warn!("SSE client lagged; skipped {missed} events");
+                None
             }
-            crate::observability::ObserverEvent::AgentStart { provider, model } => {
-                serde_json::json!({
-                    "type": "agent_start",
-                    "provider": provider,
-                    "model": model,
-                    "timestamp": chrono::Utc::now().to_rfc3339(),
-                })
+        },
+    );
+
+    tokio_stream::iter(replay)
+        .chain(live)
+        .filter(move |event| {
+            // Live events already sent during replay are dropped.
+            if event.id <= cursor {
+                return false;
             }
-            crate::observability::ObserverEvent::AgentEnd {
-                provider,
-                model,
-                duration,
-                tokens_used,
-                cost_usd,
-            } => serde_json::json!({
-                "type": "agent_end",
-                "provider": provider,
-                "model": model,
-                "duration_ms": duration.as_millis(),
-                "tokens_used": tokens_used,
-                "cost_usd": cost_usd,
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-            }),
-            _ => return, // Skip events we don't broadcast
-        };
-
-        let _ = self.tx.send(json);
-    }
+            cursor = event.id;
+            true
+        })
+        .filter(move |event| filter.matches(event))
+}
 
-    fn record_metric(&self, metric: &crate::observability::traits::ObserverMetric) {
-        self.inner.record_metric(metric);
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
 
-    fn flush(&self) {
-        self.inner.flush();
-    }
+    #[tokio::test]
+    async fn replays_after_last_event_id_then_streams_live() {
+        let hub = Arc::new(EventHub::new(10, None));
+        hub.publish("tool_call", Some("telegram".into()), json!({ "n": 1 }));
+        hub.publish("cron_run", None, json!({ "n": 2 }));
+        hub.publish("tool_call", Some("discord".into()), json!({ "n": 3 }));
+
+        let filter = EventFilter::parse(Some("tool_call"), None);
+        let mut stream = Box::pin(event_stream(&hub, filter, Some(1)));
+        hub.publish("tool_call", None, json!({ "n": 4 }));
+        hub.publish("llm_request", None, json!({ "n": 5 }));
+        hub.publish("tool_call", Some("telegram".into()), json!({ "n": 6 }));
 
-    fn name(&self) -> &str {
-        "broadcast"
+        let mut ids = Vec::new();
+        while ids.len() < 3 {
+            ids.push(stream.next().await.unwrap().id);
+        }
+        assert_eq!(ids, vec![3, 4, 6]);
     }
 
-    fn as_any(&self) -> &dyn std::any::Any {
-        self
+    #[tokio::test]
+    async fn without_last_event_id_only_live_events_matching_channel_are_sent() {
+        let hub = Arc::new(EventHub::new(10, None));
+        hub.publish("tool_call", Some("telegram".into()), json!({}));
+
+        let filter = EventFilter::parse(None, Some("telegram"));
+        let mut stream = Box::pin(event_stream(&hub, filter, None));
+        hub.publish("tool_call", Some("slack".into()), json!({}));
+        hub.publish("channel_message", Some("telegram".into()), json!({}));
+
+        let event = stream.next().await.unwrap();
+        assert_eq!(
+            (event.id, event.event_type.as_str()),
+            (3, "channel_message")
+        );
     }
 }
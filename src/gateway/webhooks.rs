@@ -0,0 +1,725 @@
+//! Outbound webhook subscriptions.
+//!
+//! Third-party systems register a URL and an event filter through
+//! `/api/webhooks`. The dispatcher follows the agent event hub
+//! ([`crate::observability::events`]) and POSTs matching events as JSON:
+//!
+//! ```json
+//! {"event": "tool_failure", "subscription_id": "…", "data": {"id": 42, "type": "tool_call", …}}
+//! ```
+//!
+//! Deliveries carry `X-ZeroClaw-Event`, `X-ZeroClaw-Delivery` and, when the
+//! subscription has a secret, `X-ZeroClaw-Signature: sha256=<hex>` — an
+//! HMAC-SHA256 of the raw body. Failed deliveries are retried with exponential
+//! backoff; once retries run out they are appended to a dead-letter log,
+//! which is rotated once it reaches [`MAX_DEAD_LETTER_BYTES`].
+//!
+//! Subscription URLs on loopback, link-local and private hosts are refused
+//! unless `gateway.outbound_webhook_allow_private_hosts` is set. Each delivery
+//! resolves the host again and connects only to the checked public addresses;
+//! redirects are never followed.
+
+use crate::config::Config;
+use crate::observability::events::{AgentEvent, EventHub};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Subscription store, next to `config.toml`.
+pub const WEBHOOK_STORE_FILE: &str = "webhooks.json";
+const DEAD_LETTER_REL_PATH: &str = "state/webhook-dead-letters.jsonl";
+/// Size at which the dead-letter log is rotated; one previous file is kept.
+pub const MAX_DEAD_LETTER_BYTES: u64 = 1024 * 1024;
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+const MAX_BACKOFF_MS: u64 = 60_000;
+
+/// Events a subscription can ask for.
+pub const WEBHOOK_EVENTS: &[&str] = &[
+    "turn_complete",
+    "tool_failure",
+    "cron_run",
+    "budget_warning",
+    "estop_change",
+];
+
+/// Webhook event name for an agent event, if subscribers can receive it.
+pub fn webhook_event(event: &AgentEvent) -> Option<&'static str> {
+    match event.event_type.as_str() {
+        "turn_complete" => Some("turn_complete"),
+        "tool_call" if event.data["success"] == Value::Bool(false) => Some("tool_failure"),
+        "cron_run" => Some("cron_run"),
+        "budget_warning" => Some("budget_warning"),
+        "estop_change" => Some("estop_change"),
+        _ => None,
+    }
+}
+
+/// `sha256=<hex>` HMAC-SHA256 signature of `body`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub url: String,
+    /// Events to deliver; empty means every event in [`WEBHOOK_EVENTS`].
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_at: String,
+}
+
+impl WebhookSubscription {
+    pub fn wants(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == event)
+    }
+
+    /// API view: the secret is never echoed back after creation.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "url": self.url,
+            "events": self.events,
+            "signed": self.secret.is_some(),
+            "created_at": self.created_at,
+        })
+    }
+}
+
+/// A delivery that failed after every retry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub failed_at: String,
+    pub subscription_id: String,
+    pub url: String,
+    pub event: String,
+    pub attempts: u32,
+    pub error: String,
+    pub payload: Value,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WebhookStoreFile {
+    #[serde(default)]
+    webhooks: Vec<WebhookSubscription>,
+}
+
+/// Subscriptions plus the delivery machinery shared by the API and dispatcher.
+pub struct OutboundWebhooks {
+    store_path: Option<PathBuf>,
+    dead_letter_path: Option<PathBuf>,
+    subscriptions: Mutex<Vec<WebhookSubscription>>,
+    client: reqwest::Client,
+    max_retries: u32,
+    backoff_ms: u64,
+    allow_private_hosts: bool,
+}
+
+impl Default for OutboundWebhooks {
+    /// In-memory subscriptions with no dead-letter file.
+    fn default() -> Self {
+        let defaults = crate::config::GatewayConfig::default();
+        Self {
+            store_path: None,
+            dead_letter_path: None,
+            subscriptions: Mutex::new(Vec::new()),
+            client: delivery_client(),
+            max_retries: defaults.outbound_webhook_max_retries,
+            backoff_ms: defaults.outbound_webhook_backoff_ms,
+            allow_private_hosts: defaults.outbound_webhook_allow_private_hosts,
+        }
+    }
+}
+
+/// Deliveries never follow redirects: a public endpoint could otherwise
+/// bounce them to a private address.
+fn delivery_builder() -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+        .connect_timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+        .redirect(reqwest::redirect::Policy::none());
+    crate::config::apply_runtime_proxy_to_builder(builder, "gateway.webhooks")
+}
+
+fn delivery_client() -> reqwest::Client {
+    delivery_builder().build().unwrap_or_else(|error| {
+        tracing::warn!("Failed to build webhook delivery client: {error}");
+        reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+impl OutboundWebhooks {
+    /// Load subscriptions stored next to `config.toml`.
+    pub fn load(config: &Config) -> Result<Self> {
+        let config_dir = config
+            .config_path
+            .parent()
+            .context("Config path has no parent directory")?;
+        let store_path = config_dir.join(WEBHOOK_STORE_FILE);
+        let subscriptions = if store_path.exists() {
+            let raw = fs::read_to_string(&store_path).with_context(|| {
+                format!("Failed to read webhook store {}", store_path.display())
+            })?;
+            serde_json::from_str::<WebhookStoreFile>(&raw)
+                .with_context(|| format!("Failed to parse webhook store {}", store_path.display()))?
+                .webhooks
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            store_path: Some(store_path),
+            dead_letter_path: Some(config.workspace_dir.join(DEAD_LETTER_REL_PATH)),
+            subscriptions: Mutex::new(subscriptions),
+            client: delivery_client(),
+            max_retries: config.gateway.outbound_webhook_max_retries,
+            backoff_ms: config.gateway.outbound_webhook_backoff_ms,
+            allow_private_hosts: config.gateway.outbound_webhook_allow_private_hosts,
+        })
+    }
+
+    pub fn list(&self) -> Vec<WebhookSubscription> {
+        self.subscriptions.lock().clone()
+    }
+
+    /// Register a subscription. Without a `secret` one is generated; the
+    /// returned record is the only place it is shown.
+    pub fn add(
+        &self,
+        url: &str,
+        events: Vec<String>,
+        secret: Option<String>,
+    ) -> Result<WebhookSubscription> {
+        let url = url.trim();
+        self.check_url(url)?;
+
+        let mut events: Vec<String> = events
+            .iter()
+            .map(|e| e.trim().to_ascii_lowercase())
+            .filter(|e| !e.is_empty())
+            .collect();
+        if events.iter().any(|e| e == "*") {
+            events.clear();
+        }
+        if let Some(unknown) = events
+            .iter()
+            .find(|e| !WEBHOOK_EVENTS.contains(&e.as_str()))
+        {
+            bail!(
+                "Unknown webhook event '{unknown}' — expected one of: {}",
+                WEBHOOK_EVENTS.join(", ")
+            );
+        }
+        events.sort();
+        events.dedup();
+
+        let secret = match secret.map(|s| s.trim().to_string()) {
+            Some(s) if !s.is_empty() => s,
+            _ => format!(
+                "{}{}",
+                uuid::Uuid::new_v4().simple(),
+                uuid::Uuid::new_v4().simple()
+            ),
+        };
+
+        let subscription = WebhookSubscription {
+            id: uuid::Uuid::new_v4().simple().to_string()[..12].to_string(),
+            url: url.to_string(),
+            events,
+            secret: Some(secret),
+            created_at: Utc::now().to_rfc3339(),
+        };
+
+        let mut subscriptions = self.subscriptions.lock();
+        subscriptions.push(subscription.clone());
+        self.persist(&subscriptions)?;
+        Ok(subscription)
+    }
+
+    /// Reject non-HTTP URLs and, unless allowed, local and private hosts.
+    fn check_url(&self, url: &str) -> Result<()> {
+        let parsed = reqwest::Url::parse(url).context("Invalid webhook URL")?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            bail!("Webhook URL must use http or https");
+        }
+        let host = parsed.host_str().context("Webhook URL has no host")?;
+        if !self.allow_private_hosts && crate::tools::http_request::is_private_or_local_host(host) {
+            bail!("Webhook URL points to a local or private host: {host}");
+        }
+        Ok(())
+    }
+
+    /// Remove a subscription; `Ok(false)` when no such id exists.
+    pub fn remove(&self, id: &str) -> Result<bool> {
+        let mut subscriptions = self.subscriptions.lock();
+        let before = subscriptions.len();
+        subscriptions.retain(|s| s.id != id);
+        if subscriptions.len() == before {
+            return Ok(false);
+        }
+        self.persist(&subscriptions)?;
+        Ok(true)
+    }
+
+    fn persist(&self, subscriptions: &[WebhookSubscription]) -> Result<()> {
+        let Some(path) = &self.store_path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let body = serde_json::to_string_pretty(&WebhookStoreFile {
+            webhooks: subscriptions.to_vec(),
+        })?;
+        let temp_path = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+        fs::write(&temp_path, body)
+            .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&temp_path, fs::Permissions::from_mode(0o600));
+        }
+
+        fs::rename(&temp_path, path)
+            .with_context(|| format!("Failed to replace webhook store {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Most recent dead letters, newest first. Reads the current log and, if
+    /// that is not enough, the rotated one; both are size-capped.
+    pub fn dead_letters(&self, limit: usize) -> Vec<DeadLetter> {
+        let Some(path) = self.dead_letter_path.as_deref() else {
+            return Vec::new();
+        };
+        let mut letters = Vec::new();
+        for file in [path.to_path_buf(), rotated_path(path)] {
+            if letters.len() >= limit {
+                break;
+            }
+            let Ok(raw) = fs::read_to_string(&file) else {
+                continue;
+            };
+            letters.extend(
+                raw.lines()
+                    .rev()
+                    .filter_map(|line| serde_json::from_str(line.trim()).ok())
+                    .take(limit - letters.len()),
+            );
+        }
+        letters
+    }
+
+    /// Deliver hub events to subscribers until the hub goes away.
+    pub async fn run(self: Arc<Self>, hub: Arc<EventHub>) {
+        let mut events = hub.subscribe();
+        loop {
+            match events.recv().await {
+                Ok(event) => self.dispatch(&event),
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!("Outbound webhooks dropped {missed} events");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    /// Start a delivery to every subscription that wants `event`.
+    pub fn dispatch(self: &Arc<Self>, event: &AgentEvent) {
+        let Some(name) = webhook_event(event) else {
+            return;
+        };
+        for subscription in self.list().into_iter().filter(|s| s.wants(name)) {
+            let this = Arc::clone(self);
+            let event = event.clone();
+            tokio::spawn(async move {
+                this.deliver(&subscription, name, &event).await;
+            });
+        }
+    }
+
+    /// POST one event, retrying with backoff. Returns whether it was delivered.
+    pub async fn deliver(
+        &self,
+        subscription: &WebhookSubscription,
+        name: &str,
+        event: &AgentEvent,
+    ) -> bool {
+        let payload = json!({
+            "event": name,
+            "subscription_id": subscription.id,
+            "data": event,
+        });
+        let body = payload.to_string();
+        let attempts = self.max_retries.saturating_add(1);
+        let mut backoff_ms = self.backoff_ms.max(1);
+        let mut last_error = String::new();
+
+        for attempt in 1..=attempts {
+            match self.post(subscription, name, event.id, &body).await {
+                Ok(()) => return true,
+                Err(e) => last_error = format!("{e:#}"),
+            }
+            if attempt < attempts {
+                tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
+                backoff_ms = backoff_ms.saturating_mul(2).min(MAX_BACKOFF_MS);
+            }
+        }
+
+        tracing::warn!(
+            subscription = %subscription.id,
+            event = name,
+            "Outbound webhook failed after {attempts} attempts: {last_error}"
+        );
+        let letter = DeadLetter {
+            failed_at: Utc::now().to_rfc3339(),
+            subscription_id: subscription.id.clone(),
+            url: subscription.url.clone(),
+            event: name.to_string(),
+            attempts,
+            error: last_error,
+            payload,
+        };
+        if let Err(e) = self.append_dead_letter(&letter) {
+            tracing::warn!("Failed to write webhook dead letter: {e}");
+        }
+        false
+    }
+
+    async fn post(
+        &self,
+        subscription: &WebhookSubscription,
+        name: &str,
+        event_id: u64,
+        body: &str,
+    ) -> Result<()> {
+        self.check_url(&subscription.url)?;
+        // Resolve on every delivery: DNS may point at a private address by now.
+        let client = if self.allow_private_hosts {
+            self.client.clone()
+        } else {
+            let url = reqwest::Url::parse(&subscription.url)?;
+            crate::tools::http_request::public_client(&url, delivery_builder()).await?
+        };
+        let mut request = client
+            .post(&subscription.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-ZeroClaw-Event", name)
+            .header(
+                "X-ZeroClaw-Delivery",
+                format!("{}-{event_id}", subscription.id),
+            );
+        if let Some(secret) = &subscription.secret {
+            request = request.header("X-ZeroClaw-Signature", sign(secret, body.as_bytes()));
+        }
+
+        let response = request
+            .body(body.to_string())
+            .send()
+            .await
+            .context("Webhook request failed")?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("Webhook endpoint returned {status}");
+        }
+        Ok(())
+    }
+
+    fn append_dead_letter(&self, letter: &DeadLetter) -> Result<()> {
+        let Some(path) = self.dead_letter_path.as_deref() else {
+            return Ok(());
+        };
+        if fs::metadata(path).is_ok_and(|meta| meta.len() >= MAX_DEAD_LETTER_BYTES) {
+            fs::rename(path, rotated_path(path))
+                .with_context(|| format!("Failed to rotate {}", path.display()))?;
+        }
+        append_line(path, &serde_json::to_string(letter)?)
+    }
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+fn append_line(path: &Path, line: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    writeln!(file, "{line}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn event(event_type: &str, data: Value) -> AgentEvent {
+        AgentEvent {
+            id: 7,
+            timestamp: Utc::now().to_rfc3339(),
+            event_type: event_type.into(),
+            channel: None,
+            data,
+        }
+    }
+
+    fn test_config(tmp: &tempfile::TempDir) -> Config {
+        let mut config = Config {
+            config_path: tmp.path().join("config.toml"),
+            workspace_dir: tmp.path().join("workspace"),
+            ..Config::default()
+        };
+        config.gateway.outbound_webhook_max_retries = 2;
+        config.gateway.outbound_webhook_backoff_ms = 1;
+        // Mock servers listen on loopback.
+        config.gateway.outbound_webhook_allow_private_hosts = true;
+        config
+    }
+
+    fn dead_letter(n: usize, payload: Value) -> DeadLetter {
+        DeadLetter {
+            failed_at: Utc::now().to_rfc3339(),
+            subscription_id: format!("sub-{n}"),
+            url: "https://example.com/hook".into(),
+            event: "cron_run".into(),
+            attempts: 1,
+            error: "boom".into(),
+            payload,
+        }
+    }
+
+    #[test]
+    fn maps_agent_events_to_webhook_events() {
+        assert_eq!(
+            webhook_event(&event("tool_call", json!({ "success": false }))),
+            Some("tool_failure")
+        );
+        assert_eq!(
+            webhook_event(&event("tool_call", json!({ "success": true }))),
+            None
+        );
+        assert_eq!(
+            webhook_event(&event("cron_run", json!({}))),
+            Some("cron_run")
+        );
+        assert_eq!(webhook_event(&event("llm_request", json!({}))), None);
+    }
+
+    #[test]
+    fn subscriptions_are_validated_and_persisted() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let hooks = OutboundWebhooks::load(&config).unwrap();
+
+        assert!(hooks.add("ftp://example.com", Vec::new(), None).is_err());
+        let err = hooks
+            .add("https://example.com/hook", vec!["tool_call".into()], None)
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown webhook event"), "{err}");
+
+        let created = hooks
+            .add(
+                "https://example.com/hook",
+                vec!["cron_run".into(), "estop_change".into()],
+                None,
+            )
+            .unwrap();
+        assert_eq!(created.secret.as_deref().map(str::len), Some(64));
+        assert!(created.wants("cron_run") && !created.wants("turn_complete"));
+        assert_eq!(created.to_json()["signed"], true);
+        assert!(created.to_json().get("secret").is_none());
+
+        let reloaded = OutboundWebhooks::load(&config).unwrap();
+        assert_eq!(reloaded.list(), vec![created.clone()]);
+        assert!(reloaded.remove(&created.id).unwrap());
+        assert!(!reloaded.remove(&created.id).unwrap());
+        assert!(OutboundWebhooks::load(&config).unwrap().list().is_empty());
+    }
+
+    #[test]
+    fn private_hosts_are_rejected_unless_allowed() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut config = test_config(&tmp);
+        config.gateway.outbound_webhook_allow_private_hosts = false;
+        let hooks = OutboundWebhooks::load(&config).unwrap();
+
+        for url in [
+            "http://169.254.169.254/latest/meta-data",
+            "http://127.0.0.1:8080/hook",
+            "http://localhost/hook",
+            "http://10.0.0.5/hook",
+            "http://[::1]/hook",
+        ] {
+            let err = hooks.add(url, Vec::new(), None).unwrap_err();
+            assert!(err.to_string().contains("private host"), "{url}: {err}");
+        }
+        assert!(hooks.list().is_empty());
+
+        config.gateway.outbound_webhook_allow_private_hosts = true;
+        let hooks = OutboundWebhooks::load(&config).unwrap();
+        assert!(hooks
+            .add("http://127.0.0.1:8080/hook", Vec::new(), None)
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn deliveries_recheck_hosts_and_ignore_redirects() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(
+                ResponseTemplate::new(307)
+                    .insert_header("location", format!("{}/internal", server.uri())),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/internal"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut config = test_config(&tmp);
+        config.gateway.outbound_webhook_max_retries = 0;
+        let hooks = OutboundWebhooks::load(&config).unwrap();
+        let subscription = hooks
+            .add(&format!("{}/hook", server.uri()), Vec::new(), None)
+            .unwrap();
+        let cron = event("cron_run", json!({}));
+        assert!(!hooks.deliver(&subscription, "cron_run", &cron).await);
+        assert!(hooks.dead_letters(1)[0].error.contains("307"));
+
+        // A subscription stored before private hosts were disallowed is not
+        // delivered either.
+        config.gateway.outbound_webhook_allow_private_hosts = false;
+        let hooks = OutboundWebhooks::load(&config).unwrap();
+        assert!(!hooks.deliver(&subscription, "cron_run", &cron).await);
+        assert!(hooks.dead_letters(1)[0].error.contains("private host"));
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn dead_letter_log_is_rotated() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let hooks = OutboundWebhooks::load(&test_config(&tmp)).unwrap();
+        let filler = json!("x".repeat(usize::try_from(MAX_DEAD_LETTER_BYTES / 2).unwrap()));
+
+        for n in 0..5 {
+            hooks
+                .append_dead_letter(&dead_letter(n, filler.clone()))
+                .unwrap();
+        }
+
+        let path = hooks.dead_letter_path.clone().unwrap();
+        assert!(fs::metadata(&path).unwrap().len() < MAX_DEAD_LETTER_BYTES);
+        assert!(fs::metadata(rotated_path(&path)).unwrap().len() <= MAX_DEAD_LETTER_BYTES * 2);
+
+        // Newest first, across the current and rotated logs; older ones are gone.
+        let ids: Vec<String> = hooks
+            .dead_letters(10)
+            .into_iter()
+            .map(|letter| letter.subscription_id)
+            .collect();
+        assert_eq!(ids, vec!["sub-4", "sub-3", "sub-2"]);
+        assert_eq!(hooks.dead_letters(1).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn delivery_is_signed_and_retried() {
+        let server = MockServer::start().await;
+        let tmp = tempfile::TempDir::new().unwrap();
+        let hooks = OutboundWebhooks::load(&test_config(&tmp)).unwrap();
+        let subscription = hooks
+            .add(
+                &format!("{}/hook", server.uri()),
+                Vec::new(),
+                Some("s3cret".into()),
+            )
+            .unwrap();
+        let failure = event("tool_call", json!({ "tool": "shell", "success": false }));
+        let body = json!({
+            "event": "tool_failure",
+            "subscription_id": subscription.id,
+            "data": failure,
+        })
+        .to_string();
+
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .and(header("x-zeroclaw-event", "tool_failure"))
+            .and(header(
+                "x-zeroclaw-signature",
+                sign("s3cret", body.as_bytes()).as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert!(hooks.deliver(&subscription, "tool_failure", &failure).await);
+        assert!(hooks.dead_letters(10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn exhausted_retries_are_dead_lettered() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let hooks = OutboundWebhooks::load(&test_config(&tmp)).unwrap();
+        let subscription = hooks.add(&server.uri(), Vec::new(), None).unwrap();
+        let cron = event("cron_run", json!({ "job_id": "nightly", "success": false }));
+
+        assert!(!hooks.deliver(&subscription, "cron_run", &cron).await);
+        let letters = hooks.dead_letters(10);
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].attempts, 3);
+        assert_eq!(letters[0].event, "cron_run");
+        assert!(letters[0].error.contains("500"), "{}", letters[0].error);
+        assert_eq!(letters[0].payload["data"]["data"]["job_id"], "nightly");
+    }
+}
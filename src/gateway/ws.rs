@@ -1,15 +1,41 @@
 //! WebSocket agent chat handler.
 //!
+//! One socket can carry several chat sessions. Sessions belong to the gateway
+//! (`AppState::ws_sessions`), not to the socket: a turn keeps running when the
+//! connection drops, and the client can reconnect and resume from the last
+//! frame it saw.
+//!
 //! Protocol:
 //! ```text
-//! Client -> Server: {"type":"message","content":"Hello"}
-//! Server -> Client: {"type":"chunk","content":"Hi! "}
-//! Server -> Client: {"type":"tool_call","name":"shell","args":{...}}
-//! Server -> Client: {"type":"tool_result","name":"shell","output":"..."}
-//! Server -> Client: {"type":"done","full_response":"..."}
+//! Client -> Server: {"type":"message","content":"Hello"}              (connection's default session)
+//! Server -> Client: {"type":"session","session_id":"s1"}
+//! Client -> Server: {"type":"open","request_id":"r1"}                  (additional session)
+//! Server -> Client: {"type":"session","session_id":"s2","request_id":"r1"}
+//! Client -> Server: {"type":"message","session_id":"s2","content":"Hello"}
+//! Server -> Client: {"type":"tool_call","session_id":"s2","seq":1,"name":"shell"}
+//! Server -> Client: {"type":"tool_result","session_id":"s2","seq":2,"name":"shell","success":true,"duration_ms":12}
+//! Server -> Client: {"type":"chunk","session_id":"s2","seq":3,"content":"Hi! "}
+//! Server -> Client: {"type":"done","session_id":"s2","seq":4,"full_response":"Hi! ..."}
+//! Client -> Server: {"type":"cancel","session_id":"s2"}
+//! Server -> Client: {"type":"cancelled","session_id":"s2","seq":5}
+//! Client -> Server: {"type":"resume","session_id":"s2","last_seq":2}
+//! Server -> Client: frames 3.. replayed, then
+//!                   {"type":"resumed","session_id":"s2","last_seq":5,"complete":true,"running":false}
+//! Client -> Server: {"type":"close","session_id":"s2"}
 //! ```
+//!
+//! Frames carrying a `seq` are numbered per session and kept in a bounded
+//! replay buffer. `complete: false` in `resumed` means some missed frames had
+//! already been evicted.
 
 use super::AppState;
+use crate::agent::loop_::{
+    build_tool_instructions, is_tool_loop_cancelled, run_tool_call_loop, trim_history,
+    DRAFT_CLEAR_SENTINEL,
+};
+use crate::observability::{Observer, ObserverEvent};
+use crate::providers::ChatMessage;
+use crate::security::pairing::TokenScope;
 use axum::{
     extract::{
         ws::{Message, WebSocket},
@@ -18,13 +44,259 @@ use axum::{
     response::IntoResponse,
 };
 use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
 use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Sequenced frames kept per session for resume.
+const SESSION_REPLAY_FRAMES: usize = 512;
+/// Detached, idle sessions are dropped after this long.
+const SESSION_IDLE_TTL: Duration = Duration::from_secs(30 * 60);
+/// Upper bound on live sessions across all sockets.
+const MAX_SESSIONS: usize = 256;
+/// Upper bound on live sessions per bearer token (per socket without pairing).
+const MAX_SESSIONS_PER_CLIENT: usize = 16;
+
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
 
 #[derive(Deserialize)]
 pub struct WsQuery {
     pub token: Option<String>,
 }
 
+/// Chat sessions that outlive individual sockets.
+#[derive(Default)]
+pub struct WsSessions {
+    sessions: Mutex<HashMap<String, Arc<WsSession>>>,
+}
+
+impl WsSessions {
+    /// Create a session for `client`, dropping expired ones first. `None` when
+    /// the gateway is at `MAX_SESSIONS` or the client at
+    /// `MAX_SESSIONS_PER_CLIENT`.
+    fn create(&self, client: &str, system_prompt: String) -> Option<Arc<WsSession>> {
+        let mut sessions = self.sessions.lock();
+        sessions.retain(|_, session| !session.is_expired());
+        let owned = sessions.values().filter(|s| s.client == client).count();
+        if sessions.len() >= MAX_SESSIONS || owned >= MAX_SESSIONS_PER_CLIENT {
+            return None;
+        }
+        let session = Arc::new(WsSession::new(client.to_string(), system_prompt));
+        sessions.insert(session.id.clone(), Arc::clone(&session));
+        Some(session)
+    }
+
+    fn get(&self, id: &str) -> Option<Arc<WsSession>> {
+        self.sessions.lock().get(id).cloned()
+    }
+
+    fn remove(&self, id: &str) -> Option<Arc<WsSession>> {
+        self.sessions.lock().remove(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// One conversation: its history, the in-flight turn and the replay buffer.
+struct WsSession {
+    id: String,
+    /// Token (or socket) that opened the session, for the per-client cap.
+    client: String,
+    inner: Mutex<SessionInner>,
+}
+
+struct SessionInner {
+    history: Vec<ChatMessage>,
+    next_seq: u64,
+    frames: VecDeque<(u64, String)>,
+    /// Socket currently receiving this session's frames.
+    outbound: Option<(u64, mpsc::UnboundedSender<String>)>,
+    turn: Option<CancellationToken>,
+    last_active: Instant,
+}
+
+impl WsSession {
+    fn new(client: String, system_prompt: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            client,
+            inner: Mutex::new(SessionInner {
+                history: vec![ChatMessage::system(system_prompt)],
+                next_seq: 1,
+                frames: VecDeque::new(),
+                outbound: None,
+                turn: None,
+                last_active: Instant::now(),
+            }),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        let inner = self.inner.lock();
+        inner.turn.is_none()
+            && inner.outbound.as_ref().is_none_or(|(_, tx)| tx.is_closed())
+            && inner.last_active.elapsed() > SESSION_IDLE_TTL
+    }
+
+    /// Number, buffer and deliver a frame.
+    fn emit(&self, mut frame: serde_json::Value) {
+        let mut inner = self.inner.lock();
+        let seq = inner.next_seq;
+        inner.next_seq += 1;
+        frame["session_id"] = serde_json::json!(self.id);
+        frame["seq"] = serde_json::json!(seq);
+        let text = frame.to_string();
+
+        if inner.frames.len() >= SESSION_REPLAY_FRAMES {
+            inner.frames.pop_front();
+        }
+        inner.frames.push_back((seq, text.clone()));
+        inner.last_active = Instant::now();
+        if let Some((_, tx)) = &inner.outbound {
+            let _ = tx.send(text);
+        }
+    }
+
+    /// Route frames to `tx`, first replaying everything after `last_seq`.
+    ///
+    /// Replay and attach happen under one lock so no frame is lost or
+    /// duplicated in between. Returns the `resumed` frame.
+    fn attach(
+        &self,
+        conn_id: u64,
+        tx: &mpsc::UnboundedSender<String>,
+        last_seq: Option<u64>,
+    ) -> serde_json::Value {
+        let mut inner = self.inner.lock();
+        let latest = inner.next_seq - 1;
+        let mut complete = true;
+        if let Some(last_seq) = last_seq {
+            let first_missed = last_seq + 1;
+            complete = first_missed > latest
+                || inner
+                    .frames
+                    .front()
+                    .is_some_and(|(seq, _)| *seq <= first_missed);
+            for (_, text) in inner.frames.iter().filter(|(seq, _)| *seq > last_seq) {
+                let _ = tx.send(text.clone());
+            }
+        }
+        inner.outbound = Some((conn_id, tx.clone()));
+        inner.last_active = Instant::now();
+        serde_json::json!({
+            "type": "resumed",
+            "session_id": self.id,
+            "last_seq": latest,
+            "complete": complete,
+            "running": inner.turn.is_some(),
+        })
+    }
+
+    fn detach(&self, conn_id: u64) {
+        let mut inner = self.inner.lock();
+        if inner
+            .outbound
+            .as_ref()
+            .is_some_and(|(id, _)| *id == conn_id)
+        {
+            inner.outbound = None;
+            inner.last_active = Instant::now();
+        }
+    }
+
+    /// Start a turn: append the user message and hand back the history to run.
+    /// `None` while another turn is in flight.
+    fn begin_turn(&self, content: &str) -> Option<(CancellationToken, Vec<ChatMessage>)> {
+        let mut inner = self.inner.lock();
+        if inner.turn.is_some() {
+            return None;
+        }
+        let token = CancellationToken::new();
+        inner.turn = Some(token.clone());
+        inner.last_active = Instant::now();
+        let mut history = inner.history.clone();
+        history.push(ChatMessage::user(content));
+        Some((token, history))
+    }
+
+    /// Finish a turn, committing `history` when it completed.
+    fn end_turn(&self, history: Option<Vec<ChatMessage>>) {
+        let mut inner = self.inner.lock();
+        inner.turn = None;
+        inner.last_active = Instant::now();
+        if let Some(history) = history {
+            inner.history = history;
+        }
+    }
+
+    fn cancel(&self) -> bool {
+        let inner = self.inner.lock();
+        match &inner.turn {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Forwards tool activity of a turn to its session as frames.
+struct SessionObserver {
+    inner: Arc<dyn Observer>,
+    session: Arc<WsSession>,
+}
+
+impl Observer for SessionObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        self.inner.record_event(event);
+        match event {
+            ObserverEvent::ToolCallStart { tool } => self.session.emit(serde_json::json!({
+                "type": "tool_call",
+                "name": tool,
+            })),
+            ObserverEvent::ToolCall {
+                tool,
+                duration,
+                success,
+            } => self.session.emit(serde_json::json!({
+                "type": "tool_result",
+                "name": tool,
+                "success": success,
+                "duration_ms": u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+            })),
+            _ => {}
+        }
+    }
+
+    fn record_metric(&self, metric: &crate::observability::traits::ObserverMetric) {
+        self.inner.record_metric(metric);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+
+    fn name(&self) -> &str {
+        "ws_session"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
 /// GET /ws/chat — WebSocket upgrade for agent chat
 pub async fn handle_ws_chat(
     State(state): State<AppState>,
@@ -32,136 +304,741 @@ pub async fn handle_ws_chat(
     ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
     // Auth via query param (browser WebSocket limitation)
+    let mut client = None;
     if state.pairing.require_pairing() {
         let token = params.token.as_deref().unwrap_or("");
-        if !state.pairing.is_authenticated(token) {
+        if !state.pairing.is_authorized(token, TokenScope::Webhook) {
             return (
                 axum::http::StatusCode::UNAUTHORIZED,
-                "Unauthorized — provide ?token=<bearer_token>",
+                "Unauthorized — provide ?token=<bearer_token> with the 'webhook' scope",
             )
                 .into_response();
         }
+        client = Some(format!("token:{token}"));
     }
 
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+    ws.on_upgrade(move |socket| handle_socket(socket, state, client))
         .into_response()
 }
 
-async fn handle_socket(socket: WebSocket, state: AppState) {
+async fn handle_socket(socket: WebSocket, state: AppState, client: Option<String>) {
     let (mut sender, mut receiver) = socket.split();
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+
+    let writer = tokio::spawn(async move {
+        while let Some(text) = rx.recv().await {
+            if sender.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+        }
+    });
 
+    let mut conn = WsConnection::new(state, tx, client);
     while let Some(msg) = receiver.next().await {
-        let msg = match msg {
-            Ok(Message::Text(text)) => text,
-            Ok(Message::Close(_)) => break,
-            Err(_) => break,
-            _ => continue,
+        match msg {
+            Ok(Message::Text(text)) => conn.handle_frame(&text),
+            Ok(Message::Close(_)) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    // In-flight turns keep running; their frames wait in the replay buffer.
+    conn.detach_all();
+    writer.abort();
+}
+
+/// Client-side state of one socket: which sessions it is attached to.
+struct WsConnection {
+    id: u64,
+    /// Key for the per-client session cap
+    client: String,
+    /// Whether `client` is a bearer token. Authenticated sockets only see
+    /// sessions opened with the same token.
+    authenticated: bool,
+    state: AppState,
+    tx: mpsc::UnboundedSender<String>,
+    attached: HashMap<String, Arc<WsSession>>,
+    default_session: Option<String>,
+}
+
+impl WsConnection {
+    fn new(state: AppState, tx: mpsc::UnboundedSender<String>, client: Option<String>) -> Self {
+        let id = NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed);
+        Self {
+            id,
+            authenticated: client.is_some(),
+            client: client.unwrap_or_else(|| format!("socket:{id}")),
+            state,
+            tx,
+            attached: HashMap::new(),
+            default_session: None,
+        }
+    }
+
+    /// Send an unsequenced control frame.
+    fn send(&self, frame: serde_json::Value) {
+        let _ = self.tx.send(frame.to_string());
+    }
+
+    fn error(&self, session_id: Option<&str>, message: &str) {
+        let mut frame = serde_json::json!({"type": "error", "message": message});
+        if let Some(id) = session_id {
+            frame["session_id"] = serde_json::json!(id);
+        }
+        self.send(frame);
+    }
+
+    fn detach_all(&mut self) {
+        for session in self.attached.values() {
+            session.detach(self.id);
+        }
+        self.attached.clear();
+    }
+
+    fn handle_frame(&mut self, text: &str) {
+        let Ok(parsed) = serde_json::from_str::<serde_json::Value>(text) else {
+            self.error(None, "Invalid JSON");
+            return;
         };
+        let session_id = parsed["session_id"].as_str();
 
-        // Parse incoming message
-        let parsed: serde_json::Value = match serde_json::from_str(&msg) {
-            Ok(v) => v,
-            Err(_) => {
-                let err = serde_json::json!({"type": "error", "message": "Invalid JSON"});
-                let _ = sender.send(Message::Text(err.to_string().into())).await;
-                continue;
+        match parsed["type"].as_str().unwrap_or("") {
+            "open" => {
+                if let Some(session) = self.open_session() {
+                    let mut frame = serde_json::json!({
+                        "type": "session",
+                        "session_id": session.id,
+                    });
+                    if let Some(request_id) = parsed.get("request_id") {
+                        frame["request_id"] = request_id.clone();
+                    }
+                    self.send(frame);
+                }
+            }
+            "message" => {
+                let content = parsed["content"].as_str().unwrap_or("");
+                if content.is_empty() {
+                    return;
+                }
+                let session = match session_id {
+                    Some(id) => self.lookup(id),
+                    None => self.default_session(),
+                };
+                if let Some(session) = session {
+                    self.start_turn(&session, content);
+                }
+            }
+            "resume" => {
+                let Some(id) = session_id else {
+                    self.error(None, "resume requires session_id");
+                    return;
+                };
+                let Some(session) = self.owned_session(id) else {
+                    self.error(Some(id), "Unknown session");
+                    return;
+                };
+                let last_seq = parsed["last_seq"].as_u64().unwrap_or(0);
+                let resumed = session.attach(self.id, &self.tx, Some(last_seq));
+                self.attached.insert(session.id.clone(), session);
+                self.send(resumed);
+            }
+            "cancel" => {
+                let Some(session) = session_id
+                    .or(self.default_session.as_deref())
+                    .and_then(|id| self.owned_session(id))
+                else {
+                    self.error(session_id, "Unknown session");
+                    return;
+                };
+                if !session.cancel() {
+                    self.error(Some(&session.id), "No turn in progress");
+                }
             }
+            "close" => {
+                let Some(id) = session_id else {
+                    self.error(None, "close requires session_id");
+                    return;
+                };
+                self.attached.remove(id);
+                if self.default_session.as_deref() == Some(id) {
+                    self.default_session = None;
+                }
+                if self.owned_session(id).is_some() {
+                    if let Some(session) = self.state.ws_sessions.remove(id) {
+                        session.cancel();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn open_session(&mut self) -> Option<Arc<WsSession>> {
+        let Some(session) = self
+            .state
+            .ws_sessions
+            .create(&self.client, system_prompt(&self.state))
+        else {
+            self.error(None, "Too many sessions");
+            return None;
         };
+        session.attach(self.id, &self.tx, None);
+        self.attached
+            .insert(session.id.clone(), Arc::clone(&session));
+        Some(session)
+    }
 
-        let msg_type = parsed["type"].as_str().unwrap_or("");
-        if msg_type != "message" {
-            continue;
+    /// The session used by messages without `session_id`, created on first use.
+    fn default_session(&mut self) -> Option<Arc<WsSession>> {
+        if let Some(session) = self
+            .default_session
+            .as_deref()
+            .and_then(|id| self.state.ws_sessions.get(id))
+        {
+            return Some(session);
         }
+        let session = self.open_session()?;
+        self.default_session = Some(session.id.clone());
+        self.send(serde_json::json!({"type": "session", "session_id": session.id}));
+        Some(session)
+    }
+
+    /// A session this socket may use. Another token's sessions look unknown.
+    fn owned_session(&self, id: &str) -> Option<Arc<WsSession>> {
+        self.state
+            .ws_sessions
+            .get(id)
+            .filter(|session| !self.authenticated || session.client == self.client)
+    }
 
-        let content = parsed["content"].as_str().unwrap_or("").to_string();
-        if content.is_empty() {
-            continue;
+    /// A known session, attaching it to this socket if needed.
+    fn lookup(&mut self, id: &str) -> Option<Arc<WsSession>> {
+        let Some(session) = self.owned_session(id) else {
+            self.error(Some(id), "Unknown session");
+            return None;
+        };
+        if !self.attached.contains_key(id) {
+            session.attach(self.id, &self.tx, None);
+            self.attached.insert(id.to_string(), Arc::clone(&session));
         }
+        Some(session)
+    }
 
-        // Process message with the LLM provider
-        let provider_label = state
-            .config
-            .lock()
-            .default_provider
-            .clone()
-            .unwrap_or_else(|| "unknown".to_string());
-
-        // Broadcast agent_start event
-        let _ = state.event_tx.send(serde_json::json!({
-            "type": "agent_start",
-            "provider": provider_label,
-            "model": state.model,
-        }));
-
-        // Simple single-turn chat (no streaming for now — use provider.chat_with_system)
-        let system_prompt = {
-            let config_guard = state.config.lock();
-            crate::channels::build_system_prompt(
-                &config_guard.workspace_dir,
-                &state.model,
-                &[],
-                &[],
-                Some(&config_guard.identity),
-                None,
-            )
+    fn start_turn(&self, session: &Arc<WsSession>, content: &str) {
+        let Some((token, history)) = session.begin_turn(content) else {
+            self.error(Some(&session.id), "A turn is already in progress");
+            return;
         };
+        tokio::spawn(crate::observability::events::with_channel(
+            "ws".to_string(),
+            run_turn(self.state.clone(), Arc::clone(session), history, token),
+        ));
+    }
+}
 
-        let messages = vec![
-            crate::providers::ChatMessage::system(system_prompt),
-            crate::providers::ChatMessage::user(&content),
-        ];
+/// System prompt for a new session, listing the gateway's tools.
+fn system_prompt(state: &AppState) -> String {
+    let config = state.config.lock();
+    let excluded = &config.autonomy.non_cli_excluded_tools;
+    let tool_descs: Vec<(&str, &str)> = state
+        .tools_registry
+        .iter()
+        .filter(|tool| !excluded.iter().any(|ex| ex == tool.name()))
+        .map(|tool| (tool.name(), tool.description()))
+        .collect();
+    let mut prompt = crate::channels::build_system_prompt(
+        &config.workspace_dir,
+        &state.model,
+        &tool_descs,
+        &[],
+        Some(&config.identity),
+        None,
+    );
+    if !state.provider.supports_native_tools() {
+        prompt.push_str(&build_tool_instructions(state.tools_registry.as_ref()));
+    }
+    prompt
+}
 
-        let multimodal_config = state.config.lock().multimodal.clone();
-        let prepared =
-            match crate::multimodal::prepare_messages_for_provider(&messages, &multimodal_config)
-                .await
-            {
-                Ok(p) => p,
-                Err(e) => {
-                    let err = serde_json::json!({
-                        "type": "error",
-                        "message": format!("Multimodal prep failed: {e}")
-                    });
-                    let _ = sender.send(Message::Text(err.to_string().into())).await;
-                    continue;
-                }
-            };
+/// Run one agent turn for `session`, streaming its frames.
+async fn run_turn(
+    state: AppState,
+    session: Arc<WsSession>,
+    mut history: Vec<ChatMessage>,
+    token: CancellationToken,
+) {
+    let (provider_label, multimodal, max_iterations, max_history, excluded) = {
+        let config = state.config.lock();
+        (
+            config
+                .default_provider
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+            config.multimodal.clone(),
+            config.agent.max_tool_iterations,
+            config.agent.max_history_messages,
+            config.autonomy.non_cli_excluded_tools.clone(),
+        )
+    };
+
+    state.observer.record_event(&ObserverEvent::AgentStart {
+        provider: provider_label.clone(),
+        model: state.model.clone(),
+    });
+    let started = Instant::now();
+
+    // Deltas before the clear sentinel are progress lines; the final answer
+    // streams after it.
+    let (delta_tx, mut delta_rx) = mpsc::channel::<String>(64);
+    let chunk_session = Arc::clone(&session);
+    let forwarder = tokio::spawn(async move {
+        let mut answering = false;
+        while let Some(delta) = delta_rx.recv().await {
+            if delta == DRAFT_CLEAR_SENTINEL {
+                answering = true;
+            } else if answering {
+                chunk_session.emit(serde_json::json!({"type": "chunk", "content": delta}));
+            }
+        }
+    });
+
+    let observer = SessionObserver {
+        inner: Arc::clone(&state.observer),
+        session: Arc::clone(&session),
+    };
+    let result = run_tool_call_loop(
+        state.provider.as_ref(),
+        &mut history,
+        state.tools_registry.as_ref(),
+        &observer,
+        &provider_label,
+        &state.model,
+        state.temperature,
+        true,
+        None,
+        "ws",
+        None,
+        &multimodal,
+        max_iterations,
+        Some(token.clone()),
+        Some(delta_tx),
+        state.hooks.as_deref(),
+        &excluded,
+    )
+    .await;
+    let _ = forwarder.await;
+
+    match result {
+        Ok(response) => {
+            trim_history(&mut history, max_history);
+            session.end_turn(Some(history));
+            session.emit(serde_json::json!({
+                "type": "done",
+                "full_response": response,
+            }));
+            state.observer.record_event(&ObserverEvent::AgentEnd {
+                provider: provider_label,
+                model: state.model.clone(),
+                duration: started.elapsed(),
+                tokens_used: None,
+                cost_usd: None,
+            });
+            state.observer.record_event(&ObserverEvent::TurnComplete);
+        }
+        Err(e) if is_tool_loop_cancelled(&e) || token.is_cancelled() => {
+            session.end_turn(None);
+            session.emit(serde_json::json!({"type": "cancelled"}));
+        }
+        Err(e) => {
+            session.end_turn(None);
+            let sanitized = crate::providers::sanitize_api_error(&e.to_string());
+            session.emit(serde_json::json!({
+                "type": "error",
+                "message": sanitized,
+            }));
+            state.observer.record_event(&ObserverEvent::Error {
+                component: "ws_chat".to_string(),
+                message: sanitized,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::gateway::{GatewayRateLimiter, IdempotencyStore};
+    use crate::providers::Provider;
+    use crate::security::pairing::PairingGuard;
+    use async_trait::async_trait;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Replies "reply <n>"; hangs on messages containing "slow" and calls
+    /// `shell` for messages containing "use a tool".
+    #[derive(Default)]
+    struct ScriptedProvider {
+        last_history_len: AtomicUsize,
+        last_message: Mutex<String>,
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("unused".into())
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.last_history_len
+                .store(messages.len(), Ordering::SeqCst);
+            let last = messages
+                .last()
+                .map(|m| m.content.clone())
+                .unwrap_or_default();
+            *self.last_message.lock() = last.clone();
+            if last.contains("slow") {
+                std::future::pending::<()>().await;
+            }
+            if last.contains("use a tool") {
+                return Ok(
+                    r#"<tool_call>{"name":"shell","arguments":{"command":"ls"}}</tool_call>"#
+                        .into(),
+                );
+            }
+            Ok(format!("reply {}", messages.len()))
+        }
+    }
 
-        match state
-            .provider
-            .chat_with_history(&prepared.messages, &state.model, state.temperature)
+    fn test_state(provider: Arc<ScriptedProvider>) -> AppState {
+        let tmp = std::env::temp_dir();
+        let config = Config {
+            workspace_dir: tmp,
+            ..Config::default()
+        };
+        AppState {
+            config: Arc::new(Mutex::new(config)),
+            provider,
+            model: "test-model".into(),
+            temperature: 0.0,
+            mem: Arc::new(crate::memory::NoneMemory::new()),
+            auto_save: false,
+            webhook_secret_hash: None,
+            pairing: Arc::new(PairingGuard::new(false, &[])),
+            trust_forwarded_headers: false,
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300), 1000)),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            linq: None,
+            linq_signing_secret: None,
+            nextcloud_talk: None,
+            nextcloud_talk_webhook_secret: None,
+            observer: Arc::new(crate::observability::NoopObserver),
+            tools_registry: Arc::new(vec![]),
+            cost_tracker: None,
+            ws_sessions: Arc::new(WsSessions::default()),
+            hooks: None,
+            webhooks: Arc::new(crate::gateway::webhooks::OutboundWebhooks::default()),
+        }
+    }
+
+    async fn next_frame(rx: &mut mpsc::UnboundedReceiver<String>) -> serde_json::Value {
+        let text = tokio::time::timeout(Duration::from_secs(5), rx.recv())
             .await
-        {
-            Ok(response) => {
-                // Send the full response as a done message
-                let done = serde_json::json!({
-                    "type": "done",
-                    "full_response": response,
-                });
-                let _ = sender.send(Message::Text(done.to_string().into())).await;
-
-                // Broadcast agent_end event
-                let _ = state.event_tx.send(serde_json::json!({
-                    "type": "agent_end",
-                    "provider": provider_label,
-                    "model": state.model,
-                }));
+            .expect("timed out waiting for a frame")
+            .expect("socket closed");
+        serde_json::from_str(&text).unwrap()
+    }
+
+    /// Frames up to and including the next one of type `until`.
+    async fn frames_until(
+        rx: &mut mpsc::UnboundedReceiver<String>,
+        until: &str,
+    ) -> Vec<serde_json::Value> {
+        let mut frames = Vec::new();
+        loop {
+            let frame = next_frame(rx).await;
+            let done = frame["type"] == until;
+            frames.push(frame);
+            if done {
+                return frames;
             }
-            Err(e) => {
-                let sanitized = crate::providers::sanitize_api_error(&e.to_string());
-                let err = serde_json::json!({
-                    "type": "error",
-                    "message": sanitized,
-                });
-                let _ = sender.send(Message::Text(err.to_string().into())).await;
-
-                // Broadcast error event
-                let _ = state.event_tx.send(serde_json::json!({
-                    "type": "error",
-                    "component": "ws_chat",
-                    "message": sanitized,
-                }));
+        }
+    }
+
+    #[test]
+    fn resume_replays_frames_after_last_seq() {
+        let session = WsSession::new("socket:1".into(), "prompt".into());
+        for n in 0..3 {
+            session.emit(serde_json::json!({"type": "chunk", "content": n.to_string()}));
+        }
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let resumed = session.attach(1, &tx, Some(1));
+        let replayed: Vec<serde_json::Value> = std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|text| serde_json::from_str(&text).unwrap())
+            .collect();
+        assert_eq!(replayed.len(), 2);
+        assert_eq!(replayed[0]["seq"], 2);
+        assert_eq!(replayed[1]["content"], "2");
+        assert_eq!(resumed["last_seq"], 3);
+        assert_eq!(resumed["complete"], true);
+
+        // Live frames follow the replay on the attached socket.
+        session.emit(serde_json::json!({"type": "done"}));
+        assert!(rx.try_recv().unwrap().contains("\"seq\":4"));
+
+        for _ in 0..SESSION_REPLAY_FRAMES {
+            session.emit(serde_json::json!({"type": "chunk"}));
+        }
+        let (tx, _rx) = mpsc::unbounded_channel();
+        assert_eq!(session.attach(2, &tx, Some(0))["complete"], false);
+    }
+
+    #[tokio::test]
+    async fn turns_stream_frames_and_keep_history() {
+        let provider = Arc::new(ScriptedProvider::default());
+        let state = test_state(Arc::clone(&provider));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut conn = WsConnection::new(state.clone(), tx, None);
+
+        conn.handle_frame(r#"{"type":"message","content":"hello"}"#);
+        let session = next_frame(&mut rx).await;
+        assert_eq!(session["type"], "session");
+        let session_id = session["session_id"].as_str().unwrap().to_string();
+
+        let frames = frames_until(&mut rx, "done").await;
+        let text: String = frames
+            .iter()
+            .filter(|f| f["type"] == "chunk")
+            .filter_map(|f| f["content"].as_str())
+            .collect();
+        assert_eq!(text, "reply 2");
+        assert_eq!(frames.last().unwrap()["full_response"], "reply 2");
+        let seqs: Vec<u64> = frames.iter().filter_map(|f| f["seq"].as_u64()).collect();
+        assert!(seqs.windows(2).all(|w| w[1] == w[0] + 1), "{seqs:?}");
+        assert!(frames
+            .iter()
+            .all(|f| f["session_id"] == session_id.as_str()));
+
+        // Second turn in the same session sees the first exchange.
+        conn.handle_frame(r#"{"type":"message","content":"again"}"#);
+        frames_until(&mut rx, "done").await;
+        assert_eq!(provider.last_history_len.load(Ordering::SeqCst), 4);
+
+        // A second session on the same socket starts from scratch.
+        conn.handle_frame(r#"{"type":"open","request_id":"r1"}"#);
+        let opened = next_frame(&mut rx).await;
+        assert_eq!(opened["request_id"], "r1");
+        let other = opened["session_id"].as_str().unwrap();
+        assert_ne!(other, session_id);
+        conn.handle_frame(
+            &serde_json::json!({"type": "message", "session_id": other, "content": "hi"})
+                .to_string(),
+        );
+        let frames = frames_until(&mut rx, "done").await;
+        assert_eq!(frames.last().unwrap()["session_id"], other);
+        assert_eq!(provider.last_history_len.load(Ordering::SeqCst), 2);
+        assert_eq!(state.ws_sessions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn dropped_socket_can_cancel_and_resume() {
+        let state = test_state(Arc::new(ScriptedProvider::default()));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut conn = WsConnection::new(state.clone(), tx, None);
+
+        conn.handle_frame(r#"{"type":"message","content":"slow task"}"#);
+        let session_id = next_frame(&mut rx).await["session_id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        conn.handle_frame(r#"{"type":"message","content":"another"}"#);
+        let busy = next_frame(&mut rx).await;
+        assert_eq!(busy["type"], "error");
+        assert!(busy["message"].as_str().unwrap().contains("in progress"));
+
+        // Connection drops mid-turn; a new socket cancels and resumes.
+        conn.detach_all();
+        drop(conn);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut conn = WsConnection::new(state, tx, None);
+        conn.handle_frame(
+            &serde_json::json!({"type": "cancel", "session_id": session_id}).to_string(),
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        conn.handle_frame(
+            &serde_json::json!({"type": "resume", "session_id": session_id, "last_seq": 0})
+                .to_string(),
+        );
+
+        let frames = frames_until(&mut rx, "resumed").await;
+        assert_eq!(frames[0]["type"], "cancelled");
+        let resumed = frames.last().unwrap();
+        assert_eq!(resumed["complete"], true);
+        assert_eq!(resumed["running"], false);
+
+        // The cancelled turn left no history behind.
+        conn.handle_frame(
+            &serde_json::json!({"type": "message", "session_id": session_id, "content": "hi"})
+                .to_string(),
+        );
+        let done = frames_until(&mut rx, "done").await;
+        assert_eq!(done.last().unwrap()["full_response"], "reply 2");
+    }
+
+    #[tokio::test]
+    async fn sessions_are_capped_per_client() {
+        let state = test_state(Arc::new(ScriptedProvider::default()));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut conn = WsConnection::new(state.clone(), tx, Some("token:a".into()));
+        for _ in 0..MAX_SESSIONS_PER_CLIENT {
+            conn.handle_frame(r#"{"type":"open"}"#);
+            assert_eq!(next_frame(&mut rx).await["type"], "session");
+        }
+        conn.handle_frame(r#"{"type":"open"}"#);
+        assert_eq!(next_frame(&mut rx).await["message"], "Too many sessions");
+
+        // A new socket with the same token shares the cap; other clients do not.
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut same = WsConnection::new(state.clone(), tx, Some("token:a".into()));
+        same.handle_frame(r#"{"type":"open"}"#);
+        assert_eq!(next_frame(&mut rx).await["type"], "error");
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut other = WsConnection::new(state, tx, Some("token:b".into()));
+        other.handle_frame(r#"{"type":"open"}"#);
+        assert_eq!(next_frame(&mut rx).await["type"], "session");
+    }
+
+    #[tokio::test]
+    async fn sessions_are_private_to_their_token() {
+        let state = test_state(Arc::new(ScriptedProvider::default()));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut owner = WsConnection::new(state.clone(), tx, Some("token:a".into()));
+        owner.handle_frame(r#"{"type":"message","content":"slow task"}"#);
+        let session_id = next_frame(&mut rx).await["session_id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut other = WsConnection::new(state.clone(), tx, Some("token:b".into()));
+        for frame in [
+            serde_json::json!({"type": "resume", "session_id": session_id, "last_seq": 0}),
+            serde_json::json!({"type": "message", "session_id": session_id, "content": "hi"}),
+            serde_json::json!({"type": "cancel", "session_id": session_id}),
+        ] {
+            other.handle_frame(&frame.to_string());
+            let reply = next_frame(&mut rx).await;
+            assert_eq!(reply["message"], "Unknown session", "{frame}");
+        }
+        other.handle_frame(
+            &serde_json::json!({"type": "close", "session_id": session_id}).to_string(),
+        );
+        let session = state
+            .ws_sessions
+            .get(&session_id)
+            .expect("session survives");
+        assert!(session.cancel(), "the owner's turn is still running");
+    }
+
+    struct DenyShellHook;
+
+    #[async_trait]
+    impl crate::hooks::HookHandler for DenyShellHook {
+        fn name(&self) -> &str {
+            "deny-shell"
+        }
+
+        async fn before_tool_call(
+            &self,
+            name: String,
+            args: serde_json::Value,
+        ) -> crate::hooks::HookResult<(String, serde_json::Value)> {
+            if name == "shell" {
+                crate::hooks::HookResult::Cancel("shell is denied".into())
+            } else {
+                crate::hooks::HookResult::Continue((name, args))
             }
         }
     }
+
+    #[tokio::test]
+    async fn turns_run_tool_calls_through_the_gateway_hooks() {
+        let provider = Arc::new(ScriptedProvider::default());
+        let mut state = test_state(Arc::clone(&provider));
+        let mut hooks = crate::hooks::HookRunner::new();
+        hooks.register(Box::new(DenyShellHook));
+        state.hooks = Some(Arc::new(hooks));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut conn = WsConnection::new(state, tx, None);
+
+        conn.handle_frame(r#"{"type":"message","content":"use a tool"}"#);
+        frames_until(&mut rx, "done").await;
+        assert!(provider
+            .last_message
+            .lock()
+            .contains("Cancelled by hook: shell is denied"));
+    }
+
+    #[tokio::test]
+    async fn completed_turns_are_delivered_to_turn_complete_webhooks() {
+        use crate::gateway::webhooks::OutboundWebhooks;
+        use crate::observability::events;
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("x-zeroclaw-event", "turn_complete"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut config = Config {
+            config_path: tmp.path().join("config.toml"),
+            workspace_dir: tmp.path().join("workspace"),
+            ..Config::default()
+        };
+        config.gateway.outbound_webhook_allow_private_hosts = true;
+        events::init_from_config(&config.observability, &config.workspace_dir);
+        let webhooks = Arc::new(OutboundWebhooks::load(&config).unwrap());
+        webhooks
+            .add(&server.uri(), vec!["turn_complete".into()], None)
+            .unwrap();
+        tokio::spawn(Arc::clone(&webhooks).run(events::hub().unwrap()));
+
+        let mut state = test_state(Arc::new(ScriptedProvider::default()));
+        state.observer = Arc::from(crate::observability::create_observer(&config.observability));
+        state.webhooks = webhooks;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut conn = WsConnection::new(state, tx, None);
+        conn.handle_frame(r#"{"type":"message","content":"hello"}"#);
+        frames_until(&mut rx, "done").await;
+
+        let delivered = tokio::time::timeout(Duration::from_secs(5), async {
+            while server
+                .received_requests()
+                .await
+                .unwrap_or_default()
+                .is_empty()
+            {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await;
+        assert!(delivered.is_ok(), "turn_complete webhook was not delivered");
+    }
 }
@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::container::capability_ceiling::{
+    is_within_ceiling, tool_permission_level, CeilingManager, PermissionLevel,
+};
+use crate::hooks::traits::{HookHandler, HookResult, ToolCallContext};
+use crate::security::audit::{AuditEvent, AuditEventType, AuditLogger};
+
+/// Enforces the acting tenant user's capability ceiling before every tool call.
+///
+/// Each tool maps to a permission level; a call above the user's effective
+/// ceiling (base ceiling raised by any approved escalation) is cancelled.
+/// Every decision is written to the audit log.
+pub struct CapabilityCeilingHook {
+    ceilings: CeilingSource,
+    user_id: String,
+    audit: Option<Arc<AuditLogger>>,
+}
+
+/// Where the ceiling comes from: the live tenant database, or a level fixed
+/// when a supervised container was launched.
+enum CeilingSource {
+    Store(CeilingManager),
+    Fixed(PermissionLevel),
+}
+
+impl CapabilityCeilingHook {
+    pub fn new(ceilings: CeilingManager, user_id: String) -> Self {
+        Self {
+            ceilings: CeilingSource::Store(ceilings),
+            user_id,
+            audit: None,
+        }
+    }
+
+    /// Enforce a ceiling resolved ahead of time (container instances have no
+    /// access to the tenant database).
+    pub fn fixed(ceiling: PermissionLevel, user_id: String) -> Self {
+        Self {
+            ceilings: CeilingSource::Fixed(ceiling),
+            user_id,
+            audit: None,
+        }
+    }
+
+    /// Attach an audit logger that records every allow and deny decision.
+    pub fn with_audit(mut self, audit: Arc<AuditLogger>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+}
+
+#[async_trait]
+impl HookHandler for CapabilityCeilingHook {
+    fn name(&self) -> &str {
+        "capability-ceiling"
+    }
+
+    /// Runs after the rewriting hooks, just before the tool rules.
+    fn priority(&self) -> i32 {
+        i32::MIN + 1
+    }
+
+    async fn before_tool_call_in_context(
+        &self,
+        context: &ToolCallContext,
+        name: String,
+        args: Value,
+    ) -> HookResult<(String, Value)> {
+        let required = tool_permission_level(&name);
+        let ceiling = match &self.ceilings {
+            CeilingSource::Store(manager) => manager.effective_ceiling(&self.user_id),
+            CeilingSource::Fixed(level) => *level,
+        };
+        let allowed = is_within_ceiling(required, ceiling);
+
+        if let Some(audit) = &self.audit {
+            let event_type = if allowed {
+                AuditEventType::SecurityEvent
+            } else {
+                AuditEventType::PolicyViolation
+            };
+            let mut event = AuditEvent::new(event_type)
+                .with_actor(context.channel.clone(), Some(self.user_id.clone()), None)
+                .with_action(
+                    format!("tool:{name} requires {required:?} (ceiling {ceiling:?})"),
+                    format!("{required:?}"),
+                    false,
+                    allowed,
+                );
+            event.security.policy_violation = !allowed;
+            if let Err(e) = audit.log(&event) {
+                tracing::warn!(tool = %name, "Failed to write ceiling audit entry: {e}");
+            }
+        }
+
+        if allowed {
+            HookResult::Continue((name, args))
+        } else {
+            tracing::info!(
+                hook = "capability-ceiling",
+                tool = %name,
+                user = %self.user_id,
+                ?required,
+                ?ceiling,
+                "tool call above ceiling"
+            );
+            HookResult::Cancel(format!(
+                "tool '{name}' needs {required:?} permission but the agent ceiling is \
+                 {ceiling:?}; ask an admin to approve an escalation"
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::PermissionLevel;
+    use crate::tenant::TenantDb;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn cancels_tools_above_ceiling_and_audits() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = TenantDb::open(tmp.path()).unwrap();
+        CeilingManager::new(db.clone())
+            .set_ceiling("u1", PermissionLevel::Read)
+            .unwrap();
+        let audit = AuditLogger::new(
+            crate::config::AuditConfig::default(),
+            tmp.path().to_path_buf(),
+        )
+        .unwrap();
+        let hook = CapabilityCeilingHook::new(CeilingManager::new(db), "u1".into())
+            .with_audit(Arc::new(audit));
+        let context = ToolCallContext {
+            channel: "api".into(),
+            sender: Some("u1".into()),
+        };
+
+        let read = hook
+            .before_tool_call_in_context(&context, "file_read".into(), json!({}))
+            .await;
+        assert!(matches!(read, HookResult::Continue(_)));
+        let shell = hook
+            .before_tool_call_in_context(&context, "shell".into(), json!({}))
+            .await;
+        assert!(matches!(shell, HookResult::Cancel(_)));
+
+        let log = std::fs::read_to_string(
+            tmp.path()
+                .join(crate::config::AuditConfig::default().log_path),
+        )
+        .unwrap();
+        assert_eq!(log.lines().count(), 2);
+        assert!(log.contains("\"allowed\":false"));
+    }
+}
@@ -1,3 +1,7 @@
+pub mod capability_ceiling;
 pub mod command_logger;
+pub mod tool_policy;
 
+pub use capability_ceiling::CapabilityCeilingHook;
 pub use command_logger::CommandLoggerHook;
+pub use tool_policy::ToolPolicyHook;
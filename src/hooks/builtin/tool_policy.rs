@@ -0,0 +1,223 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::approval::{prompt_cli_interactive, ApprovalRequest, ApprovalResponse};
+use crate::hooks::traits::{HookHandler, HookResult, ToolCallContext};
+use crate::security::tool_rules::{ToolCallFacts, ToolRules, Verdict};
+
+/// Enforces `[security.tool_rules]` before every tool call.
+///
+/// `ask` prompts on the CLI; other channels have no interactive approval, so
+/// `ask` is treated as `deny` there. In dry-run mode decisions are only logged.
+pub struct ToolPolicyHook {
+    rules: Arc<ToolRules>,
+    dry_run: bool,
+}
+
+impl ToolPolicyHook {
+    pub fn new(rules: Arc<ToolRules>, dry_run: bool) -> Self {
+        Self { rules, dry_run }
+    }
+}
+
+#[async_trait]
+impl HookHandler for ToolPolicyHook {
+    fn name(&self) -> &str {
+        "tool-policy"
+    }
+
+    /// Runs after every other hook so it judges the final tool name and args.
+    fn priority(&self) -> i32 {
+        i32::MIN
+    }
+
+    async fn before_tool_call_in_context(
+        &self,
+        context: &ToolCallContext,
+        name: String,
+        args: Value,
+    ) -> HookResult<(String, Value)> {
+        let decision = self.rules.evaluate(&ToolCallFacts {
+            tool: &name,
+            args: &args,
+            channel: &context.channel,
+            sender: context.sender.as_deref(),
+            at: self.rules.now(),
+        });
+        let rule = decision.rule.as_deref().unwrap_or("default");
+
+        if decision.verdict != Verdict::Allow {
+            tracing::info!(
+                hook = "tool-policy",
+                tool = %name,
+                channel = %context.channel,
+                rule,
+                verdict = %decision.verdict,
+                dry_run = self.dry_run,
+                reason = %decision.reason,
+                "tool rule matched"
+            );
+        }
+        if self.dry_run {
+            return HookResult::Continue((name, args));
+        }
+
+        match decision.verdict {
+            Verdict::Allow => HookResult::Continue((name, args)),
+            Verdict::Deny => HookResult::Cancel(format!(
+                "blocked by tool rule '{rule}': {}",
+                decision.reason
+            )),
+            Verdict::Ask if context.channel == "cli" => {
+                let request = ApprovalRequest {
+                    tool_name: name.clone(),
+                    arguments: args.clone(),
+                };
+                let response =
+                    tokio::task::spawn_blocking(move || prompt_cli_interactive(&request))
+                        .await
+                        .unwrap_or(ApprovalResponse::No);
+                if response == ApprovalResponse::No {
+                    HookResult::Cancel(format!("denied by user (tool rule '{rule}')"))
+                } else {
+                    HookResult::Continue((name, args))
+                }
+            }
+            Verdict::Ask => HookResult::Cancel(format!(
+                "tool rule '{rule}' requires approval, which is not available on channel '{}'",
+                context.channel
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn hook(rules: &str, dry_run: bool) -> ToolPolicyHook {
+        ToolPolicyHook::new(Arc::new(ToolRules::from_toml(rules).unwrap()), dry_run)
+    }
+
+    fn context(channel: &str, sender: Option<&str>) -> ToolCallContext {
+        ToolCallContext {
+            channel: channel.into(),
+            sender: sender.map(str::to_string),
+        }
+    }
+
+    const RULES: &str = r#"
+[[rules]]
+name = "no-rm"
+tools = ["shell"]
+action = "deny"
+reason = "recursive delete"
+args = [{ path = "command", contains = "rm -rf" }]
+
+[[rules]]
+name = "ask-on-write"
+tools = ["file_write"]
+action = "ask"
+"#;
+
+    #[tokio::test]
+    async fn deny_cancels_with_rule_name() {
+        let hook = hook(RULES, false);
+        let result = hook
+            .before_tool_call_in_context(
+                &context("telegram", Some("alice")),
+                "shell".into(),
+                json!({"command": "rm -rf /"}),
+            )
+            .await;
+        match result {
+            HookResult::Cancel(reason) => {
+                assert!(reason.contains("no-rm"));
+                assert!(reason.contains("recursive delete"));
+            }
+            HookResult::Continue(_) => panic!("expected cancel"),
+        }
+    }
+
+    #[tokio::test]
+    async fn allowed_calls_pass_through_unchanged() {
+        let hook = hook(RULES, false);
+        let args = json!({"command": "ls"});
+        let result = hook
+            .before_tool_call_in_context(&context("telegram", None), "shell".into(), args.clone())
+            .await;
+        match result {
+            HookResult::Continue((name, passed)) => {
+                assert_eq!(name, "shell");
+                assert_eq!(passed, args);
+            }
+            HookResult::Cancel(reason) => panic!("unexpected cancel: {reason}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn ask_is_denied_off_cli() {
+        let hook = hook(RULES, false);
+        let result = hook
+            .before_tool_call_in_context(
+                &context("discord", Some("bob")),
+                "file_write".into(),
+                json!({"path": "a.txt"}),
+            )
+            .await;
+        assert!(
+            matches!(result, HookResult::Cancel(ref reason) if reason.contains("not available on channel 'discord'"))
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_never_blocks() {
+        let hook = hook(RULES, true);
+        let result = hook
+            .before_tool_call_in_context(
+                &context("telegram", None),
+                "shell".into(),
+                json!({"command": "rm -rf /"}),
+            )
+            .await;
+        assert!(!result.is_cancel());
+    }
+
+    #[tokio::test]
+    async fn runner_passes_context_to_policy_hook() {
+        let mut runner = crate::hooks::HookRunner::new();
+        runner.register(Box::new(hook(
+            r#"
+[[rules]]
+name = "only-ops"
+senders = ["ops"]
+action = "allow"
+
+[[rules]]
+name = "everyone-else"
+action = "deny"
+"#,
+            false,
+        )));
+
+        let ops = runner
+            .run_before_tool_call_in_context(
+                &context("slack", Some("ops")),
+                "shell".into(),
+                json!({}),
+            )
+            .await;
+        assert!(!ops.is_cancel());
+
+        let other = runner
+            .run_before_tool_call_in_context(
+                &context("slack", Some("intern")),
+                "shell".into(),
+                json!({}),
+            )
+            .await;
+        assert!(other.is_cancel());
+    }
+}
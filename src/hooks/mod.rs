@@ -7,4 +7,4 @@ pub use runner::HookRunner;
 // They may appear unused internally but are intentionally re-exported for
 // external integrations and future plugin authors.
 #[allow(unused_imports)]
-pub use traits::{HookHandler, HookResult};
+pub use traits::{HookHandler, HookResult, ToolCallContext};
@@ -9,7 +9,7 @@ use crate::channels::traits::ChannelMessage;
 use crate::providers::traits::{ChatMessage, ChatResponse};
 use crate::tools::traits::ToolResult;
 
-use super::traits::{HookHandler, HookResult};
+use super::traits::{HookHandler, HookResult, ToolCallContext};
 
 /// Dispatcher that manages registered hook handlers.
 ///
@@ -28,6 +28,57 @@ impl HookRunner {
         }
     }
 
+    /// Build the runner for `[hooks]` and `[security.tool_rules]`.
+    ///
+    /// Tool rules are registered whenever they are enabled, even with
+    /// `hooks.enabled = false`, so disabling lifecycle hooks never silently
+    /// drops a security policy. The same holds for the capability ceiling of
+    /// the acting tenant user, or of the owner of a supervised container
+    /// (`[tenant]`). Returns `None` when nothing is registered.
+    pub fn from_config(config: &crate::config::Config) -> anyhow::Result<Option<Self>> {
+        let mut runner = Self::new();
+        if config.hooks.enabled && config.hooks.builtin.command_logger {
+            runner.register(Box::new(super::builtin::CommandLoggerHook::new()));
+        }
+        if config.security.tool_rules.enabled {
+            let rules = crate::security::tool_rules::load_from_config(config)?;
+            runner.register(Box::new(super::builtin::ToolPolicyHook::new(
+                std::sync::Arc::new(rules),
+                config.security.tool_rules.dry_run,
+            )));
+        }
+        if let Some(tenant) = &config.tenant_context {
+            let db = crate::tenant::TenantDb::open(&tenant.data_dir)?;
+            let mut hook = super::builtin::CapabilityCeilingHook::new(
+                crate::container::CeilingManager::new(db),
+                tenant.user_id.clone(),
+            );
+            if let Ok(audit) = crate::security::audit::AuditLogger::new(
+                config.security.audit.clone(),
+                tenant.data_dir.clone(),
+            ) {
+                hook = hook.with_audit(std::sync::Arc::new(audit));
+            }
+            runner.register(Box::new(hook));
+        } else if let Some(tenant) = &config.tenant {
+            let mut hook = super::builtin::CapabilityCeilingHook::fixed(
+                tenant.ceiling,
+                tenant.user_id.clone(),
+            );
+            if let Some(audit) = config.config_path.parent().and_then(|zeroclaw_dir| {
+                crate::security::audit::AuditLogger::new(
+                    config.security.audit.clone(),
+                    zeroclaw_dir.to_path_buf(),
+                )
+                .ok()
+            }) {
+                hook = hook.with_audit(std::sync::Arc::new(audit));
+            }
+            runner.register(Box::new(hook));
+        }
+        Ok((config.hooks.enabled || !runner.handlers.is_empty()).then_some(runner))
+    }
+
     /// Register a handler and re-sort by descending priority.
     pub fn register(&mut self, handler: Box<dyn HookHandler>) {
         self.handlers.push(handler);
@@ -214,14 +265,28 @@ impl HookRunner {
 
     pub async fn run_before_tool_call(
         &self,
+        name: String,
+        args: Value,
+    ) -> HookResult<(String, Value)> {
+        self.run_before_tool_call_in_context(&ToolCallContext::default(), name, args)
+            .await
+    }
+
+    pub async fn run_before_tool_call_in_context(
+        &self,
+        context: &ToolCallContext,
         mut name: String,
         mut args: Value,
     ) -> HookResult<(String, Value)> {
         for h in &self.handlers {
             let hook_name = h.name();
-            match AssertUnwindSafe(h.before_tool_call(name.clone(), args.clone()))
-                .catch_unwind()
-                .await
+            match AssertUnwindSafe(h.before_tool_call_in_context(
+                context,
+                name.clone(),
+                args.clone(),
+            ))
+            .catch_unwind()
+            .await
             {
                 Ok(HookResult::Continue((n, a))) => {
                     name = n;
@@ -234,11 +299,13 @@ impl HookRunner {
                     );
                     return HookResult::Cancel(reason);
                 }
+                // Tool gating hooks enforce policy: a crash must not let the call through.
                 Err(_) => {
                     tracing::error!(
                         hook = hook_name,
-                        "before_tool_call hook panicked; continuing with previous values"
+                        "before_tool_call hook panicked; cancelling the tool call"
                     );
+                    return HookResult::Cancel(format!("hook '{hook_name}' failed"));
                 }
             }
         }
@@ -413,6 +480,23 @@ mod tests {
         }
     }
 
+    /// A tool gating hook that panics.
+    struct PanickingToolHook;
+
+    #[async_trait]
+    impl HookHandler for PanickingToolHook {
+        fn name(&self) -> &str {
+            "panicky"
+        }
+        async fn before_tool_call(
+            &self,
+            _name: String,
+            _args: Value,
+        ) -> HookResult<(String, Value)> {
+            panic!("policy lookup failed");
+        }
+    }
+
     #[test]
     fn register_and_sort_by_priority() {
         let mut runner = HookRunner::new();
@@ -459,6 +543,20 @@ mod tests {
         assert!(result.is_cancel());
     }
 
+    #[tokio::test]
+    async fn panicking_tool_hook_cancels_the_call() {
+        let mut runner = HookRunner::new();
+        runner.register(Box::new(PanickingToolHook));
+
+        let result = runner
+            .run_before_tool_call("shell".into(), serde_json::json!({"command": "ls"}))
+            .await;
+        match result {
+            HookResult::Cancel(reason) => assert!(reason.contains("panicky")),
+            HookResult::Continue(_) => panic!("a panicking hook must not allow the call"),
+        }
+    }
+
     #[tokio::test]
     async fn modifying_hook_pipelines_data() {
         let mut runner = HookRunner::new();
@@ -19,6 +19,15 @@ impl<T> HookResult<T> {
     }
 }
 
+/// Origin of a tool call, passed to [`HookHandler::before_tool_call_in_context`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolCallContext {
+    /// Channel the request came from (`"cli"`, `"telegram"`, ...).
+    pub channel: String,
+    /// Sender identity on that channel, when known.
+    pub sender: Option<String>,
+}
+
 /// Trait for hook handlers. All methods have default no-op implementations.
 /// Implement only the events you care about.
 #[async_trait]
@@ -64,6 +73,17 @@ pub trait HookHandler: Send + Sync {
         HookResult::Continue((name, args))
     }
 
+    /// Like [`before_tool_call`](Self::before_tool_call), with the channel and
+    /// sender that triggered the call. Defaults to `before_tool_call`.
+    async fn before_tool_call_in_context(
+        &self,
+        _context: &ToolCallContext,
+        name: String,
+        args: Value,
+    ) -> HookResult<(String, Value)> {
+        self.before_tool_call(name, args).await
+    }
+
     async fn on_message_received(&self, message: ChannelMessage) -> HookResult<ChannelMessage> {
         HookResult::Continue(message)
     }
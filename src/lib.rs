@@ -64,6 +64,8 @@ pub mod peripherals;
 pub mod providers;
 pub mod rag;
 pub mod relay;
+#[cfg(feature = "robot-kit")]
+pub mod robot;
 pub mod runtime;
 pub(crate) mod security;
 pub mod server;
@@ -142,6 +144,83 @@ Examples:
     },
 }
 
+/// Gateway management subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GatewayCommands {
+    /// Manage gateway bearer tokens (scopes, expiry, revocation)
+    Tokens {
+        #[command(subcommand)]
+        token_command: GatewayTokenCommands,
+    },
+}
+
+/// Gateway token subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GatewayTokenCommands {
+    /// List gateway tokens with scopes, expiry and last use
+    List,
+    /// Create a named token (plaintext is printed once)
+    #[command(long_about = "\
+Create a named gateway bearer token.
+
+Scopes: webhook (POST /webhook, /ws/chat), read (read-only dashboard API), \
+admin (everything, including config writes and token management).
+
+Examples:
+  zeroclaw gateway tokens create ci-bot --scope webhook --ttl 30d
+  zeroclaw gateway tokens create dashboard --scope read")]
+    Create {
+        /// Unique token name
+        name: String,
+        /// Scope to grant (repeatable: webhook, read, admin)
+        #[arg(long = "scope", required = true)]
+        scopes: Vec<String>,
+        /// Time-to-live (e.g. "12h", "30d"); omit for a non-expiring token
+        #[arg(long)]
+        ttl: Option<String>,
+    },
+    /// Revoke a token by name
+    Revoke {
+        /// Token name (see `zeroclaw gateway tokens list`)
+        name: String,
+    },
+}
+
+/// Tool-call rule subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PolicyCommands {
+    /// Validate the rules file and list rules in evaluation order
+    Check,
+    /// Dry-run a tool call against the rules and show which rule fires
+    #[command(long_about = "\
+Dry-run a tool call against the rules file.
+
+Prints every rule checked, why it did or did not match, and the final \
+decision. Rate limits are reported but not consumed.
+
+Examples:
+  zeroclaw policy explain --tool shell --args '{\"command\":\"rm -rf /tmp\"}'
+  zeroclaw policy explain --tool http_request --channel telegram --sender alice
+  zeroclaw policy explain --tool shell --at 2026-01-31T23:30")]
+    Explain {
+        /// Tool name
+        #[arg(long)]
+        tool: String,
+        /// Tool arguments as a JSON object
+        #[arg(long)]
+        args: Option<String>,
+        /// Channel the call comes from
+        #[arg(long, default_value = "cli")]
+        channel: String,
+        /// Sender identity on that channel
+        #[arg(long)]
+        sender: Option<String>,
+        /// Evaluate at this wall-clock time (YYYY-MM-DDTHH:MM) instead of now
+        #[arg(long)]
+        at: Option<String>,
+    },
+}
+
 /// Skills management subcommands
 #[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum SkillCommands {
@@ -162,6 +241,41 @@ pub enum SkillCommands {
         /// Skill name to remove
         name: String,
     },
+    /// Re-fetch pinned skills from their source, re-audit and update skills.lock
+    Update {
+        /// Skill to update (default: all pinned skills)
+        name: Option<String>,
+    },
+    /// Show pinned skills whose source has newer content
+    Outdated,
+    /// Sign a skill directory with an ed25519 publisher key (writes SKILL.sig)
+    Sign {
+        /// Skill directory to sign
+        path: std::path::PathBuf,
+        /// Publisher name recorded in the signature
+        #[arg(long)]
+        publisher: String,
+        /// Signing key file (default: ~/.zeroclaw/skill-signing.key, created if missing)
+        #[arg(long)]
+        key: Option<std::path::PathBuf>,
+    },
+    /// Verify the signature of a skill directory or installed skill name
+    Verify {
+        /// Skill path or installed skill name
+        source: String,
+    },
+    /// Add a publisher public key to the local trust store
+    Trust {
+        /// Publisher name
+        publisher: String,
+        /// Hex-encoded ed25519 public key
+        public_key: String,
+    },
+    /// Remove a publisher from the local trust store
+    Untrust {
+        /// Publisher name
+        publisher: String,
+    },
 }
 
 /// Migration subcommands
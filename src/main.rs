@@ -56,6 +56,9 @@ mod rag {
     pub use zeroclaw::rag::*;
 }
 mod config;
+mod container {
+    pub use zeroclaw::container::*;
+}
 mod cost;
 mod cron;
 mod daemon;
@@ -74,9 +77,16 @@ mod observability;
 mod onboard;
 mod peripherals;
 mod providers;
+mod relay {
+    pub use zeroclaw::relay::*;
+}
+#[cfg(feature = "robot-kit")]
+mod robot;
 mod runtime;
 mod security;
-mod server;
+mod server {
+    pub use zeroclaw::server::*;
+}
 mod service;
 mod skillforge;
 mod skills;
@@ -91,8 +101,9 @@ use config::Config;
 
 // Re-export so binary modules can use crate::<CommandEnum> while keeping a single source of truth.
 pub use zeroclaw::{
-    ChannelCommands, CronCommands, HardwareCommands, IntegrationCommands, MigrateCommands,
-    PeripheralCommands, ServiceCommands, SkillCommands,
+    ChannelCommands, CronCommands, GatewayCommands, GatewayTokenCommands, HardwareCommands,
+    IntegrationCommands, MigrateCommands, PeripheralCommands, PolicyCommands, ServiceCommands,
+    SkillCommands,
 };
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
@@ -212,8 +223,12 @@ Examples:
   zeroclaw gateway                  # use config defaults
   zeroclaw gateway -p 8080          # listen on port 8080
   zeroclaw gateway --host 0.0.0.0   # bind to all interfaces
-  zeroclaw gateway -p 0             # random available port")]
+  zeroclaw gateway -p 0             # random available port
+  zeroclaw gateway tokens list      # show bearer tokens and scopes")]
     Gateway {
+        #[command(subcommand)]
+        gateway_command: Option<GatewayCommands>,
+
         /// Port to listen on (use 0 for random available port); defaults to config gateway.port
         #[arg(short, long)]
         port: Option<u16>,
@@ -296,6 +311,22 @@ Examples:
         tools: Vec<String>,
     },
 
+    /// Check and dry-run policy-as-code tool rules
+    #[command(long_about = "\
+Check and dry-run policy-as-code tool rules.
+
+Rules live in ~/.zeroclaw/tool_rules.toml (see [security.tool_rules]) and \
+are evaluated before every tool call. The first matching rule decides: \
+allow, deny, ask, or rate-limit.
+
+Examples:
+  zeroclaw policy check
+  zeroclaw policy explain --tool shell --args '{\"command\":\"rm -rf /\"}' --channel telegram")]
+    Policy {
+        #[command(subcommand)]
+        policy_command: PolicyCommands,
+    },
+
     /// Configure and manage scheduled tasks
     #[command(long_about = "\
 Configure and manage scheduled tasks.
@@ -780,7 +811,16 @@ async fn main() -> Result<()> {
         .await
         .map(|_| ()),
 
-        Commands::Gateway { port, host } => {
+        Commands::Gateway {
+            gateway_command: Some(gateway_command),
+            ..
+        } => gateway::handle_command(gateway_command, &config).await,
+
+        Commands::Gateway {
+            gateway_command: None,
+            port,
+            host,
+        } => {
             let port = port.unwrap_or(config.gateway.port);
             let host = host.unwrap_or_else(|| config.gateway.host.clone());
             if port == 0 {
@@ -903,6 +943,10 @@ async fn main() -> Result<()> {
             tools,
         } => handle_estop_command(&config, estop_command, level, domains, tools),
 
+        Commands::Policy { policy_command } => {
+            security::tool_rules::handle_command(policy_command, &config)
+        }
+
         Commands::Cron { cron_command } => cron::handle_command(cron_command, &config),
 
         Commands::Models { model_command } => match model_command {
@@ -1004,7 +1048,7 @@ async fn main() -> Result<()> {
 
         Commands::Server { host, port } => {
             info!("Starting multi-tenant server on {}:{}", host, port);
-            zeroclaw::server::run_server(port, &host, &config.workspace_dir).await?;
+            server::run_server(port, &host, &config.workspace_dir).await?;
             Ok(())
         }
 
@@ -0,0 +1,567 @@
+//! Agent event stream behind `GET /api/events`.
+//!
+//! Observers built by [`create_observer`](super::create_observer) publish every
+//! [`ObserverEvent`] here as a typed [`AgentEvent`] with a monotonic id. The
+//! hub keeps the most recent events in a bounded ring buffer, persisted as
+//! JSONL by a background writer, so SSE clients can replay from
+//! `Last-Event-ID` across restarts.
+
+use super::traits::ObserverMetric;
+use super::{Observer, ObserverEvent};
+use crate::config::ObservabilityConfig;
+use anyhow::Result;
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, LazyLock, RwLock};
+use tokio::sync::broadcast;
+
+const DEFAULT_EVENTS_REL_PATH: &str = "state/events.jsonl";
+const LIVE_CHANNEL_CAPACITY: usize = 256;
+
+tokio::task_local! {
+    static EVENT_CHANNEL: String;
+}
+
+/// Tag every event recorded while `fut` runs with `channel`.
+pub async fn with_channel<F: Future>(channel: String, fut: F) -> F::Output {
+    EVENT_CHANNEL.scope(channel, fut).await
+}
+
+fn current_channel() -> Option<String> {
+    EVENT_CHANNEL.try_with(Clone::clone).ok()
+}
+
+/// One published event, as sent to SSE clients and stored in the buffer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentEvent {
+    pub id: u64,
+    pub timestamp: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    #[serde(default)]
+    pub data: Value,
+}
+
+/// Client-side subscription filter. Empty lists match everything.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub types: Vec<String>,
+    pub channels: Vec<String>,
+}
+
+impl EventFilter {
+    /// Build a filter from comma-separated `types` and `channels` lists.
+    pub fn parse(types: Option<&str>, channels: Option<&str>) -> Self {
+        fn split(raw: Option<&str>) -> Vec<String> {
+            raw.unwrap_or_default()
+                .split(',')
+                .map(|item| item.trim().to_ascii_lowercase())
+                .filter(|item| !item.is_empty())
+                .collect()
+        }
+        Self {
+            types: split(types),
+            channels: split(channels),
+        }
+    }
+
+    pub fn matches(&self, event: &AgentEvent) -> bool {
+        let type_ok = self.types.is_empty()
+            || self
+                .types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(&event.event_type));
+        let channel_ok = self.channels.is_empty()
+            || event
+                .channel
+                .as_deref()
+                .is_some_and(|c| self.channels.iter().any(|f| f.eq_ignore_ascii_case(c)));
+        type_ok && channel_ok
+    }
+}
+
+/// Event type name, channel and JSON payload for an observer event.
+pub fn describe(event: &ObserverEvent) -> (&'static str, Option<String>, Value) {
+    match event {
+        ObserverEvent::AgentStart { provider, model } => (
+            "agent_start",
+            None,
+            json!({ "provider": provider, "model": model }),
+        ),
+        ObserverEvent::LlmRequest {
+            provider,
+            model,
+            messages_count,
+        } => (
+            "llm_request",
+            None,
+            json!({ "provider": provider, "model": model, "messages_count": messages_count }),
+        ),
+        ObserverEvent::LlmResponse {
+            provider,
+            model,
+            duration,
+            success,
+            error_message,
+            input_tokens,
+            output_tokens,
+        } => (
+            "llm_response",
+            None,
+            json!({
+                "provider": provider,
+                "model": model,
+                "duration_ms": duration.as_millis(),
+                "success": success,
+                "error": error_message,
+                "input_tokens": input_tokens,
+                "output_tokens": output_tokens,
+            }),
+        ),
+        ObserverEvent::AgentEnd {
+            provider,
+            model,
+            duration,
+            tokens_used,
+            cost_usd,
+        } => (
+            "agent_end",
+            None,
+            json!({
+                "provider": provider,
+                "model": model,
+                "duration_ms": duration.as_millis(),
+                "tokens_used": tokens_used,
+                "cost_usd": cost_usd,
+            }),
+        ),
+        ObserverEvent::ToolCallStart { tool } => ("tool_call_start", None, json!({ "tool": tool })),
+        ObserverEvent::ToolCall {
+            tool,
+            duration,
+            success,
+        } => (
+            "tool_call",
+            None,
+            json!({ "tool": tool, "duration_ms": duration.as_millis(), "success": success }),
+        ),
+        ObserverEvent::TurnComplete => ("turn_complete", None, json!({})),
+        ObserverEvent::ChannelMessage { channel, direction } => (
+            "channel_message",
+            Some(channel.clone()),
+            json!({ "direction": direction }),
+        ),
+        ObserverEvent::HeartbeatTick => ("heartbeat_tick", None, json!({})),
+        ObserverEvent::Error { component, message } => (
+            "error",
+            None,
+            json!({ "component": component, "message": message }),
+        ),
+        ObserverEvent::RobotSafety { kind, detail } => (
+            "robot_safety",
+            None,
+            json!({ "kind": kind, "detail": detail }),
+        ),
+        ObserverEvent::CronRun {
+            job_id,
+            name,
+            success,
+            duration,
+        } => (
+            "cron_run",
+            None,
+            json!({
+                "job_id": job_id,
+                "name": name,
+                "success": success,
+                "duration_ms": duration.as_millis(),
+            }),
+        ),
+        ObserverEvent::BudgetWarning {
+            period,
+            current_usd,
+            limit_usd,
+            exceeded,
+        } => (
+            "budget_warning",
+            None,
+            json!({
+                "period": period,
+                "current_usd": current_usd,
+                "limit_usd": limit_usd,
+                "exceeded": exceeded,
+            }),
+        ),
+        ObserverEvent::EstopChange { engaged, detail } => (
+            "estop_change",
+            None,
+            json!({ "engaged": engaged, "detail": detail }),
+        ),
+    }
+}
+
+struct HubState {
+    buffer: VecDeque<AgentEvent>,
+    next_id: u64,
+}
+
+enum WriterMessage {
+    Event(AgentEvent),
+    /// Reply once every earlier event has been written.
+    Flush(mpsc::SyncSender<()>),
+}
+
+/// Bounded, persisted ring buffer of published events with a live feed.
+pub struct EventHub {
+    capacity: usize,
+    state: Mutex<HubState>,
+    live: broadcast::Sender<AgentEvent>,
+    /// Feeds the writer thread; `None` when the hub is not persisted.
+    writer: Option<mpsc::Sender<WriterMessage>>,
+}
+
+impl EventHub {
+    /// Create a hub holding up to `capacity` events. With a `path`, events
+    /// stored by a previous run are loaded and ids continue after them.
+    pub fn new(capacity: usize, path: Option<PathBuf>) -> Self {
+        let capacity = capacity.max(1);
+        let mut buffer = VecDeque::with_capacity(capacity);
+        if let Some(path) = path.as_deref() {
+            for event in load_events(path) {
+                if buffer.len() == capacity {
+                    buffer.pop_front();
+                }
+                buffer.push_back(event);
+            }
+        }
+        let next_id = buffer.back().map_or(1, |event| event.id + 1);
+        let (live, _) = broadcast::channel(LIVE_CHANNEL_CAPACITY);
+        let writer = path.map(|path| {
+            let (tx, rx) = mpsc::channel();
+            let mut persister = Persister {
+                path,
+                capacity,
+                recent: buffer.clone(),
+                appended: 0,
+            };
+            let spawned = std::thread::Builder::new()
+                .name("event-writer".into())
+                .spawn(move || persister.run(&rx));
+            if let Err(err) = spawned {
+                tracing::warn!("Agent events will not be persisted: {err}");
+            }
+            tx
+        });
+        Self {
+            capacity,
+            state: Mutex::new(HubState { buffer, next_id }),
+            live,
+            writer,
+        }
+    }
+
+    /// Assign the next id, store the event and send it to live subscribers.
+    pub fn publish(&self, event_type: &str, channel: Option<String>, data: Value) -> AgentEvent {
+        let mut state = self.state.lock();
+        let event = AgentEvent {
+            id: state.next_id,
+            timestamp: Utc::now().to_rfc3339(),
+            event_type: event_type.to_string(),
+            channel,
+            data,
+        };
+        state.next_id += 1;
+        if state.buffer.len() == self.capacity {
+            state.buffer.pop_front();
+        }
+        state.buffer.push_back(event.clone());
+        // Queued under the lock so the file keeps id order.
+        if let Some(writer) = &self.writer {
+            let _ = writer.send(WriterMessage::Event(event.clone()));
+        }
+        drop(state);
+
+        let _ = self.live.send(event.clone());
+        event
+    }
+
+    /// Live feed of events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<AgentEvent> {
+        self.live.subscribe()
+    }
+
+    /// Buffered events with an id greater than `last_id`, oldest first.
+    pub fn replay_after(&self, last_id: u64) -> Vec<AgentEvent> {
+        self.state
+            .lock()
+            .buffer
+            .iter()
+            .filter(|event| event.id > last_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Id of the most recently published event, or 0 when none exist.
+    pub fn last_id(&self) -> u64 {
+        self.state.lock().next_id - 1
+    }
+
+    /// Block until every event published so far has been written to disk.
+    pub fn flush(&self) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+        let (done_tx, done_rx) = mpsc::sync_channel(1);
+        if writer.send(WriterMessage::Flush(done_tx)).is_ok() {
+            let _ = done_rx.recv();
+        }
+    }
+}
+
+/// Writes published events to the JSONL file off the publishing thread.
+struct Persister {
+    path: PathBuf,
+    capacity: usize,
+    /// The last `capacity` events, used to compact the file.
+    recent: VecDeque<AgentEvent>,
+    /// Lines appended to the file since it was last rewritten.
+    appended: usize,
+}
+
+impl Persister {
+    fn run(&mut self, rx: &mpsc::Receiver<WriterMessage>) {
+        for message in rx {
+            match message {
+                WriterMessage::Event(event) => {
+                    if let Err(err) = self.persist(event) {
+                        tracing::warn!("Failed to persist agent event: {err}");
+                    }
+                }
+                WriterMessage::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+
+    /// Append `event`, rewriting the file from the recent events once it has
+    /// grown by another `capacity` lines.
+    fn persist(&mut self, event: AgentEvent) -> Result<()> {
+        let mut line = serde_json::to_string(&event)?;
+        line.push('\n');
+        if self.recent.len() == self.capacity {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(event);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if self.appended >= self.capacity {
+            let mut lines = String::new();
+            for event in &self.recent {
+                lines.push_str(&serde_json::to_string(event)?);
+                lines.push('\n');
+            }
+            let tmp = self
+                .path
+                .with_extension(format!("tmp.{}", std::process::id()));
+            write_private(&tmp, lines.as_bytes(), false)?;
+            fs::rename(tmp, &self.path)?;
+            self.appended = 0;
+            return Ok(());
+        }
+
+        write_private(&self.path, line.as_bytes(), true)?;
+        self.appended += 1;
+        Ok(())
+    }
+}
+
+fn write_private(path: &Path, bytes: &[u8], append: bool) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.create(true);
+    if append {
+        options.append(true);
+    } else {
+        options.write(true).truncate(true);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    file.write_all(bytes)?;
+    Ok(())
+}
+
+fn load_events(path: &Path) -> Vec<AgentEvent> {
+    let Ok(raw) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| match serde_json::from_str::<AgentEvent>(line) {
+            Ok(event) => Some(event),
+            Err(err) => {
+                tracing::warn!("Skipping malformed agent event line: {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+static EVENT_HUB: LazyLock<RwLock<Option<Arc<EventHub>>>> = LazyLock::new(|| RwLock::new(None));
+
+/// Resolve the event buffer path from config.
+pub fn resolve_events_path(config: &ObservabilityConfig, workspace_dir: &Path) -> PathBuf {
+    let raw = config.events_path.trim();
+    if raw.is_empty() {
+        return workspace_dir.join(DEFAULT_EVENTS_REL_PATH);
+    }
+    let configured = PathBuf::from(raw);
+    if configured.is_absolute() {
+        configured
+    } else {
+        workspace_dir.join(configured)
+    }
+}
+
+/// Initialize (or disable, when `events_buffer_size` is 0) the event hub.
+pub fn init_from_config(config: &ObservabilityConfig, workspace_dir: &Path) {
+    let hub = (config.events_buffer_size > 0).then(|| {
+        Arc::new(EventHub::new(
+            config.events_buffer_size,
+            Some(resolve_events_path(config, workspace_dir)),
+        ))
+    });
+    *EVENT_HUB.write().unwrap_or_else(|e| e.into_inner()) = hub;
+}
+
+/// The process-wide hub, when initialized.
+pub fn hub() -> Option<Arc<EventHub>> {
+    EVENT_HUB.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Publish an observer event to the hub, tagging it with the current channel.
+pub fn publish(event: &ObserverEvent) {
+    let Some(hub) = hub() else {
+        return;
+    };
+    let (event_type, channel, data) = describe(event);
+    hub.publish(event_type, channel.or_else(current_channel), data);
+}
+
+/// Observer wrapper that also publishes every event to the hub.
+pub struct EventStreamObserver {
+    inner: Box<dyn Observer>,
+}
+
+impl EventStreamObserver {
+    pub fn new(inner: Box<dyn Observer>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Observer for EventStreamObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        self.inner.record_event(event);
+        publish(event);
+    }
+
+    fn record_metric(&self, metric: &ObserverMetric) {
+        self.inner.record_metric(metric);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+
+    // Name and downcasts go to the wrapped backend (e.g. `/metrics` needs
+    // the Prometheus observer).
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.inner.as_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn ring_buffer_is_bounded_and_survives_restart() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("state/events.jsonl");
+
+        let hub = EventHub::new(3, Some(path.clone()));
+        for i in 0..8 {
+            hub.publish("tool_call", None, json!({ "n": i }));
+        }
+        let ids: Vec<u64> = hub.replay_after(0).iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![6, 7, 8]);
+        assert_eq!(hub.replay_after(7).len(), 1);
+        hub.flush();
+
+        let reloaded = EventHub::new(3, Some(path.clone()));
+        assert_eq!(reloaded.last_id(), 8);
+        assert_eq!(reloaded.replay_after(5), hub.replay_after(5));
+        assert_eq!(reloaded.publish("turn_complete", None, json!({})).id, 9);
+        reloaded.flush();
+
+        let lines = fs::read_to_string(&path).unwrap().lines().count();
+        assert!(lines <= 6, "file should be compacted, got {lines} lines");
+    }
+
+    #[test]
+    fn filter_matches_types_and_channels() {
+        let hub = EventHub::new(10, None);
+        let tool = hub.publish("tool_call", Some("telegram".into()), json!({}));
+        let cron = hub.publish("cron_run", None, json!({}));
+
+        let filter = EventFilter::parse(Some("tool_call, cron_run"), None);
+        assert!(filter.matches(&tool) && filter.matches(&cron));
+
+        let filter = EventFilter::parse(None, Some("Telegram"));
+        assert!(filter.matches(&tool));
+        assert!(!filter.matches(&cron));
+
+        let filter = EventFilter::parse(Some("llm_request"), None);
+        assert!(!filter.matches(&tool));
+    }
+
+    #[tokio::test]
+    async fn events_carry_scoped_channel() {
+        let event = ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(5),
+            success: false,
+        };
+        let (event_type, channel, data) = describe(&event);
+        assert_eq!(event_type, "tool_call");
+        assert_eq!(channel, None);
+        assert_eq!(data["success"], false);
+
+        let channel = with_channel("discord".into(), async { current_channel() }).await;
+        assert_eq!(channel.as_deref(), Some("discord"));
+        assert_eq!(current_channel(), None);
+    }
+}
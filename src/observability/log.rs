@@ -50,6 +50,41 @@ impl Observer for LogObserver {
             ObserverEvent::Error { component, message } => {
                 info!(component = %component, error = %message, "error");
             }
+            ObserverEvent::RobotSafety { kind, detail } => {
+                info!(kind = %kind, detail = %detail, "robot.safety");
+            }
+            ObserverEvent::CronRun {
+                job_id,
+                name,
+                success,
+                duration,
+            } => {
+                let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+                info!(
+                    job_id = %job_id,
+                    name = name.as_deref().unwrap_or(""),
+                    success = success,
+                    duration_ms = ms,
+                    "cron.run"
+                );
+            }
+            ObserverEvent::BudgetWarning {
+                period,
+                current_usd,
+                limit_usd,
+                exceeded,
+            } => {
+                info!(
+                    period = %period,
+                    current_usd = current_usd,
+                    limit_usd = limit_usd,
+                    exceeded = exceeded,
+                    "budget.warning"
+                );
+            }
+            ObserverEvent::EstopChange { engaged, detail } => {
+                info!(engaged = engaged, detail = %detail, "estop.change");
+            }
             ObserverEvent::LlmRequest {
                 provider,
                 model,
@@ -1,3 +1,4 @@
+pub mod events;
 pub mod log;
 pub mod multi;
 pub mod noop;
@@ -22,8 +23,16 @@ pub use verbose::VerboseObserver;
 
 use crate::config::ObservabilityConfig;
 
-/// Factory: create the right observer from config
+/// Factory: create the right observer from config.
+///
+/// Every observer also publishes its events to the `/api/events` stream.
 pub fn create_observer(config: &ObservabilityConfig) -> Box<dyn Observer> {
+    Box::new(events::EventStreamObserver::new(create_backend_observer(
+        config,
+    )))
+}
+
+fn create_backend_observer(config: &ObservabilityConfig) -> Box<dyn Observer> {
     match config.backend.as_str() {
         "log" => Box::new(LogObserver::new()),
         "prometheus" => Box::new(PrometheusObserver::new()),
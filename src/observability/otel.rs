@@ -23,6 +23,7 @@ pub struct OtelObserver {
     channel_messages: Counter<u64>,
     heartbeat_ticks: Counter<u64>,
     errors: Counter<u64>,
+    robot_safety_events: Counter<u64>,
     request_latency: Histogram<f64>,
     tokens_used: Counter<u64>,
     active_sessions: Gauge<u64>,
@@ -131,6 +132,11 @@ impl OtelObserver {
             .with_description("Total errors by component")
             .build();
 
+        let robot_safety_events = meter
+            .u64_counter("zeroclaw.robot.safety_events")
+            .with_description("Total robot safety monitor events by kind")
+            .build();
+
         let request_latency = meter
             .f64_histogram("zeroclaw.request.latency")
             .with_description("Request latency in seconds")
@@ -164,6 +170,7 @@ impl OtelObserver {
             channel_messages,
             heartbeat_ticks,
             errors,
+            robot_safety_events,
             request_latency,
             tokens_used,
             active_sessions,
@@ -335,6 +342,75 @@ impl Observer for OtelObserver {
                 self.errors
                     .add(1, &[KeyValue::new("component", component.clone())]);
             }
+            ObserverEvent::RobotSafety { kind, detail } => {
+                let mut span = tracer.build(
+                    opentelemetry::trace::SpanBuilder::from_name("robot.safety")
+                        .with_kind(SpanKind::Internal)
+                        .with_attributes(vec![
+                            KeyValue::new("robot.safety.kind", kind.clone()),
+                            KeyValue::new("robot.safety.detail", detail.clone()),
+                        ]),
+                );
+                span.end();
+
+                self.robot_safety_events
+                    .add(1, &[KeyValue::new("kind", kind.clone())]);
+            }
+            ObserverEvent::CronRun {
+                job_id,
+                name,
+                success,
+                duration,
+            } => {
+                let secs = duration.as_secs_f64();
+                let start_time = SystemTime::now()
+                    .checked_sub(*duration)
+                    .unwrap_or(SystemTime::now());
+                let mut span = tracer.build(
+                    opentelemetry::trace::SpanBuilder::from_name("cron.run")
+                        .with_kind(SpanKind::Internal)
+                        .with_start_time(start_time)
+                        .with_attributes(vec![
+                            KeyValue::new("cron.job_id", job_id.clone()),
+                            KeyValue::new("cron.name", name.clone().unwrap_or_default()),
+                            KeyValue::new("success", *success),
+                            KeyValue::new("duration_s", secs),
+                        ]),
+                );
+                if !success {
+                    span.set_status(Status::error("cron job failed"));
+                }
+                span.end();
+            }
+            ObserverEvent::BudgetWarning {
+                period,
+                current_usd,
+                limit_usd,
+                exceeded,
+            } => {
+                let mut span = tracer.build(
+                    opentelemetry::trace::SpanBuilder::from_name("budget.warning")
+                        .with_kind(SpanKind::Internal)
+                        .with_attributes(vec![
+                            KeyValue::new("budget.period", period.clone()),
+                            KeyValue::new("budget.current_usd", *current_usd),
+                            KeyValue::new("budget.limit_usd", *limit_usd),
+                            KeyValue::new("budget.exceeded", *exceeded),
+                        ]),
+                );
+                span.end();
+            }
+            ObserverEvent::EstopChange { engaged, detail } => {
+                let mut span = tracer.build(
+                    opentelemetry::trace::SpanBuilder::from_name("estop.change")
+                        .with_kind(SpanKind::Internal)
+                        .with_attributes(vec![
+                            KeyValue::new("estop.engaged", *engaged),
+                            KeyValue::new("estop.detail", detail.clone()),
+                        ]),
+                );
+                span.end();
+            }
         }
     }
 
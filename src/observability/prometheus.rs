@@ -16,6 +16,10 @@ pub struct PrometheusObserver {
     channel_messages: IntCounterVec,
     heartbeat_ticks: prometheus::IntCounter,
     errors: IntCounterVec,
+    robot_safety_events: IntCounterVec,
+    cron_runs: IntCounterVec,
+    budget_warnings: IntCounterVec,
+    estop_changes: IntCounterVec,
 
     // Histograms
     agent_duration: HistogramVec,
@@ -81,6 +85,39 @@ impl PrometheusObserver {
         )
         .expect("valid metric");
 
+        let robot_safety_events = IntCounterVec::new(
+            prometheus::Opts::new(
+                "zeroclaw_robot_safety_events_total",
+                "Total robot safety monitor events by kind",
+            ),
+            &["kind"],
+        )
+        .expect("valid metric");
+
+        let cron_runs = IntCounterVec::new(
+            prometheus::Opts::new("zeroclaw_cron_runs_total", "Total scheduled cron job runs"),
+            &["success"],
+        )
+        .expect("valid metric");
+
+        let budget_warnings = IntCounterVec::new(
+            prometheus::Opts::new(
+                "zeroclaw_budget_warnings_total",
+                "Total cost budget warnings by period",
+            ),
+            &["period", "exceeded"],
+        )
+        .expect("valid metric");
+
+        let estop_changes = IntCounterVec::new(
+            prometheus::Opts::new(
+                "zeroclaw_estop_changes_total",
+                "Total emergency stop state changes",
+            ),
+            &["engaged"],
+        )
+        .expect("valid metric");
+
         let agent_duration = HistogramVec::new(
             HistogramOpts::new(
                 "zeroclaw_agent_duration_seconds",
@@ -139,6 +176,12 @@ impl PrometheusObserver {
         registry.register(Box::new(channel_messages.clone())).ok();
         registry.register(Box::new(heartbeat_ticks.clone())).ok();
         registry.register(Box::new(errors.clone())).ok();
+        registry
+            .register(Box::new(robot_safety_events.clone()))
+            .ok();
+        registry.register(Box::new(cron_runs.clone())).ok();
+        registry.register(Box::new(budget_warnings.clone())).ok();
+        registry.register(Box::new(estop_changes.clone())).ok();
         registry.register(Box::new(agent_duration.clone())).ok();
         registry.register(Box::new(tool_duration.clone())).ok();
         registry.register(Box::new(request_latency.clone())).ok();
@@ -156,6 +199,10 @@ impl PrometheusObserver {
             channel_messages,
             heartbeat_ticks,
             errors,
+            robot_safety_events,
+            cron_runs,
+            budget_warnings,
+            estop_changes,
             agent_duration,
             tool_duration,
             request_latency,
@@ -251,6 +298,25 @@ impl Observer for PrometheusObserver {
             } => {
                 self.errors.with_label_values(&[component]).inc();
             }
+            ObserverEvent::RobotSafety { kind, detail: _ } => {
+                self.robot_safety_events.with_label_values(&[kind]).inc();
+            }
+            ObserverEvent::CronRun { success, .. } => {
+                let success = success.to_string();
+                self.cron_runs.with_label_values(&[&success]).inc();
+            }
+            ObserverEvent::BudgetWarning {
+                period, exceeded, ..
+            } => {
+                let exceeded = exceeded.to_string();
+                self.budget_warnings
+                    .with_label_values(&[period, &exceeded])
+                    .inc();
+            }
+            ObserverEvent::EstopChange { engaged, .. } => {
+                let engaged = engaged.to_string();
+                self.estop_changes.with_label_values(&[&engaged]).inc();
+            }
         }
     }
 
@@ -65,6 +65,36 @@ pub enum ObserverEvent {
         /// Human-readable error description. Must not contain secrets or tokens.
         message: String,
     },
+    /// The robot kit's safety monitor reported an event.
+    RobotSafety {
+        /// Event kind (e.g., `"emergency_stop"`, `"obstacle_detected"`).
+        kind: String,
+        /// Human-readable detail such as the stop reason or obstacle distance.
+        detail: String,
+    },
+    /// A scheduled cron job finished (after retries).
+    CronRun {
+        job_id: String,
+        name: Option<String>,
+        success: bool,
+        duration: Duration,
+    },
+    /// Spending crossed the cost warning threshold or a budget limit.
+    BudgetWarning {
+        /// `"day"` or `"month"`.
+        period: String,
+        current_usd: f64,
+        limit_usd: f64,
+        /// The limit itself was reached, not just the warning threshold.
+        exceeded: bool,
+    },
+    /// The emergency stop state was engaged, changed or released.
+    EstopChange {
+        /// Whether any stop level is still engaged.
+        engaged: bool,
+        /// Active levels (e.g., `"kill_all"`, `"tools: shell"`), or `"released"`.
+        detail: String,
+    },
 }
 
 /// Numeric metrics emitted by the agent runtime.
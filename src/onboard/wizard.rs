@@ -127,6 +127,8 @@ pub async fn run_wizard(force: bool) -> Result<Config> {
         workspace_base: None,
         workspace_dir: workspace_dir.clone(),
         config_path: config_path.clone(),
+        tenant_context: None,
+        tenant: None,
         api_key: if api_key.is_empty() {
             None
         } else {
@@ -163,11 +165,14 @@ pub async fn run_wizard(force: bool) -> Result<Config> {
         identity: crate::config::IdentityConfig::default(),
         cost: crate::config::CostConfig::default(),
         peripherals: crate::config::PeripheralsConfig::default(),
+        robot: crate::config::RobotConfig::default(),
+        containers: crate::config::ContainersConfig::default(),
         agents: std::collections::HashMap::new(),
         hooks: crate::config::HooksConfig::default(),
         hardware: hardware_config,
         query_classification: crate::config::QueryClassificationConfig::default(),
         transcription: crate::config::TranscriptionConfig::default(),
+        tts: crate::config::TtsConfig::default(),
     };
 
     println!(
@@ -476,6 +481,8 @@ async fn run_quick_setup_with_home(
         workspace_base: None,
         workspace_dir: workspace_dir.clone(),
         config_path: config_path.clone(),
+        tenant_context: None,
+        tenant: None,
         api_key: credential_override.map(|c| {
             let mut s = String::with_capacity(c.len());
             s.push_str(c);
@@ -512,11 +519,14 @@ async fn run_quick_setup_with_home(
         identity: crate::config::IdentityConfig::default(),
         cost: crate::config::CostConfig::default(),
         peripherals: crate::config::PeripheralsConfig::default(),
+        robot: crate::config::RobotConfig::default(),
+        containers: crate::config::ContainersConfig::default(),
         agents: std::collections::HashMap::new(),
         hooks: crate::config::HooksConfig::default(),
         hardware: crate::config::HardwareConfig::default(),
         query_classification: crate::config::QueryClassificationConfig::default(),
         transcription: crate::config::TranscriptionConfig::default(),
+        tts: crate::config::TtsConfig::default(),
     };
 
     config.save().await?;
@@ -2752,7 +2762,10 @@ fn setup_tool_mode() -> Result<(ComposioConfig, SecretsConfig)> {
         .default(true)
         .interact()?;
 
-    let secrets_config = SecretsConfig { encrypt };
+    let secrets_config = SecretsConfig {
+        encrypt,
+        ..SecretsConfig::default()
+    };
 
     if encrypt {
         println!(
@@ -0,0 +1,390 @@
+//! I2C, SPI, PWM and ADC operations for serial peripherals.
+//!
+//! Each operation is one agent tool and one command of the newline-JSON serial
+//! protocol. Tool arguments are checked against the buses, pins and channels
+//! the board reports from `capabilities`, then sent in wire form: byte
+//! payloads as hex strings, PWM duty as an integer per mille.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// Largest I2C/SPI payload the firmware accepts, in bytes.
+pub const MAX_TRANSFER_BYTES: usize = 32;
+
+/// Valid 7-bit I2C addresses (0x00-0x07 and 0x78-0x7F are reserved).
+const I2C_ADDRESSES: std::ops::RangeInclusive<u64> = 0x08..=0x77;
+
+/// What a board reports from the `capabilities` command. Fields missing from
+/// older firmware are empty, which disables the matching operations.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardCapabilities {
+    #[serde(default)]
+    pub gpio: Vec<u64>,
+    #[serde(default)]
+    pub led_pin: Option<u64>,
+    /// I2C bus numbers
+    #[serde(default)]
+    pub i2c: Vec<u64>,
+    /// SPI bus numbers
+    #[serde(default)]
+    pub spi: Vec<u64>,
+    /// Pins with a PWM output
+    #[serde(default)]
+    pub pwm: Vec<u64>,
+    /// ADC channel numbers
+    #[serde(default)]
+    pub adc: Vec<u64>,
+    #[serde(default)]
+    pub adc_bits: Option<u32>,
+    #[serde(default)]
+    pub adc_vref_mv: Option<u32>,
+}
+
+impl BoardCapabilities {
+    pub fn parse(raw: &str) -> Result<Self> {
+        serde_json::from_str(raw).context("Invalid capabilities response")
+    }
+
+    /// One-line summary for the `hardware_capabilities` tool.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("gpio {:?}", self.gpio)];
+        if let Some(led) = self.led_pin {
+            parts.push(format!("led_pin {led}"));
+        }
+        for (name, list) in [
+            ("i2c", &self.i2c),
+            ("spi", &self.spi),
+            ("pwm", &self.pwm),
+            ("adc", &self.adc),
+        ] {
+            if !list.is_empty() {
+                parts.push(format!("{name} {list:?}"));
+            }
+        }
+        parts.join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusOp {
+    I2cScan,
+    I2cRead,
+    I2cWrite,
+    SpiTransfer,
+    PwmSet,
+    AdcRead,
+}
+
+impl BusOp {
+    pub const ALL: [BusOp; 6] = [
+        BusOp::I2cScan,
+        BusOp::I2cRead,
+        BusOp::I2cWrite,
+        BusOp::SpiTransfer,
+        BusOp::PwmSet,
+        BusOp::AdcRead,
+    ];
+
+    /// Tool name, also the serial protocol command.
+    pub fn name(self) -> &'static str {
+        match self {
+            BusOp::I2cScan => "i2c_scan",
+            BusOp::I2cRead => "i2c_read",
+            BusOp::I2cWrite => "i2c_write",
+            BusOp::SpiTransfer => "spi_transfer",
+            BusOp::PwmSet => "pwm_set",
+            BusOp::AdcRead => "adc_read",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            BusOp::I2cScan => "List the addresses of devices that respond on an I2C bus of a connected peripheral",
+            BusOp::I2cRead => "Read bytes from an I2C device on a connected peripheral, optionally starting at a register",
+            BusOp::I2cWrite => "Write bytes to an I2C device on a connected peripheral, optionally starting at a register",
+            BusOp::SpiTransfer => "Clock bytes out on an SPI bus of a connected peripheral and return the bytes clocked in",
+            BusOp::PwmSet => "Set the PWM duty cycle of a pin on a connected peripheral. For a servo use freq_hz 50: duty 5% is about 1 ms (0°), 7.5% centre, 10% about 2 ms (180°)",
+            BusOp::AdcRead => "Read an analog input (ADC channel) on a connected peripheral, as a raw value and millivolts",
+        }
+    }
+
+    pub fn parameters_schema(self) -> Value {
+        let bus = json!({ "type": "integer", "description": "Bus number (see hardware_capabilities)", "default": 0 });
+        let addr =
+            json!({ "type": "integer", "description": "7-bit device address, e.g. 104 (0x68)" });
+        let reg = json!({ "type": "integer", "description": "Register to start at (0-255)" });
+        let data = json!({
+            "type": "array",
+            "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+            "maxItems": MAX_TRANSFER_BYTES,
+            "description": "Bytes to send"
+        });
+        match self {
+            BusOp::I2cScan => json!({
+                "type": "object",
+                "properties": { "bus": bus }
+            }),
+            BusOp::I2cRead => json!({
+                "type": "object",
+                "properties": {
+                    "bus": bus,
+                    "addr": addr,
+                    "reg": reg,
+                    "len": { "type": "integer", "minimum": 1, "maximum": MAX_TRANSFER_BYTES, "description": "Number of bytes to read" }
+                },
+                "required": ["addr", "len"]
+            }),
+            BusOp::I2cWrite => json!({
+                "type": "object",
+                "properties": { "bus": bus, "addr": addr, "reg": reg, "data": data },
+                "required": ["addr", "data"]
+            }),
+            BusOp::SpiTransfer => json!({
+                "type": "object",
+                "properties": { "bus": bus, "data": data },
+                "required": ["data"]
+            }),
+            BusOp::PwmSet => json!({
+                "type": "object",
+                "properties": {
+                    "pin": { "type": "integer", "description": "PWM-capable pin" },
+                    "duty": { "type": "number", "minimum": 0, "maximum": 100, "description": "Duty cycle in percent" },
+                    "freq_hz": { "type": "integer", "description": "PWM frequency; omit for the board default, 50 for servos" }
+                },
+                "required": ["pin", "duty"]
+            }),
+            BusOp::AdcRead => json!({
+                "type": "object",
+                "properties": {
+                    "channel": { "type": "integer", "description": "ADC channel, e.g. 0 for A0" }
+                },
+                "required": ["channel"]
+            }),
+        }
+    }
+
+    /// Validate tool arguments against the board and build the command args.
+    pub fn wire_args(self, args: &Value, caps: &BoardCapabilities) -> Result<Value> {
+        let mut wire = Map::new();
+        match self {
+            BusOp::I2cScan | BusOp::I2cRead | BusOp::I2cWrite => {
+                wire.insert("bus".into(), json!(bus(args, &caps.i2c, "I2C")?));
+                if self != BusOp::I2cScan {
+                    let addr = int(args, "addr")?;
+                    if !I2C_ADDRESSES.contains(&addr) {
+                        bail!("I2C address {addr:#04x} is outside 0x08-0x77");
+                    }
+                    wire.insert("addr".into(), json!(addr));
+                    if let Some(reg) = args.get("reg").filter(|v| !v.is_null()) {
+                        let reg = reg
+                            .as_u64()
+                            .filter(|r| *r <= 0xFF)
+                            .context("'reg' must be 0-255")?;
+                        wire.insert("reg".into(), json!(reg));
+                    }
+                }
+                if self == BusOp::I2cRead {
+                    let len = int(args, "len")?;
+                    if len == 0 || len > MAX_TRANSFER_BYTES as u64 {
+                        bail!("'len' must be 1-{MAX_TRANSFER_BYTES}");
+                    }
+                    wire.insert("len".into(), json!(len));
+                }
+                if self == BusOp::I2cWrite {
+                    wire.insert("data".into(), json!(hex::encode(bytes(args)?)));
+                }
+            }
+            BusOp::SpiTransfer => {
+                wire.insert("bus".into(), json!(bus(args, &caps.spi, "SPI")?));
+                wire.insert("data".into(), json!(hex::encode(bytes(args)?)));
+            }
+            BusOp::PwmSet => {
+                let pin = int(args, "pin")?;
+                if !caps.pwm.contains(&pin) {
+                    bail!("Pin {pin} has no PWM output (PWM pins: {:?})", caps.pwm);
+                }
+                let duty = args
+                    .get("duty")
+                    .and_then(Value::as_f64)
+                    .filter(|d| (0.0..=100.0).contains(d))
+                    .context("'duty' must be a percentage from 0 to 100")?;
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let duty_permille = (duty * 10.0).round() as u64;
+                wire.insert("pin".into(), json!(pin));
+                wire.insert("duty_permille".into(), json!(duty_permille));
+                if let Some(freq) = args.get("freq_hz").filter(|v| !v.is_null()) {
+                    let freq = freq
+                        .as_u64()
+                        .filter(|f| (1..=100_000).contains(f))
+                        .context("'freq_hz' must be 1-100000")?;
+                    wire.insert("freq_hz".into(), json!(freq));
+                }
+            }
+            BusOp::AdcRead => {
+                let channel = int(args, "channel")?;
+                if !caps.adc.contains(&channel) {
+                    bail!(
+                        "ADC channel {channel} not available (channels: {:?})",
+                        caps.adc
+                    );
+                }
+                wire.insert("channel".into(), json!(channel));
+            }
+        }
+        Ok(Value::Object(wire))
+    }
+
+    /// Turn the firmware's result string into output for the agent.
+    pub fn format_result(self, result: &str, caps: &BoardCapabilities) -> Result<String> {
+        let result = result.trim();
+        Ok(match self {
+            BusOp::I2cScan => {
+                let found = result
+                    .split(',')
+                    .filter(|a| !a.trim().is_empty())
+                    .map(|a| {
+                        a.trim()
+                            .parse::<u8>()
+                            .map(|a| format!("{a:#04x}"))
+                            .with_context(|| format!("Invalid I2C address from firmware: {a}"))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                if found.is_empty() {
+                    "No I2C devices found".into()
+                } else {
+                    format!("I2C devices at {}", found.join(", "))
+                }
+            }
+            BusOp::I2cRead | BusOp::SpiTransfer => {
+                let data = hex::decode(result).context("Invalid hex bytes from firmware")?;
+                format!("{data:?} (hex {result})")
+            }
+            BusOp::AdcRead => {
+                let raw: u32 = result
+                    .parse()
+                    .with_context(|| format!("Invalid ADC reading from firmware: {result}"))?;
+                match (caps.adc_bits, caps.adc_vref_mv) {
+                    (Some(bits), Some(vref)) if bits > 0 && bits < 32 => {
+                        let max = (1u64 << bits) - 1;
+                        let mv = u64::from(raw) * u64::from(vref) / max;
+                        format!("raw {raw} of {max} ({mv} mV)")
+                    }
+                    _ => format!("raw {raw}"),
+                }
+            }
+            BusOp::I2cWrite | BusOp::PwmSet => result.to_string(),
+        })
+    }
+}
+
+fn int(args: &Value, key: &str) -> Result<u64> {
+    args.get(key)
+        .and_then(Value::as_u64)
+        .with_context(|| format!("Missing '{key}' parameter"))
+}
+
+/// The requested bus, defaulting to the first one, if the board has it.
+fn bus(args: &Value, available: &[u64], kind: &str) -> Result<u64> {
+    let bus = match args.get("bus").filter(|v| !v.is_null()) {
+        Some(v) => v.as_u64().context("'bus' must be a bus number")?,
+        None => 0,
+    };
+    if !available.contains(&bus) {
+        bail!("Board has no {kind} bus {bus} (buses: {available:?})");
+    }
+    Ok(bus)
+}
+
+fn bytes(args: &Value) -> Result<Vec<u8>> {
+    let data = args
+        .get("data")
+        .and_then(Value::as_array)
+        .context("Missing 'data' parameter")?;
+    if data.is_empty() || data.len() > MAX_TRANSFER_BYTES {
+        bail!("'data' must have 1-{MAX_TRANSFER_BYTES} bytes");
+    }
+    data.iter()
+        .map(|b| {
+            b.as_u64()
+                .and_then(|b| u8::try_from(b).ok())
+                .context("'data' bytes must be 0-255")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uno() -> BoardCapabilities {
+        BoardCapabilities::parse(
+            r#"{"gpio":[0,1,2,3,4,5,6,7,8,9,10,11,12,13],"led_pin":13,"i2c":[0],"spi":[0],
+                "pwm":[3,5,6,9,10,11],"adc":[0,1,2,3,4,5],"adc_bits":10,"adc_vref_mv":5000}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn builds_wire_args_for_valid_requests() {
+        let caps = uno();
+        assert_eq!(
+            BusOp::I2cRead
+                .wire_args(&json!({ "addr": 0x68, "reg": 0x75, "len": 1 }), &caps)
+                .unwrap(),
+            json!({ "bus": 0, "addr": 0x68, "reg": 0x75, "len": 1 })
+        );
+        assert_eq!(
+            BusOp::SpiTransfer
+                .wire_args(&json!({ "data": [0x9f, 0, 255] }), &caps)
+                .unwrap(),
+            json!({ "bus": 0, "data": "9f00ff" })
+        );
+        assert_eq!(
+            BusOp::PwmSet
+                .wire_args(&json!({ "pin": 9, "duty": 7.5, "freq_hz": 50 }), &caps)
+                .unwrap(),
+            json!({ "pin": 9, "duty_permille": 75, "freq_hz": 50 })
+        );
+    }
+
+    #[test]
+    fn rejects_buses_pins_and_values_the_board_lacks() {
+        let caps = uno();
+        let rejected = [
+            (BusOp::I2cScan, json!({ "bus": 1 })),
+            (BusOp::I2cRead, json!({ "addr": 0x78, "len": 1 })),
+            (BusOp::I2cRead, json!({ "addr": 0x68, "len": 33 })),
+            (BusOp::I2cWrite, json!({ "addr": 0x68, "data": [256] })),
+            (BusOp::SpiTransfer, json!({ "data": [] })),
+            (BusOp::PwmSet, json!({ "pin": 13, "duty": 50 })),
+            (BusOp::PwmSet, json!({ "pin": 9, "duty": 120 })),
+            (BusOp::AdcRead, json!({ "channel": 6 })),
+        ];
+        for (op, args) in rejected {
+            assert!(op.wire_args(&args, &caps).is_err(), "{op:?} {args}");
+        }
+
+        // Older firmware reports only GPIO.
+        let gpio_only = BoardCapabilities::parse(r#"{"gpio":[13],"led_pin":13}"#).unwrap();
+        assert!(BusOp::I2cScan.wire_args(&json!({}), &gpio_only).is_err());
+    }
+
+    #[test]
+    fn formats_firmware_results() {
+        let caps = uno();
+        assert_eq!(
+            BusOp::I2cScan.format_result("60,104", &caps).unwrap(),
+            "I2C devices at 0x3c, 0x68"
+        );
+        assert_eq!(
+            BusOp::I2cRead.format_result("0a1b", &caps).unwrap(),
+            "[10, 27] (hex 0a1b)"
+        );
+        assert_eq!(
+            BusOp::AdcRead.format_result("1023", &caps).unwrap(),
+            "raw 1023 of 1023 (5000 mV)"
+        );
+        assert!(BusOp::I2cRead.format_result("zz", &caps).is_err());
+    }
+}
@@ -1,12 +1,14 @@
-//! Hardware capabilities tool — Phase C: query device for reported GPIO pins.
+//! Hardware capabilities tool — Phase C: query device for reported GPIO pins,
+//! buses and ADC channels.
 
+use super::bus::BoardCapabilities;
 use super::serial::SerialTransport;
 use crate::tools::traits::{Tool, ToolResult};
 use async_trait::async_trait;
 use serde_json::json;
 use std::sync::Arc;
 
-/// Tool: query device capabilities (GPIO pins, LED pin) from firmware.
+/// Tool: query device capabilities (GPIO pins, LED pin, buses) from firmware.
 pub struct HardwareCapabilitiesTool {
     /// (board_name, transport) for each serial board.
     boards: Vec<(String, Arc<SerialTransport>)>,
@@ -25,7 +27,7 @@ impl Tool for HardwareCapabilitiesTool {
     }
 
     fn description(&self) -> &str {
-        "Query connected hardware for reported GPIO pins and LED pin. Use when: user asks what pins are available."
+        "Query connected hardware for reported GPIO pins, LED pin, I2C/SPI buses, PWM pins and ADC channels. Use when: user asks what pins or buses are available, or before using i2c_*, spi_transfer, pwm_set or adc_read."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -53,15 +55,8 @@ impl Tool for HardwareCapabilitiesTool {
             match transport.capabilities().await {
                 Ok(result) => {
                     let output = if result.success {
-                        if let Ok(parsed) =
-                            serde_json::from_str::<serde_json::Value>(&result.output)
-                        {
-                            format!(
-                                "{}: gpio {:?}, led_pin {:?}",
-                                board_name,
-                                parsed.get("gpio").unwrap_or(&json!([])),
-                                parsed.get("led_pin").unwrap_or(&json!(null))
-                            )
+                        if let Ok(parsed) = BoardCapabilities::parse(&result.output) {
+                            format!("{}: {}", board_name, parsed.summary())
                         } else {
                             format!("{}: {}", board_name, result.output)
                         }
@@ -0,0 +1,398 @@
+//! Hardware event subscriptions — boards push pin and sensor events that
+//! trigger agent turns.
+//!
+//! Each `[[peripherals.subscriptions]]` entry is sent to its board with the
+//! `subscribe` command, e.g.
+//! `{"cmd":"subscribe","args":{"sub":"door","kind":"gpio_edge","pin":2,"edge":"rising"}}`.
+//! The board then pushes events tagged with the subscription name (see
+//! [`super::serial`]). Events pass the subscription's debounce and rate limit
+//! before they are handed to the `hardware` channel.
+
+use super::bus::BoardCapabilities;
+use super::serial::BoardEvent;
+use crate::config::{
+    GpioEdge, HardwareEventKind, HardwareSubscriptionConfig, PeripheralBoardConfig,
+    PeripheralsConfig,
+};
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+/// Shortest `adc_sample` interval a board is asked for.
+const MIN_SAMPLE_INTERVAL_MS: u64 = 100;
+
+/// A subscription event that passed its debounce and rate limit.
+#[derive(Debug, Clone)]
+pub struct HardwareEvent {
+    pub subscription: HardwareSubscriptionConfig,
+    pub data: Value,
+    /// Text for the agent.
+    pub message: String,
+}
+
+fn kind_name(kind: HardwareEventKind) -> &'static str {
+    match kind {
+        HardwareEventKind::GpioEdge => "gpio_edge",
+        HardwareEventKind::AdcThreshold => "adc_threshold",
+        HardwareEventKind::AdcSample => "adc_sample",
+    }
+}
+
+/// Validate a subscription against the board and build the `subscribe` args.
+pub fn subscribe_args(sub: &HardwareSubscriptionConfig, caps: &BoardCapabilities) -> Result<Value> {
+    let mut args = Map::new();
+    args.insert("sub".into(), json!(sub.name));
+    args.insert("kind".into(), json!(kind_name(sub.kind)));
+    match sub.kind {
+        HardwareEventKind::GpioEdge => {
+            let pin = sub.pin.context("gpio_edge subscriptions need a 'pin'")?;
+            if !caps.gpio.contains(&pin) {
+                bail!("Pin {pin} is not a GPIO pin (pins: {:?})", caps.gpio);
+            }
+            let edge = match sub.edge {
+                GpioEdge::Rising => "rising",
+                GpioEdge::Falling => "falling",
+                GpioEdge::Both => "both",
+            };
+            args.insert("pin".into(), json!(pin));
+            args.insert("edge".into(), json!(edge));
+        }
+        HardwareEventKind::AdcThreshold | HardwareEventKind::AdcSample => {
+            let channel = sub.channel.context("ADC subscriptions need a 'channel'")?;
+            if !caps.adc.contains(&channel) {
+                bail!(
+                    "ADC channel {channel} not available (channels: {:?})",
+                    caps.adc
+                );
+            }
+            args.insert("channel".into(), json!(channel));
+            if sub.kind == HardwareEventKind::AdcThreshold {
+                if sub.above.is_none() && sub.below.is_none() {
+                    bail!("adc_threshold subscriptions need 'above' or 'below'");
+                }
+                if let Some(above) = sub.above {
+                    args.insert("above".into(), json!(above));
+                }
+                if let Some(below) = sub.below {
+                    args.insert("below".into(), json!(below));
+                }
+            } else {
+                let interval = sub
+                    .interval_ms
+                    .context("adc_sample subscriptions need 'interval_ms'")?;
+                if interval < MIN_SAMPLE_INTERVAL_MS {
+                    bail!("'interval_ms' must be at least {MIN_SAMPLE_INTERVAL_MS}");
+                }
+                args.insert("interval_ms".into(), json!(interval));
+            }
+        }
+    }
+    Ok(Value::Object(args))
+}
+
+/// Text for the agent: the subscription's `message` with `{value}` filled in,
+/// or a description of the event.
+pub fn describe(sub: &HardwareSubscriptionConfig, data: &Value) -> String {
+    let value = data["value"].to_string();
+    if let Some(message) = &sub.message {
+        return message.replace("{value}", &value);
+    }
+    let what = match sub.kind {
+        HardwareEventKind::GpioEdge => format!(
+            "pin {} went {}",
+            data["pin"],
+            if data["value"].as_u64() == Some(0) {
+                "low"
+            } else {
+                "high"
+            }
+        ),
+        HardwareEventKind::AdcThreshold => {
+            let (crossed, threshold) = if data["crossed"] == "below" {
+                ("fell below", sub.below)
+            } else {
+                ("rose above", sub.above)
+            };
+            format!(
+                "ADC channel {} {crossed} {} (raw {value})",
+                data["channel"],
+                threshold.unwrap_or_default()
+            )
+        }
+        HardwareEventKind::AdcSample => format!("ADC channel {} reads {value}", data["channel"]),
+    };
+    format!("Hardware event '{}' on {}: {what}", sub.name, sub.board)
+}
+
+/// Debounce and rate limit for one subscription.
+struct Gate {
+    debounce: Duration,
+    min_interval: Duration,
+    /// Latest event waiting out the debounce, with when it may be delivered.
+    pending: Option<(Value, Instant)>,
+    last_delivered: Option<Instant>,
+}
+
+impl Gate {
+    fn new(sub: &HardwareSubscriptionConfig) -> Self {
+        Self {
+            debounce: Duration::from_millis(sub.debounce_ms),
+            min_interval: Duration::from_secs(sub.min_interval_secs),
+            pending: None,
+            last_delivered: None,
+        }
+    }
+
+    /// Record an event; returns it if it can be delivered right away.
+    fn offer(&mut self, data: Value, now: Instant) -> Option<Value> {
+        if self.debounce.is_zero() {
+            return self.admit(data, now);
+        }
+        self.pending = Some((data, now + self.debounce));
+        None
+    }
+
+    /// The debounced event, once no newer event arrived for the debounce period.
+    fn poll(&mut self, now: Instant) -> Option<Value> {
+        match self.pending.take() {
+            Some((data, at)) if at <= now => self.admit(data, now),
+            pending => {
+                self.pending = pending;
+                None
+            }
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.pending.as_ref().map(|(_, at)| *at)
+    }
+
+    fn admit(&mut self, data: Value, now: Instant) -> Option<Value> {
+        if let Some(last) = self.last_delivered {
+            if now.duration_since(last) < self.min_interval {
+                return None;
+            }
+        }
+        self.last_delivered = Some(now);
+        Some(data)
+    }
+}
+
+/// Subscribe on every board with subscriptions and send the events that pass
+/// their gates to `tx`. Runs until a board connection drops or `tx` closes.
+pub async fn run(config: &PeripheralsConfig, tx: mpsc::Sender<HardwareEvent>) -> Result<()> {
+    for sub in &config.subscriptions {
+        if !config.boards.iter().any(|b| b.board == sub.board) {
+            bail!(
+                "Subscription '{}' names unknown board {}",
+                sub.name,
+                sub.board
+            );
+        }
+    }
+
+    let (board_tx, mut board_rx) = mpsc::channel(64);
+    let mut forwarders = JoinSet::new();
+    let mut gates = HashMap::new();
+    // Held so the board connections stay open.
+    let mut connections = Vec::new();
+    for board in &config.boards {
+        let subs: Vec<_> = config
+            .subscriptions
+            .iter()
+            .filter(|s| s.board == board.board)
+            .collect();
+        if subs.is_empty() {
+            continue;
+        }
+        let peripheral = super::connect_protocol_board(board).await?;
+        let transport = peripheral.transport();
+        let caps = transport.board_capabilities().await?;
+        let events = transport.events();
+        for sub in subs {
+            let args = subscribe_args(sub, &caps)
+                .with_context(|| format!("Invalid subscription '{}'", sub.name))?;
+            let result = transport.request("subscribe", args).await?;
+            if !result.success {
+                bail!(
+                    "Board {} rejected subscription '{}': {}",
+                    board.board,
+                    sub.name,
+                    result.error.as_deref().unwrap_or("unknown error")
+                );
+            }
+            gates.insert(
+                (board.board.clone(), sub.name.clone()),
+                (sub.clone(), Gate::new(sub)),
+            );
+        }
+        forwarders.spawn(forward(board.clone(), events, board_tx.clone()));
+        connections.push(peripheral);
+        tracing::info!(board = %board.board, "Hardware event subscriptions active");
+    }
+    drop(board_tx);
+    if gates.is_empty() {
+        return Ok(());
+    }
+
+    loop {
+        let deadline = gates.values().filter_map(|(_, gate)| gate.deadline()).min();
+        tokio::select! {
+            received = board_rx.recv() => {
+                let Some((board, event)) = received else {
+                    bail!("Hardware event connections closed");
+                };
+                let Some(name) = event.sub else {
+                    tracing::debug!(board = %board, event = %event.event, "Ignoring untagged board event");
+                    continue;
+                };
+                let Some((sub, gate)) = gates.get_mut(&(board, name)) else {
+                    continue;
+                };
+                if let Some(data) = gate.offer(event.data, Instant::now()) {
+                    deliver(&tx, sub, data).await?;
+                }
+            }
+            () = sleep_until(deadline) => {
+                let now = Instant::now();
+                for (sub, gate) in gates.values_mut() {
+                    if let Some(data) = gate.poll(now) {
+                        deliver(&tx, sub, data).await?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn deliver(
+    tx: &mpsc::Sender<HardwareEvent>,
+    sub: &HardwareSubscriptionConfig,
+    data: Value,
+) -> Result<()> {
+    let event = HardwareEvent {
+        message: describe(sub, &data),
+        subscription: sub.clone(),
+        data,
+    };
+    tx.send(event)
+        .await
+        .map_err(|_| anyhow::anyhow!("Hardware event receiver closed"))
+}
+
+async fn forward(
+    board: PeripheralBoardConfig,
+    mut events: broadcast::Receiver<BoardEvent>,
+    tx: mpsc::Sender<(String, BoardEvent)>,
+) {
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                if tx.send((board.board.clone(), event)).await.is_err() {
+                    break;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                tracing::warn!(board = %board.board, "Dropped {missed} hardware events");
+            }
+            Err(broadcast::error::RecvError::Closed) => {
+                tracing::warn!(board = %board.board, "Board connection closed");
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HardwareEventAction;
+
+    fn subscription(kind: HardwareEventKind) -> HardwareSubscriptionConfig {
+        HardwareSubscriptionConfig {
+            name: "door".into(),
+            board: "uno".into(),
+            kind,
+            pin: Some(2),
+            edge: GpioEdge::Rising,
+            channel: Some(0),
+            above: Some(600),
+            below: None,
+            interval_ms: None,
+            message: None,
+            action: HardwareEventAction::Channel,
+            debounce_ms: 0,
+            min_interval_secs: 0,
+        }
+    }
+
+    #[test]
+    fn subscribe_args_are_checked_against_capabilities() {
+        let caps = BoardCapabilities {
+            gpio: vec![2, 13],
+            adc: vec![0],
+            ..BoardCapabilities::default()
+        };
+        let args = subscribe_args(&subscription(HardwareEventKind::GpioEdge), &caps).unwrap();
+        assert_eq!(
+            args,
+            json!({"sub": "door", "kind": "gpio_edge", "pin": 2, "edge": "rising"})
+        );
+        let args = subscribe_args(&subscription(HardwareEventKind::AdcThreshold), &caps).unwrap();
+        assert_eq!(args["above"], 600);
+
+        let mut sub = subscription(HardwareEventKind::GpioEdge);
+        sub.pin = Some(7);
+        assert!(subscribe_args(&sub, &caps).is_err());
+        let mut sub = subscription(HardwareEventKind::AdcSample);
+        sub.interval_ms = Some(10);
+        assert!(subscribe_args(&sub, &caps).is_err());
+    }
+
+    #[test]
+    fn describes_events_or_fills_message() {
+        let mut sub = subscription(HardwareEventKind::AdcThreshold);
+        let data = json!({"channel": 0, "value": 612, "crossed": "above"});
+        assert_eq!(
+            describe(&sub, &data),
+            "Hardware event 'door' on uno: ADC channel 0 rose above 600 (raw 612)"
+        );
+        sub.message = Some("Temperature high (raw {value})".into());
+        assert_eq!(describe(&sub, &data), "Temperature high (raw 612)");
+    }
+
+    #[test]
+    fn gate_debounces_and_rate_limits() {
+        let mut sub = subscription(HardwareEventKind::GpioEdge);
+        sub.debounce_ms = 50;
+        sub.min_interval_secs = 10;
+        let mut gate = Gate::new(&sub);
+        let start = Instant::now();
+
+        assert_eq!(gate.offer(json!(1), start), None);
+        assert_eq!(
+            gate.offer(json!(2), start + Duration::from_millis(30)),
+            None
+        );
+        assert_eq!(gate.poll(start + Duration::from_millis(60)), None);
+        assert_eq!(gate.poll(start + Duration::from_millis(80)), Some(json!(2)));
+
+        // Within the rate limit the next event is dropped.
+        gate.offer(json!(3), start + Duration::from_secs(1));
+        assert_eq!(gate.poll(start + Duration::from_secs(2)), None);
+        assert_eq!(gate.deadline(), None);
+        gate.offer(json!(4), start + Duration::from_secs(11));
+        assert_eq!(gate.poll(start + Duration::from_secs(12)), Some(json!(4)));
+    }
+}
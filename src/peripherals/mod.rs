@@ -3,18 +3,18 @@
 //! Peripherals extend the agent with physical capabilities. See
 //! `docs/hardware-peripherals-design.md` for the full design.
 
-pub mod traits;
-
-#[cfg(feature = "hardware")]
+pub mod bus;
+pub mod capabilities_tool;
+pub mod events;
 pub mod serial;
+pub mod traits;
+pub mod virtual_board;
 
 #[cfg(feature = "hardware")]
 pub mod arduino_flash;
 #[cfg(feature = "hardware")]
 pub mod arduino_upload;
 #[cfg(feature = "hardware")]
-pub mod capabilities_tool;
-#[cfg(feature = "hardware")]
 pub mod nucleo_flash;
 #[cfg(feature = "hardware")]
 pub mod uno_q_bridge;
@@ -27,7 +27,6 @@ pub mod rpi;
 pub use traits::Peripheral;
 
 use crate::config::{Config, PeripheralBoardConfig, PeripheralsConfig};
-#[cfg(feature = "hardware")]
 use crate::tools::HardwareMemoryMapTool;
 use crate::tools::Tool;
 use anyhow::Result;
@@ -60,17 +59,29 @@ pub async fn handle_command(cmd: crate::PeripheralCommands, config: &Config) ->
                 println!("  board = \"nucleo-f401re\"");
                 println!("  transport = \"serial\"");
                 println!("  path = \"/dev/ttyACM0\"");
+                println!();
+                println!(
+                    "No hardware? Use a simulated board: zeroclaw peripheral add virtual virtual"
+                );
             } else {
                 println!("Configured peripherals:");
                 for b in boards {
-                    let path = b.path.as_deref().unwrap_or("(native)");
+                    let fallback = if b.transport == "virtual" {
+                        "(default script)"
+                    } else {
+                        "(native)"
+                    };
+                    let path = b.path.as_deref().unwrap_or(fallback);
                     println!("  {}  {}  {}", b.board, b.transport, path);
                 }
             }
         }
         crate::PeripheralCommands::Add { board, path } => {
-            let transport = if path == "native" { "native" } else { "serial" };
-            let path_opt = if path == "native" {
+            let transport = match path.as_str() {
+                "native" | "virtual" => path.as_str(),
+                _ => "serial",
+            };
+            let path_opt = if path == "native" || path == "virtual" {
                 None
             } else {
                 Some(path.clone())
@@ -135,8 +146,9 @@ pub async fn handle_command(cmd: crate::PeripheralCommands, config: &Config) ->
 }
 
 /// Create and connect peripherals from config, returning their tools.
-/// Returns empty vec if peripherals disabled or hardware feature off.
-#[cfg(feature = "hardware")]
+/// Returns empty vec if peripherals disabled. Without the hardware feature
+/// only virtual boards are connected.
+#[allow(clippy::unused_async)]
 pub async fn create_peripheral_tools(config: &PeripheralsConfig) -> Result<Vec<Box<dyn Tool>>> {
     if !config.enabled || config.boards.is_empty() {
         return Ok(Vec::new());
@@ -146,7 +158,23 @@ pub async fn create_peripheral_tools(config: &PeripheralsConfig) -> Result<Vec<B
     let mut serial_transports: Vec<(String, std::sync::Arc<serial::SerialTransport>)> = Vec::new();
 
     for board in &config.boards {
+        // Virtual board: simulated MCU over an in-memory pipe
+        if board.transport == "virtual" {
+            match connect_protocol_board(board).await {
+                Ok(peripheral) => {
+                    serial_transports.push((board.board.clone(), peripheral.transport()));
+                    tools.extend(peripheral.tools());
+                    tracing::info!(board = %board.board, "Virtual peripheral started");
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to start virtual board {}: {:#}", board.board, e);
+                }
+            }
+            continue;
+        }
+
         // Arduino Uno Q: Bridge transport (socket to local Bridge app)
+        #[cfg(feature = "hardware")]
         if board.transport == "bridge" && (board.board == "arduino-uno-q" || board.board == "uno-q")
         {
             tools.push(Box::new(uno_q_bridge::UnoQGpioReadTool));
@@ -172,35 +200,45 @@ pub async fn create_peripheral_tools(config: &PeripheralsConfig) -> Result<Vec<B
             continue;
         }
 
+        #[cfg(not(feature = "hardware"))]
+        tracing::warn!(
+            "Skipping board {}: transport '{}' requires the 'hardware' feature",
+            board.board,
+            board.transport
+        );
+
         // Serial transport (STM32, ESP32, Arduino, etc.)
-        if board.transport != "serial" {
-            continue;
-        }
-        if board.path.is_none() {
-            tracing::warn!("Skipping serial board {}: no path", board.board);
-            continue;
-        }
+        #[cfg(feature = "hardware")]
+        {
+            if board.transport != "serial" {
+                continue;
+            }
+            if board.path.is_none() {
+                tracing::warn!("Skipping serial board {}: no path", board.board);
+                continue;
+            }
 
-        match serial::SerialPeripheral::connect(board).await {
-            Ok(peripheral) => {
-                let mut p = peripheral;
-                if p.connect().await.is_err() {
-                    tracing::warn!("Peripheral {} connect warning (continuing)", p.name());
-                }
-                serial_transports.push((board.board.clone(), p.transport()));
-                tools.extend(p.tools());
-                if board.board == "arduino-uno" {
-                    if let Some(ref path) = board.path {
-                        tools.push(Box::new(arduino_upload::ArduinoUploadTool::new(
-                            path.clone(),
-                        )));
-                        tracing::info!("Arduino upload tool added (port: {})", path);
+            match serial::SerialPeripheral::connect(board).await {
+                Ok(peripheral) => {
+                    let mut p = peripheral;
+                    if p.connect().await.is_err() {
+                        tracing::warn!("Peripheral {} connect warning (continuing)", p.name());
+                    }
+                    serial_transports.push((board.board.clone(), p.transport()));
+                    tools.extend(p.tools());
+                    if board.board == "arduino-uno" {
+                        if let Some(ref path) = board.path {
+                            tools.push(Box::new(arduino_upload::ArduinoUploadTool::new(
+                                path.clone(),
+                            )));
+                            tracing::info!("Arduino upload tool added (port: {})", path);
+                        }
                     }
+                    tracing::info!(board = %board.board, "Serial peripheral connected");
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to connect {}: {}", board.board, e);
                 }
-                tracing::info!(board = %board.board, "Serial peripheral connected");
-            }
-            Err(e) => {
-                tracing::warn!("Failed to connect {}: {}", board.board, e);
             }
         }
     }
@@ -227,9 +265,44 @@ pub async fn create_peripheral_tools(config: &PeripheralsConfig) -> Result<Vec<B
     Ok(tools)
 }
 
-#[cfg(not(feature = "hardware"))]
-pub async fn create_peripheral_tools(_config: &PeripheralsConfig) -> Result<Vec<Box<dyn Tool>>> {
-    Ok(Vec::new())
+/// Connect a board that speaks the serial JSON protocol: a virtual board, or
+/// a serial one with the hardware feature. Connections are shared, so a board
+/// already open for tools or event subscriptions is reused.
+#[allow(clippy::unused_async)]
+pub(crate) async fn connect_protocol_board(
+    board: &PeripheralBoardConfig,
+) -> Result<serial::SerialPeripheral> {
+    if board.transport == "virtual" {
+        let key = format!(
+            "virtual:{}:{}",
+            board.board,
+            board.path.as_deref().unwrap_or_default()
+        );
+        return serial::SerialPeripheral::shared(
+            format!("{}-virtual", board.board),
+            board.board.clone(),
+            &key,
+            || {
+                Ok(Box::new(
+                    virtual_board::VirtualBoard::from_config(board)?.spawn(),
+                ))
+            },
+        );
+    }
+    #[cfg(feature = "hardware")]
+    if board.transport == "serial" {
+        return serial::SerialPeripheral::connect(board).await;
+    }
+    anyhow::bail!(
+        "Board {} (transport '{}') does not speak the serial protocol{}",
+        board.board,
+        board.transport,
+        if cfg!(feature = "hardware") {
+            ""
+        } else {
+            "; serial boards need the 'hardware' feature"
+        }
+    )
 }
 
 #[cfg(test)]
@@ -248,6 +321,7 @@ mod tests {
                 baud: 115_200,
             }],
             datasheet_dir: None,
+            subscriptions: Vec::new(),
         };
         let result = list_configured_boards(&config);
         assert!(
@@ -275,6 +349,7 @@ mod tests {
                 },
             ],
             datasheet_dir: None,
+            subscriptions: Vec::new(),
         };
         let result = list_configured_boards(&config);
         assert_eq!(result.len(), 2);
@@ -288,6 +363,7 @@ mod tests {
             enabled: true,
             boards: vec![],
             datasheet_dir: None,
+            subscriptions: Vec::new(),
         };
         let result = list_configured_boards(&config);
         assert!(
@@ -302,6 +378,7 @@ mod tests {
             enabled: false,
             boards: vec![],
             datasheet_dir: None,
+            subscriptions: Vec::new(),
         };
         let tools = create_peripheral_tools(&config).await.unwrap();
         assert!(
@@ -3,19 +3,41 @@
 //! Protocol: newline-delimited JSON.
 //! Request:  {"id":"1","cmd":"gpio_write","args":{"pin":13,"value":1}}
 //! Response: {"id":"1","ok":true,"result":"done"}
+//!
+//! Besides GPIO, boards may report I2C, SPI, PWM and ADC support from
+//! `capabilities`; see [`super::bus`].
+//!
+//! Boards may also push lines without an id at any time, for subscriptions
+//! set up with `subscribe`; see [`super::events`].
+//! Event:    {"event":"gpio_edge","sub":"door","data":{"pin":2,"value":1}}
+//!
+//! The protocol runs over any byte stream: a USB serial port (`hardware`
+//! feature) or an in-memory pipe to a [`super::virtual_board::VirtualBoard`].
 
+use super::bus::{BoardCapabilities, BusOp};
 use super::traits::Peripheral;
+#[cfg(feature = "hardware")]
 use crate::config::PeripheralBoardConfig;
 use crate::tools::traits::{Tool, ToolResult};
+use anyhow::Context;
 use async_trait::async_trait;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::sync::Mutex;
-use tokio_serial::{SerialPortBuilderExt, SerialStream};
+use std::sync::{Arc, LazyLock, Weak};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, WriteHalf};
+use tokio::sync::{broadcast, mpsc, Mutex, OnceCell};
+use tokio::task::JoinHandle;
+#[cfg(feature = "hardware")]
+use tokio_serial::SerialPortBuilderExt;
+
+/// Byte stream the JSON protocol runs over.
+pub trait SerialIo: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> SerialIo for T {}
 
 /// Allowed serial path patterns (security: deny arbitrary paths).
+#[cfg(feature = "hardware")]
 const ALLOWED_PATH_PREFIXES: &[&str] = &[
     "/dev/ttyACM",
     "/dev/ttyUSB",
@@ -26,12 +48,36 @@ const ALLOWED_PATH_PREFIXES: &[&str] = &[
     "COM",               // Windows
 ];
 
+#[cfg(feature = "hardware")]
 fn is_path_allowed(path: &str) -> bool {
     ALLOWED_PATH_PREFIXES.iter().any(|p| path.starts_with(p))
 }
 
+/// Events buffered per subscriber before the oldest are dropped.
+const EVENT_BUFFER: usize = 64;
+
+/// Open transports by port, so tools and event subscriptions created
+/// separately share one connection to each board.
+static TRANSPORTS: LazyLock<parking_lot::Mutex<HashMap<String, Weak<SerialTransport>>>> =
+    LazyLock::new(Default::default);
+
+/// An unsolicited line pushed by the board.
+#[derive(Debug, Clone)]
+pub struct BoardEvent {
+    pub event: String,
+    /// Subscription that produced the event, if any.
+    pub sub: Option<String>,
+    pub data: Value,
+}
+
+/// Write side of the connection and the non-event lines read from it.
+struct Link {
+    writer: WriteHalf<Box<dyn SerialIo>>,
+    responses: mpsc::Receiver<String>,
+}
+
 /// JSON request/response over serial.
-async fn send_request(port: &mut SerialStream, cmd: &str, args: Value) -> anyhow::Result<Value> {
+async fn send_request(link: &mut Link, cmd: &str, args: Value) -> anyhow::Result<Value> {
     static ID: AtomicU64 = AtomicU64::new(0);
     let id = ID.fetch_add(1, Ordering::Relaxed);
     let id_str = id.to_string();
@@ -43,18 +89,14 @@ async fn send_request(port: &mut SerialStream, cmd: &str, args: Value) -> anyhow
     });
     let line = format!("{}\n", req);
 
-    port.write_all(line.as_bytes()).await?;
-    port.flush().await?;
+    link.writer.write_all(line.as_bytes()).await?;
+    link.writer.flush().await?;
 
-    let mut buf = Vec::new();
-    let mut b = [0u8; 1];
-    while port.read_exact(&mut b).await.is_ok() {
-        if b[0] == b'\n' {
-            break;
-        }
-        buf.push(b[0]);
-    }
-    let line_str = String::from_utf8_lossy(&buf);
+    let line_str = link
+        .responses
+        .recv()
+        .await
+        .context("Serial connection closed")?;
     let resp: Value = serde_json::from_str(line_str.trim())?;
     let resp_id = resp["id"].as_str().unwrap_or("");
     if resp_id != id_str {
@@ -63,20 +105,98 @@ async fn send_request(port: &mut SerialStream, cmd: &str, args: Value) -> anyhow
     Ok(resp)
 }
 
+/// Read lines from the board, publishing events and queueing everything else
+/// as the response to the pending request.
+async fn read_lines(
+    reader: impl AsyncRead + Unpin,
+    responses: mpsc::Sender<String>,
+    events: broadcast::Sender<BoardEvent>,
+) {
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                tracing::warn!("Serial read failed: {e}");
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Ok(value) = serde_json::from_str::<Value>(&line) {
+            if let Some(event) = value["event"].as_str() {
+                let _ = events.send(BoardEvent {
+                    event: event.to_string(),
+                    sub: value["sub"].as_str().map(String::from),
+                    data: value["data"].clone(),
+                });
+                continue;
+            }
+        }
+        if responses.try_send(line).is_err() {
+            tracing::debug!("Dropping serial line with no pending request");
+        }
+    }
+}
+
 /// Shared serial transport for tools. Pub(crate) for capabilities tool.
 pub(crate) struct SerialTransport {
-    port: Mutex<SerialStream>,
+    link: Mutex<Link>,
+    /// Only the reader task sends, so subscribers see the channel close
+    /// when the connection drops.
+    events: broadcast::Receiver<BoardEvent>,
+    reader: JoinHandle<()>,
+    /// Capabilities reported by the firmware, fetched on first use.
+    board_capabilities: OnceCell<BoardCapabilities>,
 }
 
 /// Timeout for serial request/response (seconds).
 const SERIAL_TIMEOUT_SECS: u64 = 5;
 
 impl SerialTransport {
-    async fn request(&self, cmd: &str, args: Value) -> anyhow::Result<ToolResult> {
-        let mut port = self.port.lock().await;
+    fn new(port: Box<dyn SerialIo>) -> Self {
+        let (reader, writer) = tokio::io::split(port);
+        let (responses_tx, responses) = mpsc::channel(8);
+        let (events_tx, events) = broadcast::channel(EVENT_BUFFER);
+        Self {
+            link: Mutex::new(Link { writer, responses }),
+            reader: tokio::spawn(read_lines(reader, responses_tx, events_tx)),
+            events,
+            board_capabilities: OnceCell::new(),
+        }
+    }
+
+    /// The live transport registered under `key`, or a new one over `open()`.
+    fn shared(
+        key: &str,
+        open: impl FnOnce() -> anyhow::Result<Box<dyn SerialIo>>,
+    ) -> anyhow::Result<Arc<Self>> {
+        let mut transports = TRANSPORTS.lock();
+        transports.retain(|_, t| t.strong_count() > 0);
+        if let Some(transport) = transports.get(key).and_then(Weak::upgrade) {
+            if !transport.reader.is_finished() {
+                return Ok(transport);
+            }
+        }
+        let transport = Arc::new(Self::new(open()?));
+        transports.insert(key.to_string(), Arc::downgrade(&transport));
+        Ok(transport)
+    }
+
+    /// Events pushed by the board from now on.
+    pub(crate) fn events(&self) -> broadcast::Receiver<BoardEvent> {
+        self.events.resubscribe()
+    }
+
+    pub(crate) async fn request(&self, cmd: &str, args: Value) -> anyhow::Result<ToolResult> {
+        let mut link = self.link.lock().await;
+        // Drop late replies to requests that timed out.
+        while link.responses.try_recv().is_ok() {}
         let resp = tokio::time::timeout(
             std::time::Duration::from_secs(SERIAL_TIMEOUT_SECS),
-            send_request(&mut port, cmd, args),
+            send_request(&mut link, cmd, args),
         )
         .await
         .map_err(|_| {
@@ -101,6 +221,30 @@ impl SerialTransport {
     pub async fn capabilities(&self) -> anyhow::Result<ToolResult> {
         self.request("capabilities", json!({})).await
     }
+
+    /// Parsed capabilities, used to validate bus, pin and channel arguments.
+    pub(crate) async fn board_capabilities(&self) -> anyhow::Result<BoardCapabilities> {
+        self.board_capabilities
+            .get_or_try_init(|| async {
+                let result = self.capabilities().await?;
+                if !result.success {
+                    anyhow::bail!(
+                        "Board did not report capabilities: {}",
+                        result.error.as_deref().unwrap_or("unknown")
+                    );
+                }
+                BoardCapabilities::parse(&result.output)
+            })
+            .await
+            .cloned()
+    }
+}
+
+impl Drop for SerialTransport {
+    fn drop(&mut self) {
+        // The reader holds half of the port; stop it so the port closes.
+        self.reader.abort();
+    }
 }
 
 /// Serial peripheral for STM32, Arduino, etc. over USB CDC.
@@ -112,6 +256,7 @@ pub struct SerialPeripheral {
 
 impl SerialPeripheral {
     /// Create and connect to a serial peripheral.
+    #[cfg(feature = "hardware")]
     #[allow(clippy::unused_async)]
     pub async fn connect(config: &PeripheralBoardConfig) -> anyhow::Result<Self> {
         let path = config
@@ -126,19 +271,27 @@ impl SerialPeripheral {
             );
         }
 
-        let port = tokio_serial::new(path, config.baud)
-            .open_native_async()
-            .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path, e))?;
-
         let name = format!("{}-{}", config.board, path.replace('/', "_"));
-        let transport = Arc::new(SerialTransport {
-            port: Mutex::new(port),
-        });
+        Self::shared(name, config.board.clone(), path, || {
+            let port = tokio_serial::new(path, config.baud)
+                .open_native_async()
+                .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path, e))?;
+            Ok(Box::new(port))
+        })
+    }
 
+    /// Speak the protocol over the stream from `open`, or reuse the open
+    /// connection registered under `key` (the port path).
+    pub fn shared(
+        name: String,
+        board_type: String,
+        key: &str,
+        open: impl FnOnce() -> anyhow::Result<Box<dyn SerialIo>>,
+    ) -> anyhow::Result<Self> {
         Ok(Self {
-            name: name.clone(),
-            board_type: config.board.clone(),
-            transport,
+            name,
+            board_type,
+            transport: SerialTransport::shared(key, open)?,
         })
     }
 }
@@ -170,14 +323,21 @@ impl Peripheral for SerialPeripheral {
     }
 
     fn tools(&self) -> Vec<Box<dyn Tool>> {
-        vec![
+        let mut tools: Vec<Box<dyn Tool>> = vec![
             Box::new(GpioReadTool {
                 transport: self.transport.clone(),
             }),
             Box::new(GpioWriteTool {
                 transport: self.transport.clone(),
             }),
-        ]
+        ];
+        tools.extend(BusOp::ALL.into_iter().map(|op| {
+            Box::new(BusTool {
+                op,
+                transport: self.transport.clone(),
+            }) as Box<dyn Tool>
+        }));
+        tools
     }
 }
 
@@ -273,3 +433,35 @@ impl Tool for GpioWriteTool {
             .await
     }
 }
+
+/// Tool: an I2C, SPI, PWM or ADC operation, checked against the board's
+/// reported capabilities before it is sent.
+struct BusTool {
+    op: BusOp,
+    transport: Arc<SerialTransport>,
+}
+
+#[async_trait]
+impl Tool for BusTool {
+    fn name(&self) -> &str {
+        self.op.name()
+    }
+
+    fn description(&self) -> &str {
+        self.op.description()
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.op.parameters_schema()
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let capabilities = self.transport.board_capabilities().await?;
+        let wire_args = self.op.wire_args(&args, &capabilities)?;
+        let mut result = self.transport.request(self.op.name(), wire_args).await?;
+        if result.success {
+            result.output = self.op.format_result(&result.output, &capabilities)?;
+        }
+        Ok(result)
+    }
+}
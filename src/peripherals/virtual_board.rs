@@ -0,0 +1,922 @@
+//! Virtual board — a simulated MCU for hardware-free development and CI.
+//!
+//! The board runs in-process and answers the newline-JSON protocol of
+//! [`super::serial`] over an in-memory pipe, so the GPIO, bus and
+//! `hardware_capabilities` tools work against it unchanged. Select it with
+//! `transport = "virtual"`; the board `path`, if set, names a TOML script:
+//!
+//! ```toml
+//! [[pins]]                 # input levels read by gpio_read
+//! pin = 2
+//! waveform = { type = "square", low = 0, high = 1, period_ms = 1000 }
+//!
+//! [[adc]]                  # sensor signal read by adc_read
+//! channel = 0
+//! waveform = { type = "sine", min = 200, max = 800, period_ms = 2000 }
+//!
+//! [[i2c]]                  # device answering i2c_scan/i2c_read/i2c_write
+//! address = 0x68
+//! registers = { 0x75 = 0x68 }
+//!
+//! [[faults]]               # fail the 4th adc_read, then recover
+//! cmd = "adc_read"
+//! kind = "error"
+//! message = "ADC overrun"
+//! after = 3
+//! count = 1
+//! ```
+//!
+//! Without a script the board reports the same capabilities as the Arduino
+//! Uno firmware, with all inputs low. SPI is a loopback: each transfer
+//! returns the bytes sent. `subscribe` works like the firmware: the board
+//! checks its subscriptions every 10 ms and pushes events for them.
+
+use super::bus::BoardCapabilities;
+use crate::config::{GpioEdge, PeripheralBoardConfig};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream};
+
+/// Capacity of the in-memory pipe between host and board, in bytes.
+const PIPE_BUFFER_BYTES: usize = 4096;
+
+/// How often subscriptions are checked, like a firmware main loop.
+const SUBSCRIPTION_POLL: Duration = Duration::from_millis(10);
+
+/// Scripted behaviour of a virtual board.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VirtualBoardScript {
+    /// Reported from `capabilities` and used to reject invalid pins, buses
+    /// and channels like the firmware does.
+    pub capabilities: BoardCapabilities,
+    pub pins: Vec<PinScript>,
+    pub adc: Vec<AdcScript>,
+    pub i2c: Vec<I2cDeviceScript>,
+    pub faults: Vec<FaultScript>,
+}
+
+impl Default for VirtualBoardScript {
+    fn default() -> Self {
+        Self {
+            capabilities: BoardCapabilities {
+                gpio: (0..=13).collect(),
+                led_pin: Some(13),
+                i2c: vec![0],
+                spi: vec![0],
+                pwm: vec![3, 5, 6, 9, 10, 11],
+                adc: (0..=5).collect(),
+                adc_bits: Some(10),
+                adc_vref_mv: Some(5000),
+            },
+            pins: Vec::new(),
+            adc: Vec::new(),
+            i2c: Vec::new(),
+            faults: Vec::new(),
+        }
+    }
+}
+
+impl VirtualBoardScript {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("invalid board script {}", path.display()))
+    }
+}
+
+/// Input level of a GPIO pin; any non-zero sample reads as 1.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PinScript {
+    pub pin: u64,
+    pub waveform: Waveform,
+}
+
+/// Raw reading of an ADC channel, clamped to the ADC resolution.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdcScript {
+    pub channel: u64,
+    pub waveform: Waveform,
+}
+
+/// An I2C device with a 256-byte register file. Unlisted registers read 0.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct I2cDeviceScript {
+    #[serde(default)]
+    pub bus: u64,
+    pub address: u8,
+    /// Initial register values keyed by register number ("0x75" or "117").
+    #[serde(default)]
+    pub registers: HashMap<String, u8>,
+}
+
+/// A signal sampled on every read.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Waveform {
+    Constant {
+        value: u32,
+    },
+    Square {
+        low: u32,
+        high: u32,
+        period_ms: u64,
+    },
+    /// Starts at `min`, peaks at `max` half a period later.
+    Sine {
+        min: u32,
+        max: u32,
+        period_ms: u64,
+    },
+    /// Rises linearly from `min` to `max`, then drops back.
+    Ramp {
+        min: u32,
+        max: u32,
+        period_ms: u64,
+    },
+    /// One value per read, repeating from the start.
+    Sequence {
+        values: Vec<u32>,
+    },
+}
+
+impl Waveform {
+    /// Value at `elapsed_ms` after the board started, for the `read`-th read.
+    pub fn sample(&self, elapsed_ms: u64, read: u64) -> u32 {
+        #[allow(clippy::cast_precision_loss)]
+        let phase =
+            |period_ms: u64| (elapsed_ms % period_ms.max(1)) as f64 / period_ms.max(1) as f64;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let between = |min: u32, max: u32, fraction: f64| {
+            min + (f64::from(max.saturating_sub(min)) * fraction).round() as u32
+        };
+        match self {
+            Waveform::Constant { value } => *value,
+            Waveform::Square {
+                low,
+                high,
+                period_ms,
+            } => {
+                if phase(*period_ms) < 0.5 {
+                    *low
+                } else {
+                    *high
+                }
+            }
+            Waveform::Sine {
+                min,
+                max,
+                period_ms,
+            } => between(
+                *min,
+                *max,
+                (1.0 - (phase(*period_ms) * std::f64::consts::TAU).cos()) / 2.0,
+            ),
+            Waveform::Ramp {
+                min,
+                max,
+                period_ms,
+            } => between(*min, *max, phase(*period_ms)),
+            Waveform::Sequence { values } => {
+                if values.is_empty() {
+                    0
+                } else {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let index = (read % values.len() as u64) as usize;
+                    values[index]
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultKind {
+    /// Reply `ok: false` with the fault's `message`.
+    Error,
+    /// Never reply, so the host request times out.
+    NoReply,
+    /// Reply with a line that is not JSON.
+    Garbage,
+    /// Reply to a different request id.
+    WrongId,
+    /// Reply normally after `delay_ms`.
+    Delay,
+}
+
+/// A fault injected into matching requests: the first `after` are answered
+/// normally, then the next `count` (all, if unset) get the fault.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FaultScript {
+    /// Command to fault; every command when unset.
+    #[serde(default)]
+    pub cmd: Option<String>,
+    pub kind: FaultKind,
+    #[serde(default)]
+    pub after: u64,
+    #[serde(default)]
+    pub count: Option<u64>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+/// What the board sends back for one request line.
+#[derive(Debug, PartialEq)]
+enum Reply {
+    Line(String),
+    Delayed(Duration, String),
+    Silent,
+}
+
+/// Time-varying signal with a count of how often it has been read.
+struct Signal {
+    waveform: Waveform,
+    reads: u64,
+}
+
+impl Signal {
+    fn new(waveform: Waveform) -> Self {
+        Self { waveform, reads: 0 }
+    }
+
+    fn read(&mut self, elapsed_ms: u64) -> u32 {
+        let value = self.peek(elapsed_ms);
+        self.reads += 1;
+        value
+    }
+
+    /// The value the next read would return, without counting a read.
+    fn peek(&self, elapsed_ms: u64) -> u32 {
+        self.waveform.sample(elapsed_ms, self.reads)
+    }
+}
+
+/// Condition a `subscribe` command asked the board to watch.
+#[derive(Debug, Clone, Copy)]
+enum Watch {
+    GpioEdge {
+        pin: u64,
+        edge: GpioEdge,
+    },
+    AdcThreshold {
+        channel: u64,
+        above: Option<u32>,
+        below: Option<u32>,
+    },
+    AdcSample {
+        channel: u64,
+        interval_ms: u64,
+    },
+}
+
+impl Watch {
+    fn event(self) -> &'static str {
+        match self {
+            Watch::GpioEdge { .. } => "gpio_edge",
+            Watch::AdcThreshold { .. } => "adc_threshold",
+            Watch::AdcSample { .. } => "adc_sample",
+        }
+    }
+}
+
+struct Subscription {
+    name: String,
+    watch: Watch,
+    /// Value seen at the previous check.
+    last: u32,
+    next_sample_ms: u64,
+}
+
+struct I2cDevice {
+    registers: [u8; 256],
+    /// Register the next read or write starts at when none is given.
+    pointer: u8,
+}
+
+impl I2cDevice {
+    fn transfer(&mut self, reg: Option<u64>) -> &mut Self {
+        if let Some(reg) = reg.and_then(|r| u8::try_from(r).ok()) {
+            self.pointer = reg;
+        }
+        self
+    }
+
+    fn read(&mut self, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                let value = self.registers[usize::from(self.pointer)];
+                self.pointer = self.pointer.wrapping_add(1);
+                value
+            })
+            .collect()
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        for byte in data {
+            self.registers[usize::from(self.pointer)] = *byte;
+            self.pointer = self.pointer.wrapping_add(1);
+        }
+    }
+}
+
+/// Simulated MCU state. Run it with [`VirtualBoard::spawn`].
+pub struct VirtualBoard {
+    capabilities: BoardCapabilities,
+    started: Instant,
+    pins: HashMap<u64, Signal>,
+    /// Levels set by `gpio_write`, which override scripted inputs.
+    outputs: HashMap<u64, u32>,
+    adc: HashMap<u64, Signal>,
+    i2c: HashMap<(u64, u8), I2cDevice>,
+    /// Each fault with the number of matching requests seen so far.
+    faults: Vec<(FaultScript, u64)>,
+    subscriptions: Vec<Subscription>,
+}
+
+impl VirtualBoard {
+    pub fn new(script: VirtualBoardScript) -> Result<Self> {
+        let mut i2c = HashMap::new();
+        for device in script.i2c {
+            let mut registers = [0u8; 256];
+            for (reg, value) in &device.registers {
+                let index = match reg.strip_prefix("0x") {
+                    Some(hex) => u8::from_str_radix(hex, 16),
+                    None => reg.parse(),
+                }
+                .with_context(|| {
+                    format!(
+                        "invalid register '{reg}' on I2C device {:#04x}",
+                        device.address
+                    )
+                })?;
+                registers[usize::from(index)] = *value;
+            }
+            i2c.insert(
+                (device.bus, device.address),
+                I2cDevice {
+                    registers,
+                    pointer: 0,
+                },
+            );
+        }
+        Ok(Self {
+            capabilities: script.capabilities,
+            started: Instant::now(),
+            pins: script
+                .pins
+                .into_iter()
+                .map(|p| (p.pin, Signal::new(p.waveform)))
+                .collect(),
+            outputs: HashMap::new(),
+            adc: script
+                .adc
+                .into_iter()
+                .map(|a| (a.channel, Signal::new(a.waveform)))
+                .collect(),
+            i2c,
+            faults: script.faults.into_iter().map(|f| (f, 0)).collect(),
+            subscriptions: Vec::new(),
+        })
+    }
+
+    /// Board for a `transport = "virtual"` config entry, scripted by `path`.
+    pub fn from_config(config: &PeripheralBoardConfig) -> Result<Self> {
+        let script = match config.path.as_deref() {
+            Some(path) => VirtualBoardScript::load(Path::new(path))?,
+            None => VirtualBoardScript::default(),
+        };
+        Self::new(script)
+    }
+
+    /// Run the board on a background task and return the host end of its pipe.
+    /// The board stops when the host end is dropped.
+    pub fn spawn(self) -> DuplexStream {
+        let (host, device) = tokio::io::duplex(PIPE_BUFFER_BYTES);
+        tokio::spawn(self.run(device));
+        host
+    }
+
+    async fn run(mut self, device: DuplexStream) {
+        let (reader, mut writer) = tokio::io::split(device);
+        let mut lines = BufReader::new(reader).lines();
+        let mut poll = tokio::time::interval(SUBSCRIPTION_POLL);
+        poll.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            let output = tokio::select! {
+                line = lines.next_line() => {
+                    let Ok(Some(line)) = line else { break };
+                    match self.handle_line(&line) {
+                        Reply::Line(line) => vec![line],
+                        Reply::Delayed(delay, line) => {
+                            tokio::time::sleep(delay).await;
+                            vec![line]
+                        }
+                        Reply::Silent => continue,
+                    }
+                }
+                _ = poll.tick() => self.poll_subscriptions(),
+            };
+            for line in output {
+                if writer
+                    .write_all(format!("{line}\n").as_bytes())
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        u64::try_from(self.started.elapsed().as_millis()).unwrap_or(u64::MAX)
+    }
+
+    fn adc_max(&self) -> u32 {
+        self.capabilities
+            .adc_bits
+            .filter(|bits| *bits > 0 && *bits < 32)
+            .map_or(u32::MAX, |bits| (1u32 << bits) - 1)
+    }
+
+    /// Current value of a watched pin or channel, without counting a read.
+    fn watched(&self, watch: Watch, elapsed_ms: u64) -> u32 {
+        match watch {
+            Watch::GpioEdge { pin, .. } => {
+                let level = match self.outputs.get(&pin) {
+                    Some(level) => *level,
+                    None => self
+                        .pins
+                        .get(&pin)
+                        .map_or(0, |signal| signal.peek(elapsed_ms)),
+                };
+                u32::from(level != 0)
+            }
+            Watch::AdcThreshold { channel, .. } | Watch::AdcSample { channel, .. } => self
+                .adc
+                .get(&channel)
+                .map_or(0, |signal| signal.peek(elapsed_ms))
+                .min(self.adc_max()),
+        }
+    }
+
+    /// Check every subscription and return the event lines to push.
+    fn poll_subscriptions(&mut self) -> Vec<String> {
+        let elapsed_ms = self.elapsed_ms();
+        let values: Vec<u32> = self
+            .subscriptions
+            .iter()
+            .map(|sub| self.watched(sub.watch, elapsed_ms))
+            .collect();
+        let mut events = Vec::new();
+        for (sub, value) in self.subscriptions.iter_mut().zip(values) {
+            let last = std::mem::replace(&mut sub.last, value);
+            let data = match sub.watch {
+                Watch::GpioEdge { pin, edge } => {
+                    let fired = match edge {
+                        GpioEdge::Rising => value > last,
+                        GpioEdge::Falling => value < last,
+                        GpioEdge::Both => value != last,
+                    };
+                    fired.then(|| json!({ "pin": pin, "value": value }))
+                }
+                Watch::AdcThreshold {
+                    channel,
+                    above,
+                    below,
+                } => {
+                    let crossed = if above.is_some_and(|a| last <= a && value > a) {
+                        Some("above")
+                    } else if below.is_some_and(|b| last >= b && value < b) {
+                        Some("below")
+                    } else {
+                        None
+                    };
+                    crossed.map(
+                        |crossed| json!({ "channel": channel, "value": value, "crossed": crossed }),
+                    )
+                }
+                Watch::AdcSample {
+                    channel,
+                    interval_ms,
+                } => {
+                    if elapsed_ms >= sub.next_sample_ms {
+                        sub.next_sample_ms = elapsed_ms.saturating_add(interval_ms);
+                        Some(json!({ "channel": channel, "value": value }))
+                    } else {
+                        None
+                    }
+                }
+            };
+            if let Some(data) = data {
+                events.push(
+                    json!({ "event": sub.watch.event(), "sub": sub.name, "data": data })
+                        .to_string(),
+                );
+            }
+        }
+        events
+    }
+
+    /// Watch a pin or ADC channel; a subscription with the same name is replaced.
+    fn subscribe(&mut self, args: &Value, elapsed_ms: u64) -> Result<String, String> {
+        let caps = &self.capabilities;
+        let name = args["sub"]
+            .as_str()
+            .filter(|name| !name.is_empty())
+            .ok_or("Missing subscription name")?
+            .to_string();
+        let channel = || {
+            arg(args, "channel")
+                .filter(|c| caps.adc.contains(c))
+                .ok_or("Invalid ADC channel")
+        };
+        let threshold = |key| arg(args, key).and_then(|v| u32::try_from(v).ok());
+        let watch = match args["kind"].as_str() {
+            Some("gpio_edge") => Watch::GpioEdge {
+                pin: arg(args, "pin")
+                    .filter(|p| caps.gpio.contains(p))
+                    .ok_or_else(|| format!("Invalid pin {}", args["pin"]))?,
+                edge: match args["edge"].as_str() {
+                    None | Some("both") => GpioEdge::Both,
+                    Some("rising") => GpioEdge::Rising,
+                    Some("falling") => GpioEdge::Falling,
+                    Some(other) => return Err(format!("Invalid edge {other}")),
+                },
+            },
+            Some("adc_threshold") => {
+                let (above, below) = (threshold("above"), threshold("below"));
+                if above.is_none() && below.is_none() {
+                    return Err("Missing threshold".into());
+                }
+                Watch::AdcThreshold {
+                    channel: channel()?,
+                    above,
+                    below,
+                }
+            }
+            Some("adc_sample") => Watch::AdcSample {
+                channel: channel()?,
+                interval_ms: arg(args, "interval_ms")
+                    .filter(|i| *i > 0)
+                    .ok_or("Invalid interval")?,
+            },
+            _ => return Err(format!("Unknown subscription kind {}", args["kind"])),
+        };
+        let last = self.watched(watch, elapsed_ms);
+        let next_sample_ms = match watch {
+            Watch::AdcSample { interval_ms, .. } => elapsed_ms.saturating_add(interval_ms),
+            _ => 0,
+        };
+        self.subscriptions.retain(|sub| sub.name != name);
+        self.subscriptions.push(Subscription {
+            name,
+            watch,
+            last,
+            next_sample_ms,
+        });
+        Ok("done".into())
+    }
+
+    fn handle_line(&mut self, line: &str) -> Reply {
+        let Ok(request) = serde_json::from_str::<Value>(line.trim()) else {
+            return Reply::Line(response("", Err("Invalid JSON".into())));
+        };
+        let id = request["id"].as_str().unwrap_or_default();
+        let cmd = request["cmd"].as_str().unwrap_or_default();
+
+        let fault = self.next_fault(cmd);
+        if let Some(fault) = &fault {
+            match fault.kind {
+                FaultKind::NoReply => return Reply::Silent,
+                FaultKind::Garbage => return Reply::Line("\u{fffd}#garbage".into()),
+                FaultKind::Error => {
+                    let message = fault.message.as_deref().unwrap_or("Injected fault");
+                    return Reply::Line(response(id, Err(message.into())));
+                }
+                FaultKind::WrongId | FaultKind::Delay => {}
+            }
+        }
+
+        let result = self.execute(cmd, &request["args"]);
+        match fault {
+            Some(fault) if fault.kind == FaultKind::WrongId => {
+                Reply::Line(response(&format!("{id}-stale"), result))
+            }
+            Some(fault) if fault.kind == FaultKind::Delay => {
+                Reply::Delayed(Duration::from_millis(fault.delay_ms), response(id, result))
+            }
+            _ => Reply::Line(response(id, result)),
+        }
+    }
+
+    /// Count `cmd` against every matching fault and return the first active one.
+    fn next_fault(&mut self, cmd: &str) -> Option<FaultScript> {
+        let mut active = None;
+        for (fault, seen) in &mut self.faults {
+            if fault.cmd.as_deref().is_some_and(|c| c != cmd) {
+                continue;
+            }
+            *seen += 1;
+            let started = *seen > fault.after;
+            let within = fault.count.is_none_or(|count| *seen <= fault.after + count);
+            if active.is_none() && started && within {
+                active = Some(fault.clone());
+            }
+        }
+        active
+    }
+
+    fn execute(&mut self, cmd: &str, args: &Value) -> Result<String, String> {
+        let elapsed_ms = self.elapsed_ms();
+        let max = self.adc_max();
+        let caps = &self.capabilities;
+        match cmd {
+            "ping" => Ok("pong".into()),
+            "capabilities" => serde_json::to_string(caps).map_err(|e| e.to_string()),
+            "gpio_read" | "gpio_write" => {
+                let pin = arg(args, "pin")
+                    .filter(|p| caps.gpio.contains(p))
+                    .ok_or_else(|| format!("Invalid pin {}", args["pin"]))?;
+                if cmd == "gpio_write" {
+                    let value = arg(args, "value").ok_or("Missing value")?;
+                    self.outputs.insert(pin, u32::from(value != 0));
+                    return Ok("done".into());
+                }
+                let level = match self.outputs.get(&pin) {
+                    Some(level) => *level,
+                    None => self
+                        .pins
+                        .get_mut(&pin)
+                        .map_or(0, |signal| signal.read(elapsed_ms)),
+                };
+                Ok(u32::from(level != 0).to_string())
+            }
+            "i2c_scan" => {
+                let bus = bus_arg(args, &caps.i2c, "I2C")?;
+                let mut found: Vec<u8> = self
+                    .i2c
+                    .keys()
+                    .filter(|(b, _)| *b == bus)
+                    .map(|(_, address)| *address)
+                    .collect();
+                found.sort_unstable();
+                Ok(found
+                    .iter()
+                    .map(u8::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","))
+            }
+            "i2c_read" | "i2c_write" => {
+                let bus = bus_arg(args, &caps.i2c, "I2C")?;
+                let address = arg(args, "addr")
+                    .and_then(|a| u8::try_from(a).ok())
+                    .ok_or("Invalid I2C arguments")?;
+                let device = self
+                    .i2c
+                    .get_mut(&(bus, address))
+                    .ok_or("I2C device did not acknowledge")?
+                    .transfer(arg(args, "reg"));
+                if cmd == "i2c_read" {
+                    let len = arg(args, "len")
+                        .and_then(|l| usize::try_from(l).ok())
+                        .filter(|l| (1..=super::bus::MAX_TRANSFER_BYTES).contains(l))
+                        .ok_or("Invalid I2C arguments")?;
+                    return Ok(hex::encode(device.read(len)));
+                }
+                let mut data = hex_arg(args)?;
+                // Without a register, the first byte written selects it.
+                if arg(args, "reg").is_none() && !data.is_empty() {
+                    device.pointer = data.remove(0);
+                }
+                device.write(&data);
+                Ok("done".into())
+            }
+            "spi_transfer" => {
+                bus_arg(args, &caps.spi, "SPI")?;
+                Ok(hex::encode(hex_arg(args)?))
+            }
+            "pwm_set" => {
+                arg(args, "pin")
+                    .filter(|p| caps.pwm.contains(p))
+                    .and(arg(args, "duty_permille").filter(|d| *d <= 1000))
+                    .ok_or("Invalid PWM arguments")?;
+                Ok("done".into())
+            }
+            "adc_read" => {
+                let channel = arg(args, "channel")
+                    .filter(|c| caps.adc.contains(c))
+                    .ok_or("Invalid ADC channel")?;
+                let raw = self
+                    .adc
+                    .get_mut(&channel)
+                    .map_or(0, |signal| signal.read(elapsed_ms));
+                Ok(raw.min(max).to_string())
+            }
+            "subscribe" => self.subscribe(args, elapsed_ms),
+            _ => Err(format!("Unknown command: {cmd}")),
+        }
+    }
+}
+
+fn response(id: &str, result: Result<String, String>) -> String {
+    match result {
+        Ok(result) => json!({ "id": id, "ok": true, "result": result }),
+        Err(error) => json!({ "id": id, "ok": false, "result": "", "error": error }),
+    }
+    .to_string()
+}
+
+fn arg(args: &Value, key: &str) -> Option<u64> {
+    args.get(key).and_then(Value::as_u64)
+}
+
+fn bus_arg(args: &Value, available: &[u64], kind: &str) -> Result<u64, String> {
+    arg(args, "bus")
+        .filter(|b| available.contains(b))
+        .ok_or_else(|| format!("Invalid {kind} bus"))
+}
+
+fn hex_arg(args: &Value) -> Result<Vec<u8>, String> {
+    args["data"]
+        .as_str()
+        .and_then(|d| hex::decode(d).ok())
+        .filter(|d| d.len() <= super::bus::MAX_TRANSFER_BYTES)
+        .ok_or_else(|| "Invalid data".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(board: &mut VirtualBoard, id: &str, cmd: &str, args: Value) -> Value {
+        let line = json!({ "id": id, "cmd": cmd, "args": args }).to_string();
+        match board.handle_line(&line) {
+            Reply::Line(line) | Reply::Delayed(_, line) => serde_json::from_str(&line).unwrap(),
+            Reply::Silent => Value::Null,
+        }
+    }
+
+    #[test]
+    fn waveforms_sample_expected_values() {
+        let square = Waveform::Square {
+            low: 0,
+            high: 1,
+            period_ms: 100,
+        };
+        assert_eq!(square.sample(10, 0), 0);
+        assert_eq!(square.sample(60, 0), 1);
+        let sine = Waveform::Sine {
+            min: 0,
+            max: 1000,
+            period_ms: 100,
+        };
+        assert_eq!(sine.sample(0, 0), 0);
+        assert_eq!(sine.sample(50, 0), 1000);
+        let ramp = Waveform::Ramp {
+            min: 100,
+            max: 200,
+            period_ms: 10,
+        };
+        assert_eq!(ramp.sample(25, 0), 150);
+        let sequence = Waveform::Sequence {
+            values: vec![1, 2, 3],
+        };
+        assert_eq!(sequence.sample(0, 4), 2);
+    }
+
+    #[test]
+    fn simulates_gpio_i2c_and_adc() {
+        let script: VirtualBoardScript = toml::from_str(
+            r#"
+            [[pins]]
+            pin = 2
+            waveform = { type = "constant", value = 1 }
+
+            [[adc]]
+            channel = 0
+            waveform = { type = "sequence", values = [100, 5000] }
+
+            [[i2c]]
+            address = 0x68
+            registers = { 0x75 = 0x68, 1 = 7 }
+            "#,
+        )
+        .unwrap();
+        let mut board = VirtualBoard::new(script).unwrap();
+
+        assert_eq!(
+            request(&mut board, "1", "gpio_read", json!({"pin": 2}))["result"],
+            "1"
+        );
+        request(&mut board, "2", "gpio_write", json!({"pin": 2, "value": 0}));
+        assert_eq!(
+            request(&mut board, "3", "gpio_read", json!({"pin": 2}))["result"],
+            "0"
+        );
+        assert_eq!(
+            request(&mut board, "4", "gpio_read", json!({"pin": 40}))["ok"],
+            false
+        );
+
+        let adc = json!({"channel": 0});
+        assert_eq!(
+            request(&mut board, "5", "adc_read", adc.clone())["result"],
+            "100"
+        );
+        // Clamped to the 10-bit range.
+        assert_eq!(request(&mut board, "6", "adc_read", adc)["result"], "1023");
+
+        let bus = json!({"bus": 0});
+        assert_eq!(request(&mut board, "7", "i2c_scan", bus)["result"], "104");
+        let read = json!({"bus": 0, "addr": 0x68, "reg": 0x75, "len": 1});
+        assert_eq!(request(&mut board, "8", "i2c_read", read)["result"], "68");
+        let write = json!({"bus": 0, "addr": 0x68, "data": "0109"});
+        request(&mut board, "9", "i2c_write", write);
+        let read = json!({"bus": 0, "addr": 0x68, "reg": 0, "len": 3});
+        assert_eq!(
+            request(&mut board, "10", "i2c_read", read)["result"],
+            "000900"
+        );
+        let missing = json!({"bus": 0, "addr": 0x50, "len": 1});
+        assert_eq!(
+            request(&mut board, "11", "i2c_read", missing)["error"],
+            "I2C device did not acknowledge"
+        );
+    }
+
+    #[test]
+    fn injects_faults_after_and_for_count() {
+        let script: VirtualBoardScript = toml::from_str(
+            r#"
+            [[faults]]
+            cmd = "ping"
+            kind = "error"
+            message = "brownout"
+            after = 1
+            count = 1
+
+            [[faults]]
+            cmd = "capabilities"
+            kind = "no_reply"
+
+            [[faults]]
+            cmd = "gpio_read"
+            kind = "wrong_id"
+            "#,
+        )
+        .unwrap();
+        let mut board = VirtualBoard::new(script).unwrap();
+
+        assert_eq!(request(&mut board, "1", "ping", json!({}))["ok"], true);
+        assert_eq!(
+            request(&mut board, "2", "ping", json!({}))["error"],
+            "brownout"
+        );
+        assert_eq!(request(&mut board, "3", "ping", json!({}))["ok"], true);
+        assert_eq!(
+            request(&mut board, "4", "capabilities", json!({})),
+            Value::Null
+        );
+        assert_eq!(
+            request(&mut board, "5", "gpio_read", json!({"pin": 1}))["id"],
+            "5-stale"
+        );
+    }
+
+    #[test]
+    fn pushes_events_for_subscriptions() {
+        let mut board = VirtualBoard::new(VirtualBoardScript::default()).unwrap();
+        let door = json!({"sub": "door", "kind": "gpio_edge", "pin": 2, "edge": "rising"});
+        assert_eq!(request(&mut board, "1", "subscribe", door)["ok"], true);
+        let bad = json!({"sub": "x", "kind": "adc_threshold", "channel": 0});
+        assert_eq!(
+            request(&mut board, "2", "subscribe", bad)["error"],
+            "Missing threshold"
+        );
+        assert!(board.poll_subscriptions().is_empty());
+
+        request(&mut board, "3", "gpio_write", json!({"pin": 2, "value": 1}));
+        let events = board.poll_subscriptions();
+        assert_eq!(events.len(), 1);
+        let event: Value = serde_json::from_str(&events[0]).unwrap();
+        assert_eq!(
+            event,
+            json!({"event": "gpio_edge", "sub": "door", "data": {"pin": 2, "value": 1}})
+        );
+        // Falling edges are filtered out.
+        request(&mut board, "4", "gpio_write", json!({"pin": 2, "value": 0}));
+        assert!(board.poll_subscriptions().is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+//! Agent-side access to the relay for instances started by the container
+//! supervisor, which provides `ZEROCLAW_RELAY_URL`, `ZEROCLAW_CONTAINER_ID` and
+//! the instance's relay identity.
+
+use super::envelope::{AgentIdentity, AgentPublicKey, ReplayGuard};
+use super::{Delivery, RelayMessage};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+pub const RELAY_URL_ENV: &str = "ZEROCLAW_RELAY_URL";
+pub const CONTAINER_ID_ENV: &str = "ZEROCLAW_CONTAINER_ID";
+/// Inbox requests older than this are rejected by the relay.
+pub const INBOX_REQUEST_MAX_AGE_SECS: i64 = 60;
+
+/// Key lookups from a container carry its id, a unix timestamp and a
+/// signature over [`key_request_message`] in these headers.
+pub const REQUESTER_HEADER: &str = "x-relay-container";
+pub const TIMESTAMP_HEADER: &str = "x-relay-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-relay-signature";
+
+/// The string a container signs to fetch its queued messages.
+pub fn inbox_request_message(container_id: &str, timestamp: i64) -> String {
+    format!("zeroclaw-relay-inbox:{container_id}:{timestamp}")
+}
+
+/// The string a container signs to look up the public key of `target`.
+pub fn key_request_message(container_id: &str, target: &str, timestamp: i64) -> String {
+    format!("zeroclaw-relay-keys:{container_id}:{target}:{timestamp}")
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboxRequest {
+    pub container_id: String,
+    pub timestamp: i64,
+    pub signature: String,
+}
+
+/// A decrypted, verified message from another agent.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedMessage {
+    pub id: String,
+    pub from_container: String,
+    pub text: String,
+    pub sent_at: String,
+}
+
+#[derive(Deserialize)]
+struct ApiEnvelope<T> {
+    data: Option<T>,
+    error: Option<String>,
+}
+
+pub struct RelayClient {
+    http: reqwest::Client,
+    base_url: String,
+    container_id: String,
+    identity: AgentIdentity,
+    replay: parking_lot::Mutex<ReplayGuard>,
+}
+
+impl RelayClient {
+    pub fn new(base_url: &str, container_id: &str, identity: AgentIdentity) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(15))
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            container_id: container_id.to_string(),
+            identity,
+            replay: parking_lot::Mutex::new(ReplayGuard::default()),
+        }
+    }
+
+    /// Client for a supervised instance, or `None` outside one.
+    pub fn from_env(zeroclaw_dir: &Path) -> Option<Self> {
+        let base_url = std::env::var(RELAY_URL_ENV)
+            .ok()
+            .filter(|v| !v.is_empty())?;
+        let container_id = std::env::var(CONTAINER_ID_ENV)
+            .ok()
+            .filter(|v| !v.is_empty())?;
+        match AgentIdentity::load(zeroclaw_dir) {
+            Ok(identity) => Some(Self::new(&base_url, &container_id, identity)),
+            Err(e) => {
+                tracing::warn!("Relay configured but identity unavailable: {e:#}");
+                None
+            }
+        }
+    }
+
+    pub fn container_id(&self) -> &str {
+        &self.container_id
+    }
+
+    async fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T> {
+        let response: ApiEnvelope<T> = request
+            .send()
+            .await
+            .context("relay request failed")?
+            .json()
+            .await
+            .context("invalid relay response")?;
+        match (response.data, response.error) {
+            (Some(data), _) => Ok(data),
+            (None, Some(error)) => bail!("relay error: {error}"),
+            (None, None) => bail!("empty relay response"),
+        }
+    }
+
+    async fn public_key(&self, container_id: &str) -> Result<AgentPublicKey> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct KeyResponse {
+            public_key: String,
+        }
+        let url = format!(
+            "{}/api/relay/keys/{}",
+            self.base_url,
+            urlencoding::encode(container_id)
+        );
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = self
+            .identity
+            .sign(key_request_message(&self.container_id, container_id, timestamp).as_bytes());
+        let request = self
+            .http
+            .get(url)
+            .header(REQUESTER_HEADER, &self.container_id)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, signature);
+        let key: KeyResponse = self.call(request).await?;
+        AgentPublicKey::decode(&key.public_key)
+    }
+
+    /// Encrypt `text` for container `to` and hand it to the relay.
+    pub async fn send(&self, to: &str, text: &str, ttl_secs: i64) -> Result<Delivery> {
+        #[derive(Deserialize)]
+        struct SendResponse {
+            delivery: Delivery,
+        }
+        let recipient = self.public_key(to).await?;
+        let msg = RelayMessage::seal(
+            &self.identity,
+            &self.container_id,
+            to,
+            &recipient,
+            text.as_bytes(),
+            ttl_secs,
+        )?;
+        let url = format!("{}/api/relay/messages", self.base_url);
+        let response: SendResponse = self.call(self.http.post(url).json(&msg)).await?;
+        Ok(response.delivery)
+    }
+
+    /// Fetch, verify and decrypt queued messages. Messages that fail
+    /// verification or were already seen are dropped with a warning.
+    pub async fn receive(&self) -> Result<Vec<ReceivedMessage>> {
+        #[derive(Deserialize)]
+        struct InboxResponse {
+            messages: Vec<RelayMessage>,
+        }
+        let timestamp = chrono::Utc::now().timestamp();
+        let request = InboxRequest {
+            container_id: self.container_id.clone(),
+            timestamp,
+            signature: self
+                .identity
+                .sign(inbox_request_message(&self.container_id, timestamp).as_bytes()),
+        };
+        let url = format!("{}/api/relay/inbox", self.base_url);
+        let inbox: InboxResponse = self.call(self.http.post(url).json(&request)).await?;
+
+        let mut received = Vec::new();
+        for msg in inbox.messages {
+            match self.open(&msg).await {
+                Ok(message) => received.push(message),
+                Err(e) => tracing::warn!("Dropping relay message {}: {e:#}", msg.id),
+            }
+        }
+        Ok(received)
+    }
+
+    async fn open(&self, msg: &RelayMessage) -> Result<ReceivedMessage> {
+        if msg.to_container != self.container_id {
+            bail!("message addressed to {}", msg.to_container);
+        }
+        let envelope = msg.envelope()?;
+        let sender = self.public_key(&msg.from_container).await?;
+        let plaintext = self.identity.open(&envelope, &sender)?;
+        self.replay
+            .lock()
+            .check(&envelope.header, chrono::Utc::now().timestamp())?;
+        Ok(ReceivedMessage {
+            id: msg.id.clone(),
+            from_container: msg.from_container.clone(),
+            text: String::from_utf8(plaintext).context("message is not UTF-8")?,
+            sent_at: chrono::DateTime::from_timestamp(envelope.header.created_at, 0)
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default(),
+        })
+    }
+}
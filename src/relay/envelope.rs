@@ -0,0 +1,445 @@
+//! Signed, encrypted envelopes for agent-to-agent messages.
+//!
+//! A sender encrypts for the recipient's X25519 key with an ephemeral key
+//! (ChaCha20-Poly1305, key = SHA-256 of the shared secret and both public keys)
+//! and signs the whole envelope with its Ed25519 key. The relay can check who
+//! sent a message and where it goes, but cannot read it.
+
+use crate::container::KeyRotationManager;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+
+const IDENTITY_FILE: &str = "relay_identity.json";
+const KDF_CONTEXT: &[u8] = b"zeroclaw-relay-v1";
+const PUBLIC_KEY_PREFIX: &str = "zcr1";
+/// Envelopes dated further than this in the future are rejected.
+pub const MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+#[derive(Serialize, Deserialize)]
+struct StoredIdentity {
+    key_id: String,
+    signing_key: String,
+    encryption_key: String,
+}
+
+/// A container's relay keys: Ed25519 for signing, X25519 for encryption.
+pub struct AgentIdentity {
+    key_id: String,
+    signing: SigningKey,
+    encryption: StaticSecret,
+}
+
+/// The public half of an [`AgentIdentity`], encoded as
+/// `zcr1.<key_id>.<ed25519 base64>.<x25519 base64>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentPublicKey {
+    pub key_id: String,
+    signing: VerifyingKey,
+    encryption: X25519PublicKey,
+}
+
+impl AgentIdentity {
+    pub fn generate(key_id: String) -> Result<Self> {
+        Ok(Self {
+            key_id,
+            signing: SigningKey::from_bytes(&random_bytes()?),
+            encryption: StaticSecret::from(random_bytes()?),
+        })
+    }
+
+    /// Load the identity stored in `dir`.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(IDENTITY_FILE);
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let stored: StoredIdentity = serde_json::from_str(&raw)
+            .with_context(|| format!("invalid relay identity {}", path.display()))?;
+        Ok(Self {
+            key_id: stored.key_id,
+            signing: SigningKey::from_bytes(&decode_key(&stored.signing_key)?),
+            encryption: StaticSecret::from(decode_key(&stored.encryption_key)?),
+        })
+    }
+
+    /// Load the identity in `dir`, creating one on first use. New identities
+    /// are recorded in the directory's key rotation history.
+    pub fn load_or_create(dir: &Path) -> Result<Self> {
+        if dir.join(IDENTITY_FILE).exists() {
+            return Self::load(dir);
+        }
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+
+        let mut identity = Self::generate(String::new())?;
+        let mut rotation = KeyRotationManager::new(dir);
+        identity.key_id = rotation.generate_versioned_identity(identity.public().key_material())?;
+
+        let stored = StoredIdentity {
+            key_id: identity.key_id.clone(),
+            signing_key: STANDARD.encode(identity.signing.to_bytes()),
+            encryption_key: STANDARD.encode(identity.encryption.to_bytes()),
+        };
+        let path = dir.join(IDENTITY_FILE);
+        std::fs::write(&path, serde_json::to_string_pretty(&stored)?)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(identity)
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn public(&self) -> AgentPublicKey {
+        AgentPublicKey {
+            key_id: self.key_id.clone(),
+            signing: self.signing.verifying_key(),
+            encryption: X25519PublicKey::from(&self.encryption),
+        }
+    }
+
+    pub fn sign(&self, message: &[u8]) -> String {
+        STANDARD.encode(self.signing.sign(message).to_bytes())
+    }
+
+    /// Encrypt `plaintext` for `recipient` and sign the result.
+    pub fn seal(
+        &self,
+        header: EnvelopeHeader,
+        recipient: &AgentPublicKey,
+        plaintext: &[u8],
+    ) -> Result<SealedEnvelope> {
+        let ephemeral = StaticSecret::from(random_bytes()?);
+        let ephemeral_public = X25519PublicKey::from(&ephemeral);
+        let key = derive_key(
+            &ephemeral.diffie_hellman(&recipient.encryption),
+            &ephemeral_public,
+            &recipient.encryption,
+        );
+        let nonce: [u8; 12] = random_bytes()?;
+
+        let mut envelope = SealedEnvelope {
+            header,
+            sender_key_id: self.key_id.clone(),
+            ephemeral_key: STANDARD.encode(ephemeral_public.as_bytes()),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: String::new(),
+            signature: String::new(),
+        };
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad: &envelope.header.aad(),
+                },
+            )
+            .map_err(|e| anyhow!("encryption failed: {e}"))?;
+        envelope.ciphertext = STANDARD.encode(ciphertext);
+        envelope.signature = self.sign(&envelope.signed_bytes());
+        Ok(envelope)
+    }
+
+    /// Verify `envelope` against the sender's key and decrypt it.
+    pub fn open(&self, envelope: &SealedEnvelope, sender: &AgentPublicKey) -> Result<Vec<u8>> {
+        sender.verify_envelope(envelope)?;
+        let ephemeral_public = X25519PublicKey::from(decode_key(&envelope.ephemeral_key)?);
+        let own_public = X25519PublicKey::from(&self.encryption);
+        let key = derive_key(
+            &self.encryption.diffie_hellman(&ephemeral_public),
+            &ephemeral_public,
+            &own_public,
+        );
+        let nonce = STANDARD
+            .decode(&envelope.nonce)
+            .context("invalid envelope nonce")?;
+        if nonce.len() != 12 {
+            bail!("invalid envelope nonce");
+        }
+        let ciphertext = STANDARD
+            .decode(&envelope.ciphertext)
+            .context("invalid envelope ciphertext")?;
+        ChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: &envelope.header.aad(),
+                },
+            )
+            .map_err(|_| anyhow!("envelope decryption failed"))
+    }
+}
+
+impl AgentPublicKey {
+    pub fn encode(&self) -> String {
+        format!(
+            "{PUBLIC_KEY_PREFIX}.{}.{}",
+            self.key_id,
+            self.key_material()
+        )
+    }
+
+    /// `<ed25519 base64>.<x25519 base64>`, as recorded in the key rotation history.
+    pub fn key_material(&self) -> String {
+        format!(
+            "{}.{}",
+            STANDARD.encode(self.signing.as_bytes()),
+            STANDARD.encode(self.encryption.as_bytes())
+        )
+    }
+
+    pub fn decode(encoded: &str) -> Result<Self> {
+        let parts: Vec<&str> = encoded.trim().split('.').collect();
+        let [PUBLIC_KEY_PREFIX, key_id, signing, encryption] = parts.as_slice() else {
+            bail!("invalid relay public key");
+        };
+        let signing = VerifyingKey::from_bytes(&decode_key(signing)?)
+            .map_err(|e| anyhow!("invalid relay signing key: {e}"))?;
+        Ok(Self {
+            key_id: (*key_id).to_string(),
+            signing,
+            encryption: X25519PublicKey::from(decode_key(encryption)?),
+        })
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &str) -> Result<()> {
+        let bytes: [u8; 64] = STANDARD
+            .decode(signature)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .context("invalid signature encoding")?;
+        self.signing
+            .verify(message, &Signature::from_bytes(&bytes))
+            .map_err(|_| anyhow!("signature verification failed"))
+    }
+
+    /// Check that `envelope` was signed by this key.
+    pub fn verify_envelope(&self, envelope: &SealedEnvelope) -> Result<()> {
+        if envelope.sender_key_id != self.key_id {
+            bail!(
+                "envelope signed with key {}, expected {}",
+                envelope.sender_key_id,
+                self.key_id
+            );
+        }
+        self.verify(&envelope.signed_bytes(), &envelope.signature)
+    }
+}
+
+/// Routing metadata, authenticated as AEAD associated data and by the signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvelopeHeader {
+    pub id: String,
+    pub from_container: String,
+    pub to_container: String,
+    /// Unix seconds
+    pub created_at: i64,
+    /// Unix seconds; undelivered envelopes are dropped after this
+    pub expires_at: i64,
+}
+
+impl EnvelopeHeader {
+    pub fn new(from_container: &str, to_container: &str, ttl_secs: i64) -> Self {
+        let now = chrono::Utc::now().timestamp();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            from_container: from_container.to_string(),
+            to_container: to_container.to_string(),
+            created_at: now,
+            expires_at: now + ttl_secs.max(1),
+        }
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at <= now
+    }
+
+    fn aad(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for field in [
+            self.id.as_bytes(),
+            self.from_container.as_bytes(),
+            self.to_container.as_bytes(),
+            &self.created_at.to_be_bytes(),
+            &self.expires_at.to_be_bytes(),
+        ] {
+            push_field(&mut out, field);
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SealedEnvelope {
+    #[serde(flatten)]
+    pub header: EnvelopeHeader,
+    pub sender_key_id: String,
+    pub ephemeral_key: String,
+    pub nonce: String,
+    pub ciphertext: String,
+    pub signature: String,
+}
+
+impl SealedEnvelope {
+    fn signed_bytes(&self) -> Vec<u8> {
+        let mut out = KDF_CONTEXT.to_vec();
+        push_field(&mut out, &self.header.aad());
+        for field in [
+            &self.sender_key_id,
+            &self.ephemeral_key,
+            &self.nonce,
+            &self.ciphertext,
+        ] {
+            push_field(&mut out, field.as_bytes());
+        }
+        out
+    }
+}
+
+/// Remembers envelope ids until they expire so each is accepted at most once.
+#[derive(Debug, Default)]
+pub struct ReplayGuard {
+    seen: HashMap<String, i64>,
+}
+
+impl ReplayGuard {
+    pub fn check(&mut self, header: &EnvelopeHeader, now: i64) -> Result<()> {
+        self.seen.retain(|_, expires_at| *expires_at > now);
+        if header.is_expired(now) {
+            bail!("message {} has expired", header.id);
+        }
+        if header.created_at > now + MAX_CLOCK_SKEW_SECS {
+            bail!("message {} is dated in the future", header.id);
+        }
+        if self.seen.contains_key(&header.id) {
+            bail!("message {} was already delivered (replay)", header.id);
+        }
+        self.seen.insert(header.id.clone(), header.expires_at);
+        Ok(())
+    }
+}
+
+fn derive_key(
+    shared: &x25519_dalek::SharedSecret,
+    ephemeral: &X25519PublicKey,
+    recipient: &X25519PublicKey,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(KDF_CONTEXT);
+    hasher.update(shared.as_bytes());
+    hasher.update(ephemeral.as_bytes());
+    hasher.update(recipient.as_bytes());
+    hasher.finalize().into()
+}
+
+fn push_field(out: &mut Vec<u8>, field: &[u8]) {
+    out.extend_from_slice(&(field.len() as u64).to_be_bytes());
+    out.extend_from_slice(field);
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|e| anyhow!("RNG failed: {e}"))?;
+    Ok(bytes)
+}
+
+fn decode_key(encoded: &str) -> Result<[u8; 32]> {
+    STANDARD
+        .decode(encoded)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .context("invalid 32-byte key encoding")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sealed(from: &AgentIdentity, to: &AgentIdentity, text: &str) -> SealedEnvelope {
+        from.seal(
+            EnvelopeHeader::new("a", "b", 60),
+            &to.public(),
+            text.as_bytes(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn seal_and_open_round_trip() {
+        let alice = AgentIdentity::generate("ka".into()).unwrap();
+        let bob = AgentIdentity::generate("kb".into()).unwrap();
+        let envelope = sealed(&alice, &bob, "hello bob");
+        assert!(!envelope.ciphertext.contains("hello"));
+        assert_eq!(bob.open(&envelope, &alice.public()).unwrap(), b"hello bob");
+
+        // Only the recipient can decrypt, and only the sender's key verifies.
+        let eve = AgentIdentity::generate("ke".into()).unwrap();
+        assert!(eve.open(&envelope, &alice.public()).is_err());
+        assert!(bob.open(&envelope, &eve.public()).is_err());
+    }
+
+    #[test]
+    fn tampered_envelopes_are_rejected() {
+        let alice = AgentIdentity::generate("ka".into()).unwrap();
+        let bob = AgentIdentity::generate("kb".into()).unwrap();
+
+        let mut rerouted = sealed(&alice, &bob, "hi");
+        rerouted.header.to_container = "c".into();
+        assert!(bob.open(&rerouted, &alice.public()).is_err());
+
+        let mut extended = sealed(&alice, &bob, "hi");
+        extended.header.expires_at += 3600;
+        assert!(alice.public().verify_envelope(&extended).is_err());
+    }
+
+    #[test]
+    fn public_key_encoding_round_trips() {
+        let identity = AgentIdentity::generate("key-1".into()).unwrap();
+        let encoded = identity.public().encode();
+        assert!(encoded.starts_with("zcr1.key-1."));
+        assert_eq!(AgentPublicKey::decode(&encoded).unwrap(), identity.public());
+        assert!(AgentPublicKey::decode("zcr1.only.three").is_err());
+    }
+
+    #[test]
+    fn identity_is_persisted_and_recorded_in_rotation_history() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let created = AgentIdentity::load_or_create(tmp.path()).unwrap();
+        let loaded = AgentIdentity::load_or_create(tmp.path()).unwrap();
+        assert_eq!(created.public(), loaded.public());
+
+        let rotation = KeyRotationManager::new(tmp.path());
+        let current = rotation.get_current_identity().unwrap();
+        assert_eq!(current.key_id, created.key_id());
+        assert_eq!(current.public_key, created.public().key_material());
+    }
+
+    #[test]
+    fn replay_guard_rejects_duplicates_and_expired() {
+        let mut guard = ReplayGuard::default();
+        let header = EnvelopeHeader::new("a", "b", 60);
+        let now = header.created_at;
+        guard.check(&header, now).unwrap();
+        assert!(guard.check(&header, now + 1).is_err());
+        assert!(guard.check(&header, header.expires_at).is_err());
+
+        let mut future = EnvelopeHeader::new("a", "b", 60);
+        future.created_at = now + MAX_CLOCK_SKEW_SECS + 10;
+        assert!(guard.check(&future, now).is_err());
+    }
+}
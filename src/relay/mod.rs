@@ -1,9 +1,19 @@
-use anyhow::Result;
+pub mod client;
+pub mod envelope;
+
+pub use client::{ReceivedMessage, RelayClient};
+pub use envelope::{AgentIdentity, AgentPublicKey, EnvelopeHeader, ReplayGuard, SealedEnvelope};
+
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{mpsc, RwLock};
 
+/// Undelivered messages kept per offline container; the oldest are dropped first.
+const MAX_QUEUED_PER_CONTAINER: usize = 256;
+
+/// Transport wrapper around a JSON-encoded [`SealedEnvelope`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RelayMessage {
     pub id: String,
@@ -13,38 +23,194 @@ pub struct RelayMessage {
     pub timestamp: String,
 }
 
+impl RelayMessage {
+    /// Encrypt `plaintext` from `identity` (running in `from`) to container `to`.
+    pub fn seal(
+        identity: &AgentIdentity,
+        from: &str,
+        to: &str,
+        recipient: &AgentPublicKey,
+        plaintext: &[u8],
+        ttl_secs: i64,
+    ) -> Result<Self> {
+        let envelope = identity.seal(
+            EnvelopeHeader::new(from, to, ttl_secs),
+            recipient,
+            plaintext,
+        )?;
+        Ok(Self {
+            id: envelope.header.id.clone(),
+            from_container: from.to_string(),
+            to_container: to.to_string(),
+            payload: serde_json::to_vec(&envelope)?,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Decode the envelope and check it matches the transport fields.
+    pub fn envelope(&self) -> Result<SealedEnvelope> {
+        let envelope: SealedEnvelope =
+            serde_json::from_slice(&self.payload).context("invalid relay envelope")?;
+        if envelope.header.id != self.id
+            || envelope.header.from_container != self.from_container
+            || envelope.header.to_container != self.to_container
+        {
+            bail!("relay envelope does not match message routing");
+        }
+        Ok(envelope)
+    }
+}
+
+/// How [`RelayServer::send`] handled a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Delivery {
+    /// Handed to the recipient's live subscription
+    Delivered,
+    /// Held until the recipient subscribes or polls, or the message expires
+    Queued,
+}
+
+#[derive(Default)]
+struct Routing {
+    inboxes: HashMap<String, mpsc::UnboundedSender<RelayMessage>>,
+    queues: HashMap<String, VecDeque<(i64, RelayMessage)>>,
+    replay: ReplayGuard,
+}
+
+/// A container's tenant and encoded [`AgentPublicKey`].
+struct Registration {
+    tenant_id: String,
+    public_key: String,
+}
+
+/// Routes sealed messages between registered containers of the same tenant.
+/// Senders are authenticated against their registered key; payloads stay
+/// encrypted.
 pub struct RelayServer {
-    container_keys: Arc<RwLock<HashMap<String, String>>>,
-    message_tx: broadcast::Sender<RelayMessage>,
+    container_keys: Arc<RwLock<HashMap<String, Registration>>>,
+    routing: RwLock<Routing>,
 }
 
 impl RelayServer {
     pub fn new() -> Self {
-        let (message_tx, _) = broadcast::channel(1000);
         Self {
             container_keys: Arc::new(RwLock::new(HashMap::new())),
-            message_tx,
+            routing: RwLock::new(Routing::default()),
         }
     }
 
-    pub async fn register_container(&self, container_id: String, public_key: String) {
+    /// Register a container of `tenant_id` with its encoded [`AgentPublicKey`],
+    /// replacing any previous key.
+    pub async fn register_container(
+        &self,
+        container_id: String,
+        tenant_id: String,
+        public_key: String,
+    ) -> Result<()> {
+        AgentPublicKey::decode(&public_key)?;
         let mut keys = self.container_keys.write().await;
-        keys.insert(container_id, public_key);
+        keys.insert(
+            container_id,
+            Registration {
+                tenant_id,
+                public_key,
+            },
+        );
+        Ok(())
     }
 
     pub async fn get_container_key(&self, container_id: &str) -> Option<String> {
         let keys = self.container_keys.read().await;
-        keys.get(container_id).cloned()
+        keys.get(container_id).map(|r| r.public_key.clone())
+    }
+
+    pub async fn container_tenant(&self, container_id: &str) -> Option<String> {
+        let keys = self.container_keys.read().await;
+        keys.get(container_id).map(|r| r.tenant_id.clone())
+    }
+
+    async fn public_key(&self, container_id: &str) -> Result<AgentPublicKey> {
+        let encoded = self
+            .get_container_key(container_id)
+            .await
+            .with_context(|| format!("unknown container {container_id}"))?;
+        AgentPublicKey::decode(&encoded)
+    }
+
+    /// Authenticate and route a message to its recipient, queueing it while
+    /// the recipient is offline. Both containers must belong to one tenant.
+    pub async fn send(&self, msg: RelayMessage) -> Result<Delivery> {
+        let envelope = msg.envelope()?;
+        self.public_key(&msg.from_container)
+            .await?
+            .verify_envelope(&envelope)?;
+        self.public_key(&msg.to_container).await?;
+        if self.container_tenant(&msg.from_container).await
+            != self.container_tenant(&msg.to_container).await
+        {
+            bail!(
+                "container {} cannot message {} in another tenant",
+                msg.from_container,
+                msg.to_container
+            );
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let mut routing = self.routing.write().await;
+        routing.replay.check(&envelope.header, now)?;
+
+        if let Some(inbox) = routing.inboxes.get(&msg.to_container) {
+            match inbox.send(msg) {
+                Ok(()) => return Ok(Delivery::Delivered),
+                Err(mpsc::error::SendError(msg)) => {
+                    let to = msg.to_container.clone();
+                    routing.inboxes.remove(&to);
+                    enqueue(&mut routing, envelope.header.expires_at, msg);
+                }
+            }
+        } else {
+            enqueue(&mut routing, envelope.header.expires_at, msg);
+        }
+        Ok(Delivery::Queued)
     }
 
-    #[inline]
-    pub fn subscribe(&self) -> broadcast::Receiver<RelayMessage> {
-        self.message_tx.subscribe()
+    /// Live delivery for `container_id`. Queued messages are sent first; a new
+    /// subscription replaces the previous one.
+    pub async fn subscribe(&self, container_id: &str) -> mpsc::UnboundedReceiver<RelayMessage> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut routing = self.routing.write().await;
+        for msg in drain_queue(&mut routing, container_id) {
+            let _ = tx.send(msg);
+        }
+        routing.inboxes.insert(container_id.to_string(), tx);
+        rx
+    }
+
+    /// Take the unexpired queued messages for `container_id`.
+    pub async fn take_queued(&self, container_id: &str) -> Vec<RelayMessage> {
+        drain_queue(&mut *self.routing.write().await, container_id)
     }
+}
 
-    pub fn broadcast(&self, msg: RelayMessage) {
-        let _ = self.message_tx.send(msg);
+fn enqueue(routing: &mut Routing, expires_at: i64, msg: RelayMessage) {
+    let queue = routing.queues.entry(msg.to_container.clone()).or_default();
+    if queue.len() >= MAX_QUEUED_PER_CONTAINER {
+        queue.pop_front();
     }
+    queue.push_back((expires_at, msg));
+}
+
+fn drain_queue(routing: &mut Routing, container_id: &str) -> Vec<RelayMessage> {
+    let now = chrono::Utc::now().timestamp();
+    routing
+        .queues
+        .remove(container_id)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(expires_at, _)| *expires_at > now)
+        .map(|(_, msg)| msg)
+        .collect()
 }
 
 impl Default for RelayServer {
@@ -52,3 +218,86 @@ impl Default for RelayServer {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn registered(relay: &RelayServer, id: &str) -> AgentIdentity {
+        registered_in(relay, id, "t1").await
+    }
+
+    async fn registered_in(relay: &RelayServer, id: &str, tenant: &str) -> AgentIdentity {
+        let identity = AgentIdentity::generate(format!("key-{id}")).unwrap();
+        relay
+            .register_container(id.into(), tenant.into(), identity.public().encode())
+            .await
+            .unwrap();
+        identity
+    }
+
+    #[tokio::test]
+    async fn routes_only_to_recipient_and_queues_offline() {
+        let relay = RelayServer::new();
+        let alice = registered(&relay, "alice").await;
+        let bob = registered(&relay, "bob").await;
+        let _carol = registered(&relay, "carol").await;
+        let mut carol_inbox = relay.subscribe("carol").await;
+
+        let msg = RelayMessage::seal(&alice, "alice", "bob", &bob.public(), b"queued", 60).unwrap();
+        assert_eq!(relay.send(msg).await.unwrap(), Delivery::Queued);
+
+        let mut bob_inbox = relay.subscribe("bob").await;
+        let queued = bob_inbox.recv().await.unwrap();
+        let envelope = queued.envelope().unwrap();
+        assert_eq!(bob.open(&envelope, &alice.public()).unwrap(), b"queued");
+
+        let msg = RelayMessage::seal(&alice, "alice", "bob", &bob.public(), b"live", 60).unwrap();
+        assert_eq!(relay.send(msg).await.unwrap(), Delivery::Delivered);
+        assert!(bob_inbox.recv().await.is_some());
+        assert!(carol_inbox.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_forged_replayed_and_expired_messages() {
+        let relay = RelayServer::new();
+        let alice = registered(&relay, "alice").await;
+        let bob = registered(&relay, "bob").await;
+
+        // Signed by bob but claiming to come from alice.
+        let forged = RelayMessage::seal(&bob, "alice", "bob", &bob.public(), b"x", 60).unwrap();
+        assert!(relay.send(forged).await.is_err());
+
+        let msg = RelayMessage::seal(&alice, "alice", "bob", &bob.public(), b"x", 60).unwrap();
+        relay.send(msg.clone()).await.unwrap();
+        assert!(relay.send(msg).await.is_err());
+
+        let mut stale =
+            RelayMessage::seal(&alice, "alice", "bob", &bob.public(), b"x", 60).unwrap();
+        let mut envelope = stale.envelope().unwrap();
+        envelope.header.expires_at = envelope.header.created_at - 1;
+        stale.payload = serde_json::to_vec(&envelope).unwrap();
+        assert!(relay.send(stale).await.is_err());
+
+        let unknown =
+            RelayMessage::seal(&alice, "alice", "nobody", &bob.public(), b"x", 60).unwrap();
+        assert!(relay.send(unknown).await.is_err());
+        assert_eq!(relay.take_queued("bob").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_messages_across_tenants() {
+        let relay = RelayServer::new();
+        let alice = registered_in(&relay, "alice", "t1").await;
+        let mallory = registered_in(&relay, "mallory", "t2").await;
+
+        let msg =
+            RelayMessage::seal(&alice, "alice", "mallory", &mallory.public(), b"x", 60).unwrap();
+        assert!(relay.send(msg).await.is_err());
+        let msg =
+            RelayMessage::seal(&mallory, "mallory", "alice", &alice.public(), b"x", 60).unwrap();
+        assert!(relay.send(msg).await.is_err());
+        assert!(relay.take_queued("alice").await.is_empty());
+        assert!(relay.take_queued("mallory").await.is_empty());
+    }
+}
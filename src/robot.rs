@@ -0,0 +1,373 @@
+//! Robot kit bridge — registers `zeroclaw-robot-kit` tools with the agent.
+//!
+//! The robot kit defines its own `Tool` trait; [`RobotTool`] adapts each kit
+//! tool to [`crate::tools::Tool`]. One robot runtime is shared per process,
+//! so every agent (channels, gateway, cron) drives the same robot through the
+//! same `SafetyMonitor`. The monitor's loop runs in the background, fed by
+//! the simulator's LIDAR when `robot.toml` selects the sim backend. Its safety
+//! events are recorded as [`ObserverEvent::RobotSafety`], and an engaged
+//! `zeroclaw estop` (kill-all) triggers the robot's emergency stop until it is
+//! resumed.
+
+use crate::config::Config;
+use crate::observability::{Observer, ObserverEvent};
+use crate::security::EstopManager;
+use crate::tools::{Tool, ToolResult};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use zeroclaw_robot_kit as kit;
+
+/// How often the estop state file is checked.
+const ESTOP_POLL: Duration = Duration::from_millis(500);
+
+/// How often the simulator publishes a LIDAR reading to the safety monitor.
+const SIM_SENSOR_PERIOD: Duration = Duration::from_millis(200);
+
+/// Robot kit tools in registration order.
+const TOOL_NAMES: [&str; 6] = ["drive", "look", "listen", "speak", "sense", "emote"];
+
+static RUNTIME: LazyLock<parking_lot::Mutex<Option<Arc<RobotRuntime>>>> =
+    LazyLock::new(|| parking_lot::Mutex::new(None));
+
+/// Adapts a robot kit tool to the agent's tool trait.
+pub struct RobotTool {
+    inner: Arc<dyn kit::Tool>,
+}
+
+impl RobotTool {
+    pub fn new(inner: Arc<dyn kit::Tool>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl Tool for RobotTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.inner.parameters_schema()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<ToolResult> {
+        let result = self.inner.execute(args).await?;
+        Ok(ToolResult {
+            success: result.success,
+            output: result.output,
+            error: result.error,
+        })
+    }
+}
+
+/// The robot's tools and safety monitor, shared by every agent in the process.
+struct RobotRuntime {
+    config_path: Option<String>,
+    tools: Vec<Arc<dyn kit::Tool>>,
+    safety: Arc<kit::SafetyMonitor>,
+    sensor_feed: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl Drop for RobotRuntime {
+    fn drop(&mut self) {
+        self.safety.shutdown();
+        if let Some(feed) = &self.sensor_feed {
+            feed.abort();
+        }
+    }
+}
+
+impl RobotRuntime {
+    fn shared(config: &Config) -> Result<Arc<Self>> {
+        let mut current = RUNTIME.lock();
+        if let Some(runtime) = current
+            .as_ref()
+            .filter(|r| r.config_path == config.robot.config_path)
+        {
+            return Ok(Arc::clone(runtime));
+        }
+        let runtime = Arc::new(Self::start(config)?);
+        *current = Some(Arc::clone(&runtime));
+        Ok(runtime)
+    }
+
+    fn start(config: &Config) -> Result<Self> {
+        let kit_config = match config.robot.config_path.as_deref() {
+            Some(path) => kit::RobotConfig::load(Path::new(shellexpand::tilde(path).as_ref()))
+                .with_context(|| format!("Failed to load robot config {path}"))?,
+            None => kit::RobotConfig::default(),
+        };
+        let (safety, _) = kit::SafetyMonitor::new(kit_config.safety.clone());
+        let safety = Arc::new(safety);
+        let drive: Arc<dyn kit::Tool> = Arc::new(kit::DriveTool::new(kit_config.clone()));
+        let mut sensor_feed = None;
+
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                let observer: Arc<dyn Observer> =
+                    Arc::from(crate::observability::create_observer(&config.observability));
+                handle.spawn(forward_safety_events(safety.subscribe(), observer));
+                let (sensor_tx, sensor_rx) = mpsc::channel(64);
+                handle.spawn({
+                    let safety = Arc::clone(&safety);
+                    async move { safety.run(sensor_rx).await }
+                });
+                if let Some(world) = sim_world(&kit_config) {
+                    world.connect(sensor_tx);
+                    sensor_feed = Some(world.spawn_sensor_feed(SIM_SENSOR_PERIOD));
+                }
+                if config.security.estop.enabled {
+                    handle.spawn(watch_estop(
+                        config.clone(),
+                        Arc::clone(&safety),
+                        Arc::clone(&drive),
+                    ));
+                }
+            }
+            Err(_) => {
+                tracing::warn!("No async runtime; robot safety events and estop are not monitored");
+            }
+        }
+
+        Ok(Self {
+            config_path: config.robot.config_path.clone(),
+            safety: Arc::clone(&safety),
+            sensor_feed,
+            tools: vec![
+                Arc::new(kit::SafeDrive::new(drive, safety)),
+                Arc::new(kit::LookTool::new(kit_config.clone())),
+                Arc::new(kit::ListenTool::new(kit_config.clone())),
+                Arc::new(kit::SpeakTool::new(kit_config.clone())),
+                Arc::new(kit::SenseTool::new(kit_config.clone())),
+                Arc::new(kit::EmoteTool::new(kit_config)),
+            ],
+        })
+    }
+}
+
+/// Robot tools enabled by `[robot]`, adapted to the agent's tool trait.
+pub fn robot_tools(config: &Config) -> Result<Vec<Arc<dyn Tool>>> {
+    if !config.robot.enabled {
+        return Ok(Vec::new());
+    }
+    for name in &config.robot.tools {
+        if !TOOL_NAMES.contains(&name.as_str()) {
+            bail!(
+                "Unknown robot tool '{name}' (available: {})",
+                TOOL_NAMES.join(", ")
+            );
+        }
+    }
+    let runtime = RobotRuntime::shared(config)?;
+    Ok(runtime
+        .tools
+        .iter()
+        .filter(|tool| {
+            config.robot.tools.is_empty() || config.robot.tools.iter().any(|n| n == tool.name())
+        })
+        .map(|tool| Arc::new(RobotTool::new(Arc::clone(tool))) as Arc<dyn Tool>)
+        .collect())
+}
+
+/// The simulated world the drive and sense tools share, when either uses it.
+fn sim_world(kit_config: &kit::RobotConfig) -> Option<Arc<kit::SimWorld>> {
+    if kit_config.drive.backend != "sim" && kit_config.sensors.lidar_type != "sim" {
+        return None;
+    }
+    kit::SimWorld::shared(&kit_config.sim)
+        .map_err(|e| tracing::warn!("Simulator sensors unavailable: {e:#}"))
+        .ok()
+}
+
+fn observer_event(event: &kit::SafetyEvent) -> Option<ObserverEvent> {
+    let (kind, detail) = match event {
+        kit::SafetyEvent::ObstacleDetected { distance, angle } => (
+            "obstacle_detected",
+            format!("Obstacle at {distance:.2}m ({angle}°)"),
+        ),
+        kit::SafetyEvent::EmergencyStop { reason } => ("emergency_stop", reason.clone()),
+        kit::SafetyEvent::WatchdogTimeout => (
+            "watchdog_timeout",
+            "No drive commands within the watchdog period".to_string(),
+        ),
+        // Every drive call is approved or denied; tool call events cover approvals.
+        kit::SafetyEvent::MovementApproved => return None,
+        kit::SafetyEvent::MovementDenied { reason } => ("movement_denied", reason.clone()),
+        kit::SafetyEvent::BumpDetected { sensor } => ("bump_detected", sensor.clone()),
+        kit::SafetyEvent::Recovered => ("recovered", "Ready to move".to_string()),
+    };
+    Some(ObserverEvent::RobotSafety {
+        kind: kind.to_string(),
+        detail,
+    })
+}
+
+async fn forward_safety_events(
+    mut events: broadcast::Receiver<kit::SafetyEvent>,
+    observer: Arc<dyn Observer>,
+) {
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                if let Some(event) = observer_event(&event) {
+                    observer.record_event(&event);
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                tracing::warn!("Dropped {missed} robot safety events");
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+fn estop_engaged(config: &Config) -> bool {
+    config
+        .config_path
+        .parent()
+        .and_then(|dir| EstopManager::load(&config.security.estop, dir).ok())
+        .is_some_and(|manager| manager.status().kill_all)
+}
+
+/// Hold the robot in emergency stop while the kill-all estop is engaged.
+async fn watch_estop(config: Config, safety: Arc<kit::SafetyMonitor>, drive: Arc<dyn kit::Tool>) {
+    let mut engaged = false;
+    let mut stopped = false;
+    let mut poll = tokio::time::interval(ESTOP_POLL);
+    loop {
+        poll.tick().await;
+        if estop_engaged(&config) {
+            if !engaged {
+                safety.emergency_stop("ZeroClaw estop engaged").await;
+                engaged = true;
+                stopped = false;
+            }
+            // The drive rate limit can reject a stop right after a move; retry.
+            if !stopped {
+                stopped = drive
+                    .execute(serde_json::json!({ "action": "stop" }))
+                    .await
+                    .is_ok_and(|result| result.success);
+            }
+        } else if engaged {
+            safety.reset_estop().await;
+            engaged = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::EstopLevel;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn registers_selected_robot_tools() {
+        let mut config = Config::default();
+        assert!(robot_tools(&config).unwrap().is_empty());
+
+        config.robot.enabled = true;
+        let names: Vec<String> = robot_tools(&config)
+            .unwrap()
+            .iter()
+            .map(|t| t.name().to_string())
+            .collect();
+        assert_eq!(names, TOOL_NAMES);
+
+        config.robot.tools = vec!["drive".into(), "sense".into()];
+        let tools = robot_tools(&config).unwrap();
+        assert_eq!(tools.len(), 2);
+        let stop = tools[0]
+            .execute(serde_json::json!({ "action": "stop" }))
+            .await
+            .unwrap();
+        assert!(stop.success, "{stop:?}");
+
+        config.robot.tools = vec!["fly".into()];
+        assert!(robot_tools(&config).is_err());
+    }
+
+    #[test]
+    fn safety_events_map_to_observer_events() {
+        let event = observer_event(&kit::SafetyEvent::EmergencyStop {
+            reason: "button".into(),
+        });
+        assert!(matches!(
+            event,
+            Some(ObserverEvent::RobotSafety { ref kind, ref detail })
+                if kind == "emergency_stop" && detail == "button"
+        ));
+        assert!(observer_event(&kit::SafetyEvent::MovementApproved).is_none());
+    }
+
+    #[tokio::test]
+    async fn safety_monitor_runs_on_simulated_sensors() {
+        let tmp = TempDir::new().unwrap();
+        let map = tmp.path().join("map.toml");
+        std::fs::write(
+            &map,
+            "robot_radius = 0.1\n[[obstacles]]\npoints = [[0.2, -2.0], [0.2, 2.0]]\n",
+        )
+        .unwrap();
+        let mut kit_config = kit::RobotConfig::default();
+        kit_config.drive.backend = "sim".into();
+        kit_config.sensors.lidar_type = "sim".into();
+        kit_config.sim.map = Some(map);
+        let robot_toml = tmp.path().join("robot.toml");
+        std::fs::write(&robot_toml, toml::to_string(&kit_config).unwrap()).unwrap();
+
+        let mut config = Config::default();
+        config.robot.config_path = Some(robot_toml.to_string_lossy().into_owned());
+        let runtime = RobotRuntime::start(&config).unwrap();
+        let mut events = runtime.safety.subscribe();
+
+        let obstacle = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(event @ kit::SafetyEvent::ObstacleDetected { .. }) = events.recv().await {
+                    return event;
+                }
+            }
+        })
+        .await
+        .expect("the monitor never reported the wall");
+        assert!(matches!(
+            observer_event(&obstacle),
+            Some(ObserverEvent::RobotSafety { ref kind, .. }) if kind == "obstacle_detected"
+        ));
+        assert!(!runtime.safety.can_move().await);
+    }
+
+    #[tokio::test]
+    async fn estop_triggers_robot_emergency_stop() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = Config {
+            config_path: tmp.path().join("config.toml"),
+            ..Config::default()
+        };
+        config.security.estop.enabled = true;
+        let kit_config = kit::RobotConfig::default();
+        let (safety, mut events) = kit::SafetyMonitor::new(kit_config.safety.clone());
+        let safety = Arc::new(safety);
+        let drive: Arc<dyn kit::Tool> = Arc::new(kit::DriveTool::new(kit_config));
+        let watcher = tokio::spawn(watch_estop(config.clone(), Arc::clone(&safety), drive));
+
+        let mut manager = EstopManager::load(&config.security.estop, tmp.path()).unwrap();
+        manager.engage(EstopLevel::KillAll).unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event, kit::SafetyEvent::EmergencyStop { .. }));
+        assert!(!safety.can_move().await);
+        watcher.abort();
+    }
+}
@@ -50,6 +50,109 @@ impl DockerRuntime {
 
         Ok(resolved)
     }
+
+    /// Network, resource, rootfs and workspace flags shared by one-shot and
+    /// long-lived containers. Explicit limits override the runtime config.
+    fn apply_isolation_flags(
+        &self,
+        process: &mut tokio::process::Command,
+        workspace_dir: &Path,
+        memory_limit_mb: Option<u64>,
+        cpu_limit: Option<f64>,
+    ) -> Result<()> {
+        let network = self.config.network.trim();
+        if !network.is_empty() {
+            process.arg("--network").arg(network);
+        }
+
+        if let Some(memory_limit_mb) = memory_limit_mb
+            .or(self.config.memory_limit_mb)
+            .filter(|mb| *mb > 0)
+        {
+            process.arg("--memory").arg(format!("{memory_limit_mb}m"));
+        }
+
+        if let Some(cpu_limit) = cpu_limit
+            .or(self.config.cpu_limit)
+            .filter(|cpus| *cpus > 0.0)
+        {
+            process.arg("--cpus").arg(cpu_limit.to_string());
+        }
+
+        if self.config.read_only_rootfs {
+            process.arg("--read-only");
+        }
+
+        if self.config.mount_workspace {
+            let host_workspace = self.workspace_mount_path(workspace_dir).with_context(|| {
+                format!(
+                    "Failed to validate workspace mount path {}",
+                    workspace_dir.display()
+                )
+            })?;
+
+            process
+                .arg("--volume")
+                .arg(format!("{}:/workspace:rw", host_workspace.display()))
+                .arg("--workdir")
+                .arg("/workspace");
+        }
+        Ok(())
+    }
+
+    /// `docker run` for a long-lived, named container running `image`'s entrypoint.
+    pub fn build_instance_command(
+        &self,
+        spec: &DockerInstanceSpec<'_>,
+        workspace_dir: &Path,
+    ) -> Result<tokio::process::Command> {
+        let mut process = tokio::process::Command::new("docker");
+        process
+            .arg("run")
+            .arg("--rm")
+            .arg("--init")
+            .arg("--name")
+            .arg(spec.name);
+        self.apply_isolation_flags(
+            &mut process,
+            workspace_dir,
+            spec.memory_limit_mb,
+            spec.cpu_limit,
+        )?;
+        if let Some(config_dir) = spec.config_dir {
+            process.arg("--volume").arg(format!(
+                "{}:{INSTANCE_CONFIG_MOUNT}:ro",
+                config_dir.display()
+            ));
+        }
+        for (key, value) in &spec.env {
+            process.arg("--env").arg(format!("{key}={value}"));
+        }
+        let image = spec
+            .image
+            .map(str::trim)
+            .filter(|image| !image.is_empty())
+            .unwrap_or_else(|| self.config.image.trim());
+        process.arg(image);
+        Ok(process)
+    }
+}
+
+/// Where [`DockerInstanceSpec::config_dir`] appears inside the container.
+pub const INSTANCE_CONFIG_MOUNT: &str = "/zeroclaw";
+
+/// A named, long-lived container started by [`DockerRuntime::build_instance_command`].
+#[derive(Debug, Clone, Default)]
+pub struct DockerInstanceSpec<'a> {
+    /// Container name (`docker run --name`)
+    pub name: &'a str,
+    /// Image to run; the runtime's configured image when `None`
+    pub image: Option<&'a str>,
+    pub memory_limit_mb: Option<u64>,
+    pub cpu_limit: Option<f64>,
+    /// Host directory mounted read-only at [`INSTANCE_CONFIG_MOUNT`]
+    pub config_dir: Option<&'a Path>,
+    pub env: Vec<(String, String)>,
 }
 
 impl RuntimeAdapter for DockerRuntime {
@@ -94,38 +197,7 @@ impl RuntimeAdapter for DockerRuntime {
             .arg("--rm")
             .arg("--init")
             .arg("--interactive");
-
-        let network = self.config.network.trim();
-        if !network.is_empty() {
-            process.arg("--network").arg(network);
-        }
-
-        if let Some(memory_limit_mb) = self.config.memory_limit_mb.filter(|mb| *mb > 0) {
-            process.arg("--memory").arg(format!("{memory_limit_mb}m"));
-        }
-
-        if let Some(cpu_limit) = self.config.cpu_limit.filter(|cpus| *cpus > 0.0) {
-            process.arg("--cpus").arg(cpu_limit.to_string());
-        }
-
-        if self.config.read_only_rootfs {
-            process.arg("--read-only");
-        }
-
-        if self.config.mount_workspace {
-            let host_workspace = self.workspace_mount_path(workspace_dir).with_context(|| {
-                format!(
-                    "Failed to validate workspace mount path {}",
-                    workspace_dir.display()
-                )
-            })?;
-
-            process
-                .arg("--volume")
-                .arg(format!("{}:/workspace:rw", host_workspace.display()))
-                .arg("--workdir")
-                .arg("/workspace");
-        }
+        self.apply_isolation_flags(&mut process, workspace_dir, None, None)?;
 
         process
             .arg(self.config.image.trim())
@@ -255,6 +327,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn docker_instance_command_names_container_and_applies_limits() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig {
+            memory_limit_mb: Some(512),
+            mount_workspace: true,
+            ..DockerRuntimeConfig::default()
+        });
+        let spec = DockerInstanceSpec {
+            name: "zeroclaw-abc",
+            image: Some("zeroclaw:latest"),
+            memory_limit_mb: Some(256),
+            cpu_limit: Some(2.0),
+            config_dir: Some(Path::new("/srv/zeroclaw/containers/abc")),
+            env: vec![("ZEROCLAW_WORKSPACE".into(), "/workspace".into())],
+        };
+        let cmd = runtime
+            .build_instance_command(&spec, &std::env::temp_dir())
+            .unwrap();
+        let debug = format!("{cmd:?}");
+        assert!(debug.contains("zeroclaw-abc"));
+        assert!(
+            debug.contains("256m"),
+            "spec limit overrides config: {debug}"
+        );
+        assert!(debug.contains("--cpus"));
+        assert!(debug.contains("ZEROCLAW_WORKSPACE=/workspace"));
+        assert!(debug.contains("/srv/zeroclaw/containers/abc:/zeroclaw:ro"));
+        assert!(debug.contains("zeroclaw:latest"));
+        assert!(!debug.contains("--interactive"));
+    }
+
     #[test]
     fn docker_no_memory_flag_when_not_configured() {
         let cfg = DockerRuntimeConfig {
@@ -2,10 +2,23 @@
 
 use crate::config::{SandboxBackend, SecurityConfig};
 use crate::security::traits::Sandbox;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-/// Create a sandbox based on auto-detection or explicit config
-pub fn create_sandbox(config: &SecurityConfig) -> Arc<dyn Sandbox> {
+/// Create a sandbox based on auto-detection or explicit config. Filesystem
+/// backends (Landlock) keep `workspace_dir` writable for the sandboxed command.
+pub fn create_sandbox(config: &SecurityConfig, workspace_dir: Option<&Path>) -> Arc<dyn Sandbox> {
+    create_sandbox_with_read_only(config, workspace_dir, &[])
+}
+
+/// Like [`create_sandbox`], but filesystem backends also let the command read
+/// `read_only` directories it cannot modify.
+pub fn create_sandbox_with_read_only(
+    config: &SecurityConfig,
+    workspace_dir: Option<&Path>,
+    read_only: &[PathBuf],
+) -> Arc<dyn Sandbox> {
+    let workspace_dir = workspace_dir.map(Path::to_path_buf);
     let backend = &config.sandbox.backend;
 
     // If explicitly disabled, return noop
@@ -20,8 +33,10 @@ pub fn create_sandbox(config: &SecurityConfig) -> Arc<dyn Sandbox> {
             {
                 #[cfg(target_os = "linux")]
                 {
-                    if let Ok(sandbox) = super::landlock::LandlockSandbox::new() {
-                        return Arc::new(sandbox);
+                    if let Ok(sandbox) =
+                        super::landlock::LandlockSandbox::with_workspace(workspace_dir)
+                    {
+                        return Arc::new(sandbox.with_read_only(read_only));
                     }
                 }
             }
@@ -57,6 +72,24 @@ pub fn create_sandbox(config: &SecurityConfig) -> Arc<dyn Sandbox> {
             );
             Arc::new(super::traits::NoopSandbox)
         }
+        SandboxBackend::Seccomp => {
+            #[cfg(all(feature = "sandbox-seccomp", target_os = "linux"))]
+            {
+                let profile = config.sandbox.seccomp_profile;
+                let sandbox = if config.sandbox.seccomp_landlock {
+                    super::seccomp::SeccompSandbox::with_landlock(profile, workspace_dir)
+                } else {
+                    super::seccomp::SeccompSandbox::new(profile)
+                };
+                if let Ok(sandbox) = sandbox {
+                    return Arc::new(sandbox.with_read_only(read_only));
+                }
+            }
+            tracing::warn!(
+                "Seccomp requested but not available, falling back to application-layer"
+            );
+            Arc::new(super::traits::NoopSandbox)
+        }
         SandboxBackend::Docker => {
             if let Ok(sandbox) = super::docker::DockerSandbox::new() {
                 return Arc::new(sandbox);
@@ -66,21 +99,59 @@ pub fn create_sandbox(config: &SecurityConfig) -> Arc<dyn Sandbox> {
         }
         SandboxBackend::Auto | SandboxBackend::None => {
             // Auto-detect best available
-            detect_best_sandbox()
+            detect_best_sandbox(workspace_dir, read_only)
         }
     }
 }
 
+/// Sandbox for the shell tool. Shell commands are only wrapped when
+/// `[security.sandbox]` asks for it, with `enabled = true` or a named backend;
+/// the default `auto` leaves them unsandboxed, as they were before.
+pub fn create_shell_sandbox(
+    config: &SecurityConfig,
+    workspace_dir: Option<&Path>,
+) -> Arc<dyn Sandbox> {
+    let requested = config.sandbox.enabled == Some(true)
+        || !matches!(
+            config.sandbox.backend,
+            SandboxBackend::Auto | SandboxBackend::None
+        );
+    if !requested {
+        return Arc::new(super::traits::NoopSandbox);
+    }
+    create_sandbox(config, workspace_dir)
+}
+
 /// Auto-detect the best available sandbox
-fn detect_best_sandbox() -> Arc<dyn Sandbox> {
+#[cfg_attr(
+    not(any(feature = "sandbox-seccomp", feature = "sandbox-landlock")),
+    allow(unused_variables)
+)]
+fn detect_best_sandbox(workspace_dir: Option<PathBuf>, read_only: &[PathBuf]) -> Arc<dyn Sandbox> {
     #[cfg(target_os = "linux")]
     {
-        // Try Landlock first (native, no dependencies)
+        // Try seccomp first (native syscall filter, stacked with Landlock when available)
+        #[cfg(feature = "sandbox-seccomp")]
+        {
+            if let Ok(sandbox) = super::seccomp::SeccompSandbox::with_landlock(
+                crate::config::SeccompProfile::Standard,
+                workspace_dir.clone(),
+            ) {
+                if sandbox.has_landlock() {
+                    tracing::info!("Seccomp + Landlock sandbox enabled");
+                } else {
+                    tracing::info!("Seccomp sandbox enabled");
+                }
+                return Arc::new(sandbox.with_read_only(read_only));
+            }
+        }
+
+        // Try Landlock next (native, no dependencies)
         #[cfg(feature = "sandbox-landlock")]
         {
-            if let Ok(sandbox) = super::landlock::LandlockSandbox::probe() {
+            if let Ok(sandbox) = super::landlock::LandlockSandbox::with_workspace(workspace_dir) {
                 tracing::info!("Landlock sandbox enabled (Linux kernel 5.13+)");
-                return Arc::new(sandbox);
+                return Arc::new(sandbox.with_read_only(read_only));
             }
         }
 
@@ -121,7 +192,7 @@ mod tests {
 
     #[test]
     fn detect_best_sandbox_returns_something() {
-        let sandbox = detect_best_sandbox();
+        let sandbox = detect_best_sandbox(None, &[]);
         // Should always return at least NoopSandbox
         assert!(sandbox.is_available());
     }
@@ -133,10 +204,11 @@ mod tests {
                 enabled: Some(false),
                 backend: SandboxBackend::None,
                 firejail_args: Vec::new(),
+                ..Default::default()
             },
             ..Default::default()
         };
-        let sandbox = create_sandbox(&config);
+        let sandbox = create_sandbox(&config, None);
         assert_eq!(sandbox.name(), "none");
     }
 
@@ -147,11 +219,46 @@ mod tests {
                 enabled: None, // Auto-detect
                 backend: SandboxBackend::Auto,
                 firejail_args: Vec::new(),
+                ..Default::default()
             },
             ..Default::default()
         };
-        let sandbox = create_sandbox(&config);
+        let sandbox = create_sandbox(&config, None);
         // Should return some sandbox (at least NoopSandbox)
         assert!(sandbox.is_available());
     }
+
+    #[test]
+    fn shell_sandbox_is_opt_in() {
+        let sandbox = create_shell_sandbox(&SecurityConfig::default(), None);
+        assert_eq!(sandbox.name(), "none");
+
+        let config = SecurityConfig {
+            sandbox: SandboxConfig {
+                backend: SandboxBackend::Seccomp,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let sandbox = create_shell_sandbox(&config, None);
+        assert_eq!(sandbox.name(), create_sandbox(&config, None).name());
+        #[cfg(all(feature = "sandbox-seccomp", target_os = "linux"))]
+        assert_eq!(sandbox.name(), "seccomp");
+    }
+
+    #[test]
+    fn explicit_seccomp_returns_seccomp_or_noop() {
+        let config = SecurityConfig {
+            sandbox: SandboxConfig {
+                enabled: Some(true),
+                backend: SandboxBackend::Seccomp,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let sandbox = create_sandbox(&config, None);
+        assert!(matches!(sandbox.name(), "seccomp" | "none"));
+        #[cfg(all(feature = "sandbox-seccomp", target_os = "linux"))]
+        assert_eq!(sandbox.name(), "seccomp");
+    }
 }
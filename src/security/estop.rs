@@ -55,6 +55,28 @@ impl EstopState {
             || !self.frozen_tools.is_empty()
     }
 
+    /// Short description of the engaged levels, or `"released"`.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.kill_all {
+            parts.push("kill_all".to_string());
+        }
+        if self.network_kill {
+            parts.push("network_kill".to_string());
+        }
+        if !self.blocked_domains.is_empty() {
+            parts.push(format!("domains: {}", self.blocked_domains.join(", ")));
+        }
+        if !self.frozen_tools.is_empty() {
+            parts.push(format!("tools: {}", self.frozen_tools.join(", ")));
+        }
+        if parts.is_empty() {
+            "released".to_string()
+        } else {
+            parts.join("; ")
+        }
+    }
+
     fn normalize(&mut self) {
         self.blocked_domains = dedup_sort(&self.blocked_domains);
         self.frozen_tools = dedup_sort(&self.frozen_tools);
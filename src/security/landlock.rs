@@ -4,7 +4,10 @@
 //! This module uses the pure-Rust `landlock` crate for filesystem access control.
 
 #[cfg(all(feature = "sandbox-landlock", target_os = "linux"))]
-use landlock::{AccessFs, PathBeneath, PathFd, Ruleset, RulesetAttr, RulesetCreatedAttr};
+use landlock::{
+    AccessFs, BitFlags, PathBeneath, PathFd, Ruleset, RulesetAttr, RulesetCreated,
+    RulesetCreatedAttr,
+};
 
 use crate::security::traits::Sandbox;
 use std::path::Path;
@@ -14,6 +17,7 @@ use std::path::Path;
 #[derive(Debug)]
 pub struct LandlockSandbox {
     workspace_dir: Option<std::path::PathBuf>,
+    read_only_dirs: Vec<std::path::PathBuf>,
 }
 
 #[cfg(all(feature = "sandbox-landlock", target_os = "linux"))]
@@ -31,7 +35,10 @@ impl LandlockSandbox {
             .and_then(|ruleset| ruleset.create());
 
         match test_ruleset {
-            Ok(_) => Ok(Self { workspace_dir }),
+            Ok(_) => Ok(Self {
+                workspace_dir,
+                read_only_dirs: Vec::new(),
+            }),
             Err(e) => {
                 tracing::debug!("Landlock not available: {}", e);
                 Err(std::io::Error::new(
@@ -47,89 +54,90 @@ impl LandlockSandbox {
         Self::new()
     }
 
-    /// Apply Landlock restrictions to the current process
-    fn apply_restrictions(&self) -> std::io::Result<()> {
-        let mut ruleset = Ruleset::default()
-            .handle_access(
-                AccessFs::ReadFile
-                    | AccessFs::WriteFile
-                    | AccessFs::ReadDir
-                    | AccessFs::RemoveDir
-                    | AccessFs::RemoveFile
-                    | AccessFs::MakeChar
-                    | AccessFs::MakeSock
-                    | AccessFs::MakeFifo
-                    | AccessFs::MakeBlock
-                    | AccessFs::MakeReg
-                    | AccessFs::MakeSym,
-            )
-            .and_then(|ruleset| ruleset.create())
-            .map_err(|e| std::io::Error::other(e.to_string()))?;
+    /// Also let the sandboxed command read (but not modify) `dirs`.
+    #[must_use]
+    pub fn with_read_only(mut self, dirs: &[std::path::PathBuf]) -> Self {
+        self.read_only_dirs.extend_from_slice(dirs);
+        self
+    }
 
-        // Allow workspace directory (read/write)
+    /// Build the ruleset in the parent: the workspace and `/tmp` are
+    /// writable, system and extra read-only directories are read-only and
+    /// everything else is denied. [`Sandbox::wrap_command`] enforces it in
+    /// the child.
+    fn ruleset(&self) -> std::io::Result<RulesetCreated> {
+        let handled = AccessFs::ReadFile
+            | AccessFs::WriteFile
+            | AccessFs::ReadDir
+            | AccessFs::RemoveDir
+            | AccessFs::RemoveFile
+            | AccessFs::MakeChar
+            | AccessFs::MakeDir
+            | AccessFs::MakeSock
+            | AccessFs::MakeFifo
+            | AccessFs::MakeBlock
+            | AccessFs::MakeReg
+            | AccessFs::MakeSym;
+        let read_only = AccessFs::ReadFile | AccessFs::ReadDir;
+        let writable = handled & !(AccessFs::MakeChar | AccessFs::MakeBlock);
+
+        let mut rules: Vec<(&Path, BitFlags<AccessFs>)> = vec![(Path::new("/tmp"), writable)];
         if let Some(ref workspace) = self.workspace_dir {
-            if workspace.exists() {
-                let workspace_fd =
-                    PathFd::new(workspace).map_err(|e| std::io::Error::other(e.to_string()))?;
-                ruleset = ruleset
-                    .add_rule(PathBeneath::new(
-                        workspace_fd,
-                        AccessFs::ReadFile | AccessFs::WriteFile | AccessFs::ReadDir,
-                    ))
-                    .map_err(|e| std::io::Error::other(e.to_string()))?;
-            }
+            rules.push((workspace, writable));
         }
+        // Executables, shared libraries and system configuration
+        for dir in ["/usr", "/bin", "/sbin", "/lib", "/lib64", "/etc"] {
+            rules.push((Path::new(dir), read_only));
+        }
+        for dir in &self.read_only_dirs {
+            rules.push((dir, read_only));
+        }
+        rules.push((
+            Path::new("/dev/null"),
+            AccessFs::ReadFile | AccessFs::WriteFile,
+        ));
 
-        // Allow /tmp for general operations
-        let tmp_fd =
-            PathFd::new(Path::new("/tmp")).map_err(|e| std::io::Error::other(e.to_string()))?;
-        ruleset = ruleset
-            .add_rule(PathBeneath::new(
-                tmp_fd,
-                AccessFs::ReadFile | AccessFs::WriteFile,
-            ))
-            .map_err(|e| std::io::Error::other(e.to_string()))?;
-
-        // Allow /usr and /bin for executing commands
-        let usr_fd =
-            PathFd::new(Path::new("/usr")).map_err(|e| std::io::Error::other(e.to_string()))?;
-        ruleset = ruleset
-            .add_rule(PathBeneath::new(
-                usr_fd,
-                AccessFs::ReadFile | AccessFs::ReadDir,
-            ))
-            .map_err(|e| std::io::Error::other(e.to_string()))?;
-
-        let bin_fd =
-            PathFd::new(Path::new("/bin")).map_err(|e| std::io::Error::other(e.to_string()))?;
-        ruleset = ruleset
-            .add_rule(PathBeneath::new(
-                bin_fd,
-                AccessFs::ReadFile | AccessFs::ReadDir,
-            ))
+        let mut ruleset = Ruleset::default()
+            .handle_access(handled)
+            .and_then(|ruleset| ruleset.create())
             .map_err(|e| std::io::Error::other(e.to_string()))?;
-
-        // Apply the ruleset
-        match ruleset.restrict_self() {
-            Ok(_) => {
-                tracing::debug!("Landlock restrictions applied successfully");
-                Ok(())
-            }
-            Err(e) => {
-                tracing::warn!("Failed to apply Landlock restrictions: {}", e);
-                Err(std::io::Error::other(e.to_string()))
+        for (path, access) in rules {
+            if !path.exists() {
+                continue;
             }
+            let fd = PathFd::new(path).map_err(|e| std::io::Error::other(e.to_string()))?;
+            ruleset = ruleset
+                .add_rule(PathBeneath::new(fd, access))
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
         }
+        Ok(ruleset)
     }
 }
 
 #[cfg(all(feature = "sandbox-landlock", target_os = "linux"))]
 impl Sandbox for LandlockSandbox {
-    fn wrap_command(&self, _cmd: &mut std::process::Command) -> std::io::Result<()> {
-        // Apply Landlock restrictions before executing the command
-        // Note: This affects the current process, not the child process
-        // Child processes inherit the Landlock restrictions
-        self.apply_restrictions()
+    fn wrap_command(&self, cmd: &mut std::process::Command) -> std::io::Result<()> {
+        use std::os::unix::process::CommandExt;
+
+        // Restricting the zeroclaw process itself would lock the agent out of
+        // its own files, so only the child restricts itself, right before exec.
+        let mut ruleset = Some(self.ruleset()?);
+        // SAFETY: the closure runs in the forked child before exec. The
+        // ruleset was built in the parent; `restrict_self` only issues the
+        // `prctl` and `landlock_restrict_self` syscalls.
+        unsafe {
+            cmd.pre_exec(move || {
+                let Some(ruleset) = ruleset.take() else {
+                    // A second spawn of the same command has no ruleset left.
+                    return Err(std::io::Error::from_raw_os_error(libc::EPERM));
+                };
+                ruleset
+                    .restrict_self()
+                    .map(|_| ())
+                    .map_err(|_| std::io::Error::from_raw_os_error(libc::EPERM))
+            });
+        }
+        Ok(())
     }
 
     fn is_available(&self) -> bool {
@@ -218,6 +226,61 @@ mod tests {
         assert_eq!(LandlockSandbox.name(), "landlock");
     }
 
+    #[cfg(all(feature = "sandbox-landlock", target_os = "linux"))]
+    #[test]
+    fn landlock_restricts_the_child_but_not_this_process() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let Ok(sandbox) = LandlockSandbox::with_workspace(Some(workspace.path().to_path_buf()))
+        else {
+            return;
+        };
+        let outside = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        let run = |script: String| {
+            let mut cmd = std::process::Command::new("sh");
+            cmd.args(["-c", &script]);
+            sandbox.wrap_command(&mut cmd).unwrap();
+            cmd.status()
+                .expect("sandboxed command should spawn")
+                .success()
+        };
+
+        assert!(run(format!("echo ok > {}/out", workspace.path().display())));
+        assert!(!run(format!("cat {} > /dev/null", outside.display())));
+        // The parent keeps full access.
+        assert!(std::fs::read_to_string(&outside).is_ok());
+        assert!(workspace.path().join("out").exists());
+    }
+
+    #[cfg(all(feature = "sandbox-landlock", target_os = "linux"))]
+    #[test]
+    fn read_only_dirs_are_readable_but_not_writable() {
+        // Not under /tmp, which the ruleset leaves writable.
+        let root = tempfile::TempDir::new_in(Path::new(env!("CARGO_MANIFEST_DIR")).join("target"))
+            .unwrap();
+        let workspace = root.path().join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+        std::fs::write(root.path().join("config.toml"), "x = 1").unwrap();
+        let Ok(sandbox) = LandlockSandbox::with_workspace(Some(workspace.clone())) else {
+            return;
+        };
+        let sandbox = sandbox.with_read_only(&[root.path().to_path_buf()]);
+        let run = |script: String| {
+            let mut cmd = std::process::Command::new("sh");
+            cmd.args(["-c", &script]);
+            sandbox.wrap_command(&mut cmd).unwrap();
+            cmd.status()
+                .expect("sandboxed command should spawn")
+                .success()
+        };
+
+        let config = root.path().join("config.toml");
+        assert!(run(format!("cat {} > /dev/null", config.display())));
+        assert!(!run(format!("echo y > {}", config.display())));
+        assert!(!run(format!("rm {}", config.display())));
+        assert!(run(format!("echo ok > {}/out", workspace.display())));
+        assert_eq!(std::fs::read_to_string(&config).unwrap(), "x = 1");
+    }
+
     #[test]
     fn landlock_with_none_workspace() {
         // Should work even without a workspace directory
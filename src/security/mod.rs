@@ -4,13 +4,16 @@
 //! [`SecurityPolicy`] defines autonomy levels, workspace boundaries, and
 //! access-control rules that are enforced across the tool and runtime subsystems.
 //! [`PairingGuard`] implements device pairing for channel authentication, and
-//! [`SecretStore`] handles encrypted credential storage.
+//! [`SecretStore`] handles encrypted credential storage, with
+//! [`SecretResolver`] resolving `vault://`, `env://`, `file://` and `cmd://`
+//! references in config.
 //!
 //! OS-level isolation is provided through the [`Sandbox`] trait defined in
 //! [`traits`], with pluggable backends including Docker, Firejail, Bubblewrap,
-//! and Landlock. The [`create_sandbox`] function selects the best available
-//! backend at runtime. An [`AuditLogger`] records security-relevant events for
-//! forensic review.
+//! Landlock, and seccomp-bpf. The [`create_sandbox`] function selects the best
+//! available backend at runtime. An [`AuditLogger`] records security-relevant
+//! events for forensic review, and [`tool_rules`] evaluates policy-as-code
+//! rules before every tool call.
 //!
 //! # Extension
 //!
@@ -32,13 +35,17 @@ pub mod landlock;
 pub mod otp;
 pub mod pairing;
 pub mod policy;
+#[cfg(all(feature = "sandbox-seccomp", target_os = "linux"))]
+pub mod seccomp;
+pub mod secret_resolver;
 pub mod secrets;
+pub mod tool_rules;
 pub mod traits;
 
 #[allow(unused_imports)]
 pub use audit::{AuditEvent, AuditEventType, AuditLogger};
 #[allow(unused_imports)]
-pub use detect::create_sandbox;
+pub use detect::{create_sandbox, create_sandbox_with_read_only, create_shell_sandbox};
 pub use domain_matcher::DomainMatcher;
 #[allow(unused_imports)]
 pub use estop::{EstopLevel, EstopManager, EstopState, ResumeSelector};
@@ -48,6 +55,8 @@ pub use otp::OtpValidator;
 pub use pairing::PairingGuard;
 pub use policy::{AutonomyLevel, SecurityPolicy};
 #[allow(unused_imports)]
+pub use secret_resolver::SecretResolver;
+#[allow(unused_imports)]
 pub use secrets::SecretStore;
 #[allow(unused_imports)]
 pub use traits::{NoopSandbox, Sandbox};
@@ -57,6 +57,16 @@ impl OtpValidator {
         Ok((validator, uri))
     }
 
+    /// Validator for an externally stored base32 secret, such as a user's
+    /// enrolled authenticator in the multitenant server.
+    pub fn from_base32_secret(config: &OtpConfig, encoded: &str) -> Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+            secret: decode_base32_secret(encoded)?,
+            cached_codes: Mutex::new(HashMap::new()),
+        })
+    }
+
     pub fn validate(&self, code: &str) -> Result<bool> {
         self.validate_at(code, unix_timestamp_now())
     }
@@ -105,8 +115,12 @@ impl OtpValidator {
     }
 
     pub fn otpauth_uri(&self) -> String {
+        self.otpauth_uri_for("zeroclaw")
+    }
+
+    pub fn otpauth_uri_for(&self, account: &str) -> String {
         let secret = encode_base32_secret(&self.secret);
-        let account = "zeroclaw";
+        let account = urlencoding::encode(account);
         format!(
             "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&period={period}",
             issuer = OTP_ISSUER,
@@ -121,6 +135,12 @@ impl OtpValidator {
     }
 }
 
+/// Fresh random 160-bit TOTP secret, base32-encoded.
+pub fn generate_base32_secret() -> String {
+    let raw: [u8; 20] = rand::random();
+    encode_base32_secret(&raw)
+}
+
 pub fn secret_file_path(zeroclaw_dir: &Path) -> PathBuf {
     zeroclaw_dir.join(OTP_SECRET_FILE)
 }
@@ -7,12 +7,22 @@
 //
 // Already-paired tokens are persisted in config so restarts don't require
 // re-pairing.
+//
+// In addition to paired tokens, operators can mint named tokens with a
+// restricted set of scopes and an optional expiry (`zeroclaw gateway tokens
+// create`). Token metadata (scopes, expiry, last use) lives in a JSON store
+// next to `config.toml`; only SHA-256 hashes are ever written to disk.
 
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Instant, SystemTime};
 
 /// Maximum failed pairing attempts before lockout.
 const MAX_PAIR_ATTEMPTS: u32 = 5;
@@ -20,10 +30,117 @@ const MAX_PAIR_ATTEMPTS: u32 = 5;
 const PAIR_LOCKOUT_SECS: u64 = 300; // 5 minutes
 /// Maximum number of tracked client entries to bound memory usage.
 const MAX_TRACKED_CLIENTS: usize = 1024;
+/// Default token store file name, resolved relative to the config directory.
+pub const TOKEN_STORE_FILE: &str = "gateway_tokens.json";
+/// Minimum interval between persisted `last_used_at` updates for one token.
+const LAST_USED_PERSIST_INTERVAL_SECS: i64 = 60;
 
 /// Per-client failed attempt counter with optional lockout timestamp.
 type FailedAttempts = HashMap<String, (u32, Option<Instant>)>;
 
+/// Access scope carried by a gateway bearer token.
+///
+/// `Admin` implies every other scope. `Webhook` allows submitting messages
+/// to the agent (`/webhook`, `/ws/chat`); `Read` allows the read-only
+/// dashboard endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenScope {
+    Webhook,
+    Read,
+    Admin,
+}
+
+impl TokenScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Webhook => "webhook",
+            Self::Read => "read",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// Whether holding `self` satisfies a route requiring `required`.
+    pub fn grants(self, required: TokenScope) -> bool {
+        self == Self::Admin || self == required
+    }
+}
+
+impl std::str::FromStr for TokenScope {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "webhook" => Ok(Self::Webhook),
+            "read" => Ok(Self::Read),
+            "admin" => Ok(Self::Admin),
+            other => anyhow::bail!("Unknown token scope '{other}' (expected webhook, read, admin)"),
+        }
+    }
+}
+
+/// Metadata for a gateway bearer token. The plaintext token is never stored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GatewayToken {
+    /// Unique, human-readable token name (used for revocation).
+    pub name: String,
+    /// SHA-256 hash of the bearer token (lowercase hex).
+    pub token_hash: String,
+    pub scopes: Vec<TokenScope>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Issued through `POST /pair` (also mirrored in `gateway.paired_tokens`).
+    #[serde(default)]
+    pub paired: bool,
+}
+
+impl GatewayToken {
+    fn paired(token_hash: String) -> Self {
+        Self {
+            name: paired_token_name(&token_hash),
+            token_hash,
+            scopes: vec![TokenScope::Admin],
+            created_at: Utc::now(),
+            expires_at: None,
+            last_used_at: None,
+            paired: true,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    pub fn grants(&self, required: TokenScope) -> bool {
+        self.scopes.iter().any(|scope| scope.grants(required))
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TokenStoreFile {
+    #[serde(default)]
+    tokens: Vec<GatewayToken>,
+}
+
+/// On-disk location of the token store plus the version we last synced with,
+/// so revocations made by the CLI are picked up by a running gateway.
+#[derive(Debug)]
+struct TokenStoreSync {
+    path: PathBuf,
+    synced: Option<FileStamp>,
+}
+
+/// Modification time and size of the token store. The size catches rewrites
+/// that land within the filesystem's mtime granularity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
 /// Manages pairing state for the gateway.
 ///
 /// Bearer tokens are stored as SHA-256 hashes to prevent plaintext exposure
@@ -36,8 +153,10 @@ pub struct PairingGuard {
     require_pairing: bool,
     /// One-time pairing code (generated on startup, consumed on first pair).
     pairing_code: Arc<Mutex<Option<String>>>,
-    /// Set of SHA-256 hashed bearer tokens (persisted across restarts).
-    paired_tokens: Arc<Mutex<HashSet<String>>>,
+    /// Known tokens keyed by SHA-256 hash (paired and named).
+    tokens: Arc<Mutex<HashMap<String, GatewayToken>>>,
+    /// Optional JSON store for token metadata; `None` keeps tokens in memory only.
+    store: Option<Arc<Mutex<TokenStoreSync>>>,
     /// Brute-force protection: per-client failed attempt counter + lockout time.
     failed_attempts: Arc<Mutex<FailedAttempts>>,
 }
@@ -51,15 +170,18 @@ impl PairingGuard {
     /// Existing tokens are accepted in both forms:
     /// - Plaintext (`zc_...`): hashed on load for backward compatibility
     /// - Already hashed (64-char hex): stored as-is
+    ///
+    /// Paired tokens carry the `admin` scope and never expire.
     pub fn new(require_pairing: bool, existing_tokens: &[String]) -> Self {
-        let tokens: HashSet<String> = existing_tokens
+        let tokens: HashMap<String, GatewayToken> = existing_tokens
             .iter()
             .map(|t| {
-                if is_token_hash(t) {
+                let hash = if is_token_hash(t) {
                     t.clone()
                 } else {
                     hash_token(t)
-                }
+                };
+                (hash.clone(), GatewayToken::paired(hash))
             })
             .collect();
         let code = if require_pairing && tokens.is_empty() {
@@ -70,11 +192,44 @@ impl PairingGuard {
         Self {
             require_pairing,
             pairing_code: Arc::new(Mutex::new(code)),
-            paired_tokens: Arc::new(Mutex::new(tokens)),
+            tokens: Arc::new(Mutex::new(tokens)),
+            store: None,
             failed_attempts: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Create a pairing guard backed by a token store file.
+    ///
+    /// Named tokens are loaded from `store_path`; paired tokens from config
+    /// are merged in (keeping any metadata already recorded in the store).
+    pub fn with_token_store(
+        require_pairing: bool,
+        existing_tokens: &[String],
+        store_path: &Path,
+    ) -> Result<Self> {
+        let mut guard = Self::new(require_pairing, existing_tokens);
+        let paired_hashes: Vec<String> = guard.tokens.lock().keys().cloned().collect();
+        let (stored, stamp) = read_token_store(store_path)?;
+        {
+            let mut tokens = guard.tokens.lock();
+            for token in stored {
+                // Paired tokens dropped from config were revoked elsewhere.
+                if token.paired && !paired_hashes.contains(&token.token_hash) {
+                    continue;
+                }
+                tokens.insert(token.token_hash.clone(), token);
+            }
+            if require_pairing && !tokens.is_empty() {
+                *guard.pairing_code.lock() = None;
+            }
+        }
+        guard.store = Some(Arc::new(Mutex::new(TokenStoreSync {
+            path: store_path.to_path_buf(),
+            synced: stamp,
+        })));
+        Ok(guard)
+    }
+
     /// The one-time pairing code (only set when no tokens exist yet).
     pub fn pairing_code(&self) -> Option<String> {
         self.pairing_code.lock().clone()
@@ -109,8 +264,13 @@ impl PairingGuard {
                         attempts.remove(client_id);
                     }
                     let token = generate_token();
-                    let mut tokens = self.paired_tokens.lock();
-                    tokens.insert(hash_token(&token));
+                    let hash = hash_token(&token);
+                    self.tokens
+                        .lock()
+                        .insert(hash.clone(), GatewayToken::paired(hash));
+                    if let Err(err) = self.persist_store() {
+                        tracing::warn!("Failed to persist gateway token store: {err:#}");
+                    }
 
                     // Consume the pairing code so it cannot be reused
                     *pairing_code = None;
@@ -164,27 +324,249 @@ impl PairingGuard {
             .expect("failed to spawn blocking task this should not happen")
     }
 
-    /// Check if a bearer token is valid (compares against stored hashes).
+    /// Check if a bearer token is valid and unexpired, regardless of scope.
     pub fn is_authenticated(&self, token: &str) -> bool {
         if !self.require_pairing {
             return true;
         }
+        self.authenticate(token).is_some()
+    }
+
+    /// Check if a bearer token is valid, unexpired and grants `scope`.
+    pub fn is_authorized(&self, token: &str, scope: TokenScope) -> bool {
+        if !self.require_pairing {
+            return true;
+        }
+        self.authenticate(token)
+            .is_some_and(|record| record.grants(scope))
+    }
+
+    /// Resolve a bearer token to its metadata, recording the use.
+    ///
+    /// Returns `None` for unknown or expired tokens.
+    pub fn authenticate(&self, token: &str) -> Option<GatewayToken> {
+        self.reload_store_if_changed();
         let hashed = hash_token(token);
-        let tokens = self.paired_tokens.lock();
-        tokens.contains(&hashed)
+        let now = Utc::now();
+        let (record, should_persist) = {
+            let mut tokens = self.tokens.lock();
+            let record = tokens.get_mut(&hashed)?;
+            if record.is_expired(now) {
+                return None;
+            }
+            let should_persist = record
+                .last_used_at
+                .is_none_or(|last| (now - last).num_seconds() >= LAST_USED_PERSIST_INTERVAL_SECS);
+            record.last_used_at = Some(now);
+            (record.clone(), should_persist)
+        };
+        if should_persist {
+            if let Err(err) = self.persist_store() {
+                tracing::warn!("Failed to record gateway token use: {err:#}");
+            }
+        }
+        Some(record)
     }
 
     /// Returns true if the gateway is already paired (has at least one token).
     pub fn is_paired(&self) -> bool {
-        let tokens = self.paired_tokens.lock();
+        let tokens = self.tokens.lock();
         !tokens.is_empty()
     }
 
     /// Get all paired token hashes (for persisting to config).
     pub fn tokens(&self) -> Vec<String> {
-        let tokens = self.paired_tokens.lock();
-        tokens.iter().cloned().collect()
+        let tokens = self.tokens.lock();
+        tokens
+            .values()
+            .filter(|record| record.paired)
+            .map(|record| record.token_hash.clone())
+            .collect()
+    }
+
+    /// List metadata for all known tokens, sorted by name.
+    pub fn list_tokens(&self) -> Vec<GatewayToken> {
+        self.reload_store_if_changed();
+        let mut tokens: Vec<GatewayToken> = self.tokens.lock().values().cloned().collect();
+        tokens.sort_by(|a, b| a.name.cmp(&b.name));
+        tokens
+    }
+
+    /// Mint a named token with the given scopes and optional time-to-live.
+    ///
+    /// Returns the plaintext token (shown once) and its stored metadata.
+    pub fn create_token(
+        &self,
+        name: &str,
+        scopes: &[TokenScope],
+        ttl: Option<chrono::Duration>,
+    ) -> Result<(String, GatewayToken)> {
+        let name = normalize_token_name(name)?;
+        if scopes.is_empty() {
+            anyhow::bail!("At least one scope is required");
+        }
+        self.reload_store_if_changed();
+
+        let token = generate_token();
+        let now = Utc::now();
+        let mut scopes = scopes.to_vec();
+        scopes.sort_by_key(|scope| scope.as_str());
+        scopes.dedup();
+        let record = GatewayToken {
+            name: name.clone(),
+            token_hash: hash_token(&token),
+            scopes,
+            created_at: now,
+            expires_at: ttl.map(|ttl| now + ttl),
+            last_used_at: None,
+            paired: false,
+        };
+        {
+            let mut tokens = self.tokens.lock();
+            if tokens.values().any(|existing| existing.name == name) {
+                anyhow::bail!("A gateway token named '{name}' already exists");
+            }
+            tokens.insert(record.token_hash.clone(), record.clone());
+        }
+        self.persist_store()?;
+        Ok((token, record))
+    }
+
+    /// Revoke a token by name. Returns the removed record, if any.
+    ///
+    /// When a paired token is revoked the caller must also persist
+    /// [`tokens`](Self::tokens) back to `gateway.paired_tokens`.
+    pub fn revoke_token(&self, name: &str) -> Result<Option<GatewayToken>> {
+        self.reload_store_if_changed();
+        let removed = {
+            let mut tokens = self.tokens.lock();
+            let hash = tokens
+                .values()
+                .find(|record| record.name == name)
+                .map(|record| record.token_hash.clone());
+            hash.and_then(|hash| tokens.remove(&hash))
+        };
+        if removed.is_some() {
+            self.persist_store()?;
+        }
+        Ok(removed)
     }
+
+    /// Re-read the token store when another process (the CLI) changed it.
+    fn reload_store_if_changed(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let mut sync = store.lock();
+        if file_stamp(&sync.path) == sync.synced {
+            return;
+        }
+        match read_token_store(&sync.path) {
+            // A deleted store keeps the in-memory tokens; they are rewritten
+            // on the next persist.
+            Ok((_, None)) => sync.synced = None,
+            Ok((stored, stamp)) => {
+                *self.tokens.lock() = stored
+                    .into_iter()
+                    .map(|token| (token.token_hash.clone(), token))
+                    .collect();
+                sync.synced = stamp;
+            }
+            Err(err) => {
+                tracing::warn!(
+                    path = %sync.path.display(),
+                    "Failed to reload gateway token store: {err:#}"
+                );
+            }
+        }
+    }
+
+    fn persist_store(&self) -> Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        let mut sync = store.lock();
+        let mut tokens: Vec<GatewayToken> = self.tokens.lock().values().cloned().collect();
+        tokens.sort_by(|a, b| a.name.cmp(&b.name));
+        write_token_store(&sync.path, &TokenStoreFile { tokens })?;
+        sync.synced = file_stamp(&sync.path);
+        Ok(())
+    }
+}
+
+/// Resolve the token store path for a given config directory.
+pub fn token_store_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(TOKEN_STORE_FILE)
+}
+
+fn read_token_store(path: &Path) -> Result<(Vec<GatewayToken>, Option<FileStamp>)> {
+    if !path.exists() {
+        return Ok((Vec::new(), None));
+    }
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read gateway token store {}", path.display()))?;
+    let parsed: TokenStoreFile = serde_json::from_str(&raw)
+        .with_context(|| format!("Failed to parse gateway token store {}", path.display()))?;
+    Ok((parsed.tokens, file_stamp(path)))
+}
+
+fn write_token_store(path: &Path, store: &TokenStoreFile) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create token store dir {}", parent.display()))?;
+    }
+    let body =
+        serde_json::to_string_pretty(store).context("Failed to serialize gateway token store")?;
+    let temp_path = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+    fs::write(&temp_path, body).with_context(|| {
+        format!(
+            "Failed to write temporary token store file {}",
+            temp_path.display()
+        )
+    })?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&temp_path, fs::Permissions::from_mode(0o600));
+    }
+
+    fs::rename(&temp_path, path).with_context(|| {
+        format!(
+            "Failed to atomically replace token store file {}",
+            path.display()
+        )
+    })?;
+    Ok(())
+}
+
+fn file_stamp(path: &Path) -> Option<FileStamp> {
+    let meta = fs::metadata(path).ok()?;
+    Some(FileStamp {
+        modified: meta.modified().ok()?,
+        len: meta.len(),
+    })
+}
+
+fn paired_token_name(token_hash: &str) -> String {
+    format!("paired-{}", &token_hash[..token_hash.len().min(8)])
+}
+
+fn normalize_token_name(raw: &str) -> Result<String> {
+    let value = raw.trim();
+    if value.is_empty() {
+        anyhow::bail!("Token name must not be empty");
+    }
+    if value.starts_with("paired-") {
+        anyhow::bail!("Token names starting with 'paired-' are reserved");
+    }
+    if !value
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' || ch == '.')
+    {
+        anyhow::bail!("Token name '{raw}' contains invalid characters");
+    }
+    Ok(value.to_string())
 }
 
 /// Generate a 6-digit numeric pairing code using cryptographically secure randomness.
@@ -541,4 +923,155 @@ mod tests {
             "Legitimate client should not be locked out by attacker"
         );
     }
+
+    // ── Scoped tokens ────────────────────────────────────────
+
+    #[test]
+    async fn token_scope_admin_grants_everything() {
+        assert!(TokenScope::Admin.grants(TokenScope::Webhook));
+        assert!(TokenScope::Admin.grants(TokenScope::Read));
+        assert!(TokenScope::Read.grants(TokenScope::Read));
+        assert!(!TokenScope::Read.grants(TokenScope::Webhook));
+        assert!(!TokenScope::Webhook.grants(TokenScope::Admin));
+    }
+
+    #[test]
+    async fn token_scope_parses_case_insensitively() {
+        assert_eq!(
+            "Webhook".parse::<TokenScope>().unwrap(),
+            TokenScope::Webhook
+        );
+        assert_eq!(" read ".parse::<TokenScope>().unwrap(), TokenScope::Read);
+        assert!("root".parse::<TokenScope>().is_err());
+    }
+
+    #[test]
+    async fn paired_tokens_have_admin_scope() {
+        let guard = PairingGuard::new(true, &["zc_valid".into()]);
+        assert!(guard.is_authorized("zc_valid", TokenScope::Admin));
+        assert!(guard.is_authorized("zc_valid", TokenScope::Webhook));
+    }
+
+    #[test]
+    async fn created_token_is_limited_to_its_scopes() {
+        let guard = PairingGuard::new(true, &["zc_admin".into()]);
+        let (token, record) = guard
+            .create_token("dashboard", &[TokenScope::Read], None)
+            .unwrap();
+        assert!(!record.paired);
+        assert!(guard.is_authenticated(&token));
+        assert!(guard.is_authorized(&token, TokenScope::Read));
+        assert!(!guard.is_authorized(&token, TokenScope::Webhook));
+        assert!(!guard.is_authorized(&token, TokenScope::Admin));
+        // Named tokens are not mirrored into config paired_tokens.
+        assert_eq!(guard.tokens().len(), 1);
+    }
+
+    #[test]
+    async fn expired_token_is_rejected() {
+        let guard = PairingGuard::new(true, &[]);
+        let (token, _) = guard
+            .create_token(
+                "short",
+                &[TokenScope::Webhook],
+                Some(chrono::Duration::zero()),
+            )
+            .unwrap();
+        assert!(!guard.is_authenticated(&token));
+        assert!(!guard.is_authorized(&token, TokenScope::Webhook));
+    }
+
+    #[test]
+    async fn create_token_rejects_duplicates_and_reserved_names() {
+        let guard = PairingGuard::new(true, &[]);
+        guard
+            .create_token("ci", &[TokenScope::Webhook], None)
+            .unwrap();
+        assert!(guard.create_token("ci", &[TokenScope::Read], None).is_err());
+        assert!(guard
+            .create_token("paired-abc", &[TokenScope::Read], None)
+            .is_err());
+        assert!(guard
+            .create_token("bad name", &[TokenScope::Read], None)
+            .is_err());
+        assert!(guard.create_token("empty-scopes", &[], None).is_err());
+    }
+
+    #[test]
+    async fn authenticate_records_last_used() {
+        let guard = PairingGuard::new(true, &[]);
+        let (token, record) = guard
+            .create_token("ci", &[TokenScope::Webhook], None)
+            .unwrap();
+        assert!(record.last_used_at.is_none());
+        let used = guard.authenticate(&token).unwrap();
+        assert!(used.last_used_at.is_some());
+    }
+
+    #[test]
+    async fn revoke_token_by_name() {
+        let guard = PairingGuard::new(true, &["zc_paired".into()]);
+        let (token, _) = guard
+            .create_token("ci", &[TokenScope::Webhook], None)
+            .unwrap();
+        assert!(guard.revoke_token("ci").unwrap().is_some());
+        assert!(!guard.is_authenticated(&token));
+        assert!(guard.revoke_token("ci").unwrap().is_none());
+
+        let paired_name = paired_token_name(&hash_token("zc_paired"));
+        let removed = guard.revoke_token(&paired_name).unwrap().unwrap();
+        assert!(removed.paired);
+        assert!(guard.tokens().is_empty());
+    }
+
+    #[test]
+    async fn token_store_roundtrips_named_tokens() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = token_store_path(tmp.path());
+        let guard = PairingGuard::with_token_store(true, &["zc_paired".into()], &path).unwrap();
+        let (token, _) = guard
+            .create_token("ci", &[TokenScope::Webhook], None)
+            .unwrap();
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            !raw.contains(&token),
+            "plaintext token must not be persisted"
+        );
+
+        let reloaded = PairingGuard::with_token_store(true, &["zc_paired".into()], &path).unwrap();
+        assert!(reloaded.is_authorized(&token, TokenScope::Webhook));
+        assert!(reloaded.is_authorized("zc_paired", TokenScope::Admin));
+        assert_eq!(reloaded.list_tokens().len(), 2);
+    }
+
+    #[test]
+    async fn token_store_drops_paired_tokens_removed_from_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = token_store_path(tmp.path());
+        let guard = PairingGuard::with_token_store(true, &["zc_paired".into()], &path).unwrap();
+        guard.create_token("ci", &[TokenScope::Read], None).unwrap();
+
+        let reloaded = PairingGuard::with_token_store(true, &[], &path).unwrap();
+        assert!(!reloaded.is_authenticated("zc_paired"));
+        assert_eq!(reloaded.list_tokens().len(), 1);
+        assert!(reloaded.pairing_code().is_none());
+    }
+
+    #[test]
+    async fn running_guard_sees_revocation_from_other_process() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = token_store_path(tmp.path());
+        let gateway = PairingGuard::with_token_store(true, &[], &path).unwrap();
+        let (token, _) = gateway
+            .create_token("ci", &[TokenScope::Webhook], None)
+            .unwrap();
+        assert!(gateway.is_authenticated(&token));
+
+        // Simulate `zeroclaw gateway tokens revoke ci` from another process.
+        let cli = PairingGuard::with_token_store(true, &[], &path).unwrap();
+        cli.revoke_token("ci").unwrap();
+
+        assert!(!gateway.is_authenticated(&token));
+    }
 }
@@ -982,6 +982,25 @@ impl SecurityPolicy {
         false
     }
 
+    /// Whether a canonical path falls under a `forbidden_paths` entry that is
+    /// narrower than the workspace or allowed root containing it. Entries that
+    /// enclose the root itself (such as `/home` for a workspace in the user's
+    /// home) are left to [`Self::is_resolved_path_allowed`].
+    pub fn is_resolved_path_forbidden(&self, resolved: &Path) -> bool {
+        let roots: Vec<PathBuf> = std::iter::once(&self.workspace_dir)
+            .chain(&self.allowed_roots)
+            .map(|root| root.canonicalize().unwrap_or_else(|_| root.clone()))
+            .filter(|root| resolved.starts_with(root))
+            .collect();
+
+        self.forbidden_paths.iter().any(|forbidden| {
+            let forbidden = expand_user_path(forbidden);
+            let forbidden = forbidden.canonicalize().unwrap_or(forbidden);
+            resolved.starts_with(&forbidden)
+                && !roots.iter().any(|root| root.starts_with(&forbidden))
+        })
+    }
+
     pub fn resolved_path_violation_message(&self, resolved: &Path) -> String {
         let guidance = if self.allowed_roots.is_empty() {
             "Add the directory to [autonomy].allowed_roots (for example: allowed_roots = [\"/absolute/path\"]), or move the file into the workspace."
@@ -2035,6 +2054,29 @@ mod tests {
         assert!(!p.is_resolved_path_allowed(Path::new("/")));
     }
 
+    #[test]
+    fn resolved_path_forbidden_only_below_the_granting_root() {
+        let p = SecurityPolicy {
+            workspace_dir: PathBuf::from("/home/user/project"),
+            allowed_roots: vec![PathBuf::from("/srv/shared")],
+            forbidden_paths: vec![
+                "/home".into(),
+                "/home/user/project/.secrets".into(),
+                "/srv".into(),
+                "/srv/shared/keys".into(),
+            ],
+            ..SecurityPolicy::default()
+        };
+        // `/home` and `/srv` enclose the roots themselves, so they don't apply.
+        assert!(!p.is_resolved_path_forbidden(Path::new("/home/user/project/out.png")));
+        assert!(!p.is_resolved_path_forbidden(Path::new("/srv/shared/report.pdf")));
+        // Narrower entries inside a root still do.
+        assert!(p.is_resolved_path_forbidden(Path::new("/home/user/project/.secrets/key")));
+        assert!(p.is_resolved_path_forbidden(Path::new("/srv/shared/keys/id_rsa")));
+        // Outside every root, any matching entry applies.
+        assert!(p.is_resolved_path_forbidden(Path::new("/home/other/file")));
+    }
+
     #[test]
     fn checklist_default_policy_is_workspace_only() {
         let p = SecurityPolicy::default();
@@ -0,0 +1,673 @@
+// External secret references — resolve config values from secret managers.
+//
+// Any field protected by `SecretStore` may hold a reference instead of a
+// literal value:
+//
+//   vault://<mount>/<path>#<field>   HashiCorp Vault KV v2 (field defaults to `value`)
+//   env://<VAR>                      process environment variable
+//   file://<path>                    file contents (`~` expanded, trailing newline trimmed)
+//   cmd://<command line>             stdout of a shell command, e.g. `pass show openai`
+//                                    (requires `secrets.allow_command_refs = true`)
+//
+// References are resolved once at config load. Resolved values are cached
+// process-wide for `secrets.cache_ttl_secs` so runtime config reloads do not
+// hit Vault or spawn password managers on every change. The original reference
+// is remembered in `SecretRefBindings` and written back on save — the resolved
+// plaintext never reaches `config.toml`.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Reference schemes recognised in config values.
+pub const SECRET_REF_SCHEMES: &[&str] = &["vault", "env", "file", "cmd"];
+
+/// Default KV v2 field read when a `vault://` reference has no `#field`.
+const DEFAULT_VAULT_FIELD: &str = "value";
+
+/// Maximum time a `cmd://` reference may run before it is killed.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Returns true when `value` is an external secret reference
+/// (`vault://`, `env://`, `file://` or `cmd://`).
+pub fn is_secret_ref(value: &str) -> bool {
+    split_ref(value).is_some()
+}
+
+/// Split a reference into `(scheme, target)`. Returns `None` for literals.
+fn split_ref(value: &str) -> Option<(&str, &str)> {
+    let (scheme, target) = value.trim().split_once("://")?;
+    SECRET_REF_SCHEMES
+        .contains(&scheme)
+        .then_some((scheme, target))
+}
+
+/// A source of secret values for one reference scheme.
+#[async_trait]
+pub trait SecretBackend: Send + Sync {
+    /// Scheme served by this backend (`"vault"`, `"env"`, ...).
+    fn scheme(&self) -> &'static str;
+
+    /// Fetch the plaintext for `target` (everything after `scheme://`).
+    async fn fetch(&self, target: &str) -> Result<String>;
+
+    /// Key under which fetched values are cached, or `None` to always fetch.
+    fn cache_key(&self, target: &str) -> Option<String> {
+        Some(format!("{}://{target}", self.scheme()))
+    }
+}
+
+/// Resolves secret references through the registered backends.
+pub struct SecretResolver {
+    backends: HashMap<&'static str, Arc<dyn SecretBackend>>,
+    cache_ttl: Duration,
+}
+
+impl SecretResolver {
+    /// Resolver with only the `env://` and `file://` backends.
+    pub fn new(cache_ttl: Duration) -> Self {
+        let mut resolver = Self {
+            backends: HashMap::new(),
+            cache_ttl,
+        };
+        resolver.register(Arc::new(EnvBackend));
+        resolver.register(Arc::new(FileBackend));
+        resolver
+    }
+
+    /// Build a resolver from the `[secrets]` config section.
+    ///
+    /// `cmd://` is only registered when `allow_command_refs` is set; `vault://`
+    /// only when a Vault address is configured (or `VAULT_ADDR` is set).
+    pub fn from_config(config: &crate::config::SecretsConfig) -> Self {
+        let mut resolver = Self::new(Duration::from_secs(config.cache_ttl_secs));
+        if config.allow_command_refs {
+            resolver.register(Arc::new(CommandBackend::default()));
+        }
+        if let Some(client) = VaultKvClient::from_config(&config.vault) {
+            resolver.register(Arc::new(VaultBackend::new(client)));
+        }
+        resolver
+    }
+
+    /// Register (or replace) the backend for its scheme.
+    pub fn register(&mut self, backend: Arc<dyn SecretBackend>) {
+        self.backends.insert(backend.scheme(), backend);
+    }
+
+    /// Resolve `value` if it is a reference; literals are returned unchanged.
+    pub async fn resolve(&self, value: &str) -> Result<String> {
+        let Some((scheme, target)) = split_ref(value) else {
+            return Ok(value.to_string());
+        };
+        let Some(backend) = self.backends.get(scheme) else {
+            match scheme {
+                "cmd" => bail!(
+                    "cmd:// secret references are disabled; set secrets.allow_command_refs = true"
+                ),
+                "vault" => {
+                    bail!("vault:// secret reference requires secrets.vault.address or VAULT_ADDR")
+                }
+                _ => bail!("No secret backend registered for {scheme}://"),
+            }
+        };
+
+        let cache_key = backend
+            .cache_key(target)
+            .filter(|_| !self.cache_ttl.is_zero());
+        if let Some(ref key) = cache_key {
+            if let Some(hit) = cache_get(key, self.cache_ttl) {
+                return Ok(hit);
+            }
+        }
+
+        let resolved = backend
+            .fetch(target)
+            .await
+            .with_context(|| format!("Failed to resolve secret reference {scheme}://{target}"))?;
+        if resolved.is_empty() {
+            bail!("Secret reference {scheme}://{target} resolved to an empty value");
+        }
+
+        if let Some(key) = cache_key {
+            cache_put(key, resolved.clone());
+        }
+        Ok(resolved)
+    }
+}
+
+// ── Process-wide cache ──────────────────────────────────────────
+
+struct CachedSecret {
+    value: String,
+    fetched_at: Instant,
+}
+
+fn cache() -> &'static Mutex<HashMap<String, CachedSecret>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, CachedSecret>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn cache_get(key: &str, ttl: Duration) -> Option<String> {
+    let mut cache = cache().lock();
+    match cache.get(key) {
+        Some(entry) if entry.fetched_at.elapsed() < ttl => Some(entry.value.clone()),
+        Some(_) => {
+            cache.remove(key);
+            None
+        }
+        None => None,
+    }
+}
+
+fn cache_put(key: String, value: String) {
+    cache().lock().insert(
+        key,
+        CachedSecret {
+            value,
+            fetched_at: Instant::now(),
+        },
+    );
+}
+
+/// Drop all cached secret values (e.g. after rotating a secret upstream).
+pub fn clear_cache() {
+    cache().lock().clear();
+}
+
+// ── Built-in backends ───────────────────────────────────────────
+
+/// `env://VAR` — reads the process environment. Never cached.
+pub struct EnvBackend;
+
+#[async_trait]
+impl SecretBackend for EnvBackend {
+    fn scheme(&self) -> &'static str {
+        "env"
+    }
+
+    async fn fetch(&self, target: &str) -> Result<String> {
+        std::env::var(target).with_context(|| format!("Environment variable {target} is not set"))
+    }
+
+    fn cache_key(&self, _target: &str) -> Option<String> {
+        None
+    }
+}
+
+/// `file://path` — reads a file such as a Docker/Kubernetes secret mount.
+pub struct FileBackend;
+
+#[async_trait]
+impl SecretBackend for FileBackend {
+    fn scheme(&self) -> &'static str {
+        "file"
+    }
+
+    async fn fetch(&self, target: &str) -> Result<String> {
+        let path = PathBuf::from(shellexpand::tilde(target).as_ref());
+        let contents = tokio::fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Failed to read secret file {}", path.display()))?;
+        Ok(trim_line_ending(&contents))
+    }
+}
+
+/// `cmd://command` — runs a command through `sh -c` and uses its stdout.
+pub struct CommandBackend {
+    timeout: Duration,
+}
+
+impl Default for CommandBackend {
+    fn default() -> Self {
+        Self {
+            timeout: COMMAND_TIMEOUT,
+        }
+    }
+}
+
+#[async_trait]
+impl SecretBackend for CommandBackend {
+    fn scheme(&self) -> &'static str {
+        "cmd"
+    }
+
+    async fn fetch(&self, target: &str) -> Result<String> {
+        let mut command = if cfg!(windows) {
+            let mut c = tokio::process::Command::new("cmd");
+            c.arg("/C");
+            c
+        } else {
+            let mut c = tokio::process::Command::new("sh");
+            c.arg("-c");
+            c
+        };
+        command
+            .arg(target)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true);
+
+        let output = tokio::time::timeout(self.timeout, command.output())
+            .await
+            .with_context(|| format!("Secret command timed out after {:?}", self.timeout))?
+            .context("Failed to run secret command")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!(
+                "Secret command exited with {}: {}",
+                output.status,
+                stderr.trim()
+            );
+        }
+        let stdout =
+            String::from_utf8(output.stdout).context("Secret command output is not UTF-8")?;
+        Ok(trim_line_ending(&stdout))
+    }
+}
+
+/// `vault://mount/path#field` — reads a HashiCorp Vault KV v2 secret.
+pub struct VaultBackend {
+    client: VaultKvClient,
+}
+
+impl VaultBackend {
+    pub fn new(client: VaultKvClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl SecretBackend for VaultBackend {
+    fn scheme(&self) -> &'static str {
+        "vault"
+    }
+
+    async fn fetch(&self, target: &str) -> Result<String> {
+        let (location, field) = match target.split_once('#') {
+            Some((location, field)) if !field.is_empty() => (location, field),
+            Some((location, _)) => (location, DEFAULT_VAULT_FIELD),
+            None => (target, DEFAULT_VAULT_FIELD),
+        };
+        let Some((mount, path)) = location.trim_matches('/').split_once('/') else {
+            bail!("Vault reference must look like vault://<mount>/<path>#<field>");
+        };
+        self.client.read_field(mount, path, field).await
+    }
+
+    fn cache_key(&self, target: &str) -> Option<String> {
+        Some(format!("vault@{}://{target}", self.client.address))
+    }
+}
+
+/// Minimal HashiCorp Vault KV v2 client (token auth).
+pub struct VaultKvClient {
+    address: String,
+    token: String,
+    namespace: Option<String>,
+    client: reqwest::Client,
+}
+
+impl VaultKvClient {
+    pub fn new(address: &str, token: &str, namespace: Option<String>, timeout_secs: u64) -> Self {
+        Self {
+            address: address.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            namespace: namespace.filter(|ns| !ns.trim().is_empty()),
+            client: crate::config::build_runtime_proxy_client_with_timeouts(
+                "secrets.vault",
+                timeout_secs,
+                timeout_secs.min(10),
+            ),
+        }
+    }
+
+    /// Build a client from `[secrets.vault]`, falling back to the standard
+    /// `VAULT_ADDR` / `VAULT_TOKEN` / `VAULT_NAMESPACE` environment variables.
+    /// Returns `None` when no address is available.
+    pub fn from_config(config: &crate::config::VaultSecretsConfig) -> Option<Self> {
+        let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+        let address = non_empty(config.address.clone())
+            .or_else(|| non_empty(std::env::var("VAULT_ADDR").ok()))?;
+        let token = non_empty(config.token.clone())
+            .or_else(|| non_empty(std::env::var("VAULT_TOKEN").ok()))
+            .unwrap_or_default();
+        let namespace = non_empty(config.namespace.clone())
+            .or_else(|| non_empty(std::env::var("VAULT_NAMESPACE").ok()));
+        Some(Self::new(&address, &token, namespace, config.timeout_secs))
+    }
+
+    /// Read `field` from the latest version of `<mount>/data/<path>`.
+    pub async fn read_field(&self, mount: &str, path: &str, field: &str) -> Result<String> {
+        if self.token.is_empty() {
+            bail!("Vault token missing; set secrets.vault.token or VAULT_TOKEN");
+        }
+        let url = format!(
+            "{}/v1/{}/data/{}",
+            self.address,
+            mount.trim_matches('/'),
+            path.trim_matches('/')
+        );
+        let mut request = self.client.get(&url).header("X-Vault-Token", &self.token);
+        if let Some(ref ns) = self.namespace {
+            request = request.header("X-Vault-Namespace", ns);
+        }
+
+        let response = request.send().await.context("Vault request failed")?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("Vault returned {status} for {mount}/{path}");
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .context("Vault response is not JSON")?;
+
+        match body.pointer("/data/data").and_then(|data| data.get(field)) {
+            Some(serde_json::Value::String(value)) => Ok(value.clone()),
+            Some(serde_json::Value::Null) | None => {
+                bail!("Vault secret {mount}/{path} has no field '{field}'")
+            }
+            Some(other) => Ok(other.to_string()),
+        }
+    }
+}
+
+fn trim_line_ending(value: &str) -> String {
+    value.trim_end_matches(['\n', '\r']).to_string()
+}
+
+// ── Reference bindings (kept so save() never writes plaintext) ──
+
+#[derive(Clone)]
+struct SecretRefBinding {
+    reference: String,
+    digest: [u8; 32],
+}
+
+/// Config fields that were loaded from external references.
+///
+/// Stores the original reference and a digest of the resolved value per field
+/// path (e.g. `config.api_key`). On save, a field whose value is unchanged is
+/// written back as its reference; a field the user changed is saved normally.
+#[derive(Clone, Default)]
+pub struct SecretRefBindings {
+    fields: HashMap<String, SecretRefBinding>,
+}
+
+impl SecretRefBindings {
+    /// Remember that `field` was resolved from `reference` to `resolved`.
+    pub fn record(&mut self, field: &str, reference: &str, resolved: &str) {
+        self.fields.insert(
+            field.to_string(),
+            SecretRefBinding {
+                reference: reference.to_string(),
+                digest: digest(resolved),
+            },
+        );
+    }
+
+    /// The reference to persist for `field`, if its value is still the one
+    /// resolved at load time.
+    pub fn reference_for(&self, field: &str, current: &str) -> Option<&str> {
+        self.fields
+            .get(field)
+            .filter(|binding| binding.digest == digest(current))
+            .map(|binding| binding.reference.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+}
+
+impl std::fmt::Debug for SecretRefBindings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Only references are shown; digests of resolved values stay private.
+        f.debug_map()
+            .entries(
+                self.fields
+                    .iter()
+                    .map(|(field, binding)| (field, &binding.reference)),
+            )
+            .finish()
+    }
+}
+
+fn digest(value: &str) -> [u8; 32] {
+    Sha256::digest(value.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{SecretsConfig, VaultSecretsConfig};
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn detects_only_known_schemes() {
+        assert!(is_secret_ref("vault://secret/app#key"));
+        assert!(is_secret_ref("env://OPENAI_API_KEY"));
+        assert!(is_secret_ref("file:///run/secrets/key"));
+        assert!(is_secret_ref("cmd://pass show openai"));
+        assert!(!is_secret_ref("sk-plaintext"));
+        assert!(!is_secret_ref("postgres://user:pw@localhost/db"));
+        assert!(!is_secret_ref("enc2:abcdef"));
+    }
+
+    #[tokio::test]
+    async fn literals_pass_through() {
+        let resolver = SecretResolver::new(Duration::ZERO);
+        assert_eq!(resolver.resolve("sk-literal").await.unwrap(), "sk-literal");
+    }
+
+    #[tokio::test]
+    async fn env_ref_resolves() {
+        std::env::set_var("ZEROCLAW_TEST_SECRET_REF_ENV", "from-env");
+        let resolver = SecretResolver::new(Duration::from_secs(60));
+        let value = resolver
+            .resolve("env://ZEROCLAW_TEST_SECRET_REF_ENV")
+            .await
+            .unwrap();
+        assert_eq!(value, "from-env");
+        std::env::remove_var("ZEROCLAW_TEST_SECRET_REF_ENV");
+    }
+
+    #[tokio::test]
+    async fn missing_env_ref_errors() {
+        let resolver = SecretResolver::new(Duration::ZERO);
+        let err = resolver
+            .resolve("env://ZEROCLAW_TEST_SECRET_REF_MISSING")
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("ZEROCLAW_TEST_SECRET_REF_MISSING"));
+    }
+
+    #[tokio::test]
+    async fn file_ref_trims_trailing_newline() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("api_key");
+        std::fs::write(&path, "sk-from-file\n").unwrap();
+        let resolver = SecretResolver::new(Duration::ZERO);
+        let value = resolver
+            .resolve(&format!("file://{}", path.display()))
+            .await
+            .unwrap();
+        assert_eq!(value, "sk-from-file");
+    }
+
+    #[tokio::test]
+    async fn file_ref_is_cached_within_ttl() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("cached");
+        std::fs::write(&path, "first").unwrap();
+        let reference = format!("file://{}", path.display());
+        let resolver = SecretResolver::new(Duration::from_secs(300));
+
+        assert_eq!(resolver.resolve(&reference).await.unwrap(), "first");
+        std::fs::write(&path, "second").unwrap();
+        assert_eq!(resolver.resolve(&reference).await.unwrap(), "first");
+
+        cache().lock().remove(&format!("file://{}", path.display()));
+        assert_eq!(resolver.resolve(&reference).await.unwrap(), "second");
+    }
+
+    #[tokio::test]
+    async fn cmd_ref_requires_opt_in() {
+        let resolver = SecretResolver::from_config(&SecretsConfig::default());
+        let err = resolver.resolve("cmd://echo hi").await.unwrap_err();
+        assert!(err.to_string().contains("allow_command_refs"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cmd_ref_uses_stdout_when_allowed() {
+        let config = SecretsConfig {
+            allow_command_refs: true,
+            cache_ttl_secs: 0,
+            ..SecretsConfig::default()
+        };
+        let resolver = SecretResolver::from_config(&config);
+        let value = resolver
+            .resolve("cmd://printf 'sk-from-cmd\\n'")
+            .await
+            .unwrap();
+        assert_eq!(value, "sk-from-cmd");
+
+        let err = resolver.resolve("cmd://exit 3").await.unwrap_err();
+        assert!(format!("{err:#}").contains("exited"));
+    }
+
+    #[tokio::test]
+    async fn vault_kv2_read_field() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/zeroclaw/openai"))
+            .and(header("X-Vault-Token", "root-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": {
+                    "data": { "api_key": "sk-from-vault", "value": "default-field" },
+                    "metadata": { "version": 3 }
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let mut resolver = SecretResolver::new(Duration::ZERO);
+        resolver.register(Arc::new(VaultBackend::new(VaultKvClient::new(
+            &server.uri(),
+            "root-token",
+            None,
+            5,
+        ))));
+
+        assert_eq!(
+            resolver
+                .resolve("vault://secret/zeroclaw/openai#api_key")
+                .await
+                .unwrap(),
+            "sk-from-vault"
+        );
+        assert_eq!(
+            resolver
+                .resolve("vault://secret/zeroclaw/openai")
+                .await
+                .unwrap(),
+            "default-field"
+        );
+        let err = resolver
+            .resolve("vault://secret/zeroclaw/openai#missing")
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("no field 'missing'"));
+    }
+
+    #[tokio::test]
+    async fn vault_sends_namespace_and_reports_http_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/kv/data/app"))
+            .and(header("X-Vault-Namespace", "team-a"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+
+        let client = VaultKvClient::new(&server.uri(), "t", Some("team-a".into()), 5);
+        let err = client.read_field("kv", "app", "value").await.unwrap_err();
+        assert!(err.to_string().contains("403"));
+    }
+
+    #[tokio::test]
+    async fn vault_ref_without_address_errors() {
+        let config = SecretsConfig {
+            vault: VaultSecretsConfig {
+                address: None,
+                ..VaultSecretsConfig::default()
+            },
+            ..SecretsConfig::default()
+        };
+        if std::env::var("VAULT_ADDR").is_ok() {
+            return;
+        }
+        let resolver = SecretResolver::from_config(&config);
+        let err = resolver
+            .resolve("vault://secret/app#key")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("VAULT_ADDR"));
+    }
+
+    #[test]
+    fn bindings_return_reference_only_for_unchanged_values() {
+        let mut bindings = SecretRefBindings::default();
+        bindings.record("config.api_key", "env://OPENAI_API_KEY", "sk-resolved");
+
+        assert_eq!(
+            bindings.reference_for("config.api_key", "sk-resolved"),
+            Some("env://OPENAI_API_KEY")
+        );
+        assert_eq!(bindings.reference_for("config.api_key", "sk-new"), None);
+        assert_eq!(bindings.reference_for("config.other", "sk-resolved"), None);
+    }
+
+    #[test]
+    fn bindings_debug_does_not_leak_values() {
+        let mut bindings = SecretRefBindings::default();
+        bindings.record("config.api_key", "env://OPENAI_API_KEY", "sk-resolved");
+        let debug = format!("{bindings:?}");
+        assert!(debug.contains("env://OPENAI_API_KEY"));
+        assert!(!debug.contains("sk-resolved"));
+    }
+
+    /// Live test against a local Vault dev server. Run manually:
+    ///
+    /// ```sh
+    /// vault server -dev -dev-root-token-id=root &
+    /// VAULT_ADDR=http://127.0.0.1:8200 VAULT_TOKEN=root \
+    ///   vault kv put secret/zeroclaw-test api_key=sk-live
+    /// VAULT_ADDR=http://127.0.0.1:8200 VAULT_TOKEN=root \
+    ///   cargo test --lib secret_resolver::tests::vault_dev_server -- --ignored
+    /// ```
+    #[tokio::test]
+    #[ignore = "requires a local Vault dev server (VAULT_ADDR / VAULT_TOKEN)"]
+    async fn vault_dev_server() {
+        let resolver = SecretResolver::from_config(&SecretsConfig {
+            cache_ttl_secs: 0,
+            ..SecretsConfig::default()
+        });
+        let value = resolver
+            .resolve("vault://secret/zeroclaw-test#api_key")
+            .await
+            .unwrap();
+        assert_eq!(value, "sk-live");
+    }
+}
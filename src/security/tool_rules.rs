@@ -0,0 +1,1079 @@
+//! Policy-as-code rules for tool calls.
+//!
+//! A TOML rules file (default `~/.zeroclaw/tool_rules.toml`) is evaluated
+//! before every tool call through the `tool-policy` hook. Rules are checked in
+//! file order and the first matching rule decides the outcome:
+//!
+//! - `allow`: run the tool
+//! - `deny`: cancel the call with the rule's reason
+//! - `ask`: require interactive approval (CLI only; denied elsewhere)
+//! - `rate-limit`: allow up to `limit.max` calls per `limit.per_secs`, then deny
+//!
+//! A rule matches when every condition it declares holds: tool name, channel
+//! and sender globs, argument JSON paths, time-of-day window and weekdays.
+//!
+//! ```toml
+//! default = "allow"
+//! timezone = "Europe/Berlin"
+//!
+//! [[rules]]
+//! name = "no-recursive-delete"
+//! tools = ["shell"]
+//! action = "deny"
+//! reason = "Recursive deletes are blocked"
+//! args = [{ path = "command", regex = "rm\\s+-[a-z]*r" }]
+//!
+//! [[rules]]
+//! name = "night-shell-needs-approval"
+//! tools = ["shell", "file_write"]
+//! hours = "22:00-07:00"
+//! action = "ask"
+//!
+//! [[rules]]
+//! name = "http-budget"
+//! tools = ["http_request"]
+//! action = "rate-limit"
+//! limit = { max = 20, per_secs = 3600, per = "sender" }
+//! ```
+
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Default rules file name, resolved relative to the config directory.
+pub const DEFAULT_RULES_FILE: &str = "tool_rules.toml";
+
+/// Rate-limit buckets kept before idle ones are evicted. Per-sender and
+/// per-channel scopes create a bucket for every distinct caller.
+const MAX_RATE_LIMIT_BUCKETS: usize = 4096;
+
+/// Action a rule yields when it matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuleAction {
+    Allow,
+    Deny,
+    Ask,
+    RateLimit,
+}
+
+impl RuleAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+            Self::Ask => "ask",
+            Self::RateLimit => "rate-limit",
+        }
+    }
+}
+
+/// Final outcome of evaluating a tool call against the rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Allow,
+    Deny,
+    Ask,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+            Self::Ask => "ask",
+        })
+    }
+}
+
+/// Bucket used to count calls for a `rate-limit` rule.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitScope {
+    /// One shared budget for every caller.
+    #[default]
+    Global,
+    /// Separate budget per sender.
+    Sender,
+    /// Separate budget per channel.
+    Channel,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    pub max: u32,
+    pub per_secs: u64,
+    #[serde(default)]
+    pub per: RateLimitScope,
+}
+
+/// Condition on one tool argument, addressed by JSON path
+/// (`command`, `$.options.force`, `files[0].path` or `/files/0/path`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArgCondition {
+    pub path: String,
+    /// Exact JSON value match.
+    #[serde(default)]
+    pub equals: Option<Value>,
+    /// Regex searched in the value (non-strings are matched on their JSON text).
+    #[serde(default)]
+    pub regex: Option<String>,
+    /// Substring match.
+    #[serde(default)]
+    pub contains: Option<String>,
+    /// Whether the path must (true) or must not (false) be present.
+    #[serde(default)]
+    pub exists: Option<bool>,
+}
+
+/// One rule as written in the rules file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolRule {
+    pub name: String,
+    pub action: RuleAction,
+    /// Tool name globs (empty = any tool).
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Channel name globs (empty = any channel).
+    #[serde(default)]
+    pub channels: Vec<String>,
+    /// Sender globs (empty = any sender). Never matches calls without a sender.
+    #[serde(default)]
+    pub senders: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<ArgCondition>,
+    /// Time-of-day window `HH:MM-HH:MM`; may wrap past midnight.
+    #[serde(default)]
+    pub hours: Option<String>,
+    /// Weekdays (`mon` … `sun`); empty = every day.
+    #[serde(default)]
+    pub days: Vec<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Required for `rate-limit` rules.
+    #[serde(default)]
+    pub limit: Option<RateLimit>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default = "default_action")]
+    default: RuleAction,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    rules: Vec<ToolRule>,
+}
+
+fn default_action() -> RuleAction {
+    RuleAction::Allow
+}
+
+/// The facts a tool call is judged on.
+#[derive(Debug, Clone)]
+pub struct ToolCallFacts<'a> {
+    pub tool: &'a str,
+    pub args: &'a Value,
+    pub channel: &'a str,
+    pub sender: Option<&'a str>,
+    /// Wall-clock time in the rules' timezone.
+    pub at: NaiveDateTime,
+}
+
+/// Result of [`ToolRules::evaluate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleDecision {
+    pub verdict: Verdict,
+    /// Name of the rule that fired, or `None` when the default applied.
+    pub rule: Option<String>,
+    pub reason: String,
+}
+
+/// Per-rule line of an [`Explanation`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleTrace {
+    pub rule: String,
+    pub action: RuleAction,
+    pub matched: bool,
+    /// First condition that failed, or what happened on a match.
+    pub detail: String,
+}
+
+/// Dry-run evaluation: the decision plus why each rule did or did not match.
+#[derive(Debug, Clone, Serialize)]
+pub struct Explanation {
+    pub decision: RuleDecision,
+    pub trace: Vec<RuleTrace>,
+}
+
+struct CompiledArg {
+    raw_path: String,
+    pointer: String,
+    condition: ArgCondition,
+    regex: Option<regex::Regex>,
+}
+
+struct CompiledRule {
+    rule: ToolRule,
+    tools: Vec<glob::Pattern>,
+    channels: Vec<glob::Pattern>,
+    senders: Vec<glob::Pattern>,
+    args: Vec<CompiledArg>,
+    hours: Option<(NaiveTime, NaiveTime)>,
+    days: Vec<Weekday>,
+}
+
+/// Compiled rule set with rate-limit counters.
+pub struct ToolRules {
+    rules: Vec<CompiledRule>,
+    default: RuleAction,
+    timezone: Option<chrono_tz::Tz>,
+    source: Option<PathBuf>,
+    hits: Mutex<HashMap<(usize, String), VecDeque<Instant>>>,
+}
+
+impl ToolRules {
+    /// Load and compile a rules file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read tool rules file {}", path.display()))?;
+        let mut rules = Self::from_toml(&contents)
+            .with_context(|| format!("Invalid tool rules file {}", path.display()))?;
+        rules.source = Some(path.to_path_buf());
+        Ok(rules)
+    }
+
+    /// Parse and compile rules from TOML text.
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        let file: RulesFile = toml::from_str(contents).context("Failed to parse tool rules")?;
+        if file.default == RuleAction::RateLimit {
+            bail!("default action must be allow, deny or ask");
+        }
+        let timezone = file
+            .timezone
+            .as_deref()
+            .map(|tz| {
+                tz.parse::<chrono_tz::Tz>()
+                    .map_err(|_| anyhow::anyhow!("Unknown timezone '{tz}'"))
+            })
+            .transpose()?;
+
+        let mut seen = HashSet::new();
+        let mut rules = Vec::with_capacity(file.rules.len());
+        for rule in file.rules {
+            if rule.name.trim().is_empty() {
+                bail!("every rule needs a non-empty name");
+            }
+            if !seen.insert(rule.name.clone()) {
+                bail!("duplicate rule name '{}'", rule.name);
+            }
+            rules
+                .push(compile_rule(rule.clone()).with_context(|| format!("rule '{}'", rule.name))?);
+        }
+
+        Ok(Self {
+            rules,
+            default: file.default,
+            timezone,
+            source: None,
+            hits: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Number of rules in the set.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Rules in evaluation order.
+    pub fn rules(&self) -> impl Iterator<Item = &ToolRule> {
+        self.rules.iter().map(|compiled| &compiled.rule)
+    }
+
+    pub fn default_action(&self) -> RuleAction {
+        self.default
+    }
+
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+
+    /// Current wall-clock time in the rules' timezone (local time if unset).
+    pub fn now(&self) -> NaiveDateTime {
+        match self.timezone {
+            Some(tz) => chrono::Utc::now().with_timezone(&tz).naive_local(),
+            None => chrono::Local::now().naive_local(),
+        }
+    }
+
+    /// Decide a tool call. Counts the call against a matching rate limit.
+    pub fn evaluate(&self, facts: &ToolCallFacts<'_>) -> RuleDecision {
+        for (idx, compiled) in self.rules.iter().enumerate() {
+            if compiled.mismatch(facts).is_none() {
+                return self.decide(idx, compiled, facts, true);
+            }
+        }
+        self.default_decision()
+    }
+
+    /// Dry-run evaluation: reports every rule up to the one that fires,
+    /// without recording rate-limit hits.
+    pub fn explain(&self, facts: &ToolCallFacts<'_>) -> Explanation {
+        let mut trace = Vec::new();
+        for (idx, compiled) in self.rules.iter().enumerate() {
+            match compiled.mismatch(facts) {
+                Some(detail) => trace.push(RuleTrace {
+                    rule: compiled.rule.name.clone(),
+                    action: compiled.rule.action,
+                    matched: false,
+                    detail,
+                }),
+                None => {
+                    let decision = self.decide(idx, compiled, facts, false);
+                    trace.push(RuleTrace {
+                        rule: compiled.rule.name.clone(),
+                        action: compiled.rule.action,
+                        matched: true,
+                        detail: decision.reason.clone(),
+                    });
+                    return Explanation { decision, trace };
+                }
+            }
+        }
+        Explanation {
+            decision: self.default_decision(),
+            trace,
+        }
+    }
+
+    fn default_decision(&self) -> RuleDecision {
+        RuleDecision {
+            verdict: match self.default {
+                RuleAction::Deny => Verdict::Deny,
+                RuleAction::Ask => Verdict::Ask,
+                RuleAction::Allow | RuleAction::RateLimit => Verdict::Allow,
+            },
+            rule: None,
+            reason: format!("no rule matched; default is {}", self.default.as_str()),
+        }
+    }
+
+    fn decide(
+        &self,
+        idx: usize,
+        compiled: &CompiledRule,
+        facts: &ToolCallFacts<'_>,
+        record: bool,
+    ) -> RuleDecision {
+        let rule = &compiled.rule;
+        let with_reason = |verdict: Verdict, fallback: String| RuleDecision {
+            verdict,
+            rule: Some(rule.name.clone()),
+            reason: rule.reason.clone().unwrap_or(fallback),
+        };
+        match rule.action {
+            RuleAction::Allow => with_reason(Verdict::Allow, "allowed".into()),
+            RuleAction::Deny => with_reason(Verdict::Deny, "denied by rule".into()),
+            RuleAction::Ask => with_reason(Verdict::Ask, "approval required".into()),
+            RuleAction::RateLimit => {
+                // Compiled rules always carry a limit for this action.
+                let Some(limit) = rule.limit.as_ref() else {
+                    return with_reason(Verdict::Deny, "rate limit misconfigured".into());
+                };
+                let key = match limit.per {
+                    RateLimitScope::Global => String::new(),
+                    RateLimitScope::Sender => facts.sender.unwrap_or_default().to_string(),
+                    RateLimitScope::Channel => facts.channel.to_string(),
+                };
+                let window = Duration::from_secs(limit.per_secs);
+                let mut hits = self.hits.lock();
+                if hits.len() >= MAX_RATE_LIMIT_BUCKETS && !hits.contains_key(&(idx, key.clone())) {
+                    self.evict_buckets(&mut hits);
+                }
+                let bucket = hits.entry((idx, key)).or_default();
+                while bucket.front().is_some_and(|hit| hit.elapsed() >= window) {
+                    bucket.pop_front();
+                }
+                let used = u32::try_from(bucket.len()).unwrap_or(u32::MAX);
+                if used >= limit.max {
+                    return with_reason(
+                        Verdict::Deny,
+                        format!(
+                            "rate limit exceeded ({} calls per {}s)",
+                            limit.max, limit.per_secs
+                        ),
+                    );
+                }
+                if record {
+                    bucket.push_back(Instant::now());
+                }
+                RuleDecision {
+                    verdict: Verdict::Allow,
+                    rule: Some(rule.name.clone()),
+                    reason: format!(
+                        "within rate limit ({}/{} calls per {}s)",
+                        used + u32::from(record),
+                        limit.max,
+                        limit.per_secs
+                    ),
+                }
+            }
+        }
+    }
+}
+
+impl ToolRules {
+    /// Drop buckets whose hits have all left their window; if every bucket is
+    /// still active, drop the one used least recently.
+    fn evict_buckets(&self, hits: &mut HashMap<(usize, String), VecDeque<Instant>>) {
+        hits.retain(|(idx, _), bucket| {
+            let window = self
+                .rules
+                .get(*idx)
+                .and_then(|compiled| compiled.rule.limit.as_ref())
+                .map_or(Duration::ZERO, |limit| Duration::from_secs(limit.per_secs));
+            bucket.back().is_some_and(|hit| hit.elapsed() < window)
+        });
+        if hits.len() >= MAX_RATE_LIMIT_BUCKETS {
+            let oldest = hits
+                .iter()
+                .min_by_key(|(_, bucket)| bucket.back().copied())
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                hits.remove(&oldest);
+            }
+        }
+    }
+}
+
+impl CompiledRule {
+    /// Describe the first condition that does not hold, or `None` on a match.
+    fn mismatch(&self, facts: &ToolCallFacts<'_>) -> Option<String> {
+        if !self.tools.is_empty() && !self.tools.iter().any(|p| p.matches(facts.tool)) {
+            return Some(format!(
+                "tool '{}' not in {:?}",
+                facts.tool, self.rule.tools
+            ));
+        }
+        if !self.channels.is_empty() && !self.channels.iter().any(|p| p.matches(facts.channel)) {
+            return Some(format!(
+                "channel '{}' not in {:?}",
+                facts.channel, self.rule.channels
+            ));
+        }
+        if !self.senders.is_empty() {
+            let Some(sender) = facts.sender else {
+                return Some("no sender for this call".into());
+            };
+            if !self.senders.iter().any(|p| p.matches(sender)) {
+                return Some(format!("sender '{sender}' not in {:?}", self.rule.senders));
+            }
+        }
+        if let Some((start, end)) = self.hours {
+            let time = facts.at.time();
+            let inside = if start <= end {
+                time >= start && time < end
+            } else {
+                time >= start || time < end
+            };
+            if !inside {
+                return Some(format!(
+                    "{} is outside {}",
+                    time.format("%H:%M"),
+                    self.rule.hours.as_deref().unwrap_or_default()
+                ));
+            }
+        }
+        if !self.days.is_empty() && !self.days.contains(&facts.at.weekday()) {
+            return Some(format!(
+                "{} not in {:?}",
+                facts.at.weekday(),
+                self.rule.days
+            ));
+        }
+        for arg in &self.args {
+            if let Some(detail) = arg.mismatch(facts.args) {
+                return Some(detail);
+            }
+        }
+        None
+    }
+}
+
+impl CompiledArg {
+    fn mismatch(&self, args: &Value) -> Option<String> {
+        let path = &self.raw_path;
+        let value = args.pointer(&self.pointer);
+        if let Some(expected) = self.condition.exists {
+            if value.is_some() != expected {
+                return Some(if expected {
+                    format!("argument '{path}' is missing")
+                } else {
+                    format!("argument '{path}' is present")
+                });
+            }
+        }
+        let needs_value = self.condition.equals.is_some()
+            || self.regex.is_some()
+            || self.condition.contains.is_some();
+        let Some(value) = value else {
+            return needs_value.then(|| format!("argument '{path}' is missing"));
+        };
+        let text = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        if let Some(ref expected) = self.condition.equals {
+            if value != expected {
+                return Some(format!("argument '{path}' != {expected}"));
+            }
+        }
+        if let Some(ref regex) = self.regex {
+            if !regex.is_match(&text) {
+                return Some(format!("argument '{path}' does not match /{regex}/"));
+            }
+        }
+        if let Some(ref needle) = self.condition.contains {
+            if !text.contains(needle.as_str()) {
+                return Some(format!("argument '{path}' does not contain '{needle}'"));
+            }
+        }
+        None
+    }
+}
+
+fn compile_rule(rule: ToolRule) -> Result<CompiledRule> {
+    match (rule.action, rule.limit.as_ref()) {
+        (RuleAction::RateLimit, None) => bail!("rate-limit rules need a `limit`"),
+        (RuleAction::RateLimit, Some(limit)) if limit.max == 0 || limit.per_secs == 0 => {
+            bail!("limit.max and limit.per_secs must be greater than zero")
+        }
+        (action, Some(_)) if action != RuleAction::RateLimit => {
+            bail!("`limit` is only valid on rate-limit rules")
+        }
+        _ => {}
+    }
+
+    let globs = |patterns: &[String], what: &str| -> Result<Vec<glob::Pattern>> {
+        patterns
+            .iter()
+            .map(|p| {
+                glob::Pattern::new(p.trim())
+                    .with_context(|| format!("invalid {what} pattern '{p}'"))
+            })
+            .collect()
+    };
+    let tools = globs(&rule.tools, "tool")?;
+    let channels = globs(&rule.channels, "channel")?;
+    let senders = globs(&rule.senders, "sender")?;
+
+    let args = rule
+        .args
+        .iter()
+        .map(|condition| {
+            let regex = condition
+                .regex
+                .as_deref()
+                .map(regex::Regex::new)
+                .transpose()
+                .with_context(|| format!("invalid regex for argument '{}'", condition.path))?;
+            Ok(CompiledArg {
+                raw_path: condition.path.clone(),
+                pointer: json_path_to_pointer(&condition.path)?,
+                condition: condition.clone(),
+                regex,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let hours = rule.hours.as_deref().map(parse_hours).transpose()?;
+    let days = rule
+        .days
+        .iter()
+        .map(|day| {
+            day.trim()
+                .parse::<Weekday>()
+                .map_err(|_| anyhow::anyhow!("invalid weekday '{day}'"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(CompiledRule {
+        rule,
+        tools,
+        channels,
+        senders,
+        args,
+        hours,
+        days,
+    })
+}
+
+fn parse_hours(raw: &str) -> Result<(NaiveTime, NaiveTime)> {
+    let (start, end) = raw
+        .split_once('-')
+        .with_context(|| format!("hours must look like HH:MM-HH:MM, got '{raw}'"))?;
+    let parse = |value: &str| {
+        NaiveTime::parse_from_str(value.trim(), "%H:%M")
+            .with_context(|| format!("invalid time '{}' in hours", value.trim()))
+    };
+    Ok((parse(start)?, parse(end)?))
+}
+
+/// Convert `a.b[0].c`, `$.a.b` or `/a/b` into a JSON pointer.
+fn json_path_to_pointer(path: &str) -> Result<String> {
+    let path = path.trim();
+    if path.starts_with('/') {
+        return Ok(path.to_string());
+    }
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let path = path.strip_prefix('.').unwrap_or(path);
+    if path.is_empty() {
+        bail!("argument path must not be empty");
+    }
+
+    let mut pointer = String::new();
+    for segment in path.split('.') {
+        let (key, indexes) = match segment.find('[') {
+            Some(pos) => (&segment[..pos], &segment[pos..]),
+            None => (segment, ""),
+        };
+        if !key.is_empty() {
+            pointer.push('/');
+            pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+        }
+        let mut rest = indexes;
+        while let Some(stripped) = rest.strip_prefix('[') {
+            let (index, tail) = stripped
+                .split_once(']')
+                .with_context(|| format!("unclosed '[' in argument path '{path}'"))?;
+            index
+                .parse::<usize>()
+                .with_context(|| format!("invalid index '{index}' in argument path '{path}'"))?;
+            pointer.push('/');
+            pointer.push_str(index);
+            rest = tail;
+        }
+        if !rest.is_empty() || (key.is_empty() && indexes.is_empty()) {
+            bail!("invalid argument path '{path}'");
+        }
+    }
+    Ok(pointer)
+}
+
+/// Resolve the configured rules path (`~` expanded, relative to the config dir).
+pub fn resolve_rules_path(config_dir: &Path, path: &str) -> PathBuf {
+    let expanded = PathBuf::from(shellexpand::tilde(path).into_owned());
+    if expanded.is_absolute() {
+        expanded
+    } else {
+        config_dir.join(expanded)
+    }
+}
+
+/// Load the rules configured in `[security.tool_rules]`.
+pub fn load_from_config(config: &crate::config::Config) -> Result<ToolRules> {
+    let config_dir = config
+        .config_path
+        .parent()
+        .context("Config path must have a parent directory")?;
+    ToolRules::load(&resolve_rules_path(
+        config_dir,
+        &config.security.tool_rules.path,
+    ))
+}
+
+pub fn handle_command(
+    command: crate::PolicyCommands,
+    config: &crate::config::Config,
+) -> Result<()> {
+    let rules = load_from_config(config)?;
+    match command {
+        crate::PolicyCommands::Check => {
+            println!(
+                "✅ {} — {} rule(s), default {}",
+                rules
+                    .source()
+                    .map_or_else(String::new, |p| p.display().to_string()),
+                rules.len(),
+                rules.default_action().as_str()
+            );
+            for rule in rules.rules() {
+                println!("  {:<28} {}", rule.name, rule.action.as_str());
+            }
+            if !config.security.tool_rules.enabled {
+                println!();
+                println!("Rules are not enforced: set [security.tool_rules].enabled = true");
+            }
+            Ok(())
+        }
+        crate::PolicyCommands::Explain {
+            tool,
+            args,
+            channel,
+            sender,
+            at,
+        } => {
+            let args: Value = match args {
+                Some(raw) => serde_json::from_str(&raw).context("--args must be a JSON object")?,
+                None => Value::Object(serde_json::Map::new()),
+            };
+            let at = match at {
+                Some(raw) => NaiveDateTime::parse_from_str(&raw, "%Y-%m-%dT%H:%M")
+                    .or_else(|_| NaiveDateTime::parse_from_str(&raw, "%Y-%m-%d %H:%M"))
+                    .with_context(|| {
+                        format!("--at must look like 2026-01-31T22:30, got '{raw}'")
+                    })?,
+                None => rules.now(),
+            };
+            let facts = ToolCallFacts {
+                tool: &tool,
+                args: &args,
+                channel: &channel,
+                sender: sender.as_deref(),
+                at,
+            };
+            let explanation = rules.explain(&facts);
+            for line in &explanation.trace {
+                println!(
+                    "{} {:<28} {:<10} {}",
+                    if line.matched { "→" } else { " " },
+                    line.rule,
+                    line.action.as_str(),
+                    line.detail
+                );
+            }
+            let decision = &explanation.decision;
+            println!();
+            println!(
+                "Decision: {} ({}) — {}",
+                decision.verdict,
+                decision.rule.as_deref().unwrap_or("default"),
+                decision.reason
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at(raw: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    fn facts<'a>(tool: &'a str, args: &'a Value) -> ToolCallFacts<'a> {
+        ToolCallFacts {
+            tool,
+            args,
+            channel: "telegram",
+            sender: Some("alice"),
+            // A Wednesday
+            at: at("2026-10-14T12:00"),
+        }
+    }
+
+    #[test]
+    fn empty_file_allows_by_default() {
+        let rules = ToolRules::from_toml("").unwrap();
+        let args = json!({});
+        let decision = rules.evaluate(&facts("shell", &args));
+        assert_eq!(decision.verdict, Verdict::Allow);
+        assert!(decision.rule.is_none());
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = ToolRules::from_toml(
+            r#"
+default = "deny"
+
+[[rules]]
+name = "block-rm"
+tools = ["shell"]
+action = "deny"
+reason = "no recursive deletes"
+args = [{ path = "command", regex = "rm\\s+-rf" }]
+
+[[rules]]
+name = "shell-ok"
+tools = ["shell"]
+action = "allow"
+"#,
+        )
+        .unwrap();
+
+        let dangerous = json!({"command": "rm -rf /tmp/x"});
+        let decision = rules.evaluate(&facts("shell", &dangerous));
+        assert_eq!(decision.verdict, Verdict::Deny);
+        assert_eq!(decision.rule.as_deref(), Some("block-rm"));
+        assert_eq!(decision.reason, "no recursive deletes");
+
+        let safe = json!({"command": "ls"});
+        assert_eq!(
+            rules.evaluate(&facts("shell", &safe)).rule.as_deref(),
+            Some("shell-ok")
+        );
+
+        let other = json!({});
+        let decision = rules.evaluate(&facts("file_read", &other));
+        assert_eq!(decision.verdict, Verdict::Deny);
+        assert!(decision.rule.is_none());
+    }
+
+    #[test]
+    fn matches_channel_sender_and_tool_globs() {
+        let rules = ToolRules::from_toml(
+            r#"
+[[rules]]
+name = "mcp-trusted"
+tools = ["mcp_*"]
+senders = ["alice", "ops-*"]
+action = "allow"
+
+[[rules]]
+name = "mcp-telegram"
+tools = ["mcp_*"]
+channels = ["tele*"]
+action = "ask"
+"#,
+        )
+        .unwrap();
+        let args = json!({});
+        let mut call = facts("mcp_github", &args);
+        assert_eq!(rules.evaluate(&call).rule.as_deref(), Some("mcp-trusted"));
+
+        call.sender = Some("ops-bot");
+        assert_eq!(rules.evaluate(&call).rule.as_deref(), Some("mcp-trusted"));
+
+        call.sender = Some("mallory");
+        assert_eq!(rules.evaluate(&call).verdict, Verdict::Ask);
+
+        call.sender = None;
+        assert_eq!(rules.evaluate(&call).rule.as_deref(), Some("mcp-telegram"));
+
+        call.channel = "discord";
+        assert!(rules.evaluate(&call).rule.is_none());
+        assert!(rules.evaluate(&facts("shell", &args)).rule.is_none());
+    }
+
+    #[test]
+    fn hours_window_wraps_midnight_and_days_filter() {
+        let rules = ToolRules::from_toml(
+            r#"
+[[rules]]
+name = "night"
+hours = "22:00-06:00"
+action = "deny"
+
+[[rules]]
+name = "weekend"
+days = ["sat", "sun"]
+action = "ask"
+"#,
+        )
+        .unwrap();
+        let args = json!({});
+        let mut call = facts("shell", &args);
+
+        call.at = at("2026-10-14T23:30");
+        assert_eq!(rules.evaluate(&call).rule.as_deref(), Some("night"));
+        call.at = at("2026-10-14T05:59");
+        assert_eq!(rules.evaluate(&call).rule.as_deref(), Some("night"));
+        call.at = at("2026-10-14T06:00");
+        assert!(rules.evaluate(&call).rule.is_none());
+        // Saturday noon
+        call.at = at("2026-10-17T12:00");
+        assert_eq!(rules.evaluate(&call).rule.as_deref(), Some("weekend"));
+    }
+
+    #[test]
+    fn arg_conditions_support_nested_paths() {
+        let rules = ToolRules::from_toml(
+            r#"
+[[rules]]
+name = "forced-push"
+tools = ["git_operations"]
+action = "deny"
+args = [
+  { path = "$.options.force", equals = true },
+  { path = "remotes[1]", contains = "prod" },
+]
+
+[[rules]]
+name = "no-path"
+action = "deny"
+args = [{ path = "/path", exists = false }]
+"#,
+        )
+        .unwrap();
+
+        let forced =
+            json!({"options": {"force": true}, "remotes": ["dev", "prod-eu"], "path": "."});
+        assert_eq!(
+            rules
+                .evaluate(&facts("git_operations", &forced))
+                .rule
+                .as_deref(),
+            Some("forced-push")
+        );
+
+        let gentle =
+            json!({"options": {"force": false}, "remotes": ["dev", "prod-eu"], "path": "."});
+        assert!(rules
+            .evaluate(&facts("git_operations", &gentle))
+            .rule
+            .is_none());
+
+        let pathless = json!({"command": "ls"});
+        assert_eq!(
+            rules.evaluate(&facts("shell", &pathless)).rule.as_deref(),
+            Some("no-path")
+        );
+    }
+
+    #[test]
+    fn rate_limit_counts_per_sender() {
+        let rules = ToolRules::from_toml(
+            r#"
+[[rules]]
+name = "http-budget"
+tools = ["http_request"]
+action = "rate-limit"
+limit = { max = 2, per_secs = 3600, per = "sender" }
+"#,
+        )
+        .unwrap();
+        let args = json!({});
+        let mut call = facts("http_request", &args);
+
+        assert_eq!(rules.evaluate(&call).verdict, Verdict::Allow);
+        assert_eq!(rules.evaluate(&call).verdict, Verdict::Allow);
+        let decision = rules.evaluate(&call);
+        assert_eq!(decision.verdict, Verdict::Deny);
+        assert!(decision.reason.contains("rate limit exceeded"));
+
+        call.sender = Some("bob");
+        assert_eq!(rules.evaluate(&call).verdict, Verdict::Allow);
+    }
+
+    #[test]
+    fn rate_limit_buckets_are_bounded() {
+        let rules = ToolRules::from_toml(
+            r#"
+[[rules]]
+name = "http-budget"
+tools = ["http_request"]
+action = "rate-limit"
+limit = { max = 1, per_secs = 3600, per = "sender" }
+"#,
+        )
+        .unwrap();
+        let args = json!({});
+        let senders: Vec<String> = (0..MAX_RATE_LIMIT_BUCKETS + 10)
+            .map(|i| format!("user-{i}"))
+            .collect();
+        for sender in &senders {
+            let mut call = facts("http_request", &args);
+            call.sender = Some(sender);
+            assert_eq!(rules.evaluate(&call).verdict, Verdict::Allow);
+        }
+        assert!(rules.hits.lock().len() <= MAX_RATE_LIMIT_BUCKETS);
+
+        // The most recent sender keeps its budget.
+        let mut call = facts("http_request", &args);
+        call.sender = senders.last().map(String::as_str);
+        assert_eq!(rules.evaluate(&call).verdict, Verdict::Deny);
+    }
+
+    #[test]
+    fn explain_traces_rules_without_consuming_rate_limit() {
+        let rules = ToolRules::from_toml(
+            r#"
+[[rules]]
+name = "only-cli"
+channels = ["cli"]
+action = "allow"
+
+[[rules]]
+name = "budget"
+action = "rate-limit"
+limit = { max = 1, per_secs = 60 }
+"#,
+        )
+        .unwrap();
+        let args = json!({});
+        let call = facts("shell", &args);
+
+        for _ in 0..3 {
+            let explanation = rules.explain(&call);
+            assert_eq!(explanation.trace.len(), 2);
+            assert!(!explanation.trace[0].matched);
+            assert!(explanation.trace[0].detail.contains("channel 'telegram'"));
+            assert!(explanation.trace[1].matched);
+            assert_eq!(explanation.decision.verdict, Verdict::Allow);
+        }
+        // The budget is still intact for a real call.
+        assert_eq!(rules.evaluate(&call).verdict, Verdict::Allow);
+        assert_eq!(rules.evaluate(&call).verdict, Verdict::Deny);
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        for bad in [
+            "default = \"rate-limit\"",
+            "[[rules]]\nname = \"x\"\naction = \"rate-limit\"",
+            "[[rules]]\nname = \"x\"\naction = \"allow\"\nlimit = { max = 1, per_secs = 1 }",
+            "[[rules]]\nname = \"x\"\naction = \"deny\"\nhours = \"25:00-01:00\"",
+            "[[rules]]\nname = \"x\"\naction = \"deny\"\ndays = [\"someday\"]",
+            "[[rules]]\nname = \"x\"\naction = \"deny\"\nargs = [{ path = \"a\", regex = \"(\" }]",
+            "[[rules]]\nname = \"x\"\naction = \"deny\"\nargs = [{ path = \"a[x]\" }]",
+            "[[rules]]\nname = \"x\"\naction = \"deny\"\n[[rules]]\nname = \"x\"\naction = \"allow\"",
+            "[[rules]]\nname = \"x\"\naction = \"deny\"\ntypo = 1",
+            "timezone = \"Mars/Olympus\"",
+        ] {
+            assert!(ToolRules::from_toml(bad).is_err(), "should reject: {bad}");
+        }
+    }
+
+    #[test]
+    fn json_paths_convert_to_pointers() {
+        assert_eq!(json_path_to_pointer("command").unwrap(), "/command");
+        assert_eq!(json_path_to_pointer("$.a.b").unwrap(), "/a/b");
+        assert_eq!(
+            json_path_to_pointer("files[0].path").unwrap(),
+            "/files/0/path"
+        );
+        assert_eq!(json_path_to_pointer("/raw/ptr").unwrap(), "/raw/ptr");
+        assert_eq!(json_path_to_pointer("m[1][2]").unwrap(), "/m/1/2");
+        assert!(json_path_to_pointer("").is_err());
+    }
+
+    #[test]
+    fn load_reads_file_and_records_source() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join(DEFAULT_RULES_FILE);
+        std::fs::write(&path, "[[rules]]\nname = \"a\"\naction = \"deny\"\n").unwrap();
+        let rules = ToolRules::load(&path).unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules.source(), Some(path.as_path()));
+        assert_eq!(resolve_rules_path(tmp.path(), DEFAULT_RULES_FILE), path);
+    }
+}
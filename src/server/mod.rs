@@ -1,12 +1,29 @@
-use crate::config::Config;
-use crate::tenant::{ContainerManager, GroupStore, TenantStore, UserRole, UserStore, VaultStore};
-use anyhow::Result;
+use crate::config::{Config, OtpConfig};
+use crate::container::capability_ceiling::{
+    CeilingManager, EscalationRequest, PermissionLevel, DEFAULT_ESCALATION_SECS,
+};
+use crate::relay::client::{
+    inbox_request_message, key_request_message, InboxRequest, INBOX_REQUEST_MAX_AGE_SECS,
+    REQUESTER_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+use crate::relay::{AgentPublicKey, RelayMessage, RelayServer};
+use crate::security::audit::{AuditEvent, AuditEventType, AuditLogger};
+use crate::security::{OtpValidator, SecretStore};
+use crate::tenant::container::ContainerBackend;
+use crate::tenant::credentials::{self, LoginRateLimiter, PasswordCheck};
+use crate::tenant::supervisor::{ContainerSupervisor, SupervisorSettings};
+use crate::tenant::{
+    ChatHistoryEntry, Container, ContainerManager, ContainerResources, GroupRole, GroupStore,
+    GroupVaultEntry, GroupVaultStore, NotificationStore, QuorumPolicy, TenantDb, TenantStore,
+    TenantWorkspace, UserRole, UserStore, VaultStore,
+};
+use anyhow::{Context, Result};
 use axum::{
     body::Body,
-    extract::{ws, Path, Query, State},
+    extract::{ws, ConnectInfo, Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
-    routing::{delete, get, patch, post},
+    routing::{delete, get, patch, post, put},
     Json, Router,
 };
 use chrono::Utc;
@@ -62,11 +79,26 @@ struct AppState {
     tenants: Arc<RwLock<TenantStore>>,
     groups: Arc<RwLock<GroupStore>>,
     vault: Arc<RwLock<VaultStore>>,
+    /// Quorum-unlocked secrets shared within a group
+    group_vault: Arc<RwLock<GroupVaultStore>>,
+    notifications: Arc<RwLock<NotificationStore>>,
+    /// Per-user agent permission ceilings and escalations
+    ceilings: Arc<RwLock<CeilingManager>>,
+    /// Records ceiling and escalation decisions
+    audit: Arc<AuditLogger>,
     containers: Arc<RwLock<ContainerManager>>,
+    /// Launches and supervises the instances behind `containers`
+    supervisor: Arc<ContainerSupervisor>,
+    /// Routes encrypted messages between container agents
+    relay: Arc<RelayServer>,
     config: Arc<RwLock<Option<Config>>>,
     http_client: Client,
     chat_messages: Arc<RwLock<Vec<ChatMessage>>>,
     start_time: std::time::Instant,
+    login_limiter: Arc<LoginRateLimiter>,
+    /// Encrypts per-user TOTP secrets at rest
+    secrets: Arc<SecretStore>,
+    data_dir: std::path::PathBuf,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -184,6 +216,26 @@ fn build_config_schema() -> ConfigSchemaResponse {
 struct LoginRequest {
     email: String,
     password: String,
+    /// TOTP code, required when the account has MFA enabled
+    #[serde(default)]
+    totp: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PasswordResetRequest {
+    token: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+struct MfaCodeRequest {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct MfaDisableRequest {
+    password: String,
+    code: String,
 }
 
 #[derive(Deserialize)]
@@ -197,6 +249,9 @@ struct VaultRequest {
     name: String,
     credential_type: String,
     value: String,
+    /// Let other members of the tenant use this entry (e.g. as a provider key)
+    #[serde(default)]
+    shared: bool,
 }
 
 #[derive(Deserialize)]
@@ -204,6 +259,48 @@ struct GroupRequest {
     name: String,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupMemberRequest {
+    user_id: String,
+    #[serde(default)]
+    role: Option<GroupRole>,
+}
+
+fn default_vault_threshold() -> u8 {
+    1
+}
+
+fn default_unlock_window_secs() -> u64 {
+    3600
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupVaultRequest {
+    name: String,
+    value: String,
+    #[serde(default = "default_vault_threshold")]
+    threshold: u8,
+    /// Users whose approval counts; empty means any group member
+    #[serde(default)]
+    approvers: Vec<String>,
+    #[serde(default = "default_unlock_window_secs")]
+    unlock_window_secs: u64,
+}
+
+#[derive(Deserialize)]
+struct VaultAccessRequest {
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NotificationQuery {
+    #[serde(default)]
+    unread: bool,
+}
+
 #[derive(Deserialize)]
 struct TenantRequest {
     name: String,
@@ -255,37 +352,92 @@ struct GatewayRequest {
 }
 
 async fn login(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     State(state): State<AppState>,
     Json(req): Json<LoginRequest>,
 ) -> Json<ApiResponse<serde_json::Value>> {
+    let email_key = req.email.trim().to_ascii_lowercase();
+    if !state.login_limiter.check(&peer.ip().to_string())
+        || !state.login_limiter.check(&format!("email:{email_key}"))
+    {
+        return Json(ApiResponse::err("Too many login attempts, try again later"));
+    }
+
     let user_data = {
         let users = state.users.read().await;
         users.get_by_email(&req.email).map(|u| {
             (
                 u.id.clone(),
                 u.email.clone(),
-                u.role.clone(),
+                u.role,
                 u.password_hash.clone(),
                 u.display_name.clone().unwrap_or_default(),
+                u.is_locked(),
+                u.mfa_enabled.then(|| u.mfa_secret.clone()).flatten(),
             )
         })
     };
 
-    if let Some((user_id, user_email, user_role, password_hash, user_name)) = user_data {
-        if verify_password(&req.password, &password_hash) {
-            let mut usersw = state.users.write().await;
-            if let Ok(session) = usersw.create_session(user_id.clone(), 604800) {
-                let role_str = match user_role {
-                    UserRole::Admin => "admin",
-                    UserRole::Owner => "admin",
-                    _ => "user",
-                };
-                return Json(ApiResponse::ok(
-                    serde_json::json!({ "token": session.token, "user": { "email": user_email, "role": role_str, "id": user_id, "name": user_name } }),
-                ));
+    let Some((user_id, user_email, user_role, password_hash, user_name, locked, mfa_secret)) =
+        user_data
+    else {
+        // Spend comparable time on unknown emails so they cannot be enumerated.
+        let _ = credentials::hash_password(&req.password);
+        return Json(ApiResponse::err("Invalid credentials"));
+    };
+    if locked {
+        return Json(ApiResponse::err(
+            "Account temporarily locked after repeated failed logins",
+        ));
+    }
+
+    let check = credentials::verify_password(&req.password, &password_hash);
+    let mut second_factor_ok = true;
+    if check.is_valid() {
+        if let Some(secret) = mfa_secret {
+            let Some(code) = req.totp.as_deref().filter(|c| !c.trim().is_empty()) else {
+                return Json(ApiResponse::err("mfa_required"));
+            };
+            second_factor_ok = verify_user_totp(&state.secrets, &secret, code);
+        }
+    }
+
+    let mut users = state.users.write().await;
+    if !check.is_valid() || !second_factor_ok {
+        let locked = users
+            .record_login_failure(
+                &user_id,
+                credentials::MAX_FAILED_LOGINS,
+                credentials::LOCKOUT_SECS,
+            )
+            .unwrap_or(false);
+        if locked {
+            tracing::warn!("Locked account {user_email} after repeated failed logins");
+        }
+        return Json(ApiResponse::err("Invalid credentials"));
+    }
+
+    if check == PasswordCheck::ValidNeedsRehash {
+        match credentials::hash_password(&req.password) {
+            Ok(hash) => {
+                if let Err(e) = users.set_password_hash(&user_id, hash) {
+                    tracing::warn!("Failed to upgrade password hash for {user_email}: {e}");
+                }
             }
+            Err(e) => tracing::warn!("Failed to rehash password for {user_email}: {e}"),
         }
     }
+    let _ = users.record_login_success(&user_id);
+
+    if let Ok(session) = users.create_session(user_id.clone(), 604_800) {
+        let role_str = match user_role {
+            UserRole::Admin | UserRole::Owner => "admin",
+            _ => "user",
+        };
+        return Json(ApiResponse::ok(
+            serde_json::json!({ "token": session.token, "user": { "email": user_email, "role": role_str, "id": user_id, "name": user_name } }),
+        ));
+    }
     Json(ApiResponse::err("Invalid credentials"))
 }
 
@@ -293,8 +445,13 @@ async fn register(
     State(state): State<AppState>,
     Json(req): Json<RegisterRequest>,
 ) -> Json<ApiResponse<serde_json::Value>> {
+    if let Err(e) = credentials::validate_new_password(&req.password) {
+        return Json(ApiResponse::err(&e.to_string()));
+    }
+    let Ok(password_hash) = credentials::hash_password(&req.password) else {
+        return Json(ApiResponse::err("Registration failed"));
+    };
     let mut users = state.users.write().await;
-    let password_hash = hash_password(&req.password);
 
     let is_first_user = users.is_empty();
     let role = if is_first_user {
@@ -358,72 +515,961 @@ async fn get_me(
     ))
 }
 
-async fn list_vault(State(state): State<AppState>) -> Json<ApiResponse<Vec<serde_json::Value>>> {
+async fn list_vault(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Vec<serde_json::Value>>> {
+    let Some((user_id, _)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
     let vault = state.vault.read().await;
-    let entries: Vec<_> = vault.get_by_user("demo").iter().map(|e| {
-        serde_json::json!({ "id": e.id, "name": e.name, "credentialType": e.credential_type, "updatedAt": e.updated_at })
+    let entries: Vec<_> = vault.get_by_user(&user_id).iter().map(|e| {
+        serde_json::json!({ "id": e.id, "name": e.name, "credentialType": e.credential_type, "shared": e.is_shared(), "updatedAt": e.updated_at })
     }).collect();
     Json(ApiResponse::ok(entries))
 }
 
 async fn create_vault(
+    headers: HeaderMap,
     State(state): State<AppState>,
     Json(req): Json<VaultRequest>,
 ) -> Json<ApiResponse<serde_json::Value>> {
+    let Some((user_id, tenant_id)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let metadata = req.shared.then(|| {
+        std::collections::HashMap::from([(
+            crate::tenant::vault::SHARED_METADATA_KEY.to_string(),
+            serde_json::Value::Bool(true),
+        )])
+    });
     let mut vault = state.vault.write().await;
     match vault.store(
-        "demo".to_string(),
-        "demo".to_string(),
+        tenant_id,
+        user_id,
         req.name,
         req.credential_type,
         &req.value,
-        None,
+        metadata,
         None,
     ) {
-        Ok(id) => Json(ApiResponse::ok(serde_json::json!({ "id": id }))),
+        Ok(entry) => Json(ApiResponse::ok(serde_json::json!({ "id": entry.id }))),
         Err(e) => Json(ApiResponse::err(&e.to_string())),
     }
 }
 
 async fn delete_vault(
+    headers: HeaderMap,
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Json<ApiResponse<serde_json::Value>> {
+    let Some((user_id, _)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
     let mut vault = state.vault.write().await;
-    vault.delete(&id);
-    Json(ApiResponse::ok(serde_json::json!({ "deleted": true })))
+    if vault.get(&id).is_none_or(|entry| entry.user_id != user_id) {
+        return Json(ApiResponse::err("Entry not found"));
+    }
+    match vault.delete(&id) {
+        Ok(()) => Json(ApiResponse::ok(serde_json::json!({ "deleted": true }))),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+/// The caller's role in `group_id`, if the group belongs to their tenant and
+/// they are a member.
+async fn group_role(
+    state: &AppState,
+    group_id: &str,
+    user_id: &str,
+    tenant_id: &str,
+) -> Option<GroupRole> {
+    let groups = state.groups.read().await;
+    groups.get(group_id).filter(|g| g.tenant_id == tenant_id)?;
+    groups
+        .get_members(group_id)
+        .iter()
+        .find(|m| m.user_id == user_id)
+        .map(|m| m.role)
+}
+
+async fn list_groups(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<Vec<serde_json::Value>>> {
+    let Some((user_id, tenant_id)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let groups = state.groups.read().await;
+    let list: Vec<_> = groups
+        .list_by_tenant(&tenant_id)
+        .iter()
+        .filter(|g| groups.is_member(&g.id, &user_id))
+        .map(|g| {
+            let members: Vec<_> = groups
+                .get_members(&g.id)
+                .iter()
+                .map(|m| serde_json::json!({ "userId": m.user_id, "role": m.role }))
+                .collect();
+            serde_json::json!({ "id": g.id, "name": g.name, "members": members })
+        })
+        .collect();
+    Json(ApiResponse::ok(list))
+}
+
+async fn create_group(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(req): Json<GroupRequest>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let Some((user_id, tenant_id)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let mut groups = state.groups.write().await;
+    let group = match groups.create(tenant_id, req.name, None, user_id.clone()) {
+        Ok(group) => group,
+        Err(e) => return Json(ApiResponse::err(&e.to_string())),
+    };
+    match groups.add_member(&group.id, user_id, GroupRole::Owner) {
+        Ok(()) => Json(ApiResponse::ok(
+            serde_json::json!({ "id": group.id, "name": group.name }),
+        )),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+async fn add_group_member(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(group_id): Path<String>,
+    Json(req): Json<GroupMemberRequest>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let Some((user_id, tenant_id)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    match group_role(&state, &group_id, &user_id, &tenant_id).await {
+        Some(GroupRole::Owner | GroupRole::Admin) => {}
+        Some(GroupRole::Member) => return Json(ApiResponse::err("Forbidden")),
+        None => return Json(ApiResponse::err("Group not found")),
+    }
+    let role = req.role.unwrap_or_default();
+    if role == GroupRole::Owner {
+        return Json(ApiResponse::err("Groups have a single owner"));
+    }
+    if state
+        .users
+        .read()
+        .await
+        .get(&req.user_id)
+        .is_none_or(|u| u.tenant_id != tenant_id)
+    {
+        return Json(ApiResponse::err("User not found"));
+    }
+    let mut groups = state.groups.write().await;
+    match groups.add_member(&group_id, req.user_id, role) {
+        Ok(()) => Json(ApiResponse::ok(serde_json::json!({ "added": true }))),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+fn group_vault_entry_json(entry: &GroupVaultEntry) -> serde_json::Value {
+    serde_json::json!({
+        "id": entry.id,
+        "name": entry.name,
+        "createdBy": entry.created_by,
+        "threshold": entry.threshold,
+        "approvers": entry.approvers,
+        "approvals": entry.approvals.iter().map(|a| &a.user_id).collect::<Vec<_>>(),
+        "pendingRequest": entry.pending_request,
+        "unlockWindowSecs": entry.unlock_window_secs,
+        "unlocked": entry.is_unlocked(),
+        "unlockedUntil": entry.unlocked_until.as_ref().filter(|_| entry.is_unlocked()),
+    })
+}
+
+async fn list_group_vault(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(group_id): Path<String>,
+) -> Json<ApiResponse<Vec<serde_json::Value>>> {
+    let Some((user_id, tenant_id)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    if group_role(&state, &group_id, &user_id, &tenant_id)
+        .await
+        .is_none()
+    {
+        return Json(ApiResponse::err("Group not found"));
+    }
+    let vault = state.group_vault.read().await;
+    Json(ApiResponse::ok(
+        vault
+            .list_by_group(&group_id)
+            .into_iter()
+            .map(group_vault_entry_json)
+            .collect(),
+    ))
+}
+
+async fn create_group_vault(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(group_id): Path<String>,
+    Json(req): Json<GroupVaultRequest>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let Some((user_id, tenant_id)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    if group_role(&state, &group_id, &user_id, &tenant_id)
+        .await
+        .is_none()
+    {
+        return Json(ApiResponse::err("Group not found"));
+    }
+    {
+        let groups = state.groups.read().await;
+        if let Some(outsider) = req
+            .approvers
+            .iter()
+            .find(|a| !groups.is_member(&group_id, a))
+        {
+            return Json(ApiResponse::err(&format!(
+                "Approver {outsider} is not a group member"
+            )));
+        }
+    }
+    let encrypted = match state.secrets.encrypt(&req.value) {
+        Ok(v) => v,
+        Err(e) => return Json(ApiResponse::err(&e.to_string())),
+    };
+    let policy = QuorumPolicy {
+        threshold: req.threshold,
+        approvers: req.approvers,
+        unlock_window_secs: req.unlock_window_secs,
+    };
+    let mut vault = state.group_vault.write().await;
+    match vault.create_with(group_id, req.name, encrypted, user_id, policy) {
+        Ok(entry) => Json(ApiResponse::ok(group_vault_entry_json(&entry))),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+/// Group members able to approve `entry`, other than `except`.
+async fn eligible_approvers(
+    state: &AppState,
+    entry: &GroupVaultEntry,
+    except: &str,
+) -> Vec<String> {
+    let groups = state.groups.read().await;
+    groups
+        .get_members(&entry.group_id)
+        .iter()
+        .map(|m| m.user_id.clone())
+        .filter(|id| id != except && entry.can_approve(id, true))
+        .collect()
+}
+
+async fn notify_users(
+    state: &AppState,
+    users: &[String],
+    kind: &str,
+    message: &str,
+    data: serde_json::Value,
+) {
+    let mut notifications = state.notifications.write().await;
+    for user in users {
+        if let Err(e) = notifications.notify(user, kind, message.to_string(), data.clone()) {
+            tracing::warn!("Failed to notify {user}: {e}");
+        }
+    }
+}
+
+/// The entry `entry_id` in `group_id`, when the caller is a member.
+async fn member_vault_entry(
+    headers: &HeaderMap,
+    state: &AppState,
+    group_id: &str,
+    entry_id: &str,
+) -> Result<(String, GroupVaultEntry), &'static str> {
+    let (user_id, tenant_id) = session_identity(headers, state)
+        .await
+        .ok_or("Unauthorized")?;
+    if group_role(state, group_id, &user_id, &tenant_id)
+        .await
+        .is_none()
+    {
+        return Err("Group not found");
+    }
+    let entry = state
+        .group_vault
+        .read()
+        .await
+        .get(entry_id)
+        .filter(|e| e.group_id == group_id)
+        .cloned()
+        .ok_or("Entry not found")?;
+    Ok((user_id, entry))
+}
+
+async fn request_group_vault_access(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path((group_id, entry_id)): Path<(String, String)>,
+    Json(req): Json<VaultAccessRequest>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let (user_id, entry) = match member_vault_entry(&headers, &state, &group_id, &entry_id).await {
+        Ok(found) => found,
+        Err(e) => return Json(ApiResponse::err(e)),
+    };
+    let request = match state.group_vault.write().await.request_access(
+        &entry_id,
+        user_id.clone(),
+        req.reason.clone(),
+    ) {
+        Ok(request) => request,
+        Err(e) => return Json(ApiResponse::err(&e.to_string())),
+    };
+    let approvers = eligible_approvers(&state, &entry, &user_id).await;
+    let reason = req.reason.map(|r| format!(": {r}")).unwrap_or_default();
+    notify_users(
+        &state,
+        &approvers,
+        "vault.access_requested",
+        &format!("{user_id} requested access to '{}'{reason}", entry.name),
+        serde_json::json!({ "groupId": group_id, "entryId": entry_id, "requestId": request.id }),
+    )
+    .await;
+    Json(ApiResponse::ok(
+        serde_json::json!({ "request": request, "notified": approvers.len() }),
+    ))
+}
+
+async fn approve_group_vault(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path((group_id, entry_id)): Path<(String, String)>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let (user_id, entry) = match member_vault_entry(&headers, &state, &group_id, &entry_id).await {
+        Ok(found) => found,
+        Err(e) => return Json(ApiResponse::err(e)),
+    };
+    if !entry.can_approve(&user_id, true) {
+        return Json(ApiResponse::err("You are not an approver for this entry"));
+    }
+    let requester = entry
+        .pending_request
+        .as_ref()
+        .map(|r| r.requested_by.clone());
+    let mut vault = state.group_vault.write().await;
+    let unlocked = match vault.approve(&entry_id, user_id) {
+        Ok(unlocked) => unlocked,
+        Err(e) => return Json(ApiResponse::err(&e.to_string())),
+    };
+    let entry = vault.get(&entry_id).cloned();
+    drop(vault);
+    if let (true, Some(requester), Some(entry)) = (unlocked, requester, &entry) {
+        notify_users(
+            &state,
+            &[requester],
+            "vault.unlocked",
+            &format!("'{}' is unlocked", entry.name),
+            serde_json::json!({ "groupId": group_id, "entryId": entry_id, "unlockedUntil": entry.unlocked_until }),
+        )
+        .await;
+    }
+    match entry {
+        Some(entry) => Json(ApiResponse::ok(group_vault_entry_json(&entry))),
+        None => Json(ApiResponse::err("Entry not found")),
+    }
+}
+
+async fn reveal_group_vault(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path((group_id, entry_id)): Path<(String, String)>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let (user_id, _) = match member_vault_entry(&headers, &state, &group_id, &entry_id).await {
+        Ok(found) => found,
+        Err(e) => return Json(ApiResponse::err(e)),
+    };
+    let vault = state.group_vault.read().await;
+    match vault.reveal(&entry_id, &user_id, "api", |v| state.secrets.decrypt(v)) {
+        Ok(value) => Json(ApiResponse::ok(serde_json::json!({ "value": value }))),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+async fn group_vault_audit(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path((group_id, entry_id)): Path<(String, String)>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    if let Err(e) = member_vault_entry(&headers, &state, &group_id, &entry_id).await {
+        return Json(ApiResponse::err(e));
+    }
+    match state.group_vault.read().await.access_log(&entry_id) {
+        Ok(log) => Json(ApiResponse::ok(
+            serde_json::to_value(log).unwrap_or_default(),
+        )),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+async fn list_notifications(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<NotificationQuery>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let Some((user_id, _)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let notifications = state.notifications.read().await;
+    Json(ApiResponse::ok(
+        serde_json::to_value(notifications.list_for_user(&user_id, query.unread))
+            .unwrap_or_default(),
+    ))
+}
+
+async fn mark_notification_read(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let Some((user_id, _)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    match state.notifications.write().await.mark_read(&user_id, &id) {
+        Ok(true) => Json(ApiResponse::ok(serde_json::json!({ "read": true }))),
+        Ok(false) => Json(ApiResponse::err("Notification not found")),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+/// The caller's id and tenant, when they are an admin.
+async fn admin_identity(headers: &HeaderMap, state: &AppState) -> Option<(String, String)> {
+    let (user_id, tenant_id) = session_identity(headers, state).await?;
+    let role = state.users.read().await.get(&user_id)?.role;
+    (role == UserRole::Admin).then_some((user_id, tenant_id))
+}
+
+/// Admins of `tenant_id` other than `except`.
+async fn tenant_admins(state: &AppState, tenant_id: &str, except: &str) -> Vec<String> {
+    state
+        .users
+        .read()
+        .await
+        .list_by_tenant(tenant_id)
+        .into_iter()
+        .filter(|u| u.role == UserRole::Admin && u.id != except)
+        .map(|u| u.id)
+        .collect()
+}
+
+async fn in_tenant(state: &AppState, user_id: &str, tenant_id: &str) -> bool {
+    state
+        .users
+        .read()
+        .await
+        .get(user_id)
+        .is_some_and(|u| u.tenant_id == tenant_id)
+}
+
+/// Record a ceiling or escalation decision in the audit log.
+fn audit_ceiling_decision(state: &AppState, actor: &str, action: String, allowed: bool) {
+    let event = AuditEvent::new(AuditEventType::ConfigChange)
+        .with_actor("api".into(), Some(actor.to_string()), None)
+        .with_action(action, "ceiling".into(), allowed, allowed);
+    if let Err(e) = state.audit.log(&event) {
+        tracing::warn!("Failed to write ceiling audit entry: {e}");
+    }
+}
+
+async fn my_ceiling(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let Some((user_id, _)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let ceilings = state.ceilings.read().await;
+    Json(ApiResponse::ok(serde_json::json!({
+        "ceiling": ceilings.get_ceiling(&user_id),
+        "effectiveCeiling": ceilings.effective_ceiling(&user_id),
+        "escalations": ceilings.list_escalations_for_user(&user_id),
+    })))
+}
+
+#[derive(Deserialize)]
+struct EscalationBody {
+    level: PermissionLevel,
+    #[serde(default)]
+    reason: String,
+}
+
+async fn request_escalation(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(req): Json<EscalationBody>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let Some((user_id, tenant_id)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let request = match state.ceilings.write().await.request_escalation(
+        &user_id,
+        req.level,
+        req.reason.clone(),
+    ) {
+        Ok(request) => request,
+        Err(e) => return Json(ApiResponse::err(&e.to_string())),
+    };
+    audit_ceiling_decision(
+        &state,
+        &user_id,
+        format!(
+            "escalation:{} requested {:?}",
+            request.id, request.requested_level
+        ),
+        false,
+    );
+    let admins = tenant_admins(&state, &tenant_id, &user_id).await;
+    notify_users(
+        &state,
+        &admins,
+        "ceiling.escalation_requested",
+        &format!(
+            "{user_id} requested {:?} agent permissions: {}",
+            req.level, req.reason
+        ),
+        serde_json::json!({ "requestId": request.id, "userId": user_id }),
+    )
+    .await;
+    Json(ApiResponse::ok(
+        serde_json::json!({ "request": request, "notified": admins.len() }),
+    ))
+}
+
+#[derive(Deserialize)]
+struct EscalationListQuery {
+    #[serde(default)]
+    status: Option<String>,
+}
+
+async fn admin_list_escalations(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<EscalationListQuery>,
+) -> Json<ApiResponse<Vec<EscalationRequest>>> {
+    let Some((_, tenant_id)) = admin_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Admin access required"));
+    };
+    let requests = state.ceilings.read().await.list_escalations();
+    let users = state.users.read().await;
+    let list = requests
+        .into_iter()
+        .filter(|r| query.status.as_deref().is_none_or(|s| r.status == s))
+        .filter(|r| {
+            users
+                .get(&r.user_id)
+                .is_some_and(|u| u.tenant_id == tenant_id)
+        })
+        .collect();
+    Json(ApiResponse::ok(list))
+}
+
+#[derive(Deserialize)]
+struct ApproveEscalationBody {
+    #[serde(default)]
+    duration_secs: Option<i64>,
+}
+
+async fn admin_approve_escalation(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<ApproveEscalationBody>,
+) -> Json<ApiResponse<EscalationRequest>> {
+    let duration = req.duration_secs.unwrap_or(DEFAULT_ESCALATION_SECS);
+    resolve_escalation(&headers, &state, &id, Some(duration)).await
+}
+
+async fn admin_deny_escalation(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Json<ApiResponse<EscalationRequest>> {
+    resolve_escalation(&headers, &state, &id, None).await
+}
+
+/// Approve (for `duration` seconds) or deny an escalation from the admin's tenant.
+async fn resolve_escalation(
+    headers: &HeaderMap,
+    state: &AppState,
+    id: &str,
+    duration: Option<i64>,
+) -> Json<ApiResponse<EscalationRequest>> {
+    let Some((admin_id, tenant_id)) = admin_identity(headers, state).await else {
+        return Json(ApiResponse::err("Admin access required"));
+    };
+    let mut ceilings = state.ceilings.write().await;
+    let Some(request) = ceilings.get_escalation(id) else {
+        return Json(ApiResponse::err("Escalation request not found"));
+    };
+    if !in_tenant(state, &request.user_id, &tenant_id).await {
+        return Json(ApiResponse::err("Escalation request not found"));
+    }
+    let result = match duration {
+        Some(secs) => ceilings.approve_escalation(id, &admin_id, secs),
+        None => ceilings.deny_escalation(id, &admin_id),
+    };
+    drop(ceilings);
+    let request = match result {
+        Ok(request) => request,
+        Err(e) => return Json(ApiResponse::err(&e.to_string())),
+    };
+    let approved = duration.is_some();
+    audit_ceiling_decision(
+        state,
+        &admin_id,
+        format!(
+            "escalation:{} {} {:?} for {} until {}",
+            request.id,
+            request.status,
+            request.requested_level,
+            request.user_id,
+            request.expires_at.as_deref().unwrap_or("-")
+        ),
+        approved,
+    );
+    let (kind, message) = if approved {
+        (
+            "ceiling.escalation_approved",
+            format!(
+                "Your agent may use {:?} tools for now",
+                request.requested_level
+            ),
+        )
+    } else {
+        (
+            "ceiling.escalation_denied",
+            format!("Your {:?} escalation was denied", request.requested_level),
+        )
+    };
+    notify_users(
+        state,
+        std::slice::from_ref(&request.user_id),
+        kind,
+        &message,
+        serde_json::json!({ "requestId": request.id, "expiresAt": request.expires_at }),
+    )
+    .await;
+    Json(ApiResponse::ok(request))
+}
+
+#[derive(Deserialize)]
+struct SetCeilingBody {
+    level: PermissionLevel,
+}
+
+async fn admin_set_ceiling(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    Json(req): Json<SetCeilingBody>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let Some((admin_id, tenant_id)) = admin_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Admin access required"));
+    };
+    if !in_tenant(&state, &user_id, &tenant_id).await {
+        return Json(ApiResponse::err("User not found"));
+    }
+    if let Err(e) = state
+        .ceilings
+        .write()
+        .await
+        .set_ceiling(&user_id, req.level)
+    {
+        return Json(ApiResponse::err(&e.to_string()));
+    }
+    audit_ceiling_decision(
+        &state,
+        &admin_id,
+        format!("ceiling of {user_id} set to {:?}", req.level),
+        true,
+    );
+    Json(ApiResponse::ok(
+        serde_json::json!({ "userId": user_id, "ceiling": req.level }),
+    ))
+}
+
+async fn list_containers(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Json<ApiResponse<Vec<serde_json::Value>>> {
+    let Some((user_id, _)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let containers = state.containers.read().await;
+    Json(ApiResponse::ok(
+        containers
+            .list_by_user(&user_id)
+            .into_iter()
+            .filter_map(|c| serde_json::to_value(c).ok())
+            .collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+struct CreateContainerRequest {
+    name: String,
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(default)]
+    backend: ContainerBackend,
+    #[serde(default)]
+    resources: ContainerResources,
+    /// Start the instance right away (default)
+    #[serde(default = "default_true")]
+    start: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+async fn create_container(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateContainerRequest>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let Some((user_id, tenant_id)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let max_containers = state
+        .tenants
+        .read()
+        .await
+        .get(&tenant_id)
+        .and_then(|t| t.settings.max_containers);
+    let (image, resources) = {
+        let config = state.config.read().await;
+        let (policy, default_image) = config.as_ref().map_or_else(
+            || {
+                (
+                    crate::config::ContainersConfig::default(),
+                    crate::config::DockerRuntimeConfig::default().image,
+                )
+            },
+            |c| (c.containers.clone(), c.runtime.docker.image.clone()),
+        );
+        match apply_container_policy(&policy, &default_image, &req) {
+            Ok(allowed) => allowed,
+            Err(e) => return Json(ApiResponse::err(&e)),
+        }
+    };
+
+    let container = {
+        let mut containers = state.containers.write().await;
+        if let Some(max) = max_containers {
+            if containers.list_by_tenant(&tenant_id).len() >= max as usize {
+                return Json(ApiResponse::err("Tenant container limit reached"));
+            }
+        }
+        match containers.create_with(tenant_id, user_id, req.name, image, req.backend, resources) {
+            Ok(container) => container,
+            Err(e) => return Json(ApiResponse::err(&e.to_string())),
+        }
+    };
+
+    if req.start {
+        if let Err(e) = state.supervisor.start(&container.id).await {
+            return Json(ApiResponse::err(&e.to_string()));
+        }
+    }
+    container_response(&state, &container.id).await
+}
+
+/// Check a create request against `[containers]`: the image and backend must
+/// be allowlisted, and resources are clamped to the maxima (a missing limit
+/// gets the maximum). Returns the image and resources to use.
+fn apply_container_policy(
+    policy: &crate::config::ContainersConfig,
+    default_image: &str,
+    req: &CreateContainerRequest,
+) -> Result<(String, ContainerResources), String> {
+    let backend = match req.backend {
+        ContainerBackend::Native => "native",
+        ContainerBackend::Docker => "docker",
+    };
+    if !policy.allowed_backends.iter().any(|b| b == backend) {
+        return Err(format!("Container backend '{backend}' is not allowed"));
+    }
+    let image = req
+        .image
+        .clone()
+        .unwrap_or_else(|| default_image.to_string());
+    let image_allowed = if policy.allowed_images.is_empty() {
+        image == default_image
+    } else {
+        policy.allowed_images.contains(&image)
+    };
+    if !image_allowed {
+        return Err(format!("Container image '{image}' is not allowed"));
+    }
+    let resources = ContainerResources {
+        cpu_limit: Some(
+            req.resources
+                .cpu_limit
+                .map_or(policy.max_cpu, |n| n.min(policy.max_cpu)),
+        ),
+        memory_limit: Some(
+            req.resources
+                .memory_limit
+                .map_or(policy.max_memory_mb, |n| n.min(policy.max_memory_mb)),
+        ),
+        disk_limit: Some(
+            req.resources
+                .disk_limit
+                .map_or(policy.max_disk_mb, |n| n.min(policy.max_disk_mb)),
+        ),
+    };
+    Ok((image, resources))
+}
+
+/// The container if it belongs to the bearer session's user.
+async fn owned_container(headers: &HeaderMap, state: &AppState, id: &str) -> Option<Container> {
+    let (user_id, _) = session_identity(headers, state).await?;
+    state
+        .containers
+        .read()
+        .await
+        .get(id)
+        .filter(|c| c.user_id == user_id)
+}
+
+async fn container_response(state: &AppState, id: &str) -> Json<ApiResponse<serde_json::Value>> {
+    match state.containers.read().await.get(id) {
+        Some(container) => Json(ApiResponse::ok(
+            serde_json::to_value(container).unwrap_or_default(),
+        )),
+        None => Json(ApiResponse::err("Container not found")),
+    }
+}
+
+async fn start_container(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    if owned_container(&headers, &state, &id).await.is_none() {
+        return Json(ApiResponse::err("Container not found"));
+    }
+    if let Err(e) = state.supervisor.start(&id).await {
+        return Json(ApiResponse::err(&e.to_string()));
+    }
+    container_response(&state, &id).await
+}
+
+async fn stop_container(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    if owned_container(&headers, &state, &id).await.is_none() {
+        return Json(ApiResponse::err("Container not found"));
+    }
+    if let Err(e) = state.supervisor.stop(&id).await {
+        return Json(ApiResponse::err(&e.to_string()));
+    }
+    container_response(&state, &id).await
+}
+
+async fn delete_container(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    if owned_container(&headers, &state, &id).await.is_none() {
+        return Json(ApiResponse::err("Container not found"));
+    }
+    match state.supervisor.remove(&id).await {
+        Ok(()) => Json(ApiResponse::ok(serde_json::json!({ "deleted": id }))),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+/// Returns a container's public key to a user session or a container of the
+/// same tenant.
+async fn relay_public_key(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let caller_tenant = match session_identity(&headers, &state).await {
+        Some((_, tenant_id)) => Some(tenant_id),
+        None => match relay_requester(&headers, &state, &container_id).await {
+            Some(requester) => state.relay.container_tenant(&requester).await,
+            None => return Json(ApiResponse::err("Unauthorized")),
+        },
+    };
+    if caller_tenant.is_none() || caller_tenant != state.relay.container_tenant(&container_id).await
+    {
+        return Json(ApiResponse::err("Unknown container"));
+    }
+    match state.relay.get_container_key(&container_id).await {
+        Some(key) => Json(ApiResponse::ok(serde_json::json!({ "publicKey": key }))),
+        None => Json(ApiResponse::err("Unknown container")),
+    }
 }
 
-async fn list_groups(State(state): State<AppState>) -> Json<ApiResponse<Vec<serde_json::Value>>> {
-    let groups = state.groups.read().await;
-    let list: Vec<_> = groups
-        .list_by_tenant("default")
-        .iter()
-        .map(|g| serde_json::json!({ "id": g.id, "name": g.name }))
-        .collect();
-    Json(ApiResponse::ok(list))
+/// The container that signed a key lookup for `target`, if the signature is
+/// valid and recent.
+async fn relay_requester(headers: &HeaderMap, state: &AppState, target: &str) -> Option<String> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let requester = header(REQUESTER_HEADER)?;
+    let timestamp: i64 = header(TIMESTAMP_HEADER)?.parse().ok()?;
+    let signature = header(SIGNATURE_HEADER)?;
+    if (Utc::now().timestamp() - timestamp).abs() > INBOX_REQUEST_MAX_AGE_SECS {
+        return None;
+    }
+    let encoded = state.relay.get_container_key(requester).await?;
+    let signed = key_request_message(requester, target, timestamp);
+    AgentPublicKey::decode(&encoded)
+        .and_then(|key| key.verify(signed.as_bytes(), signature))
+        .ok()?;
+    Some(requester.to_string())
 }
 
-async fn create_group(
+/// Accepts sealed messages from container agents; the relay authenticates
+/// the sender's signature before routing.
+async fn relay_send(
     State(state): State<AppState>,
-    Json(req): Json<GroupRequest>,
+    Json(msg): Json<RelayMessage>,
 ) -> Json<ApiResponse<serde_json::Value>> {
-    let mut groups = state.groups.write().await;
-    match groups.create("default".to_string(), req.name, None, "system".to_string()) {
-        Ok(group) => Json(ApiResponse::ok(
-            serde_json::json!({ "id": group.id, "name": group.name }),
-        )),
+    match state.relay.send(msg).await {
+        Ok(delivery) => Json(ApiResponse::ok(serde_json::json!({ "delivery": delivery }))),
         Err(e) => Json(ApiResponse::err(&e.to_string())),
     }
 }
 
-async fn list_containers(
+/// Returns a container's queued messages to a request signed with its key.
+async fn relay_inbox(
     State(state): State<AppState>,
-) -> Json<ApiResponse<Vec<serde_json::Value>>> {
-    let containers = state.containers.read().await;
-    Json(ApiResponse::ok(vec![
-        serde_json::json!({ "id": "demo", "status": "stopped" }),
-    ]))
+    Json(req): Json<InboxRequest>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    if (Utc::now().timestamp() - req.timestamp).abs() > INBOX_REQUEST_MAX_AGE_SECS {
+        return Json(ApiResponse::err("Inbox request expired"));
+    }
+    let Some(encoded) = state.relay.get_container_key(&req.container_id).await else {
+        return Json(ApiResponse::err("Unknown container"));
+    };
+    let signed = inbox_request_message(&req.container_id, req.timestamp);
+    let verified = AgentPublicKey::decode(&encoded)
+        .and_then(|key| key.verify(signed.as_bytes(), &req.signature));
+    if verified.is_err() {
+        return Json(ApiResponse::err("Invalid inbox signature"));
+    }
+    let messages = state.relay.take_queued(&req.container_id).await;
+    Json(ApiResponse::ok(serde_json::json!({ "messages": messages })))
 }
 
 async fn admin_list_tenants(
@@ -458,6 +1504,9 @@ async fn admin_list_tenants(
                     "userCount": user_count,
                     "groupCount": group_count,
                     "containerCount": container_count,
+                    "chatRequests": t.stats.chat_requests,
+                    "storageUsedBytes": t.stats.storage_used_bytes,
+                    "lastActivity": t.stats.last_activity,
                 }
             })
         })
@@ -602,8 +1651,14 @@ async fn admin_create_user(
     State(state): State<AppState>,
     Json(req): Json<RegisterRequest>,
 ) -> Json<ApiResponse<serde_json::Value>> {
+    if let Err(e) = credentials::validate_new_password(&req.password) {
+        return Json(ApiResponse::err(&e.to_string()));
+    }
+    let password_hash = match credentials::hash_password(&req.password) {
+        Ok(hash) => hash,
+        Err(e) => return Json(ApiResponse::err(&e.to_string())),
+    };
     let mut users = state.users.write().await;
-    let password_hash = hash_password(&req.password);
 
     match users.create(
         "default".to_string(),
@@ -636,43 +1691,361 @@ struct ChatRequest {
     message: String,
 }
 
-async fn handle_chat_proxy(Json(req): Json<ChatRequest>) -> Response<Body> {
-    let config = match Config::load_or_init().await {
-        Ok(cfg) => cfg,
+#[derive(Deserialize)]
+struct ChatHistoryQuery {
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+const CHAT_HISTORY_DEFAULT_LIMIT: usize = 100;
+
+fn chat_error(status: StatusCode, msg: &str) -> Response<Body> {
+    (status, Json(ApiResponse::<()>::err(msg))).into_response()
+}
+
+/// Run the agent for the calling user inside their tenant's workspace, with the
+/// tenant's provider key from the vault and a private per-user memory.
+async fn handle_chat(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(req): Json<ChatRequest>,
+) -> Response<Body> {
+    let Some((user_id, tenant_id)) = session_identity(&headers, &state).await else {
+        return chat_error(StatusCode::UNAUTHORIZED, "Unauthorized");
+    };
+    let Some(base_config) = state.config.read().await.clone() else {
+        return chat_error(StatusCode::SERVICE_UNAVAILABLE, "Server config unavailable");
+    };
+    let tenant_ws = match TenantWorkspace::new(&state.data_dir, &tenant_id) {
+        Ok(ws) => ws,
+        Err(e) => return chat_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+    let memory_dir = match tenant_ws.user_dir(&user_id) {
+        Ok(dir) => dir,
+        Err(e) => return chat_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+
+    let tenant = crate::config::TenantContext {
+        data_dir: state.data_dir.clone(),
+        tenant_id: tenant_id.clone(),
+        user_id: user_id.clone(),
+    };
+    let config =
+        match tenant_chat_config(&base_config, &tenant_ws, &*state.vault.read().await, tenant) {
+            Ok(config) => config,
+            Err(e) => return chat_error(StatusCode::FAILED_DEPENDENCY, &e.to_string()),
+        };
+
+    let started = Utc::now().to_rfc3339();
+    let result =
+        crate::agent::process_message_with_memory_dir(config, &req.message, &memory_dir).await;
+
+    let mut turns = vec![ChatHistoryEntry {
+        role: "user".into(),
+        content: req.message.clone(),
+        timestamp: started,
+    }];
+    if let Ok(response) = &result {
+        turns.push(ChatHistoryEntry {
+            role: "assistant".into(),
+            content: response.clone(),
+            timestamp: Utc::now().to_rfc3339(),
+        });
+    }
+    if let Err(e) = tenant_ws.append_history(&user_id, &turns) {
+        tracing::warn!("Failed to persist chat history for {user_id}: {e}");
+    }
+    if let Err(e) = state
+        .tenants
+        .write()
+        .await
+        .record_chat(&tenant_id, tenant_ws.storage_used_bytes())
+    {
+        tracing::debug!("Tenant stats not updated for {tenant_id}: {e}");
+    }
+
+    match result {
+        Ok(response) => Json(serde_json::json!({ "response": response })).into_response(),
+        Err(e) => chat_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+/// Config for a tenant chat turn. It is read from the tenant's own
+/// `config.toml` (defaults when absent) rather than the host config, so host
+/// API keys, fallback chains, integrations and auth profiles never serve a
+/// tenant turn; only the host's provider and model choice carry over to a
+/// tenant without a config file. The provider key comes from the tenant config or the
+/// vault, and the turn is refused when neither has one.
+fn tenant_chat_config(
+    host: &Config,
+    tenant_ws: &TenantWorkspace,
+    vault: &VaultStore,
+    tenant: crate::config::TenantContext,
+) -> Result<Config> {
+    let path = tenant_ws.config_path();
+    let mut config: Config = match fs::read_to_string(&path) {
+        Ok(raw) => toml::from_str(&raw)
+            .with_context(|| format!("Failed to parse tenant config {}", path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config {
+            default_provider: host.default_provider.clone(),
+            default_model: host.default_model.clone(),
+            ..Config::default()
+        },
         Err(e) => {
-            return Response::builder()
-                .status(StatusCode::SERVICE_UNAVAILABLE)
-                .body(Body::from(format!("Config error: {}", e)))
-                .unwrap_or_else(|_| Response::new(Body::empty()));
+            return Err(e)
+                .with_context(|| format!("Failed to read tenant config {}", path.display()))
         }
     };
+    config.workspace_dir = tenant_ws.workspace_dir();
+    // Auth profiles and the secret store resolve next to the config file.
+    if let (Some(key), Some(dir)) = (config.api_key.as_mut(), path.parent()) {
+        if SecretStore::is_encrypted(key) {
+            *key = SecretStore::new(dir, config.secrets.encrypt)
+                .decrypt(key)
+                .context("Failed to decrypt tenant api_key")?;
+        }
+    }
+    config.config_path = path;
+    // Fallback providers and shell passthrough read the host's environment.
+    config.reliability.fallback_providers.clear();
+    config.autonomy.shell_env_passthrough.clear();
+
+    let provider = config
+        .default_provider
+        .clone()
+        .unwrap_or_else(|| "openrouter".to_string());
+    if config
+        .api_key
+        .as_deref()
+        .is_none_or(|k| k.trim().is_empty())
+    {
+        config.api_key = match vault.provider_key(&tenant.tenant_id, &tenant.user_id, &provider) {
+            Ok(key) => key.filter(|k| !k.trim().is_empty()),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to read provider key for tenant {}: {e}",
+                    tenant.tenant_id
+                );
+                None
+            }
+        };
+    }
+    if config.api_key.is_none() {
+        anyhow::bail!("No provider key in vault for '{provider}'");
+    }
+    config.tenant_context = Some(tenant);
+    Ok(config)
+}
 
-    match crate::agent::loop_::run(config, Some(req.message), None, None, 0.7, vec![], false).await
+async fn chat_history(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<ChatHistoryQuery>,
+) -> Json<ApiResponse<Vec<ChatHistoryEntry>>> {
+    let Some((user_id, tenant_id)) = session_identity(&headers, &state).await else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let limit = query.limit.unwrap_or(CHAT_HISTORY_DEFAULT_LIMIT);
+    match TenantWorkspace::new(&state.data_dir, &tenant_id)
+        .and_then(|ws| ws.load_history(&user_id, limit))
     {
-        Ok(response) => Response::builder()
-            .status(StatusCode::OK)
-            .header("Content-Type", "application/json")
-            .body(Body::from(
-                serde_json::json!({ "response": response }).to_string(),
-            ))
-            .unwrap_or_else(|_| Response::new(Body::empty())),
-        Err(e) => Response::builder()
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .body(Body::from(e.to_string()))
-            .unwrap_or_else(|_| Response::new(Body::empty())),
+        Ok(history) => Json(ApiResponse::ok(history)),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
     }
 }
 
-fn verify_password(password: &str, hash: &str) -> bool {
-    hash == hash_password(password)
+/// Issue a single-use password reset token for a user. The token is only
+/// returned here; the store keeps its hash.
+async fn admin_create_password_reset(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Response<Body> {
+    let denied =
+        |status: StatusCode, msg: &str| (status, Json(ApiResponse::<()>::err(msg))).into_response();
+    if session_identity(&headers, &state).await.is_none() {
+        return denied(StatusCode::UNAUTHORIZED, "Unauthorized");
+    }
+    let Some((_, tenant_id)) = admin_identity(&headers, &state).await else {
+        return denied(StatusCode::FORBIDDEN, "Admin access required");
+    };
+    if !in_tenant(&state, &user_id, &tenant_id).await {
+        return denied(StatusCode::NOT_FOUND, "User not found");
+    }
+
+    let token = credentials::generate_reset_token();
+    let mut users = state.users.write().await;
+    match users.create_password_reset(
+        &user_id,
+        credentials::hash_reset_token(&token),
+        credentials::RESET_TOKEN_TTL_SECS,
+    ) {
+        Ok(()) => Json(ApiResponse::ok(serde_json::json!({
+            "token": token,
+            "expiresInSecs": credentials::RESET_TOKEN_TTL_SECS,
+        })))
+        .into_response(),
+        Err(e) => Json(ApiResponse::<()>::err(&e.to_string())).into_response(),
+    }
+}
+
+async fn reset_password(
+    State(state): State<AppState>,
+    Json(req): Json<PasswordResetRequest>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    if let Err(e) = credentials::validate_new_password(&req.password) {
+        return Json(ApiResponse::err(&e.to_string()));
+    }
+    let password_hash = match credentials::hash_password(&req.password) {
+        Ok(hash) => hash,
+        Err(e) => return Json(ApiResponse::err(&e.to_string())),
+    };
+
+    let mut users = state.users.write().await;
+    let user_id = match users.consume_password_reset(&credentials::hash_reset_token(&req.token)) {
+        Ok(Some(user_id)) => user_id,
+        Ok(None) => return Json(ApiResponse::err("Invalid or expired reset token")),
+        Err(e) => return Json(ApiResponse::err(&e.to_string())),
+    };
+    let result = users
+        .set_password_hash(&user_id, password_hash)
+        .and_then(|()| users.record_login_success(&user_id))
+        .and_then(|()| users.revoke_user_sessions(&user_id));
+    match result {
+        Ok(()) => Json(ApiResponse::ok(serde_json::json!({ "reset": true }))),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+/// Start TOTP enrolment: store a new (unconfirmed) secret and return its URI.
+async fn mfa_setup(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let mut users = state.users.write().await;
+    let Some(user_id) = session_user_id(&headers, &users) else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let Some(user) = users.get(&user_id) else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    if user.mfa_enabled {
+        return Json(ApiResponse::err("MFA is already enabled"));
+    }
+    let email = user.email.clone();
+
+    let secret = crate::security::otp::generate_base32_secret();
+    let uri = match user_totp(&secret) {
+        Ok(validator) => validator.otpauth_uri_for(&email),
+        Err(e) => return Json(ApiResponse::err(&e.to_string())),
+    };
+    let stored = match state.secrets.encrypt(&secret) {
+        Ok(stored) => stored,
+        Err(e) => return Json(ApiResponse::err(&e.to_string())),
+    };
+    match users.set_mfa(&user_id, Some(stored), false) {
+        Ok(()) => Json(ApiResponse::ok(serde_json::json!({
+            "secret": secret,
+            "otpauthUri": uri,
+        }))),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+/// Confirm TOTP enrolment with a code from the authenticator.
+async fn mfa_enable(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(req): Json<MfaCodeRequest>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let mut users = state.users.write().await;
+    let Some(user_id) = session_user_id(&headers, &users) else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let Some(secret) = users.get(&user_id).and_then(|u| u.mfa_secret.clone()) else {
+        return Json(ApiResponse::err("Run MFA setup first"));
+    };
+    if !verify_user_totp(&state.secrets, &secret, &req.code) {
+        return Json(ApiResponse::err("Invalid code"));
+    }
+    match users.set_mfa(&user_id, Some(secret), true) {
+        Ok(()) => Json(ApiResponse::ok(serde_json::json!({ "mfaEnabled": true }))),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+async fn mfa_disable(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(req): Json<MfaDisableRequest>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let mut users = state.users.write().await;
+    let Some(user_id) = session_user_id(&headers, &users) else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let Some(user) = users.get(&user_id) else {
+        return Json(ApiResponse::err("Unauthorized"));
+    };
+    let password_ok = credentials::verify_password(&req.password, &user.password_hash).is_valid();
+    let code_ok = user
+        .mfa_secret
+        .as_deref()
+        .is_some_and(|secret| verify_user_totp(&state.secrets, secret, &req.code));
+    if !password_ok || !code_ok {
+        return Json(ApiResponse::err("Invalid credentials"));
+    }
+    match users.set_mfa(&user_id, None, false) {
+        Ok(()) => Json(ApiResponse::ok(serde_json::json!({ "mfaEnabled": false }))),
+        Err(e) => Json(ApiResponse::err(&e.to_string())),
+    }
+}
+
+/// `(user_id, tenant_id)` of the bearer session's user.
+async fn session_identity(headers: &HeaderMap, state: &AppState) -> Option<(String, String)> {
+    let users = state.users.read().await;
+    let user_id = session_user_id(headers, &users)?;
+    let tenant_id = users.get(&user_id)?.tenant_id.clone();
+    Some((user_id, tenant_id))
+}
+
+fn session_user_id(headers: &HeaderMap, users: &UserStore) -> Option<String> {
+    let token = headers
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))?;
+    users.validate_session(token).map(|s| s.user_id.clone())
+}
+
+/// TOTP validator for a user's secret (standard 30 second step).
+fn user_totp(secret: &str) -> Result<OtpValidator> {
+    let config = OtpConfig {
+        enabled: true,
+        token_ttl_secs: 30,
+        cache_valid_secs: 0,
+        ..OtpConfig::default()
+    };
+    OtpValidator::from_base32_secret(&config, secret)
+}
+
+fn verify_user_totp(secrets: &SecretStore, stored_secret: &str, code: &str) -> bool {
+    secrets
+        .decrypt(stored_secret)
+        .and_then(|secret| user_totp(&secret))
+        .and_then(|validator| validator.validate(code))
+        .unwrap_or(false)
 }
 
-fn hash_password(password: &str) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    let mut hasher = DefaultHasher::new();
-    password.hash(&mut hasher);
-    format!("{:x}", hasher.finish())
+/// Whether the WebSocket session's user owns container `id`.
+async fn ws_owns_container(state: &AppState, session_user: Option<&str>, id: &str) -> bool {
+    let Some(user_id) = session_user else {
+        return false;
+    };
+    state
+        .containers
+        .read()
+        .await
+        .get(id)
+        .is_some_and(|c| c.user_id == user_id)
 }
 
 async fn ws_handler(
@@ -682,16 +2055,32 @@ async fn ws_handler(
 ) -> impl IntoResponse {
     let query_token = params.token.as_deref().unwrap_or("");
 
-    let authenticated = if !query_token.is_empty() {
-        let users = state.users.read().await;
-        users.validate_session(query_token).is_some()
+    let session_user = if query_token.is_empty() {
+        None
     } else {
-        false
+        let users = state.users.read().await;
+        users
+            .validate_session(query_token)
+            .map(|s| s.user_id.clone())
     };
+    let authenticated = session_user.is_some();
 
     let start_time = state.start_time.elapsed().as_millis() as u64;
     ws.on_upgrade(move |socket| async move {
         let (mut send, mut recv) = socket.split();
+        // Responses and pushed events (container logs) share one writer.
+        let (out_tx, mut out_rx) = tokio::sync::mpsc::unbounded_channel::<serde_json::Value>();
+        let writer = tokio::spawn(async move {
+            while let Some(message) = out_rx.recv().await {
+                if send
+                    .send(ws::Message::Text(message.to_string().into()))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
 
         while let Some(msg) = recv.next().await {
             if let Ok(ws::Message::Text(text)) = msg {
@@ -709,10 +2098,10 @@ async fn ws_handler(
                                         "channels.status", "config.get", "config.set", "config.apply", "config.patch", "config.schema",
                                         "cron.status", "cron.list", "cron.add", "cron.update", "cron.run", "cron.remove",
                                         "skills.status", "skills.update", "skills.install",
-                                        "node.list", "logs.tail", "usage.cost", "exec.approvals.get",
+                                        "node.list", "logs.tail", "containers.logs.subscribe", "usage.cost", "exec.approvals.get",
                                         "exec.approvals.set", "update.run", "gateway.list_methods"
                                     ],
-                                    "events": ["chat", "agents", "sessions", "channels", "cron", "exec"]
+                                    "events": ["chat", "agents", "sessions", "channels", "cron", "exec", "container.log"]
                                 },
                                 "snapshot": {
                                     "agents": [],
@@ -1159,12 +2548,84 @@ async fn ws_handler(
                             })
                         }
                         Some("logs.tail") => {
-                            serde_json::json!({
-                                "type": "res",
-                                "id": req.id,
-                                "ok": true,
-                                "payload": { "entries": [], "truncated": false }
-                            })
+                            let params = req.params.unwrap_or(serde_json::json!({}));
+                            let container_id = params.get("containerId").and_then(|v| v.as_str());
+                            let limit = params
+                                .get("limit")
+                                .and_then(serde_json::Value::as_u64)
+                                .and_then(|n| usize::try_from(n).ok())
+                                .unwrap_or(100);
+                            match container_id {
+                                Some(id) if ws_owns_container(&state, session_user.as_deref(), id).await => {
+                                    let entries = state.supervisor.recent_logs(id, limit);
+                                    serde_json::json!({
+                                        "type": "res",
+                                        "id": req.id,
+                                        "ok": true,
+                                        "payload": { "entries": entries, "truncated": entries.len() >= limit }
+                                    })
+                                }
+                                Some(_) => serde_json::json!({
+                                    "type": "res",
+                                    "id": req.id,
+                                    "ok": false,
+                                    "error": "Container not found"
+                                }),
+                                None => serde_json::json!({
+                                    "type": "res",
+                                    "id": req.id,
+                                    "ok": true,
+                                    "payload": { "entries": [], "truncated": false }
+                                }),
+                            }
+                        }
+                        Some("containers.logs.subscribe") => {
+                            let params = req.params.unwrap_or(serde_json::json!({}));
+                            let container_id = params
+                                .get("containerId")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or_default()
+                                .to_string();
+                            if ws_owns_container(&state, session_user.as_deref(), &container_id).await {
+                                let mut logs = state.supervisor.subscribe();
+                                let events = out_tx.clone();
+                                tokio::spawn(async move {
+                                    loop {
+                                        // Stop as soon as the socket goes away, not at the next line.
+                                        let received = tokio::select! {
+                                            received = logs.recv() => received,
+                                            () = events.closed() => break,
+                                        };
+                                        match received {
+                                            Ok(line) if line.container_id == container_id => {
+                                                let event = serde_json::json!({
+                                                    "type": "event",
+                                                    "event": "container.log",
+                                                    "payload": line
+                                                });
+                                                if events.send(event).is_err() {
+                                                    break;
+                                                }
+                                            }
+                                            Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                                            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                                        }
+                                    }
+                                });
+                                serde_json::json!({
+                                    "type": "res",
+                                    "id": req.id,
+                                    "ok": true,
+                                    "payload": { "subscribed": true }
+                                })
+                            } else {
+                                serde_json::json!({
+                                    "type": "res",
+                                    "id": req.id,
+                                    "ok": false,
+                                    "error": "Container not found"
+                                })
+                            }
                         }
                         Some("usage.cost") => {
                             serde_json::json!({
@@ -1258,17 +2719,15 @@ async fn ws_handler(
                                                 });
                                             }
 
-                                            let _ = send.send(ws::Message::Text(
-                                                serde_json::json!({
-                                                    "type": "event",
-                                                    "event": "chat",
-                                                    "payload": {
-                                                        "runId": run_id,
-                                                        "status": "ok",
-                                                        "content": response
-                                                    }
-                                                }).to_string().into()
-                                            )).await;
+                                            let _ = out_tx.send(serde_json::json!({
+                                                "type": "event",
+                                                "event": "chat",
+                                                "payload": {
+                                                    "runId": run_id,
+                                                    "status": "ok",
+                                                    "content": response
+                                                }
+                                            }));
 
                                             serde_json::json!({
                                                 "type": "res",
@@ -1320,7 +2779,7 @@ async fn ws_handler(
                                         "channels.status", "config.get", "config.set", "config.apply", "config.patch", "config.schema",
                                         "cron.status", "cron.list", "cron.add", "cron.update", "cron.run", "cron.remove",
                                         "skills.status", "skills.update", "skills.install",
-                                        "node.list", "logs.tail", "usage.cost", "exec.approvals.get",
+                                        "node.list", "logs.tail", "containers.logs.subscribe", "usage.cost", "exec.approvals.get",
                                         "exec.approvals.set", "update.run", "gateway.list_methods"
                                     ]
                                 }
@@ -1344,16 +2803,66 @@ async fn ws_handler(
                         }
                     };
 
-                    let _ = send.send(ws::Message::Text(response.to_string().into())).await;
+                    let _ = out_tx.send(response);
                 }
             }
         }
+        writer.abort();
     }).into_response()
 }
 
-pub async fn run_server(port: u16, host: &str, data_dir: &std::path::Path) -> Result<()> {
-    let users_store = UserStore::new(data_dir);
+/// Open the stores under `data_dir` and wire up the container supervisor.
+fn build_state(
+    data_dir: &std::path::Path,
+    config: Option<Config>,
+    relay_url: Option<String>,
+) -> Result<AppState> {
+    let db = TenantDb::open(data_dir)?;
+    let secrets = SecretStore::new(data_dir, true);
+    let containers = Arc::new(RwLock::new(ContainerManager::new(db.clone())));
+    let relay = Arc::new(RelayServer::new());
+    let mut supervisor_settings = SupervisorSettings::new(data_dir);
+    supervisor_settings.relay_url = relay_url;
+    if let Some(cfg) = &config {
+        supervisor_settings.docker = cfg.runtime.docker.clone();
+        supervisor_settings.security = cfg.security.clone();
+    }
+    let supervisor = ContainerSupervisor::new(
+        Arc::clone(&containers),
+        supervisor_settings,
+        Some(Arc::clone(&relay)),
+    );
+    let audit = AuditLogger::new(
+        config
+            .as_ref()
+            .map(|c| c.security.audit.clone())
+            .unwrap_or_default(),
+        data_dir.to_path_buf(),
+    )?;
+
+    Ok(AppState {
+        users: Arc::new(RwLock::new(UserStore::new(db.clone()))),
+        tenants: Arc::new(RwLock::new(TenantStore::new(db.clone()))),
+        groups: Arc::new(RwLock::new(GroupStore::new(db.clone()))),
+        vault: Arc::new(RwLock::new(VaultStore::open(db.clone(), data_dir)?)),
+        group_vault: Arc::new(RwLock::new(GroupVaultStore::new(data_dir))),
+        notifications: Arc::new(RwLock::new(NotificationStore::new(data_dir))),
+        ceilings: Arc::new(RwLock::new(CeilingManager::new(db))),
+        audit: Arc::new(audit),
+        containers,
+        supervisor,
+        relay,
+        config: Arc::new(RwLock::new(config)),
+        http_client: Client::new(),
+        chat_messages: Arc::new(RwLock::new(Vec::new())),
+        start_time: std::time::Instant::now(),
+        login_limiter: Arc::new(LoginRateLimiter::default()),
+        secrets: Arc::new(secrets),
+        data_dir: data_dir.to_path_buf(),
+    })
+}
 
+pub async fn run_server(port: u16, host: &str, data_dir: &std::path::Path) -> Result<()> {
     let config = match Config::load_or_init().await {
         Ok(cfg) => {
             tracing::info!("Config loaded from {:?}", cfg.config_path);
@@ -1365,17 +2874,13 @@ pub async fn run_server(port: u16, host: &str, data_dir: &std::path::Path) -> Re
         }
     };
 
-    let state = AppState {
-        users: Arc::new(RwLock::new(users_store)),
-        tenants: Arc::new(RwLock::new(TenantStore::new(data_dir))),
-        groups: Arc::new(RwLock::new(GroupStore::new(data_dir))),
-        vault: Arc::new(RwLock::new(VaultStore::new(data_dir, &[0u8; 32]))),
-        containers: Arc::new(RwLock::new(ContainerManager::new(data_dir))),
-        config: Arc::new(RwLock::new(config)),
-        http_client: Client::new(),
-        chat_messages: Arc::new(RwLock::new(Vec::new())),
-        start_time: std::time::Instant::now(),
-    };
+    let relay_host = if host == "0.0.0.0" { "127.0.0.1" } else { host };
+    let state = build_state(
+        data_dir,
+        config,
+        Some(format!("http://{relay_host}:{port}")),
+    )?;
+    state.supervisor.resume().await;
 
     let web_dist = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .join("multitenant-web")
@@ -1405,10 +2910,48 @@ pub async fn run_server(port: u16, host: &str, data_dir: &std::path::Path) -> Re
         .route("/api/auth/login", post(login))
         .route("/api/auth/register", post(register))
         .route("/api/auth/me", get(get_me))
+        .route("/api/auth/password-reset", post(reset_password))
+        .route("/api/auth/mfa/setup", post(mfa_setup))
+        .route("/api/auth/mfa/enable", post(mfa_enable))
+        .route("/api/auth/mfa/disable", post(mfa_disable))
         .route("/api/vault", get(list_vault).post(create_vault))
         .route("/api/vault/{id}", delete(delete_vault))
         .route("/api/groups", get(list_groups).post(create_group))
-        .route("/api/containers", get(list_containers))
+        .route("/api/groups/{group_id}/members", post(add_group_member))
+        .route(
+            "/api/groups/{group_id}/vault",
+            get(list_group_vault).post(create_group_vault),
+        )
+        .route(
+            "/api/groups/{group_id}/vault/{entry_id}/request",
+            post(request_group_vault_access),
+        )
+        .route(
+            "/api/groups/{group_id}/vault/{entry_id}/approve",
+            post(approve_group_vault),
+        )
+        .route(
+            "/api/groups/{group_id}/vault/{entry_id}/reveal",
+            post(reveal_group_vault),
+        )
+        .route(
+            "/api/groups/{group_id}/vault/{entry_id}/audit",
+            get(group_vault_audit),
+        )
+        .route("/api/notifications", get(list_notifications))
+        .route("/api/notifications/{id}/read", post(mark_notification_read))
+        .route("/api/ceilings/me", get(my_ceiling))
+        .route("/api/ceilings/escalations", post(request_escalation))
+        .route(
+            "/api/containers",
+            get(list_containers).post(create_container),
+        )
+        .route("/api/containers/{id}", delete(delete_container))
+        .route("/api/containers/{id}/start", post(start_container))
+        .route("/api/containers/{id}/stop", post(stop_container))
+        .route("/api/relay/keys/{container_id}", get(relay_public_key))
+        .route("/api/relay/messages", post(relay_send))
+        .route("/api/relay/inbox", post(relay_inbox))
         .route(
             "/api/admin/tenants",
             get(admin_list_tenants).post(admin_create_tenant),
@@ -1422,12 +2965,30 @@ pub async fn run_server(port: u16, host: &str, data_dir: &std::path::Path) -> Re
             get(admin_list_users).post(admin_create_user),
         )
         .route("/api/admin/users/{user_id}/tenant", post(admin_assign_user))
+        .route(
+            "/api/admin/users/{user_id}/password-reset",
+            post(admin_create_password_reset),
+        )
         .route(
             "/api/admin/users/{user_id}/role",
             patch(admin_update_user_role),
         )
         .route("/api/admin/stats", get(admin_stats))
-        .route("/api/chat", post(handle_chat_proxy))
+        .route("/api/admin/ceilings/{user_id}", put(admin_set_ceiling))
+        .route(
+            "/api/admin/ceilings/escalations",
+            get(admin_list_escalations),
+        )
+        .route(
+            "/api/admin/ceilings/escalations/{id}/approve",
+            post(admin_approve_escalation),
+        )
+        .route(
+            "/api/admin/ceilings/escalations/{id}/deny",
+            post(admin_deny_escalation),
+        )
+        .route("/api/chat", post(handle_chat))
+        .route("/api/chat/history", get(chat_history))
         .fallback_service(static_service)
         .with_state(state);
 
@@ -1436,7 +2997,241 @@ pub async fn run_server(port: u16, host: &str, data_dir: &std::path::Path) -> Re
     info!("Server running on http://{}", addr);
     let listener = tokio::net::TcpListener::bind(addr).await?;
 
-    axum::serve(listener, app.into_make_service()).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relay::AgentIdentity;
+    use axum::http::HeaderValue;
+
+    fn test_state(dir: &tempfile::TempDir) -> AppState {
+        build_state(dir.path(), None, None).unwrap()
+    }
+
+    async fn user_with_session(state: &AppState, role: UserRole) -> (String, HeaderMap) {
+        let mut users = state.users.write().await;
+        let user = users
+            .create(
+                "t1".into(),
+                format!("{}@example.com", Uuid::new_v4()),
+                String::new(),
+                role,
+            )
+            .unwrap();
+        let session = users.create_session(user.id.clone(), 3600).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Authorization",
+            HeaderValue::from_str(&format!("Bearer {}", session.token)).unwrap(),
+        );
+        (user.id, headers)
+    }
+
+    #[tokio::test]
+    async fn password_reset_requires_an_admin_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(&dir);
+        let (target, member_headers) = user_with_session(&state, UserRole::User).await;
+        let (_, admin_headers) = user_with_session(&state, UserRole::Admin).await;
+
+        let reset = |headers: HeaderMap| {
+            admin_create_password_reset(headers, State(state.clone()), Path(target.clone()))
+        };
+        assert_eq!(
+            reset(HeaderMap::new()).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(reset(member_headers).await.status(), StatusCode::FORBIDDEN);
+        assert_eq!(reset(admin_headers).await.status(), StatusCode::OK);
+    }
+
+    async fn relay_container(state: &AppState, id: &str, tenant: &str) -> AgentIdentity {
+        let identity = AgentIdentity::generate(format!("key-{id}")).unwrap();
+        state
+            .relay
+            .register_container(id.into(), tenant.into(), identity.public().encode())
+            .await
+            .unwrap();
+        identity
+    }
+
+    fn signed_key_request(identity: &AgentIdentity, requester: &str, target: &str) -> HeaderMap {
+        let timestamp = Utc::now().timestamp();
+        let signature = identity.sign(key_request_message(requester, target, timestamp).as_bytes());
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUESTER_HEADER, HeaderValue::from_str(requester).unwrap());
+        headers.insert(TIMESTAMP_HEADER, HeaderValue::from(timestamp));
+        headers.insert(SIGNATURE_HEADER, HeaderValue::from_str(&signature).unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn relay_keys_require_a_credential_from_the_same_tenant() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(&dir);
+        let alice = relay_container(&state, "alice", "t1").await;
+        relay_container(&state, "bob", "t1").await;
+        let mallory = relay_container(&state, "mallory", "t2").await;
+
+        let lookup = |headers: HeaderMap| async {
+            relay_public_key(headers, State(state.clone()), Path("bob".into()))
+                .await
+                .0
+                .data
+                .is_some()
+        };
+        assert!(!lookup(HeaderMap::new()).await);
+        assert!(!lookup(signed_key_request(&mallory, "mallory", "bob")).await);
+        assert!(!lookup(signed_key_request(&mallory, "alice", "bob")).await);
+        assert!(lookup(signed_key_request(&alice, "alice", "bob")).await);
+
+        let (_, session) = user_with_session(&state, UserRole::User).await;
+        assert!(lookup(session).await);
+    }
+
+    fn host_config_with_secrets() -> Config {
+        let mut host = Config::default();
+        host.api_key = Some("host-secret".into());
+        host.default_model = Some("host-model".into());
+        host.reliability.api_keys = vec!["host-rotation".into()];
+        host.reliability.fallback_providers = vec!["openai".into()];
+        host.composio.enabled = true;
+        host.composio.api_key = Some("host-composio".into());
+        host.autonomy.shell_env_passthrough = vec!["OPENAI_API_KEY".into()];
+        host
+    }
+
+    #[tokio::test]
+    async fn chat_without_a_vault_key_never_uses_the_host_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = build_state(dir.path(), Some(host_config_with_secrets()), None).unwrap();
+        let (_, headers) = user_with_session(&state, UserRole::User).await;
+
+        let response = handle_chat(
+            headers,
+            State(state),
+            Json(ChatRequest {
+                message: "hi".into(),
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FAILED_DEPENDENCY);
+    }
+
+    #[tokio::test]
+    async fn tenant_turns_use_the_tenant_config_and_vault_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(&dir);
+        let (user_id, _) = user_with_session(&state, UserRole::User).await;
+        let tenant_ws = TenantWorkspace::new(dir.path(), "t1").unwrap();
+        let tenant = crate::config::TenantContext {
+            data_dir: dir.path().to_path_buf(),
+            tenant_id: "t1".into(),
+            user_id: user_id.clone(),
+        };
+        let host = host_config_with_secrets();
+
+        state
+            .vault
+            .write()
+            .await
+            .store(
+                "t1".into(),
+                user_id,
+                "openrouter".into(),
+                "api_key".into(),
+                "tenant-key",
+                None,
+                None,
+            )
+            .unwrap();
+        let config = tenant_chat_config(
+            &host,
+            &tenant_ws,
+            &*state.vault.read().await,
+            tenant.clone(),
+        )
+        .unwrap();
+        assert_eq!(config.api_key.as_deref(), Some("tenant-key"));
+        assert_eq!(config.default_model.as_deref(), Some("host-model"));
+        assert_eq!(config.config_path, tenant_ws.config_path());
+        let rendered = toml::to_string(&config).unwrap();
+        for secret in [
+            "host-secret",
+            "host-rotation",
+            "host-composio",
+            "OPENAI_API_KEY",
+        ] {
+            assert!(!rendered.contains(secret), "tenant config leaked {secret}");
+        }
+        assert!(config.reliability.fallback_providers.is_empty());
+
+        // The tenant's own config, with its own encrypted key, wins over the
+        // vault and the host choice.
+        let secrets = SecretStore::new(tenant_ws.config_path().parent().unwrap(), true);
+        let own = Config {
+            api_key: Some(secrets.encrypt("tenant-config-key").unwrap()),
+            default_provider: Some("openai".into()),
+            ..Config::default()
+        };
+        fs::write(tenant_ws.config_path(), toml::to_string(&own).unwrap()).unwrap();
+        let config =
+            tenant_chat_config(&host, &tenant_ws, &*state.vault.read().await, tenant).unwrap();
+        assert_eq!(config.api_key.as_deref(), Some("tenant-config-key"));
+        assert_eq!(config.default_provider.as_deref(), Some("openai"));
+    }
+
+    #[test]
+    fn container_requests_are_limited_by_policy() {
+        let policy = crate::config::ContainersConfig::default();
+        let request = |image: Option<&str>, backend, resources| CreateContainerRequest {
+            name: "agent".into(),
+            image: image.map(str::to_string),
+            backend,
+            resources,
+            start: false,
+        };
+
+        let unbounded = ContainerResources {
+            cpu_limit: Some(64),
+            memory_limit: None,
+            disk_limit: Some(1),
+        };
+        let (image, resources) = apply_container_policy(
+            &policy,
+            "alpine:3.20",
+            &request(None, ContainerBackend::Docker, unbounded),
+        )
+        .unwrap();
+        assert_eq!(image, "alpine:3.20");
+        assert_eq!(resources.cpu_limit, Some(policy.max_cpu));
+        assert_eq!(resources.memory_limit, Some(policy.max_memory_mb));
+        assert_eq!(resources.disk_limit, Some(1));
+
+        let other_image = request(
+            Some("attacker/miner"),
+            ContainerBackend::Docker,
+            ContainerResources::default(),
+        );
+        assert!(apply_container_policy(&policy, "alpine:3.20", &other_image).is_err());
+
+        let docker_only = crate::config::ContainersConfig {
+            allowed_backends: vec!["docker".into()],
+            ..policy
+        };
+        let native = request(
+            None,
+            ContainerBackend::Native,
+            ContainerResources::default(),
+        );
+        assert!(apply_container_policy(&docker_only, "alpine:3.20", &native).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Lockfile name, stored next to the installed skills.
+pub const LOCKFILE_NAME: &str = "skills.lock";
+const LOCKFILE_VERSION: u32 = 1;
+
+/// Where an installed skill came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkillSourceKind {
+    Git,
+    Local,
+}
+
+/// A pinned, installed skill.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedSkill {
+    /// Directory name under `skills/`
+    pub name: String,
+    pub version: String,
+    pub source: String,
+    pub source_kind: SkillSourceKind,
+    /// Commit the skill was installed from (git sources only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// `sha256:<hex>` digest over the skill's files, see [`content_digest`]
+    pub digest: String,
+    /// Trusted publisher whose signature was verified at install time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    pub installed_at: String,
+}
+
+/// `skills.lock`: one entry per installed skill with its source, commit and digest.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkillLockfile {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default, rename = "skill")]
+    pub skills: Vec<LockedSkill>,
+}
+
+impl SkillLockfile {
+    pub fn path(skills_dir: &Path) -> PathBuf {
+        skills_dir.join(LOCKFILE_NAME)
+    }
+
+    /// Load the lockfile, returning an empty one when it does not exist yet.
+    pub fn load(skills_dir: &Path) -> Result<Self> {
+        let path = Self::path(skills_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, skills_dir: &Path) -> Result<()> {
+        let mut lockfile = self.clone();
+        lockfile.version = LOCKFILE_VERSION;
+        lockfile.skills.sort_by(|a, b| a.name.cmp(&b.name));
+        let body = toml::to_string_pretty(&lockfile)?;
+        let path = Self::path(skills_dir);
+        std::fs::write(
+            &path,
+            format!("# Generated by `zeroclaw skills`. Do not edit by hand.\n\n{body}"),
+        )
+        .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LockedSkill> {
+        self.skills.iter().find(|entry| entry.name == name)
+    }
+
+    pub fn upsert(&mut self, entry: LockedSkill) {
+        self.remove(&entry.name);
+        self.skills.push(entry);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<LockedSkill> {
+        let index = self.skills.iter().position(|entry| entry.name == name)?;
+        Some(self.skills.remove(index))
+    }
+}
+
+/// Digest over every file in `dir` (relative path and contents, in sorted order),
+/// ignoring `.git`. Stable across machines and copy operations.
+pub fn content_digest(dir: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    for rel in skill_files(dir)? {
+        let bytes = std::fs::read(dir.join(&rel))
+            .with_context(|| format!("failed to read {}", dir.join(&rel).display()))?;
+        hasher.update(rel.as_bytes());
+        hasher.update([0]);
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
+    }
+    Ok(format!("sha256:{}", hex::encode(hasher.finalize())))
+}
+
+/// Files under a skill directory as sorted, `/`-separated relative paths,
+/// skipping `.git`. Symlinks are an error: installed skills cannot contain
+/// them, and following one would hash content from outside the skill.
+pub(super) fn skill_files(dir: &Path) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_files(root: &Path, dir: &Path, out: &mut Vec<String>) -> Result<()> {
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?
+    {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            anyhow::bail!("symlinks are not allowed in skills: {}", path.display());
+        }
+        if file_type.is_dir() {
+            collect_files(root, &path, out)?;
+        } else if file_type.is_file() {
+            let rel = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/");
+            out.push(rel);
+        }
+    }
+    Ok(())
+}
+
+/// Current `HEAD` commit of a git checkout.
+pub fn git_head_commit(repo_dir: &Path) -> Result<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(repo_dir)
+        .output()
+        .context("failed to run git rev-parse")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git rev-parse failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Commit the remote `HEAD` of `source` currently points to.
+pub fn git_remote_head(source: &str) -> Result<String> {
+    let output = std::process::Command::new("git")
+        .args(["ls-remote", source, "HEAD"])
+        .output()
+        .context("failed to run git ls-remote")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git ls-remote failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+        .context("git ls-remote returned no HEAD")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(name: &str) -> LockedSkill {
+        LockedSkill {
+            name: name.into(),
+            version: "1.0.0".into(),
+            source: format!("/src/{name}"),
+            source_kind: SkillSourceKind::Local,
+            commit: None,
+            digest: "sha256:00".into(),
+            publisher: None,
+            installed_at: "2026-01-01T00:00:00Z".into(),
+        }
+    }
+
+    #[test]
+    fn lockfile_roundtrips_sorted_entries() {
+        let tmp = TempDir::new().unwrap();
+        let mut lock = SkillLockfile::load(tmp.path()).unwrap();
+        assert!(lock.skills.is_empty());
+
+        lock.upsert(entry("zeta"));
+        lock.upsert(entry("alpha"));
+        let mut updated = entry("zeta");
+        updated.version = "2.0.0".into();
+        lock.upsert(updated);
+        lock.save(tmp.path()).unwrap();
+
+        let loaded = SkillLockfile::load(tmp.path()).unwrap();
+        assert_eq!(loaded.version, LOCKFILE_VERSION);
+        assert_eq!(loaded.skills.len(), 2);
+        assert_eq!(loaded.skills[0].name, "alpha");
+        assert_eq!(loaded.get("zeta").unwrap().version, "2.0.0");
+    }
+
+    #[test]
+    fn content_digest_tracks_content_and_ignores_git() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("docs")).unwrap();
+        std::fs::write(dir.join("SKILL.md"), "# Skill").unwrap();
+        std::fs::write(dir.join("docs/a.md"), "a").unwrap();
+        let first = content_digest(dir).unwrap();
+        assert!(first.starts_with("sha256:"));
+
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(dir.join(".git/HEAD"), "ref").unwrap();
+        assert_eq!(content_digest(dir).unwrap(), first);
+
+        std::fs::write(dir.join("docs/a.md"), "b").unwrap();
+        assert_ne!(content_digest(dir).unwrap(), first);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn content_digest_rejects_symlinked_files() {
+        let tmp = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("secret"), "outside").unwrap();
+        std::fs::write(tmp.path().join("SKILL.md"), "# Skill").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret"), tmp.path().join("link.md"))
+            .unwrap();
+
+        let err = content_digest(tmp.path()).unwrap_err();
+        assert!(err.to_string().contains("symlinks"), "{err}");
+    }
+}
@@ -7,6 +7,11 @@ use std::process::Command;
 use std::time::{Duration, SystemTime};
 
 mod audit;
+mod lock;
+mod requires;
+mod signing;
+
+use lock::{LockedSkill, SkillLockfile, SkillSourceKind};
 
 const OPEN_SKILLS_REPO_URL: &str = "https://github.com/besoeasy/open-skills";
 const OPEN_SKILLS_SYNC_MARKER: &str = ".zeroclaw-open-skills-sync";
@@ -28,6 +33,8 @@ pub struct Skill {
     pub tools: Vec<SkillTool>,
     #[serde(default)]
     pub prompts: Vec<String>,
+    #[serde(default)]
+    pub policy: SkillPolicy,
     #[serde(skip)]
     pub location: Option<PathBuf>,
 }
@@ -45,6 +52,30 @@ pub struct SkillTool {
     pub args: HashMap<String, String>,
 }
 
+/// Per-skill execution policy (`[policy]` in SKILL.toml).
+///
+/// Applied on top of the global security policy when the skill's tools are
+/// invoked; it can only narrow what the global policy already allows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkillPolicy {
+    /// Commands `shell` tools may run (empty = global allowlist only)
+    #[serde(default)]
+    pub allowed_commands: Vec<String>,
+    /// Hosts `http` tools may reach within `[http_request].allowed_domains`
+    /// (empty = the host in each tool's URL)
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// Per-call timeout in seconds (default: 60)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// OS sandbox backend for `shell`/`script` tools (unset = no extra sandbox)
+    #[serde(default)]
+    pub sandbox: Option<crate::config::SandboxBackend>,
+    /// Require `approved=true` on every call in supervised mode
+    #[serde(default)]
+    pub require_approval: bool,
+}
+
 /// Skill manifest parsed from SKILL.toml
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct SkillManifest {
@@ -53,6 +84,10 @@ struct SkillManifest {
     tools: Vec<SkillTool>,
     #[serde(default)]
     prompts: Vec<String>,
+    #[serde(default)]
+    policy: SkillPolicy,
+    #[serde(default)]
+    requires: requires::SkillRequirements,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,11 +113,55 @@ pub fn load_skills(workspace_dir: &Path) -> Vec<Skill> {
 
 /// Load skills using runtime config values (preferred at runtime).
 pub fn load_skills_with_config(workspace_dir: &Path, config: &crate::config::Config) -> Vec<Skill> {
-    load_skills_with_open_skills_config(
+    let skills = load_skills_with_open_skills_config(
         workspace_dir,
         Some(config.skills.open_skills_enabled),
         config.skills.open_skills_dir.as_deref(),
-    )
+    );
+    if config.skills.signature_policy != crate::config::SkillSignaturePolicy::RequireSigned {
+        return skills;
+    }
+    retain_trusted_skills(skills, &zeroclaw_dir(config))
+}
+
+fn zeroclaw_dir(config: &crate::config::Config) -> PathBuf {
+    config
+        .config_path
+        .parent()
+        .map_or_else(|| PathBuf::from("."), PathBuf::from)
+}
+
+/// Drop skills whose directory lacks a valid signature from a trusted publisher.
+fn retain_trusted_skills(skills: Vec<Skill>, zeroclaw_dir: &Path) -> Vec<Skill> {
+    let trust = match signing::TrustStore::load(zeroclaw_dir) {
+        Ok(trust) => trust,
+        Err(err) => {
+            tracing::warn!("Skipping all skills: failed to load publisher trust store: {err}");
+            return Vec::new();
+        }
+    };
+    skills
+        .into_iter()
+        .filter(|skill| {
+            let Some(dir) = skill.location.as_deref().and_then(Path::parent) else {
+                return false;
+            };
+            match signing::verify_skill_directory(dir, &trust) {
+                Ok(signing::SignatureStatus::Trusted { .. }) => true,
+                Ok(status) => {
+                    tracing::warn!(
+                        "Skipping skill '{}' (signature policy require-signed): {status:?}",
+                        skill.name
+                    );
+                    false
+                }
+                Err(err) => {
+                    tracing::warn!("Skipping skill '{}': {err}", skill.name);
+                    false
+                }
+            }
+        })
+        .collect()
 }
 
 fn load_skills_with_open_skills_config(
@@ -413,6 +492,7 @@ fn load_skill_toml(path: &Path) -> Result<Skill> {
         tags: manifest.skill.tags,
         tools: manifest.tools,
         prompts: manifest.prompts,
+        policy: manifest.policy,
         location: Some(path.to_path_buf()),
     })
 }
@@ -434,6 +514,7 @@ fn load_skill_md(path: &Path, dir: &Path) -> Result<Skill> {
         tags: Vec::new(),
         tools: Vec::new(),
         prompts: vec![content],
+        policy: SkillPolicy::default(),
         location: Some(path.to_path_buf()),
     })
 }
@@ -454,6 +535,7 @@ fn load_open_skill_md(path: &Path) -> Result<Skill> {
         tags: vec!["open-skills".to_string()],
         tools: Vec::new(),
         prompts: vec![content],
+        policy: SkillPolicy::default(),
         location: Some(path.to_path_buf()),
     })
 }
@@ -805,7 +887,7 @@ fn install_local_skill_source(source: &str, skills_path: &Path) -> Result<(PathB
     }
 }
 
-fn install_git_skill_source(source: &str, skills_path: &Path) -> Result<(PathBuf, usize)> {
+fn install_git_skill_source(source: &str, skills_path: &Path) -> Result<(PathBuf, usize, String)> {
     let before = snapshot_skill_children(skills_path)?;
     let output = std::process::Command::new("git")
         .args(["clone", "--depth", "1", source])
@@ -817,9 +899,16 @@ fn install_git_skill_source(source: &str, skills_path: &Path) -> Result<(PathBuf
     }
 
     let installed_dir = detect_newly_installed_directory(skills_path, &before)?;
+    let commit = match lock::git_head_commit(&installed_dir) {
+        Ok(commit) => commit,
+        Err(err) => {
+            let _ = std::fs::remove_dir_all(&installed_dir);
+            return Err(err);
+        }
+    };
     remove_git_metadata(&installed_dir)?;
     match enforce_skill_security_audit(&installed_dir) {
-        Ok(report) => Ok((installed_dir, report.files_scanned)),
+        Ok(report) => Ok((installed_dir, report.files_scanned, commit)),
         Err(err) => {
             let _ = std::fs::remove_dir_all(&installed_dir);
             Err(err)
@@ -827,6 +916,329 @@ fn install_git_skill_source(source: &str, skills_path: &Path) -> Result<(PathBuf
     }
 }
 
+/// Maximum depth of skill dependency chains resolved during install.
+const MAX_SKILL_DEPENDENCY_DEPTH: usize = 8;
+
+/// Signature policy and trust store applied while installing or updating skills.
+struct SignatureGate {
+    policy: crate::config::SkillSignaturePolicy,
+    trust: signing::TrustStore,
+}
+
+impl SignatureGate {
+    fn from_config(config: &crate::config::Config) -> Result<Self> {
+        Ok(Self {
+            policy: config.skills.signature_policy,
+            trust: signing::TrustStore::load(&zeroclaw_dir(config))?,
+        })
+    }
+
+    /// Enforce the policy on `skill_dir`, returning the trusted publisher if any.
+    fn check(&self, skill_dir: &Path) -> Result<Option<String>> {
+        let status = signing::enforce_signature_policy(skill_dir, self.policy, &self.trust)?;
+        Ok(match status {
+            Some(signing::SignatureStatus::Trusted { publisher }) => {
+                println!(
+                    "  {} Signed by trusted publisher '{publisher}'",
+                    console::style("✓").green().bold()
+                );
+                Some(publisher)
+            }
+            Some(signing::SignatureStatus::Untrusted { publisher, .. }) => {
+                println!(
+                    "  {} Signed by '{publisher}', who is not in the trust store",
+                    console::style("!").yellow().bold()
+                );
+                None
+            }
+            Some(signing::SignatureStatus::Unsigned) => {
+                println!(
+                    "  {} Skill is not signed",
+                    console::style("!").yellow().bold()
+                );
+                None
+            }
+            None => None,
+        })
+    }
+}
+
+/// Install a skill from a git or local source, resolve its requirements and pin it in `lockfile`.
+fn install_skill_source(
+    source: &str,
+    skills_path: &Path,
+    lockfile: &mut SkillLockfile,
+    gate: &SignatureGate,
+    depth: usize,
+) -> Result<(PathBuf, usize)> {
+    let (dir, files_scanned, source, source_kind, commit) = if is_git_source(source) {
+        let (dir, files_scanned, commit) = install_git_skill_source(source, skills_path)
+            .with_context(|| format!("failed to install git skill source: {source}"))?;
+        (
+            dir,
+            files_scanned,
+            source.to_string(),
+            SkillSourceKind::Git,
+            Some(commit),
+        )
+    } else {
+        let (dir, files_scanned) = install_local_skill_source(source, skills_path)
+            .with_context(|| format!("failed to install local skill source: {source}"))?;
+        let canonical = PathBuf::from(source)
+            .canonicalize()
+            .map_or_else(|_| source.to_string(), |p| p.display().to_string());
+        (dir, files_scanned, canonical, SkillSourceKind::Local, None)
+    };
+
+    let pinned = gate.check(&dir).and_then(|publisher| {
+        resolve_skill_requirements(&dir, skills_path, lockfile, gate, depth)?;
+        let mut entry = locked_skill_entry(&dir, &source, source_kind, commit)?;
+        entry.publisher = publisher;
+        Ok(entry)
+    });
+    match pinned {
+        Ok(entry) => {
+            lockfile.upsert(entry);
+            Ok((dir, files_scanned))
+        }
+        Err(err) => {
+            let _ = std::fs::remove_dir_all(&dir);
+            Err(err)
+        }
+    }
+}
+
+fn read_skill_manifest(skill_dir: &Path) -> Result<Option<SkillManifest>> {
+    let path = skill_dir.join("SKILL.toml");
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)?;
+    let manifest =
+        toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))?;
+    Ok(Some(manifest))
+}
+
+/// Check a skill's `[requires]`, installing missing skill dependencies that declare a source.
+fn resolve_skill_requirements(
+    skill_dir: &Path,
+    skills_path: &Path,
+    lockfile: &mut SkillLockfile,
+    gate: &SignatureGate,
+    depth: usize,
+) -> Result<()> {
+    let Some(manifest) = read_skill_manifest(skill_dir)? else {
+        return Ok(());
+    };
+    let requirements = manifest.requires;
+    if requirements.is_empty() {
+        return Ok(());
+    }
+
+    let mut report = requires::check_requirements(&requirements, skills_path);
+    if !report.installable.is_empty() {
+        if depth >= MAX_SKILL_DEPENDENCY_DEPTH {
+            anyhow::bail!(
+                "Skill dependency chain exceeds {MAX_SKILL_DEPENDENCY_DEPTH} levels (cycle?)"
+            );
+        }
+        for missing in &report.installable {
+            println!(
+                "  Installing dependency '{}' from {}",
+                missing.name, missing.source
+            );
+            install_skill_source(&missing.source, skills_path, lockfile, gate, depth + 1)
+                .with_context(|| {
+                    format!("failed to install skill dependency '{}'", missing.name)
+                })?;
+        }
+        report = requires::check_requirements(&requirements, skills_path);
+    }
+
+    for warning in &report.warnings {
+        println!("  {} {warning}", console::style("!").yellow().bold());
+    }
+    let mut unmet = report.errors;
+    unmet.extend(
+        report
+            .installable
+            .iter()
+            .map(|missing| format!("required skill '{}' was not installed", missing.name)),
+    );
+    if !unmet.is_empty() {
+        anyhow::bail!(
+            "Skill '{}' has unmet requirements: {}",
+            manifest.skill.name,
+            unmet.join("; ")
+        );
+    }
+    Ok(())
+}
+
+fn locked_skill_entry(
+    skill_dir: &Path,
+    source: &str,
+    source_kind: SkillSourceKind,
+    commit: Option<String>,
+) -> Result<LockedSkill> {
+    let name = skill_dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .context("installed skill directory has no name")?
+        .to_string();
+    let version = read_skill_manifest(skill_dir)?
+        .map_or_else(default_version, |manifest| manifest.skill.version);
+    Ok(LockedSkill {
+        name,
+        version,
+        source: source.to_string(),
+        source_kind,
+        commit,
+        digest: lock::content_digest(skill_dir)?,
+        publisher: None,
+        installed_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Result of comparing a locked skill against its source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SkillSourceStatus {
+    UpToDate,
+    Outdated { latest: String },
+    Unavailable(String),
+}
+
+fn short_commit(commit: &str) -> &str {
+    commit.get(..12).unwrap_or(commit)
+}
+
+fn check_skill_source(entry: &LockedSkill) -> SkillSourceStatus {
+    match entry.source_kind {
+        SkillSourceKind::Git => match lock::git_remote_head(&entry.source) {
+            Ok(head) if entry.commit.as_deref() == Some(head.as_str()) => {
+                SkillSourceStatus::UpToDate
+            }
+            Ok(head) => SkillSourceStatus::Outdated {
+                latest: short_commit(&head).to_string(),
+            },
+            Err(err) => SkillSourceStatus::Unavailable(err.to_string()),
+        },
+        SkillSourceKind::Local => match lock::content_digest(Path::new(&entry.source)) {
+            Ok(digest) if digest == entry.digest => SkillSourceStatus::UpToDate,
+            Ok(_) => {
+                let latest = read_skill_manifest(Path::new(&entry.source))
+                    .ok()
+                    .flatten()
+                    .map_or_else(
+                        || "changed".to_string(),
+                        |m| format!("v{}", m.skill.version),
+                    );
+                SkillSourceStatus::Outdated { latest }
+            }
+            Err(err) => SkillSourceStatus::Unavailable(err.to_string()),
+        },
+    }
+}
+
+/// Outcome of `skills update` for one skill.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SkillUpdateOutcome {
+    UpToDate,
+    Updated { from: String, to: String },
+}
+
+/// Re-fetch a locked skill into a staging directory, audit it, resolve its
+/// requirements and swap it in place of the installed copy.
+fn update_locked_skill(
+    entry: &LockedSkill,
+    skills_path: &Path,
+    lockfile: &mut SkillLockfile,
+    gate: &SignatureGate,
+) -> Result<SkillUpdateOutcome> {
+    let staging_root = skills_path
+        .parent()
+        .unwrap_or(skills_path)
+        .join(".skills-staging");
+    std::fs::create_dir_all(&staging_root)?;
+    let staging = staging_root.join(format!(
+        "{}-{}",
+        entry.name,
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    ));
+
+    let result = stage_and_swap(entry, skills_path, &staging, lockfile, gate);
+    if staging.exists() {
+        let _ = std::fs::remove_dir_all(&staging);
+    }
+    let _ = std::fs::remove_dir(&staging_root);
+    result
+}
+
+fn stage_and_swap(
+    entry: &LockedSkill,
+    skills_path: &Path,
+    staging: &Path,
+    lockfile: &mut SkillLockfile,
+    gate: &SignatureGate,
+) -> Result<SkillUpdateOutcome> {
+    let commit = match entry.source_kind {
+        SkillSourceKind::Git => {
+            let output = std::process::Command::new("git")
+                .args(["clone", "--depth", "1", &entry.source])
+                .arg(staging)
+                .output()?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                anyhow::bail!("Git clone failed: {stderr}");
+            }
+            let commit = lock::git_head_commit(staging)?;
+            if entry.commit.as_deref() == Some(commit.as_str()) {
+                return Ok(SkillUpdateOutcome::UpToDate);
+            }
+            remove_git_metadata(staging)?;
+            Some(commit)
+        }
+        SkillSourceKind::Local => {
+            let source = Path::new(&entry.source);
+            if lock::content_digest(source)? == entry.digest {
+                return Ok(SkillUpdateOutcome::UpToDate);
+            }
+            copy_dir_recursive_secure(source, staging)?;
+            None
+        }
+    };
+
+    enforce_skill_security_audit(staging)?;
+    let publisher = gate.check(staging)?;
+    resolve_skill_requirements(staging, skills_path, lockfile, gate, 0)?;
+    let mut updated = locked_skill_entry(staging, &entry.source, entry.source_kind, commit)?;
+    updated.name = entry.name.clone();
+    updated.publisher = publisher;
+
+    let dest = skills_path.join(&entry.name);
+    let backup = staging.with_extension("previous");
+    if dest.exists() {
+        std::fs::rename(&dest, &backup)
+            .with_context(|| format!("failed to move aside {}", dest.display()))?;
+    }
+    if let Err(err) = std::fs::rename(staging, &dest) {
+        if backup.exists() {
+            let _ = std::fs::rename(&backup, &dest);
+        }
+        return Err(err).with_context(|| format!("failed to replace {}", dest.display()));
+    }
+    if backup.exists() {
+        let _ = std::fs::remove_dir_all(&backup);
+    }
+
+    let outcome = SkillUpdateOutcome::Updated {
+        from: entry.version.clone(),
+        to: updated.version.clone(),
+    };
+    lockfile.upsert(updated);
+    Ok(outcome)
+}
+
 /// Handle the `skills` CLI command
 #[allow(clippy::too_many_lines)]
 pub fn handle_command(command: crate::SkillCommands, config: &crate::config::Config) -> Result<()> {
@@ -909,28 +1321,222 @@ pub fn handle_command(command: crate::SkillCommands, config: &crate::config::Con
             let skills_path = skills_dir(workspace_dir);
             std::fs::create_dir_all(&skills_path)?;
 
-            if is_git_source(&source) {
-                let (installed_dir, files_scanned) =
-                    install_git_skill_source(&source, &skills_path)
-                        .with_context(|| format!("failed to install git skill source: {source}"))?;
+            let mut lockfile = SkillLockfile::load(&skills_path)?;
+            let gate = SignatureGate::from_config(config)?;
+            let (dest, files_scanned) =
+                install_skill_source(&source, &skills_path, &mut lockfile, &gate, 0)?;
+            lockfile.save(&skills_path)?;
+            println!(
+                "  {} Skill installed and audited: {} ({} files scanned)",
+                console::style("✓").green().bold(),
+                dest.display(),
+                files_scanned
+            );
+
+            println!("  Security audit completed successfully.");
+            println!(
+                "  Pinned in {}",
+                SkillLockfile::path(&skills_path).display()
+            );
+            Ok(())
+        }
+        crate::SkillCommands::Update { name } => {
+            let skills_path = skills_dir(workspace_dir);
+            let mut lockfile = SkillLockfile::load(&skills_path)?;
+            let gate = SignatureGate::from_config(config)?;
+            let entries: Vec<LockedSkill> = match &name {
+                Some(name) => vec![lockfile.get(name).cloned().with_context(|| {
+                    format!("Skill '{name}' is not in skills.lock; reinstall it to pin a source")
+                })?],
+                None => lockfile.skills.clone(),
+            };
+            if entries.is_empty() {
+                println!("No pinned skills in skills.lock.");
+                return Ok(());
+            }
+
+            let mut failures = 0usize;
+            for entry in &entries {
+                match update_locked_skill(entry, &skills_path, &mut lockfile, &gate) {
+                    Ok(SkillUpdateOutcome::UpToDate) => {
+                        println!(
+                            "  {} {} is up to date",
+                            console::style("✓").green(),
+                            entry.name
+                        );
+                    }
+                    Ok(SkillUpdateOutcome::Updated { from, to }) => {
+                        println!(
+                            "  {} {} updated v{from} → v{to} (audited)",
+                            console::style("✓").green().bold(),
+                            entry.name
+                        );
+                    }
+                    Err(err) => {
+                        failures += 1;
+                        println!(
+                            "  {} {} update failed: {err:#}",
+                            console::style("✗").red().bold(),
+                            entry.name
+                        );
+                    }
+                }
+            }
+            lockfile.save(&skills_path)?;
+            if failures > 0 {
+                anyhow::bail!("{failures} skill update(s) failed; installed copies were kept.");
+            }
+            Ok(())
+        }
+        crate::SkillCommands::Outdated => {
+            let skills_path = skills_dir(workspace_dir);
+            let lockfile = SkillLockfile::load(&skills_path)?;
+            if lockfile.skills.is_empty() {
+                println!("No pinned skills in skills.lock.");
+                return Ok(());
+            }
+
+            let mut outdated = 0usize;
+            for entry in &lockfile.skills {
+                let installed = skills_path.join(&entry.name);
+                let pin = entry.commit.as_deref().map_or_else(
+                    || format!("v{}", entry.version),
+                    |c| format!("v{} @ {}", entry.version, short_commit(c)),
+                );
+                let status = match check_skill_source(entry) {
+                    SkillSourceStatus::UpToDate => console::style("up to date".to_string()).green(),
+                    SkillSourceStatus::Outdated { latest } => {
+                        outdated += 1;
+                        console::style(format!("outdated → {latest}")).yellow()
+                    }
+                    SkillSourceStatus::Unavailable(err) => {
+                        console::style(format!("source unavailable ({err})")).dim()
+                    }
+                };
                 println!(
-                    "  {} Skill installed and audited: {} ({} files scanned)",
-                    console::style("✓").green().bold(),
-                    installed_dir.display(),
-                    files_scanned
+                    "  {} {} — {}",
+                    console::style(&entry.name).white().bold(),
+                    pin,
+                    status
                 );
-            } else {
-                let (dest, files_scanned) = install_local_skill_source(&source, &skills_path)
-                    .with_context(|| format!("failed to install local skill source: {source}"))?;
+
+                if !installed.exists() {
+                    println!(
+                        "    {} installed copy is missing",
+                        console::style("✗").red()
+                    );
+                    continue;
+                }
+                if lock::content_digest(&installed)? != entry.digest {
+                    println!(
+                        "    {} installed files differ from skills.lock digest",
+                        console::style("!").yellow().bold()
+                    );
+                }
+                let report = audit::audit_skill_directory(&installed)?;
+                if !report.is_clean() {
+                    println!(
+                        "    {} audit: {}",
+                        console::style("✗").red().bold(),
+                        report.summary()
+                    );
+                }
+            }
+            println!();
+            println!(
+                "{outdated} of {} pinned skill(s) outdated.",
+                lockfile.skills.len()
+            );
+            Ok(())
+        }
+        crate::SkillCommands::Sign {
+            path,
+            publisher,
+            key,
+        } => {
+            let key_path = key.unwrap_or_else(|| zeroclaw_dir(config).join("skill-signing.key"));
+            let (signing_key, created) = signing::load_or_create_signing_key(&key_path)?;
+            if created {
                 println!(
-                    "  {} Skill installed and audited: {} ({} files scanned)",
-                    console::style("✓").green().bold(),
-                    dest.display(),
-                    files_scanned
+                    "  Generated signing key at {} (keep it private).",
+                    key_path.display()
                 );
             }
+            enforce_skill_security_audit(&path)?;
+            let signature = signing::sign_skill_directory(&path, &signing_key, &publisher)?;
+            println!(
+                "  {} Signed {} as '{}' ({})",
+                console::style("✓").green().bold(),
+                path.display(),
+                signature.publisher,
+                signature.manifest_digest
+            );
+            println!("  Public key: {}", signature.public_key);
+            println!(
+                "  Consumers trust it with: zeroclaw skills trust {} {}",
+                signature.publisher, signature.public_key
+            );
+            Ok(())
+        }
+        crate::SkillCommands::Verify { source } => {
+            let source_path = PathBuf::from(&source);
+            let target = if source_path.exists() {
+                source_path
+            } else {
+                skills_dir(workspace_dir).join(&source)
+            };
+            if !target.exists() {
+                anyhow::bail!("Skill source or installed skill not found: {source}");
+            }
 
-            println!("  Security audit completed successfully.");
+            let trust = signing::TrustStore::load(&zeroclaw_dir(config))?;
+            match signing::verify_skill_directory(&target, &trust)? {
+                signing::SignatureStatus::Trusted { publisher } => {
+                    println!(
+                        "  {} {} is signed by trusted publisher '{publisher}'.",
+                        console::style("✓").green().bold(),
+                        target.display()
+                    );
+                    Ok(())
+                }
+                signing::SignatureStatus::Untrusted {
+                    publisher,
+                    public_key,
+                } => anyhow::bail!(
+                    "{} has a valid signature from '{publisher}' ({public_key}), \
+                     but that key is not trusted",
+                    target.display()
+                ),
+                signing::SignatureStatus::Unsigned => {
+                    anyhow::bail!("{} is not signed", target.display())
+                }
+            }
+        }
+        crate::SkillCommands::Trust {
+            publisher,
+            public_key,
+        } => {
+            let dir = zeroclaw_dir(config);
+            let mut trust = signing::TrustStore::load(&dir)?;
+            trust.add(&publisher, &public_key)?;
+            trust.save(&dir)?;
+            println!(
+                "  {} Trusted publisher '{publisher}'.",
+                console::style("✓").green().bold()
+            );
+            Ok(())
+        }
+        crate::SkillCommands::Untrust { publisher } => {
+            let dir = zeroclaw_dir(config);
+            let mut trust = signing::TrustStore::load(&dir)?;
+            if !trust.remove(&publisher) {
+                anyhow::bail!("Publisher not in trust store: {publisher}");
+            }
+            trust.save(&dir)?;
+            println!(
+                "  {} Removed publisher '{publisher}' from the trust store.",
+                console::style("✓").green().bold()
+            );
             Ok(())
         }
         crate::SkillCommands::Remove { name } => {
@@ -956,6 +1562,11 @@ pub fn handle_command(command: crate::SkillCommands, config: &crate::config::Con
             }
 
             std::fs::remove_dir_all(&skill_path)?;
+            let skills_path = skills_dir(workspace_dir);
+            let mut lockfile = SkillLockfile::load(&skills_path)?;
+            if lockfile.remove(&name).is_some() {
+                lockfile.save(&skills_path)?;
+            }
             println!(
                 "  {} Skill '{}' removed.",
                 console::style("✓").green().bold(),
@@ -1075,6 +1686,7 @@ command = "echo hello"
             tags: vec![],
             tools: vec![],
             prompts: vec!["Do the thing.".to_string()],
+            policy: SkillPolicy::default(),
             location: None,
         }];
         let prompt = skills_to_prompt(&skills, Path::new("/tmp"));
@@ -1099,6 +1711,7 @@ command = "echo hello"
                 args: HashMap::new(),
             }],
             prompts: vec!["Do the thing.".to_string()],
+            policy: SkillPolicy::default(),
             location: Some(PathBuf::from("/tmp/workspace/skills/test/SKILL.md")),
         }];
         let prompt = skills_to_prompt_with_mode(
@@ -1299,6 +1912,7 @@ description = "Bare minimum"
                 args: HashMap::new(),
             }],
             prompts: vec![],
+            policy: SkillPolicy::default(),
             location: None,
         }];
         let prompt = skills_to_prompt(&skills, Path::new("/tmp"));
@@ -1318,6 +1932,7 @@ description = "Bare minimum"
             tags: vec![],
             tools: vec![],
             prompts: vec!["Use <tool> & check \"quotes\".".to_string()],
+            policy: SkillPolicy::default(),
             location: None,
         }];
 
@@ -1471,6 +2086,191 @@ description = "Bare minimum"
         assert_eq!(skills[0].name, "http_request");
         assert_ne!(skills[0].name, "CONTRIBUTING");
     }
+
+    fn unsigned_gate() -> SignatureGate {
+        SignatureGate {
+            policy: crate::config::SkillSignaturePolicy::Off,
+            trust: signing::TrustStore::default(),
+        }
+    }
+
+    fn write_skill_source(dir: &Path, name: &str, version: &str, extra: &str) -> PathBuf {
+        let skill_dir = dir.join(name);
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.toml"),
+            format!(
+                "[skill]\nname = \"{name}\"\ndescription = \"d\"\nversion = \"{version}\"\n{extra}"
+            ),
+        )
+        .unwrap();
+        skill_dir
+    }
+
+    #[test]
+    fn install_pins_skill_and_installs_declared_dependency() {
+        let dir = tempfile::tempdir().unwrap();
+        let sources = dir.path().join("sources");
+        let skills_path = dir.path().join("workspace/skills");
+        fs::create_dir_all(&skills_path).unwrap();
+        let base = write_skill_source(&sources, "base", "1.2.0", "");
+        let app = write_skill_source(
+            &sources,
+            "app",
+            "0.3.0",
+            &format!(
+                "\n[requires.skills]\nbase = {{ version = \"^1.1\", source = \"{}\" }}\n",
+                base.display()
+            ),
+        );
+
+        let mut lockfile = SkillLockfile::load(&skills_path).unwrap();
+        install_skill_source(
+            app.to_str().unwrap(),
+            &skills_path,
+            &mut lockfile,
+            &unsigned_gate(),
+            0,
+        )
+        .unwrap();
+        lockfile.save(&skills_path).unwrap();
+
+        let lockfile = SkillLockfile::load(&skills_path).unwrap();
+        assert_eq!(lockfile.skills.len(), 2);
+        let app_entry = lockfile.get("app").unwrap();
+        assert_eq!(app_entry.version, "0.3.0");
+        assert_eq!(app_entry.source_kind, SkillSourceKind::Local);
+        assert!(app_entry.digest.starts_with("sha256:"));
+        assert!(skills_path.join("base/SKILL.toml").is_file());
+        assert_eq!(check_skill_source(app_entry), SkillSourceStatus::UpToDate);
+    }
+
+    #[test]
+    fn require_signed_install_accepts_trusted_and_rejects_unsigned() {
+        let dir = tempfile::tempdir().unwrap();
+        let sources = dir.path().join("sources");
+        let skills_path = dir.path().join("workspace/skills");
+        fs::create_dir_all(&skills_path).unwrap();
+        let signed = write_skill_source(&sources, "signed", "1.0.0", "");
+        let unsigned = write_skill_source(&sources, "unsigned", "1.0.0", "");
+
+        let (key, _) =
+            signing::load_or_create_signing_key(&dir.path().join("publisher.key")).unwrap();
+        let signature = signing::sign_skill_directory(&signed, &key, "acme").unwrap();
+        let mut gate = SignatureGate {
+            policy: crate::config::SkillSignaturePolicy::RequireSigned,
+            trust: signing::TrustStore::default(),
+        };
+        gate.trust.add("acme", &signature.public_key).unwrap();
+
+        let mut lockfile = SkillLockfile::default();
+        install_skill_source(
+            signed.to_str().unwrap(),
+            &skills_path,
+            &mut lockfile,
+            &gate,
+            0,
+        )
+        .unwrap();
+        assert_eq!(
+            lockfile.get("signed").unwrap().publisher.as_deref(),
+            Some("acme")
+        );
+
+        let err = install_skill_source(
+            unsigned.to_str().unwrap(),
+            &skills_path,
+            &mut lockfile,
+            &gate,
+            0,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not signed"), "{err:#}");
+        assert!(!skills_path.join("unsigned").exists());
+        assert!(lockfile.get("unsigned").is_none());
+    }
+
+    #[test]
+    fn install_rolls_back_on_unmet_requirements() {
+        let dir = tempfile::tempdir().unwrap();
+        let skills_path = dir.path().join("workspace/skills");
+        fs::create_dir_all(&skills_path).unwrap();
+        let source = write_skill_source(
+            dir.path(),
+            "needy",
+            "1.0.0",
+            "\n[requires]\nbins = [\"zeroclaw-test-missing-binary\"]\n[requires.skills]\nghost = \"*\"\n",
+        );
+
+        let mut lockfile = SkillLockfile::default();
+        let err = install_skill_source(
+            source.to_str().unwrap(),
+            &skills_path,
+            &mut lockfile,
+            &unsigned_gate(),
+            0,
+        )
+        .unwrap_err();
+        let message = format!("{err:#}");
+        assert!(
+            message.contains("zeroclaw-test-missing-binary"),
+            "{message}"
+        );
+        assert!(message.contains("ghost"), "{message}");
+        assert!(!skills_path.join("needy").exists());
+        assert!(lockfile.skills.is_empty());
+    }
+
+    #[test]
+    fn update_reaudits_and_swaps_changed_local_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let skills_path = dir.path().join("workspace/skills");
+        fs::create_dir_all(&skills_path).unwrap();
+        let source = write_skill_source(dir.path(), "tool", "1.0.0", "");
+
+        let mut lockfile = SkillLockfile::default();
+        install_skill_source(
+            source.to_str().unwrap(),
+            &skills_path,
+            &mut lockfile,
+            &unsigned_gate(),
+            0,
+        )
+        .unwrap();
+        let entry = lockfile.get("tool").unwrap().clone();
+        assert_eq!(
+            update_locked_skill(&entry, &skills_path, &mut lockfile, &unsigned_gate()).unwrap(),
+            SkillUpdateOutcome::UpToDate
+        );
+
+        write_skill_source(dir.path(), "tool", "1.1.0", "");
+        assert_eq!(
+            check_skill_source(&entry),
+            SkillSourceStatus::Outdated {
+                latest: "v1.1.0".into()
+            }
+        );
+        assert_eq!(
+            update_locked_skill(&entry, &skills_path, &mut lockfile, &unsigned_gate()).unwrap(),
+            SkillUpdateOutcome::Updated {
+                from: "1.0.0".into(),
+                to: "1.1.0".into()
+            }
+        );
+        assert_eq!(lockfile.get("tool").unwrap().version, "1.1.0");
+        let installed = fs::read_to_string(skills_path.join("tool/SKILL.toml")).unwrap();
+        assert!(installed.contains("1.1.0"));
+        assert!(!dir.path().join("workspace/.skills-staging").exists());
+
+        // A source that now fails the audit leaves the installed copy untouched.
+        fs::write(source.join("run.sh"), "echo hi").unwrap();
+        let entry = lockfile.get("tool").unwrap().clone();
+        assert!(
+            update_locked_skill(&entry, &skills_path, &mut lockfile, &unsigned_gate()).is_err()
+        );
+        assert!(!skills_path.join("tool/run.sh").exists());
+        assert_eq!(lockfile.get("tool").unwrap().version, "1.1.0");
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,288 @@
+use super::{load_skills_from_directory, Skill};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Requirements declared in a skill manifest (`[requires]` in SKILL.toml).
+///
+/// ```toml
+/// [requires]
+/// zeroclaw = ">=0.1.5"
+/// bins = ["jq"]
+/// env = ["GITHUB_TOKEN"]
+///
+/// [requires.skills]
+/// git-helpers = ">=1.0"
+/// notes = { version = "^2", source = "https://github.com/acme/notes-skill" }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkillRequirements {
+    /// Minimum ZeroClaw version as a semver requirement
+    #[serde(default)]
+    pub zeroclaw: Option<String>,
+    /// Binaries that must be on `PATH`
+    #[serde(default)]
+    pub bins: Vec<String>,
+    /// Environment variables the skill expects (missing ones only warn)
+    #[serde(default)]
+    pub env: Vec<String>,
+    /// Other skills this skill depends on, keyed by skill name
+    #[serde(default)]
+    pub skills: BTreeMap<String, SkillDependency>,
+}
+
+impl SkillRequirements {
+    pub fn is_empty(&self) -> bool {
+        self.zeroclaw.is_none()
+            && self.bins.is_empty()
+            && self.env.is_empty()
+            && self.skills.is_empty()
+    }
+}
+
+/// A dependency on another skill: a bare version requirement or a table with a source.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum SkillDependency {
+    Version(String),
+    Detailed {
+        #[serde(default = "default_dependency_version")]
+        version: String,
+        #[serde(default)]
+        source: Option<String>,
+    },
+}
+
+fn default_dependency_version() -> String {
+    "*".to_string()
+}
+
+impl SkillDependency {
+    pub fn version_req(&self) -> &str {
+        match self {
+            Self::Version(version) | Self::Detailed { version, .. } => version,
+        }
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        match self {
+            Self::Version(_) => None,
+            Self::Detailed { source, .. } => source.as_deref(),
+        }
+    }
+}
+
+/// A skill dependency that is not installed but can be fetched from `source`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingSkill {
+    pub name: String,
+    pub source: String,
+}
+
+/// Outcome of checking a skill's requirements against the current environment.
+#[derive(Debug, Clone, Default)]
+pub struct RequirementReport {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    pub installable: Vec<MissingSkill>,
+}
+
+impl RequirementReport {
+    pub fn is_satisfied(&self) -> bool {
+        self.errors.is_empty() && self.installable.is_empty()
+    }
+}
+
+/// Check `requirements` against the running binary, `PATH`, the environment and
+/// the skills installed in `skills_dir`.
+pub fn check_requirements(
+    requirements: &SkillRequirements,
+    skills_dir: &Path,
+) -> RequirementReport {
+    let mut report = RequirementReport::default();
+
+    if let Some(raw) = requirements.zeroclaw.as_deref() {
+        match version_matches(raw, env!("CARGO_PKG_VERSION")) {
+            Ok(true) => {}
+            Ok(false) => report.errors.push(format!(
+                "requires ZeroClaw {raw}, running {}",
+                env!("CARGO_PKG_VERSION")
+            )),
+            Err(e) => report
+                .errors
+                .push(format!("invalid ZeroClaw version requirement '{raw}': {e}")),
+        }
+    }
+
+    for bin in &requirements.bins {
+        if which::which(bin).is_err() {
+            report
+                .errors
+                .push(format!("required binary '{bin}' not found on PATH"));
+        }
+    }
+
+    for var in &requirements.env {
+        if std::env::var_os(var).is_none() {
+            report
+                .warnings
+                .push(format!("environment variable '{var}' is not set"));
+        }
+    }
+
+    if !requirements.skills.is_empty() {
+        let installed = load_skills_from_directory(skills_dir);
+        for (name, dependency) in &requirements.skills {
+            check_skill_dependency(name, dependency, &installed, &mut report);
+        }
+    }
+
+    report
+}
+
+fn check_skill_dependency(
+    name: &str,
+    dependency: &SkillDependency,
+    installed: &[Skill],
+    report: &mut RequirementReport,
+) {
+    let dir_name = |skill: &Skill| {
+        skill
+            .location
+            .as_deref()
+            .and_then(Path::parent)
+            .and_then(Path::file_name)
+            .map(|n| n.to_string_lossy().into_owned())
+    };
+    let found = installed
+        .iter()
+        .find(|skill| skill.name == name || dir_name(skill).as_deref() == Some(name));
+    let Some(skill) = found else {
+        match dependency.source() {
+            Some(source) => report.installable.push(MissingSkill {
+                name: name.to_string(),
+                source: source.to_string(),
+            }),
+            None => report
+                .errors
+                .push(format!("required skill '{name}' is not installed")),
+        }
+        return;
+    };
+
+    let req = dependency.version_req();
+    match version_matches(req, &skill.version) {
+        Ok(true) => {}
+        Ok(false) => report.errors.push(format!(
+            "required skill '{name}' {req} is not satisfied by installed v{}",
+            skill.version
+        )),
+        Err(e) => report.errors.push(format!(
+            "cannot check skill '{name}' version '{}' against '{req}': {e}",
+            skill.version
+        )),
+    }
+}
+
+/// Whether `version` satisfies the semver requirement `req` (`*` matches anything).
+pub fn version_matches(req: &str, version: &str) -> anyhow::Result<bool> {
+    let req = req.trim();
+    if req.is_empty() || req == "*" {
+        return Ok(true);
+    }
+    let req = semver::VersionReq::parse(req)?;
+    let version = semver::Version::parse(version.trim().trim_start_matches('v'))?;
+    Ok(req.matches(&version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_skill(dir: &Path, name: &str, version: &str) {
+        let skill_dir = dir.join(name);
+        std::fs::create_dir_all(&skill_dir).unwrap();
+        std::fs::write(
+            skill_dir.join("SKILL.toml"),
+            format!("[skill]\nname = \"{name}\"\ndescription = \"d\"\nversion = \"{version}\"\n"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn requirements_parse_short_and_detailed_dependencies() {
+        let parsed: SkillRequirements = toml::from_str(
+            r#"
+zeroclaw = ">=0.1"
+bins = ["sh"]
+[skills]
+a = ">=1.0"
+b = { version = "^2", source = "https://example.com/b.git" }
+"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.skills["a"].version_req(), ">=1.0");
+        assert_eq!(
+            parsed.skills["b"].source(),
+            Some("https://example.com/b.git")
+        );
+        assert!(!parsed.is_empty());
+    }
+
+    #[test]
+    fn version_matching_uses_semver() {
+        assert!(version_matches(">=1.2", "1.3.0").unwrap());
+        assert!(!version_matches(">=1.2", "1.1.9").unwrap());
+        assert!(version_matches("*", "not-semver").unwrap());
+        assert!(version_matches("^1", "v1.0.0").unwrap());
+        assert!(version_matches(">=1", "latest").is_err());
+    }
+
+    #[test]
+    fn check_reports_missing_skills_bins_and_versions() {
+        let tmp = TempDir::new().unwrap();
+        write_skill(tmp.path(), "base", "1.0.0");
+
+        let mut requirements = SkillRequirements {
+            zeroclaw: Some(">=999.0".into()),
+            bins: vec!["definitely-not-a-real-binary-zc".into()],
+            env: vec!["ZEROCLAW_TEST_SURELY_UNSET_VAR".into()],
+            ..SkillRequirements::default()
+        };
+        requirements
+            .skills
+            .insert("base".into(), SkillDependency::Version(">=2".into()));
+        requirements
+            .skills
+            .insert("ghost".into(), SkillDependency::Version("*".into()));
+        requirements.skills.insert(
+            "remote".into(),
+            SkillDependency::Detailed {
+                version: "*".into(),
+                source: Some("https://example.com/remote.git".into()),
+            },
+        );
+
+        let report = check_requirements(&requirements, tmp.path());
+        assert_eq!(report.errors.len(), 4, "{:?}", report.errors);
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.installable.len(), 1);
+        assert_eq!(report.installable[0].name, "remote");
+        assert!(!report.is_satisfied());
+    }
+
+    #[test]
+    fn check_passes_when_requirements_met() {
+        let tmp = TempDir::new().unwrap();
+        write_skill(tmp.path(), "base", "1.4.0");
+        let mut requirements = SkillRequirements {
+            zeroclaw: Some(format!(">={}", env!("CARGO_PKG_VERSION"))),
+            ..SkillRequirements::default()
+        };
+        requirements
+            .skills
+            .insert("base".into(), SkillDependency::Version("^1.2".into()));
+        assert!(check_requirements(&requirements, tmp.path()).is_satisfied());
+    }
+}
@@ -0,0 +1,408 @@
+use anyhow::{Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Detached signature file stored in the skill root.
+pub const SIGNATURE_FILE: &str = "SKILL.sig";
+/// Trusted publisher keys, stored in the ZeroClaw config directory.
+pub const TRUST_STORE_FILE: &str = "trusted_publishers.toml";
+/// Domain separator so skill signatures cannot be replayed as other ed25519 messages.
+const SIGNATURE_CONTEXT: &str = "zeroclaw-skill-signature-v1";
+
+/// Contents of `SKILL.sig`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillSignature {
+    pub publisher: String,
+    /// Hex-encoded ed25519 public key
+    pub public_key: String,
+    /// `sha256:<hex>` of the content manifest, for quick comparison
+    pub manifest_digest: String,
+    /// Hex-encoded ed25519 signature over the content manifest
+    pub signature: String,
+    pub signed_at: String,
+}
+
+/// A publisher whose key is trusted for skill installs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustedPublisher {
+    pub name: String,
+    /// Hex-encoded ed25519 public key
+    pub public_key: String,
+}
+
+/// Local trust store of publisher keys (`trusted_publishers.toml`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrustStore {
+    #[serde(default, rename = "publisher")]
+    pub publishers: Vec<TrustedPublisher>,
+}
+
+impl TrustStore {
+    pub fn path(zeroclaw_dir: &Path) -> PathBuf {
+        zeroclaw_dir.join(TRUST_STORE_FILE)
+    }
+
+    pub fn load(zeroclaw_dir: &Path) -> Result<Self> {
+        let path = Self::path(zeroclaw_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, zeroclaw_dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(zeroclaw_dir)?;
+        let path = Self::path(zeroclaw_dir);
+        std::fs::write(&path, toml::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Trust `public_key` for `name`, replacing any previous key for that publisher.
+    pub fn add(&mut self, name: &str, public_key: &str) -> Result<()> {
+        let key = parse_public_key(public_key)?;
+        self.publishers.retain(|p| p.name != name);
+        self.publishers.push(TrustedPublisher {
+            name: name.to_string(),
+            public_key: hex::encode(key.as_bytes()),
+        });
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.publishers.len();
+        self.publishers.retain(|p| p.name != name);
+        self.publishers.len() != before
+    }
+
+    fn find_key(&self, public_key: &str) -> Option<&TrustedPublisher> {
+        self.publishers
+            .iter()
+            .find(|p| p.public_key.eq_ignore_ascii_case(public_key))
+    }
+}
+
+/// Result of checking a skill directory's signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Unsigned,
+    /// Valid signature from a key that is not in the trust store
+    Untrusted {
+        publisher: String,
+        public_key: String,
+    },
+    /// Valid signature from a trusted publisher
+    Trusted {
+        publisher: String,
+    },
+}
+
+/// Canonical content manifest: one `sha256:<hex>  <path>` line per file, sorted
+/// by path, excluding `SKILL.sig` and `.git`. Walks the tree like
+/// [`super::lock::content_digest`], so symlinks are rejected.
+pub fn content_manifest(skill_dir: &Path) -> Result<String> {
+    let mut manifest = String::new();
+    for rel in super::lock::skill_files(skill_dir)? {
+        if rel == SIGNATURE_FILE {
+            continue;
+        }
+        let bytes = std::fs::read(skill_dir.join(&rel))
+            .with_context(|| format!("failed to read {}", skill_dir.join(&rel).display()))?;
+        let _ = writeln!(
+            manifest,
+            "sha256:{}  {rel}",
+            hex::encode(Sha256::digest(&bytes))
+        );
+    }
+    Ok(manifest)
+}
+
+fn signed_message(publisher: &str, manifest: &str) -> Vec<u8> {
+    format!("{SIGNATURE_CONTEXT}\npublisher: {publisher}\n{manifest}").into_bytes()
+}
+
+fn manifest_digest(manifest: &str) -> String {
+    format!(
+        "sha256:{}",
+        hex::encode(Sha256::digest(manifest.as_bytes()))
+    )
+}
+
+fn parse_public_key(raw: &str) -> Result<VerifyingKey> {
+    let bytes: [u8; 32] = hex::decode(raw.trim())
+        .context("public key must be hex-encoded")?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("public key must be 32 bytes"))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| anyhow::anyhow!("invalid ed25519 public key: {e}"))
+}
+
+/// Load a hex-encoded signing key from `path`, generating one (mode 0600) if missing.
+/// Returns the key and whether it was newly created.
+pub fn load_or_create_signing_key(path: &Path) -> Result<(SigningKey, bool)> {
+    if path.exists() {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read signing key {}", path.display()))?;
+        let bytes: [u8; 32] = hex::decode(raw.trim())
+            .context("signing key must be hex-encoded")?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("signing key must be 32 bytes"))?;
+        return Ok((SigningKey::from_bytes(&bytes), false));
+    }
+
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| anyhow::anyhow!("RNG failed: {e}"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, hex::encode(bytes))
+        .with_context(|| format!("failed to write signing key {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok((SigningKey::from_bytes(&bytes), true))
+}
+
+/// Sign `skill_dir` as `publisher` and write `SKILL.sig`.
+pub fn sign_skill_directory(
+    skill_dir: &Path,
+    signing_key: &SigningKey,
+    publisher: &str,
+) -> Result<SkillSignature> {
+    let manifest = content_manifest(skill_dir)?;
+    let signature = signing_key.sign(&signed_message(publisher, &manifest));
+    let sig = SkillSignature {
+        publisher: publisher.to_string(),
+        public_key: hex::encode(signing_key.verifying_key().as_bytes()),
+        manifest_digest: manifest_digest(&manifest),
+        signature: hex::encode(signature.to_bytes()),
+        signed_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let path = skill_dir.join(SIGNATURE_FILE);
+    std::fs::write(&path, toml::to_string_pretty(&sig)?)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(sig)
+}
+
+/// Verify `SKILL.sig` in `skill_dir` against its current content.
+///
+/// Returns an error when a signature is present but does not match the content
+/// (tampered or corrupt); an unsigned skill is not an error.
+pub fn verify_skill_directory(skill_dir: &Path, trust: &TrustStore) -> Result<SignatureStatus> {
+    let path = skill_dir.join(SIGNATURE_FILE);
+    if !path.is_file() {
+        return Ok(SignatureStatus::Unsigned);
+    }
+    let sig: SkillSignature = toml::from_str(
+        &std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?,
+    )
+    .with_context(|| format!("failed to parse {}", path.display()))?;
+
+    let key = parse_public_key(&sig.public_key)?;
+    let signature_bytes: [u8; 64] = hex::decode(sig.signature.trim())
+        .context("signature must be hex-encoded")?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("signature must be 64 bytes"))?;
+    let manifest = content_manifest(skill_dir)?;
+    if manifest_digest(&manifest) != sig.manifest_digest {
+        anyhow::bail!("skill content does not match its signature (files changed since signing)");
+    }
+    key.verify(
+        &signed_message(&sig.publisher, &manifest),
+        &Signature::from_bytes(&signature_bytes),
+    )
+    .map_err(|_| anyhow::anyhow!("invalid signature from publisher '{}'", sig.publisher))?;
+
+    let public_key = hex::encode(key.as_bytes());
+    Ok(match trust.find_key(&public_key) {
+        Some(trusted) => SignatureStatus::Trusted {
+            publisher: trusted.name.clone(),
+        },
+        None => SignatureStatus::Untrusted {
+            publisher: sig.publisher,
+            public_key,
+        },
+    })
+}
+
+/// Apply the install signature policy to `skill_dir`.
+///
+/// `require-signed` rejects anything but a trusted signature; `warn` only rejects
+/// invalid signatures and logs the rest; `off` skips verification.
+pub fn enforce_signature_policy(
+    skill_dir: &Path,
+    policy: crate::config::SkillSignaturePolicy,
+    trust: &TrustStore,
+) -> Result<Option<SignatureStatus>> {
+    use crate::config::SkillSignaturePolicy;
+
+    if policy == SkillSignaturePolicy::Off {
+        return Ok(None);
+    }
+    let status = verify_skill_directory(skill_dir, trust)?;
+    let problem = match &status {
+        SignatureStatus::Trusted { .. } => None,
+        SignatureStatus::Unsigned => Some("skill is not signed".to_string()),
+        SignatureStatus::Untrusted {
+            publisher,
+            public_key,
+        } => Some(format!(
+            "publisher '{publisher}' (key {public_key}) is not in the trust store"
+        )),
+    };
+    if let Some(problem) = problem {
+        if policy == SkillSignaturePolicy::RequireSigned {
+            anyhow::bail!("Signature policy require-signed: {problem}");
+        }
+        tracing::warn!("Skill {}: {problem}", skill_dir.display());
+    }
+    Ok(Some(status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SkillSignaturePolicy;
+    use tempfile::TempDir;
+
+    fn skill(dir: &Path) -> PathBuf {
+        let skill_dir = dir.join("demo");
+        std::fs::create_dir_all(skill_dir.join("docs")).unwrap();
+        std::fs::write(skill_dir.join("SKILL.md"), "# Demo\nDo things.\n").unwrap();
+        std::fs::write(skill_dir.join("docs/guide.md"), "guide").unwrap();
+        skill_dir
+    }
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    #[test]
+    fn content_manifest_is_sorted_and_excludes_signature() {
+        let tmp = TempDir::new().unwrap();
+        let dir = skill(tmp.path());
+        std::fs::write(dir.join(SIGNATURE_FILE), "x").unwrap();
+        let manifest = content_manifest(&dir).unwrap();
+        let paths: Vec<&str> = manifest
+            .lines()
+            .map(|l| l.split_once("  ").unwrap().1)
+            .collect();
+        assert_eq!(paths, vec!["SKILL.md", "docs/guide.md"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_files_cannot_be_signed() {
+        let tmp = TempDir::new().unwrap();
+        let dir = skill(tmp.path());
+        std::fs::write(tmp.path().join("outside.md"), "outside").unwrap();
+        std::os::unix::fs::symlink(tmp.path().join("outside.md"), dir.join("docs/link.md"))
+            .unwrap();
+
+        assert!(content_manifest(&dir).is_err());
+        assert!(sign_skill_directory(&dir, &key(7), "acme").is_err());
+    }
+
+    #[test]
+    fn signed_skill_verifies_and_tracks_trust() {
+        let tmp = TempDir::new().unwrap();
+        let dir = skill(tmp.path());
+        let signing_key = key(7);
+        sign_skill_directory(&dir, &signing_key, "acme").unwrap();
+
+        let mut trust = TrustStore::default();
+        assert!(matches!(
+            verify_skill_directory(&dir, &trust).unwrap(),
+            SignatureStatus::Untrusted { ref publisher, .. } if publisher == "acme"
+        ));
+
+        trust
+            .add("acme", &hex::encode(signing_key.verifying_key().as_bytes()))
+            .unwrap();
+        assert_eq!(
+            verify_skill_directory(&dir, &trust).unwrap(),
+            SignatureStatus::Trusted {
+                publisher: "acme".into()
+            }
+        );
+    }
+
+    #[test]
+    fn tampered_or_forged_skill_fails_verification() {
+        let tmp = TempDir::new().unwrap();
+        let dir = skill(tmp.path());
+        sign_skill_directory(&dir, &key(1), "acme").unwrap();
+        std::fs::write(dir.join("docs/guide.md"), "changed").unwrap();
+        assert!(verify_skill_directory(&dir, &TrustStore::default()).is_err());
+
+        // Re-point the signature at a different key without re-signing.
+        sign_skill_directory(&dir, &key(1), "acme").unwrap();
+        let path = dir.join(SIGNATURE_FILE);
+        let mut sig: SkillSignature =
+            toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        sig.public_key = hex::encode(key(2).verifying_key().as_bytes());
+        std::fs::write(&path, toml::to_string(&sig).unwrap()).unwrap();
+        assert!(verify_skill_directory(&dir, &TrustStore::default()).is_err());
+    }
+
+    #[test]
+    fn policy_modes_gate_unsigned_and_untrusted_skills() {
+        let tmp = TempDir::new().unwrap();
+        let dir = skill(tmp.path());
+        let trust = TrustStore::default();
+
+        assert!(
+            enforce_signature_policy(&dir, SkillSignaturePolicy::Off, &trust)
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            enforce_signature_policy(&dir, SkillSignaturePolicy::Warn, &trust).unwrap(),
+            Some(SignatureStatus::Unsigned)
+        );
+        assert!(
+            enforce_signature_policy(&dir, SkillSignaturePolicy::RequireSigned, &trust).is_err()
+        );
+
+        sign_skill_directory(&dir, &key(3), "acme").unwrap();
+        assert!(
+            enforce_signature_policy(&dir, SkillSignaturePolicy::RequireSigned, &trust).is_err()
+        );
+        let mut trust = trust;
+        trust
+            .add("acme", &hex::encode(key(3).verifying_key().as_bytes()))
+            .unwrap();
+        assert!(
+            enforce_signature_policy(&dir, SkillSignaturePolicy::RequireSigned, &trust).is_ok()
+        );
+    }
+
+    #[test]
+    fn trust_store_and_signing_key_persist() {
+        let tmp = TempDir::new().unwrap();
+        let (created, is_new) =
+            load_or_create_signing_key(&tmp.path().join("keys/skill.key")).unwrap();
+        assert!(is_new);
+        let (loaded, is_new) =
+            load_or_create_signing_key(&tmp.path().join("keys/skill.key")).unwrap();
+        assert!(!is_new);
+        assert_eq!(created.to_bytes(), loaded.to_bytes());
+
+        let mut trust = TrustStore::default();
+        trust
+            .add("acme", &hex::encode(loaded.verifying_key().as_bytes()))
+            .unwrap();
+        assert!(trust.add("bad", "zz").is_err());
+        trust.save(tmp.path()).unwrap();
+        let mut reloaded = TrustStore::load(tmp.path()).unwrap();
+        assert_eq!(reloaded.publishers.len(), 1);
+        assert!(reloaded.remove("acme"));
+        assert!(!reloaded.remove("acme"));
+    }
+}
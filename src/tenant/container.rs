@@ -1,6 +1,7 @@
+use super::db::{or_default, query_doc, to_doc, TenantDb};
 use anyhow::Result;
+use rusqlite::{params, Transaction};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Container {
@@ -13,6 +14,31 @@ pub struct Container {
     pub created_at: String,
     pub started_at: Option<String>,
     pub resources: ContainerResources,
+    #[serde(default)]
+    pub backend: ContainerBackend,
+    #[serde(default)]
+    pub stopped_at: Option<String>,
+    /// Automatic restarts since the last explicit start
+    #[serde(default)]
+    pub restart_count: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Result of the most recent health probe (`None` until probed)
+    #[serde(default)]
+    pub healthy: Option<bool>,
+    #[serde(default)]
+    pub last_health_check: Option<String>,
+}
+
+/// How a container's agent instance is run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerBackend {
+    /// `docker run` via the Docker runtime
+    Docker,
+    /// A sandboxed `zeroclaw daemon` child process
+    #[default]
+    Native,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -33,8 +59,11 @@ impl Default for ContainerStatus {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContainerResources {
+    /// CPU cores
     pub cpu_limit: Option<u32>,
+    /// Memory in MB
     pub memory_limit: Option<u64>,
+    /// Disk usage of the instance directory in MB
     pub disk_limit: Option<u64>,
 }
 
@@ -48,27 +77,35 @@ impl Default for ContainerResources {
     }
 }
 
+/// The legacy `containers.json` layout, read once by the JSON importer.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ContainerStoreData {
     pub containers: Vec<Container>,
 }
 
 pub struct ContainerManager {
-    data: ContainerStoreData,
-    path: std::path::PathBuf,
+    db: TenantDb,
+}
+
+pub(crate) fn put(tx: &Transaction<'_>, container: &Container) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO containers (id, tenant_id, user_id, created_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            container.id,
+            container.tenant_id,
+            container.user_id,
+            container.created_at,
+            to_doc(container)?
+        ],
+    )?;
+    Ok(())
 }
 
 impl ContainerManager {
     #[inline]
-    pub fn new(base_path: &Path) -> Self {
-        let path = base_path.join("containers.json");
-        let data = if path.exists() {
-            let content = std::fs::read_to_string(&path).unwrap_or_default();
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            ContainerStoreData::default()
-        };
-        Self { data, path }
+    pub fn new(db: TenantDb) -> Self {
+        Self { db }
     }
 
     pub fn create(
@@ -77,6 +114,25 @@ impl ContainerManager {
         user_id: String,
         name: String,
         image: String,
+    ) -> Result<Container> {
+        self.create_with(
+            tenant_id,
+            user_id,
+            name,
+            image,
+            ContainerBackend::default(),
+            ContainerResources::default(),
+        )
+    }
+
+    pub fn create_with(
+        &mut self,
+        tenant_id: String,
+        user_id: String,
+        name: String,
+        image: String,
+        backend: ContainerBackend,
+        resources: ContainerResources,
     ) -> Result<Container> {
         let container = Container {
             id: uuid::Uuid::new_v4().to_string(),
@@ -87,59 +143,91 @@ impl ContainerManager {
             status: ContainerStatus::Pending,
             created_at: chrono::Utc::now().to_rfc3339(),
             started_at: None,
-            resources: ContainerResources::default(),
+            resources,
+            backend,
+            stopped_at: None,
+            restart_count: 0,
+            last_error: None,
+            healthy: None,
+            last_health_check: None,
         };
-        self.data.containers.push(container.clone());
-        self.save()?;
+        self.db.write(|tx| put(tx, &container))?;
         Ok(container)
     }
 
     #[inline]
-    pub fn get(&self, id: &str) -> Option<&Container> {
-        self.data.containers.iter().find(|c| c.id == id)
+    pub fn get(&self, id: &str) -> Option<Container> {
+        or_default(
+            self.db
+                .query_doc("SELECT data FROM containers WHERE id = ?1", params![id]),
+        )
+    }
+
+    #[inline]
+    pub fn list_by_user(&self, user_id: &str) -> Vec<Container> {
+        or_default(self.db.query_docs(
+            "SELECT data FROM containers WHERE user_id = ?1 ORDER BY created_at, id",
+            params![user_id],
+        ))
     }
 
     #[inline]
-    pub fn list_by_user(&self, user_id: &str) -> Vec<&Container> {
-        self.data
-            .containers
-            .iter()
-            .filter(|c| c.user_id == user_id)
-            .collect()
+    pub fn list_by_tenant(&self, tenant_id: &str) -> Vec<Container> {
+        or_default(self.db.query_docs(
+            "SELECT data FROM containers WHERE tenant_id = ?1 ORDER BY created_at, id",
+            params![tenant_id],
+        ))
     }
 
     #[inline]
-    pub fn list_by_tenant(&self, tenant_id: &str) -> Vec<&Container> {
-        self.data
-            .containers
-            .iter()
-            .filter(|c| c.tenant_id == tenant_id)
-            .collect()
+    pub fn list(&self) -> Vec<Container> {
+        or_default(
+            self.db
+                .query_docs("SELECT data FROM containers ORDER BY created_at, id", []),
+        )
     }
 
+    /// Record state only; `ContainerSupervisor::start` launches the instance.
     pub fn start(&mut self, id: &str) -> Result<()> {
-        if let Some(c) = self.data.containers.iter_mut().find(|c| c.id == id) {
+        self.update(id, |c| {
             c.status = ContainerStatus::Starting;
             c.started_at = Some(chrono::Utc::now().to_rfc3339());
-            self.save()?;
-        }
-        Ok(())
+            c.stopped_at = None;
+            c.restart_count = 0;
+            c.last_error = None;
+        })
     }
 
+    /// Record state only; `ContainerSupervisor::stop` terminates the instance.
     pub fn stop(&mut self, id: &str) -> Result<()> {
-        if let Some(c) = self.data.containers.iter_mut().find(|c| c.id == id) {
-            c.status = ContainerStatus::Stopping;
-            self.save()?;
-        }
-        Ok(())
+        self.update(id, |c| c.status = ContainerStatus::Stopping)
     }
 
-    #[inline]
-    fn save(&self) -> Result<()> {
-        if let Some(parent) = self.path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::write(&self.path, serde_json::to_string(&self.data)?)?;
-        Ok(())
+    /// Apply `change` to a container and persist it in one transaction.
+    pub fn update(&mut self, id: &str, change: impl FnOnce(&mut Container)) -> Result<()> {
+        self.db.write(|tx| {
+            let Some(mut container) = query_doc::<Container>(
+                tx,
+                "SELECT data FROM containers WHERE id = ?1",
+                params![id],
+            )?
+            else {
+                anyhow::bail!("Container not found")
+            };
+            change(&mut container);
+            put(tx, &container)
+        })
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<Option<Container>> {
+        self.db.write(|tx| {
+            let removed = query_doc::<Container>(
+                tx,
+                "SELECT data FROM containers WHERE id = ?1",
+                params![id],
+            )?;
+            tx.execute("DELETE FROM containers WHERE id = ?1", params![id])?;
+            Ok(removed)
+        })
     }
 }
@@ -0,0 +1,193 @@
+//! Credential handling for tenant users: Argon2id password hashing with
+//! migration from legacy hashes, login throttling and reset tokens.
+
+use anyhow::Result;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Consecutive failed logins before an account is locked.
+pub const MAX_FAILED_LOGINS: u32 = 5;
+/// How long a locked account stays locked.
+pub const LOCKOUT_SECS: i64 = 15 * 60;
+/// Lifetime of a password reset token.
+pub const RESET_TOKEN_TTL_SECS: i64 = 60 * 60;
+/// Minimum accepted password length.
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+fn argon2() -> Argon2<'static> {
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::default())
+}
+
+/// Hash `password` with Argon2id and a random salt, returning a PHC string.
+pub fn hash_password(password: &str) -> Result<String> {
+    let mut salt = [0u8; 16];
+    getrandom::getrandom(&mut salt).map_err(|e| anyhow::anyhow!("RNG failed: {e}"))?;
+    let salt = SaltString::encode_b64(&salt).map_err(|e| anyhow::anyhow!("invalid salt: {e}"))?;
+    argon2()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| anyhow::anyhow!("password hashing failed: {e}"))
+}
+
+/// Outcome of checking a password against a stored hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordCheck {
+    Invalid,
+    Valid,
+    /// Correct, but stored with a legacy or outdated scheme; rehash and store.
+    ValidNeedsRehash,
+}
+
+impl PasswordCheck {
+    pub fn is_valid(self) -> bool {
+        self != Self::Invalid
+    }
+}
+
+/// Verify `password` against `stored`, accepting Argon2 PHC strings and the
+/// legacy unsalted hex hashes written by earlier versions.
+pub fn verify_password(password: &str, stored: &str) -> PasswordCheck {
+    if let Ok(parsed) = PasswordHash::new(stored) {
+        if argon2()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_err()
+        {
+            return PasswordCheck::Invalid;
+        }
+        let current = parsed.algorithm.as_str() == Algorithm::Argon2id.ident().as_str()
+            && Params::try_from(&parsed).is_ok_and(|params| {
+                let target = Params::default();
+                params.m_cost() == target.m_cost()
+                    && params.t_cost() == target.t_cost()
+                    && params.p_cost() == target.p_cost()
+            });
+        return if current {
+            PasswordCheck::Valid
+        } else {
+            PasswordCheck::ValidNeedsRehash
+        };
+    }
+
+    if crate::security::pairing::constant_time_eq(&legacy_hash(password), stored) {
+        PasswordCheck::ValidNeedsRehash
+    } else {
+        PasswordCheck::Invalid
+    }
+}
+
+/// The pre-Argon2 scheme (`DefaultHasher`), kept only to migrate existing users.
+fn legacy_hash(password: &str) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    let mut hasher = DefaultHasher::new();
+    password.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+/// Reject passwords that are too short to be worth hashing.
+pub fn validate_new_password(password: &str) -> Result<()> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        anyhow::bail!("Password must be at least {MIN_PASSWORD_LEN} characters");
+    }
+    Ok(())
+}
+
+/// Random URL-safe token for password resets.
+pub fn generate_reset_token() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).expect("RNG failed");
+    hex::encode(bytes)
+}
+
+/// Reset tokens are stored hashed so a leaked user store cannot be replayed.
+pub fn hash_reset_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.trim().as_bytes()))
+}
+
+/// Sliding-window limiter for login attempts, keyed by client address or email.
+pub struct LoginRateLimiter {
+    max_attempts: usize,
+    window: Duration,
+    attempts: Mutex<HashMap<String, Vec<Instant>>>,
+}
+
+impl LoginRateLimiter {
+    pub fn new(max_attempts: usize, window: Duration) -> Self {
+        Self {
+            max_attempts,
+            window,
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record an attempt for `key`; returns `false` when the key is over its budget.
+    pub fn check(&self, key: &str) -> bool {
+        let now = Instant::now();
+        let mut attempts = self.attempts.lock();
+        attempts.retain(|_, times| {
+            times.retain(|t| now.duration_since(*t) < self.window);
+            !times.is_empty()
+        });
+        let times = attempts.entry(key.to_string()).or_default();
+        if times.len() >= self.max_attempts {
+            return false;
+        }
+        times.push(now);
+        true
+    }
+}
+
+impl Default for LoginRateLimiter {
+    fn default() -> Self {
+        Self::new(10, Duration::from_secs(60))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argon2_hashes_verify_and_are_salted() {
+        let first = hash_password("correct horse").unwrap();
+        let second = hash_password("correct horse").unwrap();
+        assert!(first.starts_with("$argon2id$"));
+        assert_ne!(first, second);
+        assert_eq!(
+            verify_password("correct horse", &first),
+            PasswordCheck::Valid
+        );
+        assert_eq!(verify_password("wrong", &first), PasswordCheck::Invalid);
+    }
+
+    #[test]
+    fn legacy_hashes_verify_and_request_rehash() {
+        let legacy = legacy_hash("hunter22");
+        assert_eq!(
+            verify_password("hunter22", &legacy),
+            PasswordCheck::ValidNeedsRehash
+        );
+        assert_eq!(verify_password("hunter2", &legacy), PasswordCheck::Invalid);
+    }
+
+    #[test]
+    fn rate_limiter_blocks_after_budget() {
+        let limiter = LoginRateLimiter::new(2, Duration::from_secs(60));
+        assert!(limiter.check("1.2.3.4"));
+        assert!(limiter.check("1.2.3.4"));
+        assert!(!limiter.check("1.2.3.4"));
+        assert!(limiter.check("5.6.7.8"));
+    }
+
+    #[test]
+    fn reset_tokens_hash_deterministically() {
+        let token = generate_reset_token();
+        assert_eq!(token.len(), 64);
+        assert_eq!(hash_reset_token(&token), hash_reset_token(&token));
+        assert_ne!(hash_reset_token(&token), token);
+    }
+}
@@ -0,0 +1,380 @@
+//! SQLite storage shared by the multi-tenant stores.
+//!
+//! Each record is a row keyed by id, with indexed columns for the fields the
+//! stores filter on and the full record as a JSON document. Writes run in
+//! `BEGIN IMMEDIATE` transactions, so a read-modify-write never interleaves
+//! with another writer, whether in this process or another one. The database
+//! is in WAL mode and reads go through a few separate connections, so they
+//! do not queue behind a write.
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Params, Transaction, TransactionBehavior};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const DB_FILE: &str = "tenant.db";
+
+/// How long a writer waits for another process holding the lock.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Read-only connections kept next to the writer.
+const READ_CONNECTIONS: usize = 4;
+
+enum Migration {
+    Sql(&'static str),
+    /// Import the JSON files the stores used before this database, see
+    /// [`super::import`].
+    ImportJsonStores,
+}
+
+/// Migrations, applied in order. `PRAGMA user_version` records how many have
+/// run; append new entries, never edit old ones.
+const MIGRATIONS: &[Migration] = &[
+    Migration::Sql(
+        "CREATE TABLE tenants (
+        id          TEXT PRIMARY KEY,
+        status      TEXT NOT NULL,
+        created_at  TEXT NOT NULL,
+        data        TEXT NOT NULL
+    );
+    CREATE TABLE sequences (
+        name   TEXT PRIMARY KEY,
+        value  INTEGER NOT NULL
+    );
+    CREATE TABLE users (
+        id          TEXT PRIMARY KEY,
+        tenant_id   TEXT NOT NULL,
+        email       TEXT NOT NULL,
+        created_at  TEXT NOT NULL,
+        data        TEXT NOT NULL
+    );
+    CREATE INDEX idx_users_tenant ON users(tenant_id);
+    CREATE INDEX idx_users_email ON users(email);
+    CREATE TABLE sessions (
+        token       TEXT PRIMARY KEY,
+        user_id     TEXT NOT NULL,
+        expires_at  TEXT NOT NULL,
+        data        TEXT NOT NULL
+    );
+    CREATE INDEX idx_sessions_user ON sessions(user_id);
+    CREATE TABLE reset_tokens (
+        token_hash  TEXT PRIMARY KEY,
+        user_id     TEXT NOT NULL,
+        expires_at  TEXT NOT NULL
+    );
+    CREATE INDEX idx_reset_tokens_user ON reset_tokens(user_id);
+    CREATE TABLE allowlist (
+        email  TEXT PRIMARY KEY
+    );
+    CREATE TABLE groups (
+        id          TEXT PRIMARY KEY,
+        tenant_id   TEXT NOT NULL,
+        created_at  TEXT NOT NULL,
+        data        TEXT NOT NULL
+    );
+    CREATE INDEX idx_groups_tenant ON groups(tenant_id);
+    CREATE TABLE group_members (
+        group_id   TEXT NOT NULL,
+        user_id    TEXT NOT NULL,
+        joined_at  TEXT NOT NULL,
+        data       TEXT NOT NULL,
+        PRIMARY KEY (group_id, user_id)
+    );
+    CREATE INDEX idx_group_members_user ON group_members(user_id);
+    CREATE TABLE containers (
+        id          TEXT PRIMARY KEY,
+        tenant_id   TEXT NOT NULL,
+        user_id     TEXT NOT NULL,
+        created_at  TEXT NOT NULL,
+        data        TEXT NOT NULL
+    );
+    CREATE INDEX idx_containers_tenant ON containers(tenant_id);
+    CREATE INDEX idx_containers_user ON containers(user_id);
+    CREATE TABLE vault_entries (
+        id          TEXT PRIMARY KEY,
+        tenant_id   TEXT NOT NULL,
+        user_id     TEXT NOT NULL,
+        name        TEXT NOT NULL,
+        created_at  TEXT NOT NULL,
+        data        TEXT NOT NULL
+    );
+    CREATE INDEX idx_vault_entries_tenant ON vault_entries(tenant_id);
+    CREATE INDEX idx_vault_entries_user ON vault_entries(user_id);
+    CREATE UNIQUE INDEX idx_vault_entries_name ON vault_entries(tenant_id, user_id, name);
+    CREATE TABLE ceilings (
+        user_id  TEXT PRIMARY KEY,
+        data     TEXT NOT NULL
+    );
+    CREATE TABLE escalation_requests (
+        id          TEXT PRIMARY KEY,
+        user_id     TEXT NOT NULL,
+        created_at  TEXT NOT NULL,
+        data        TEXT NOT NULL
+    );
+    CREATE INDEX idx_escalation_requests_user ON escalation_requests(user_id);
+    CREATE TABLE json_imports (
+        file         TEXT PRIMARY KEY,
+        imported_at  TEXT NOT NULL
+    );",
+    ),
+    Migration::ImportJsonStores,
+];
+
+/// Handle to `tenant.db` in the server data directory. Clones share the same
+/// connections.
+#[derive(Clone)]
+pub struct TenantDb {
+    conn: Arc<Mutex<Connection>>,
+    readers: Arc<Vec<Mutex<Connection>>>,
+    data_dir: PathBuf,
+}
+
+impl TenantDb {
+    /// Open (creating if needed) the database under `data_dir` and apply
+    /// pending migrations, including the one-time JSON store import.
+    pub fn open(data_dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(data_dir)
+            .with_context(|| format!("failed to create {}", data_dir.display()))?;
+        let db_path = data_dir.join(DB_FILE);
+        let conn = open_connection(&db_path)?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous  = NORMAL;",
+        )?;
+        let readers = (0..READ_CONNECTIONS)
+            .map(|_| {
+                let reader = open_connection(&db_path)?;
+                reader.pragma_update(None, "query_only", true)?;
+                Ok(Mutex::new(reader))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let db = Self {
+            conn: Arc::new(Mutex::new(conn)),
+            readers: Arc::new(readers),
+            data_dir: data_dir.to_path_buf(),
+        };
+        db.migrate()?;
+        Ok(db)
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    /// Apply pending migrations, bumping `user_version` after each one. The
+    /// version is re-checked under the write lock, so concurrent opens apply
+    /// each migration once.
+    fn migrate(&self) -> Result<()> {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            let version = index + 1;
+            if self.read(user_version)? >= version {
+                continue;
+            }
+            match migration {
+                Migration::Sql(sql) => self.write(|tx| {
+                    if user_version(tx)? < version {
+                        tx.execute_batch(sql)
+                            .with_context(|| format!("tenant db migration {version} failed"))?;
+                        tx.pragma_update(None, "user_version", version)?;
+                    }
+                    Ok(())
+                })?,
+                // Imports one file per transaction; `json_imports` keeps a
+                // retry after a partial run from importing a file twice.
+                Migration::ImportJsonStores => {
+                    super::import::import_json_stores(self)?;
+                    self.write(|tx| {
+                        if user_version(tx)? < version {
+                            tx.pragma_update(None, "user_version", version)?;
+                        }
+                        Ok(())
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run `f` in an immediate transaction, committing if it succeeds.
+    pub fn write<T>(&self, f: impl FnOnce(&Transaction<'_>) -> Result<T>) -> Result<T> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let value = f(&tx)?;
+        tx.commit()?;
+        Ok(value)
+    }
+
+    /// Run read-only queries on a free read connection.
+    pub fn read<T>(&self, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+        let reader = self
+            .readers
+            .iter()
+            .find_map(Mutex::try_lock)
+            .unwrap_or_else(|| self.readers[0].lock());
+        f(&reader)
+    }
+
+    /// All JSON documents selected by `sql`, whose only column is `data`.
+    pub fn query_docs<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+        params: impl Params,
+    ) -> Result<Vec<T>> {
+        self.read(|conn| query_docs(conn, sql, params))
+    }
+
+    /// The first JSON document selected by `sql`, if any.
+    pub fn query_doc<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+        params: impl Params,
+    ) -> Result<Option<T>> {
+        self.read(|conn| query_doc(conn, sql, params))
+    }
+}
+
+fn open_connection(path: &Path) -> Result<Connection> {
+    let conn =
+        Connection::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(conn)
+}
+
+fn user_version(conn: &Connection) -> Result<usize> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+pub fn query_docs<T: DeserializeOwned>(
+    conn: &Connection,
+    sql: &str,
+    params: impl Params,
+) -> Result<Vec<T>> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let rows = stmt.query_map(params, |row| row.get::<_, String>(0))?;
+    rows.map(|data| Ok(serde_json::from_str(&data?)?)).collect()
+}
+
+pub fn query_doc<T: DeserializeOwned>(
+    conn: &Connection,
+    sql: &str,
+    params: impl Params,
+) -> Result<Option<T>> {
+    let data: Option<String> = conn
+        .prepare_cached(sql)?
+        .query_row(params, |row| row.get(0))
+        .optional()?;
+    data.map(|d| Ok(serde_json::from_str(&d)?)).transpose()
+}
+
+pub fn to_doc<T: Serialize>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string(value)?)
+}
+
+/// The next value of the named counter.
+pub fn next_sequence(tx: &Transaction<'_>, name: &str) -> Result<u32> {
+    tx.execute(
+        "INSERT INTO sequences (name, value) VALUES (?1, 1)
+         ON CONFLICT(name) DO UPDATE SET value = value + 1",
+        params![name],
+    )?;
+    Ok(tx.query_row(
+        "SELECT value FROM sequences WHERE name = ?1",
+        params![name],
+        |row| row.get(0),
+    )?)
+}
+
+/// Log a failed read and fall back to `T::default()`, matching how the stores
+/// treat unreadable data.
+pub fn or_default<T: Default>(result: Result<T>) -> T {
+    result.unwrap_or_else(|e| {
+        tracing::warn!("Tenant store read failed: {e:#}");
+        T::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_are_idempotent_and_sequences_persist() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = TenantDb::open(tmp.path()).unwrap();
+        assert_eq!(db.write(|tx| next_sequence(tx, "t")).unwrap(), 1);
+        assert_eq!(db.write(|tx| next_sequence(tx, "t")).unwrap(), 2);
+        drop(db);
+
+        let db = TenantDb::open(tmp.path()).unwrap();
+        let version: usize = db
+            .read(|c| Ok(c.query_row("PRAGMA user_version", [], |r| r.get(0))?))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+        assert_eq!(db.write(|tx| next_sequence(tx, "t")).unwrap(), 3);
+    }
+
+    #[test]
+    fn reads_do_not_wait_for_a_write() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = TenantDb::open(tmp.path()).unwrap();
+        db.write(|tx| {
+            next_sequence(tx, "t")?;
+            // The writer's transaction is still open; readers see the last commit.
+            let committed: Option<u32> = db.read(|conn| {
+                Ok(conn
+                    .query_row("SELECT value FROM sequences WHERE name = 't'", [], |r| {
+                        r.get(0)
+                    })
+                    .optional()?)
+            })?;
+            assert_eq!(committed, None);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn failed_writes_roll_back() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = TenantDb::open(tmp.path()).unwrap();
+        let result: Result<()> = db.write(|tx| {
+            next_sequence(tx, "t")?;
+            anyhow::bail!("abort")
+        });
+        assert!(result.is_err());
+        assert_eq!(db.write(|tx| next_sequence(tx, "t")).unwrap(), 1);
+    }
+
+    #[test]
+    fn concurrent_writers_do_not_lose_updates() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let id = crate::tenant::TenantStore::new(TenantDb::open(tmp.path()).unwrap())
+            .create("Acme".into(), None)
+            .unwrap()
+            .id;
+
+        // Separate handles have separate connections, like separate processes.
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let mut store =
+                    crate::tenant::TenantStore::new(TenantDb::open(tmp.path()).unwrap());
+                let id = id.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        store.record_chat(&id, 0).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let store = crate::tenant::TenantStore::new(TenantDb::open(tmp.path()).unwrap());
+        assert_eq!(store.get(&id).unwrap().stats.chat_requests, 100);
+    }
+}
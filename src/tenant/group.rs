@@ -1,7 +1,8 @@
+use super::db::{or_default, query_doc, to_doc, TenantDb};
 use anyhow::Result;
+use rusqlite::{params, Transaction};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Group {
@@ -42,6 +43,7 @@ impl Default for GroupRole {
     }
 }
 
+/// The legacy `groups.json` layout, read once by the JSON importer.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GroupStoreData {
     pub groups: Vec<Group>,
@@ -49,21 +51,42 @@ pub struct GroupStoreData {
 }
 
 pub struct GroupStore {
-    data: GroupStoreData,
-    path: std::path::PathBuf,
+    db: TenantDb,
+}
+
+pub(crate) fn put(tx: &Transaction<'_>, group: &Group) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO groups (id, tenant_id, created_at, data) VALUES (?1, ?2, ?3, ?4)",
+        params![group.id, group.tenant_id, group.created_at, to_doc(group)?],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn put_member(tx: &Transaction<'_>, group_id: &str, member: &GroupMember) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO group_members (group_id, user_id, joined_at, data)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![group_id, member.user_id, member.joined_at, to_doc(member)?],
+    )?;
+    Ok(())
+}
+
+fn touch(tx: &Transaction<'_>, group_id: &str) -> Result<()> {
+    if let Some(mut group) = query_doc::<Group>(
+        tx,
+        "SELECT data FROM groups WHERE id = ?1",
+        params![group_id],
+    )? {
+        group.updated_at = chrono::Utc::now().to_rfc3339();
+        put(tx, &group)?;
+    }
+    Ok(())
 }
 
 impl GroupStore {
     #[inline]
-    pub fn new(base_path: &Path) -> Self {
-        let path = base_path.join("groups.json");
-        let data = if path.exists() {
-            let content = std::fs::read_to_string(&path).unwrap_or_default();
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            GroupStoreData::default()
-        };
-        Self { data, path }
+    pub fn new(db: TenantDb) -> Self {
+        Self { db }
     }
 
     pub fn create(
@@ -84,85 +107,74 @@ impl GroupStore {
             updated_at: now,
             settings: GroupSettings::default(),
         };
-        self.data.groups.push(group.clone());
-        self.data.members.insert(group.id.clone(), vec![]);
-        self.save()?;
+        self.db.write(|tx| put(tx, &group))?;
         Ok(group)
     }
 
     #[inline]
-    pub fn get(&self, id: &str) -> Option<&Group> {
-        self.data.groups.iter().find(|g| g.id == id)
-    }
-
-    #[inline]
-    pub fn list_by_tenant(&self, tenant_id: &str) -> Vec<&Group> {
-        self.data
-            .groups
-            .iter()
-            .filter(|g| g.tenant_id == tenant_id)
-            .collect()
+    pub fn get(&self, id: &str) -> Option<Group> {
+        or_default(
+            self.db
+                .query_doc("SELECT data FROM groups WHERE id = ?1", params![id]),
+        )
     }
 
     #[inline]
-    pub fn get_mut(&mut self, id: &str) -> Option<&mut Group> {
-        self.data.groups.iter_mut().find(|g| g.id == id)
+    pub fn list_by_tenant(&self, tenant_id: &str) -> Vec<Group> {
+        or_default(self.db.query_docs(
+            "SELECT data FROM groups WHERE tenant_id = ?1 ORDER BY created_at, id",
+            params![tenant_id],
+        ))
     }
 
     pub fn add_member(&mut self, group_id: &str, user_id: String, role: GroupRole) -> Result<()> {
-        let members = self
-            .data
-            .members
-            .entry(group_id.to_string())
-            .or_insert_with(Vec::new);
-        if !members.iter().any(|m| m.user_id == user_id) {
-            members.push(GroupMember {
-                user_id,
-                role,
-                joined_at: chrono::Utc::now().to_rfc3339(),
-            });
-            if let Some(group) = self.get_mut(group_id) {
-                group.updated_at = chrono::Utc::now().to_rfc3339();
+        self.db.write(|tx| {
+            let exists: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM group_members WHERE group_id = ?1 AND user_id = ?2)",
+                params![group_id, user_id],
+                |row| row.get(0),
+            )?;
+            if !exists {
+                let member = GroupMember {
+                    user_id,
+                    role,
+                    joined_at: chrono::Utc::now().to_rfc3339(),
+                };
+                put_member(tx, group_id, &member)?;
+                touch(tx, group_id)?;
             }
-            self.save()?;
-        }
-        Ok(())
+            Ok(())
+        })
     }
 
     pub fn remove_member(&mut self, group_id: &str, user_id: &str) -> Result<()> {
-        if let Some(members) = self.data.members.get_mut(group_id) {
-            members.retain(|m| m.user_id != user_id);
-            if let Some(group) = self.get_mut(group_id) {
-                group.updated_at = chrono::Utc::now().to_rfc3339();
+        self.db.write(|tx| {
+            let removed = tx.execute(
+                "DELETE FROM group_members WHERE group_id = ?1 AND user_id = ?2",
+                params![group_id, user_id],
+            )?;
+            if removed > 0 {
+                touch(tx, group_id)?;
             }
-            self.save()?;
-        }
-        Ok(())
+            Ok(())
+        })
     }
 
     #[inline]
-    pub fn get_members(&self, group_id: &str) -> &[GroupMember] {
-        self.data
-            .members
-            .get(group_id)
-            .map(|v| v.as_slice())
-            .unwrap_or(&[])
+    pub fn get_members(&self, group_id: &str) -> Vec<GroupMember> {
+        or_default(self.db.query_docs(
+            "SELECT data FROM group_members WHERE group_id = ?1 ORDER BY joined_at, user_id",
+            params![group_id],
+        ))
     }
 
     pub fn is_member(&self, group_id: &str, user_id: &str) -> bool {
-        self.data
-            .members
-            .get(group_id)
-            .map(|m| m.iter().any(|mm| mm.user_id == user_id))
-            .unwrap_or(false)
-    }
-
-    #[inline]
-    fn save(&self) -> Result<()> {
-        if let Some(parent) = self.path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::write(&self.path, serde_json::to_string(&self.data)?)?;
-        Ok(())
+        or_default(self.db.read(|conn| {
+            Ok(conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM group_members WHERE group_id = ?1 AND user_id = ?2)",
+                params![group_id, user_id],
+                |row| row.get(0),
+            )?)
+        }))
     }
 }
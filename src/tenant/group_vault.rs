@@ -1,8 +1,14 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::io::Write;
 use std::path::Path;
 
+const AUDIT_FILE: &str = "group_vault_audit.jsonl";
+
+fn default_unlock_window_secs() -> u64 {
+    3600
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupVaultEntry {
     pub id: String,
@@ -11,8 +17,34 @@ pub struct GroupVaultEntry {
     pub encrypted_value: String,
     pub created_by: String,
     pub created_at: String,
+    /// Approvals required to unlock (M of N)
     pub threshold: u8,
+    /// Approvals for the pending request
     pub approvals: Vec<Approval>,
+    /// Users whose approval counts (N); empty means any group member
+    #[serde(default)]
+    pub approvers: Vec<String>,
+    /// How long a quorum unlock lasts
+    #[serde(default = "default_unlock_window_secs")]
+    pub unlock_window_secs: u64,
+    #[serde(default)]
+    pub pending_request: Option<AccessRequest>,
+    #[serde(default)]
+    pub unlocked_until: Option<String>,
+}
+
+impl GroupVaultEntry {
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked_until
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .is_some_and(|until| until > chrono::Utc::now())
+    }
+
+    /// Whether `user_id` may approve, given the group's current members.
+    pub fn can_approve(&self, user_id: &str, is_member: bool) -> bool {
+        is_member && (self.approvers.is_empty() || self.approvers.iter().any(|a| a == user_id))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +53,32 @@ pub struct Approval {
     pub approved_at: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessRequest {
+    pub id: String,
+    pub requested_by: String,
+    pub reason: Option<String>,
+    pub requested_at: String,
+}
+
+/// Quorum settings for a new entry.
+#[derive(Debug, Clone)]
+pub struct QuorumPolicy {
+    pub threshold: u8,
+    pub approvers: Vec<String>,
+    pub unlock_window_secs: u64,
+}
+
+/// One decrypt of a vault entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessRecord {
+    pub entry_id: String,
+    pub user_id: String,
+    /// What read the secret, e.g. `api` or `tool:vault_get`
+    pub via: String,
+    pub accessed_at: String,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GroupVaultData {
     pub entries: Vec<GroupVaultEntry>,
@@ -29,6 +87,8 @@ pub struct GroupVaultData {
 pub struct GroupVaultStore {
     data: GroupVaultData,
     path: std::path::PathBuf,
+    /// Append-only, so readers in other processes never overwrite it
+    audit_path: std::path::PathBuf,
 }
 
 impl GroupVaultStore {
@@ -41,7 +101,11 @@ impl GroupVaultStore {
         } else {
             GroupVaultData::default()
         };
-        Self { data, path }
+        Self {
+            data,
+            path,
+            audit_path: base_path.join(AUDIT_FILE),
+        }
     }
 
     pub fn create(
@@ -52,6 +116,40 @@ impl GroupVaultStore {
         created_by: String,
         threshold: u8,
     ) -> Result<GroupVaultEntry> {
+        self.create_with(
+            group_id,
+            name,
+            encrypted_value,
+            created_by,
+            QuorumPolicy {
+                threshold,
+                approvers: Vec::new(),
+                unlock_window_secs: default_unlock_window_secs(),
+            },
+        )
+    }
+
+    pub fn create_with(
+        &mut self,
+        group_id: String,
+        name: String,
+        encrypted_value: String,
+        created_by: String,
+        policy: QuorumPolicy,
+    ) -> Result<GroupVaultEntry> {
+        if policy.threshold == 0 {
+            bail!("Threshold must be at least 1");
+        }
+        if !policy.approvers.is_empty() && usize::from(policy.threshold) > policy.approvers.len() {
+            bail!(
+                "Threshold {} exceeds the {} listed approvers",
+                policy.threshold,
+                policy.approvers.len()
+            );
+        }
+        if policy.unlock_window_secs == 0 {
+            bail!("Unlock window must be at least one second");
+        }
         let entry = GroupVaultEntry {
             id: uuid::Uuid::new_v4().to_string(),
             group_id,
@@ -59,8 +157,12 @@ impl GroupVaultStore {
             encrypted_value,
             created_by,
             created_at: chrono::Utc::now().to_rfc3339(),
-            threshold,
+            threshold: policy.threshold,
             approvals: Vec::new(),
+            approvers: policy.approvers,
+            unlock_window_secs: policy.unlock_window_secs,
+            pending_request: None,
+            unlocked_until: None,
         };
         self.data.entries.push(entry.clone());
         self.save()?;
@@ -72,6 +174,14 @@ impl GroupVaultStore {
         self.data.entries.iter().find(|e| e.id == id)
     }
 
+    fn get_mut(&mut self, id: &str) -> Result<&mut GroupVaultEntry> {
+        self.data
+            .entries
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Entry not found"))
+    }
+
     #[inline]
     pub fn list_by_group(&self, group_id: &str) -> Vec<&GroupVaultEntry> {
         self.data
@@ -81,40 +191,127 @@ impl GroupVaultStore {
             .collect()
     }
 
-    pub fn approve(&mut self, entry_id: &str, user_id: String) -> Result<bool> {
-        let approval_count = {
-            let entry = self
-                .data
-                .entries
-                .iter_mut()
-                .find(|e| e.id == entry_id)
-                .ok_or_else(|| anyhow::anyhow!("Entry not found"))?;
-            if !entry.approvals.iter().any(|a| a.user_id == user_id) {
-                entry.approvals.push(Approval {
-                    user_id,
-                    approved_at: chrono::Utc::now().to_rfc3339(),
-                });
-            }
-            entry.approvals.len()
+    /// Start a new approval round, discarding approvals from earlier rounds.
+    pub fn request_access(
+        &mut self,
+        entry_id: &str,
+        user_id: String,
+        reason: Option<String>,
+    ) -> Result<AccessRequest> {
+        let request = AccessRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            requested_by: user_id,
+            reason,
+            requested_at: chrono::Utc::now().to_rfc3339(),
         };
+        let entry = self.get_mut(entry_id)?;
+        entry.approvals.clear();
+        entry.pending_request = Some(request.clone());
+        self.save()?;
+        Ok(request)
+    }
 
-        let threshold = {
-            if let Some(entry) = self.data.entries.iter_mut().find(|e| e.id == entry_id) {
-                entry.threshold
-            } else {
-                return Ok(false);
-            }
+    /// Record an approval of the pending request. Reaching the threshold
+    /// unlocks the entry for its unlock window and closes the request.
+    /// Returns whether it is unlocked.
+    ///
+    /// Callers check that `user_id` may approve (see [`GroupVaultEntry::can_approve`]).
+    pub fn approve(&mut self, entry_id: &str, user_id: String) -> Result<bool> {
+        let entry = self.get_mut(entry_id)?;
+        if !entry.approvers.is_empty() && !entry.approvers.contains(&user_id) {
+            bail!("User is not an approver for this entry");
+        }
+        let Some(request) = &entry.pending_request else {
+            bail!("No pending access request for this entry");
         };
+        if request.requested_by == user_id {
+            bail!("Requesters cannot approve their own access request");
+        }
+        let now = chrono::Utc::now();
+        if !entry.approvals.iter().any(|a| a.user_id == user_id) {
+            entry.approvals.push(Approval {
+                user_id,
+                approved_at: now.to_rfc3339(),
+            });
+        }
 
+        let unlocked = entry.approvals.len() >= usize::from(entry.threshold);
+        if unlocked {
+            let window = i64::try_from(entry.unlock_window_secs).unwrap_or(i64::MAX);
+            entry.unlocked_until = Some(
+                (now + chrono::Duration::try_seconds(window).unwrap_or(chrono::Duration::MAX))
+                    .to_rfc3339(),
+            );
+            entry.pending_request = None;
+        }
         self.save()?;
-        Ok(approval_count >= threshold as usize)
+        Ok(unlocked)
     }
 
     #[inline]
     pub fn is_unlocked(&self, entry_id: &str) -> bool {
-        self.get(entry_id)
-            .map(|e| e.approvals.len() >= e.threshold as usize)
-            .unwrap_or(false)
+        self.get(entry_id).is_some_and(GroupVaultEntry::is_unlocked)
+    }
+
+    /// End an unlock window early.
+    pub fn lock(&mut self, entry_id: &str) -> Result<()> {
+        self.get_mut(entry_id)?.unlocked_until = None;
+        self.save()
+    }
+
+    /// Decrypt an unlocked entry with `decrypt` and record the access.
+    pub fn reveal(
+        &self,
+        entry_id: &str,
+        user_id: &str,
+        via: &str,
+        decrypt: impl FnOnce(&str) -> Result<String>,
+    ) -> Result<String> {
+        let entry = self
+            .get(entry_id)
+            .ok_or_else(|| anyhow::anyhow!("Entry not found"))?;
+        if !entry.is_unlocked() {
+            bail!(
+                "Vault entry '{}' is locked; it needs {} approval(s) to unlock",
+                entry.name,
+                entry.threshold
+            );
+        }
+        let value = decrypt(&entry.encrypted_value)?;
+        self.record_access(AccessRecord {
+            entry_id: entry_id.to_string(),
+            user_id: user_id.to_string(),
+            via: via.to_string(),
+            accessed_at: chrono::Utc::now().to_rfc3339(),
+        })?;
+        Ok(value)
+    }
+
+    fn record_access(&self, record: AccessRecord) -> Result<()> {
+        if let Some(parent) = self.audit_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.audit_path)
+            .with_context(|| format!("failed to open {}", self.audit_path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        Ok(())
+    }
+
+    /// Every recorded decrypt of `entry_id`, oldest first.
+    pub fn access_log(&self, entry_id: &str) -> Result<Vec<AccessRecord>> {
+        if !self.audit_path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&self.audit_path)
+            .with_context(|| format!("failed to read {}", self.audit_path.display()))?;
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<AccessRecord>(line).ok())
+            .filter(|r| r.entry_id == entry_id)
+            .collect())
     }
 
     pub fn delete(&mut self, id: &str) -> Result<()> {
@@ -131,3 +328,122 @@ impl GroupVaultStore {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decrypt(value: &str) -> Result<String> {
+        Ok(value.to_uppercase())
+    }
+
+    fn entry(store: &mut GroupVaultStore, threshold: u8, approvers: &[&str]) -> String {
+        store
+            .create_with(
+                "g1".into(),
+                "db_password".into(),
+                "secret".into(),
+                "alice".into(),
+                QuorumPolicy {
+                    threshold,
+                    approvers: approvers.iter().map(|a| (*a).to_string()).collect(),
+                    unlock_window_secs: 60,
+                },
+            )
+            .unwrap()
+            .id
+    }
+
+    #[test]
+    fn quorum_unlocks_for_window_and_logs_reads() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut store = GroupVaultStore::new(tmp.path());
+        let id = entry(&mut store, 2, &["bob", "carol", "dave"]);
+
+        store
+            .request_access(&id, "alice".into(), Some("deploy".into()))
+            .unwrap();
+        assert!(store.approve(&id, "alice".into()).is_err());
+        assert!(!store.approve(&id, "bob".into()).unwrap());
+        assert!(!store.approve(&id, "bob".into()).unwrap());
+        assert!(store.reveal(&id, "alice", "api", decrypt).is_err());
+
+        assert!(store.approve(&id, "carol".into()).unwrap());
+        assert!(store.get(&id).unwrap().pending_request.is_none());
+        assert_eq!(
+            store.reveal(&id, "alice", "api", decrypt).unwrap(),
+            "SECRET"
+        );
+        store.reveal(&id, "bob", "tool:vault_get", decrypt).unwrap();
+
+        let log = store.access_log(&id).unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[1].via, "tool:vault_get");
+
+        // Persisted across reloads.
+        let reloaded = GroupVaultStore::new(tmp.path());
+        assert!(reloaded.is_unlocked(&id));
+    }
+
+    #[test]
+    fn expired_or_locked_entries_cannot_be_read() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut store = GroupVaultStore::new(tmp.path());
+        let id = entry(&mut store, 1, &[]);
+        store.request_access(&id, "alice".into(), None).unwrap();
+        assert!(store.approve(&id, "bob".into()).unwrap());
+        assert!(store.is_unlocked(&id));
+
+        store.get_mut(&id).unwrap().unlocked_until =
+            Some((chrono::Utc::now() - chrono::Duration::seconds(1)).to_rfc3339());
+        assert!(!store.is_unlocked(&id));
+        assert!(store.reveal(&id, "bob", "api", decrypt).is_err());
+
+        // A new request starts a fresh round.
+        store.request_access(&id, "alice".into(), None).unwrap();
+        assert!(store.approve(&id, "bob".into()).unwrap());
+        store.lock(&id).unwrap();
+        assert!(store.reveal(&id, "bob", "api", decrypt).is_err());
+        assert!(store.access_log(&id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn approval_requires_a_pending_request() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut store = GroupVaultStore::new(tmp.path());
+        let id = entry(&mut store, 1, &[]);
+
+        assert!(store.approve(&id, "bob".into()).is_err());
+        assert!(!store.is_unlocked(&id));
+        assert!(store.get(&id).unwrap().pending_request.is_none());
+    }
+
+    #[test]
+    fn requester_cannot_approve_own_request() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut store = GroupVaultStore::new(tmp.path());
+        let id = entry(&mut store, 1, &[]);
+
+        store.request_access(&id, "alice".into(), None).unwrap();
+        assert!(store.approve(&id, "alice".into()).is_err());
+        assert!(!store.is_unlocked(&id));
+        assert!(store.approve(&id, "bob".into()).unwrap());
+    }
+
+    #[test]
+    fn rejects_unsatisfiable_policies() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut store = GroupVaultStore::new(tmp.path());
+        let policy = |threshold, approvers: &[&str]| QuorumPolicy {
+            threshold,
+            approvers: approvers.iter().map(|a| (*a).to_string()).collect(),
+            unlock_window_secs: 60,
+        };
+        let create = |store: &mut GroupVaultStore, p| {
+            store.create_with("g".into(), "n".into(), "v".into(), "u".into(), p)
+        };
+        assert!(create(&mut store, policy(0, &[])).is_err());
+        assert!(create(&mut store, policy(3, &["a", "b"])).is_err());
+        assert!(create(&mut store, policy(2, &["a", "b"])).is_ok());
+    }
+}
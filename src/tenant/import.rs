@@ -0,0 +1,222 @@
+//! One-shot import of the JSON files the tenant stores used before `tenant.db`.
+//!
+//! Runs once, as a `tenant.db` migration. Each file is imported in its own
+//! transaction, recorded in `json_imports` and renamed to `<file>.imported`,
+//! so resuming an interrupted import never imports a file twice.
+
+use super::container::ContainerStoreData;
+use super::db::{to_doc, TenantDb};
+use super::group::GroupStoreData;
+use super::tenant::TenantStoreData;
+use super::user::UserStoreData;
+use super::vault::VaultStoreData;
+use crate::container::capability_ceiling::CeilingStoreData;
+use anyhow::{Context, Result};
+use rusqlite::{params, OptionalExtension, Transaction};
+use serde::de::DeserializeOwned;
+
+type Importer = fn(&Transaction<'_>, &str) -> Result<usize>;
+
+const JSON_STORES: &[(&str, Importer)] = &[
+    ("tenants.json", import_tenants),
+    ("users.json", import_users),
+    ("groups.json", import_groups),
+    ("containers.json", import_containers),
+    ("vault_store.json", import_vault),
+    ("ceilings.json", import_ceilings),
+];
+
+/// Import every JSON store file in the data directory that has not been
+/// imported yet. Returns the number of records imported.
+pub fn import_json_stores(db: &TenantDb) -> Result<usize> {
+    let mut total = 0;
+    for (file, import) in JSON_STORES {
+        let path = db.data_dir().join(file);
+        if !path.exists() {
+            continue;
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let imported = db.write(|tx| {
+            let done: Option<String> = tx
+                .query_row(
+                    "SELECT imported_at FROM json_imports WHERE file = ?1",
+                    params![file],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if done.is_some() {
+                return Ok(None);
+            }
+            let count = import(tx, &content).with_context(|| format!("failed to import {file}"))?;
+            tx.execute(
+                "INSERT INTO json_imports (file, imported_at) VALUES (?1, ?2)",
+                params![file, chrono::Utc::now().to_rfc3339()],
+            )?;
+            Ok(Some(count))
+        })?;
+        if let Some(count) = imported {
+            tracing::info!("Imported {count} records from {file} into the tenant database");
+            total += count;
+        }
+        let mut archived = path.clone().into_os_string();
+        archived.push(".imported");
+        std::fs::rename(&path, &archived)
+            .with_context(|| format!("failed to archive {}", path.display()))?;
+    }
+    Ok(total)
+}
+
+/// Parse a store file, treating an empty file as an empty store.
+fn parse<T: DeserializeOwned + Default>(content: &str) -> Result<T> {
+    if content.trim().is_empty() {
+        return Ok(T::default());
+    }
+    Ok(serde_json::from_str(content)?)
+}
+
+fn import_tenants(tx: &Transaction<'_>, content: &str) -> Result<usize> {
+    let data: TenantStoreData = parse(content)?;
+    for tenant in &data.tenants {
+        super::tenant::put(tx, tenant)?;
+    }
+    tx.execute(
+        "INSERT INTO sequences (name, value) VALUES ('tenant', ?1)
+         ON CONFLICT(name) DO UPDATE SET value = max(value, excluded.value)",
+        params![data.next_id],
+    )?;
+    Ok(data.tenants.len())
+}
+
+fn import_users(tx: &Transaction<'_>, content: &str) -> Result<usize> {
+    let data: UserStoreData = parse(content)?;
+    for user in &data.users {
+        super::user::put(tx, user)?;
+    }
+    for session in &data.sessions {
+        tx.execute(
+            "INSERT OR REPLACE INTO sessions (token, user_id, expires_at, data)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                session.token,
+                session.user_id,
+                session.expires_at,
+                to_doc(session)?
+            ],
+        )?;
+    }
+    for token in &data.reset_tokens {
+        tx.execute(
+            "INSERT OR REPLACE INTO reset_tokens (token_hash, user_id, expires_at)
+             VALUES (?1, ?2, ?3)",
+            params![token.token_hash, token.user_id, token.expires_at],
+        )?;
+    }
+    for email in &data.allowlist {
+        tx.execute(
+            "INSERT OR IGNORE INTO allowlist (email) VALUES (?1)",
+            params![email],
+        )?;
+    }
+    Ok(data.users.len() + data.sessions.len() + data.reset_tokens.len() + data.allowlist.len())
+}
+
+fn import_groups(tx: &Transaction<'_>, content: &str) -> Result<usize> {
+    let data: GroupStoreData = parse(content)?;
+    let mut count = data.groups.len();
+    for group in &data.groups {
+        super::group::put(tx, group)?;
+    }
+    for (group_id, members) in &data.members {
+        for member in members {
+            super::group::put_member(tx, group_id, member)?;
+        }
+        count += members.len();
+    }
+    Ok(count)
+}
+
+fn import_containers(tx: &Transaction<'_>, content: &str) -> Result<usize> {
+    let data: ContainerStoreData = parse(content)?;
+    for container in &data.containers {
+        super::container::put(tx, container)?;
+    }
+    Ok(data.containers.len())
+}
+
+fn import_vault(tx: &Transaction<'_>, content: &str) -> Result<usize> {
+    let data: VaultStoreData = parse(content)?;
+    for entry in &data.entries {
+        super::vault::put(tx, entry)?;
+    }
+    Ok(data.entries.len())
+}
+
+fn import_ceilings(tx: &Transaction<'_>, content: &str) -> Result<usize> {
+    let data: CeilingStoreData = parse(content)?;
+    for ceiling in data.ceilings.values() {
+        crate::container::capability_ceiling::put_ceiling(tx, ceiling)?;
+    }
+    for request in &data.escalation_requests {
+        crate::container::capability_ceiling::put_escalation(tx, request)?;
+    }
+    Ok(data.ceilings.len() + data.escalation_requests.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tenant::{ContainerManager, GroupStore, TenantStore, UserStore};
+
+    #[test]
+    fn imports_legacy_json_once() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path();
+        std::fs::write(
+            dir.join("tenants.json"),
+            r#"{"tenants":[{"id":"tenant_3","name":"Acme","domain":null,
+                "created_at":"2025-01-01T00:00:00Z","updated_at":"2025-01-01T00:00:00Z",
+                "settings":{"max_users":null,"max_containers":null,"allow_signup":false,"mfa_required":false},
+                "status":"Active"}],"next_id":3}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("users.json"),
+            r#"{"users":[{"id":"u1","tenant_id":"tenant_3","email":"a@acme.test","password_hash":"h",
+                "display_name":null,"role":"Admin","mfa_enabled":false,"mfa_secret":null,
+                "created_at":"2025-01-01T00:00:00Z","updated_at":"2025-01-01T00:00:00Z",
+                "last_login":null,"status":"Active"}],"sessions":[],"allowlist":["a@acme.test"]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("groups.json"),
+            r#"{"groups":[{"id":"g1","tenant_id":"tenant_3","name":"ops","description":null,
+                "created_by":"u1","created_at":"2025-01-01T00:00:00Z","updated_at":"2025-01-01T00:00:00Z",
+                "settings":{"sharing_enabled":false,"require_approval":false,"max_members":null}}],
+                "members":{"g1":[{"user_id":"u1","role":"Owner","joined_at":"2025-01-01T00:00:00Z"}]}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("containers.json"), "").unwrap();
+
+        let db = TenantDb::open(dir).unwrap();
+        assert!(dir.join("tenants.json.imported").exists());
+        assert!(!dir.join("containers.json").exists());
+
+        let mut tenants = TenantStore::new(db.clone());
+        assert_eq!(tenants.get("tenant_3").unwrap().name, "Acme");
+        assert_eq!(tenants.create("Next".into(), None).unwrap().id, "tenant_4");
+        let users = UserStore::new(db.clone());
+        assert_eq!(users.list_by_tenant("tenant_3").len(), 1);
+        assert!(users.is_allowlisted("a@acme.test"));
+        assert!(!users.is_allowlisted("b@acme.test"));
+        assert!(GroupStore::new(db.clone()).is_member("g1", "u1"));
+        assert!(ContainerManager::new(db.clone()).list().is_empty());
+
+        // A stale copy reappearing is not imported again, or even looked at.
+        std::fs::write(dir.join("tenants.json"), r#"{"tenants":[],"next_id":0}"#).unwrap();
+        drop(db);
+        let db = TenantDb::open(dir).unwrap();
+        assert_eq!(TenantStore::new(db).list().len(), 2);
+        assert!(dir.join("tenants.json").exists());
+    }
+}
@@ -1,13 +1,24 @@
 pub mod container;
+pub mod credentials;
+pub mod db;
 pub mod group;
 pub mod group_vault;
+pub mod import;
+pub mod notification;
+pub mod supervisor;
 pub mod tenant;
 pub mod user;
 pub mod vault;
+pub mod workspace;
 
 pub use container::{Container, ContainerManager, ContainerResources, ContainerStatus};
-pub use group::{Group, GroupMember, GroupStore};
-pub use group_vault::{Approval, GroupVaultEntry, GroupVaultStore};
+pub use db::TenantDb;
+pub use group::{Group, GroupMember, GroupRole, GroupStore};
+pub use group_vault::{
+    AccessRecord, AccessRequest, Approval, GroupVaultEntry, GroupVaultStore, QuorumPolicy,
+};
+pub use notification::{Notification, NotificationStore};
 pub use tenant::{Tenant, TenantSettings, TenantStats, TenantStore};
 pub use user::{User, UserRole, UserSession, UserStatus, UserStore};
 pub use vault::{VaultEntry, VaultStore};
+pub use workspace::{ChatHistoryEntry, TenantWorkspace};
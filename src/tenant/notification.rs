@@ -0,0 +1,113 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Notifications kept per user; the oldest are dropped first.
+const MAX_PER_USER: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: String,
+    pub user_id: String,
+    /// Machine-readable kind, e.g. `vault.access_requested`
+    pub kind: String,
+    pub message: String,
+    #[serde(default)]
+    pub data: serde_json::Value,
+    pub created_at: String,
+    #[serde(default)]
+    pub read: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationStoreData {
+    pub notifications: HashMap<String, Vec<Notification>>,
+}
+
+pub struct NotificationStore {
+    data: NotificationStoreData,
+    path: std::path::PathBuf,
+}
+
+impl NotificationStore {
+    #[inline]
+    pub fn new(base_path: &Path) -> Self {
+        let path = base_path.join("notifications.json");
+        let data = if path.exists() {
+            let content = std::fs::read_to_string(&path).unwrap_or_default();
+            serde_json::from_str(&content).unwrap_or_default()
+        } else {
+            NotificationStoreData::default()
+        };
+        Self { data, path }
+    }
+
+    pub fn notify(
+        &mut self,
+        user_id: &str,
+        kind: &str,
+        message: String,
+        data: serde_json::Value,
+    ) -> Result<Notification> {
+        let notification = Notification {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            kind: kind.to_string(),
+            message,
+            data,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            read: false,
+        };
+        let list = self
+            .data
+            .notifications
+            .entry(user_id.to_string())
+            .or_default();
+        list.push(notification.clone());
+        if list.len() > MAX_PER_USER {
+            let excess = list.len() - MAX_PER_USER;
+            list.drain(..excess);
+        }
+        self.save()?;
+        Ok(notification)
+    }
+
+    /// A user's notifications, newest first.
+    pub fn list_for_user(&self, user_id: &str, unread_only: bool) -> Vec<&Notification> {
+        self.data
+            .notifications
+            .get(user_id)
+            .map(|list| {
+                list.iter()
+                    .rev()
+                    .filter(|n| !unread_only || !n.read)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns false if the user has no such notification.
+    pub fn mark_read(&mut self, user_id: &str, id: &str) -> Result<bool> {
+        let Some(notification) = self
+            .data
+            .notifications
+            .get_mut(user_id)
+            .and_then(|list| list.iter_mut().find(|n| n.id == id))
+        else {
+            return Ok(false);
+        };
+        notification.read = true;
+        self.save()?;
+        Ok(true)
+    }
+
+    #[inline]
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string(&self.data)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,919 @@
+//! Runs the agent instances behind [`ContainerManager`] records.
+//!
+//! Each started container gets a supervision task that launches the instance
+//! (a Docker container or a sandboxed native `zeroclaw daemon`), streams its
+//! output into a per-container log buffer and a broadcast channel, and restarts
+//! it with backoff when it crashes. A maintenance loop probes health and disk
+//! usage and garbage-collects containers that have been stopped for a while.
+
+use super::container::{Container, ContainerBackend, ContainerManager, ContainerStatus};
+use super::TenantDb;
+use crate::config::{Config, DockerRuntimeConfig, SecurityConfig, TenantInstanceConfig};
+use crate::container::CeilingManager;
+use crate::relay::client::{CONTAINER_ID_ENV, RELAY_URL_ENV};
+use crate::relay::{AgentIdentity, RelayServer};
+use crate::runtime::docker::{DockerInstanceSpec, DockerRuntime, INSTANCE_CONFIG_MOUNT};
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::{broadcast, watch, RwLock};
+
+/// Log lines kept per container for `logs.tail`.
+const LOG_BUFFER_LINES: usize = 500;
+const MAX_RESTART_BACKOFF_SECS: u64 = 60;
+
+/// One line of instance output (or a supervisor notice).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerLogLine {
+    pub container_id: String,
+    /// `stdout`, `stderr` or `supervisor`
+    pub stream: &'static str,
+    pub line: String,
+    pub timestamp: String,
+}
+
+/// Supervisor settings, usually derived from the server's config.
+#[derive(Debug, Clone)]
+pub struct SupervisorSettings {
+    /// Instance directories live in `<data_dir>/containers/<id>`
+    pub data_dir: PathBuf,
+    pub docker: DockerRuntimeConfig,
+    /// Sandbox applied to native instances
+    pub security: SecurityConfig,
+    /// Program and arguments for native instances
+    pub native_command: Vec<String>,
+    /// Automatic restarts before a crashing container is marked `Error`
+    pub max_restarts: u32,
+    /// A run at least this long resets the restart count
+    pub stable_after: Duration,
+    pub health_interval: Duration,
+    /// How long stopped containers are kept before garbage collection
+    pub gc_after: Duration,
+    /// Base URL instances use to reach the relay (`ZEROCLAW_RELAY_URL`)
+    pub relay_url: Option<String>,
+}
+
+impl SupervisorSettings {
+    pub fn new(data_dir: &Path) -> Self {
+        let exe = std::env::current_exe()
+            .map_or_else(|_| "zeroclaw".to_string(), |p| p.display().to_string());
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            docker: DockerRuntimeConfig::default(),
+            security: SecurityConfig::default(),
+            native_command: vec![
+                exe,
+                "daemon".into(),
+                "--host".into(),
+                "127.0.0.1".into(),
+                "--port".into(),
+                "0".into(),
+            ],
+            max_restarts: 5,
+            stable_after: Duration::from_secs(10 * 60),
+            health_interval: Duration::from_secs(30),
+            gc_after: Duration::from_secs(24 * 60 * 60),
+            relay_url: None,
+        }
+    }
+}
+
+pub struct ContainerSupervisor {
+    store: Arc<RwLock<ContainerManager>>,
+    settings: SupervisorSettings,
+    /// Instance identities are registered here for inter-agent messaging
+    relay: Option<Arc<RelayServer>>,
+    /// Stop signals for running supervision tasks, by container id
+    running: Mutex<HashMap<String, watch::Sender<bool>>>,
+    logs: Arc<Mutex<HashMap<String, VecDeque<ContainerLogLine>>>>,
+    log_tx: broadcast::Sender<ContainerLogLine>,
+}
+
+impl ContainerSupervisor {
+    pub fn new(
+        store: Arc<RwLock<ContainerManager>>,
+        settings: SupervisorSettings,
+        relay: Option<Arc<RelayServer>>,
+    ) -> Arc<Self> {
+        let (log_tx, _) = broadcast::channel(1024);
+        Arc::new(Self {
+            store,
+            settings,
+            relay,
+            running: Mutex::new(HashMap::new()),
+            logs: Arc::new(Mutex::new(HashMap::new())),
+            log_tx,
+        })
+    }
+
+    /// Relaunch containers that were running before a restart and start the
+    /// health/GC loop.
+    pub async fn resume(self: &Arc<Self>) {
+        let active: Vec<String> = self
+            .store
+            .read()
+            .await
+            .list()
+            .iter()
+            .filter(|c| {
+                matches!(
+                    c.status,
+                    ContainerStatus::Starting | ContainerStatus::Running
+                )
+            })
+            .map(|c| c.id.clone())
+            .collect();
+        for id in active {
+            if let Err(e) = self.start(&id).await {
+                tracing::warn!("Failed to resume container {id}: {e}");
+            }
+        }
+
+        let supervisor = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(supervisor.settings.health_interval);
+            loop {
+                ticker.tick().await;
+                supervisor.probe_health().await;
+                supervisor.collect_garbage().await;
+            }
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ContainerLogLine> {
+        self.log_tx.subscribe()
+    }
+
+    /// The last `limit` log lines of a container, oldest first.
+    pub fn recent_logs(&self, id: &str, limit: usize) -> Vec<ContainerLogLine> {
+        self.logs.lock().get(id).map_or_else(Vec::new, |lines| {
+            let skip = lines.len().saturating_sub(limit);
+            lines.iter().skip(skip).cloned().collect()
+        })
+    }
+
+    pub fn is_running(&self, id: &str) -> bool {
+        self.running.lock().contains_key(id)
+    }
+
+    /// The instance's config directory. It holds the supervisor-written
+    /// `config.toml` and relay identity, which the instance may only read.
+    pub fn instance_dir(&self, id: &str) -> PathBuf {
+        self.settings.data_dir.join("containers").join(id)
+    }
+
+    /// The only directory the instance may write to.
+    pub fn instance_workspace(&self, id: &str) -> PathBuf {
+        self.instance_dir(id).join("workspace")
+    }
+
+    /// Launch and supervise a container. Starting a running container is a no-op.
+    pub async fn start(self: &Arc<Self>, id: &str) -> Result<()> {
+        if self.is_running(id) {
+            return Ok(());
+        }
+        self.store.write().await.start(id)?;
+        let (stop_tx, stop_rx) = watch::channel(false);
+        self.running.lock().insert(id.to_string(), stop_tx);
+
+        let supervisor = Arc::clone(self);
+        let id = id.to_string();
+        tokio::spawn(async move {
+            supervisor.supervise(&id, stop_rx).await;
+            supervisor.running.lock().remove(&id);
+        });
+        Ok(())
+    }
+
+    /// Stop a container and wait briefly for its instance to exit.
+    pub async fn stop(&self, id: &str) -> Result<()> {
+        let signal = self.running.lock().get(id).cloned();
+        let Some(signal) = signal else {
+            // Not supervised (e.g. already crashed): just record the state.
+            return self.store.write().await.update(id, |c| {
+                c.status = ContainerStatus::Stopped;
+                c.stopped_at
+                    .get_or_insert_with(|| chrono::Utc::now().to_rfc3339());
+            });
+        };
+        self.store.write().await.stop(id)?;
+        let _ = signal.send(true);
+        for _ in 0..50 {
+            if !self.is_running(id) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        Ok(())
+    }
+
+    /// Stop a container and delete its record, logs and instance directory.
+    pub async fn remove(&self, id: &str) -> Result<()> {
+        self.stop(id).await?;
+        let removed = self.store.write().await.remove(id)?;
+        self.logs.lock().remove(id);
+        if let Some(container) = removed {
+            self.cleanup_instance(&container).await;
+        }
+        Ok(())
+    }
+
+    async fn supervise(&self, id: &str, mut stop_rx: watch::Receiver<bool>) {
+        loop {
+            let Some(container) = self.store.read().await.get(id) else {
+                return;
+            };
+            let mut child = match self.spawn_instance(&container).await {
+                Ok(child) => child,
+                Err(e) => {
+                    self.log(id, "supervisor", format!("failed to launch: {e:#}"));
+                    self.set_exited(id, ContainerStatus::Error, Some(format!("{e:#}")))
+                        .await;
+                    return;
+                }
+            };
+            self.log(
+                id,
+                "supervisor",
+                format!("started {} instance", backend_name(container.backend)),
+            );
+            let _ = self.store.write().await.update(id, |c| {
+                c.status = ContainerStatus::Running;
+                c.healthy = Some(true);
+            });
+            let launched = std::time::Instant::now();
+
+            let status = tokio::select! {
+                status = child.wait() => Some(status),
+                _ = stop_rx.wait_for(|stop| *stop) => None,
+            };
+
+            let Some(status) = status else {
+                let _ = child.kill().await;
+                self.terminate_docker(&container).await;
+                self.log(id, "supervisor", "stopped".into());
+                self.set_exited(id, ContainerStatus::Stopped, None).await;
+                return;
+            };
+
+            let description = match status {
+                Ok(status) => format!("instance exited with {status}"),
+                Err(e) => format!("failed to wait for instance: {e}"),
+            };
+            self.log(id, "supervisor", description.clone());
+
+            // Only crash loops use up the restart budget.
+            if launched.elapsed() >= self.settings.stable_after {
+                let _ = self.store.write().await.update(id, |c| c.restart_count = 0);
+            }
+
+            let restarts = self
+                .store
+                .read()
+                .await
+                .get(id)
+                .map_or(u32::MAX, |c| c.restart_count);
+            if restarts >= self.settings.max_restarts {
+                self.log(id, "supervisor", "restart limit reached".into());
+                self.set_exited(id, ContainerStatus::Error, Some(description))
+                    .await;
+                return;
+            }
+
+            let backoff =
+                Duration::from_secs((1u64 << restarts.min(6)).min(MAX_RESTART_BACKOFF_SECS));
+            let _ = self.store.write().await.update(id, |c| {
+                c.status = ContainerStatus::Starting;
+                c.restart_count += 1;
+                c.last_error = Some(description);
+                c.healthy = Some(false);
+            });
+            self.log(
+                id,
+                "supervisor",
+                format!("restarting in {}s", backoff.as_secs()),
+            );
+            let stopped = tokio::select! {
+                () = tokio::time::sleep(backoff) => false,
+                _ = stop_rx.wait_for(|stop| *stop) => true,
+            };
+            if stopped {
+                self.set_exited(id, ContainerStatus::Stopped, None).await;
+                return;
+            }
+        }
+    }
+
+    async fn spawn_instance(&self, container: &Container) -> Result<tokio::process::Child> {
+        let dir = self.instance_dir(&container.id);
+        let workspace = self.instance_workspace(&container.id);
+        std::fs::create_dir_all(&workspace)
+            .with_context(|| format!("failed to create {}", workspace.display()))?;
+        self.write_tenant_config(container, &dir)?;
+        let relay_env = self.provision_relay(container, &dir).await?;
+
+        let mut command = match container.backend {
+            ContainerBackend::Docker => self.docker_command(container, &dir, relay_env)?,
+            ContainerBackend::Native => self.native_command(container, &dir, relay_env)?,
+        };
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = command.spawn().context("failed to spawn instance")?;
+        if let Some(stdout) = child.stdout.take() {
+            self.pump(&container.id, "stdout", stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            self.pump(&container.id, "stderr", stderr);
+        }
+        Ok(child)
+    }
+
+    /// Pin the owner's tenant identity and current capability ceiling into the
+    /// instance's `config.toml`. The instance cannot reach the tenant database,
+    /// so an escalation approved later takes effect on the next launch.
+    ///
+    /// The file lives outside the instance workspace and is replaced, never
+    /// edited in place, with a read-only copy; the sandbox and the Docker
+    /// mount only expose it read-only, so the instance cannot lift its own
+    /// ceiling.
+    fn write_tenant_config(&self, container: &Container, dir: &Path) -> Result<()> {
+        let ceiling = CeilingManager::new(TenantDb::open(&self.settings.data_dir)?)
+            .effective_ceiling(&container.user_id);
+        let tenant = TenantInstanceConfig {
+            tenant_id: container.tenant_id.clone(),
+            user_id: container.user_id.clone(),
+            ceiling,
+        };
+
+        let path = dir.join("config.toml");
+        // Keep the instance's own settings; only `[tenant]` is overwritten.
+        let mut table = match std::fs::read_to_string(&path) {
+            Ok(raw) => raw
+                .parse::<toml::Table>()
+                .with_context(|| format!("failed to parse {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                toml::Table::try_from(Config::default())?
+            }
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+        };
+        table.insert("tenant".into(), toml::Value::try_from(tenant)?);
+
+        let staged = dir.join("config.toml.tmp");
+        std::fs::write(&staged, toml::to_string(&table)?)
+            .with_context(|| format!("failed to write {}", staged.display()))?;
+        let mut permissions = std::fs::metadata(&staged)?.permissions();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            permissions.set_mode(0o444);
+        }
+        #[cfg(not(unix))]
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&staged, permissions)?;
+        std::fs::rename(&staged, &path)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Create the instance's relay identity in its config directory, register
+    /// the public key and return the environment the relay client needs.
+    async fn provision_relay(
+        &self,
+        container: &Container,
+        dir: &Path,
+    ) -> Result<Vec<(String, String)>> {
+        let Some(relay) = &self.relay else {
+            return Ok(Vec::new());
+        };
+        let identity =
+            AgentIdentity::load_or_create(dir).context("failed to create relay identity")?;
+        relay
+            .register_container(
+                container.id.clone(),
+                container.tenant_id.clone(),
+                identity.public().encode(),
+            )
+            .await?;
+        let mut env = vec![(CONTAINER_ID_ENV.to_string(), container.id.clone())];
+        if let Some(url) = &self.settings.relay_url {
+            env.push((RELAY_URL_ENV.to_string(), url.clone()));
+        }
+        Ok(env)
+    }
+
+    fn docker_command(
+        &self,
+        container: &Container,
+        dir: &Path,
+        extra_env: Vec<(String, String)>,
+    ) -> Result<tokio::process::Command> {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig {
+            mount_workspace: true,
+            ..self.settings.docker.clone()
+        });
+        let name = docker_name(&container.id);
+        let spec = DockerInstanceSpec {
+            name: &name,
+            image: Some(&container.image),
+            memory_limit_mb: container.resources.memory_limit,
+            cpu_limit: container.resources.cpu_limit.map(f64::from),
+            config_dir: Some(dir),
+            env: [
+                ("ZEROCLAW_WORKSPACE".to_string(), "/workspace".to_string()),
+                (
+                    "ZEROCLAW_CONFIG_DIR".to_string(),
+                    INSTANCE_CONFIG_MOUNT.to_string(),
+                ),
+            ]
+            .into_iter()
+            .chain(extra_env)
+            .collect(),
+        };
+        runtime.build_instance_command(&spec, &self.instance_workspace(&container.id))
+    }
+
+    fn native_command(
+        &self,
+        container: &Container,
+        dir: &Path,
+        extra_env: Vec<(String, String)>,
+    ) -> Result<tokio::process::Command> {
+        let (program, args) = self
+            .settings
+            .native_command
+            .split_first()
+            .context("native instance command is empty")?;
+        let workspace = self.instance_workspace(&container.id);
+        let mut command = std::process::Command::new(program);
+        command
+            .args(args)
+            .current_dir(&workspace)
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", &workspace)
+            .env("ZEROCLAW_CONFIG_DIR", dir)
+            .envs(extra_env);
+        apply_native_limits(&mut command, &container.id, &container.resources);
+
+        let sandbox = crate::security::create_sandbox_with_read_only(
+            &self.settings.security,
+            Some(&workspace),
+            &[dir.to_path_buf()],
+        );
+        sandbox
+            .wrap_command(&mut command)
+            .with_context(|| format!("failed to apply {} sandbox", sandbox.name()))?;
+        Ok(tokio::process::Command::from(command))
+    }
+
+    fn pump<R>(&self, id: &str, stream: &'static str, reader: R)
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let id = id.to_string();
+        let logs = Arc::clone(&self.logs);
+        let tx = self.log_tx.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let entry = ContainerLogLine {
+                    container_id: id.clone(),
+                    stream,
+                    line,
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                };
+                push_log(&mut logs.lock(), entry.clone());
+                let _ = tx.send(entry);
+            }
+        });
+    }
+
+    fn log(&self, id: &str, stream: &'static str, line: String) {
+        let entry = ContainerLogLine {
+            container_id: id.to_string(),
+            stream,
+            line,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        push_log(&mut self.logs.lock(), entry.clone());
+        let _ = self.log_tx.send(entry);
+    }
+
+    async fn set_exited(&self, id: &str, status: ContainerStatus, error: Option<String>) {
+        let _ = self.store.write().await.update(id, |c| {
+            c.status = status;
+            c.stopped_at = Some(chrono::Utc::now().to_rfc3339());
+            c.healthy = None;
+            if error.is_some() {
+                c.last_error = error;
+            }
+        });
+    }
+
+    async fn terminate_docker(&self, container: &Container) {
+        if container.backend == ContainerBackend::Docker {
+            let _ = tokio::process::Command::new("docker")
+                .args(["rm", "-f", &docker_name(&container.id)])
+                .output()
+                .await;
+        }
+    }
+
+    async fn cleanup_instance(&self, container: &Container) {
+        self.terminate_docker(container).await;
+        let dir = self.instance_dir(&container.id);
+        if dir.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&dir) {
+                tracing::warn!("Failed to remove {}: {e}", dir.display());
+            }
+        }
+    }
+
+    /// Probe running containers: the process or container must be alive and the
+    /// instance directory within its disk limit (exceeding it stops the container).
+    pub async fn probe_health(&self) {
+        let running: Vec<Container> = self
+            .store
+            .read()
+            .await
+            .list()
+            .iter()
+            .filter(|c| c.status == ContainerStatus::Running)
+            .cloned()
+            .collect();
+
+        for container in running {
+            let id = &container.id;
+            let mut healthy = self.is_running(id);
+            if healthy && container.backend == ContainerBackend::Docker {
+                healthy = docker_container_running(&docker_name(id)).await;
+            }
+
+            let used_mb = dir_size(&self.instance_dir(id)) / (1024 * 1024);
+            let over_disk = container
+                .resources
+                .disk_limit
+                .is_some_and(|limit| used_mb > limit);
+
+            let now = chrono::Utc::now().to_rfc3339();
+            let _ = self.store.write().await.update(id, |c| {
+                c.healthy = Some(healthy && !over_disk);
+                c.last_health_check = Some(now);
+            });
+
+            if over_disk {
+                self.log(
+                    id,
+                    "supervisor",
+                    format!("disk limit exceeded ({used_mb} MB), stopping"),
+                );
+                let _ = self.stop(id).await;
+                let _ = self.store.write().await.update(id, |c| {
+                    c.status = ContainerStatus::Error;
+                    c.last_error = Some("disk limit exceeded".into());
+                });
+            } else if !healthy {
+                self.log(id, "supervisor", "health probe failed".into());
+            }
+        }
+    }
+
+    /// Remove containers that have been stopped (or failed) for longer than `gc_after`.
+    pub async fn collect_garbage(&self) {
+        let cutoff = chrono::Utc::now()
+            - chrono::Duration::from_std(self.settings.gc_after).unwrap_or_default();
+        let expired: Vec<String> = self
+            .store
+            .read()
+            .await
+            .list()
+            .iter()
+            .filter(|c| {
+                matches!(c.status, ContainerStatus::Stopped | ContainerStatus::Error)
+                    && !self.is_running(&c.id)
+                    && c.stopped_at
+                        .as_deref()
+                        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                        .is_some_and(|t| t <= cutoff)
+            })
+            .map(|c| c.id.clone())
+            .collect();
+        for id in expired {
+            tracing::info!("Garbage-collecting stopped container {id}");
+            if let Err(e) = self.remove(&id).await {
+                tracing::warn!("Failed to garbage-collect container {id}: {e}");
+            }
+        }
+    }
+}
+
+fn push_log(logs: &mut HashMap<String, VecDeque<ContainerLogLine>>, entry: ContainerLogLine) {
+    let lines = logs.entry(entry.container_id.clone()).or_default();
+    if lines.len() >= LOG_BUFFER_LINES {
+        lines.pop_front();
+    }
+    lines.push_back(entry);
+}
+
+fn backend_name(backend: ContainerBackend) -> &'static str {
+    match backend {
+        ContainerBackend::Docker => "docker",
+        ContainerBackend::Native => "native",
+    }
+}
+
+fn docker_name(id: &str) -> String {
+    format!("zeroclaw-{id}")
+}
+
+async fn docker_container_running(name: &str) -> bool {
+    tokio::process::Command::new("docker")
+        .args(["inspect", "-f", "{{.State.Running}}", name])
+        .output()
+        .await
+        .is_ok_and(|out| {
+            out.status.success() && String::from_utf8_lossy(&out.stdout).trim() == "true"
+        })
+}
+
+/// Memory (data segment) and file size limits, plus CPU affinity for the core
+/// limit. Docker instances get the equivalent `--memory`/`--cpus` flags instead.
+///
+/// `RLIMIT_DATA` caps the heap and private mappings without counting the
+/// address space the runtime merely reserves, which `RLIMIT_AS` would. Each
+/// instance's cores start at an offset derived from its id, so instances with
+/// a core limit spread over the machine instead of all sharing the first cores.
+#[cfg(target_os = "linux")]
+fn apply_native_limits(
+    command: &mut std::process::Command,
+    id: &str,
+    resources: &super::container::ContainerResources,
+) {
+    use std::os::unix::process::CommandExt;
+
+    let memory_bytes = resources
+        .memory_limit
+        .filter(|mb| *mb > 0)
+        .map(|mb| mb.saturating_mul(1024 * 1024));
+    let file_bytes = resources
+        .disk_limit
+        .filter(|mb| *mb > 0)
+        .map(|mb| mb.saturating_mul(1024 * 1024));
+    let cpus = resources.cpu_limit.filter(|n| *n > 0).map(|n| {
+        // SAFETY: cpu_set_t is plain data; CPU_ZERO/CPU_SET only write within it.
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        for cpu in instance_cores(id, n as usize) {
+            unsafe { libc::CPU_SET(cpu, &mut set) };
+        }
+        set
+    });
+
+    // SAFETY: runs in the forked child before exec and only makes the
+    // async-signal-safe `setrlimit` and `sched_setaffinity` syscalls.
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(bytes) = memory_bytes {
+                let limit = libc::rlimit {
+                    rlim_cur: bytes,
+                    rlim_max: bytes,
+                };
+                if libc::setrlimit(libc::RLIMIT_DATA, &raw const limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            if let Some(bytes) = file_bytes {
+                let limit = libc::rlimit {
+                    rlim_cur: bytes,
+                    rlim_max: bytes,
+                };
+                if libc::setrlimit(libc::RLIMIT_FSIZE, &raw const limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            if let Some(set) = cpus.as_ref() {
+                if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), set) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn apply_native_limits(
+    _command: &mut std::process::Command,
+    _id: &str,
+    _resources: &super::container::ContainerResources,
+) {
+    tracing::debug!("Native container resource limits are only enforced on Linux");
+}
+
+/// `count` of the available cores, starting at an offset derived from the
+/// instance id and wrapping around.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn instance_cores(id: &str, count: usize) -> Vec<usize> {
+    use std::hash::{Hash, Hasher};
+
+    let available = std::thread::available_parallelism().map_or(1, usize::from);
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    id.hash(&mut hasher);
+    let start = usize::try_from(hasher.finish() % available as u64).unwrap_or_default();
+    (0..count.min(available))
+        .map(|i| (start + i) % available)
+        .collect()
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SandboxBackend;
+    use crate::tenant::ContainerResources;
+
+    fn supervisor(dir: &Path, script: &str) -> (Arc<ContainerSupervisor>, String) {
+        let store = Arc::new(RwLock::new(ContainerManager::new(
+            crate::tenant::TenantDb::open(dir).unwrap(),
+        )));
+        let mut settings = SupervisorSettings::new(dir);
+        settings.security.sandbox.backend = SandboxBackend::None;
+        settings.native_command = vec!["sh".into(), "-c".into(), script.into()];
+        settings.max_restarts = 1;
+        settings.gc_after = Duration::ZERO;
+        let id = store
+            .try_write()
+            .unwrap()
+            .create_with(
+                "tenant".into(),
+                "user".into(),
+                "agent".into(),
+                "unused".into(),
+                ContainerBackend::Native,
+                ContainerResources::default(),
+            )
+            .unwrap()
+            .id;
+        (ContainerSupervisor::new(store, settings, None), id)
+    }
+
+    async fn wait_until(mut done: impl FnMut() -> bool) {
+        for _ in 0..100 {
+            if done() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("condition not reached");
+    }
+
+    #[tokio::test]
+    async fn launch_pins_the_owner_ceiling_into_the_instance_config() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (supervisor, id) = supervisor(tmp.path(), "exit 0");
+        CeilingManager::new(crate::tenant::TenantDb::open(tmp.path()).unwrap())
+            .set_ceiling("user", crate::container::PermissionLevel::Read)
+            .unwrap();
+        let container = supervisor.store.read().await.get(&id).unwrap();
+        let dir = supervisor.instance_dir(&id);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        let mut existing = Config::default();
+        existing.default_temperature = 0.2;
+        std::fs::write(&path, toml::to_string(&existing).unwrap()).unwrap();
+
+        supervisor.write_tenant_config(&container, &dir).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().permissions().readonly());
+        assert!(!path.starts_with(supervisor.instance_workspace(&id)));
+        // Relaunching replaces the read-only file.
+        supervisor.write_tenant_config(&container, &dir).unwrap();
+
+        let mut config: Config = toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        config.config_path = path;
+        assert!((config.default_temperature - 0.2).abs() < f64::EPSILON);
+        let tenant = config.tenant.as_ref().unwrap();
+        assert_eq!(tenant.user_id, "user");
+        assert_eq!(tenant.ceiling, crate::container::PermissionLevel::Read);
+
+        let hooks = crate::hooks::HookRunner::from_config(&config)
+            .unwrap()
+            .unwrap();
+        let shell = hooks
+            .run_before_tool_call("shell".into(), serde_json::json!({}))
+            .await;
+        assert!(shell.is_cancel());
+    }
+
+    #[test]
+    fn instances_spread_over_the_available_cores() {
+        let available = std::thread::available_parallelism().map_or(1, usize::from);
+        let cores = instance_cores("container-a", 2);
+        assert_eq!(cores.len(), 2.min(available));
+        assert!(cores.iter().all(|core| *core < available));
+        assert_eq!(instance_cores("container-a", 2), cores);
+        if available > 1 {
+            let starts: std::collections::HashSet<_> = (0..16)
+                .map(|i| instance_cores(&format!("container-{i}"), 1)[0])
+                .collect();
+            assert!(starts.len() > 1, "every instance got core {starts:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn crashing_instance_is_restarted_then_marked_error() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (supervisor, id) = supervisor(tmp.path(), "echo hello from $HOME; exit 3");
+        supervisor.start(&id).await.unwrap();
+        wait_until(|| !supervisor.is_running(&id)).await;
+
+        let store = supervisor.store.read().await;
+        let container = store.get(&id).unwrap();
+        assert_eq!(container.status, ContainerStatus::Error);
+        assert_eq!(container.restart_count, 1);
+        assert!(container.last_error.as_deref().unwrap().contains('3'));
+
+        let logs = supervisor.recent_logs(&id, 100);
+        let stdout: Vec<_> = logs.iter().filter(|l| l.stream == "stdout").collect();
+        assert_eq!(stdout.len(), 2);
+        assert!(stdout[0]
+            .line
+            .ends_with(&*supervisor.instance_workspace(&id).to_string_lossy()));
+    }
+
+    #[tokio::test]
+    async fn stable_runs_reset_the_restart_count() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (mut supervisor, id) = supervisor(tmp.path(), "exit 3");
+        Arc::get_mut(&mut supervisor).unwrap().settings.stable_after = Duration::ZERO;
+        supervisor.start(&id).await.unwrap();
+        wait_until(|| {
+            supervisor
+                .recent_logs(&id, 100)
+                .iter()
+                .filter(|l| l.line.starts_with("started"))
+                .count()
+                >= 3
+        })
+        .await;
+        supervisor.stop(&id).await.unwrap();
+
+        let store = supervisor.store.read().await;
+        let container = store.get(&id).unwrap();
+        assert_eq!(container.status, ContainerStatus::Stopped);
+        assert!(container.restart_count <= 1);
+    }
+
+    #[tokio::test]
+    async fn stop_terminates_instance_and_gc_removes_it() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (supervisor, id) = supervisor(tmp.path(), "echo up; exec sleep 30");
+        let mut logs = supervisor.subscribe();
+        supervisor.start(&id).await.unwrap();
+        loop {
+            let line = logs.recv().await.unwrap();
+            if line.stream == "stdout" {
+                assert_eq!(line.line, "up");
+                break;
+            }
+        }
+        supervisor.probe_health().await;
+        assert_eq!(
+            supervisor.store.read().await.get(&id).unwrap().healthy,
+            Some(true)
+        );
+
+        supervisor.stop(&id).await.unwrap();
+        assert!(!supervisor.is_running(&id));
+        assert_eq!(
+            supervisor.store.read().await.get(&id).unwrap().status,
+            ContainerStatus::Stopped
+        );
+        assert!(supervisor.instance_dir(&id).exists());
+
+        supervisor.collect_garbage().await;
+        assert!(supervisor.store.read().await.get(&id).is_none());
+        assert!(!supervisor.instance_dir(&id).exists());
+        assert!(supervisor.recent_logs(&id, 10).is_empty());
+    }
+}
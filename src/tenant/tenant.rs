@@ -1,6 +1,7 @@
+use super::db::{next_sequence, or_default, query_doc, to_doc, TenantDb};
 use anyhow::Result;
+use rusqlite::{params, Transaction};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tenant {
@@ -11,6 +12,8 @@ pub struct Tenant {
     pub updated_at: String,
     pub settings: TenantSettings,
     pub status: TenantStatus,
+    #[serde(default)]
+    pub stats: TenantStats,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -35,13 +38,17 @@ impl Default for TenantStatus {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct TenantStats {
     pub user_count: u32,
     pub group_count: u32,
     pub container_count: u32,
     pub storage_used_bytes: u64,
+    pub chat_requests: u64,
+    pub last_activity: Option<String>,
 }
 
+/// The legacy `tenants.json` layout, read once by the JSON importer.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TenantStoreData {
     pub tenants: Vec<Tenant>,
@@ -49,21 +56,26 @@ pub struct TenantStoreData {
 }
 
 pub struct TenantStore {
-    data: TenantStoreData,
-    path: std::path::PathBuf,
+    db: TenantDb,
+}
+
+pub(crate) fn put(tx: &Transaction<'_>, tenant: &Tenant) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO tenants (id, status, created_at, data) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            tenant.id,
+            format!("{:?}", tenant.status),
+            tenant.created_at,
+            to_doc(tenant)?
+        ],
+    )?;
+    Ok(())
 }
 
 impl TenantStore {
     #[inline]
-    pub fn new(base_path: &Path) -> Self {
-        let path = base_path.join("tenants.json");
-        let data = if path.exists() {
-            let content = std::fs::read_to_string(&path).unwrap_or_default();
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            TenantStoreData::default()
-        };
-        Self { data, path }
+    pub fn new(db: TenantDb) -> Self {
+        Self { db }
     }
 
     #[inline]
@@ -77,35 +89,51 @@ impl TenantStore {
         domain: Option<String>,
         settings: TenantSettings,
     ) -> Result<Tenant> {
-        let now = chrono::Utc::now().to_rfc3339();
-        self.data.next_id += 1;
-        let tenant = Tenant {
-            id: format!("tenant_{}", self.data.next_id),
-            name,
-            domain,
-            created_at: now.clone(),
-            updated_at: now,
-            settings,
-            status: TenantStatus::Active,
-        };
-        self.data.tenants.push(tenant.clone());
-        self.save()?;
-        Ok(tenant)
+        self.db.write(|tx| {
+            let now = chrono::Utc::now().to_rfc3339();
+            let tenant = Tenant {
+                id: format!("tenant_{}", next_sequence(tx, "tenant")?),
+                name,
+                domain,
+                created_at: now.clone(),
+                updated_at: now,
+                settings,
+                status: TenantStatus::Active,
+                stats: TenantStats::default(),
+            };
+            put(tx, &tenant)?;
+            Ok(tenant)
+        })
     }
 
     #[inline]
-    pub fn get(&self, id: &str) -> Option<&Tenant> {
-        self.data.tenants.iter().find(|t| t.id == id)
+    pub fn get(&self, id: &str) -> Option<Tenant> {
+        or_default(
+            self.db
+                .query_doc("SELECT data FROM tenants WHERE id = ?1", params![id]),
+        )
     }
 
     #[inline]
-    pub fn get_mut(&mut self, id: &str) -> Option<&mut Tenant> {
-        self.data.tenants.iter_mut().find(|t| t.id == id)
+    pub fn list(&self) -> Vec<Tenant> {
+        or_default(
+            self.db
+                .query_docs("SELECT data FROM tenants ORDER BY created_at, id", []),
+        )
     }
 
-    #[inline]
-    pub fn list(&self) -> &[Tenant] {
-        &self.data.tenants
+    /// Apply `change` to a tenant and persist it in one transaction.
+    fn modify(&mut self, id: &str, change: impl FnOnce(&mut Tenant)) -> Result<bool> {
+        self.db.write(|tx| {
+            let Some(mut tenant) =
+                query_doc::<Tenant>(tx, "SELECT data FROM tenants WHERE id = ?1", params![id])?
+            else {
+                return Ok(false);
+            };
+            change(&mut tenant);
+            put(tx, &tenant)?;
+            Ok(true)
+        })
     }
 
     #[inline]
@@ -116,7 +144,7 @@ impl TenantStore {
         domain: Option<String>,
         settings: Option<TenantSettings>,
     ) -> Result<()> {
-        if let Some(tenant) = self.get_mut(id) {
+        self.modify(id, |tenant| {
             if let Some(n) = name {
                 tenant.name = n;
             }
@@ -127,27 +155,29 @@ impl TenantStore {
                 tenant.settings = s;
             }
             tenant.updated_at = chrono::Utc::now().to_rfc3339();
-            self.save()?;
-        }
+        })?;
         Ok(())
     }
 
-    #[inline]
-    pub fn delete(&mut self, id: &str) -> Result<()> {
-        if let Some(tenant) = self.get_mut(id) {
-            tenant.status = TenantStatus::Deleted;
-            tenant.updated_at = chrono::Utc::now().to_rfc3339();
-            self.save()?;
+    /// Count a chat request and refresh the tenant's storage usage.
+    pub fn record_chat(&mut self, id: &str, storage_used_bytes: u64) -> Result<()> {
+        let found = self.modify(id, |tenant| {
+            tenant.stats.chat_requests += 1;
+            tenant.stats.storage_used_bytes = storage_used_bytes;
+            tenant.stats.last_activity = Some(chrono::Utc::now().to_rfc3339());
+        })?;
+        if !found {
+            anyhow::bail!("Tenant not found")
         }
         Ok(())
     }
 
     #[inline]
-    fn save(&self) -> Result<()> {
-        if let Some(parent) = self.path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::write(&self.path, serde_json::to_string(&self.data)?)?;
+    pub fn delete(&mut self, id: &str) -> Result<()> {
+        self.modify(id, |tenant| {
+            tenant.status = TenantStatus::Deleted;
+            tenant.updated_at = chrono::Utc::now().to_rfc3339();
+        })?;
         Ok(())
     }
 }
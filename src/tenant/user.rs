@@ -1,7 +1,7 @@
+use super::db::{or_default, query_doc, to_doc, TenantDb};
 use anyhow::Result;
+use rusqlite::{params, OptionalExtension, Transaction};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
@@ -17,6 +17,22 @@ pub struct User {
     pub updated_at: String,
     pub last_login: Option<String>,
     pub status: UserStatus,
+    /// Consecutive failed logins since the last success or lockout
+    #[serde(default)]
+    pub failed_logins: u32,
+    /// RFC 3339 time until which logins are refused
+    #[serde(default)]
+    pub locked_until: Option<String>,
+}
+
+impl User {
+    /// Whether the account is currently locked out after repeated failed logins.
+    pub fn is_locked(&self) -> bool {
+        self.locked_until
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .is_some_and(|until| until > chrono::Utc::now())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -58,48 +74,64 @@ pub struct UserSession {
     pub user_agent: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordResetToken {
+    pub user_id: String,
+    /// SHA-256 of the token handed to the user
+    pub token_hash: String,
+    pub expires_at: String,
+}
+
+/// The legacy `users.json` layout, read once by the JSON importer.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UserStoreData {
     pub users: Vec<User>,
     pub sessions: Vec<UserSession>,
     pub allowlist: Vec<String>,
+    #[serde(default)]
+    pub reset_tokens: Vec<PasswordResetToken>,
 }
 
+#[derive(Clone)]
 pub struct UserStore {
-    data: UserStoreData,
-    path: std::path::PathBuf,
+    db: TenantDb,
+}
+
+pub(crate) fn put(tx: &Transaction<'_>, user: &User) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO users (id, tenant_id, email, created_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            user.id,
+            user.tenant_id,
+            user.email,
+            user.created_at,
+            to_doc(user)?
+        ],
+    )?;
+    Ok(())
 }
 
 impl UserStore {
     pub fn is_empty(&self) -> bool {
-        self.data.users.is_empty()
+        or_default(self.db.read(|conn| {
+            Ok(conn.query_row("SELECT COUNT(*) FROM users", [], |row| row.get::<_, i64>(0))?)
+        })) == 0
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &User> {
-        self.data.users.iter()
-    }
-}
-
-impl Clone for UserStore {
-    fn clone(&self) -> Self {
-        Self {
-            data: self.data.clone(),
-            path: self.path.clone(),
-        }
+    pub fn iter(&self) -> impl Iterator<Item = User> {
+        or_default(
+            self.db
+                .query_docs::<User>("SELECT data FROM users ORDER BY created_at, id", []),
+        )
+        .into_iter()
     }
 }
 
 impl UserStore {
     #[inline]
-    pub fn new(base_path: &Path) -> Self {
-        let path = base_path.join("users.json");
-        let data = if path.exists() {
-            let content = std::fs::read_to_string(&path).unwrap_or_default();
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            UserStoreData::default()
-        };
-        Self { data, path }
+    pub fn new(db: TenantDb) -> Self {
+        Self { db }
     }
 
     pub fn create(
@@ -123,70 +155,75 @@ impl UserStore {
             updated_at: now,
             last_login: None,
             status: UserStatus::Pending,
+            failed_logins: 0,
+            locked_until: None,
         };
-        self.data.users.push(user.clone());
-        self.save()?;
-        Ok(user)
+        self.create_with_user(user)
     }
 
     pub fn create_with_user(&mut self, user: User) -> Result<User> {
-        self.data.users.push(user.clone());
-        self.save()?;
+        self.db.write(|tx| put(tx, &user))?;
         Ok(user)
     }
 
     #[inline]
-    pub fn get(&self, id: &str) -> Option<&User> {
-        self.data.users.iter().find(|u| u.id == id)
+    pub fn get(&self, id: &str) -> Option<User> {
+        or_default(
+            self.db
+                .query_doc("SELECT data FROM users WHERE id = ?1", params![id]),
+        )
     }
 
     #[inline]
-    pub fn get_by_email(&self, email: &str) -> Option<&User> {
-        self.data.users.iter().find(|u| u.email == email)
+    pub fn get_by_email(&self, email: &str) -> Option<User> {
+        or_default(self.db.query_doc(
+            "SELECT data FROM users WHERE email = ?1 ORDER BY created_at LIMIT 1",
+            params![email],
+        ))
     }
 
     #[inline]
-    pub fn get_by_email_tenant(&self, email: &str, tenant_id: &str) -> Option<&User> {
-        self.data
-            .users
-            .iter()
-            .find(|u| u.email == email && u.tenant_id == tenant_id)
+    pub fn get_by_email_tenant(&self, email: &str, tenant_id: &str) -> Option<User> {
+        or_default(self.db.query_doc(
+            "SELECT data FROM users WHERE email = ?1 AND tenant_id = ?2 LIMIT 1",
+            params![email, tenant_id],
+        ))
     }
 
     #[inline]
-    pub fn list_by_tenant(&self, tenant_id: &str) -> Vec<&User> {
-        self.data
-            .users
-            .iter()
-            .filter(|u| u.tenant_id == tenant_id)
-            .collect()
+    pub fn list_by_tenant(&self, tenant_id: &str) -> Vec<User> {
+        or_default(self.db.query_docs(
+            "SELECT data FROM users WHERE tenant_id = ?1 ORDER BY created_at, id",
+            params![tenant_id],
+        ))
+    }
+
+    /// Apply `change` to a user and persist it in one transaction.
+    fn modify<T>(&mut self, id: &str, change: impl FnOnce(&mut User) -> T) -> Result<T> {
+        self.db.write(|tx| {
+            let Some(mut user) =
+                query_doc::<User>(tx, "SELECT data FROM users WHERE id = ?1", params![id])?
+            else {
+                anyhow::bail!("User not found")
+            };
+            let value = change(&mut user);
+            put(tx, &user)?;
+            Ok(value)
+        })
     }
 
     pub fn assign_tenant(&mut self, user_id: &str, tenant_id: String) -> Result<()> {
-        if let Some(user) = self.get_mut(user_id) {
+        self.modify(user_id, |user| {
             user.tenant_id = tenant_id;
             user.updated_at = chrono::Utc::now().to_rfc3339();
-            self.save()?;
-            Ok(())
-        } else {
-            anyhow::bail!("User not found")
-        }
+        })
     }
 
     pub fn update_role(&mut self, user_id: &str, role: UserRole) -> Result<()> {
-        if let Some(user) = self.get_mut(user_id) {
+        self.modify(user_id, |user| {
             user.role = role;
             user.updated_at = chrono::Utc::now().to_rfc3339();
-            self.save()?;
-            Ok(())
-        } else {
-            anyhow::bail!("User not found")
-        }
-    }
-
-    #[inline]
-    pub fn get_mut(&mut self, id: &str) -> Option<&mut User> {
-        self.data.users.iter_mut().find(|u| u.id == id)
+        })
     }
 
     pub fn update(
@@ -196,7 +233,10 @@ impl UserStore {
         role: Option<UserRole>,
         mfa_enabled: Option<bool>,
     ) -> Result<()> {
-        if let Some(user) = self.get_mut(id) {
+        if self.get(id).is_none() {
+            return Ok(());
+        }
+        self.modify(id, |user| {
             if let Some(n) = display_name {
                 user.display_name = Some(n);
             }
@@ -207,19 +247,119 @@ impl UserStore {
                 user.mfa_enabled = m;
             }
             user.updated_at = chrono::Utc::now().to_rfc3339();
-            self.save()?;
+        })
+    }
+
+    pub fn set_password_hash(&mut self, user_id: &str, password_hash: String) -> Result<()> {
+        self.modify(user_id, |user| {
+            user.password_hash = password_hash;
+            user.updated_at = chrono::Utc::now().to_rfc3339();
+        })
+    }
+
+    /// Count a failed login, locking the account once `max_failures` is reached.
+    /// Returns whether the account is now locked.
+    pub fn record_login_failure(
+        &mut self,
+        user_id: &str,
+        max_failures: u32,
+        lockout_secs: i64,
+    ) -> Result<bool> {
+        self.modify(user_id, |user| {
+            user.failed_logins += 1;
+            let locked = user.failed_logins >= max_failures;
+            if locked {
+                user.failed_logins = 0;
+                user.locked_until = Some(
+                    (chrono::Utc::now() + chrono::Duration::seconds(lockout_secs)).to_rfc3339(),
+                );
+            }
+            locked
+        })
+    }
+
+    pub fn record_login_success(&mut self, user_id: &str) -> Result<()> {
+        self.modify(user_id, |user| {
+            user.failed_logins = 0;
+            user.locked_until = None;
+            user.last_login = Some(chrono::Utc::now().to_rfc3339());
+        })
+    }
+
+    /// Store a TOTP secret (already encrypted) and whether it is confirmed.
+    pub fn set_mfa(&mut self, user_id: &str, secret: Option<String>, enabled: bool) -> Result<()> {
+        self.modify(user_id, |user| {
+            user.mfa_secret = secret;
+            user.mfa_enabled = enabled;
+            user.updated_at = chrono::Utc::now().to_rfc3339();
+        })
+    }
+
+    /// Register a reset token (by hash) for `user_id`, replacing earlier ones.
+    pub fn create_password_reset(
+        &mut self,
+        user_id: &str,
+        token_hash: String,
+        ttl_secs: i64,
+    ) -> Result<()> {
+        if self.get(user_id).is_none() {
+            anyhow::bail!("User not found");
         }
-        Ok(())
+        let now = chrono::Utc::now();
+        self.db.write(|tx| {
+            tx.execute(
+                "DELETE FROM reset_tokens WHERE user_id = ?1 OR expires_at <= ?2",
+                params![user_id, now.to_rfc3339()],
+            )?;
+            tx.execute(
+                "INSERT INTO reset_tokens (token_hash, user_id, expires_at) VALUES (?1, ?2, ?3)",
+                params![
+                    token_hash,
+                    user_id,
+                    (now + chrono::Duration::seconds(ttl_secs)).to_rfc3339()
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Consume a reset token, returning the user it belongs to if still valid.
+    pub fn consume_password_reset(&mut self, token_hash: &str) -> Result<Option<String>> {
+        let now = chrono::Utc::now();
+        self.db.write(|tx| {
+            let token: Option<(String, String)> = tx
+                .query_row(
+                    "SELECT user_id, expires_at FROM reset_tokens WHERE token_hash = ?1",
+                    params![token_hash],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            tx.execute(
+                "DELETE FROM reset_tokens WHERE token_hash = ?1",
+                params![token_hash],
+            )?;
+            Ok(token
+                .filter(|(_, expires_at)| !is_expired(expires_at, now))
+                .map(|(user_id, _)| user_id))
+        })
+    }
+
+    pub fn revoke_user_sessions(&mut self, user_id: &str) -> Result<()> {
+        self.db.write(|tx| {
+            tx.execute("DELETE FROM sessions WHERE user_id = ?1", params![user_id])?;
+            Ok(())
+        })
     }
 
     #[inline]
     pub fn delete(&mut self, id: &str) -> Result<()> {
-        if let Some(user) = self.get_mut(id) {
+        if self.get(id).is_none() {
+            return Ok(());
+        }
+        self.modify(id, |user| {
             user.status = UserStatus::Deleted;
             user.updated_at = chrono::Utc::now().to_rfc3339();
-            self.save()?;
-        }
-        Ok(())
+        })
     }
 
     pub fn create_session(&mut self, user_id: String, expires_in_secs: u64) -> Result<UserSession> {
@@ -233,61 +373,79 @@ impl UserStore {
             ip_address: None,
             user_agent: None,
         };
-        self.data.sessions.retain(|s| {
-            if let Ok(expires) = chrono::DateTime::parse_from_rfc3339(&s.expires_at) {
-                expires > chrono::Utc::now()
-            } else {
-                false
-            }
-        });
-        self.data.sessions.push(session.clone());
-        self.save()?;
+        self.db.write(|tx| {
+            // RFC 3339 UTC timestamps compare correctly as text.
+            tx.execute(
+                "DELETE FROM sessions WHERE expires_at <= ?1",
+                params![now.to_rfc3339()],
+            )?;
+            tx.execute(
+                "INSERT INTO sessions (token, user_id, expires_at, data) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    session.token,
+                    session.user_id,
+                    session.expires_at,
+                    to_doc(&session)?
+                ],
+            )?;
+            Ok(())
+        })?;
         Ok(session)
     }
 
-    pub fn validate_session(&self, token: &str) -> Option<&UserSession> {
+    pub fn validate_session(&self, token: &str) -> Option<UserSession> {
         let now = chrono::Utc::now();
-        self.data.sessions.iter().find(|s| {
-            s.token == token
-                && chrono::DateTime::parse_from_rfc3339(&s.expires_at)
-                    .map(|e| e > now)
-                    .unwrap_or(false)
-        })
+        or_default(
+            self.db.query_doc::<UserSession>(
+                "SELECT data FROM sessions WHERE token = ?1",
+                params![token],
+            ),
+        )
+        .filter(|s| !is_expired(&s.expires_at, now))
     }
 
     pub fn revoke_session(&mut self, token: &str) -> Result<()> {
-        self.data.sessions.retain(|s| s.token != token);
-        self.save()
+        self.db.write(|tx| {
+            tx.execute("DELETE FROM sessions WHERE token = ?1", params![token])?;
+            Ok(())
+        })
     }
 
     #[inline]
     pub fn allowlist_add(&mut self, email: String) -> Result<()> {
-        if !self.data.allowlist.contains(&email) {
-            self.data.allowlist.push(email);
-            self.save()?;
-        }
-        Ok(())
+        self.db.write(|tx| {
+            tx.execute(
+                "INSERT OR IGNORE INTO allowlist (email) VALUES (?1)",
+                params![email],
+            )?;
+            Ok(())
+        })
     }
 
     #[inline]
     pub fn allowlist_remove(&mut self, email: &str) -> Result<()> {
-        self.data.allowlist.retain(|e| e != email);
-        self.save()
+        self.db.write(|tx| {
+            tx.execute("DELETE FROM allowlist WHERE email = ?1", params![email])?;
+            Ok(())
+        })
     }
 
+    /// An empty allowlist allows everyone.
     #[inline]
     pub fn is_allowlisted(&self, email: &str) -> bool {
-        self.data.allowlist.is_empty() || self.data.allowlist.contains(&email.to_string())
+        or_default(self.db.read(|conn| {
+            let (total, matching): (i64, i64) = conn.query_row(
+                "SELECT COUNT(*), COUNT(*) FILTER (WHERE email = ?1) FROM allowlist",
+                params![email],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            Ok(total == 0 || matching > 0)
+        }))
     }
+}
 
-    #[inline]
-    fn save(&self) -> Result<()> {
-        if let Some(parent) = self.path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::write(&self.path, serde_json::to_string(&self.data)?)?;
-        Ok(())
-    }
+fn is_expired(expires_at: &str, now: chrono::DateTime<chrono::Utc>) -> bool {
+    chrono::DateTime::parse_from_rfc3339(expires_at).map_or(true, |e| e <= now)
 }
 
 fn generate_session_token() -> String {
@@ -1,12 +1,20 @@
-use anyhow::{bail, Context, Result};
+use super::db::{or_default, query_doc, to_doc, TenantDb};
+use anyhow::{Context, Result};
 use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
 use chacha20poly1305::{AeadCore, ChaCha20Poly1305, Key, Nonce};
+use rusqlite::{params, Transaction};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
 const ENCRYPTION_KEY_LEN: usize = 32;
+/// Per-install master key, hex encoded, in the server data directory.
+const MASTER_KEY_FILE: &str = ".vault_key";
+/// Key the server used before installs had their own; only read to migrate.
+const LEGACY_KEY: [u8; ENCRYPTION_KEY_LEN] = [0u8; ENCRYPTION_KEY_LEN];
 const NONCE_LEN: usize = 12;
+/// Metadata flag marking an entry as usable by other members of its tenant.
+pub const SHARED_METADATA_KEY: &str = "shared";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VaultEntry {
@@ -22,6 +30,18 @@ pub struct VaultEntry {
     pub expires_at: Option<String>,
 }
 
+impl VaultEntry {
+    /// Whether the owner shared this entry with the rest of the tenant.
+    pub fn is_shared(&self) -> bool {
+        self.metadata
+            .as_ref()
+            .and_then(|m| m.get(SHARED_METADATA_KEY))
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false)
+    }
+}
+
+/// The legacy `vault_store.json` layout, read once by the JSON importer.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct VaultStoreData {
     pub entries: Vec<VaultEntry>,
@@ -29,32 +49,92 @@ pub struct VaultStoreData {
 }
 
 pub struct VaultStore {
-    data: VaultStoreData,
-    path: std::path::PathBuf,
+    db: TenantDb,
     encryption_key: [u8; ENCRYPTION_KEY_LEN],
     cipher: ChaCha20Poly1305,
 }
 
+pub(crate) fn put(tx: &Transaction<'_>, entry: &VaultEntry) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO vault_entries (id, tenant_id, user_id, name, created_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            entry.id,
+            entry.tenant_id,
+            entry.user_id,
+            entry.name,
+            entry.created_at,
+            to_doc(entry)?
+        ],
+    )?;
+    Ok(())
+}
+
 impl VaultStore {
-    pub fn new(base_path: &Path, encryption_key: &[u8; ENCRYPTION_KEY_LEN]) -> Self {
-        let path = base_path.join("vault_store.json");
-        let data = if path.exists() {
-            let content = std::fs::read_to_string(&path).unwrap_or_default();
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            VaultStoreData::default()
-        };
+    pub fn new(db: TenantDb, encryption_key: &[u8; ENCRYPTION_KEY_LEN]) -> Self {
         let cipher = ChaCha20Poly1305::new(Key::from_slice(encryption_key));
         Self {
-            data,
-            path,
+            db,
             encryption_key: *encryption_key,
             cipher,
         }
     }
 
-    pub fn create(encryption_key: &[u8; ENCRYPTION_KEY_LEN]) -> Self {
-        Self::new(std::path::Path::new("."), encryption_key)
+    /// Open the vault under the install's master key, generating the key file
+    /// (mode 0600) on first start. A key file that exists but cannot be read
+    /// is an error, never a reason to fall back to another key. Entries
+    /// written under the old all-zero key are re-encrypted when the key is
+    /// first created.
+    pub fn open(db: TenantDb, data_dir: &Path) -> Result<Self> {
+        let path = data_dir.join(MASTER_KEY_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(raw) => {
+                let key: [u8; ENCRYPTION_KEY_LEN] = hex::decode(raw.trim())
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .with_context(|| format!("Vault key file {} is corrupt", path.display()))?;
+                Ok(Self::new(db, &key))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let key: [u8; ENCRYPTION_KEY_LEN] =
+                    ChaCha20Poly1305::generate_key(&mut OsRng).into();
+                write_key_file(&path, &key)?;
+                let mut vault = Self::new(db, &key);
+                vault.migrate_from(&LEGACY_KEY)?;
+                Ok(vault)
+            }
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read vault key file {}", path.display()))
+            }
+        }
+    }
+
+    /// Re-encrypt every entry that decrypts under `old_key` with the current key.
+    fn migrate_from(&mut self, old_key: &[u8; ENCRYPTION_KEY_LEN]) -> Result<()> {
+        let old = Self::new(self.db.clone(), old_key);
+        let entries: Vec<VaultEntry> = self
+            .db
+            .query_docs("SELECT data FROM vault_entries", params![])?;
+        let mut migrated = Vec::new();
+        for mut entry in entries {
+            if let Ok(plaintext) = old.decrypt(&entry.encrypted_value) {
+                entry.encrypted_value = self.encrypt(&plaintext)?;
+                migrated.push(entry);
+            }
+        }
+        if migrated.is_empty() {
+            return Ok(());
+        }
+        tracing::info!(
+            "Re-encrypted {} vault entries under the new key",
+            migrated.len()
+        );
+        self.db.write(|tx| {
+            for entry in &migrated {
+                put(tx, entry)?;
+            }
+            Ok(())
+        })
     }
 
     pub fn set_encryption_key(&mut self, key: [u8; ENCRYPTION_KEY_LEN]) {
@@ -75,59 +155,60 @@ impl VaultStore {
         let encrypted_value = self.encrypt(plaintext)?;
         let now = chrono::Utc::now().to_rfc3339();
 
-        if let Some(existing) = self
-            .data
-            .entries
-            .iter_mut()
-            .find(|e| e.tenant_id == tenant_id && e.user_id == user_id && e.name == name)
-        {
-            existing.encrypted_value = encrypted_value;
-            existing.metadata = metadata;
-            existing.updated_at = now.clone();
-            existing.expires_at = expires_at;
-            let result = existing.clone();
-            self.save()?;
-            return Ok(result);
-        }
-
-        let entry = VaultEntry {
-            id: uuid::Uuid::new_v4().to_string(),
-            tenant_id,
-            user_id,
-            name,
-            credential_type,
-            encrypted_value,
-            metadata,
-            created_at: now.clone(),
-            updated_at: now,
-            expires_at,
-        };
-        self.data.entries.push(entry.clone());
-        self.save()?;
-        Ok(entry)
+        self.db.write(|tx| {
+            let existing = query_doc::<VaultEntry>(
+                tx,
+                "SELECT data FROM vault_entries WHERE tenant_id = ?1 AND user_id = ?2 AND name = ?3",
+                params![tenant_id, user_id, name],
+            )?;
+            let entry = match existing {
+                Some(mut existing) => {
+                    existing.encrypted_value = encrypted_value;
+                    existing.metadata = metadata;
+                    existing.updated_at = now;
+                    existing.expires_at = expires_at;
+                    existing
+                }
+                None => VaultEntry {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    tenant_id,
+                    user_id,
+                    name,
+                    credential_type,
+                    encrypted_value,
+                    metadata,
+                    created_at: now.clone(),
+                    updated_at: now,
+                    expires_at,
+                },
+            };
+            put(tx, &entry)?;
+            Ok(entry)
+        })
     }
 
     #[inline]
-    pub fn get(&self, id: &str) -> Option<&VaultEntry> {
-        self.data.entries.iter().find(|e| e.id == id)
+    pub fn get(&self, id: &str) -> Option<VaultEntry> {
+        or_default(
+            self.db
+                .query_doc("SELECT data FROM vault_entries WHERE id = ?1", params![id]),
+        )
     }
 
     #[inline]
-    pub fn get_by_user(&self, user_id: &str) -> Vec<&VaultEntry> {
-        self.data
-            .entries
-            .iter()
-            .filter(|e| e.user_id == user_id)
-            .collect()
+    pub fn get_by_user(&self, user_id: &str) -> Vec<VaultEntry> {
+        or_default(self.db.query_docs(
+            "SELECT data FROM vault_entries WHERE user_id = ?1 ORDER BY created_at, id",
+            params![user_id],
+        ))
     }
 
     #[inline]
-    pub fn get_by_tenant(&self, tenant_id: &str) -> Vec<&VaultEntry> {
-        self.data
-            .entries
-            .iter()
-            .filter(|e| e.tenant_id == tenant_id)
-            .collect()
+    pub fn get_by_tenant(&self, tenant_id: &str) -> Vec<VaultEntry> {
+        or_default(self.db.query_docs(
+            "SELECT data FROM vault_entries WHERE tenant_id = ?1 ORDER BY created_at, id",
+            params![tenant_id],
+        ))
     }
 
     pub fn get_decrypted(&self, id: &str) -> Result<String> {
@@ -135,14 +216,50 @@ impl VaultStore {
         self.decrypt(&entry.encrypted_value)
     }
 
+    /// Decrypted provider key for `provider`, preferring the user's own entry over
+    /// one another member of the tenant explicitly shared. Only `api_key` and
+    /// `provider_key` entries named after the provider are considered.
+    pub fn provider_key(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        provider: &str,
+    ) -> Result<Option<String>> {
+        let now = chrono::Utc::now();
+        let mut candidates: Vec<VaultEntry> = self
+            .get_by_tenant(tenant_id)
+            .into_iter()
+            .filter(|e| {
+                (e.user_id == user_id || e.is_shared())
+                    && e.name.eq_ignore_ascii_case(provider)
+                    && matches!(e.credential_type.as_str(), "api_key" | "provider_key")
+                    && e.expires_at.as_deref().map_or(true, |t| {
+                        chrono::DateTime::parse_from_rfc3339(t).map_or(false, |t| t > now)
+                    })
+            })
+            .collect();
+        candidates.sort_by_key(|e| e.user_id != user_id);
+        candidates
+            .first()
+            .map(|e| self.decrypt(&e.encrypted_value))
+            .transpose()
+    }
+
     pub fn delete(&mut self, id: &str) -> Result<()> {
-        self.data.entries.retain(|e| e.id != id);
-        self.save()
+        self.db.write(|tx| {
+            tx.execute("DELETE FROM vault_entries WHERE id = ?1", params![id])?;
+            Ok(())
+        })
     }
 
     pub fn delete_by_user(&mut self, user_id: &str) -> Result<()> {
-        self.data.entries.retain(|e| e.user_id != user_id);
-        self.save()
+        self.db.write(|tx| {
+            tx.execute(
+                "DELETE FROM vault_entries WHERE user_id = ?1",
+                params![user_id],
+            )?;
+            Ok(())
+        })
     }
 
     #[inline]
@@ -177,15 +294,22 @@ impl VaultStore {
 
         String::from_utf8(plaintext).context("Decrypted value is not valid UTF-8")
     }
+}
 
-    #[inline]
-    fn save(&self) -> Result<()> {
-        if let Some(parent) = self.path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::write(&self.path, serde_json::to_string(&self.data)?)?;
-        Ok(())
+fn write_key_file(path: &Path, key: &[u8; ENCRYPTION_KEY_LEN]) -> Result<()> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
     }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create vault key file {}", path.display()))?;
+    file.write_all(hex::encode(key).as_bytes())
+        .with_context(|| format!("Failed to write vault key file {}", path.display()))
 }
 
 pub fn derive_key(password: &str, salt: &[u8]) -> [u8; ENCRYPTION_KEY_LEN] {
@@ -196,3 +320,93 @@ pub fn derive_key(password: &str, salt: &[u8]) -> [u8; ENCRYPTION_KEY_LEN] {
         .expect("Key derivation failed");
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_key(vault: &mut VaultStore, user: &str, value: &str, shared: bool) {
+        let metadata = shared.then(|| {
+            HashMap::from([(
+                SHARED_METADATA_KEY.to_string(),
+                serde_json::Value::Bool(true),
+            )])
+        });
+        vault
+            .store(
+                "t1".into(),
+                user.into(),
+                "openrouter".into(),
+                "api_key".into(),
+                value,
+                metadata,
+                None,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn provider_key_uses_own_or_explicitly_shared_entries() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut vault = VaultStore::new(TenantDb::open(tmp.path()).unwrap(), &[7u8; 32]);
+
+        store_key(&mut vault, "bob", "bob-private", false);
+        assert_eq!(
+            vault.provider_key("t1", "alice", "openrouter").unwrap(),
+            None
+        );
+
+        store_key(&mut vault, "carol", "carol-shared", true);
+        assert_eq!(
+            vault.provider_key("t1", "alice", "OpenRouter").unwrap(),
+            Some("carol-shared".into())
+        );
+        assert_eq!(
+            vault.provider_key("t2", "alice", "openrouter").unwrap(),
+            None
+        );
+
+        store_key(&mut vault, "alice", "alice-own", false);
+        assert_eq!(
+            vault.provider_key("t1", "alice", "openrouter").unwrap(),
+            Some("alice-own".into())
+        );
+    }
+
+    #[test]
+    fn open_uses_a_private_per_install_key() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = TenantDb::open(tmp.path()).unwrap();
+
+        // An entry written under the old all-zero key survives the switch.
+        let mut legacy = VaultStore::new(db.clone(), &LEGACY_KEY);
+        store_key(&mut legacy, "alice", "legacy-secret", false);
+
+        let mut vault = VaultStore::open(db.clone(), tmp.path()).unwrap();
+        assert_ne!(vault.encryption_key, LEGACY_KEY);
+        assert_eq!(
+            vault.provider_key("t1", "alice", "openrouter").unwrap(),
+            Some("legacy-secret".into())
+        );
+        assert!(legacy.provider_key("t1", "alice", "openrouter").is_err());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(tmp.path().join(MASTER_KEY_FILE))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        store_key(&mut vault, "bob", "bob-secret", false);
+        let reopened = VaultStore::open(db.clone(), tmp.path()).unwrap();
+        assert_eq!(
+            reopened.provider_key("t1", "bob", "openrouter").unwrap(),
+            Some("bob-secret".into())
+        );
+
+        std::fs::write(tmp.path().join(MASTER_KEY_FILE), "not a key").unwrap();
+        assert!(VaultStore::open(db, tmp.path()).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const HISTORY_FILE: &str = "chat_history.jsonl";
+
+/// One persisted chat turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatHistoryEntry {
+    pub role: String,
+    pub content: String,
+    pub timestamp: String,
+}
+
+/// On-disk layout for a tenant:
+///
+/// ```text
+/// <data_dir>/tenants/<tenant_id>/config.toml       tenant agent config (not agent-writable)
+/// <data_dir>/tenants/<tenant_id>/workspace/         shared agent workspace
+/// <data_dir>/tenants/<tenant_id>/users/<user_id>/   per-user memory and chat history
+/// ```
+#[derive(Debug, Clone)]
+pub struct TenantWorkspace {
+    root: PathBuf,
+}
+
+impl TenantWorkspace {
+    pub fn new(data_dir: &Path, tenant_id: &str) -> Result<Self> {
+        validate_component(tenant_id)?;
+        let root = data_dir.join("tenants").join(tenant_id);
+        std::fs::create_dir_all(root.join("workspace"))
+            .with_context(|| format!("failed to create tenant workspace {}", root.display()))?;
+        Ok(Self { root })
+    }
+
+    pub fn workspace_dir(&self) -> PathBuf {
+        self.root.join("workspace")
+    }
+
+    /// The tenant's own agent config. It sits next to the workspace, so the
+    /// agent's file tools cannot rewrite it.
+    pub fn config_path(&self) -> PathBuf {
+        self.root.join("config.toml")
+    }
+
+    /// Private directory for `user_id`; memory lives in `<dir>/memory`.
+    pub fn user_dir(&self, user_id: &str) -> Result<PathBuf> {
+        validate_component(user_id)?;
+        let dir = self.root.join("users").join(user_id);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create user directory {}", dir.display()))?;
+        Ok(dir)
+    }
+
+    pub fn append_history(&self, user_id: &str, entries: &[ChatHistoryEntry]) -> Result<()> {
+        let path = self.user_dir(user_id)?.join(HISTORY_FILE);
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        for entry in entries {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+        Ok(())
+    }
+
+    /// The most recent `limit` history entries, oldest first.
+    pub fn load_history(&self, user_id: &str, limit: usize) -> Result<Vec<ChatHistoryEntry>> {
+        let path = self.user_dir(user_id)?.join(HISTORY_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let entries: Vec<ChatHistoryEntry> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        let skip = entries.len().saturating_sub(limit);
+        Ok(entries.into_iter().skip(skip).collect())
+    }
+
+    /// Total size of everything stored for the tenant.
+    pub fn storage_used_bytes(&self) -> u64 {
+        dir_size(&self.root)
+    }
+}
+
+fn validate_component(id: &str) -> Result<()> {
+    if id.is_empty()
+        || id == "."
+        || id == ".."
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        anyhow::bail!("invalid tenant or user id: {id:?}");
+    }
+    Ok(())
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_is_per_user_and_bounded() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let ws = TenantWorkspace::new(tmp.path(), "tenant_1").unwrap();
+        let entry = |content: &str| ChatHistoryEntry {
+            role: "user".into(),
+            content: content.into(),
+            timestamp: "2026-01-01T00:00:00Z".into(),
+        };
+        ws.append_history("alice", &[entry("a1"), entry("a2"), entry("a3")])
+            .unwrap();
+        ws.append_history("bob", &[entry("b1")]).unwrap();
+
+        let alice = ws.load_history("alice", 2).unwrap();
+        assert_eq!(alice.len(), 2);
+        assert_eq!(alice[0].content, "a2");
+        assert_eq!(ws.load_history("bob", 10).unwrap().len(), 1);
+        assert!(ws.storage_used_bytes() > 0);
+        assert!(ws.workspace_dir().ends_with("tenants/tenant_1/workspace"));
+    }
+
+    #[test]
+    fn rejects_path_traversal_ids() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        assert!(TenantWorkspace::new(tmp.path(), "../escape").is_err());
+        let ws = TenantWorkspace::new(tmp.path(), "default").unwrap();
+        assert!(ws.user_dir("a/b").is_err());
+        assert!(ws.user_dir("..").is_err());
+    }
+}
@@ -33,6 +33,8 @@ pub struct DelegateTool {
     parent_tools: Arc<Vec<Arc<dyn Tool>>>,
     /// Inherited multimodal handling config for sub-agent loops.
     multimodal_config: crate::config::MultimodalConfig,
+    /// Parent hook runner, so tool rules also gate sub-agent tool calls.
+    hooks: Option<Arc<crate::hooks::HookRunner>>,
 }
 
 impl DelegateTool {
@@ -63,6 +65,7 @@ impl DelegateTool {
             depth: 0,
             parent_tools: Arc::new(Vec::new()),
             multimodal_config: crate::config::MultimodalConfig::default(),
+            hooks: None,
         }
     }
 
@@ -99,6 +102,7 @@ impl DelegateTool {
             depth,
             parent_tools: Arc::new(Vec::new()),
             multimodal_config: crate::config::MultimodalConfig::default(),
+            hooks: None,
         }
     }
 
@@ -113,6 +117,12 @@ impl DelegateTool {
         self.multimodal_config = config;
         self
     }
+
+    /// Attach the parent hook runner for sub-agent tool loops.
+    pub fn with_hooks(mut self, hooks: Option<Arc<crate::hooks::HookRunner>>) -> Self {
+        self.hooks = hooks;
+        self
+    }
 }
 
 #[async_trait]
@@ -405,11 +415,12 @@ impl DelegateTool {
                 true,
                 None,
                 "delegate",
+                None,
                 &self.multimodal_config,
                 agent_config.max_iterations,
                 None,
                 None,
-                None,
+                self.hooks.as_deref(),
                 &[] as &[String],
             ),
         )
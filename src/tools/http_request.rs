@@ -299,7 +299,7 @@ impl Tool for HttpRequestTool {
 
 // Helper functions similar to browser_open.rs
 
-fn normalize_allowed_domains(domains: Vec<String>) -> Vec<String> {
+pub(crate) fn normalize_allowed_domains(domains: Vec<String>) -> Vec<String> {
     let mut normalized = domains
         .into_iter()
         .filter_map(|d| normalize_domain(&d))
@@ -338,7 +338,7 @@ fn normalize_domain(raw: &str) -> Option<String> {
     Some(d)
 }
 
-fn extract_host(url: &str) -> anyhow::Result<String> {
+pub(crate) fn extract_host(url: &str) -> anyhow::Result<String> {
     let rest = url
         .strip_prefix("http://")
         .or_else(|| url.strip_prefix("https://"))
@@ -376,7 +376,7 @@ fn extract_host(url: &str) -> anyhow::Result<String> {
     Ok(host)
 }
 
-fn host_matches_allowlist(host: &str, allowed_domains: &[String]) -> bool {
+pub(crate) fn host_matches_allowlist(host: &str, allowed_domains: &[String]) -> bool {
     if allowed_domains.iter().any(|domain| domain == "*") {
         return true;
     }
@@ -389,7 +389,7 @@ fn host_matches_allowlist(host: &str, allowed_domains: &[String]) -> bool {
     })
 }
 
-fn is_private_or_local_host(host: &str) -> bool {
+pub(crate) fn is_private_or_local_host(host: &str) -> bool {
     // Strip brackets from IPv6 addresses like [::1]
     let bare = host
         .strip_prefix('[')
@@ -432,6 +432,58 @@ fn is_non_global_v4(v4: std::net::Ipv4Addr) -> bool {
         || (a == 198 && (18..=19).contains(&b)) // Benchmarking (198.18.0.0/15)
 }
 
+/// Resolve the host of `url` and refuse it when it is, or resolves to, a
+/// loopback, link-local, private or otherwise non-global address.
+///
+/// The checked addresses are returned so the caller can pin the connection to
+/// them; a second lookup could be answered differently by a rebinding server.
+pub(crate) async fn resolve_public_addrs(
+    url: &reqwest::Url,
+) -> anyhow::Result<Vec<std::net::SocketAddr>> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow::anyhow!("URL has no host: {url}"))?;
+    if is_private_or_local_host(host) {
+        anyhow::bail!("Blocked local/private host: {host}");
+    }
+    let bare = host
+        .strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host);
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((bare, port))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to resolve {host}: {e}"))?
+        .collect();
+    if addrs.is_empty() {
+        anyhow::bail!("Failed to resolve {host}: no addresses");
+    }
+    if let Some(addr) = addrs
+        .iter()
+        .find(|addr| is_private_or_local_host(&addr.ip().to_string()))
+    {
+        anyhow::bail!(
+            "Blocked host {host}: resolves to local/private address {}",
+            addr.ip()
+        );
+    }
+    Ok(addrs)
+}
+
+/// Client for one request to `url`: the host must resolve to public addresses
+/// only, the connection is pinned to them and redirects are not followed.
+pub(crate) async fn public_client(
+    url: &reqwest::Url,
+    builder: reqwest::ClientBuilder,
+) -> anyhow::Result<reqwest::Client> {
+    let addrs = resolve_public_addrs(url).await?;
+    let host = url.host_str().unwrap_or_default();
+    Ok(builder
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve_to_addrs(host, &addrs)
+        .build()?)
+}
+
 /// Returns true if the IPv6 address is not globally routable.
 fn is_non_global_v6(v6: std::net::Ipv6Addr) -> bool {
     let segs = v6.segments();
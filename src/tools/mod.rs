@@ -7,7 +7,8 @@
 //!
 //! Tools are assembled into registries by [`default_tools`] (shell, file read/write)
 //! and [`all_tools`] (full set including memory, browser, cron, HTTP, delegation,
-//! and optional integrations). Security policy enforcement is injected via
+//! and optional integrations). Tools declared by installed skills are added as
+//! [`skill_tool::SkillToolAdapter`]s. Security policy enforcement is injected via
 //! [`SecurityPolicy`](crate::security::SecurityPolicy) at construction time.
 //!
 //! # Extension
@@ -47,8 +48,11 @@ pub mod pushover;
 pub mod schedule;
 pub mod schema;
 pub mod screenshot;
+pub mod send_to_agent;
 pub mod shell;
+pub mod skill_tool;
 pub mod traits;
+pub mod vault_get;
 pub mod web_search_tool;
 
 pub use browser::{BrowserTool, ComputerUseConfig};
@@ -83,10 +87,12 @@ pub use schedule::ScheduleTool;
 #[allow(unused_imports)]
 pub use schema::{CleaningStrategy, SchemaCleanr};
 pub use screenshot::ScreenshotTool;
+pub use send_to_agent::{AgentInboxTool, SendToAgentTool};
 pub use shell::ShellTool;
 pub use traits::Tool;
 #[allow(unused_imports)]
 pub use traits::{ToolResult, ToolSpec};
+pub use vault_get::VaultGetTool;
 pub use web_search_tool::WebSearchTool;
 
 use crate::config::{Config, DelegateAgentConfig};
@@ -94,7 +100,7 @@ use crate::memory::Memory;
 use crate::runtime::{NativeRuntime, RuntimeAdapter};
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 #[derive(Clone)]
@@ -165,6 +171,7 @@ pub fn all_tools(
     agents: &HashMap<String, DelegateAgentConfig>,
     fallback_api_key: Option<&str>,
     root_config: &crate::config::Config,
+    hooks: Option<Arc<crate::hooks::HookRunner>>,
 ) -> Vec<Box<dyn Tool>> {
     all_tools_with_runtime(
         config,
@@ -179,6 +186,7 @@ pub fn all_tools(
         agents,
         fallback_api_key,
         root_config,
+        hooks,
     )
 }
 
@@ -197,9 +205,12 @@ pub fn all_tools_with_runtime(
     agents: &HashMap<String, DelegateAgentConfig>,
     fallback_api_key: Option<&str>,
     root_config: &crate::config::Config,
+    hooks: Option<Arc<crate::hooks::HookRunner>>,
 ) -> Vec<Box<dyn Tool>> {
+    let shell_sandbox =
+        crate::security::create_shell_sandbox(&root_config.security, Some(workspace_dir));
     let mut tool_arcs: Vec<Arc<dyn Tool>> = vec![
-        Arc::new(ShellTool::new(security.clone(), runtime)),
+        Arc::new(ShellTool::new(security.clone(), runtime.clone()).with_sandbox(shell_sandbox)),
         Arc::new(FileReadTool::new(security.clone())),
         Arc::new(FileWriteTool::new(security.clone())),
         Arc::new(FileEditTool::new(security.clone())),
@@ -293,6 +304,48 @@ pub fn all_tools_with_runtime(
         }
     }
 
+    // Inter-agent messaging for instances launched by the container supervisor
+    if let Some(relay) = root_config
+        .config_path
+        .parent()
+        .and_then(crate::relay::RelayClient::from_env)
+    {
+        let relay = Arc::new(relay);
+        tool_arcs.push(Arc::new(SendToAgentTool::new(
+            security.clone(),
+            relay.clone(),
+        )));
+        tool_arcs.push(Arc::new(AgentInboxTool::new(relay)));
+    }
+
+    if let Some(context) = &root_config.tenant_context {
+        tool_arcs.push(Arc::new(VaultGetTool::new(context.clone())));
+    }
+
+    // Robot kit tools (`[robot]`)
+    #[cfg(feature = "robot-kit")]
+    match crate::robot::robot_tools(root_config) {
+        Ok(robot_tools) => tool_arcs.extend(robot_tools),
+        Err(e) => tracing::warn!("Robot tools unavailable: {e:#}"),
+    }
+    #[cfg(not(feature = "robot-kit"))]
+    if root_config.robot.enabled {
+        tracing::warn!(
+            "[robot] is enabled but this build was compiled without `robot-kit`; rebuild with `--features robot-kit`"
+        );
+    }
+
+    // Skill-declared tools (`[[tools]]` in SKILL.toml) become native tools
+    let skills = crate::skills::load_skills_with_config(workspace_dir, root_config);
+    let reserved: HashSet<String> = tool_arcs.iter().map(|t| t.name().to_string()).collect();
+    tool_arcs.extend(skill_tool::skill_tools(
+        &skills,
+        security,
+        &runtime,
+        root_config,
+        &reserved,
+    ));
+
     // Add delegation tool when agents are configured
     if !agents.is_empty() {
         let delegate_agents: HashMap<String, DelegateAgentConfig> = agents
@@ -319,7 +372,8 @@ pub fn all_tools_with_runtime(
             },
         )
         .with_parent_tools(parent_tools)
-        .with_multimodal_config(root_config.multimodal.clone());
+        .with_multimodal_config(root_config.multimodal.clone())
+        .with_hooks(hooks);
         tool_arcs.push(Arc::new(delegate_tool));
     }
 
@@ -379,6 +433,7 @@ mod tests {
             &HashMap::new(),
             None,
             &cfg,
+            None,
         );
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(!names.contains(&"browser_open"));
@@ -420,6 +475,7 @@ mod tests {
             &HashMap::new(),
             None,
             &cfg,
+            None,
         );
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(names.contains(&"browser_open"));
@@ -569,6 +625,7 @@ mod tests {
             &agents,
             Some("delegate-test-credential"),
             &cfg,
+            None,
         );
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(names.contains(&"delegate"));
@@ -601,6 +658,7 @@ mod tests {
             &HashMap::new(),
             None,
             &cfg,
+            None,
         );
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(!names.contains(&"delegate"));
@@ -0,0 +1,206 @@
+use super::traits::{Tool, ToolResult};
+use crate::relay::RelayClient;
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+
+const DEFAULT_TTL_SECS: i64 = 24 * 60 * 60;
+const MAX_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// Send an end-to-end encrypted message to the agent in another container.
+pub struct SendToAgentTool {
+    security: Arc<SecurityPolicy>,
+    relay: Arc<RelayClient>,
+}
+
+impl SendToAgentTool {
+    pub fn new(security: Arc<SecurityPolicy>, relay: Arc<RelayClient>) -> Self {
+        Self { security, relay }
+    }
+}
+
+#[async_trait]
+impl Tool for SendToAgentTool {
+    fn name(&self) -> &str {
+        "send_to_agent"
+    }
+
+    fn description(&self) -> &str {
+        "Send an encrypted message to the agent running in another container, identified by its container id. Messages to offline agents are queued until they expire."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "to_container": {
+                    "type": "string",
+                    "description": "Container id of the receiving agent"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "Message text"
+                },
+                "ttl_secs": {
+                    "type": "integer",
+                    "description": "How long an undelivered message is kept (default 86400, max 604800)"
+                }
+            },
+            "required": ["to_container", "message"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if !self.security.can_act() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: autonomy is read-only".into()),
+            });
+        }
+
+        if !self.security.record_action() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: rate limit exceeded".into()),
+            });
+        }
+
+        let to = args
+            .get("to_container")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'to_container' parameter"))?;
+        let message = args
+            .get("message")
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.trim().is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'message' parameter"))?;
+        if message.len() > MAX_MESSAGE_BYTES {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Message exceeds {MAX_MESSAGE_BYTES} bytes")),
+            });
+        }
+        let ttl_secs = args
+            .get("ttl_secs")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(DEFAULT_TTL_SECS)
+            .clamp(1, MAX_TTL_SECS);
+
+        if to == self.relay.container_id() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Cannot send a message to this agent's own container".into()),
+            });
+        }
+
+        match self.relay.send(to, message, ttl_secs).await {
+            Ok(delivery) => Ok(ToolResult {
+                success: true,
+                output: json!({ "to_container": to, "delivery": delivery }).to_string(),
+                error: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("{e:#}")),
+            }),
+        }
+    }
+}
+
+/// Fetch messages other agents sent to this container.
+pub struct AgentInboxTool {
+    relay: Arc<RelayClient>,
+}
+
+impl AgentInboxTool {
+    pub fn new(relay: Arc<RelayClient>) -> Self {
+        Self { relay }
+    }
+}
+
+#[async_trait]
+impl Tool for AgentInboxTool {
+    fn name(&self) -> &str {
+        "agent_inbox"
+    }
+
+    fn description(&self) -> &str {
+        "Fetch messages other agents sent to this container with send_to_agent. Each message is returned once."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({ "type": "object", "properties": {} })
+    }
+
+    async fn execute(&self, _args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        match self.relay.receive().await {
+            Ok(messages) => Ok(ToolResult {
+                success: true,
+                output: serde_json::to_string_pretty(&messages)?,
+                error: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("{e:#}")),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relay::AgentIdentity;
+    use crate::security::AutonomyLevel;
+
+    fn tool(level: AutonomyLevel) -> SendToAgentTool {
+        let security = Arc::new(SecurityPolicy {
+            autonomy: level,
+            ..SecurityPolicy::default()
+        });
+        let identity = AgentIdentity::generate("key".into()).unwrap();
+        // Nothing listens here; validation failures must not reach the network.
+        let relay = Arc::new(RelayClient::new("http://127.0.0.1:9", "self", identity));
+        SendToAgentTool::new(security, relay)
+    }
+
+    #[tokio::test]
+    async fn blocked_when_read_only() {
+        let result = tool(AutonomyLevel::ReadOnly)
+            .execute(json!({ "to_container": "other", "message": "hi" }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("read-only"));
+    }
+
+    #[tokio::test]
+    async fn validates_arguments() {
+        let tool = tool(AutonomyLevel::Full);
+        assert!(tool.execute(json!({ "message": "hi" })).await.is_err());
+
+        let to_self = tool
+            .execute(json!({ "to_container": "self", "message": "hi" }))
+            .await
+            .unwrap();
+        assert!(!to_self.success);
+
+        let oversized = tool
+            .execute(
+                json!({ "to_container": "other", "message": "x".repeat(MAX_MESSAGE_BYTES + 1) }),
+            )
+            .await
+            .unwrap();
+        assert!(oversized.error.unwrap().contains("exceeds"));
+    }
+}
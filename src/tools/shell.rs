@@ -1,6 +1,6 @@
 use super::traits::{Tool, ToolResult};
 use crate::runtime::RuntimeAdapter;
-use crate::security::SecurityPolicy;
+use crate::security::{NoopSandbox, Sandbox, SecurityPolicy};
 use async_trait::async_trait;
 use serde_json::json;
 use std::collections::HashSet;
@@ -21,11 +21,22 @@ const SAFE_ENV_VARS: &[&str] = &[
 pub struct ShellTool {
     security: Arc<SecurityPolicy>,
     runtime: Arc<dyn RuntimeAdapter>,
+    sandbox: Arc<dyn Sandbox>,
 }
 
 impl ShellTool {
     pub fn new(security: Arc<SecurityPolicy>, runtime: Arc<dyn RuntimeAdapter>) -> Self {
-        Self { security, runtime }
+        Self {
+            security,
+            runtime,
+            sandbox: Arc::new(NoopSandbox),
+        }
+    }
+
+    /// Run commands of the native runtime inside `sandbox`.
+    pub fn with_sandbox(mut self, sandbox: Arc<dyn Sandbox>) -> Self {
+        self.sandbox = sandbox;
+        self
     }
 }
 
@@ -38,7 +49,7 @@ fn is_valid_env_var_name(name: &str) -> bool {
     chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
 }
 
-fn collect_allowed_shell_env_vars(security: &SecurityPolicy) -> Vec<String> {
+pub(crate) fn collect_allowed_shell_env_vars(security: &SecurityPolicy) -> Vec<String> {
     let mut out = Vec::new();
     let mut seen = HashSet::new();
     for key in SAFE_ENV_VARS
@@ -146,6 +157,21 @@ impl Tool for ShellTool {
                 });
             }
         };
+        // Other runtimes isolate commands themselves.
+        if self.runtime.name() == "native" {
+            if let Err(e) = self.sandbox.wrap_command(cmd.as_std_mut()) {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!(
+                        "Failed to apply {} sandbox: {e}",
+                        self.sandbox.name()
+                    )),
+                });
+            }
+            // Wrapping backends (firejail, docker) replace the command.
+            cmd.current_dir(&self.security.workspace_dir);
+        }
         cmd.env_clear();
 
         for var in collect_allowed_shell_env_vars(&self.security) {
@@ -252,6 +278,42 @@ mod tests {
         assert!(result.error.is_none());
     }
 
+    /// Rewrites every command to `echo sandboxed`.
+    struct EchoSandbox;
+
+    impl Sandbox for EchoSandbox {
+        fn wrap_command(&self, cmd: &mut std::process::Command) -> std::io::Result<()> {
+            let mut wrapped = std::process::Command::new("echo");
+            wrapped.arg("sandboxed");
+            *cmd = wrapped;
+            Ok(())
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "test sandbox"
+        }
+    }
+
+    #[tokio::test]
+    async fn shell_runs_commands_through_the_sandbox() {
+        let tool = ShellTool::new(test_security(AutonomyLevel::Supervised), test_runtime())
+            .with_sandbox(Arc::new(EchoSandbox));
+        let result = tool
+            .execute(json!({"command": "echo hello"}))
+            .await
+            .expect("sandboxed command should run");
+        assert!(result.success);
+        assert_eq!(result.output.trim(), "sandboxed");
+    }
+
     #[tokio::test]
     async fn shell_blocks_disallowed_command() {
         let tool = ShellTool::new(test_security(AutonomyLevel::Supervised), test_runtime());
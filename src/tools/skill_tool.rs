@@ -0,0 +1,1291 @@
+//! Skill-declared tools exposed as native, typed tools.
+//!
+//! Each `[[tools]]` entry in a skill's `SKILL.toml` becomes a [`SkillToolAdapter`]
+//! registered next to the built-in tools. The parameter schema is generated from
+//! the tool's `args` table and the `{{placeholder}}` tokens in its `command`, and
+//! every call is checked against the global [`SecurityPolicy`] plus the skill's
+//! own [`SkillPolicy`] before it runs. Calls and denials are written to the
+//! audit log.
+//!
+//! Kinds:
+//! - `shell`: `command` is a shell command; placeholders are substituted as
+//!   single-quoted words and the result goes through the shell allowlist.
+//! - `script`: the first word of `command` is a script inside the skill
+//!   directory; placeholders are substituted as single-quoted arguments.
+//! - `http`: `command` is `[METHOD ]URL`; placeholders are URL-encoded.
+//!   Declared args that are not placeholders become query parameters (GET)
+//!   or a JSON body (other methods).
+
+use super::traits::{Tool, ToolResult};
+use crate::config::{Config, SandboxBackend};
+use crate::runtime::RuntimeAdapter;
+use crate::security::audit::{AuditEvent, AuditEventType, AuditLogger};
+use crate::security::policy::CommandRiskLevel;
+use crate::security::{Sandbox, SecurityPolicy};
+use crate::skills::{Skill, SkillPolicy, SkillTool};
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Default per-call timeout when the skill policy does not set one.
+const SKILL_TOOL_TIMEOUT_SECS: u64 = 60;
+/// Maximum output size in bytes (1MB).
+const MAX_OUTPUT_BYTES: usize = 1_048_576;
+/// Maximum tool name length accepted by provider function-calling APIs.
+const MAX_TOOL_NAME_LEN: usize = 64;
+/// Prefix for environment variables carrying call arguments to shell/script tools.
+const ARG_ENV_PREFIX: &str = "SKILL_ARG_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SkillToolKind {
+    Shell,
+    Script,
+    Http,
+}
+
+impl SkillToolKind {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "shell" => Some(Self::Shell),
+            "script" => Some(Self::Script),
+            "http" => Some(Self::Http),
+            _ => None,
+        }
+    }
+}
+
+/// A tool declared by a skill, callable through native tool calling.
+pub struct SkillToolAdapter {
+    name: String,
+    description: String,
+    skill_name: String,
+    skill_dir: Option<PathBuf>,
+    tool: SkillTool,
+    kind: SkillToolKind,
+    policy: SkillPolicy,
+    /// Global `[http_request].allowed_domains`; `None` when the section is disabled.
+    http_allowed_domains: Option<Vec<String>>,
+    placeholders: Vec<String>,
+    security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
+    sandbox: Option<Arc<dyn Sandbox>>,
+    audit: Option<Arc<AuditLogger>>,
+}
+
+impl SkillToolAdapter {
+    pub fn new(
+        skill: &Skill,
+        tool: &SkillTool,
+        security: Arc<SecurityPolicy>,
+        runtime: Arc<dyn RuntimeAdapter>,
+        root_config: &Config,
+    ) -> anyhow::Result<Self> {
+        let kind = SkillToolKind::parse(&tool.kind).ok_or_else(|| {
+            anyhow::anyhow!(
+                "unsupported skill tool kind '{}' (expected shell, script or http)",
+                tool.kind
+            )
+        })?;
+        if tool.command.trim().is_empty() {
+            anyhow::bail!("skill tool '{}' has an empty command", tool.name);
+        }
+        if kind != SkillToolKind::Http {
+            if let Some(name) = quoted_placeholder(&tool.command) {
+                anyhow::bail!(
+                    "skill tool '{}' quotes placeholder '{name}'; values are quoted automatically",
+                    tool.name
+                );
+            }
+        }
+
+        let sandbox = skill.policy.sandbox.clone().map(|backend| {
+            let mut security_config = root_config.security.clone();
+            security_config.sandbox.backend = backend;
+            security_config.sandbox.enabled = None;
+            crate::security::create_sandbox(&security_config, Some(&security.workspace_dir))
+        });
+
+        Ok(Self {
+            name: skill_tool_name(&skill.name, &tool.name),
+            description: format!("{} (skill: {})", tool.description.trim(), skill.name),
+            skill_name: skill.name.clone(),
+            skill_dir: skill
+                .location
+                .as_deref()
+                .and_then(|location| location.parent())
+                .map(PathBuf::from),
+            tool: tool.clone(),
+            kind,
+            policy: skill.policy.clone(),
+            http_allowed_domains: root_config.http_request.enabled.then(|| {
+                super::http_request::normalize_allowed_domains(
+                    root_config.http_request.allowed_domains.clone(),
+                )
+            }),
+            placeholders: template_placeholders(&tool.command),
+            security,
+            runtime,
+            sandbox,
+            audit: None,
+        })
+    }
+
+    /// Attach an audit logger that records every call and denial.
+    pub fn with_audit(mut self, audit: Arc<AuditLogger>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.policy
+                .timeout_secs
+                .filter(|secs| *secs > 0)
+                .unwrap_or(SKILL_TOOL_TIMEOUT_SECS),
+        )
+    }
+
+    fn sandbox_name(&self) -> Option<String> {
+        self.sandbox
+            .as_ref()
+            .map(|sandbox| sandbox.name().to_string())
+    }
+
+    fn audit_event(&self, event: AuditEvent) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let event = event
+            .with_actor(format!("skill:{}", self.skill_name), None, None)
+            .with_security(self.sandbox_name());
+        if let Err(e) = audit.log(&event) {
+            tracing::warn!(tool = %self.name, "Failed to write skill tool audit entry: {e}");
+        }
+    }
+
+    fn deny(&self, command: &str, approved: bool, reason: String) -> ToolResult {
+        let mut event = AuditEvent::new(AuditEventType::PolicyViolation)
+            .with_action(command.to_string(), "denied".into(), approved, false)
+            .with_result(false, None, 0, Some(reason.clone()));
+        event.security.policy_violation = true;
+        self.audit_event(event);
+        ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(reason),
+        }
+    }
+
+    fn finish(
+        &self,
+        command: &str,
+        risk: &str,
+        approved: bool,
+        started: Instant,
+        result: ToolResult,
+    ) -> ToolResult {
+        let duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        let event = AuditEvent::new(AuditEventType::CommandExecution)
+            .with_action(command.to_string(), risk.to_string(), approved, true)
+            .with_result(result.success, None, duration_ms, result.error.clone());
+        self.audit_event(event);
+        result
+    }
+
+    /// Collect call arguments as strings, failing on missing placeholders.
+    fn collect_args(&self, args: &serde_json::Value) -> anyhow::Result<BTreeMap<String, String>> {
+        let mut values = BTreeMap::new();
+        if let Some(object) = args.as_object() {
+            for (key, value) in object {
+                if key == "approved" && !self.tool.args.contains_key(key) {
+                    continue;
+                }
+                let rendered = match value {
+                    serde_json::Value::Null => continue,
+                    serde_json::Value::String(s) => s.clone(),
+                    serde_json::Value::Bool(_) | serde_json::Value::Number(_) => value.to_string(),
+                    _ => anyhow::bail!("Parameter '{key}' must be a string, number or boolean"),
+                };
+                if !self.tool.args.contains_key(key) && !self.placeholders.contains(key) {
+                    anyhow::bail!("Unknown parameter '{key}' for tool '{}'", self.name);
+                }
+                values.insert(key.clone(), rendered);
+            }
+        }
+        for placeholder in &self.placeholders {
+            if !values.contains_key(placeholder) {
+                anyhow::bail!("Missing '{placeholder}' parameter");
+            }
+        }
+        Ok(values)
+    }
+
+    /// Check the rendered command against the skill's own command allowlist.
+    fn skill_allows_command(&self, command: &str) -> bool {
+        if self.policy.allowed_commands.is_empty() {
+            return true;
+        }
+        let narrowed = SecurityPolicy {
+            autonomy: crate::security::AutonomyLevel::Supervised,
+            allowed_commands: self.policy.allowed_commands.clone(),
+            forbidden_commands: self.security.forbidden_commands.clone(),
+            ..SecurityPolicy::default()
+        };
+        narrowed.is_command_allowed(command)
+    }
+
+    /// Resolve the script named by the first word of `command` inside the skill directory.
+    fn resolve_script(&self) -> Result<(PathBuf, String), String> {
+        let skill_dir = self
+            .skill_dir
+            .as_ref()
+            .ok_or_else(|| "Skill has no directory; script tools are unavailable".to_string())?;
+        let command = self.tool.command.trim();
+        let (script, rest) = command
+            .split_once(char::is_whitespace)
+            .unwrap_or((command, ""));
+        let skill_root = skill_dir
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve skill directory: {e}"))?;
+        let resolved = skill_root
+            .join(script)
+            .canonicalize()
+            .map_err(|e| format!("Script '{script}' not found in skill: {e}"))?;
+        if !resolved.starts_with(&skill_root) || !resolved.is_file() {
+            return Err(format!("Script '{script}' must be a file inside the skill"));
+        }
+        Ok((resolved, rest.trim().to_string()))
+    }
+
+    async fn run_command(&self, command: &str, args: &BTreeMap<String, String>) -> ToolResult {
+        let mut cmd = match self
+            .runtime
+            .build_shell_command(command, &self.security.workspace_dir)
+        {
+            Ok(cmd) => cmd,
+            Err(e) => return failure(format!("Failed to build runtime command: {e}")),
+        };
+
+        // Same environment hygiene as the shell tool, plus the call arguments.
+        cmd.env_clear();
+        for var in super::shell::collect_allowed_shell_env_vars(&self.security) {
+            if let Ok(val) = std::env::var(&var) {
+                cmd.env(&var, val);
+            }
+        }
+        for (key, value) in args {
+            cmd.env(arg_env_name(key), value);
+        }
+
+        if let Some(sandbox) = &self.sandbox {
+            if let Err(e) = sandbox.wrap_command(cmd.as_std_mut()) {
+                return failure(format!("Failed to apply {} sandbox: {e}", sandbox.name()));
+            }
+        }
+        cmd.kill_on_drop(true);
+
+        let timeout = self.timeout();
+        match tokio::time::timeout(timeout, cmd.output()).await {
+            Ok(Ok(output)) => {
+                let stdout = truncate_output(String::from_utf8_lossy(&output.stdout).to_string());
+                let stderr = truncate_output(String::from_utf8_lossy(&output.stderr).to_string());
+                ToolResult {
+                    success: output.status.success(),
+                    output: stdout,
+                    error: if stderr.is_empty() {
+                        None
+                    } else {
+                        Some(stderr)
+                    },
+                }
+            }
+            Ok(Err(e)) => failure(format!("Failed to execute command: {e}")),
+            Err(_) => failure(format!(
+                "Command timed out after {}s and was killed",
+                timeout.as_secs()
+            )),
+        }
+    }
+
+    /// Render the HTTP request line and validate the target host against both
+    /// the skill's allowlist and the global `[http_request]` one.
+    fn prepare_http(
+        &self,
+        args: &BTreeMap<String, String>,
+    ) -> Result<(reqwest::Method, String), String> {
+        let Some(global_domains) = &self.http_allowed_domains else {
+            return Err(
+                "HTTP skill tools are disabled: enable [http_request] in config.toml".to_string(),
+            );
+        };
+        let template = self.tool.command.trim();
+        let (method, url_template) = match template.split_once(char::is_whitespace) {
+            Some((method, rest)) if !method.contains("://") => (method, rest.trim()),
+            _ => ("GET", template),
+        };
+        let method = reqwest::Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+            .map_err(|_| format!("Unsupported HTTP method: {method}"))?;
+
+        let mut url = render_template(url_template, args, |value| {
+            urlencoding::encode(value).into_owned()
+        });
+        if method == reqwest::Method::GET {
+            let query: Vec<String> = self
+                .extra_args(args)
+                .map(|(key, value)| {
+                    format!(
+                        "{}={}",
+                        urlencoding::encode(key),
+                        urlencoding::encode(value)
+                    )
+                })
+                .collect();
+            if !query.is_empty() {
+                url.push(if url.contains('?') { '&' } else { '?' });
+                url.push_str(&query.join("&"));
+            }
+        }
+
+        let host = super::http_request::extract_host(&url).map_err(|e| e.to_string())?;
+        if super::http_request::is_private_or_local_host(&host) {
+            return Err(format!("Blocked local/private host: {host}"));
+        }
+        let allowed = if self.policy.allowed_domains.is_empty() {
+            template_host(url_template).into_iter().collect()
+        } else {
+            super::http_request::normalize_allowed_domains(self.policy.allowed_domains.clone())
+        };
+        if !super::http_request::host_matches_allowlist(&host, &allowed) {
+            return Err(format!(
+                "Host '{host}' is not allowed for skill '{}'",
+                self.skill_name
+            ));
+        }
+        if !super::http_request::host_matches_allowlist(&host, global_domains) {
+            return Err(format!(
+                "Host '{host}' is not in http_request.allowed_domains"
+            ));
+        }
+        Ok((method, url))
+    }
+
+    async fn run_http(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        args: &BTreeMap<String, String>,
+    ) -> ToolResult {
+        let builder = reqwest::Client::builder()
+            .timeout(self.timeout())
+            .connect_timeout(Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none());
+        let builder = crate::config::apply_runtime_proxy_to_builder(builder, "tool.skill");
+        let client = match builder.build() {
+            Ok(client) => client,
+            Err(e) => return failure(format!("Failed to build HTTP client: {e}")),
+        };
+
+        let mut request = client.request(method.clone(), url);
+        if method != reqwest::Method::GET {
+            let body: serde_json::Map<String, serde_json::Value> = self
+                .extra_args(args)
+                .map(|(key, value)| (key.clone(), json!(value)))
+                .collect();
+            if !body.is_empty() {
+                request = request.json(&body);
+            }
+        }
+
+        match request.send().await {
+            Ok(response) => {
+                let status = response.status();
+                match response.text().await {
+                    Ok(text) => ToolResult {
+                        success: status.is_success(),
+                        output: truncate_output(text),
+                        error: (!status.is_success()).then(|| format!("HTTP {status}")),
+                    },
+                    Err(e) => failure(format!("Failed to read response body: {e}")),
+                }
+            }
+            Err(e) => failure(format!("HTTP request failed: {e}")),
+        }
+    }
+
+    /// Declared args that are not substituted into the command template.
+    fn extra_args<'a>(
+        &'a self,
+        args: &'a BTreeMap<String, String>,
+    ) -> impl Iterator<Item = (&'a String, &'a String)> {
+        args.iter()
+            .filter(|(key, _)| !self.placeholders.contains(key))
+    }
+}
+
+#[async_trait]
+impl Tool for SkillToolAdapter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        let mut properties = serde_json::Map::new();
+        let declared: BTreeMap<&String, &String> = self.tool.args.iter().collect();
+        for (name, description) in declared {
+            properties.insert(
+                name.clone(),
+                json!({ "type": "string", "description": description }),
+            );
+        }
+        for placeholder in &self.placeholders {
+            properties.entry(placeholder.clone()).or_insert_with(|| {
+                json!({
+                    "type": "string",
+                    "description": format!("Value substituted for {{{{{placeholder}}}}}")
+                })
+            });
+        }
+        if self.kind != SkillToolKind::Http && !properties.contains_key("approved") {
+            properties.insert(
+                "approved".into(),
+                json!({
+                    "type": "boolean",
+                    "description": "Set true to explicitly approve medium/high-risk commands in supervised mode",
+                    "default": false
+                }),
+            );
+        }
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": self.placeholders,
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let values = self.collect_args(&args)?;
+        let approved = args
+            .get("approved")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let template = self.tool.command.as_str();
+
+        if self.security.is_rate_limited() {
+            return Ok(self.deny(
+                template,
+                approved,
+                "Rate limit exceeded: too many actions in the last hour".into(),
+            ));
+        }
+        if !self.security.can_act() {
+            return Ok(self.deny(
+                template,
+                approved,
+                format!(
+                    "Security policy: read-only mode, cannot run '{}'",
+                    self.name
+                ),
+            ));
+        }
+        let supervised = self.security.autonomy == crate::security::AutonomyLevel::Supervised;
+        if self.policy.require_approval && supervised && !approved {
+            return Ok(self.deny(
+                template,
+                approved,
+                format!(
+                    "Skill '{}' requires explicit approval (approved=true)",
+                    self.skill_name
+                ),
+            ));
+        }
+        if let (Some(backend), Some(sandbox)) = (&self.policy.sandbox, &self.sandbox) {
+            let required = !matches!(backend, SandboxBackend::Auto | SandboxBackend::None);
+            if required && sandbox.name() == "none" {
+                return Ok(self.deny(
+                    template,
+                    approved,
+                    format!("Sandbox {backend:?} required by skill is not available"),
+                ));
+            }
+        }
+
+        match self.kind {
+            SkillToolKind::Shell => {
+                let command = render_template(template, &values, shell_quote);
+                let risk = match self.security.validate_command_execution(&command, approved) {
+                    Ok(risk) => risk,
+                    Err(reason) => return Ok(self.deny(&command, approved, reason)),
+                };
+                if !self.skill_allows_command(&command) {
+                    return Ok(self.deny(
+                        &command,
+                        approved,
+                        format!(
+                            "Command not allowed by skill '{}' policy: {command}",
+                            self.skill_name
+                        ),
+                    ));
+                }
+                if let Some(path) = self.security.forbidden_path_argument(&command) {
+                    return Ok(self.deny(
+                        &command,
+                        approved,
+                        format!("Path blocked by security policy: {path}"),
+                    ));
+                }
+                if !self.security.record_action() {
+                    return Ok(self.deny(
+                        &command,
+                        approved,
+                        "Rate limit exceeded: action budget exhausted".into(),
+                    ));
+                }
+                let started = Instant::now();
+                let result = self.run_command(&command, &values).await;
+                Ok(self.finish(&command, risk_label(risk), approved, started, result))
+            }
+            SkillToolKind::Script => {
+                let (script, rest) = match self.resolve_script() {
+                    Ok(resolved) => resolved,
+                    Err(reason) => return Ok(self.deny(template, approved, reason)),
+                };
+                let rendered_args = render_template(&rest, &values, shell_quote);
+                let command = format!(
+                    "{} {rendered_args}",
+                    shell_quote(&script.display().to_string())
+                )
+                .trim_end()
+                .to_string();
+                // The skill vouches for its own script; the policy judges the rest.
+                let script_name = script
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let mut allowed_commands = self.security.allowed_commands.clone();
+                allowed_commands.push(script_name.clone());
+                let policy = SecurityPolicy {
+                    allowed_commands,
+                    ..(*self.security).clone()
+                };
+                let checked = format!("{script_name} {rendered_args}");
+                let risk = match policy.validate_command_execution(checked.trim_end(), approved) {
+                    Ok(risk) => risk,
+                    Err(reason) => return Ok(self.deny(&command, approved, reason)),
+                };
+                if let Some(path) = self.security.forbidden_path_argument(&rendered_args) {
+                    return Ok(self.deny(
+                        &command,
+                        approved,
+                        format!("Path blocked by security policy: {path}"),
+                    ));
+                }
+                // Scripts run code the allowlist cannot inspect: treat them as medium risk.
+                if supervised && self.security.require_approval_for_medium_risk && !approved {
+                    return Ok(self.deny(
+                        &command,
+                        approved,
+                        "Command requires explicit approval (approved=true): medium-risk operation"
+                            .into(),
+                    ));
+                }
+                if !self.security.record_action() {
+                    return Ok(self.deny(
+                        &command,
+                        approved,
+                        "Rate limit exceeded: action budget exhausted".into(),
+                    ));
+                }
+                let started = Instant::now();
+                let result = self.run_command(&command, &values).await;
+                let label = match risk {
+                    CommandRiskLevel::High => "high",
+                    _ => "medium",
+                };
+                Ok(self.finish(&command, label, approved, started, result))
+            }
+            SkillToolKind::Http => {
+                let (method, url) = match self.prepare_http(&values) {
+                    Ok(prepared) => prepared,
+                    Err(reason) => return Ok(self.deny(template, approved, reason)),
+                };
+                let request_line = format!("{method} {url}");
+                if !self.security.record_action() {
+                    return Ok(self.deny(
+                        &request_line,
+                        approved,
+                        "Rate limit exceeded: action budget exhausted".into(),
+                    ));
+                }
+                let started = Instant::now();
+                let result = self.run_http(method, &url, &values).await;
+                Ok(self.finish(&request_line, "low", approved, started, result))
+            }
+        }
+    }
+}
+
+/// Build tools for every tool declared by `skills`, skipping names in `reserved`.
+#[allow(clippy::implicit_hasher)]
+pub fn skill_tools(
+    skills: &[Skill],
+    security: &Arc<SecurityPolicy>,
+    runtime: &Arc<dyn RuntimeAdapter>,
+    root_config: &Config,
+    reserved: &HashSet<String>,
+) -> Vec<Arc<dyn Tool>> {
+    if skills.iter().all(|skill| skill.tools.is_empty()) {
+        return Vec::new();
+    }
+
+    let audit = root_config.config_path.parent().and_then(|zeroclaw_dir| {
+        AuditLogger::new(
+            root_config.security.audit.clone(),
+            zeroclaw_dir.to_path_buf(),
+        )
+        .ok()
+        .map(Arc::new)
+    });
+
+    let mut seen = reserved.clone();
+    let mut tools: Vec<Arc<dyn Tool>> = Vec::new();
+    for skill in skills {
+        for tool in &skill.tools {
+            let adapter = match SkillToolAdapter::new(
+                skill,
+                tool,
+                security.clone(),
+                runtime.clone(),
+                root_config,
+            ) {
+                Ok(adapter) => adapter,
+                Err(e) => {
+                    tracing::warn!(skill = %skill.name, tool = %tool.name, "Skipping skill tool: {e}");
+                    continue;
+                }
+            };
+            if !seen.insert(adapter.name.clone()) {
+                tracing::warn!(
+                    skill = %skill.name,
+                    tool = %tool.name,
+                    "Skipping skill tool: name '{}' is already registered",
+                    adapter.name
+                );
+                continue;
+            }
+            let adapter = match &audit {
+                Some(audit) => adapter.with_audit(audit.clone()),
+                None => adapter,
+            };
+            tools.push(Arc::new(adapter));
+        }
+    }
+    tools
+}
+
+/// Registry name for a skill tool: `<skill>_<tool>`, restricted to `[a-z0-9_-]`.
+pub fn skill_tool_name(skill: &str, tool: &str) -> String {
+    fn sanitize(raw: &str) -> String {
+        raw.trim()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+    let mut name = format!("{}_{}", sanitize(skill), sanitize(tool));
+    name.truncate(MAX_TOOL_NAME_LEN);
+    name
+}
+
+fn placeholder_regex() -> &'static Regex {
+    static PLACEHOLDER_RE: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER_RE.get_or_init(|| {
+        Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").expect("placeholder regex")
+    })
+}
+
+/// Placeholder names in `template`, in order of first appearance.
+fn template_placeholders(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for capture in placeholder_regex().captures_iter(template) {
+        let name = capture[1].to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// First placeholder of a shell template that sits inside quotes. There the
+/// single-quoting applied to its value no longer holds, so such templates
+/// are rejected.
+fn quoted_placeholder(template: &str) -> Option<String> {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut scanned = 0;
+    for capture in placeholder_regex().captures_iter(template) {
+        let found = capture.get(0)?;
+        for c in template[scanned..found.start()].chars() {
+            if escaped {
+                escaped = false;
+                continue;
+            }
+            match (quote, c) {
+                (None | Some('"'), '\\') => escaped = true,
+                (None, '\'' | '"') => quote = Some(c),
+                (Some(open), _) if open == c => quote = None,
+                _ => {}
+            }
+        }
+        scanned = found.end();
+        if quote.is_some() {
+            return Some(capture[1].to_string());
+        }
+    }
+    None
+}
+
+fn render_template(
+    template: &str,
+    values: &BTreeMap<String, String>,
+    encode: impl Fn(&str) -> String,
+) -> String {
+    placeholder_regex()
+        .replace_all(template, |capture: &regex::Captures<'_>| {
+            values
+                .get(&capture[1])
+                .map(|value| encode(value))
+                .unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// Quote a value as a single shell word.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Host of a URL template, when the host part contains no placeholder.
+fn template_host(url_template: &str) -> Option<String> {
+    let host = super::http_request::extract_host(url_template).ok()?;
+    (!host.contains("{{")).then_some(host)
+}
+
+fn arg_env_name(key: &str) -> String {
+    format!("{ARG_ENV_PREFIX}{}", key.to_ascii_uppercase())
+}
+
+fn risk_label(risk: CommandRiskLevel) -> &'static str {
+    match risk {
+        CommandRiskLevel::Low => "low",
+        CommandRiskLevel::Medium => "medium",
+        CommandRiskLevel::High => "high",
+    }
+}
+
+fn truncate_output(mut text: String) -> String {
+    if text.len() > MAX_OUTPUT_BYTES {
+        let mut end = MAX_OUTPUT_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str("\n... [output truncated at 1MB]");
+    }
+    text
+}
+
+fn failure(error: String) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HttpRequestConfig;
+    use crate::runtime::NativeRuntime;
+    use crate::security::AutonomyLevel;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn test_security(workspace: &std::path::Path, autonomy: AutonomyLevel) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            autonomy,
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        })
+    }
+
+    fn test_config(tmp: &TempDir) -> Config {
+        Config {
+            workspace_dir: tmp.path().join("workspace"),
+            config_path: tmp.path().join("config.toml"),
+            http_request: HttpRequestConfig {
+                enabled: true,
+                allowed_domains: vec!["*".into()],
+                ..HttpRequestConfig::default()
+            },
+            ..Config::default()
+        }
+    }
+
+    fn skill_with(tool: SkillTool, policy: SkillPolicy, location: Option<PathBuf>) -> Skill {
+        Skill {
+            name: "demo".into(),
+            description: "Demo skill".into(),
+            version: "0.1.0".into(),
+            author: None,
+            tags: vec![],
+            tools: vec![tool],
+            prompts: vec![],
+            policy,
+            location,
+        }
+    }
+
+    fn tool(kind: &str, command: &str, args: &[(&str, &str)]) -> SkillTool {
+        SkillTool {
+            name: "greet".into(),
+            description: "Greets".into(),
+            kind: kind.into(),
+            command: command.into(),
+            args: args
+                .iter()
+                .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    fn adapter(tmp: &TempDir, skill: &Skill, autonomy: AutonomyLevel) -> SkillToolAdapter {
+        let config = test_config(tmp);
+        std::fs::create_dir_all(&config.workspace_dir).unwrap();
+        SkillToolAdapter::new(
+            skill,
+            &skill.tools[0],
+            test_security(&config.workspace_dir, autonomy),
+            Arc::new(NativeRuntime::new()),
+            &config,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn skill_tool_name_is_sanitized() {
+        assert_eq!(skill_tool_name("My Skill", "run.it"), "my_skill_run_it");
+        assert_eq!(
+            skill_tool_name(&"x".repeat(80), "y").len(),
+            MAX_TOOL_NAME_LEN
+        );
+    }
+
+    #[test]
+    fn schema_is_generated_from_args_and_placeholders() {
+        let tmp = TempDir::new().unwrap();
+        let skill = skill_with(
+            tool(
+                "shell",
+                "echo {{ who }} {{greeting}}",
+                &[("who", "Person to greet"), ("loud", "Unused flag")],
+            ),
+            SkillPolicy::default(),
+            None,
+        );
+        let schema = adapter(&tmp, &skill, AutonomyLevel::Supervised).parameters_schema();
+        assert_eq!(
+            schema["properties"]["who"]["description"],
+            "Person to greet"
+        );
+        assert!(schema["properties"]["greeting"].is_object());
+        assert!(schema["properties"]["loud"].is_object());
+        assert!(schema["properties"]["approved"].is_object());
+        assert_eq!(schema["required"], json!(["who", "greeting"]));
+    }
+
+    #[test]
+    fn unknown_kind_is_rejected() {
+        let tmp = TempDir::new().unwrap();
+        let skill = skill_with(tool("wasm", "run", &[]), SkillPolicy::default(), None);
+        let config = test_config(&tmp);
+        let result = SkillToolAdapter::new(
+            &skill,
+            &skill.tools[0],
+            test_security(tmp.path(), AutonomyLevel::Supervised),
+            Arc::new(NativeRuntime::new()),
+            &config,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn render_template_quotes_shell_values() {
+        let values = BTreeMap::from([("who".to_string(), "it's; rm -rf /".to_string())]);
+        assert_eq!(
+            render_template("echo {{who}}", &values, shell_quote),
+            r"echo 'it'\''s; rm -rf /'"
+        );
+    }
+
+    #[tokio::test]
+    async fn shell_tool_runs_templated_command() {
+        let tmp = TempDir::new().unwrap();
+        let skill = skill_with(
+            tool("shell", "echo {{who}}", &[("who", "Person")]),
+            SkillPolicy::default(),
+            None,
+        );
+        let result = adapter(&tmp, &skill, AutonomyLevel::Supervised)
+            .execute(json!({"who": "world; ls"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output.trim(), "world; ls");
+    }
+
+    #[tokio::test]
+    async fn shell_tool_requires_placeholders() {
+        let tmp = TempDir::new().unwrap();
+        let skill = skill_with(
+            tool("shell", "echo {{who}}", &[]),
+            SkillPolicy::default(),
+            None,
+        );
+        let err = adapter(&tmp, &skill, AutonomyLevel::Supervised)
+            .execute(json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("who"));
+    }
+
+    #[tokio::test]
+    async fn shell_tool_respects_global_and_skill_allowlists() {
+        let tmp = TempDir::new().unwrap();
+        let skill = skill_with(
+            tool("shell", "curl example.com", &[]),
+            SkillPolicy::default(),
+            None,
+        );
+        let result = adapter(&tmp, &skill, AutonomyLevel::Supervised)
+            .execute(json!({}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("not allowed"));
+
+        let skill = skill_with(
+            tool("shell", "echo hi", &[]),
+            SkillPolicy {
+                allowed_commands: vec!["date".into()],
+                ..SkillPolicy::default()
+            },
+            None,
+        );
+        let result = adapter(&tmp, &skill, AutonomyLevel::Supervised)
+            .execute(json!({}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("skill 'demo' policy"));
+    }
+
+    #[tokio::test]
+    async fn read_only_autonomy_blocks_skill_tools() {
+        let tmp = TempDir::new().unwrap();
+        let skill = skill_with(tool("shell", "echo hi", &[]), SkillPolicy::default(), None);
+        let result = adapter(&tmp, &skill, AutonomyLevel::ReadOnly)
+            .execute(json!({}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("read-only"));
+    }
+
+    #[tokio::test]
+    async fn require_approval_policy_gates_calls() {
+        let tmp = TempDir::new().unwrap();
+        let skill = skill_with(
+            tool("shell", "echo hi", &[]),
+            SkillPolicy {
+                require_approval: true,
+                ..SkillPolicy::default()
+            },
+            None,
+        );
+        let echo = adapter(&tmp, &skill, AutonomyLevel::Supervised);
+        let denied = echo.execute(json!({})).await.unwrap();
+        assert!(!denied.success);
+        let approved = echo.execute(json!({"approved": true})).await.unwrap();
+        assert!(approved.success, "{:?}", approved.error);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn script_tool_runs_script_inside_skill_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = TempDir::new().unwrap();
+        let skill_dir = tmp.path().join("workspace/skills/demo");
+        std::fs::create_dir_all(&skill_dir).unwrap();
+        let script = skill_dir.join("greet.sh");
+        std::fs::write(&script, "#!/bin/sh\necho \"hello $1 $SKILL_ARG_WHO\"\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let skill = skill_with(
+            tool("script", "greet.sh {{who}}", &[]),
+            SkillPolicy::default(),
+            Some(skill_dir.join("SKILL.toml")),
+        );
+        let result = adapter(&tmp, &skill, AutonomyLevel::Full)
+            .execute(json!({"who": "bob"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output.trim(), "hello bob bob");
+    }
+
+    #[test]
+    fn quoted_placeholders_are_rejected() {
+        assert_eq!(quoted_placeholder("run.sh {{a}} --x"), None);
+        assert_eq!(quoted_placeholder(r#"echo \"{{a}}"#), None);
+        assert_eq!(quoted_placeholder(r#"run.sh "{{a}}""#), Some("a".into()));
+        assert_eq!(quoted_placeholder("echo 'x {{ b }}'"), Some("b".into()));
+        assert_eq!(
+            quoted_placeholder(r#"echo "it's" {{a}} "$x {{b}}""#),
+            Some("b".into())
+        );
+
+        let tmp = TempDir::new().unwrap();
+        let skill = skill_with(
+            tool("script", r#"greet.sh "{{who}}""#, &[]),
+            SkillPolicy::default(),
+            None,
+        );
+        let result = SkillToolAdapter::new(
+            &skill,
+            &skill.tools[0],
+            test_security(tmp.path(), AutonomyLevel::Full),
+            Arc::new(NativeRuntime::new()),
+            &test_config(&tmp),
+        );
+        assert!(result.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn script_tool_arguments_cannot_inject_commands() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = TempDir::new().unwrap();
+        let skill_dir = tmp.path().join("workspace/skills/demo");
+        std::fs::create_dir_all(&skill_dir).unwrap();
+        let script = skill_dir.join("greet.sh");
+        std::fs::write(&script, "#!/bin/sh\necho \"hello $1\"\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let skill = skill_with(
+            tool("script", "greet.sh {{who}}", &[]),
+            SkillPolicy::default(),
+            Some(skill_dir.join("SKILL.toml")),
+        );
+        let tool = adapter(&tmp, &skill, AutonomyLevel::Full);
+        let pwned = tmp.path().join("workspace/pwned");
+
+        let quoted = tool
+            .execute(json!({"who": "x'\"; touch pwned; echo '"}))
+            .await
+            .unwrap();
+        assert!(quoted.success, "{:?}", quoted.error);
+        assert!(quoted.output.contains("touch pwned"));
+
+        let substituted = tool
+            .execute(json!({"who": "$(touch pwned)"}))
+            .await
+            .unwrap();
+        assert!(!substituted.success);
+        assert!(substituted
+            .error
+            .unwrap()
+            .contains("not allowed by security policy"));
+        assert!(!pwned.exists());
+    }
+
+    #[tokio::test]
+    async fn script_tool_rejects_escape_from_skill_dir() {
+        let tmp = TempDir::new().unwrap();
+        let skill_dir = tmp.path().join("workspace/skills/demo");
+        std::fs::create_dir_all(&skill_dir).unwrap();
+        std::fs::write(tmp.path().join("outside.sh"), "echo nope").unwrap();
+
+        let skill = skill_with(
+            tool("script", "../../../outside.sh", &[]),
+            SkillPolicy::default(),
+            Some(skill_dir.join("SKILL.toml")),
+        );
+        let result = adapter(&tmp, &skill, AutonomyLevel::Full)
+            .execute(json!({}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("inside the skill"));
+    }
+
+    #[test]
+    fn http_tool_pins_host_and_encodes_placeholders() {
+        let tmp = TempDir::new().unwrap();
+        let skill = skill_with(
+            tool(
+                "http",
+                "https://api.example.com/search/{{q}}",
+                &[("limit", "Max results")],
+            ),
+            SkillPolicy::default(),
+            None,
+        );
+        let http = adapter(&tmp, &skill, AutonomyLevel::Supervised);
+        let values = BTreeMap::from([
+            ("q".to_string(), "a b/c".to_string()),
+            ("limit".to_string(), "5".to_string()),
+        ]);
+        let (method, url) = http.prepare_http(&values).unwrap();
+        assert_eq!(method, reqwest::Method::GET);
+        assert_eq!(url, "https://api.example.com/search/a%20b%2Fc?limit=5");
+        assert!(http.parameters_schema()["properties"]["approved"].is_null());
+
+        let skill = skill_with(
+            tool("http", "POST https://{{host}}/hook", &[]),
+            SkillPolicy::default(),
+            None,
+        );
+        let http = adapter(&tmp, &skill, AutonomyLevel::Supervised);
+        let values = BTreeMap::from([("host".to_string(), "evil.example.org".to_string())]);
+        assert!(http
+            .prepare_http(&values)
+            .unwrap_err()
+            .contains("not allowed"));
+    }
+
+    #[test]
+    fn http_tool_blocks_private_hosts() {
+        let tmp = TempDir::new().unwrap();
+        let skill = skill_with(
+            tool("http", "http://127.0.0.1:8080/admin", &[]),
+            SkillPolicy::default(),
+            None,
+        );
+        let http = adapter(&tmp, &skill, AutonomyLevel::Supervised);
+        assert!(http
+            .prepare_http(&BTreeMap::new())
+            .unwrap_err()
+            .contains("private"));
+    }
+
+    #[test]
+    fn http_tool_is_bounded_by_global_http_request_settings() {
+        let tmp = TempDir::new().unwrap();
+        let skill = skill_with(
+            tool("http", "https://{{host}}/status", &[]),
+            SkillPolicy {
+                allowed_domains: vec!["*".into()],
+                ..SkillPolicy::default()
+            },
+            None,
+        );
+        let build = |http_request: HttpRequestConfig| {
+            let config = Config {
+                http_request,
+                ..test_config(&tmp)
+            };
+            std::fs::create_dir_all(&config.workspace_dir).unwrap();
+            SkillToolAdapter::new(
+                &skill,
+                &skill.tools[0],
+                test_security(&config.workspace_dir, AutonomyLevel::Supervised),
+                Arc::new(NativeRuntime::new()),
+                &config,
+            )
+            .unwrap()
+        };
+        let host = |name: &str| BTreeMap::from([("host".to_string(), name.to_string())]);
+
+        let disabled = build(HttpRequestConfig::default());
+        assert!(disabled
+            .prepare_http(&host("api.example.com"))
+            .unwrap_err()
+            .contains("disabled"));
+
+        let limited = build(HttpRequestConfig {
+            enabled: true,
+            allowed_domains: vec!["example.com".into()],
+            ..HttpRequestConfig::default()
+        });
+        assert!(limited.prepare_http(&host("api.example.com")).is_ok());
+        assert!(limited
+            .prepare_http(&host("evil.example.org"))
+            .unwrap_err()
+            .contains("http_request.allowed_domains"));
+        assert!(limited
+            .prepare_http(&host("169.254.169.254"))
+            .unwrap_err()
+            .contains("private"));
+    }
+
+    #[tokio::test]
+    async fn calls_and_denials_are_audited() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        std::fs::create_dir_all(&config.workspace_dir).unwrap();
+        let skills = vec![
+            skill_with(tool("shell", "echo ok", &[]), SkillPolicy::default(), None),
+            skill_with(tool("shell", "curl x", &[]), SkillPolicy::default(), None),
+        ];
+        let mut skills = skills;
+        skills[1].name = "other".into();
+        let security = test_security(&config.workspace_dir, AutonomyLevel::Supervised);
+        let runtime: Arc<dyn RuntimeAdapter> = Arc::new(NativeRuntime::new());
+        let tools = skill_tools(&skills, &security, &runtime, &config, &HashSet::new());
+        assert_eq!(tools.len(), 2);
+
+        assert!(tools[0].execute(json!({})).await.unwrap().success);
+        assert!(!tools[1].execute(json!({})).await.unwrap().success);
+
+        let log = std::fs::read_to_string(tmp.path().join("audit.log")).unwrap();
+        let events: Vec<serde_json::Value> = log
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["event_type"], "command_execution");
+        assert_eq!(events[0]["actor"]["channel"], "skill:demo");
+        assert_eq!(events[1]["event_type"], "policy_violation");
+        assert_eq!(events[1]["actor"]["channel"], "skill:other");
+    }
+
+    #[test]
+    fn skill_tools_skip_reserved_names() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let skills = vec![skill_with(
+            tool("shell", "echo ok", &[]),
+            SkillPolicy::default(),
+            None,
+        )];
+        let security = test_security(tmp.path(), AutonomyLevel::Supervised);
+        let runtime: Arc<dyn RuntimeAdapter> = Arc::new(NativeRuntime::new());
+        let reserved = HashSet::from(["demo_greet".to_string()]);
+        assert!(skill_tools(&skills, &security, &runtime, &config, &reserved).is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+use super::traits::{Tool, ToolResult};
+use crate::config::TenantContext;
+use crate::security::SecretStore;
+use crate::tenant::{GroupStore, GroupVaultStore, TenantDb};
+use async_trait::async_trait;
+use serde_json::json;
+
+/// Read a group vault secret that a quorum of the group has unlocked.
+///
+/// Stores are loaded fresh on every call so approvals and locks made through
+/// the server take effect immediately; every successful read is audited.
+pub struct VaultGetTool {
+    context: TenantContext,
+}
+
+impl VaultGetTool {
+    pub fn new(context: TenantContext) -> Self {
+        Self { context }
+    }
+
+    fn fail(error: String) -> ToolResult {
+        ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(error),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for VaultGetTool {
+    fn name(&self) -> &str {
+        "vault_get"
+    }
+
+    fn description(&self) -> &str {
+        "Read a shared secret from the group vault of a group the user belongs to. Only works while the entry is unlocked by group approval; otherwise ask the user to request access."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Name of the vault entry"
+                },
+                "group_id": {
+                    "type": "string",
+                    "description": "Group to read from when several groups have an entry with this name"
+                }
+            },
+            "required": ["name"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let name = args
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'name' parameter"))?;
+        let group_id = args.get("group_id").and_then(|v| v.as_str());
+
+        let ctx = &self.context;
+        let groups = match TenantDb::open(&ctx.data_dir) {
+            Ok(db) => GroupStore::new(db),
+            Err(e) => return Ok(Self::fail(format!("{e:#}"))),
+        };
+        let vault = GroupVaultStore::new(&ctx.data_dir);
+        let candidates: Vec<_> = groups
+            .list_by_tenant(&ctx.tenant_id)
+            .into_iter()
+            .filter(|g| group_id.is_none_or(|id| g.id == id))
+            .filter(|g| groups.is_member(&g.id, &ctx.user_id))
+            .flat_map(|g| vault.list_by_group(&g.id))
+            .filter(|e| e.name == name)
+            .collect();
+
+        let entry = match candidates.as_slice() {
+            [] => return Ok(Self::fail(format!("No vault entry named '{name}'"))),
+            [entry] => *entry,
+            _ => {
+                return Ok(Self::fail(format!(
+                    "Several groups have an entry named '{name}'; pass group_id"
+                )))
+            }
+        };
+
+        let secrets = SecretStore::new(&ctx.data_dir, true);
+        match vault.reveal(&entry.id, &ctx.user_id, "tool:vault_get", |v| {
+            secrets.decrypt(v)
+        }) {
+            Ok(value) => Ok(ToolResult {
+                success: true,
+                output: value,
+                error: None,
+            }),
+            Err(e) => Ok(Self::fail(format!("{e:#}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tenant::GroupRole;
+
+    #[tokio::test]
+    async fn reads_only_unlocked_entries_of_own_groups() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut groups = GroupStore::new(TenantDb::open(tmp.path()).unwrap());
+        let group = groups
+            .create("t1".into(), "ops".into(), None, "alice".into())
+            .unwrap();
+        groups
+            .add_member(&group.id, "alice".into(), GroupRole::Owner)
+            .unwrap();
+        let secrets = SecretStore::new(tmp.path(), true);
+        let mut vault = GroupVaultStore::new(tmp.path());
+        let entry = vault
+            .create(
+                group.id.clone(),
+                "db".into(),
+                secrets.encrypt("hunter2").unwrap(),
+                "alice".into(),
+                1,
+            )
+            .unwrap();
+
+        let tool = |user: &str| {
+            VaultGetTool::new(TenantContext {
+                data_dir: tmp.path().to_path_buf(),
+                tenant_id: "t1".into(),
+                user_id: user.into(),
+            })
+        };
+
+        let locked = tool("alice")
+            .execute(json!({ "name": "db" }))
+            .await
+            .unwrap();
+        assert!(locked.error.unwrap().contains("locked"));
+
+        vault
+            .request_access(&entry.id, "alice".into(), None)
+            .unwrap();
+        vault.approve(&entry.id, "bob".into()).unwrap();
+        let read = tool("alice")
+            .execute(json!({ "name": "db" }))
+            .await
+            .unwrap();
+        assert_eq!(read.output, "hunter2");
+
+        let outsider = tool("mallory")
+            .execute(json!({ "name": "db" }))
+            .await
+            .unwrap();
+        assert!(!outsider.success);
+
+        let log = GroupVaultStore::new(tmp.path())
+            .access_log(&entry.id)
+            .unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].user_id, "alice");
+    }
+}
@@ -8,7 +8,7 @@
 //! `send_document_by_url()` immediately via `?`, causing the entire reply
 //! (including already-sent text) to fail with no fallback.
 
-use wiremock::matchers::{method, path_regex};
+use wiremock::matchers::{body_string_contains, method, path_regex};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 use zeroclaw::channels::telegram::TelegramChannel;
 use zeroclaw::channels::traits::{Channel, SendMessage};
@@ -91,7 +91,7 @@ async fn photo_url_failure_falls_back_to_text_link() {
 
     let channel = test_channel(&server.uri());
     let msg = SendMessage::new(
-        "Check this [IMAGE:https://internal-server.local/screenshot.png]",
+        "Check this [IMAGE:https://example.com/screenshot.png]",
         "456",
     );
 
@@ -194,7 +194,7 @@ async fn multiple_attachments_independent_fallback() {
 
     let channel = test_channel(&server.uri());
     let msg = SendMessage::new(
-        "Files: [DOCUMENT:https://example.com/page.html] and [IMAGE:https://internal.local/pic.png]",
+        "Files: [DOCUMENT:https://example.com/page.html] and [IMAGE:https://example.org/pic.png]",
         "100",
     );
 
@@ -205,6 +205,46 @@ async fn multiple_attachments_independent_fallback() {
     );
 }
 
+/// Markers on local or private hosts are never handed to Telegram; the reply
+/// says the attachment was withheld instead.
+#[tokio::test]
+async fn private_host_attachment_is_withheld() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"/botTEST_TOKEN/sendPhoto$"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"/botTEST_TOKEN/sendMessage$"))
+        .and(body_string_contains(
+            "[attachment withheld: screenshot.png]",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "ok": true,
+            "result": {
+                "message_id": 1,
+                "chat": {"id": 456},
+                "text": "ok"
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let channel = test_channel(&server.uri());
+    let msg = SendMessage::new(
+        "Check this [IMAGE:https://internal-server.local/screenshot.png]",
+        "456",
+    );
+
+    let result = channel.send(&msg).await;
+    assert!(result.is_ok(), "send should succeed, got: {result:?}");
+}
+
 /// When attachment succeeds, no fallback text is sent.
 #[tokio::test]
 async fn successful_attachment_no_fallback() {
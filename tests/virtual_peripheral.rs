@@ -0,0 +1,198 @@
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use zeroclaw::config::{PeripheralBoardConfig, PeripheralsConfig};
+use zeroclaw::peripherals::{create_peripheral_tools, events};
+use zeroclaw::tools::{Tool, ToolResult};
+
+const SCRIPT: &str = r#"
+[[pins]]
+pin = 2
+waveform = { type = "constant", value = 1 }
+
+[[adc]]
+channel = 0
+waveform = { type = "sequence", values = [512, 100] }
+
+[[i2c]]
+address = 0x68
+registers = { 0x75 = 0x68 }
+
+[[faults]]
+cmd = "pwm_set"
+kind = "error"
+message = "timer busy"
+after = 1
+count = 1
+
+[[faults]]
+cmd = "spi_transfer"
+kind = "garbage"
+"#;
+
+fn virtual_board(path: Option<String>) -> PeripheralsConfig {
+    PeripheralsConfig {
+        enabled: true,
+        boards: vec![PeripheralBoardConfig {
+            board: "virtual".into(),
+            transport: "virtual".into(),
+            path,
+            baud: 115_200,
+        }],
+        datasheet_dir: None,
+        subscriptions: Vec::new(),
+    }
+}
+
+async fn scripted_tools() -> (tempfile::TempDir, Vec<Box<dyn Tool>>) {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let script = tmp.path().join("board.toml");
+    std::fs::write(&script, SCRIPT).unwrap();
+    let config = virtual_board(Some(script.to_string_lossy().into_owned()));
+    let tools = create_peripheral_tools(&config).await.unwrap();
+    (tmp, tools)
+}
+
+async fn run(tools: &[Box<dyn Tool>], name: &str, args: Value) -> anyhow::Result<ToolResult> {
+    tools
+        .iter()
+        .find(|t| t.name() == name)
+        .unwrap_or_else(|| panic!("missing tool {name}"))
+        .execute(args)
+        .await
+}
+
+#[tokio::test]
+async fn virtual_board_provides_serial_and_hardware_tools() {
+    let tools = create_peripheral_tools(&virtual_board(None)).await.unwrap();
+    let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
+    for expected in [
+        "gpio_read",
+        "gpio_write",
+        "i2c_scan",
+        "i2c_read",
+        "i2c_write",
+        "spi_transfer",
+        "pwm_set",
+        "adc_read",
+        "hardware_capabilities",
+        "hardware_board_info",
+    ] {
+        assert!(names.contains(&expected), "missing {expected} in {names:?}");
+    }
+
+    let caps = run(&tools, "hardware_capabilities", json!({}))
+        .await
+        .unwrap();
+    assert!(caps.success);
+    assert!(
+        caps.output.contains("virtual: gpio [0, 1, 2"),
+        "{}",
+        caps.output
+    );
+    assert!(caps.output.contains("adc [0, 1, 2, 3, 4, 5]"));
+}
+
+#[tokio::test]
+async fn virtual_board_follows_script() {
+    let (_tmp, tools) = scripted_tools().await;
+
+    let pin = run(&tools, "gpio_read", json!({"pin": 2})).await.unwrap();
+    assert_eq!(pin.output, "1");
+    run(&tools, "gpio_write", json!({"pin": 13, "value": 1}))
+        .await
+        .unwrap();
+    let led = run(&tools, "gpio_read", json!({"pin": 13})).await.unwrap();
+    assert_eq!(led.output, "1");
+
+    let adc = run(&tools, "adc_read", json!({"channel": 0}))
+        .await
+        .unwrap();
+    assert_eq!(adc.output, "raw 512 of 1023 (2502 mV)");
+
+    let scan = run(&tools, "i2c_scan", json!({})).await.unwrap();
+    assert_eq!(scan.output, "I2C devices at 0x68");
+    let who_am_i = run(
+        &tools,
+        "i2c_read",
+        json!({"addr": 0x68, "reg": 0x75, "len": 1}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(who_am_i.output, "[104] (hex 68)");
+
+    // Rejected on the host side from the reported capabilities.
+    assert!(run(&tools, "adc_read", json!({"channel": 9}))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn virtual_board_injects_faults() {
+    let (_tmp, tools) = scripted_tools().await;
+
+    let pwm = json!({"pin": 9, "duty": 7.5, "freq_hz": 50});
+    assert!(run(&tools, "pwm_set", pwm.clone()).await.unwrap().success);
+    let failed = run(&tools, "pwm_set", pwm.clone()).await.unwrap();
+    assert!(!failed.success);
+    assert_eq!(failed.error.as_deref(), Some("timer busy"));
+    assert!(run(&tools, "pwm_set", pwm).await.unwrap().success);
+
+    assert!(run(&tools, "spi_transfer", json!({"data": [1, 2]}))
+        .await
+        .is_err());
+    // The board keeps answering after a corrupted reply.
+    assert!(
+        run(&tools, "gpio_read", json!({"pin": 2}))
+            .await
+            .unwrap()
+            .success
+    );
+}
+
+#[tokio::test]
+async fn virtual_board_pushes_subscribed_events() {
+    let mut config = virtual_board(None);
+    config.subscriptions = toml::from_str::<PeripheralsConfig>(
+        r#"
+        [[subscriptions]]
+        name = "door"
+        board = "virtual"
+        kind = "gpio_edge"
+        pin = 2
+        edge = "rising"
+        message = "Door opened ({value})"
+        "#,
+    )
+    .unwrap()
+    .subscriptions;
+
+    let (tx, mut rx) = mpsc::channel(4);
+    let listener = tokio::spawn({
+        let config = config.clone();
+        async move { events::run(&config, tx).await }
+    });
+    // The tools share the board connection with the listener.
+    let tools = create_peripheral_tools(&config).await.unwrap();
+
+    let event = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            for value in [0, 1] {
+                run(&tools, "gpio_write", json!({"pin": 2, "value": value}))
+                    .await
+                    .unwrap();
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            if let Ok(event) = rx.try_recv() {
+                return event;
+            }
+        }
+    })
+    .await
+    .expect("no hardware event");
+    assert_eq!(event.subscription.name, "door");
+    assert_eq!(event.message, "Door opened (1)");
+    listener.abort();
+}